@@ -2,19 +2,21 @@ use std::{net::SocketAddr, time::Duration};
 
 use behavior_node::{
     behavior_tree::Node as BehaviorNodeTree, motion_assembler::assemble_motion_command,
-    node::Blackboard as BehaviorBlackboard, tree::create_tree as create_behavior_tree,
+    node::Blackboard as BehaviorBlackboard, registry::default_registry,
+    tree_description::TreeDescription,
 };
 use bevy::{app::AppExit, prelude::*};
 use color_eyre::Result;
 use coordinate_systems::{Field, Ground};
 use linear_algebra::{Point2, Pose2};
 use types::{
-    behavior_tree::NodeTrace,
+    behavior_tree::{NodeTrace, Status},
     field_dimensions::FieldDimensions,
     messages::OutgoingMessage,
     motion_command::MotionCommand,
     parameters::{BehaviorParameters, HslNetworkParameters},
     path_obstacles::PathObstacle,
+    players::Players,
     world_state::WorldState,
 };
 use voronoi::VoronoiGrid;
@@ -22,25 +24,50 @@ use voronoi::VoronoiGrid;
 use crate::behavior_tree_simulator::{
     InvariantSeverity, InvariantViolation, RobotFrame, SimulatorClock,
     SimulatorCurrentInvariantViolations, SimulatorFieldDimensions, SimulatorRobot,
-    SimulatorRobotFrames, SimulatorRobotParameters, SimulatorScenarioResult, SimulatorWorldStates,
+    SimulatorRobotFrames, SimulatorRobotParameters, SimulatorRosZRobot, SimulatorScenarioResult,
+    SimulatorWorldStates,
+};
+use crate::communication::{
+    SimulatorReceivedHslMessages, player_state_times_from_received_hsl_messages,
 };
 use crate::invariant_checks::BEHAVIOR_TICK_ERROR_CHECK_NAME;
+use crate::opponent_strategy::{OpponentStrategy, OpponentStrategyContext};
 
 #[derive(Component)]
 pub struct SimulatorRobotBehavior {
     pub tree: BehaviorNodeTree<BehaviorBlackboard>,
+    /// Description the tree is built from, node graphs load the same description.
+    pub tree_description: TreeDescription,
     pub blackboard: BehaviorBlackboard,
     pub static_layout: NodeTrace,
+    /// Replaces the behavior tree with a scripted strategy when set.
+    pub opponent_strategy: Option<Box<dyn OpponentStrategy>>,
 }
 
 impl SimulatorRobotBehavior {
-    pub fn new(parameters: BehaviorParameters) -> Self {
-        let tree = create_behavior_tree();
+    pub fn new(parameters: BehaviorParameters, tree_description: TreeDescription) -> Result<Self> {
+        let tree = tree_description.build(&default_registry())?;
         let static_layout = tree.static_layout_trace();
-        Self {
+        Ok(Self {
             tree,
+            tree_description,
             blackboard: create_behavior_blackboard(parameters),
             static_layout,
+            opponent_strategy: None,
+        })
+    }
+
+    pub fn with_opponent_strategy(self, strategy: Box<dyn OpponentStrategy>) -> Self {
+        let static_layout = NodeTrace {
+            name: strategy.name().to_string(),
+            status: Status::Idle,
+            children: Vec::new(),
+            role: None,
+        };
+        Self {
+            static_layout,
+            opponent_strategy: Some(strategy),
+            ..self
         }
     }
 
@@ -53,14 +80,19 @@ impl SimulatorRobotBehavior {
         self.blackboard.world_state = input.world_state.clone();
 
         self.blackboard.path_obstacles_output.clear();
+        self.blackboard.planned_path_output = None;
         self.blackboard.time_since_last_switch = Duration::ZERO;
         self.blackboard.direction_difference = 0.0;
         self.blackboard.voronoi_inputs.clear();
+        self.blackboard.kick_target_candidates.clear();
         self.blackboard.is_injected_motion_command = false;
         self.blackboard.walk_position = None;
         self.blackboard.body_motion = None;
         self.blackboard.head_motion = None;
         self.blackboard.voronoi_map = None;
+        self.blackboard.role = None;
+        self.blackboard.role_position = None;
+        self.blackboard.player_state_times = input.player_state_times;
 
         if let Some(ball) = self.blackboard.world_state.ball {
             self.blackboard.ball = Some(behavior_node::node::LastBall {
@@ -81,8 +113,26 @@ impl SimulatorRobotBehavior {
             self.blackboard.ball = None;
         }
 
-        let (status, trace) = self.tree.tick_with_trace(&mut self.blackboard);
-        let motion_command = assemble_motion_command(&self.blackboard, status)?;
+        let (motion_command, trace) = match &mut self.opponent_strategy {
+            Some(strategy) => {
+                let motion_command = strategy.motion_command(&OpponentStrategyContext {
+                    world_state: &self.blackboard.world_state,
+                    field_dimensions: &self.blackboard.field_dimensions,
+                });
+                let trace = NodeTrace {
+                    name: strategy.name().to_string(),
+                    status: Status::Success,
+                    children: Vec::new(),
+                    role: None,
+                };
+                (motion_command, trace)
+            }
+            None => {
+                let (status, mut trace) = self.tree.tick_with_trace(&mut self.blackboard);
+                trace.role = self.blackboard.role;
+                (assemble_motion_command(&self.blackboard, status)?, trace)
+            }
+        };
         self.blackboard.last_motion_command = motion_command.clone();
 
         let motion_type = match motion_command.clone() {
@@ -90,6 +140,9 @@ impl SimulatorRobotBehavior {
             MotionCommand::Walk { .. } => Some(types::motion_type::MotionType::Walk),
             MotionCommand::Stand { .. } => Some(types::motion_type::MotionType::Stand),
             MotionCommand::StandUp => Some(types::motion_type::MotionType::StandUp),
+            MotionCommand::KeyframeMotion { .. } => {
+                Some(types::motion_type::MotionType::KeyframeMotion)
+            }
             MotionCommand::Prepare => Some(types::motion_type::MotionType::Prepare),
             MotionCommand::Damping => Some(types::motion_type::MotionType::Damping),
             _ => None,
@@ -138,6 +191,8 @@ impl SimulatorRobotBehavior {
 
 pub struct SimulatorBehaviorTickInput {
     pub world_state: WorldState,
+    /// When the robot last heard from each teammate, silent teammates keep their old time.
+    pub player_state_times: Players<Option<ros_z::time::Time>>,
     pub field_dimensions: FieldDimensions,
     pub parameters: BehaviorParameters,
 }
@@ -160,9 +215,11 @@ fn create_behavior_blackboard(parameters: BehaviorParameters) -> BehaviorBlackbo
         parameters,
         world_state: WorldState::default(),
         path_obstacles_output: Vec::new(),
+        planned_path_output: None,
         time_since_last_switch: Duration::ZERO,
         direction_difference: 0.0,
         voronoi_inputs: Vec::new(),
+        kick_target_candidates: Vec::new(),
         ball: None,
         last_ball: None,
         last_close_enough_to_kick: false,
@@ -172,14 +229,17 @@ fn create_behavior_blackboard(parameters: BehaviorParameters) -> BehaviorBlackbo
         last_motion_type: None,
         last_sent_game_controller_return_message_time: None,
         last_sent_hsl_message_time: None,
-        last_closest_to_ball: false,
-        closest_to_ball_entered_area_since: None,
-        closest_to_ball_left_area_since: None,
+        last_sent_state_message_ball_seen: false,
+        last_sent_state_message_role: None,
+        player_state_times: Players::new(None),
+        team_roles: Players::new(None),
         is_injected_motion_command: false,
         walk_position: None,
         body_motion: None,
         head_motion: None,
         voronoi_map: None,
+        role: None,
+        role_position: None,
     }
 }
 
@@ -187,15 +247,19 @@ pub fn tick_behavior_trees(
     clock: Res<SimulatorClock>,
     field_dimensions: Res<SimulatorFieldDimensions>,
     world_states: Res<SimulatorWorldStates>,
+    received_hsl_messages: Res<SimulatorReceivedHslMessages>,
     mut robot_frames: ResMut<SimulatorRobotFrames>,
     mut current_violations: ResMut<SimulatorCurrentInvariantViolations>,
     mut scenario_result: ResMut<SimulatorScenarioResult>,
     mut exit: MessageWriter<AppExit>,
-    mut robots: Query<(
-        &SimulatorRobot,
-        &SimulatorRobotParameters,
-        &mut SimulatorRobotBehavior,
-    )>,
+    mut robots: Query<
+        (
+            &SimulatorRobot,
+            &SimulatorRobotParameters,
+            &mut SimulatorRobotBehavior,
+        ),
+        Without<SimulatorRosZRobot>,
+    >,
 ) {
     robot_frames.0.clear();
 
@@ -207,6 +271,10 @@ pub fn tick_behavior_trees(
 
         let tick_output = match behavior.tick_behavior_tree(SimulatorBehaviorTickInput {
             world_state: world_state.clone(),
+            player_state_times: player_state_times_from_received_hsl_messages(
+                robot_id,
+                &received_hsl_messages,
+            ),
             field_dimensions: field_dimensions.0,
             parameters: parameters.behavior.clone(),
         }) {
@@ -214,7 +282,7 @@ pub fn tick_behavior_trees(
             Err(error) => {
                 scenario_result.failed = true;
                 current_violations.0.push(InvariantViolation {
-                    check_name: BEHAVIOR_TICK_ERROR_CHECK_NAME,
+                    check_name: BEHAVIOR_TICK_ERROR_CHECK_NAME.into(),
                     player_number: Some(robot.player_number),
                     message: behavior_tick_failure_message(robot_id, &clock, &error),
                     severity: InvariantSeverity::Error,
@@ -230,7 +298,7 @@ pub fn tick_behavior_trees(
     }
 }
 
-fn behavior_tick_failure_message(
+pub(crate) fn behavior_tick_failure_message(
     robot_id: crate::behavior_tree_simulator::SimulatorRobotId,
     clock: &SimulatorClock,
     error: &color_eyre::Report,
@@ -266,7 +334,7 @@ mod tests {
     use super::*;
     use crate::behavior_tree_simulator::{
         DEFAULT_TICK_DURATION, SimulatorFieldDimensions, default_behavior_parameters,
-        default_walking_parameters,
+        default_behavior_tree, default_walking_parameters,
     };
 
     #[test]
@@ -285,6 +353,7 @@ mod tests {
                 ),
                 WorldState::default(),
             )])))
+            .insert_resource(SimulatorReceivedHslMessages::default())
             .insert_resource(SimulatorRobotFrames::default())
             .insert_resource(SimulatorCurrentInvariantViolations::default())
             .insert_resource(SimulatorScenarioResult::default())
@@ -292,7 +361,9 @@ mod tests {
 
         let mut behavior = SimulatorRobotBehavior::new(
             default_behavior_parameters().expect("failed to load behavior parameters"),
-        );
+            default_behavior_tree().expect("failed to load behavior tree"),
+        )
+        .expect("failed to build behavior tree");
         behavior.tree = BehaviorNodeTree::Action {
             name: "return_idle",
             action: Box::new(|_| Status::Idle),
@@ -344,4 +415,34 @@ mod tests {
             .collect::<Vec<_>>();
         assert_eq!(exits, vec![AppExit::Success]);
     }
+
+    #[test]
+    fn default_behavior_tree_matches_compiled_tree() {
+        fn without_names(mut node: serde_json::Value) -> serde_json::Value {
+            if let Some(node) = node.as_object_mut() {
+                node.remove("name");
+                if let Some(children) = node.get_mut("children") {
+                    *children = children
+                        .as_array()
+                        .expect("children are a list")
+                        .iter()
+                        .cloned()
+                        .map(without_names)
+                        .collect();
+                }
+            }
+            node
+        }
+
+        let loaded = default_behavior_tree()
+            .expect("failed to load behavior tree")
+            .build(&default_registry())
+            .expect("failed to build behavior tree");
+        let compiled = behavior_node::tree::create_tree();
+
+        assert_eq!(
+            without_names(serde_json::to_value(&loaded).unwrap()),
+            without_names(serde_json::to_value(&compiled).unwrap())
+        );
+    }
 }
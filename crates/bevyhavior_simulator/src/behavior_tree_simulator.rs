@@ -1,14 +1,17 @@
-use std::{env, time::Duration, time::SystemTime};
+use std::{env, fs::create_dir_all, path::PathBuf, time::Duration, time::SystemTime};
 
 use bevy::{
     app::{App, AppExit, Plugin, Update},
     ecs::message::Messages,
     prelude::*,
 };
-use color_eyre::{Result, eyre::bail};
+use color_eyre::{
+    Result,
+    eyre::{WrapErr, bail},
+};
 use coordinate_systems::{Ground, World};
-use linear_algebra::{Isometry2, Point2};
-use serde::Serialize;
+use linear_algebra::{Isometry2, Point2, Vector2};
+use serde::{Deserialize, Serialize};
 use types::{
     field_dimensions::FieldDimensions,
     obstacles::{Obstacle, ObstacleKind},
@@ -16,6 +19,7 @@ use types::{
     rule_obstacles::RuleObstacle,
 };
 
+use crate::timeline_file::{TIMELINE_EXPORT_DIRECTORY_VARIABLE, TIMELINE_FILE_EXTENSION};
 use crate::timeline_viewer::{TimelineViewerData, show_timeline_viewer};
 
 pub use crate::auto_referee::{
@@ -33,7 +37,7 @@ pub use crate::communication::{
     SimulatorReceivedHslMessages,
 };
 pub use crate::config::{
-    DEFAULT_TICK_DURATION, SimulationConfig, default_behavior_parameters,
+    DEFAULT_TICK_DURATION, SimulationConfig, default_behavior_parameters, default_behavior_tree,
     default_walking_parameters,
 };
 pub use crate::game_controller::SimulatorGameState;
@@ -41,15 +45,24 @@ pub use crate::invariant_checks::{
     InvariantCheck, InvariantSeverity, InvariantViolation, RobotSnapshot, SimulationSnapshot,
     SimulatorCurrentInvariantViolations, SimulatorInvariantChecks, default_invariant_checks,
 };
+pub use crate::opponent_strategy::{
+    AggressiveBallChaser, OpponentStrategy, OpponentStrategyContext, PassiveDefender,
+    RecordedTrajectory, ReplayedTrajectory, StaticGoalieWall, TrajectorySample,
+};
 pub use crate::robot::{
     SimulatorFallDownState, SimulatorGroundToWorld, SimulatorHeadYaw, SimulatorLastKickTime,
     SimulatorPrimaryState, SimulatorRobot, SimulatorRobotBundle, SimulatorRobotId,
     SimulatorRobotParameters, SimulatorSuggestedSearchPosition,
 };
+pub use crate::ros_z_node_graph::{
+    ROS_Z_NODE_GRAPH_VARIABLE, ROS_Z_ROUTER_VARIABLE, SimulatorRosZNodeGraph,
+    SimulatorRosZNodeGraphSetupFailure, SimulatorRosZRobot, robot_namespace,
+};
 pub use crate::timeline::{
     RobotFrame, SimulatorFailure, SimulatorRobotFrames, SimulatorScenarioResult, SimulatorTimeline,
     SimulatorTimelineMarker, SimulatorTimelineMarkers, TimelineFrame,
 };
+pub use crate::timeline_file::{load_timeline, save_timeline};
 pub use crate::world_states::SimulatorWorldStates;
 
 pub use crate::auto_referee::run_auto_referee;
@@ -61,7 +74,11 @@ pub use crate::communication::{
 pub use crate::coordinates::point_world_to_field;
 pub use crate::game_controller::sync_primary_states_from_game_state;
 pub use crate::invariant_checks::run_invariant_checks;
-pub use crate::kinematics::{move_robots, resolve_collisions};
+pub use crate::kinematics::{move_robots, move_scenario_obstacles, resolve_collisions};
+pub use crate::ros_z_node_graph::{
+    collect_ros_z_outgoing_messages, forward_incoming_messages_to_ros_z,
+    report_ros_z_node_graph_setup_failure, spawn_ros_z_node_graphs, tick_ros_z_node_graphs,
+};
 pub use crate::timeline::record_timeline_frame;
 pub use crate::world_states::build_world_states;
 #[derive(SystemSet, Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -103,6 +120,9 @@ pub struct BehaviorTreeSimulatorPlugin {
     pub enable_default_kinematics: bool,
     pub enable_default_communication_routing: bool,
     pub enable_default_invariant_checks: bool,
+    /// Runs the deployed `behavior_node` of every robot without an [`OpponentStrategy`] in its
+    /// own ros-z namespace instead of ticking its behavior tree in-process.
+    pub enable_ros_z_node_graph: bool,
 }
 
 impl Default for BehaviorTreeSimulatorPlugin {
@@ -117,6 +137,7 @@ impl Default for BehaviorTreeSimulatorPlugin {
             enable_default_kinematics: true,
             enable_default_communication_routing: true,
             enable_default_invariant_checks: true,
+            enable_ros_z_node_graph: env::var_os(ROS_Z_NODE_GRAPH_VARIABLE).is_some(),
         }
     }
 }
@@ -259,56 +280,143 @@ impl Plugin for BehaviorTreeSimulatorPlugin {
         if self.enable_default_kinematics {
             app.add_systems(
                 Update,
-                move_robots.in_set(BehaviorTreeSimulatorSet::ApplyKinematics),
+                (move_robots, move_scenario_obstacles)
+                    .in_set(BehaviorTreeSimulatorSet::ApplyKinematics),
             )
             .add_systems(
                 Update,
                 resolve_collisions.in_set(BehaviorTreeSimulatorSet::AfterKinematics),
             );
         }
+
+        if self.enable_ros_z_node_graph {
+            match SimulatorRosZNodeGraph::new(env::var(ROS_Z_ROUTER_VARIABLE).ok()) {
+                Ok(node_graph) => {
+                    app.insert_resource(node_graph);
+                }
+                Err(error) => {
+                    app.insert_resource(SimulatorRosZNodeGraphSetupFailure::new(error))
+                        .add_systems(
+                            Update,
+                            report_ros_z_node_graph_setup_failure
+                                .in_set(BehaviorTreeSimulatorSet::BeforeBehavior),
+                        );
+                    return;
+                }
+            }
+            app.add_systems(
+                Update,
+                forward_incoming_messages_to_ros_z
+                    .before(apply_incoming_hsl_messages)
+                    .in_set(BehaviorTreeSimulatorSet::BuildTeamContext),
+            )
+            .add_systems(
+                Update,
+                spawn_ros_z_node_graphs.in_set(BehaviorTreeSimulatorSet::BeforeBehavior),
+            )
+            .add_systems(
+                Update,
+                tick_ros_z_node_graphs
+                    .after(tick_behavior_trees)
+                    .in_set(BehaviorTreeSimulatorSet::TickBehaviorTrees),
+            )
+            .add_systems(
+                Update,
+                collect_ros_z_outgoing_messages
+                    .after(plan_communication)
+                    .in_set(BehaviorTreeSimulatorSet::PlanCommunication),
+            );
+        }
     }
 }
 
+/// Runs a scenario until it exits, both return the path of the exported timeline if
+/// `BEVYHAVIOR_SIMULATOR_TIMELINE_DIRECTORY` is set.
 pub trait AppExt {
-    fn run_to_completion(&mut self) -> Result<()>;
-    fn run_to_completion_with_viewer(&mut self) -> Result<()>;
+    fn run_to_completion(&mut self) -> Result<Option<PathBuf>>;
+    fn run_to_completion_with_viewer(&mut self) -> Result<Option<PathBuf>>;
 }
 
 impl AppExt for App {
-    fn run_to_completion(&mut self) -> Result<()> {
+    fn run_to_completion(&mut self) -> Result<Option<PathBuf>> {
         let exit = run_until_exit(self);
-        check_scenario_result(self, exit)
+        let timeline = export_timeline(self)?;
+        check_scenario_result_with_timeline(self, exit, timeline)
     }
 
-    fn run_to_completion_with_viewer(&mut self) -> Result<()> {
+    fn run_to_completion_with_viewer(&mut self) -> Result<Option<PathBuf>> {
         let exit = run_until_exit(self);
+        let timeline = export_timeline(self)?;
 
         if env::var_os("BEVYHAVIOR_SIMULATOR_NO_VIEWER").is_none() {
-            let viewer_data = TimelineViewerData {
-                field_dimensions: self.world().resource::<SimulatorFieldDimensions>().0,
-                config: self.world().resource::<SimulationConfig>().clone(),
-                frames: std::mem::take(
-                    &mut self.world_mut().resource_mut::<SimulatorTimeline>().frames,
-                ),
-                markers: self
-                    .world()
-                    .resource::<SimulatorTimelineMarkers>()
-                    .markers
-                    .clone(),
-                failures: self
-                    .world()
-                    .resource::<SimulatorScenarioResult>()
-                    .failures
-                    .clone(),
-            };
-
-            show_timeline_viewer(viewer_data)?;
+            show_timeline_viewer(take_timeline_viewer_data(self))?;
         }
 
-        check_scenario_result(self, exit)
+        check_scenario_result_with_timeline(self, exit, timeline)
     }
 }
 
+fn check_scenario_result_with_timeline(
+    app: &App,
+    exit: AppExit,
+    timeline: Option<PathBuf>,
+) -> Result<Option<PathBuf>> {
+    let result = check_scenario_result(app, exit);
+    match &timeline {
+        Some(path) => result.wrap_err_with(|| format!("timeline written to {}", path.display()))?,
+        None => result?,
+    }
+    Ok(timeline)
+}
+
+/// Name of the running scenario, used as file name for exported timelines.
+#[derive(Resource, Clone, Copy, Debug)]
+pub struct SimulatorScenarioName(pub &'static str);
+
+fn timeline_viewer_data(app: &App, frames: Vec<TimelineFrame>) -> TimelineViewerData {
+    TimelineViewerData {
+        field_dimensions: app.world().resource::<SimulatorFieldDimensions>().0,
+        config: app.world().resource::<SimulationConfig>().clone(),
+        frames,
+        markers: app
+            .world()
+            .resource::<SimulatorTimelineMarkers>()
+            .markers
+            .clone(),
+        failures: app
+            .world()
+            .resource::<SimulatorScenarioResult>()
+            .failures
+            .clone(),
+    }
+}
+
+fn take_timeline_viewer_data(app: &mut App) -> TimelineViewerData {
+    let frames = std::mem::take(&mut app.world_mut().resource_mut::<SimulatorTimeline>().frames);
+    timeline_viewer_data(app, frames)
+}
+
+fn export_timeline(app: &App) -> Result<Option<PathBuf>> {
+    let Some(directory) = env::var_os(TIMELINE_EXPORT_DIRECTORY_VARIABLE).map(PathBuf::from) else {
+        return Ok(None);
+    };
+    let scenario_name = app
+        .world()
+        .get_resource::<SimulatorScenarioName>()
+        .map_or("simulation", |name| name.0);
+    create_dir_all(&directory).wrap_err_with(|| {
+        format!(
+            "failed to create timeline directory {}",
+            directory.display()
+        )
+    })?;
+    let path = directory.join(format!("{scenario_name}.{TIMELINE_FILE_EXTENSION}"));
+
+    let frames = app.world().resource::<SimulatorTimeline>().frames.clone();
+    save_timeline(&path, &timeline_viewer_data(app, frames))?;
+    Ok(Some(path))
+}
+
 fn run_until_exit(app: &mut App) -> AppExit {
     let mut event_cursor = app
         .world_mut()
@@ -367,12 +475,15 @@ impl SimulatorScenarioObstacles {
     }
 }
 
-#[derive(Clone, Copy, Debug, Serialize)]
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
 pub struct SimulatorObstacle {
     pub kind: ObstacleKind,
     pub position: Point2<World>,
     pub radius_at_foot_height: f32,
     pub radius_at_hip_height: f32,
+    /// Moved by [`move_scenario_obstacles`] every tick
+    #[serde(default)]
+    pub velocity: Vector2<World>,
 }
 
 impl SimulatorObstacle {
@@ -387,9 +498,14 @@ impl SimulatorObstacle {
             position,
             radius_at_foot_height,
             radius_at_hip_height,
+            velocity: Vector2::zeros(),
         }
     }
 
+    pub fn with_velocity(self, velocity: Vector2<World>) -> Self {
+        Self { velocity, ..self }
+    }
+
     pub fn robot(
         position: Point2<World>,
         radius_at_foot_height: f32,
@@ -431,6 +547,7 @@ impl SimulatorObstacle {
             position: ground_to_world.inverse() * self.position,
             radius_at_foot_height: self.radius_at_foot_height,
             radius_at_hip_height: self.radius_at_hip_height,
+            velocity: ground_to_world.inverse() * self.velocity,
         }
     }
 }
@@ -113,6 +113,7 @@ fn motion_name(motion_command: &MotionCommand) -> &str {
         MotionCommand::Prepare => "prepare",
         MotionCommand::Stand { .. } => "stand",
         MotionCommand::StandUp => "stand_up",
+        MotionCommand::KeyframeMotion { .. } => "keyframe_motion",
         MotionCommand::VisualKick { .. } => "visual_kick",
         MotionCommand::Walk { .. } => "walk",
         MotionCommand::WalkWithVelocity { .. } => "walk_with_velocity",
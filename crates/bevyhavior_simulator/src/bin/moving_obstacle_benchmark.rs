@@ -0,0 +1,292 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use behavior_node::tree_description::{NodeDescription, TreeDescription};
+use bevy::prelude::*;
+use bevyhavior_simulator::behavior_tree_simulator::{
+    BehaviorTreeSimulatorSet, SimulationConfig, SimulatorClock, SimulatorGameState,
+    SimulatorGroundToWorld, SimulatorObstacle, SimulatorRobot, SimulatorRobotBundle,
+    SimulatorScenarioObstacles, SimulatorSuggestedSearchPosition, SimulatorTimeline,
+    SimulatorWorldStates, default_behavior_parameters, point_world_to_field,
+};
+use coordinate_systems::World;
+use filtering::kalman_filter::KalmanFilter;
+use hsl_network_messages::{PlayerNumber, Team};
+use linear_algebra::{IntoFramed, Isometry2, distance, point, vector};
+use nalgebra::{Matrix2x4, Matrix4, Vector2, Vector4};
+use scenario::scenario;
+use types::{
+    multivariate_normal_distribution::MultivariateNormalDistribution, primary_state::PrimaryState,
+};
+
+/// Robots walk along separate lanes crossed by scripted obstacles, planning with static obstacles,
+/// with the true obstacle velocities, and with velocities estimated from noisy positions.
+struct Lane {
+    player_number: PlayerNumber,
+    y: f32,
+    obstacle_velocities: ObstacleVelocities,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum ObstacleVelocities {
+    Ignored,
+    GroundTruth,
+    Estimated,
+}
+
+const LANES: [Lane; 3] = [
+    Lane {
+        player_number: PlayerNumber::Three,
+        y: 2.0,
+        obstacle_velocities: ObstacleVelocities::Ignored,
+    },
+    Lane {
+        player_number: PlayerNumber::Four,
+        y: 0.0,
+        obstacle_velocities: ObstacleVelocities::GroundTruth,
+    },
+    Lane {
+        player_number: PlayerNumber::Five,
+        y: -2.0,
+        obstacle_velocities: ObstacleVelocities::Estimated,
+    },
+];
+const LANE_START_X: f32 = -3.0;
+const LANE_END_X: f32 = 3.0;
+const CROSSING_X: [f32; 2] = [-1.0, 1.0];
+const CROSSING_AMPLITUDE: f32 = 0.9;
+const CROSSING_SPEED: f32 = 0.4;
+const ARRIVAL_DISTANCE: f32 = 0.25;
+const MAXIMUM_FRAMES: usize = 6000;
+/// Standard deviation of the simulated obstacle position measurements
+const MEASUREMENT_NOISE: f32 = 0.1;
+/// Matches the shipped obstacle filter parameters
+const POSITION_PROCESS_NOISE: f32 = 0.005;
+const VELOCITY_PROCESS_NOISE: f32 = 0.5;
+const MAXIMUM_OBSTACLE_VELOCITY: f32 = 1.0;
+
+#[derive(Resource, Default)]
+struct CollisionCounter {
+    touching: BTreeSet<(PlayerNumber, usize)>,
+    collisions: BTreeMap<PlayerNumber, usize>,
+}
+
+/// Constant velocity Kalman filter per robot and obstacle, like the obstacle filter runs it.
+#[derive(Resource, Default)]
+struct ObstacleVelocityEstimates {
+    states: BTreeMap<(PlayerNumber, usize), MultivariateNormalDistribution<4>>,
+    noise_seed: u64,
+}
+
+impl ObstacleVelocityEstimates {
+    /// Deterministic uniform noise with the standard deviation [`MEASUREMENT_NOISE`]
+    fn measurement_noise(&mut self) -> Vector2<f32> {
+        let mut uniform = || {
+            self.noise_seed = self
+                .noise_seed
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            (self.noise_seed >> 40) as f32 / (1u64 << 24) as f32 * 2.0 - 1.0
+        };
+        Vector2::new(uniform(), uniform()) * MEASUREMENT_NOISE * 3.0_f32.sqrt()
+    }
+}
+
+#[scenario]
+fn moving_obstacle_benchmark(app: &mut App) {
+    app.init_resource::<CollisionCounter>()
+        .init_resource::<ObstacleVelocityEstimates>()
+        .add_systems(Startup, startup)
+        .add_systems(
+            Update,
+            replace_obstacle_velocities.in_set(BehaviorTreeSimulatorSet::AfterWorldState),
+        )
+        .add_systems(Update, update.in_set(BehaviorTreeSimulatorSet::Scenario));
+}
+
+fn startup(
+    mut commands: Commands,
+    game_state: Res<SimulatorGameState>,
+    mut scenario_obstacles: ResMut<SimulatorScenarioObstacles>,
+) {
+    let tree_description = TreeDescription {
+        root: NodeDescription::Leaf("walk_to_search_position".to_string()),
+        subtrees: BTreeMap::new(),
+    };
+
+    for lane in &LANES {
+        let mut parameters =
+            default_behavior_parameters().expect("failed to load default behavior parameters");
+        parameters.path_planning.predict_obstacle_motion =
+            lane.obstacle_velocities != ObstacleVelocities::Ignored;
+
+        let target = point_world_to_field(
+            point![LANE_END_X, lane.y],
+            game_state.game_controller_state.global_field_side,
+        );
+        let mut robot = SimulatorRobotBundle::new(
+            Team::Hulks,
+            lane.player_number,
+            Isometry2::from_parts(vector![LANE_START_X, lane.y], 0.0),
+            parameters,
+        )
+        .expect("failed to create robot bundle")
+        .with_behavior_tree(tree_description.clone())
+        .expect("failed to build behavior tree")
+        .with_primary_state(PrimaryState::Playing);
+        robot.suggested_search_position = SimulatorSuggestedSearchPosition {
+            position: Some(target),
+        };
+        commands.spawn(robot);
+
+        for (index, x) in CROSSING_X.into_iter().enumerate() {
+            let direction = if index % 2 == 0 { 1.0 } else { -1.0 };
+            scenario_obstacles.add(
+                SimulatorObstacle::robot(
+                    point![x, lane.y - direction * CROSSING_AMPLITUDE],
+                    0.2,
+                    0.2,
+                )
+                .with_velocity(vector![0.0, direction * CROSSING_SPEED]),
+            );
+        }
+    }
+}
+
+fn replace_obstacle_velocities(
+    clock: Res<SimulatorClock>,
+    robots: Query<(&SimulatorRobot, &SimulatorGroundToWorld)>,
+    scenario_obstacles: Res<SimulatorScenarioObstacles>,
+    mut estimates: ResMut<ObstacleVelocityEstimates>,
+    mut world_states: ResMut<SimulatorWorldStates>,
+) {
+    let elapsed = clock.tick_duration.as_secs_f32();
+    let mut state_prediction = Matrix4::identity();
+    state_prediction
+        .fixed_view_mut::<2, 2>(0, 2)
+        .fill_diagonal(elapsed);
+    let process_noise = Matrix4::from_diagonal(&Vector4::new(
+        POSITION_PROCESS_NOISE,
+        POSITION_PROCESS_NOISE,
+        VELOCITY_PROCESS_NOISE * elapsed,
+        VELOCITY_PROCESS_NOISE * elapsed,
+    ));
+
+    for (robot, ground_to_world) in &robots {
+        let Some(lane) = LANES
+            .iter()
+            .find(|lane| lane.player_number == robot.player_number)
+        else {
+            continue;
+        };
+        if lane.obstacle_velocities != ObstacleVelocities::Estimated {
+            continue;
+        }
+        let Some(world_state) = world_states.0.get_mut(&robot.id()) else {
+            continue;
+        };
+        // scenario obstacles come first in the world state, in their original order
+        for (index, (obstacle, perceived)) in scenario_obstacles
+            .obstacles
+            .iter()
+            .zip(world_state.obstacles.iter_mut())
+            .enumerate()
+        {
+            let measurement = obstacle.position.inner.coords + estimates.measurement_noise();
+            let state = estimates
+                .states
+                .entry((robot.player_number, index))
+                .or_insert_with(|| MultivariateNormalDistribution {
+                    mean: Vector4::new(measurement.x, measurement.y, 0.0, 0.0),
+                    covariance: Matrix4::from_diagonal(&Vector4::new(
+                        MEASUREMENT_NOISE.powi(2),
+                        MEASUREMENT_NOISE.powi(2),
+                        MAXIMUM_OBSTACLE_VELOCITY.powi(2),
+                        MAXIMUM_OBSTACLE_VELOCITY.powi(2),
+                    )),
+                });
+            state.predict(
+                state_prediction,
+                Matrix4::zeros(),
+                Vector4::zeros(),
+                process_noise,
+            );
+            state.update(
+                Matrix2x4::identity(),
+                measurement,
+                nalgebra::Matrix2::identity() * MEASUREMENT_NOISE.powi(2),
+            );
+            let velocity: linear_algebra::Vector2<World> = Vector2::new(state.mean.z, state.mean.w)
+                .cap_magnitude(MAXIMUM_OBSTACLE_VELOCITY)
+                .framed();
+            perceived.velocity = ground_to_world.ground_to_world.inverse() * velocity;
+        }
+    }
+}
+
+fn update(
+    timeline: Res<SimulatorTimeline>,
+    config: Res<SimulationConfig>,
+    robots: Query<(&SimulatorRobot, &SimulatorGroundToWorld)>,
+    mut scenario_obstacles: ResMut<SimulatorScenarioObstacles>,
+    mut counter: ResMut<CollisionCounter>,
+    mut exit: MessageWriter<AppExit>,
+) {
+    for obstacle in &mut scenario_obstacles.obstacles {
+        let lane_y = LANES
+            .iter()
+            .map(|lane| lane.y)
+            .min_by(|a, b| {
+                (a - obstacle.position.y())
+                    .abs()
+                    .total_cmp(&(b - obstacle.position.y()).abs())
+            })
+            .expect("there are lanes");
+        let offset = obstacle.position.y() - lane_y;
+        if offset.abs() > CROSSING_AMPLITUDE && offset * obstacle.velocity.y() > 0.0 {
+            obstacle.velocity = -obstacle.velocity;
+        }
+    }
+
+    let mut arrived = 0;
+    for (robot, ground_to_world) in &robots {
+        let position = ground_to_world.ground_to_world * point![0.0, 0.0];
+        for (index, obstacle) in scenario_obstacles.obstacles.iter().enumerate() {
+            let key = (robot.player_number, index);
+            let is_touching = distance(position, obstacle.position)
+                < config.robot_radius + obstacle.radius_at_hip_height;
+            if is_touching && counter.touching.insert(key) {
+                *counter.collisions.entry(robot.player_number).or_default() += 1;
+            } else if !is_touching {
+                counter.touching.remove(&key);
+            }
+        }
+        if let Some(lane) = LANES
+            .iter()
+            .find(|lane| lane.player_number == robot.player_number)
+            && distance(position, point![LANE_END_X, lane.y]) < ARRIVAL_DISTANCE
+        {
+            arrived += 1;
+        }
+    }
+
+    let timed_out = timeline.frames.len() >= MAXIMUM_FRAMES;
+    if arrived == LANES.len() || timed_out {
+        for lane in &LANES {
+            println!(
+                "robot={} obstacle_velocities={:?} collisions={}",
+                lane.player_number,
+                lane.obstacle_velocities,
+                counter
+                    .collisions
+                    .get(&lane.player_number)
+                    .copied()
+                    .unwrap_or_default(),
+            );
+        }
+        println!(
+            "result=ok frames={} arrived={arrived} timed_out={timed_out}",
+            timeline.frames.len()
+        );
+        exit.write(AppExit::Success);
+    }
+}
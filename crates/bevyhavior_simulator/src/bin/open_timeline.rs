@@ -0,0 +1,16 @@
+use std::{env::args_os, path::PathBuf};
+
+use bevyhavior_simulator::{
+    behavior_tree_simulator::load_timeline, timeline_viewer::show_timeline_viewer,
+};
+use color_eyre::{Result, eyre::eyre};
+
+fn main() -> Result<()> {
+    color_eyre::install()?;
+
+    let path = args_os()
+        .nth(1)
+        .map(PathBuf::from)
+        .ok_or_else(|| eyre!("usage: open_timeline <path/to/scenario.timeline>"))?;
+    show_timeline_viewer(load_timeline(path)?)
+}
@@ -2,6 +2,7 @@ use std::{collections::BTreeMap, time::SystemTime};
 
 use bevy::prelude::*;
 use hsl_network_messages::{HulkMessage, Team};
+use ros_z::time::Time;
 use serde::Serialize;
 use types::{
     ball_position::BallPosition,
@@ -13,7 +14,7 @@ use types::{
 
 use crate::behavior_tree_simulator::{
     SimulationConfig, SimulatorClock, SimulatorGameState, SimulatorRobot, SimulatorRobotBehavior,
-    SimulatorRobotFrames, SimulatorRobotId, SimulatorWorldStates,
+    SimulatorRobotFrames, SimulatorRobotId, SimulatorRosZRobot, SimulatorWorldStates,
 };
 
 #[derive(Resource, Clone, Debug, Default)]
@@ -31,6 +32,8 @@ pub struct SimulatorReceivedHslMessages {
     pub messages_by_receiver:
         BTreeMap<SimulatorRobotId, BTreeMap<SimulatorRobotId, SimulatorReceivedHslMessage>>,
     pub player_states_by_receiver: BTreeMap<SimulatorRobotId, Players<Option<PlayerState>>>,
+    /// When each receiver last heard a state message from each teammate.
+    pub player_state_times_by_receiver: BTreeMap<SimulatorRobotId, Players<Option<Time>>>,
 }
 
 #[derive(Clone, Debug, Serialize)]
@@ -62,7 +65,7 @@ pub fn plan_communication(
     world_states: Res<SimulatorWorldStates>,
     mut robot_frames: ResMut<SimulatorRobotFrames>,
     mut outgoing_messages: ResMut<SimulatorOutgoingMessages>,
-    mut robots: Query<(&SimulatorRobot, &mut SimulatorRobotBehavior)>,
+    mut robots: Query<(&SimulatorRobot, &mut SimulatorRobotBehavior), Without<SimulatorRosZRobot>>,
 ) {
     outgoing_messages.messages.clear();
 
@@ -104,20 +107,23 @@ pub fn apply_incoming_hsl_messages(
             continue;
         };
 
-        let HulkMessage::State(state_message) = message;
-        let player_state = PlayerState {
-            pose: state_message.pose,
-            ball_position: state_message.ball_position.map(|ball| {
-                BallPosition::from_network_ball(
-                    ball,
-                    ros_z::time::Time::from_wallclock(incoming_message.received_at),
-                )
-            }),
-        };
-        received_hsl_messages
-            .player_states_by_receiver
-            .entry(incoming_message.receiver)
-            .or_default()[state_message.player_number] = Some(player_state);
+        if let HulkMessage::State(state_message) = message {
+            let received_at = Time::from_wallclock(incoming_message.received_at);
+            let player_state = PlayerState {
+                pose: state_message.pose,
+                ball_position: state_message
+                    .ball_position
+                    .map(|ball| BallPosition::from_network_ball(ball, received_at)),
+            };
+            received_hsl_messages
+                .player_states_by_receiver
+                .entry(incoming_message.receiver)
+                .or_default()[state_message.player_number] = Some(player_state);
+            received_hsl_messages
+                .player_state_times_by_receiver
+                .entry(incoming_message.receiver)
+                .or_default()[state_message.player_number] = Some(received_at);
+        }
 
         received_hsl_messages
             .messages_by_receiver
@@ -194,6 +200,17 @@ pub fn player_states_from_received_hsl_messages(
         .unwrap_or_default()
 }
 
+pub fn player_state_times_from_received_hsl_messages(
+    receiver: SimulatorRobotId,
+    received_hsl_messages: &SimulatorReceivedHslMessages,
+) -> Players<Option<Time>> {
+    received_hsl_messages
+        .player_state_times_by_receiver
+        .get(&receiver)
+        .copied()
+        .unwrap_or_default()
+}
+
 #[cfg(test)]
 mod tests {
     use std::time::{Duration, SystemTime};
@@ -215,6 +232,7 @@ mod tests {
                 age: Duration::from_millis(500),
                 position: point![x + 1.0, y],
             }),
+            search_state: Default::default(),
         })
     }
 
@@ -457,5 +475,12 @@ mod tests {
                 [PlayerNumber::Three]
                 .is_some()
         );
+        assert_eq!(
+            received_hsl_messages.player_state_times_by_receiver[&robot_id(PlayerNumber::Four)]
+                [PlayerNumber::Three],
+            Some(Time::from_wallclock(
+                SystemTime::UNIX_EPOCH + Duration::from_secs(2)
+            ))
+        );
     }
 }
@@ -1,12 +1,13 @@
 use std::{net::SocketAddr, time::Duration};
 
+use behavior_node::tree_description::TreeDescription;
 use color_eyre::{Result, eyre::Context};
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use types::parameters::{BehaviorParameters, RLWalkingParameters};
 
 pub const DEFAULT_TICK_DURATION: Duration = Duration::from_millis(10);
 
-#[derive(bevy::prelude::Resource, Clone, Debug)]
+#[derive(bevy::prelude::Resource, Clone, Debug, Serialize, Deserialize)]
 pub struct SimulationConfig {
     pub walk_translation_speed: f32,
     pub walk_rotation_speed: f32,
@@ -62,6 +63,12 @@ pub fn default_behavior_parameters() -> Result<BehaviorParameters> {
     .wrap_err("failed to parse behavior parameters")
 }
 
+pub fn default_behavior_tree() -> Result<TreeDescription> {
+    TreeDescription::from_json5(include_str!(
+        "../../../etc/parameters/ros_z/base/behavior_tree.json5"
+    ))
+}
+
 #[derive(Deserialize)]
 struct BoosterInterfaceParametersFile {
     walking: BoosterWalkingParameters,
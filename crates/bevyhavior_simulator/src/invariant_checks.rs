@@ -1,12 +1,12 @@
 use std::fmt::{Display, Formatter, Result};
-use std::{collections::BTreeMap, time::SystemTime};
+use std::{borrow::Cow, collections::BTreeMap, time::SystemTime};
 
 use bevy::prelude::*;
 use booster::FallDownState;
 use coordinate_systems::{Field, Ground, World};
 use hsl_network_messages::PlayerNumber;
 use linear_algebra::{Isometry2, Orientation2, Point2};
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use types::path::traits::EndPoints;
 use types::{
     field_dimensions::FieldDimensions, motion_command::MotionCommand, primary_state::PrimaryState,
@@ -29,7 +29,7 @@ pub struct SimulatorCurrentInvariantViolations(pub Vec<InvariantViolation>);
 
 pub const BEHAVIOR_TICK_ERROR_CHECK_NAME: &str = "behavior_tick_error";
 
-#[derive(Clone, Copy, Debug, Serialize)]
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
 pub struct RobotSnapshot {
     pub id: SimulatorRobotId,
     pub player_number: PlayerNumber,
@@ -50,9 +50,9 @@ pub struct SimulationSnapshot {
     pub config: SimulationConfig,
 }
 
-#[derive(Clone, Debug, Serialize)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct InvariantViolation {
-    pub check_name: &'static str,
+    pub check_name: Cow<'static, str>,
     pub player_number: Option<PlayerNumber>,
     pub message: String,
     pub severity: InvariantSeverity,
@@ -68,7 +68,7 @@ impl Display for InvariantViolation {
     }
 }
 
-#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize)]
 pub enum InvariantSeverity {
     Warning,
     Error,
@@ -98,7 +98,7 @@ impl InvariantCheck for RuleObstacleWalkCheck {
             for obstacle in &frame.world_state.rule_obstacles {
                 if obstacle.contains(target) {
                     violations.push(InvariantViolation {
-                        check_name: "rule_obstacle_walk",
+                        check_name: "rule_obstacle_walk".into(),
                         player_number: Some(robot_id.player_number),
                         message: format!(
                             "robot {robot_id} plans to walk into a known rule obstacle"
@@ -125,7 +125,7 @@ impl InvariantCheck for FieldBoundaryWalkCheck {
 
             if !is_inside_field_with_border_margin(target, snapshot.field_dimensions) {
                 violations.push(InvariantViolation {
-                    check_name: "field_boundary_walk",
+                    check_name: "field_boundary_walk".into(),
                     player_number: Some(robot_id.player_number),
                     message: format!("robot {robot_id} plans to walk outside the known field"),
                     severity: InvariantSeverity::Error,
@@ -14,7 +14,7 @@ use crate::behavior_tree_simulator::{
     SimulatedBall, SimulationConfig, SimulatorBall, SimulatorClock, SimulatorFallDownState,
     SimulatorFieldDimensions, SimulatorGameState, SimulatorGroundToWorld, SimulatorHeadYaw,
     SimulatorLastKickTime, SimulatorRobot, SimulatorRobotFrames, SimulatorRobotId,
-    SimulatorRobotParameters,
+    SimulatorRobotParameters, SimulatorScenarioObstacles,
 };
 
 pub fn resolve_collisions(
@@ -68,6 +68,16 @@ pub fn resolve_collisions(
     }
 }
 
+pub fn move_scenario_obstacles(
+    clock: Res<SimulatorClock>,
+    mut scenario_obstacles: ResMut<SimulatorScenarioObstacles>,
+) {
+    let elapsed = clock.tick_duration.as_secs_f32();
+    for obstacle in &mut scenario_obstacles.obstacles {
+        obstacle.position += obstacle.velocity * elapsed;
+    }
+}
+
 pub fn move_robots(
     clock: Res<SimulatorClock>,
     config: Res<SimulationConfig>,
@@ -142,7 +152,10 @@ pub fn move_robots(
                 )
             }
             MotionCommand::StandUp => fall_down_state.fall_down_state = None,
-            MotionCommand::Damping | MotionCommand::Prepare | MotionCommand::Stand { .. } => {}
+            MotionCommand::Damping
+            | MotionCommand::Prepare
+            | MotionCommand::Stand { .. }
+            | MotionCommand::KeyframeMotion { .. } => {}
         }
 
         head_yaw.yaw = apply_head_motion(
@@ -679,6 +692,7 @@ mod tests {
             name: String::new(),
             status: Status::Success,
             children: Vec::new(),
+            role: None,
         }
     }
 }
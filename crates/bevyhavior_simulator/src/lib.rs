@@ -10,7 +10,10 @@ mod coordinates;
 mod game_controller;
 mod invariant_checks;
 mod kinematics;
+mod opponent_strategy;
 mod robot;
+mod ros_z_node_graph;
 mod timeline;
+mod timeline_file;
 pub mod timeline_viewer;
 mod world_states;
@@ -0,0 +1,569 @@
+use std::{
+    fs::{read, read_to_string},
+    path::Path,
+    time::Duration,
+};
+
+use color_eyre::{
+    Result,
+    eyre::{WrapErr, bail},
+};
+use coordinate_systems::{Field, Ground};
+use hsl_network_messages::{GameControllerReturnMessage, PlayerNumber};
+use linear_algebra::{Isometry2, Orientation2, Point2, Pose2, Vector2, point};
+use ros_z::time::Time;
+use serde::Deserialize;
+use types::{
+    field_dimensions::FieldDimensions,
+    motion_command::{HeadMotion, ImageRegion, KickPower, MotionCommand},
+    primary_state::PrimaryState,
+    world_state::WorldState,
+};
+
+const MAXIMUM_WALK_SPEED: f32 = 0.3;
+const MAXIMUM_TURN_SPEED: f32 = 1.0;
+const TRANSLATION_GAIN: f32 = 1.5;
+const ROTATION_GAIN: f32 = 2.0;
+const POSITION_TOLERANCE: f32 = 0.05;
+const ORIENTATION_TOLERANCE: f32 = 0.1;
+
+/// Scripted replacement for the behavior tree of a single simulated robot.
+///
+/// Strategies are ticked instead of the behavior tree and are meant for opponent robots that
+/// should behave predictably, so that our own behavior can be evaluated against reproducible
+/// adversaries.
+pub trait OpponentStrategy: Send + Sync {
+    fn name(&self) -> &'static str;
+    fn motion_command(&mut self, context: &OpponentStrategyContext<'_>) -> MotionCommand;
+}
+
+pub struct OpponentStrategyContext<'a> {
+    pub world_state: &'a WorldState,
+    pub field_dimensions: &'a FieldDimensions,
+}
+
+impl OpponentStrategyContext<'_> {
+    fn ground_to_field(&self) -> Isometry2<Ground, Field> {
+        self.world_state
+            .robot
+            .ground_to_field
+            .unwrap_or_else(Isometry2::identity)
+    }
+
+    /// Ground-truth ball position; scripted strategies are omniscient on purpose.
+    fn ball_in_field(&self) -> Option<Point2<Field>> {
+        self.world_state
+            .rule_ball
+            .or(self.world_state.ball)
+            .map(|ball| ball.ball_in_field)
+    }
+
+    fn is_playing(&self) -> bool {
+        self.world_state.robot.primary_state == PrimaryState::Playing
+    }
+
+    fn look_at(&self, target: Option<Point2<Field>>) -> HeadMotion {
+        match target {
+            Some(target) => HeadMotion::LookAt {
+                target: self.ground_to_field().inverse() * target,
+                image_region_target: ImageRegion::Center,
+            },
+            None => HeadMotion::LookAround,
+        }
+    }
+
+    fn stand(&self) -> MotionCommand {
+        MotionCommand::Stand {
+            head: self.look_at(self.ball_in_field()),
+        }
+    }
+
+    fn walk_to(&self, target: Pose2<Field>) -> MotionCommand {
+        let head = self.look_at(self.ball_in_field());
+        match walk_velocity_towards(self.ground_to_field(), target) {
+            Some((velocity, angular_velocity)) => MotionCommand::WalkWithVelocity {
+                head,
+                velocity,
+                angular_velocity,
+            },
+            None => MotionCommand::Stand { head },
+        }
+    }
+}
+
+/// Goalkeeper that shadows the ball along its own goal line and never leaves it.
+#[derive(Clone, Copy, Debug)]
+pub struct StaticGoalieWall {
+    pub distance_in_front_of_goal_line: f32,
+}
+
+impl Default for StaticGoalieWall {
+    fn default() -> Self {
+        Self {
+            distance_in_front_of_goal_line: 0.25,
+        }
+    }
+}
+
+impl StaticGoalieWall {
+    fn target(&self, context: &OpponentStrategyContext<'_>) -> Pose2<Field> {
+        let x = -context.field_dimensions.length / 2.0 + self.distance_in_front_of_goal_line;
+        let half_goal_width = context.field_dimensions.goal_inner_width / 2.0;
+        let y = context.ball_in_field().map_or(0.0, |ball| {
+            ball.y().clamp(-half_goal_width, half_goal_width)
+        });
+        Pose2::new(point![x, y], 0.0)
+    }
+}
+
+impl OpponentStrategy for StaticGoalieWall {
+    fn name(&self) -> &'static str {
+        "static_goalie_wall"
+    }
+
+    fn motion_command(&mut self, context: &OpponentStrategyContext<'_>) -> MotionCommand {
+        if !context.is_playing() {
+            return context.stand();
+        }
+        context.walk_to(self.target(context))
+    }
+}
+
+/// Striker that always runs to the ball and kicks it as hard as possible towards the goal.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct AggressiveBallChaser;
+
+impl OpponentStrategy for AggressiveBallChaser {
+    fn name(&self) -> &'static str {
+        "aggressive_ball_chaser"
+    }
+
+    fn motion_command(&mut self, context: &OpponentStrategyContext<'_>) -> MotionCommand {
+        if !context.is_playing() {
+            return context.stand();
+        }
+        let Some(ball) = context.ball_in_field() else {
+            return MotionCommand::WalkWithVelocity {
+                head: HeadMotion::LookAround,
+                velocity: Vector2::zeros(),
+                angular_velocity: MAXIMUM_TURN_SPEED,
+            };
+        };
+
+        let field_to_ground = context.ground_to_field().inverse();
+        let goal_center: Point2<Field> = point![context.field_dimensions.length / 2.0, 0.0];
+        let ball_in_ground = field_to_ground * ball;
+        let target_in_ground = field_to_ground * goal_center;
+        let kick_direction = Orientation2::from_vector(target_in_ground - ball_in_ground);
+
+        MotionCommand::VisualKick {
+            head: context.look_at(Some(ball)),
+            ball_position: ball_in_ground,
+            kick_direction,
+            target_position: target_in_ground,
+            robot_theta_to_field: context.ground_to_field().orientation(),
+            kick_power: KickPower::Schlong,
+        }
+    }
+}
+
+/// Defender that blocks the line between ball and its own goal but never plays the ball.
+#[derive(Clone, Copy, Debug)]
+pub struct PassiveDefender {
+    pub distance_to_goal: f32,
+}
+
+impl Default for PassiveDefender {
+    fn default() -> Self {
+        Self {
+            distance_to_goal: 2.0,
+        }
+    }
+}
+
+impl PassiveDefender {
+    fn target(&self, context: &OpponentStrategyContext<'_>) -> Pose2<Field> {
+        let own_goal: Point2<Field> = point![-context.field_dimensions.length / 2.0, 0.0];
+        let Some(ball) = context.ball_in_field() else {
+            return Pose2::new(own_goal + Vector2::x_axis() * self.distance_to_goal, 0.0);
+        };
+        let goal_to_ball = ball - own_goal;
+        let distance = self.distance_to_goal.min(goal_to_ball.norm());
+        let direction = if goal_to_ball.norm() > f32::EPSILON {
+            goal_to_ball.normalize()
+        } else {
+            Vector2::x_axis()
+        };
+        Pose2::from_parts(
+            own_goal + direction * distance,
+            Orientation2::from_vector(direction),
+        )
+    }
+}
+
+impl OpponentStrategy for PassiveDefender {
+    fn name(&self) -> &'static str {
+        "passive_defender"
+    }
+
+    fn motion_command(&mut self, context: &OpponentStrategyContext<'_>) -> MotionCommand {
+        if !context.is_playing() {
+            return context.stand();
+        }
+        context.walk_to(self.target(context))
+    }
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq)]
+pub struct TrajectorySample {
+    /// Seconds since the robot entered `Playing`.
+    pub time: f32,
+    pub x: f32,
+    pub y: f32,
+    pub orientation: f32,
+}
+
+/// Pose trajectory of a single robot extracted from a game log, in the robot's own field frame.
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+pub struct RecordedTrajectory {
+    pub samples: Vec<TrajectorySample>,
+}
+
+impl RecordedTrajectory {
+    pub fn from_samples(samples: Vec<TrajectorySample>) -> Result<Self> {
+        if samples.is_empty() {
+            bail!("trajectory does not contain any samples");
+        }
+        if samples.windows(2).any(|pair| pair[1].time < pair[0].time) {
+            bail!("trajectory samples are not sorted by time");
+        }
+        Ok(Self { samples })
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let content = read_to_string(path)
+            .wrap_err_with(|| format!("failed to read trajectory {}", path.display()))?;
+        let trajectory: Self = json5::from_str(&content)
+            .wrap_err_with(|| format!("failed to parse trajectory {}", path.display()))?;
+        Self::from_samples(trajectory.samples)
+    }
+
+    /// Extracts the trajectory of `player_number` from the return messages it sent to the
+    /// GameController during a game, starting at the first message.
+    ///
+    /// Poses reported while the robot is fallen are skipped.
+    pub fn from_return_messages(
+        messages: impl IntoIterator<Item = (Duration, GameControllerReturnMessage)>,
+        player_number: PlayerNumber,
+    ) -> Result<Self> {
+        let mut messages = messages
+            .into_iter()
+            .filter(|(_, message)| message.player_number == player_number && !message.fallen)
+            .peekable();
+        let start = messages.peek().map_or(Duration::ZERO, |(time, _)| *time);
+        let samples = messages
+            .map(|(time, message)| TrajectorySample {
+                time: time.saturating_sub(start).as_secs_f32(),
+                x: message.pose.position().x(),
+                y: message.pose.position().y(),
+                orientation: message.pose.angle(),
+            })
+            .collect();
+        Self::from_samples(samples)
+    }
+
+    /// Loads the trajectory of a robot from a log of return packets captured at the
+    /// GameController.
+    ///
+    /// Each record of the log consists of the little-endian `u64` nanoseconds since the start of
+    /// the capture, the little-endian `u16` packet length and the raw packet. Packets which cannot
+    /// be decoded are skipped.
+    pub fn load_return_packet_log(
+        path: impl AsRef<Path>,
+        team_number: u8,
+        player_number: PlayerNumber,
+    ) -> Result<Self> {
+        let path = path.as_ref();
+        let log = read(path).wrap_err_with(|| format!("failed to read {}", path.display()))?;
+        let messages = parse_return_packet_log(&log)
+            .wrap_err_with(|| format!("failed to parse {}", path.display()))?
+            .into_iter()
+            .filter_map(|(time, packet)| {
+                let (sender_team_number, message) =
+                    GameControllerReturnMessage::from_packet_of_any_team(packet).ok()?;
+                (sender_team_number == team_number).then_some((time, message))
+            });
+        Self::from_return_messages(messages, player_number)
+            .wrap_err_with(|| format!("no trajectory of {player_number:?} in {}", path.display()))
+    }
+
+    pub fn duration(&self) -> Duration {
+        self.samples.last().map_or(Duration::ZERO, |sample| {
+            Duration::from_secs_f32(sample.time.max(0.0))
+        })
+    }
+
+    /// Interpolated pose at `elapsed`, clamped to the first and last sample.
+    pub fn pose_at(&self, elapsed: Duration) -> Pose2<Field> {
+        let time = elapsed.as_secs_f32();
+        let index = self.samples.partition_point(|sample| sample.time <= time);
+        match (
+            index.checked_sub(1).map(|index| &self.samples[index]),
+            self.samples.get(index),
+        ) {
+            (Some(previous), Some(next)) => {
+                let span = next.time - previous.time;
+                let t = if span > f32::EPSILON {
+                    (time - previous.time) / span
+                } else {
+                    1.0
+                };
+                let position = sample_position(previous)
+                    + (sample_position(next) - sample_position(previous)) * t;
+                let orientation = Orientation2::<Field>::new(previous.orientation)
+                    .slerp(Orientation2::new(next.orientation), t);
+                Pose2::from_parts(position, orientation)
+            }
+            (Some(sample), None) | (None, Some(sample)) => {
+                Pose2::new(sample_position(sample), sample.orientation)
+            }
+            (None, None) => Pose2::zero(),
+        }
+    }
+}
+
+fn parse_return_packet_log(mut log: &[u8]) -> Result<Vec<(Duration, &[u8])>> {
+    const RECORD_HEADER_LENGTH: usize = size_of::<u64>() + size_of::<u16>();
+
+    let mut packets = Vec::new();
+    while !log.is_empty() {
+        let Some((header, rest)) = log.split_at_checked(RECORD_HEADER_LENGTH) else {
+            bail!("truncated record header at the end of the log");
+        };
+        let (time, length) = header.split_at(size_of::<u64>());
+        let time = Duration::from_nanos(u64::from_le_bytes(time.try_into()?));
+        let length = u16::from_le_bytes(length.try_into()?).into();
+        let Some((packet, rest)) = rest.split_at_checked(length) else {
+            bail!("truncated packet at the end of the log");
+        };
+        packets.push((time, packet));
+        log = rest;
+    }
+    Ok(packets)
+}
+
+fn sample_position(sample: &TrajectorySample) -> Point2<Field> {
+    point![sample.x, sample.y]
+}
+
+/// Follows a recorded trajectory starting from the moment the robot enters `Playing` and stands
+/// still once the recording is exhausted.
+#[derive(Clone, Debug)]
+pub struct ReplayedTrajectory {
+    pub trajectory: RecordedTrajectory,
+    started_at: Option<Time>,
+}
+
+impl ReplayedTrajectory {
+    pub fn new(trajectory: RecordedTrajectory) -> Self {
+        Self {
+            trajectory,
+            started_at: None,
+        }
+    }
+}
+
+impl OpponentStrategy for ReplayedTrajectory {
+    fn name(&self) -> &'static str {
+        "replayed_trajectory"
+    }
+
+    fn motion_command(&mut self, context: &OpponentStrategyContext<'_>) -> MotionCommand {
+        if !context.is_playing() {
+            return context.stand();
+        }
+        let now = context.world_state.now;
+        let started_at = *self.started_at.get_or_insert(now);
+        let elapsed = now.duration_since(started_at);
+        if elapsed > self.trajectory.duration() {
+            return context.stand();
+        }
+        context.walk_to(self.trajectory.pose_at(elapsed))
+    }
+}
+
+fn walk_velocity_towards(
+    ground_to_field: Isometry2<Ground, Field>,
+    target: Pose2<Field>,
+) -> Option<(Vector2<Ground>, f32)> {
+    let robot = ground_to_field.as_pose();
+    let translation = target.position() - robot.position();
+    let rotation = Orientation2::<Field>::new(target.angle() - robot.angle()).angle();
+    if translation.norm() < POSITION_TOLERANCE && rotation.abs() < ORIENTATION_TOLERANCE {
+        return None;
+    }
+
+    let velocity = ground_to_field.inverse() * (translation * TRANSLATION_GAIN);
+    let speed = velocity.norm();
+    let velocity = if speed > MAXIMUM_WALK_SPEED {
+        velocity * (MAXIMUM_WALK_SPEED / speed)
+    } else {
+        velocity
+    };
+    let angular_velocity =
+        (rotation * ROTATION_GAIN).clamp(-MAXIMUM_TURN_SPEED, MAXIMUM_TURN_SPEED);
+    Some((velocity, angular_velocity))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{f32::consts::PI, time::SystemTime};
+
+    use approx::assert_relative_eq;
+    use linear_algebra::vector;
+    use types::{
+        field_dimensions::Side,
+        world_state::{BallState, RobotState},
+    };
+
+    use super::*;
+
+    fn world_state_with_ball(ball: Point2<Field>) -> WorldState {
+        WorldState {
+            robot: RobotState {
+                ground_to_field: Some(Isometry2::from_parts(vector![-4.0, 0.0], 0.0)),
+                primary_state: PrimaryState::Playing,
+                ..Default::default()
+            },
+            rule_ball: Some(BallState {
+                ball_in_ground: point![ball.x() + 4.0, ball.y()],
+                ball_in_field: ball,
+                ball_in_ground_velocity: Vector2::zeros(),
+                last_seen_ball: SystemTime::UNIX_EPOCH,
+                field_side: Side::Left,
+            }),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn goalie_wall_stays_within_goal_width() {
+        let world_state = world_state_with_ball(point![0.0, 3.0]);
+        let context = OpponentStrategyContext {
+            world_state: &world_state,
+            field_dimensions: &FieldDimensions::SPL_2025,
+        };
+
+        let target = StaticGoalieWall::default().target(&context);
+
+        assert_relative_eq!(
+            target.position().y(),
+            FieldDimensions::SPL_2025.goal_inner_width / 2.0
+        );
+        assert_relative_eq!(
+            target.position().x(),
+            -FieldDimensions::SPL_2025.length / 2.0 + 0.25
+        );
+    }
+
+    #[test]
+    fn passive_defender_never_kicks() {
+        let world_state = world_state_with_ball(point![-4.2, 0.0]);
+        let context = OpponentStrategyContext {
+            world_state: &world_state,
+            field_dimensions: &FieldDimensions::SPL_2025,
+        };
+
+        let motion_command = PassiveDefender::default().motion_command(&context);
+
+        assert!(!matches!(motion_command, MotionCommand::VisualKick { .. }));
+    }
+
+    #[test]
+    fn replayed_trajectory_interpolates_between_samples() {
+        let trajectory = RecordedTrajectory::from_samples(vec![
+            TrajectorySample {
+                time: 0.0,
+                x: 0.0,
+                y: 0.0,
+                orientation: 0.0,
+            },
+            TrajectorySample {
+                time: 2.0,
+                x: 1.0,
+                y: -1.0,
+                orientation: PI / 2.0,
+            },
+        ])
+        .expect("trajectory should be valid");
+
+        let pose = trajectory.pose_at(Duration::from_secs(1));
+        assert_relative_eq!(pose.position(), point![0.5, -0.5]);
+        assert_relative_eq!(pose.angle(), PI / 4.0, epsilon = 1e-5);
+
+        let pose = trajectory.pose_at(Duration::from_secs(5));
+        assert_relative_eq!(pose.position(), point![1.0, -1.0]);
+    }
+
+    #[test]
+    fn trajectory_is_extracted_from_return_packet_log() {
+        let record = |time: u64, team_number: u8, player_number, x: f32, fallen| {
+            let mut packet: Vec<u8> = GameControllerReturnMessage {
+                player_number,
+                fallen,
+                pose: Pose2::new(point![x, 1.0], 0.5),
+                ball: None,
+            }
+            .into();
+            packet[6] = team_number;
+            let mut record = (time * 1_000_000_000).to_le_bytes().to_vec();
+            record.extend((packet.len() as u16).to_le_bytes());
+            record.extend(packet);
+            record
+        };
+        let log = [
+            record(10, 5, PlayerNumber::Two, 1.0, false),
+            record(11, 5, PlayerNumber::Three, 2.0, false),
+            record(11, 24, PlayerNumber::Two, 3.0, false),
+            record(12, 5, PlayerNumber::Two, 4.0, true),
+            record(13, 5, PlayerNumber::Two, 5.0, false),
+        ]
+        .concat();
+
+        let messages = parse_return_packet_log(&log)
+            .unwrap()
+            .into_iter()
+            .filter_map(|(time, packet)| {
+                let (team_number, message) =
+                    GameControllerReturnMessage::from_packet_of_any_team(packet).unwrap();
+                (team_number == 5).then_some((time, message))
+            });
+        let trajectory =
+            RecordedTrajectory::from_return_messages(messages, PlayerNumber::Two).unwrap();
+
+        let times: Vec<_> = trajectory
+            .samples
+            .iter()
+            .map(|sample| sample.time)
+            .collect();
+        let xs: Vec<_> = trajectory.samples.iter().map(|sample| sample.x).collect();
+        assert_eq!(times, [0.0, 3.0]);
+        assert_relative_eq!(xs[0], 1.0, epsilon = 1e-3);
+        assert_relative_eq!(xs[1], 5.0, epsilon = 1e-3);
+        assert!(parse_return_packet_log(&log[..log.len() - 1]).is_err());
+    }
+
+    #[test]
+    fn unsorted_trajectory_is_rejected() {
+        let sample = |time| TrajectorySample {
+            time,
+            x: 0.0,
+            y: 0.0,
+            orientation: 0.0,
+        };
+
+        assert!(RecordedTrajectory::from_samples(vec![sample(1.0), sample(0.0)]).is_err());
+        assert!(RecordedTrajectory::from_samples(Vec::new()).is_err());
+    }
+}
@@ -1,18 +1,21 @@
 use std::time::SystemTime;
 
+use behavior_node::tree_description::TreeDescription;
 use bevy::prelude::*;
 use booster::FallDownState;
 use color_eyre::Result;
 use coordinate_systems::{Field, Ground, World};
 use hsl_network_messages::{PlayerNumber, Team};
 use linear_algebra::{Isometry2, Orientation2, Point2};
-use serde::Serializer;
+use serde::{Deserialize, Deserializer, Serializer};
 use types::{
     parameters::{BehaviorParameters, RLWalkingParameters},
     primary_state::PrimaryState,
 };
 
-use crate::behavior_tree_simulator::{SimulatorRobotBehavior, default_walking_parameters};
+use crate::behavior_tree_simulator::{
+    OpponentStrategy, SimulatorRobotBehavior, default_behavior_tree, default_walking_parameters,
+};
 
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub struct SimulatorRobotId {
@@ -76,6 +79,40 @@ impl serde::Serialize for SimulatorRobotId {
     }
 }
 
+impl std::str::FromStr for SimulatorRobotId {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("invalid robot id {value:?}, expected e.g. \"H3\" or \"O1\"");
+        let mut characters = value.chars();
+        let team = match characters.next() {
+            Some('H') => Team::Hulks,
+            Some('O') => Team::Opponent,
+            _ => return Err(invalid()),
+        };
+        let player_number = match characters.as_str() {
+            "1" => PlayerNumber::One,
+            "2" => PlayerNumber::Two,
+            "3" => PlayerNumber::Three,
+            "4" => PlayerNumber::Four,
+            "5" => PlayerNumber::Five,
+            _ => return Err(invalid()),
+        };
+        Ok(Self::new(team, player_number))
+    }
+}
+
+impl<'de> Deserialize<'de> for SimulatorRobotId {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(serde::de::Error::custom)
+    }
+}
+
 #[derive(Component, Clone, Copy, Debug)]
 pub struct SimulatorRobot {
     pub team: Team,
@@ -154,7 +191,7 @@ impl SimulatorRobotBundle {
             primary_state: SimulatorPrimaryState {
                 primary_state: PrimaryState::Damping,
             },
-            behavior: SimulatorRobotBehavior::new(parameters.clone()),
+            behavior: SimulatorRobotBehavior::new(parameters.clone(), default_behavior_tree()?)?,
             parameters: SimulatorRobotParameters {
                 behavior: parameters,
                 walking: default_walking_parameters()?,
@@ -171,4 +208,16 @@ impl SimulatorRobotBundle {
         self.primary_state.primary_state = primary_state;
         self
     }
+
+    /// Replaces the default behavior tree, e.g. with one loaded by [`TreeDescription::load`].
+    pub fn with_behavior_tree(mut self, tree_description: TreeDescription) -> Result<Self> {
+        self.behavior =
+            SimulatorRobotBehavior::new(self.parameters.behavior.clone(), tree_description)?;
+        Ok(self)
+    }
+
+    pub fn with_opponent_strategy(mut self, strategy: impl OpponentStrategy + 'static) -> Self {
+        self.behavior = self.behavior.with_opponent_strategy(Box::new(strategy));
+        self
+    }
 }
@@ -0,0 +1,793 @@
+use std::{
+    collections::BTreeMap,
+    env::temp_dir,
+    fs::{create_dir_all, remove_dir, remove_dir_all, write},
+    net::SocketAddr,
+    path::PathBuf,
+    process,
+    sync::Arc,
+    time::Duration,
+};
+
+use behavior_node::{
+    node::CYCLE_TIME,
+    tree_description::{TREE_FILE_NAME, TreeDescription},
+};
+use bevy::{app::AppExit, prelude::*};
+use booster::FallDownState;
+use color_eyre::{
+    Report, Result,
+    eyre::{WrapErr, eyre},
+};
+use coordinate_systems::{Field, Ground};
+use hsl_network_messages::{PlayerNumber, SearchState};
+use linear_algebra::{Isometry2, Point2};
+use ros_z::{
+    cache::Cache,
+    prelude::*,
+    qos::QosDurability,
+    time::{Clock, Time},
+};
+use tokio::{
+    runtime::{Builder, Runtime},
+    task::JoinHandle,
+    time::timeout,
+};
+use types::{
+    ball_position::HypotheticalBallPosition,
+    behavior_tree::{NodeTrace, Status},
+    field_dimensions::FieldDimensions,
+    filtered_game_controller_state::FilteredGameControllerState,
+    messages::{IncomingMessage, OutgoingMessage},
+    motion_command::MotionCommand,
+    obstacles::Obstacle,
+    parameters::BehaviorParameters,
+    players::Players,
+    primary_state::PrimaryState,
+    rule_obstacles::RuleObstacle,
+    time_wrapper::TimeWrapper,
+    world_state::{BallState, PlayerState, WorldState},
+};
+
+use crate::behavior_runtime::behavior_tick_failure_message;
+use crate::behavior_tree_simulator::{
+    InvariantSeverity, InvariantViolation, RobotFrame, SimulationConfig, SimulatorClock,
+    SimulatorCurrentInvariantViolations, SimulatorFieldDimensions, SimulatorIncomingMessages,
+    SimulatorMessage, SimulatorOutgoingMessages, SimulatorReceivedHslMessages, SimulatorRobot,
+    SimulatorRobotBehavior, SimulatorRobotFrames, SimulatorRobotId, SimulatorRobotParameters,
+    SimulatorScenarioResult, SimulatorWorldStates,
+};
+use crate::communication::player_state_times_from_received_hsl_messages;
+use crate::invariant_checks::BEHAVIOR_TICK_ERROR_CHECK_NAME;
+
+/// Environment variable enabling the ros-z node graph for every scenario.
+pub const ROS_Z_NODE_GRAPH_VARIABLE: &str = "BEVYHAVIOR_SIMULATOR_ROS_Z";
+/// Environment variable naming a zenoh router the robot contexts connect to, e.g. to inspect the
+/// simulated robots with the usual ros-z tooling.
+pub const ROS_Z_ROUTER_VARIABLE: &str = "BEVYHAVIOR_SIMULATOR_ROS_Z_ROUTER";
+
+/// How long the simulator waits in wall-clock time for a `behavior_node` cycle.
+const BEHAVIOR_NODE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Runs the deployed ros-z `behavior_node` for simulated robots instead of ticking their behavior
+/// tree in-process.
+///
+/// Every robot gets its own context in the namespace `/simulator/<robot>`. All contexts share one
+/// logical clock which follows [`SimulatorClock`], so the node cycles exactly when simulated time
+/// passes its timer deadline.
+#[derive(Resource)]
+pub struct SimulatorRosZNodeGraph {
+    runtime: Runtime,
+    clock: Clock,
+    router_endpoint: Option<String>,
+    robots: BTreeMap<SimulatorRobotId, RobotNodeGraph>,
+}
+
+impl SimulatorRosZNodeGraph {
+    pub fn new(router_endpoint: Option<String>) -> Result<Self> {
+        let runtime = Builder::new_multi_thread()
+            .enable_all()
+            .build()
+            .wrap_err("failed to build runtime for the ros-z node graph")?;
+        Ok(Self {
+            runtime,
+            clock: Clock::logical(Time::zero()),
+            router_endpoint,
+            robots: BTreeMap::new(),
+        })
+    }
+}
+
+/// Holds the error of [`SimulatorRosZNodeGraph::new`] until the first update reports it.
+#[derive(Resource, Default)]
+pub struct SimulatorRosZNodeGraphSetupFailure(Option<Report>);
+
+impl SimulatorRosZNodeGraphSetupFailure {
+    pub fn new(error: Report) -> Self {
+        Self(Some(error))
+    }
+}
+
+/// Marks robots whose behavior runs in the ros-z node graph.
+#[derive(Component, Clone, Copy, Debug, Default)]
+pub struct SimulatorRosZRobot;
+
+pub fn robot_namespace(robot_id: SimulatorRobotId) -> String {
+    format!("/simulator/{robot_id}")
+}
+
+struct RobotNodeGraph {
+    context: Arc<Context>,
+    _parameter_layer: ParameterLayer,
+    behavior_node: JoinHandle<Result<()>>,
+    inputs: RobotInputs,
+    motion_command: Subscriber<MotionCommand>,
+    outgoing_messages: Subscriber<OutgoingMessage>,
+    trace: Cache<NodeTrace>,
+    tree_layout: Cache<NodeTrace>,
+    started_at: Time,
+    completed_cycles: u128,
+    last_motion_command: MotionCommand,
+}
+
+struct RobotInputs {
+    world_state: Publisher<WorldState>,
+    field_dimensions: Publisher<FieldDimensions>,
+    player_number: Publisher<PlayerNumber>,
+    primary_state: Publisher<PrimaryState>,
+    ground_to_field: Publisher<Isometry2<Ground, Field>>,
+    ball_state: Publisher<Option<BallState>>,
+    rule_ball_state: Publisher<Option<BallState>>,
+    fall_down_state: Publisher<FallDownState>,
+    filtered_game_controller_state: Publisher<FilteredGameControllerState>,
+    game_controller_address: Publisher<Option<SocketAddr>>,
+    hypothetical_ball_positions: Publisher<Vec<HypotheticalBallPosition<Ground>>>,
+    obstacles: Publisher<Vec<Obstacle>>,
+    rule_obstacles: Publisher<Vec<RuleObstacle>>,
+    position_of_interest: Publisher<Point2<Ground>>,
+    suggested_search_position: Publisher<Point2<Field>>,
+    search_state: Publisher<SearchState>,
+    player_states: Publisher<Players<Option<TimeWrapper<PlayerState>>>>,
+    incoming_message: Publisher<TimeWrapper<IncomingMessage>>,
+}
+
+impl RobotNodeGraph {
+    async fn spawn(
+        robot_id: SimulatorRobotId,
+        parameters: &BehaviorParameters,
+        tree_description: &TreeDescription,
+        clock: Clock,
+        router_endpoint: Option<&str>,
+    ) -> Result<Self> {
+        let parameter_layer = ParameterLayer::write(robot_id, parameters, tree_description)?;
+        let builder = ContextBuilder::default()
+            .with_namespace(robot_namespace(robot_id))
+            .with_clock(clock.clone())
+            .with_parameter_layers([parameter_layer.path.clone()]);
+        let builder = match router_endpoint {
+            Some(router_endpoint) => builder
+                .with_mode("client")
+                .with_router_endpoint(router_endpoint)?,
+            None => builder.with_mode("peer").disable_multicast_scouting(),
+        };
+        let context = Arc::new(builder.build().await?);
+
+        let node = context.create_node("simulator_bridge").build().await?;
+        let transient_local = QosProfile {
+            durability: QosDurability::TransientLocal,
+            ..Default::default()
+        };
+        let inputs = RobotInputs {
+            world_state: node.publisher("world_state").build().await?,
+            field_dimensions: node
+                .publisher("field_dimensions")
+                .qos(transient_local)
+                .build()
+                .await?,
+            player_number: node
+                .publisher("player_number")
+                .qos(transient_local)
+                .build()
+                .await?,
+            primary_state: node
+                .publisher("primary_state")
+                .qos(transient_local)
+                .build()
+                .await?,
+            ground_to_field: node.publisher("ground_to_field").build().await?,
+            ball_state: node.publisher("ball_state").build().await?,
+            rule_ball_state: node.publisher("rule_ball_state").build().await?,
+            fall_down_state: node.publisher("inputs/fall_down_state").build().await?,
+            filtered_game_controller_state: node
+                .publisher("filtered_game_controller_state")
+                .build()
+                .await?,
+            game_controller_address: node.publisher("game_controller_address").build().await?,
+            hypothetical_ball_positions: node
+                .publisher("hypothetical_ball_positions")
+                .build()
+                .await?,
+            obstacles: node.publisher("obstacles").build().await?,
+            rule_obstacles: node.publisher("rule_obstacles").build().await?,
+            position_of_interest: node.publisher("position_of_interest").build().await?,
+            suggested_search_position: node.publisher("suggested_search_position").build().await?,
+            search_state: node.publisher("search_state").build().await?,
+            player_states: node.publisher("player_states").build().await?,
+            incoming_message: node.publisher("inputs/message").build().await?,
+        };
+        let motion_command = node
+            .subscriber::<MotionCommand>("behavior/motion_command")
+            .build()
+            .await?;
+        let outgoing_messages = node
+            .subscriber::<OutgoingMessage>("outputs/message")
+            .build()
+            .await?;
+        let trace = node
+            .subscriber::<NodeTrace>("behavior/trace")
+            .cache(1)
+            .build()
+            .await?;
+        let tree_layout = node
+            .subscriber::<NodeTrace>("behavior/tree_layout")
+            .qos(transient_local)
+            .cache(1)
+            .build()
+            .await?;
+
+        // The node runs its first cycle right away and arms its timer afterwards, so simulated
+        // time must not move before that first motion command arrived.
+        let started_at = clock.now();
+        let behavior_node = tokio::spawn(behavior_node::node::run(context.clone()));
+        let last_motion_command = timeout(BEHAVIOR_NODE_TIMEOUT, motion_command.recv())
+            .await
+            .map_err(|_| eyre!("behavior_node did not start within {BEHAVIOR_NODE_TIMEOUT:?}"))??;
+        // Messages of the startup cycle were composed without any inputs.
+        while let Ok(Ok(_)) = timeout(Duration::ZERO, outgoing_messages.recv()).await {}
+
+        Ok(Self {
+            context,
+            _parameter_layer: parameter_layer,
+            behavior_node,
+            inputs,
+            motion_command,
+            outgoing_messages,
+            trace,
+            tree_layout,
+            started_at,
+            completed_cycles: 0,
+            last_motion_command,
+        })
+    }
+
+    async fn publish_inputs(
+        &self,
+        world_state: &WorldState,
+        player_state_times: &Players<Option<Time>>,
+        field_dimensions: &FieldDimensions,
+        game_controller_address: Option<SocketAddr>,
+    ) -> Result<()> {
+        let inputs = &self.inputs;
+
+        inputs.field_dimensions.publish(field_dimensions).await?;
+        inputs
+            .player_number
+            .publish(&world_state.robot.player_number)
+            .await?;
+        inputs
+            .primary_state
+            .publish(&world_state.robot.primary_state)
+            .await?;
+        if let Some(ground_to_field) = &world_state.robot.ground_to_field {
+            inputs.ground_to_field.publish(ground_to_field).await?;
+        }
+        inputs.ball_state.publish(&world_state.ball).await?;
+        inputs
+            .rule_ball_state
+            .publish(&world_state.rule_ball)
+            .await?;
+        if let Some(fall_down_state) = &world_state.fall_down_state {
+            inputs.fall_down_state.publish(fall_down_state).await?;
+        }
+        if let Some(filtered_game_controller_state) = &world_state.filtered_game_controller_state {
+            inputs
+                .filtered_game_controller_state
+                .publish(filtered_game_controller_state)
+                .await?;
+        }
+        inputs
+            .game_controller_address
+            .publish(&game_controller_address)
+            .await?;
+        inputs
+            .hypothetical_ball_positions
+            .publish(&world_state.hypothetical_ball_positions)
+            .await?;
+        inputs.obstacles.publish(&world_state.obstacles).await?;
+        inputs
+            .rule_obstacles
+            .publish(&world_state.rule_obstacles)
+            .await?;
+        inputs
+            .position_of_interest
+            .publish(&world_state.position_of_interest)
+            .await?;
+        if let Some(suggested_search_position) = &world_state.suggested_search_position {
+            inputs
+                .suggested_search_position
+                .publish(suggested_search_position)
+                .await?;
+        }
+        inputs
+            .search_state
+            .publish(&world_state.search_state)
+            .await?;
+        let mut player_states = Players::new(None);
+        for (player_number, player_state) in world_state.player_states.iter() {
+            player_states[player_number] = player_state.map(|inner| TimeWrapper {
+                time: player_state_times[player_number].unwrap_or(world_state.now),
+                inner,
+            });
+        }
+        inputs.player_states.publish(&player_states).await?;
+        inputs.world_state.publish(world_state).await?;
+        Ok(())
+    }
+
+    /// Waits for all cycles the node's timer is due for at `now`.
+    async fn run_due_cycles(&mut self, now: Time) -> Result<Vec<OutgoingMessage>> {
+        let due_cycles = now.duration_since(self.started_at).as_nanos() / CYCLE_TIME.as_nanos();
+        while self.completed_cycles < due_cycles {
+            if self.behavior_node.is_finished() {
+                return Err(eyre!("behavior_node exited"));
+            }
+            self.last_motion_command = timeout(BEHAVIOR_NODE_TIMEOUT, self.motion_command.recv())
+                .await
+                .map_err(|_| {
+                    eyre!("behavior_node did not complete a cycle within {BEHAVIOR_NODE_TIMEOUT:?}")
+                })??;
+            self.completed_cycles += 1;
+        }
+        let mut outgoing_messages = Vec::new();
+        while let Ok(Ok(message)) = timeout(Duration::ZERO, self.outgoing_messages.recv()).await {
+            outgoing_messages.push(message);
+        }
+        Ok(outgoing_messages)
+    }
+
+    fn frame(
+        &self,
+        world_state: WorldState,
+        outgoing_messages: Vec<OutgoingMessage>,
+    ) -> RobotFrame {
+        let trace = self
+            .trace
+            .get_latest()
+            .map(|trace| trace.as_ref().clone())
+            .unwrap_or_else(|| NodeTrace {
+                name: "behavior_node".to_string(),
+                status: Status::Idle,
+                children: Vec::new(),
+                role: None,
+            });
+        let static_layout = self
+            .tree_layout
+            .get_latest()
+            .map(|layout| layout.as_ref().clone())
+            .unwrap_or_else(|| trace.clone());
+        RobotFrame {
+            world_state,
+            motion_command: self.last_motion_command.clone(),
+            trace,
+            static_layout,
+            path_obstacles: Vec::new(),
+            time_since_last_switch: Duration::ZERO,
+            direction_difference: 0.0,
+            walk_position: None,
+            voronoi_map: None,
+            voronoi_inputs: Vec::new(),
+            outgoing_messages,
+        }
+    }
+}
+
+impl Drop for RobotNodeGraph {
+    fn drop(&mut self) {
+        self.behavior_node.abort();
+        let _ = self.context.shutdown();
+    }
+}
+
+/// Parameter layer only one robot's context reads from, removed again together with the robot.
+struct ParameterLayer {
+    path: PathBuf,
+}
+
+impl ParameterLayer {
+    /// Writes the parameters and behavior tree of a robot into its layer.
+    fn write(
+        robot_id: SimulatorRobotId,
+        parameters: &BehaviorParameters,
+        tree_description: &TreeDescription,
+    ) -> Result<Self> {
+        let path = temp_dir()
+            .join("bevyhavior_simulator")
+            .join(process::id().to_string())
+            .join(robot_id.to_string());
+        create_dir_all(&path)
+            .wrap_err_with(|| format!("failed to create parameter layer {}", path.display()))?;
+        let layer = Self { path };
+        let parameters = serde_json::to_string_pretty(parameters)
+            .wrap_err("failed to serialize behavior parameters")?;
+        write(layer.path.join("behavior_node.json5"), parameters)
+            .wrap_err("failed to write behavior parameters")?;
+        let tree_description = serde_json::to_string_pretty(tree_description)
+            .wrap_err("failed to serialize behavior tree")?;
+        write(layer.path.join(TREE_FILE_NAME), tree_description)
+            .wrap_err("failed to write behavior tree")?;
+        Ok(layer)
+    }
+}
+
+impl Drop for ParameterLayer {
+    fn drop(&mut self) {
+        let _ = remove_dir_all(&self.path);
+        // The directory of this process only goes away with its last robot.
+        if let Some(process_directory) = self.path.parent() {
+            let _ = remove_dir(process_directory);
+        }
+    }
+}
+
+/// Starts a node graph for every robot not running one yet. Robots controlled by an
+/// [`OpponentStrategy`](crate::behavior_tree_simulator::OpponentStrategy) stay in-process.
+pub fn spawn_ros_z_node_graphs(
+    mut commands: Commands,
+    clock: Res<SimulatorClock>,
+    mut node_graph: ResMut<SimulatorRosZNodeGraph>,
+    mut current_violations: ResMut<SimulatorCurrentInvariantViolations>,
+    mut scenario_result: ResMut<SimulatorScenarioResult>,
+    mut exit: MessageWriter<AppExit>,
+    robots: Query<
+        (
+            Entity,
+            &SimulatorRobot,
+            &SimulatorRobotParameters,
+            &SimulatorRobotBehavior,
+        ),
+        Without<SimulatorRosZRobot>,
+    >,
+) {
+    let node_graph = &mut *node_graph;
+    for (entity, robot, parameters, behavior) in &robots {
+        if behavior.opponent_strategy.is_some() {
+            continue;
+        }
+        let robot_id = robot.id();
+        match node_graph.runtime.block_on(RobotNodeGraph::spawn(
+            robot_id,
+            &parameters.behavior,
+            &behavior.tree_description,
+            node_graph.clock.clone(),
+            node_graph.router_endpoint.as_deref(),
+        )) {
+            Ok(robot_node_graph) => {
+                node_graph.robots.insert(robot_id, robot_node_graph);
+                commands.entity(entity).insert(SimulatorRosZRobot);
+            }
+            Err(error) => {
+                fail_scenario(
+                    &clock,
+                    robot_id,
+                    error.wrap_err("failed to start ros-z node graph"),
+                    &mut current_violations,
+                    &mut scenario_result,
+                    &mut exit,
+                );
+                return;
+            }
+        }
+    }
+}
+
+/// Forwards the HSL messages delivered in this cycle to `inputs/message` of their receivers.
+pub fn forward_incoming_messages_to_ros_z(
+    clock: Res<SimulatorClock>,
+    node_graph: Res<SimulatorRosZNodeGraph>,
+    incoming_messages: Res<SimulatorIncomingMessages>,
+    mut current_violations: ResMut<SimulatorCurrentInvariantViolations>,
+    mut scenario_result: ResMut<SimulatorScenarioResult>,
+    mut exit: MessageWriter<AppExit>,
+) {
+    for incoming_message in &incoming_messages.messages {
+        let Some(robot_node_graph) = node_graph.robots.get(&incoming_message.receiver) else {
+            continue;
+        };
+        let message = TimeWrapper {
+            time: Time::from_wallclock(incoming_message.received_at),
+            inner: incoming_message.message.clone(),
+        };
+        if let Err(error) = node_graph
+            .runtime
+            .block_on(robot_node_graph.inputs.incoming_message.publish(&message))
+        {
+            fail_scenario(
+                &clock,
+                incoming_message.receiver,
+                Report::new(error).wrap_err("failed to forward incoming message"),
+                &mut current_violations,
+                &mut scenario_result,
+                &mut exit,
+            );
+            return;
+        }
+    }
+}
+
+/// Publishes the world state of every ros-z robot, advances the shared logical clock and
+/// collects the resulting motion commands.
+pub fn tick_ros_z_node_graphs(
+    clock: Res<SimulatorClock>,
+    config: Res<SimulationConfig>,
+    field_dimensions: Res<SimulatorFieldDimensions>,
+    world_states: Res<SimulatorWorldStates>,
+    received_hsl_messages: Res<SimulatorReceivedHslMessages>,
+    mut node_graph: ResMut<SimulatorRosZNodeGraph>,
+    mut robot_frames: ResMut<SimulatorRobotFrames>,
+    mut current_violations: ResMut<SimulatorCurrentInvariantViolations>,
+    mut scenario_result: ResMut<SimulatorScenarioResult>,
+    mut exit: MessageWriter<AppExit>,
+    robots: Query<&SimulatorRobot, With<SimulatorRosZRobot>>,
+) {
+    let node_graph = &mut *node_graph;
+
+    for robot in &robots {
+        let (Some(robot_node_graph), Some(world_state)) = (
+            node_graph.robots.get(&robot.id()),
+            world_states.0.get(&robot.id()),
+        ) else {
+            continue;
+        };
+        if let Err(error) = node_graph.runtime.block_on(robot_node_graph.publish_inputs(
+            world_state,
+            &player_state_times_from_received_hsl_messages(robot.id(), &received_hsl_messages),
+            &field_dimensions.0,
+            config.game_controller_address,
+        )) {
+            fail_scenario(
+                &clock,
+                robot.id(),
+                error.wrap_err("failed to publish inputs"),
+                &mut current_violations,
+                &mut scenario_result,
+                &mut exit,
+            );
+            return;
+        }
+    }
+
+    let now = Time::from_wallclock(clock.now);
+    if let Err(error) = node_graph.clock.set_time(now) {
+        fail_node_graph(
+            &clock,
+            Report::new(error).wrap_err("failed to advance the simulator clock"),
+            &mut current_violations,
+            &mut scenario_result,
+            &mut exit,
+        );
+        return;
+    }
+
+    for robot in &robots {
+        let robot_id = robot.id();
+        let (Some(robot_node_graph), Some(world_state)) = (
+            node_graph.robots.get_mut(&robot_id),
+            world_states.0.get(&robot_id),
+        ) else {
+            continue;
+        };
+        match node_graph
+            .runtime
+            .block_on(robot_node_graph.run_due_cycles(now))
+        {
+            Ok(outgoing_messages) => {
+                robot_frames.0.insert(
+                    robot_id,
+                    robot_node_graph.frame(world_state.clone(), outgoing_messages),
+                );
+            }
+            Err(error) => {
+                fail_scenario(
+                    &clock,
+                    robot_id,
+                    error,
+                    &mut current_violations,
+                    &mut scenario_result,
+                    &mut exit,
+                );
+                return;
+            }
+        }
+    }
+}
+
+/// Hands the messages a ros-z robot sent in this cycle to the simulated network.
+pub fn collect_ros_z_outgoing_messages(
+    robot_frames: Res<SimulatorRobotFrames>,
+    mut outgoing_messages: ResMut<SimulatorOutgoingMessages>,
+    robots: Query<&SimulatorRobot, With<SimulatorRosZRobot>>,
+) {
+    for robot in &robots {
+        let robot_id = robot.id();
+        let Some(frame) = robot_frames.0.get(&robot_id) else {
+            continue;
+        };
+        outgoing_messages
+            .messages
+            .extend(
+                frame
+                    .outgoing_messages
+                    .iter()
+                    .cloned()
+                    .map(|message| SimulatorMessage {
+                        sender: robot_id,
+                        message,
+                    }),
+            );
+    }
+}
+
+/// Reports the failed setup of the node graph once and ends the scenario.
+pub fn report_ros_z_node_graph_setup_failure(
+    clock: Res<SimulatorClock>,
+    mut setup_failure: ResMut<SimulatorRosZNodeGraphSetupFailure>,
+    mut current_violations: ResMut<SimulatorCurrentInvariantViolations>,
+    mut scenario_result: ResMut<SimulatorScenarioResult>,
+    mut exit: MessageWriter<AppExit>,
+) {
+    let Some(error) = setup_failure.0.take() else {
+        return;
+    };
+    fail_node_graph(
+        &clock,
+        error.wrap_err("failed to set up ros-z node graph"),
+        &mut current_violations,
+        &mut scenario_result,
+        &mut exit,
+    );
+}
+
+fn fail_scenario(
+    clock: &SimulatorClock,
+    robot_id: SimulatorRobotId,
+    error: Report,
+    current_violations: &mut SimulatorCurrentInvariantViolations,
+    scenario_result: &mut SimulatorScenarioResult,
+    exit: &mut MessageWriter<AppExit>,
+) {
+    report_violation(
+        Some(robot_id.player_number),
+        behavior_tick_failure_message(robot_id, clock, &error),
+        current_violations,
+        scenario_result,
+        exit,
+    );
+}
+
+/// Like [`fail_scenario`] for errors of the node graph shared by all robots.
+fn fail_node_graph(
+    clock: &SimulatorClock,
+    error: Report,
+    current_violations: &mut SimulatorCurrentInvariantViolations,
+    scenario_result: &mut SimulatorScenarioResult,
+    exit: &mut MessageWriter<AppExit>,
+) {
+    report_violation(
+        None,
+        format!("ros-z node graph failed at {:?}: {error:#}", clock.now),
+        current_violations,
+        scenario_result,
+        exit,
+    );
+}
+
+fn report_violation(
+    player_number: Option<PlayerNumber>,
+    message: String,
+    current_violations: &mut SimulatorCurrentInvariantViolations,
+    scenario_result: &mut SimulatorScenarioResult,
+    exit: &mut MessageWriter<AppExit>,
+) {
+    scenario_result.failed = true;
+    current_violations.0.push(InvariantViolation {
+        check_name: BEHAVIOR_TICK_ERROR_CHECK_NAME.into(),
+        player_number,
+        message,
+        severity: InvariantSeverity::Error,
+    });
+    exit.write(AppExit::Success);
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::read_to_string;
+
+    use hsl_network_messages::Team;
+
+    use behavior_node::registry::default_registry;
+
+    use super::*;
+    use crate::behavior_tree_simulator::{default_behavior_parameters, default_behavior_tree};
+
+    #[test]
+    fn robots_get_separate_namespaces_and_parameter_layers() {
+        let hulk = SimulatorRobotId::new(Team::Hulks, PlayerNumber::Three);
+        let opponent = SimulatorRobotId::new(Team::Opponent, PlayerNumber::Three);
+        assert_ne!(robot_namespace(hulk), robot_namespace(opponent));
+
+        let parameters = default_behavior_parameters().expect("failed to load default parameters");
+        let tree_description = default_behavior_tree().expect("failed to load default tree");
+        let layer = ParameterLayer::write(hulk, &parameters, &tree_description)
+            .expect("failed to write layer");
+        let opponent_layer = ParameterLayer::write(opponent, &parameters, &tree_description)
+            .expect("failed to write layer");
+        assert_ne!(layer.path, opponent_layer.path);
+
+        let written: BehaviorParameters = json5::from_str(
+            &read_to_string(layer.path.join("behavior_node.json5")).expect("failed to read layer"),
+        )
+        .expect("failed to parse layer");
+        assert_eq!(
+            serde_json::to_value(written).unwrap(),
+            serde_json::to_value(&parameters).unwrap()
+        );
+
+        let written_tree =
+            TreeDescription::load(&layer.path.join(TREE_FILE_NAME)).expect("failed to load tree");
+        written_tree
+            .build(&default_registry())
+            .expect("failed to build written tree");
+
+        let path = layer.path.clone();
+        drop(layer);
+        assert!(!path.exists());
+        assert!(opponent_layer.path.exists());
+    }
+
+    #[test]
+    fn behavior_node_cycles_follow_simulated_time() {
+        let node_graph = SimulatorRosZNodeGraph::new(None).expect("failed to create node graph");
+        let robot_id = SimulatorRobotId::new(Team::Hulks, PlayerNumber::Five);
+        let parameters = default_behavior_parameters().expect("failed to load default parameters");
+        let tree_description = default_behavior_tree().expect("failed to load default tree");
+        let mut robot = node_graph
+            .runtime
+            .block_on(RobotNodeGraph::spawn(
+                robot_id,
+                &parameters,
+                &tree_description,
+                node_graph.clock.clone(),
+                None,
+            ))
+            .expect("failed to spawn behavior_node");
+        let world_state = WorldState::default();
+
+        node_graph
+            .runtime
+            .block_on(robot.publish_inputs(
+                &world_state,
+                &Players::new(None),
+                &FieldDimensions::SPL_2025,
+                None,
+            ))
+            .expect("failed to publish inputs");
+        let now = robot.started_at + CYCLE_TIME * 5 + CYCLE_TIME / 2;
+        node_graph.clock.set_time(now).unwrap();
+        node_graph
+            .runtime
+            .block_on(robot.run_due_cycles(now))
+            .expect("behavior_node did not cycle");
+
+        assert_eq!(robot.completed_cycles, 5);
+    }
+}
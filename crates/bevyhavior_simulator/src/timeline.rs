@@ -9,7 +9,7 @@ use bevy::prelude::*;
 use coordinate_systems::{Field, Ground};
 use eframe::egui::Color32;
 use linear_algebra::{Point2, Pose2};
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use types::{
     behavior_tree::NodeTrace, filtered_game_state::FilteredGameState, messages::OutgoingMessage,
     motion_command::MotionCommand, path_obstacles::PathObstacle, world_state::WorldState,
@@ -44,7 +44,7 @@ impl SimulatorTimelineMarkers {
     }
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct SimulatorTimelineMarker {
     pub frame_time: SystemTime,
     pub color: Color32,
@@ -57,7 +57,7 @@ pub struct SimulatorScenarioResult {
     pub failures: Vec<SimulatorFailure>,
 }
 
-#[derive(Clone, Debug, Serialize)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub enum SimulatorFailure {
     InvariantViolation(InvariantViolation),
 }
@@ -73,7 +73,7 @@ impl Display for SimulatorFailure {
 #[derive(Resource, Clone, Debug, Default)]
 pub struct SimulatorRobotFrames(pub BTreeMap<SimulatorRobotId, RobotFrame>);
 
-#[derive(Clone, Debug, Serialize)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct TimelineFrame {
     pub now: SystemTime,
     pub game_state: FilteredGameState,
@@ -84,7 +84,7 @@ pub struct TimelineFrame {
     pub invariant_violations: Vec<InvariantViolation>,
 }
 
-#[derive(Clone, Debug, Serialize)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct RobotFrame {
     pub world_state: WorldState,
     pub motion_command: MotionCommand,
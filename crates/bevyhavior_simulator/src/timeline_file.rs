@@ -0,0 +1,147 @@
+use std::{
+    fs::File,
+    io::{BufReader, BufWriter, Read, Write},
+    path::Path,
+};
+
+use color_eyre::{
+    Result,
+    eyre::{WrapErr, bail},
+};
+
+use crate::timeline_viewer::TimelineViewerData;
+
+const TIMELINE_FILE_MAGIC: &[u8; 8] = b"HULKSBTS";
+const TIMELINE_FILE_VERSION: u32 = 1;
+
+/// Environment variable naming a directory into which every finished scenario writes
+/// `<scenario>.timeline`.
+pub const TIMELINE_EXPORT_DIRECTORY_VARIABLE: &str = "BEVYHAVIOR_SIMULATOR_TIMELINE_DIRECTORY";
+
+pub const TIMELINE_FILE_EXTENSION: &str = "timeline";
+
+pub fn save_timeline(path: impl AsRef<Path>, data: &TimelineViewerData) -> Result<()> {
+    let path = path.as_ref();
+    let file = File::create(path)
+        .wrap_err_with(|| format!("failed to create timeline file {}", path.display()))?;
+    let mut writer = BufWriter::new(file);
+    writer.write_all(TIMELINE_FILE_MAGIC)?;
+    writer.write_all(&TIMELINE_FILE_VERSION.to_le_bytes())?;
+    bincode::serialize_into(&mut writer, data)
+        .wrap_err_with(|| format!("failed to serialize timeline to {}", path.display()))?;
+    writer.flush()?;
+    Ok(())
+}
+
+pub fn load_timeline(path: impl AsRef<Path>) -> Result<TimelineViewerData> {
+    let path = path.as_ref();
+    let file = File::open(path)
+        .wrap_err_with(|| format!("failed to open timeline file {}", path.display()))?;
+    let mut reader = BufReader::new(file);
+
+    let mut magic = [0; TIMELINE_FILE_MAGIC.len()];
+    reader
+        .read_exact(&mut magic)
+        .wrap_err("failed to read timeline file header")?;
+    if &magic != TIMELINE_FILE_MAGIC {
+        bail!("{} is not a simulator timeline file", path.display());
+    }
+    let mut version = [0; 4];
+    reader
+        .read_exact(&mut version)
+        .wrap_err("failed to read timeline file version")?;
+    let version = u32::from_le_bytes(version);
+    if version != TIMELINE_FILE_VERSION {
+        bail!(
+            "unsupported timeline file version {version} in {}, expected {TIMELINE_FILE_VERSION}",
+            path.display()
+        );
+    }
+
+    bincode::deserialize_from(reader)
+        .wrap_err_with(|| format!("failed to deserialize timeline from {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::BTreeMap, env::temp_dir, time::SystemTime};
+
+    use hsl_network_messages::{PlayerNumber, Team};
+    use types::{
+        behavior_tree::{NodeTrace, Status},
+        field_dimensions::FieldDimensions,
+        filtered_game_state::FilteredGameState,
+        world_state::WorldState,
+    };
+
+    use super::*;
+    use crate::behavior_tree_simulator::{
+        InvariantSeverity, InvariantViolation, RobotFrame, SimulationConfig, SimulatorFailure,
+        SimulatorRobotId, TimelineFrame,
+    };
+
+    #[test]
+    fn saved_timeline_round_trips() {
+        let robot_id = SimulatorRobotId::new(Team::Opponent, PlayerNumber::Four);
+        let violation = InvariantViolation {
+            check_name: "field_boundary_walk".into(),
+            player_number: Some(PlayerNumber::Four),
+            message: "left the field".to_string(),
+            severity: InvariantSeverity::Warning,
+        };
+        let trace = NodeTrace {
+            name: "root".to_string(),
+            status: Status::Success,
+            children: Vec::new(),
+            role: None,
+        };
+        let data = TimelineViewerData {
+            field_dimensions: FieldDimensions::SPL_2025,
+            config: SimulationConfig::default(),
+            frames: vec![TimelineFrame {
+                now: SystemTime::UNIX_EPOCH,
+                game_state: FilteredGameState::Initial,
+                ball: None,
+                scenario_obstacles: Vec::new(),
+                robots: BTreeMap::new(),
+                robot_frames: BTreeMap::from([(
+                    robot_id,
+                    RobotFrame {
+                        world_state: WorldState::default(),
+                        motion_command: Default::default(),
+                        trace: trace.clone(),
+                        static_layout: trace,
+                        path_obstacles: Vec::new(),
+                        time_since_last_switch: Default::default(),
+                        direction_difference: 0.0,
+                        walk_position: None,
+                        voronoi_map: None,
+                        voronoi_inputs: Vec::new(),
+                        outgoing_messages: Vec::new(),
+                    },
+                )]),
+                invariant_violations: vec![violation.clone()],
+            }],
+            markers: Vec::new(),
+            failures: vec![SimulatorFailure::InvariantViolation(violation)],
+        };
+        let path = temp_dir().join(format!(
+            "bevyhavior_simulator_round_trip_{}.{TIMELINE_FILE_EXTENSION}",
+            std::process::id()
+        ));
+
+        save_timeline(&path, &data).expect("failed to save timeline");
+        let loaded = load_timeline(&path).expect("failed to load timeline");
+        std::fs::remove_file(&path).expect("failed to remove timeline file");
+
+        let [frame] = loaded.frames.as_slice() else {
+            panic!("expected exactly one frame");
+        };
+        assert_eq!(frame.robot_frames[&robot_id].trace.name, "root");
+        assert_eq!(
+            frame.invariant_violations[0].check_name,
+            "field_boundary_walk"
+        );
+        assert_eq!(loaded.failures.len(), 1);
+    }
+}
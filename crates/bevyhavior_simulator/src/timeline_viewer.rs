@@ -18,6 +18,7 @@ use eframe::{
 use egui_dock::{DockArea, DockState, Node, Split, TabViewer};
 use hsl_network_messages::{PlayerNumber, Team};
 use linear_algebra::{Orientation2, Pose2, point, vector};
+use serde::{Deserialize, Serialize};
 use serde_json::{Value, json};
 use twix_legacy::{
     behavior_tree::BehaviorTreeVisualizer,
@@ -42,7 +43,7 @@ const MAX_PLAYBACK_SPEED: f32 = 256.0;
 const WALK_PATH_LINE_COLOR: Color32 = Color32::from_rgba_premultiplied(0, 0, 202, 150);
 const WALK_PATH_ARC_COLOR: Color32 = Color32::from_rgba_premultiplied(136, 170, 182, 150);
 
-#[derive(Debug)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct TimelineViewerData {
     pub field_dimensions: FieldDimensions,
     pub config: SimulationConfig,
@@ -1103,6 +1104,7 @@ fn motion_name(motion_command: &MotionCommand) -> &'static str {
         MotionCommand::Prepare => "prepare",
         MotionCommand::Stand { .. } => "stand",
         MotionCommand::StandUp => "stand_up",
+        MotionCommand::KeyframeMotion { .. } => "keyframe_motion",
         MotionCommand::VisualKick { .. } => "visual_kick",
         MotionCommand::Walk { .. } => "walk",
         MotionCommand::WalkWithVelocity { .. } => "walk_with_velocity",
@@ -1,10 +1,14 @@
-use std::{collections::BTreeMap, time::SystemTime};
+use std::{
+    collections::BTreeMap,
+    time::{Duration, SystemTime},
+};
 
 use bevy::prelude::*;
-use coordinate_systems::{Ground, World};
+use coordinate_systems::{Field, Ground, World};
+use hsl_network_messages::{SEARCH_GRID_LENGTH, SEARCH_GRID_WIDTH, SearchState};
 use linear_algebra::{Isometry2, Orientation2, Point2, Vector2, point};
 use types::{
-    field_dimensions::GlobalFieldSide,
+    field_dimensions::{FieldDimensions, GlobalFieldSide},
     rule_obstacles::RuleObstacle,
     world_state::{BallState, RobotState, WorldState},
 };
@@ -12,18 +16,24 @@ use types::{
 use crate::{
     behavior_tree_simulator::{
         SimulatedBall, SimulationConfig, SimulatorBall, SimulatorClock, SimulatorFallDownState,
-        SimulatorGameState, SimulatorGroundToWorld, SimulatorHeadYaw, SimulatorObstacle,
-        SimulatorPrimaryState, SimulatorReceivedHslMessages, SimulatorRobot, SimulatorRobotId,
-        SimulatorRuleObstacles, SimulatorScenarioObstacles, SimulatorSuggestedSearchPosition,
+        SimulatorFieldDimensions, SimulatorGameState, SimulatorGroundToWorld, SimulatorHeadYaw,
+        SimulatorObstacle, SimulatorPrimaryState, SimulatorReceivedHslMessages, SimulatorRobot,
+        SimulatorRobotId, SimulatorRuleObstacles, SimulatorScenarioObstacles,
+        SimulatorSuggestedSearchPosition,
     },
     communication::player_states_from_received_hsl_messages,
     coordinates::{ground_to_field_from_world, world_to_field_transform},
     game_controller::{filtered_game_controller_state_for_team, global_field_side_for_team},
 };
 
+/// How long a search cell counts as observed, like `observed_cell_memory` of the search suggestor
+const OBSERVED_CELL_MEMORY: Duration = Duration::from_secs(10);
+
 #[derive(Resource, Clone, Debug, Default)]
 pub struct SimulatorWorldStates(pub BTreeMap<SimulatorRobotId, WorldState>);
 
+type LastObservedSearchCells = BTreeMap<(usize, usize), SystemTime>;
+
 pub fn build_world_states(
     clock: Res<SimulatorClock>,
     ball: Res<SimulatorBall>,
@@ -32,6 +42,7 @@ pub fn build_world_states(
     rule_obstacles: Res<SimulatorRuleObstacles>,
     scenario_obstacles: Res<SimulatorScenarioObstacles>,
     config: Res<SimulationConfig>,
+    field_dimensions: Res<SimulatorFieldDimensions>,
     robots: Query<(
         &SimulatorRobot,
         &SimulatorGroundToWorld,
@@ -41,6 +52,7 @@ pub fn build_world_states(
         &SimulatorSuggestedSearchPosition,
     )>,
     mut world_states: ResMut<SimulatorWorldStates>,
+    mut last_observed_search_cells: Local<BTreeMap<SimulatorRobotId, LastObservedSearchCells>>,
 ) {
     world_states.0.clear();
     let canonical_global_field_side = game_state.game_controller_state.global_field_side;
@@ -125,6 +137,17 @@ pub fn build_world_states(
                 rule_obstacles,
                 fall_down_state: fall_down_state.fall_down_state,
                 suggested_search_position: suggested_search_position.position,
+                search_state: SearchState {
+                    observed_cells: observed_search_cells(
+                        last_observed_search_cells.entry(robot_id).or_default(),
+                        clock.now,
+                        &field_dimensions.0,
+                        ground_to_field,
+                        head_yaw.yaw,
+                        &config,
+                    ),
+                    search_position: suggested_search_position.position,
+                },
             },
         );
     }
@@ -200,6 +223,41 @@ fn perceived_robot_obstacles_from_pose(
         .collect()
 }
 
+/// Marks the cells of the shared search grid whose center the robot currently sees, returns the
+/// cells seen within [`OBSERVED_CELL_MEMORY`].
+fn observed_search_cells(
+    last_observed: &mut LastObservedSearchCells,
+    now: SystemTime,
+    field_dimensions: &FieldDimensions,
+    ground_to_field: Isometry2<Ground, Field>,
+    head_yaw: Orientation2<Ground>,
+    config: &SimulationConfig,
+) -> u64 {
+    let field_to_ground = ground_to_field.inverse();
+    for x in 0..SEARCH_GRID_LENGTH {
+        for y in 0..SEARCH_GRID_WIDTH {
+            let cell_center: Point2<Field> = point![
+                ((x as f32 + 0.5) / SEARCH_GRID_LENGTH as f32 - 0.5) * field_dimensions.length,
+                ((y as f32 + 0.5) / SEARCH_GRID_WIDTH as f32 - 0.5) * field_dimensions.width
+            ];
+            if is_visible_from_head(field_to_ground * cell_center, head_yaw, config) {
+                last_observed.insert((x, y), now);
+            }
+        }
+    }
+
+    let mut search_state = SearchState::default();
+    for (cell, time) in last_observed.iter() {
+        if now
+            .duration_since(*time)
+            .is_ok_and(|age| age < OBSERVED_CELL_MEMORY)
+        {
+            search_state.set_observed(*cell);
+        }
+    }
+    search_state.observed_cells
+}
+
 fn is_visible_from_head(
     position_in_ground: Point2<Ground>,
     head_yaw: Orientation2<Ground>,
@@ -260,6 +318,7 @@ mod tests {
                 age: Duration::from_millis(500),
                 position: point![x + 1.0, y],
             }),
+            search_state: Default::default(),
         })
     }
 
@@ -306,6 +365,65 @@ mod tests {
         );
     }
 
+    #[test]
+    fn observed_search_cells_are_remembered_for_a_while() {
+        let field_dimensions = FieldDimensions::SPL_2025;
+        let config = SimulationConfig::default();
+        let mut last_observed = BTreeMap::new();
+        let seen = SystemTime::UNIX_EPOCH + Duration::from_secs(1);
+        let cell_in_front = SearchState::cell_at(
+            point![1.0, 0.2],
+            field_dimensions.length,
+            field_dimensions.width,
+        );
+        let cell_behind = SearchState::cell_at(
+            point![-1.0, 0.2],
+            field_dimensions.length,
+            field_dimensions.width,
+        );
+
+        let looking_forward = SearchState {
+            observed_cells: observed_search_cells(
+                &mut last_observed,
+                seen,
+                &field_dimensions,
+                Isometry2::identity(),
+                Orientation2::identity(),
+                &config,
+            ),
+            search_position: None,
+        };
+        let looking_back = SearchState {
+            observed_cells: observed_search_cells(
+                &mut last_observed,
+                seen + Duration::from_secs(1),
+                &field_dimensions,
+                Isometry2::identity(),
+                Orientation2::new(PI),
+                &config,
+            ),
+            search_position: None,
+        };
+        let looking_sideways_much_later = SearchState {
+            observed_cells: observed_search_cells(
+                &mut last_observed,
+                seen + OBSERVED_CELL_MEMORY + Duration::from_secs(2),
+                &field_dimensions,
+                Isometry2::from_parts(vector![0.0, 0.0], -FRAC_PI_2),
+                Orientation2::identity(),
+                &config,
+            ),
+            search_position: None,
+        };
+
+        assert!(looking_forward.is_observed(cell_in_front));
+        assert!(!looking_forward.is_observed(cell_behind));
+        assert!(looking_back.is_observed(cell_in_front));
+        assert!(looking_back.is_observed(cell_behind));
+        assert!(!looking_sideways_much_later.is_observed(cell_in_front));
+        assert!(!looking_sideways_much_later.is_observed(cell_behind));
+    }
+
     #[test]
     fn world_states_generate_visible_robot_obstacles() {
         let mut app = App::new();
@@ -414,6 +532,7 @@ mod tests {
                         ..Default::default()
                     },
                 )]),
+                ..Default::default()
             })
             .insert_resource(SimulatorRuleObstacles::default())
             .insert_resource(SimulatorScenarioObstacles::default())
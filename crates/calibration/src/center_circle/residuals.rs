@@ -25,7 +25,7 @@ impl CalculateResiduals for CenterCircleResiduals {
     ) -> Result<Self, Self::Error> {
         let corrected = get_corrected_camera_matrix(&measurement.matrix, parameters);
 
-        let radius_squared = field_dimensions.center_circle_diameter / 2.0;
+        let radius_squared = (field_dimensions.center_circle_diameter / 2.0).powi(2);
 
         let projected_center = corrected.pixel_to_ground(measurement.circle_and_points.center)?;
         let projected_points: Vec<Point2<Ground>> = measurement
@@ -60,6 +60,7 @@ impl LineType {
     }
 }
 
+#[derive(Clone, Debug)]
 pub struct Measurement<Frame> {
     pub line_type: LineType,
     pub line_segment: LineSegment<Frame>,
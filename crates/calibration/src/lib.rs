@@ -11,14 +11,16 @@ pub mod corrections;
 pub mod goal_and_penalty_box;
 pub mod goal_box;
 pub mod jacobian;
+pub mod measurement;
 pub mod problem;
 pub mod residuals;
 
+/// Returns `None` if the optimization did not converge.
 pub fn solve<MeasurementResidualsType>(
     initial_corrections: Corrections,
     measurements: Vec<MeasurementResidualsType::Measurement>,
     field_dimensions: FieldDimensions,
-) -> Corrections
+) -> Option<Corrections>
 where
     MeasurementResidualsType: CalculateResiduals,
     Vec<f32>: From<MeasurementResidualsType>,
@@ -29,8 +31,8 @@ where
         field_dimensions,
     );
     let (result, report) = LevenbergMarquardt::new().minimize(problem);
-    println!("Report: {report:?}");
-    let corrections = result.get_corrections();
-    println!("Corrections: {corrections:?}");
-    corrections
+    report
+        .termination
+        .was_successful()
+        .then(|| result.get_corrections())
 }
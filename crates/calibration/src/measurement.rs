@@ -0,0 +1,129 @@
+use coordinate_systems::Pixel;
+use projection::Error as ProjectionError;
+use types::field_dimensions::FieldDimensions;
+
+use crate::{
+    center_circle::{
+        measurement::Measurement as CenterCircleMeasurement, residuals::CenterCircleResiduals,
+    },
+    corrections::Corrections,
+    goal_and_penalty_box::{Measurement as LineMeasurement, Residuals as LineResiduals},
+    residuals::CalculateResiduals,
+};
+
+/// Allows solving for the corrections with center circle and line measurements at once.
+#[derive(Clone, Debug)]
+pub enum Measurement {
+    CenterCircle(CenterCircleMeasurement),
+    Line(LineMeasurement<Pixel>),
+}
+
+pub struct Residuals {
+    residuals: Vec<f32>,
+}
+
+impl CalculateResiduals for Residuals {
+    type Error = ProjectionError;
+    type Measurement = Measurement;
+
+    fn calculate_from(
+        parameters: &Corrections,
+        measurement: &Self::Measurement,
+        field_dimensions: &FieldDimensions,
+    ) -> Result<Self, Self::Error> {
+        let residuals = match measurement {
+            Measurement::CenterCircle(measurement) => {
+                CenterCircleResiduals::calculate_from(parameters, measurement, field_dimensions)?
+                    .into()
+            }
+            Measurement::Line(measurement) => {
+                LineResiduals::calculate_from(parameters, measurement, field_dimensions)?.into()
+            }
+        };
+
+        Ok(Self { residuals })
+    }
+}
+
+impl From<Residuals> for Vec<f32> {
+    fn from(residuals: Residuals) -> Self {
+        residuals.residuals
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::{FRAC_PI_2, FRAC_PI_4, TAU};
+
+    use approx::assert_abs_diff_eq;
+    use coordinate_systems::{Camera, Head};
+    use linear_algebra::{IntoTransform, Isometry2, Isometry3, point, vector};
+    use projection::{Projection, camera_matrix::CameraMatrix};
+    use types::field_dimensions::Half;
+
+    use crate::{center_circle::circle_points::CenterCirclePoints, goal_and_penalty_box::LineType};
+
+    use super::*;
+
+    fn camera_matrix() -> CameraMatrix {
+        let head_to_camera: Isometry3<Head, Camera> =
+            (nalgebra::Isometry3::rotation(nalgebra::Vector3::x() * FRAC_PI_4)
+                * nalgebra::Isometry3::rotation(nalgebra::Vector3::y() * -FRAC_PI_2)
+                * nalgebra::Isometry3::rotation(nalgebra::Vector3::x() * FRAC_PI_2))
+            .framed_transform();
+        CameraMatrix::from_normalized_focal_and_center(
+            nalgebra::vector![0.6, 0.8],
+            nalgebra::point![0.5, 0.5],
+            vector![640.0, 480.0],
+            Isometry3::from_translation(0.0, 0.0, -1.0),
+            Isometry3::identity(),
+            head_to_camera,
+        )
+    }
+
+    #[test]
+    fn measurements_of_a_calibrated_camera_have_no_residuals() {
+        let camera_matrix = camera_matrix();
+        let field_dimensions = FieldDimensions::SPL_2025;
+        let radius = field_dimensions.center_circle_diameter / 2.0;
+        let center = point![1.5, 0.0];
+        let circle_points = (0..8)
+            .map(|index| {
+                let angle = index as f32 / 8.0 * TAU;
+                camera_matrix
+                    .ground_to_pixel(center + vector![angle.cos(), angle.sin()] * radius)
+                    .unwrap()
+            })
+            .collect();
+        let center_circle = Measurement::CenterCircle(CenterCircleMeasurement {
+            matrix: camera_matrix.clone(),
+            circle_and_points: CenterCirclePoints {
+                center: camera_matrix.ground_to_pixel(center).unwrap(),
+                points: circle_points,
+            },
+        });
+
+        let field_to_ground = Isometry2::from_parts(vector![-2.5, 0.0], 0.0);
+        let line_segment = LineType::FrontPenaltyArea
+            .line_segment(&field_dimensions, Half::Opponent)
+            .try_map(|point| camera_matrix.ground_to_pixel(field_to_ground * point))
+            .unwrap();
+        let line = Measurement::Line(LineMeasurement {
+            line_type: LineType::FrontPenaltyArea,
+            line_segment,
+            camera_matrix,
+            field_to_ground,
+        });
+
+        for measurement in [center_circle, line] {
+            let residuals: Vec<f32> =
+                Residuals::calculate_from(&Corrections::default(), &measurement, &field_dimensions)
+                    .unwrap()
+                    .into();
+            assert!(!residuals.is_empty());
+            for residual in residuals {
+                assert_abs_diff_eq!(residual, 0.0, epsilon = 1e-3);
+            }
+        }
+    }
+}
@@ -3,6 +3,7 @@ use std::{
     net::{Ipv4Addr as Ipv4AddrStd, SocketAddr, SocketAddrV4},
 };
 
+use hsl_network_messages::codec;
 use log::warn;
 use ros_z::Message;
 use serde::{Deserialize, Serialize};
@@ -48,6 +49,10 @@ impl Endpoint {
         })
     }
 
+    pub fn game_controller_state_address(&self) -> io::Result<SocketAddr> {
+        self.game_controller_state_socket.local_addr()
+    }
+
     pub async fn read(&self) -> Result<IncomingMessage, Error> {
         loop {
             let mut game_controller_state_buffer = [0; 1024];
@@ -67,7 +72,7 @@ impl Endpoint {
                 },
                 result = self.hsl_socket.recv_from(&mut hsl_buffer) => {
                     let (received_bytes, _address) = result.map_err(Error::ReadError)?;
-                    match bincode::deserialize(&hsl_buffer[0..received_bytes]) {
+                    match codec::decode(&hsl_buffer[0..received_bytes]) {
                         Ok(parsed_message) => {
                             break Ok(IncomingMessage::Hsl(parsed_message));
                         }
@@ -88,7 +93,7 @@ impl Endpoint {
                 self.send_game_controller_visual_referee_message(destination, message)
                     .await;
             }
-            OutgoingMessage::Hsl(message) => match bincode::serialize(&message) {
+            OutgoingMessage::Hsl(message) => match codec::encode(&message) {
                 Ok(message) => {
                     let ipv4_addr: Ipv4AddrStd = self.ports.hsl_broadcast_address.into();
 
@@ -104,7 +109,7 @@ impl Endpoint {
                     }
                 }
                 Err(error) => {
-                    warn!("Failed to serialize Hulk Message: {error:?}")
+                    warn!("Failed to encode Hulk Message: {error:?}")
                 }
             },
         };
@@ -130,15 +135,15 @@ impl Endpoint {
 
 #[derive(Clone, Debug, Serialize, Deserialize, Message)]
 pub struct Ports {
-    game_controller_state: u16,
-    game_controller_return: u16,
-    hsl: u16,
-    hsl_broadcast_address: Ipv4Addr,
+    pub game_controller_state: u16,
+    pub game_controller_return: u16,
+    pub hsl: u16,
+    pub hsl_broadcast_address: Ipv4Addr,
 }
 
 #[derive(Clone, Copy, Debug, Serialize, Deserialize, Message)]
 pub struct Ipv4Addr {
-    octets: [u8; 4],
+    pub octets: [u8; 4],
 }
 
 impl From<Ipv4Addr> for Ipv4AddrStd {
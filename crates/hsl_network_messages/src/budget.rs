@@ -0,0 +1,96 @@
+//! Planning when to spend the limited number of team messages of a game.
+
+use std::time::Duration;
+
+/// Importance of a team message, more important messages are sent sooner after the last one.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum MessagePriority {
+    /// Periodic update without news
+    Routine,
+    /// News teammates should learn soon, e.g. a role change
+    Important,
+    /// News teammates should learn immediately, e.g. the ball was seen after being lost
+    Urgent,
+}
+
+/// Spreads the messages remaining for the team evenly over the remaining game time.
+///
+/// The budget is shared by all players which are still sending. Important and urgent messages
+/// are sent earlier, which stretches the interval of the following routine messages.
+#[derive(Clone, Debug)]
+pub struct MessageBudgetPlanner {
+    pub minimum_interval: Duration,
+    pub reserved_messages: u16,
+}
+
+impl MessageBudgetPlanner {
+    /// Time to wait after the last message before sending one of `priority`, `None` once only
+    /// the reserved messages are left.
+    pub fn send_interval(
+        &self,
+        remaining_messages: u16,
+        remaining_time: Duration,
+        sending_players: usize,
+        priority: MessagePriority,
+    ) -> Option<Duration> {
+        let spendable_messages = remaining_messages
+            .checked_sub(self.reserved_messages)
+            .filter(|messages| *messages > 0)?;
+        let budget_interval =
+            remaining_time * sending_players.max(1) as u32 / u32::from(spendable_messages);
+        let interval = match priority {
+            MessagePriority::Routine => budget_interval,
+            MessagePriority::Important => budget_interval / 4,
+            MessagePriority::Urgent => Duration::ZERO,
+        };
+        Some(interval.max(self.minimum_interval))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn planner() -> MessageBudgetPlanner {
+        MessageBudgetPlanner {
+            minimum_interval: Duration::from_secs(1),
+            reserved_messages: 20,
+        }
+    }
+
+    #[test]
+    fn routine_messages_spread_the_budget_over_the_remaining_time() {
+        let interval =
+            planner().send_interval(1220, Duration::from_secs(1200), 5, MessagePriority::Routine);
+
+        assert_eq!(interval, Some(Duration::from_secs(5)));
+    }
+
+    #[test]
+    fn more_important_messages_are_sent_sooner() {
+        let planner = planner();
+        let interval =
+            |priority| planner.send_interval(220, Duration::from_secs(1200), 5, priority);
+
+        assert_eq!(
+            interval(MessagePriority::Routine),
+            Some(Duration::from_secs(30))
+        );
+        assert_eq!(
+            interval(MessagePriority::Important),
+            Some(Duration::from_millis(7500))
+        );
+        assert_eq!(
+            interval(MessagePriority::Urgent),
+            Some(Duration::from_secs(1))
+        );
+    }
+
+    #[test]
+    fn reserved_messages_are_not_spent() {
+        let interval =
+            planner().send_interval(20, Duration::from_secs(600), 1, MessagePriority::Urgent);
+
+        assert_eq!(interval, None);
+    }
+}
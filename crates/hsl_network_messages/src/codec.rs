@@ -0,0 +1,374 @@
+//! Bit-packed encoding of [`HulkMessage`]s sent between teammates.
+//!
+//! Every packet starts with the [`PROTOCOL_VERSION`] byte followed by the message kind. Fields
+//! are quantized: positions in centimeters, orientations in degrees and ball ages in steps of
+//! 100 ms. Values outside of the representable range are saturated.
+
+use std::time::Duration;
+
+use color_eyre::{Result, eyre::bail};
+use coordinate_systems::Field;
+use linear_algebra::{Point2, Pose2, point};
+
+use crate::{
+    BallPosition, HulkMessage, PlayerNumber, SEARCH_GRID_LENGTH, SEARCH_GRID_WIDTH, SearchState,
+    StateMessage, Team, VisualRefereeMessage,
+};
+
+pub const PROTOCOL_VERSION: u8 = 1;
+pub const MAXIMUM_PACKET_SIZE: usize = 128;
+
+const MESSAGE_KIND_BITS: u32 = 2;
+const STATE_MESSAGE_KIND: u64 = 0;
+const VISUAL_REFEREE_MESSAGE_KIND: u64 = 1;
+
+const PLAYER_NUMBER_BITS: u32 = 3;
+const TEAM_BITS: u32 = 2;
+const POSITION_BITS: u32 = 12;
+const CENTIMETERS_PER_METER: f32 = 100.0;
+const ORIENTATION_BITS: u32 = 9;
+const BALL_AGE_BITS: u32 = 8;
+const BALL_AGE_STEP: Duration = Duration::from_millis(100);
+const SEARCH_GRID_BITS: u32 = (SEARCH_GRID_LENGTH * SEARCH_GRID_WIDTH) as u32;
+
+pub fn encode(message: &HulkMessage) -> Result<Vec<u8>> {
+    let mut writer = BitWriter::default();
+    writer.write(PROTOCOL_VERSION.into(), u8::BITS);
+    match message {
+        HulkMessage::State(message) => {
+            writer.write(STATE_MESSAGE_KIND, MESSAGE_KIND_BITS);
+            writer.write_player_number(message.player_number);
+            writer.write_pose(message.pose);
+            writer.write(message.ball_position.is_some().into(), 1);
+            if let Some(ball_position) = message.ball_position {
+                writer.write_position(ball_position.position);
+                writer.write_ball_age(ball_position.age);
+            }
+            writer.write(message.search_state.observed_cells, SEARCH_GRID_BITS);
+            writer.write(message.search_state.search_position.is_some().into(), 1);
+            if let Some(search_position) = message.search_state.search_position {
+                writer.write_position(search_position);
+            }
+        }
+        HulkMessage::VisualReferee(message) => {
+            writer.write(VISUAL_REFEREE_MESSAGE_KIND, MESSAGE_KIND_BITS);
+            writer.write_player_number(message.player_number);
+            writer.write(
+                match message.kicking_team {
+                    None => 0,
+                    Some(Team::Hulks) => 1,
+                    Some(Team::Opponent) => 2,
+                },
+                TEAM_BITS,
+            );
+        }
+    }
+
+    let packet = writer.bytes;
+    if packet.len() > MAXIMUM_PACKET_SIZE {
+        bail!(
+            "encoded message has {} bytes, at most {MAXIMUM_PACKET_SIZE} are allowed",
+            packet.len()
+        );
+    }
+    Ok(packet)
+}
+
+pub fn decode(packet: &[u8]) -> Result<HulkMessage> {
+    let mut reader = BitReader::new(packet);
+    let version = reader.read(u8::BITS)?;
+    if version != u64::from(PROTOCOL_VERSION) {
+        bail!("unsupported protocol version {version}, expected {PROTOCOL_VERSION}");
+    }
+    let message = match reader.read(MESSAGE_KIND_BITS)? {
+        STATE_MESSAGE_KIND => {
+            let player_number = reader.read_player_number()?;
+            let pose = reader.read_pose()?;
+            let ball_position = if reader.read_bool()? {
+                Some(BallPosition {
+                    position: reader.read_position()?,
+                    age: reader.read_ball_age()?,
+                })
+            } else {
+                None
+            };
+            let observed_cells = reader.read(SEARCH_GRID_BITS)?;
+            let search_position = if reader.read_bool()? {
+                Some(reader.read_position()?)
+            } else {
+                None
+            };
+            HulkMessage::State(StateMessage {
+                player_number,
+                pose,
+                ball_position,
+                search_state: SearchState {
+                    observed_cells,
+                    search_position,
+                },
+            })
+        }
+        VISUAL_REFEREE_MESSAGE_KIND => {
+            let player_number = reader.read_player_number()?;
+            let kicking_team = match reader.read(TEAM_BITS)? {
+                0 => None,
+                1 => Some(Team::Hulks),
+                2 => Some(Team::Opponent),
+                team => bail!("unexpected kicking team {team}"),
+            };
+            HulkMessage::VisualReferee(VisualRefereeMessage {
+                player_number,
+                kicking_team,
+            })
+        }
+        kind => bail!("unexpected message kind {kind}"),
+    };
+    Ok(message)
+}
+
+/// Writes values most significant bit first.
+#[derive(Default)]
+struct BitWriter {
+    bytes: Vec<u8>,
+    length_in_bits: usize,
+}
+
+impl BitWriter {
+    fn write(&mut self, value: u64, bits: u32) {
+        for bit in (0..bits).rev() {
+            if self.length_in_bits % 8 == 0 {
+                self.bytes.push(0);
+            }
+            if (value >> bit) & 1 == 1 {
+                let byte = self.bytes.last_mut().expect("a byte was pushed above");
+                *byte |= 0x80 >> (self.length_in_bits % 8);
+            }
+            self.length_in_bits += 1;
+        }
+    }
+
+    fn write_signed(&mut self, value: i64, bits: u32) {
+        let maximum = (1 << (bits - 1)) - 1;
+        let clamped = value.clamp(-maximum - 1, maximum);
+        self.write(clamped as u64 & ((1 << bits) - 1), bits);
+    }
+
+    fn write_player_number(&mut self, player_number: PlayerNumber) {
+        let index = match player_number {
+            PlayerNumber::One => 0,
+            PlayerNumber::Two => 1,
+            PlayerNumber::Three => 2,
+            PlayerNumber::Four => 3,
+            PlayerNumber::Five => 4,
+        };
+        self.write(index, PLAYER_NUMBER_BITS);
+    }
+
+    fn write_position(&mut self, position: Point2<Field>) {
+        for coordinate in [position.x(), position.y()] {
+            self.write_signed(
+                (coordinate * CENTIMETERS_PER_METER).round() as i64,
+                POSITION_BITS,
+            );
+        }
+    }
+
+    fn write_pose(&mut self, pose: Pose2<Field>) {
+        self.write_position(pose.position());
+        let degrees = pose
+            .orientation()
+            .angle()
+            .to_degrees()
+            .round()
+            .rem_euclid(360.0);
+        self.write(degrees as u64 % 360, ORIENTATION_BITS);
+    }
+
+    fn write_ball_age(&mut self, age: Duration) {
+        let steps = age.as_millis() / BALL_AGE_STEP.as_millis();
+        self.write(steps.min((1 << BALL_AGE_BITS) - 1) as u64, BALL_AGE_BITS);
+    }
+}
+
+struct BitReader<'packet> {
+    packet: &'packet [u8],
+    position_in_bits: usize,
+}
+
+impl<'packet> BitReader<'packet> {
+    fn new(packet: &'packet [u8]) -> Self {
+        Self {
+            packet,
+            position_in_bits: 0,
+        }
+    }
+
+    fn read(&mut self, bits: u32) -> Result<u64> {
+        let mut value = 0;
+        for _ in 0..bits {
+            let Some(byte) = self.packet.get(self.position_in_bits / 8) else {
+                bail!("packet is truncated after {} bytes", self.packet.len());
+            };
+            let bit = (byte >> (7 - self.position_in_bits % 8)) & 1;
+            value = (value << 1) | u64::from(bit);
+            self.position_in_bits += 1;
+        }
+        Ok(value)
+    }
+
+    fn read_bool(&mut self) -> Result<bool> {
+        Ok(self.read(1)? == 1)
+    }
+
+    fn read_signed(&mut self, bits: u32) -> Result<i64> {
+        let value = self.read(bits)?;
+        let shift = u64::BITS - bits;
+        Ok(((value << shift) as i64) >> shift)
+    }
+
+    fn read_player_number(&mut self) -> Result<PlayerNumber> {
+        Ok(match self.read(PLAYER_NUMBER_BITS)? {
+            0 => PlayerNumber::One,
+            1 => PlayerNumber::Two,
+            2 => PlayerNumber::Three,
+            3 => PlayerNumber::Four,
+            4 => PlayerNumber::Five,
+            index => bail!("unexpected player number index {index}"),
+        })
+    }
+
+    fn read_position(&mut self) -> Result<Point2<Field>> {
+        let x = self.read_signed(POSITION_BITS)? as f32 / CENTIMETERS_PER_METER;
+        let y = self.read_signed(POSITION_BITS)? as f32 / CENTIMETERS_PER_METER;
+        Ok(point![x, y])
+    }
+
+    fn read_pose(&mut self) -> Result<Pose2<Field>> {
+        let position = self.read_position()?;
+        let degrees = self.read(ORIENTATION_BITS)?;
+        if degrees >= 360 {
+            bail!("unexpected orientation of {degrees} degrees");
+        }
+        Ok(Pose2::new(position, (degrees as f32).to_radians()))
+    }
+
+    fn read_ball_age(&mut self) -> Result<Duration> {
+        Ok(BALL_AGE_STEP * self.read(BALL_AGE_BITS)? as u32)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::FRAC_PI_2;
+
+    use approx::assert_relative_eq;
+
+    use super::*;
+
+    fn state_message() -> StateMessage {
+        StateMessage {
+            player_number: PlayerNumber::Four,
+            pose: Pose2::new(point![-3.214, 1.006], -FRAC_PI_2),
+            ball_position: Some(BallPosition {
+                position: point![0.5, -2.25],
+                age: Duration::from_millis(1230),
+            }),
+            search_state: SearchState {
+                observed_cells: 0b1011 << 40,
+                search_position: Some(point![4.0, 0.0]),
+            },
+        }
+    }
+
+    #[test]
+    fn state_message_round_trips_with_quantization() {
+        let message = state_message();
+
+        let HulkMessage::State(decoded) =
+            decode(&encode(&HulkMessage::State(message)).unwrap()).unwrap()
+        else {
+            panic!("expected a state message");
+        };
+
+        assert_eq!(decoded.player_number, message.player_number);
+        assert_relative_eq!(decoded.pose.position(), point![-3.21, 1.01], epsilon = 1e-4);
+        assert_relative_eq!(
+            decoded.pose.orientation().angle(),
+            -FRAC_PI_2,
+            epsilon = 1e-4
+        );
+        let ball_position = decoded.ball_position.unwrap();
+        assert_relative_eq!(ball_position.position, point![0.5, -2.25], epsilon = 1e-4);
+        assert_eq!(ball_position.age, Duration::from_millis(1200));
+        assert_eq!(decoded.search_state, message.search_state);
+    }
+
+    #[test]
+    fn out_of_range_values_are_saturated() {
+        let message = StateMessage {
+            pose: Pose2::new(point![100.0, -100.0], 0.0),
+            ball_position: Some(BallPosition {
+                position: Point2::origin(),
+                age: Duration::MAX,
+            }),
+            ..state_message()
+        };
+
+        let HulkMessage::State(decoded) =
+            decode(&encode(&HulkMessage::State(message)).unwrap()).unwrap()
+        else {
+            panic!("expected a state message");
+        };
+
+        assert_relative_eq!(
+            decoded.pose.position(),
+            point![20.47, -20.48],
+            epsilon = 1e-4
+        );
+        assert_eq!(
+            decoded.ball_position.unwrap().age,
+            Duration::from_millis(25500)
+        );
+    }
+
+    #[test]
+    fn visual_referee_message_round_trips() {
+        let message = VisualRefereeMessage {
+            player_number: PlayerNumber::One,
+            kicking_team: Some(Team::Opponent),
+        };
+
+        let HulkMessage::VisualReferee(decoded) =
+            decode(&encode(&HulkMessage::VisualReferee(message)).unwrap()).unwrap()
+        else {
+            panic!("expected a visual referee message");
+        };
+
+        assert_eq!(decoded.player_number, message.player_number);
+        assert_eq!(decoded.kicking_team, message.kicking_team);
+    }
+
+    #[test]
+    fn largest_message_fits_into_a_packet() {
+        let message = StateMessage {
+            search_state: SearchState {
+                observed_cells: u64::MAX >> (u64::BITS - SEARCH_GRID_BITS),
+                search_position: Some(Point2::origin()),
+            },
+            ..state_message()
+        };
+
+        let packet = encode(&HulkMessage::State(message)).unwrap();
+
+        assert_eq!(packet.len(), 19);
+    }
+
+    #[test]
+    fn other_protocol_versions_and_truncated_packets_are_rejected() {
+        let mut packet = encode(&HulkMessage::State(state_message())).unwrap();
+
+        assert!(decode(&packet[..packet.len() - 1]).is_err());
+        packet[0] = PROTOCOL_VERSION + 1;
+        assert!(decode(&packet).is_err());
+    }
+}
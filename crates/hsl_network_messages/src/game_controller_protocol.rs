@@ -0,0 +1,213 @@
+//! Detection of the GameController protocol version of received packets.
+//!
+//! Every packet is checked for its header, version and size before it is decoded, so that packets
+//! of an unknown protocol are rejected instead of being misinterpreted.
+
+use std::{
+    fmt::{self, Display, Formatter},
+    mem::size_of,
+};
+
+use color_eyre::{Result, eyre::bail};
+
+use crate::bindings::{
+    GAMECONTROLLER_RETURN_STRUCT_HEADER, GAMECONTROLLER_RETURN_STRUCT_VERSION,
+    GAMECONTROLLER_STRUCT_HEADER, GAMECONTROLLER_STRUCT_VERSION, RoboCupGameControlData,
+    RoboCupGameControlReturnData,
+};
+
+const HEADER_SIZE: usize = 4;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PacketKind {
+    /// Game state broadcast by the GameController
+    State,
+    /// Robot status sent back to the GameController
+    Return,
+}
+
+impl PacketKind {
+    pub fn header(self) -> &'static [u8] {
+        match self {
+            PacketKind::State => &GAMECONTROLLER_STRUCT_HEADER[..HEADER_SIZE],
+            PacketKind::Return => &GAMECONTROLLER_RETURN_STRUCT_HEADER[..HEADER_SIZE],
+        }
+    }
+
+    pub fn supported_versions(self) -> &'static [u8] {
+        match self {
+            PacketKind::State => &[GAMECONTROLLER_STRUCT_VERSION],
+            PacketKind::Return => &[GAMECONTROLLER_RETURN_STRUCT_VERSION],
+        }
+    }
+
+    fn packet_size(self, version: u8) -> Option<usize> {
+        match (self, version) {
+            (PacketKind::State, GAMECONTROLLER_STRUCT_VERSION) => {
+                Some(size_of::<RoboCupGameControlData>())
+            }
+            (PacketKind::Return, GAMECONTROLLER_RETURN_STRUCT_VERSION) => {
+                Some(size_of::<RoboCupGameControlReturnData>())
+            }
+            _ => None,
+        }
+    }
+}
+
+impl Display for PacketKind {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            PacketKind::State => write!(formatter, "GameController state"),
+            PacketKind::Return => write!(formatter, "GameController return"),
+        }
+    }
+}
+
+/// Checks header, version and size of a packet and returns its protocol version.
+pub fn detect_version(kind: PacketKind, buffer: &[u8]) -> Result<u8> {
+    let Some((header, rest)) = buffer.split_first_chunk::<HEADER_SIZE>() else {
+        bail!(
+            "{kind} packet of {} bytes is too short to contain a header",
+            buffer.len()
+        );
+    };
+    if header.as_slice() != kind.header() {
+        bail!(
+            "unexpected {kind} header \"{}\", expected \"{}\"",
+            header.as_slice().escape_ascii(),
+            kind.header().escape_ascii()
+        );
+    }
+    let Some(&version) = rest.first() else {
+        bail!("{kind} packet ends after its header");
+    };
+    let Some(expected_size) = kind.packet_size(version) else {
+        bail!(
+            "unsupported {kind} version {version}, supported versions are {:?}",
+            kind.supported_versions()
+        );
+    };
+    if buffer.len() != expected_size {
+        bail!(
+            "{kind} packet of version {version} has {} bytes, expected {expected_size}",
+            buffer.len()
+        );
+    }
+    Ok(version)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use linear_algebra::Pose2;
+
+    use crate::{
+        CompetitionType, GameControllerReturnMessage, GameControllerStateMessage, GamePhase,
+        GameState, HULKS_TEAM_NUMBER, Half, Penalty, Player, PlayerNumber, SubState, Team,
+        TeamColor, TeamState,
+    };
+
+    use super::*;
+
+    fn team_state(team_number: u8) -> TeamState {
+        TeamState {
+            team_number,
+            field_player_color: TeamColor::Blue,
+            goal_keeper_color: TeamColor::Yellow,
+            goal_keeper_player_number: Some(PlayerNumber::One),
+            score: 0,
+            penalty_shoot_index: 0,
+            penalty_shoots: Vec::new(),
+            remaining_amount_of_messages: 1200,
+            players: vec![
+                Player {
+                    penalty: None,
+                    caution: 0,
+                };
+                5
+            ],
+        }
+    }
+
+    fn state_message() -> GameControllerStateMessage {
+        let mut hulks_team = team_state(HULKS_TEAM_NUMBER);
+        hulks_team.players[1].penalty = Some(Penalty::PickUp {
+            remaining: Duration::from_secs(45),
+        });
+        GameControllerStateMessage {
+            competition_type: CompetitionType::Small,
+            stopped: false,
+            game_phase: GamePhase::Normal,
+            game_state: GameState::Playing,
+            sub_state: Some(SubState::CornerKick),
+            half: Half::Second,
+            remaining_time_in_half: Duration::from_secs(321),
+            secondary_time: Duration::from_secs(12),
+            hulks_team,
+            opponent_team: team_state(7),
+            kicking_team: Some(Team::Opponent),
+            hulks_team_is_home_after_coin_toss: false,
+        }
+    }
+
+    #[test]
+    fn state_packets_survive_encoding_and_decoding() {
+        let packet = state_message().to_packet(3, 5);
+
+        assert_eq!(
+            detect_version(PacketKind::State, &packet).unwrap(),
+            GAMECONTROLLER_STRUCT_VERSION
+        );
+        let decoded = GameControllerStateMessage::try_from(packet.as_slice()).unwrap();
+        assert_eq!(decoded.game_state, GameState::Playing);
+        assert_eq!(decoded.sub_state, Some(SubState::CornerKick));
+        assert_eq!(decoded.half, Half::Second);
+        assert_eq!(decoded.remaining_time_in_half, Duration::from_secs(321));
+        assert_eq!(decoded.kicking_team, Some(Team::Opponent));
+        assert_eq!(decoded.opponent_team.team_number, 7);
+        assert!(!decoded.hulks_team_is_home_after_coin_toss);
+        assert_eq!(
+            decoded.hulks_team.players[1].penalty,
+            Some(Penalty::PickUp {
+                remaining: Duration::from_secs(45)
+            })
+        );
+    }
+
+    #[test]
+    fn packets_of_another_kind_are_rejected() {
+        let packet: Vec<u8> = GameControllerReturnMessage {
+            player_number: PlayerNumber::Two,
+            fallen: false,
+            pose: Pose2::default(),
+            ball: None,
+        }
+        .into();
+
+        let error = detect_version(PacketKind::State, &packet).unwrap_err();
+
+        assert!(error.to_string().contains("header \"RGrt\""), "{error}");
+        assert!(GameControllerStateMessage::try_from(packet.as_slice()).is_err());
+    }
+
+    #[test]
+    fn unknown_versions_are_rejected() {
+        let mut packet = state_message().to_packet(0, 5);
+        packet[HEADER_SIZE] = GAMECONTROLLER_STRUCT_VERSION + 1;
+
+        let error = detect_version(PacketKind::State, &packet).unwrap_err();
+
+        assert!(error.to_string().contains("unsupported"), "{error}");
+        assert!(GameControllerStateMessage::try_from(packet.as_slice()).is_err());
+    }
+
+    #[test]
+    fn truncated_packets_are_rejected() {
+        let packet = state_message().to_packet(0, 5);
+
+        assert!(detect_version(PacketKind::State, &packet[..3]).is_err());
+        assert!(detect_version(PacketKind::State, &packet[..HEADER_SIZE]).is_err());
+        assert!(detect_version(PacketKind::State, &packet[..packet.len() - 1]).is_err());
+    }
+}
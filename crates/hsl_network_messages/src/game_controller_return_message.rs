@@ -1,4 +1,4 @@
-use std::{ffi::c_char, mem::size_of, ptr::read, slice::from_raw_parts, time::Duration};
+use std::{ffi::c_char, mem::size_of, ptr::read_unaligned, slice::from_raw_parts, time::Duration};
 
 use color_eyre::{Report, Result, eyre::bail};
 use coordinate_systems::{Field, Ground};
@@ -12,6 +12,7 @@ use crate::{
         GAMECONTROLLER_RETURN_STRUCT_HEADER, GAMECONTROLLER_RETURN_STRUCT_VERSION,
         RoboCupGameControlReturnData,
     },
+    game_controller_protocol::{PacketKind, detect_version},
 };
 
 #[derive(Clone, Copy, Debug, Default, Deserialize, Serialize, Message)]
@@ -26,11 +27,31 @@ impl TryFrom<&[u8]> for GameControllerReturnMessage {
     type Error = Report;
 
     fn try_from(buffer: &[u8]) -> Result<Self> {
-        if buffer.len() < size_of::<RoboCupGameControlReturnData>() {
-            bail!("buffer too small");
+        match detect_version(PacketKind::Return, buffer)? {
+            GAMECONTROLLER_RETURN_STRUCT_VERSION => {
+                let message = unsafe {
+                    read_unaligned(buffer.as_ptr() as *const RoboCupGameControlReturnData)
+                };
+                message.try_into()
+            }
+            version => bail!("no decoder for GameController return version {version}"),
+        }
+    }
+}
+
+impl GameControllerReturnMessage {
+    /// Decodes a return packet of a robot of any team, e.g. of an opponent robot in a recorded
+    /// game, together with the team number of the sender.
+    pub fn from_packet_of_any_team(buffer: &[u8]) -> Result<(u8, Self)> {
+        match detect_version(PacketKind::Return, buffer)? {
+            GAMECONTROLLER_RETURN_STRUCT_VERSION => {
+                let message = unsafe {
+                    read_unaligned(buffer.as_ptr() as *const RoboCupGameControlReturnData)
+                };
+                Ok((message.teamNum, decode_fields(&message)?))
+            }
+            version => bail!("no decoder for GameController return version {version}"),
         }
-        let message = unsafe { read(buffer.as_ptr() as *const RoboCupGameControlReturnData) };
-        message.try_into()
     }
 }
 
@@ -38,49 +59,57 @@ impl TryFrom<RoboCupGameControlReturnData> for GameControllerReturnMessage {
     type Error = Report;
 
     fn try_from(message: RoboCupGameControlReturnData) -> Result<Self> {
-        if message.header[0] != GAMECONTROLLER_RETURN_STRUCT_HEADER[0] as c_char
-            && message.header[1] != GAMECONTROLLER_RETURN_STRUCT_HEADER[1] as c_char
-            && message.header[2] != GAMECONTROLLER_RETURN_STRUCT_HEADER[2] as c_char
-            && message.header[3] != GAMECONTROLLER_RETURN_STRUCT_HEADER[3] as c_char
+        if message
+            .header
+            .iter()
+            .zip(GAMECONTROLLER_RETURN_STRUCT_HEADER)
+            .any(|(received, expected)| *received != *expected as c_char)
         {
-            bail!("unexpected header");
+            bail!("unexpected header: {:?}", message.header);
         }
         if message.version != GAMECONTROLLER_RETURN_STRUCT_VERSION {
-            bail!("unexpected version");
+            bail!(
+                "unexpected version: {}, expected {GAMECONTROLLER_RETURN_STRUCT_VERSION}",
+                message.version
+            );
         }
         if message.teamNum != HULKS_TEAM_NUMBER {
             bail!("unexpected team number != {}", HULKS_TEAM_NUMBER);
         }
-        Ok(Self {
-            player_number: match message.playerNum {
-                1 => PlayerNumber::One,
-                2 => PlayerNumber::Two,
-                3 => PlayerNumber::Three,
-                4 => PlayerNumber::Four,
-                5 => PlayerNumber::Five,
-                _ => bail!("unexpected player number {}", message.playerNum),
-            },
-            fallen: match message.fallen {
-                1 => true,
-                0 => false,
-                _ => bail!("unexpected fallen state"),
-            },
-            pose: Pose2::new(
-                point![message.pose[0] / 1000.0, message.pose[1] / 1000.0],
-                message.pose[2],
-            ),
-            ball: if message.ballAge == -1.0 {
-                None
-            } else {
-                Some(BallPosition {
-                    position: point![message.ball[0] / 1000.0, message.ball[1] / 1000.0],
-                    age: Duration::from_secs_f32(message.ballAge),
-                })
-            },
-        })
+        decode_fields(&message)
     }
 }
 
+fn decode_fields(message: &RoboCupGameControlReturnData) -> Result<GameControllerReturnMessage> {
+    Ok(GameControllerReturnMessage {
+        player_number: match message.playerNum {
+            1 => PlayerNumber::One,
+            2 => PlayerNumber::Two,
+            3 => PlayerNumber::Three,
+            4 => PlayerNumber::Four,
+            5 => PlayerNumber::Five,
+            _ => bail!("unexpected player number {}", message.playerNum),
+        },
+        fallen: match message.fallen {
+            1 => true,
+            0 => false,
+            _ => bail!("unexpected fallen state"),
+        },
+        pose: Pose2::new(
+            point![message.pose[0] / 1000.0, message.pose[1] / 1000.0],
+            message.pose[2],
+        ),
+        ball: if message.ballAge == -1.0 {
+            None
+        } else {
+            Some(BallPosition {
+                position: point![message.ball[0] / 1000.0, message.ball[1] / 1000.0],
+                age: Duration::from_secs_f32(message.ballAge),
+            })
+        },
+    })
+}
+
 impl From<GameControllerReturnMessage> for Vec<u8> {
     fn from(message: GameControllerReturnMessage) -> Self {
         let message = message.into();
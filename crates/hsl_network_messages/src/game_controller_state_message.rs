@@ -2,7 +2,8 @@ use std::{
     convert::{TryFrom, TryInto},
     ffi::c_char,
     mem::size_of,
-    ptr::read,
+    ptr::read_unaligned,
+    slice::from_raw_parts,
     time::Duration,
 };
 
@@ -25,8 +26,9 @@ use crate::{
         SET_PLAY_GOAL_KICK, SET_PLAY_INDIRECT_FREE_KICK, SET_PLAY_NONE, SET_PLAY_PENALTY_KICK,
         SET_PLAY_THROW_IN, STATE_FINISHED, STATE_INITIAL, STATE_PLAYING, STATE_READY, STATE_SET,
         TEAM_BLACK, TEAM_BLUE, TEAM_BROWN, TEAM_GRAY, TEAM_GREEN, TEAM_ORANGE, TEAM_PURPLE,
-        TEAM_RED, TEAM_WHITE, TEAM_YELLOW,
+        TEAM_RED, TEAM_WHITE, TEAM_YELLOW, TeamInfo,
     },
+    game_controller_protocol::{PacketKind, detect_version},
 };
 
 #[derive(Clone, Debug, Deserialize, Serialize, PathSerialize, PathIntrospect, Message)]
@@ -49,11 +51,77 @@ impl TryFrom<&[u8]> for GameControllerStateMessage {
     type Error = Report;
 
     fn try_from(buffer: &[u8]) -> Result<Self> {
-        if buffer.len() < size_of::<RoboCupGameControlData>() {
-            bail!("buffer too small");
+        match detect_version(PacketKind::State, buffer)? {
+            GAMECONTROLLER_STRUCT_VERSION => {
+                let message =
+                    unsafe { read_unaligned(buffer.as_ptr() as *const RoboCupGameControlData) };
+                message.try_into()
+            }
+            version => bail!("no decoder for GameController state version {version}"),
+        }
+    }
+}
+
+impl GameControllerStateMessage {
+    /// Encodes the message the way the GameController broadcasts it.
+    ///
+    /// The packet number and the number of players per team are not part of the message and
+    /// have to be provided by the sender.
+    pub fn to_packet(&self, packet_number: u8, players_per_team: u8) -> Vec<u8> {
+        let message = self.to_game_control_data(packet_number, players_per_team);
+        unsafe {
+            from_raw_parts(
+                &message as *const RoboCupGameControlData as *const u8,
+                size_of::<RoboCupGameControlData>(),
+            )
+        }
+        .to_vec()
+    }
+
+    fn to_game_control_data(
+        &self,
+        packet_number: u8,
+        players_per_team: u8,
+    ) -> RoboCupGameControlData {
+        let team_number = |team| match team {
+            Team::Hulks => HULKS_TEAM_NUMBER,
+            Team::Opponent => self.opponent_team.team_number,
+        };
+        let kicking_team = match self.game_phase {
+            GamePhase::PenaltyShootout { kicking_team } => Some(kicking_team),
+            _ => self.kicking_team,
+        };
+        let hulks_team = TeamInfo::from(&self.hulks_team);
+        let opponent_team = TeamInfo::from(&self.opponent_team);
+        RoboCupGameControlData {
+            header: [
+                GAMECONTROLLER_STRUCT_HEADER[0] as c_char,
+                GAMECONTROLLER_STRUCT_HEADER[1] as c_char,
+                GAMECONTROLLER_STRUCT_HEADER[2] as c_char,
+                GAMECONTROLLER_STRUCT_HEADER[3] as c_char,
+            ],
+            version: GAMECONTROLLER_STRUCT_VERSION,
+            packetNumber: packet_number,
+            playersPerTeam: players_per_team,
+            competitionType: self.competition_type.into(),
+            stopped: u8::from(self.stopped),
+            gamePhase: self.game_phase.into(),
+            state: self.game_state.into(),
+            setPlay: SubState::to_set_play(self.sub_state),
+            firstHalf: u8::from(self.half == Half::First),
+            kickingTeam: kicking_team.map_or(NONE_TEAM_NUMBER, team_number),
+            secsRemaining: self
+                .remaining_time_in_half
+                .as_secs()
+                .try_into()
+                .unwrap_or(i16::MAX),
+            secondaryTime: self.secondary_time.as_secs().try_into().unwrap_or(i16::MAX),
+            teams: if self.hulks_team_is_home_after_coin_toss {
+                [hulks_team, opponent_team]
+            } else {
+                [opponent_team, hulks_team]
+            },
         }
-        let message = unsafe { read(buffer.as_ptr() as *const RoboCupGameControlData) };
-        message.try_into()
     }
 }
 
@@ -61,15 +129,19 @@ impl TryFrom<RoboCupGameControlData> for GameControllerStateMessage {
     type Error = Report;
 
     fn try_from(message: RoboCupGameControlData) -> Result<Self> {
-        if message.header[0] != GAMECONTROLLER_STRUCT_HEADER[0] as c_char
-            && message.header[1] != GAMECONTROLLER_STRUCT_HEADER[1] as c_char
-            && message.header[2] != GAMECONTROLLER_STRUCT_HEADER[2] as c_char
-            && message.header[3] != GAMECONTROLLER_STRUCT_HEADER[3] as c_char
+        if message
+            .header
+            .iter()
+            .zip(GAMECONTROLLER_STRUCT_HEADER)
+            .any(|(received, expected)| *received != *expected as c_char)
         {
             bail!("unexpected header: {:?}", message.header);
         }
         if message.version != GAMECONTROLLER_STRUCT_VERSION {
-            bail!("unexpected version: {}", message.version);
+            bail!(
+                "unexpected version: {}, expected {GAMECONTROLLER_STRUCT_VERSION}",
+                message.version
+            );
         }
         let (hulks_team_index, opponent_team_index) =
             match (message.teams[0].teamNumber, message.teams[1].teamNumber) {
@@ -234,6 +306,16 @@ impl CompetitionType {
     }
 }
 
+impl From<CompetitionType> for u8 {
+    fn from(competition_type: CompetitionType) -> Self {
+        match competition_type {
+            CompetitionType::Small => COMPETITION_TYPE_SMALL,
+            CompetitionType::Middle => COMPETITION_TYPE_MIDDLE,
+            CompetitionType::Large => COMPETITION_TYPE_LARGE,
+        }
+    }
+}
+
 #[derive(
     Clone,
     Copy,
@@ -274,6 +356,17 @@ impl GamePhase {
     }
 }
 
+impl From<GamePhase> for u8 {
+    fn from(game_phase: GamePhase) -> Self {
+        match game_phase {
+            GamePhase::Normal => GAME_PHASE_NORMAL,
+            GamePhase::PenaltyShootout { .. } => GAME_PHASE_PENALTY_SHOOT_OUT,
+            GamePhase::Extratime => GAME_PHASE_EXTRA_TIME,
+            GamePhase::Timeout => GAME_PHASE_TIMEOUT,
+        }
+    }
+}
+
 #[derive(
     Clone,
     Copy,
@@ -308,6 +401,18 @@ impl GameState {
     }
 }
 
+impl From<GameState> for u8 {
+    fn from(game_state: GameState) -> Self {
+        match game_state {
+            GameState::Initial => STATE_INITIAL,
+            GameState::Ready => STATE_READY,
+            GameState::Set => STATE_SET,
+            GameState::Playing => STATE_PLAYING,
+            GameState::Finished => STATE_FINISHED,
+        }
+    }
+}
+
 #[derive(
     Clone,
     Copy,
@@ -377,6 +482,18 @@ impl SubState {
             _ => bail!("unexpected sub state"),
         }
     }
+
+    fn to_set_play(sub_state: Option<Self>) -> u8 {
+        match sub_state {
+            None => SET_PLAY_NONE,
+            Some(SubState::DirectFreeKick) => SET_PLAY_DIRECT_FREE_KICK,
+            Some(SubState::IndirectFreeKick) => SET_PLAY_INDIRECT_FREE_KICK,
+            Some(SubState::PenaltyKick) => SET_PLAY_PENALTY_KICK,
+            Some(SubState::ThrowIn) => SET_PLAY_THROW_IN,
+            Some(SubState::GoalKick) => SET_PLAY_GOAL_KICK,
+            Some(SubState::CornerKick) => SET_PLAY_CORNER_KICK,
+        }
+    }
 }
 
 #[derive(
@@ -424,6 +541,45 @@ pub struct TeamState {
     pub players: Vec<Player>,
 }
 
+impl From<&TeamState> for TeamInfo {
+    fn from(team: &TeamState) -> Self {
+        let mut players = [RobotInfo::from(&Player {
+            penalty: None,
+            caution: 0,
+        }); MAX_NUM_PLAYERS as usize];
+        for (robot_info, player) in players.iter_mut().zip(&team.players) {
+            *robot_info = player.into();
+        }
+        let single_shots = team
+            .penalty_shoots
+            .iter()
+            .take(u16::BITS as usize)
+            .enumerate()
+            .filter(|(_, shoot)| matches!(shoot, PenaltyShoot::Successful))
+            .fold(0, |single_shots, (shoot_index, _)| {
+                single_shots | (1 << shoot_index)
+            });
+        Self {
+            teamNumber: team.team_number,
+            fieldPlayerColour: team.field_player_color.clone().into(),
+            goalkeeperColour: team.goal_keeper_color.clone().into(),
+            goalkeeper: match team.goal_keeper_player_number {
+                None => 0,
+                Some(PlayerNumber::One) => 1,
+                Some(PlayerNumber::Two) => 2,
+                Some(PlayerNumber::Three) => 3,
+                Some(PlayerNumber::Four) => 4,
+                Some(PlayerNumber::Five) => 5,
+            },
+            score: team.score,
+            penaltyShot: team.penalty_shoot_index,
+            singleShots: single_shots,
+            messageBudget: team.remaining_amount_of_messages,
+            players,
+        }
+    }
+}
+
 #[derive(
     Clone, Debug, Deserialize, Serialize, PathSerialize, PathDeserialize, PathIntrospect, Message,
 )]
@@ -460,6 +616,23 @@ impl TryFrom<u8> for TeamColor {
     }
 }
 
+impl From<TeamColor> for u8 {
+    fn from(team_color: TeamColor) -> Self {
+        match team_color {
+            TeamColor::Blue => TEAM_BLUE,
+            TeamColor::Red => TEAM_RED,
+            TeamColor::Yellow => TEAM_YELLOW,
+            TeamColor::Black => TEAM_BLACK,
+            TeamColor::White => TEAM_WHITE,
+            TeamColor::Green => TEAM_GREEN,
+            TeamColor::Orange => TEAM_ORANGE,
+            TeamColor::Purple => TEAM_PURPLE,
+            TeamColor::Brown => TEAM_BROWN,
+            TeamColor::Gray => TEAM_GRAY,
+        }
+    }
+}
+
 #[derive(Clone, Debug, Deserialize, Serialize, Message)]
 pub enum PenaltyShoot {
     Successful,
@@ -484,6 +657,20 @@ impl TryFrom<RobotInfo> for Player {
     }
 }
 
+impl From<&Player> for RobotInfo {
+    fn from(player: &Player) -> Self {
+        let (penalty, remaining) = match player.penalty {
+            Some(penalty) => (penalty.to_protocol(), penalty.remaining()),
+            None => (PENALTY_NONE, Duration::ZERO),
+        };
+        Self {
+            penalty,
+            secsTillUnpenalised: remaining.as_secs().try_into().unwrap_or(u8::MAX),
+            cautions: player.caution,
+        }
+    }
+}
+
 #[derive(
     Clone,
     Copy,
@@ -534,6 +721,42 @@ impl Penalty {
             _ => bail!("unexpected penalty type"),
         }
     }
+
+    fn to_protocol(self) -> u8 {
+        match self {
+            Penalty::IllegalPosition { .. } => PENALTY_ILLEGAL_POSITIONING,
+            Penalty::MotionInSet { .. } => PENALTY_MOTION_IN_SET,
+            Penalty::MotionInStop { .. } => PENALTY_MOTION_IN_STOP,
+            Penalty::LocalGameStuck { .. } => PENALTY_LOCAL_GAME_STUCK,
+            Penalty::IncapableRobot { .. } => PENALTY_INCAPABLE_ROBOT,
+            Penalty::PickUp { .. } => PENALTY_PICK_UP,
+            Penalty::BallHolding { .. } => PENALTY_BALL_HOLDING,
+            Penalty::LeavingTheField { .. } => PENALTY_LEAVING_THE_FIELD,
+            Penalty::PlayingWithArmsHands { .. } => PENALTY_PLAYING_WITH_ARMS_HANDS,
+            Penalty::Pushing { .. } => PENALTY_PUSHING,
+            Penalty::Cautioned { .. } => PENALTY_CAUTIONED,
+            Penalty::SentOff { .. } => PENALTY_SENT_OFF,
+            Penalty::Substitute { .. } => PENALTY_SUBSTITUTE,
+        }
+    }
+
+    fn remaining(&self) -> Duration {
+        match *self {
+            Penalty::IllegalPosition { remaining }
+            | Penalty::MotionInSet { remaining }
+            | Penalty::MotionInStop { remaining }
+            | Penalty::LocalGameStuck { remaining }
+            | Penalty::IncapableRobot { remaining }
+            | Penalty::PickUp { remaining }
+            | Penalty::BallHolding { remaining }
+            | Penalty::LeavingTheField { remaining }
+            | Penalty::PlayingWithArmsHands { remaining }
+            | Penalty::Pushing { remaining }
+            | Penalty::Cautioned { remaining }
+            | Penalty::SentOff { remaining }
+            | Penalty::Substitute { remaining } => remaining,
+        }
+    }
 }
 
 impl Default for Penalty {
@@ -1,4 +1,7 @@
 mod bindings;
+pub mod budget;
+pub mod codec;
+pub mod game_controller_protocol;
 mod game_controller_return_message;
 mod game_controller_state_message;
 
@@ -15,8 +18,8 @@ use serde::{Deserialize, Serialize};
 
 pub use game_controller_return_message::GameControllerReturnMessage;
 pub use game_controller_state_message::{
-    GameControllerStateMessage, GamePhase, GameState, Half, Penalty, PenaltyShoot, Player,
-    SubState, Team, TeamColor, TeamState,
+    CompetitionType, GameControllerStateMessage, GamePhase, GameState, Half, Penalty, PenaltyShoot,
+    Player, SubState, Team, TeamColor, TeamState,
 };
 
 #[derive(
@@ -32,6 +35,7 @@ pub use game_controller_state_message::{
 )]
 pub enum HulkMessage {
     State(StateMessage),
+    VisualReferee(VisualRefereeMessage),
 }
 
 impl Default for HulkMessage {
@@ -64,6 +68,76 @@ pub struct StateMessage {
     pub player_number: PlayerNumber,
     pub pose: Pose2<Field>,
     pub ball_position: Option<BallPosition<Field>>,
+    pub search_state: SearchState,
+}
+
+pub const SEARCH_GRID_LENGTH: usize = 8;
+pub const SEARCH_GRID_WIDTH: usize = 6;
+
+/// Compact ball search state shared with teammates.
+///
+/// The field is divided into a coarse grid of `SEARCH_GRID_LENGTH` x `SEARCH_GRID_WIDTH` cells,
+/// each bit of `observed_cells` marks a cell the player has recently looked at.
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    Default,
+    Deserialize,
+    Eq,
+    PartialEq,
+    Serialize,
+    PathDeserialize,
+    PathIntrospect,
+    PathSerialize,
+    Message,
+)]
+pub struct SearchState {
+    pub observed_cells: u64,
+    pub search_position: Option<Point2<Field>>,
+}
+
+impl SearchState {
+    pub fn is_observed(&self, cell: (usize, usize)) -> bool {
+        self.observed_cells & Self::cell_bit(cell) != 0
+    }
+
+    pub fn set_observed(&mut self, cell: (usize, usize)) {
+        self.observed_cells |= Self::cell_bit(cell);
+    }
+
+    /// Grid cell containing `position` on a field of the given length and width.
+    pub fn cell_at(position: Point2<Field>, field_length: f32, field_width: f32) -> (usize, usize) {
+        let cell = |coordinate: f32, extent: f32, cells: usize| {
+            (((coordinate / extent + 0.5) * cells as f32).max(0.0) as usize).min(cells - 1)
+        };
+        (
+            cell(position.x(), field_length, SEARCH_GRID_LENGTH),
+            cell(position.y(), field_width, SEARCH_GRID_WIDTH),
+        )
+    }
+
+    fn cell_bit((x, y): (usize, usize)) -> u64 {
+        1 << (x * SEARCH_GRID_WIDTH + y)
+    }
+}
+
+/// Referee signal detected by a player. `kicking_team` is `None` for the ready signal.
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    Default,
+    Deserialize,
+    Serialize,
+    PathDeserialize,
+    PathIntrospect,
+    PathSerialize,
+    Message,
+)]
+pub struct VisualRefereeMessage {
+    pub player_number: PlayerNumber,
+    pub kicking_team: Option<Team>,
 }
 
 #[derive(
@@ -128,6 +202,8 @@ impl Display for PlayerNumber {
 
 #[cfg(test)]
 mod tests {
+    use linear_algebra::point;
+
     use super::*;
     #[test]
     fn hulk_striker_message_size() {
@@ -138,7 +214,39 @@ mod tests {
                 position: Point2::origin(),
                 age: Duration::MAX,
             }),
+            search_state: SearchState {
+                observed_cells: u64::MAX,
+                search_position: Some(Point2::origin()),
+            },
+        });
+        assert!(codec::encode(&test_message).unwrap().len() <= codec::MAXIMUM_PACKET_SIZE)
+    }
+
+    #[test]
+    fn search_state_cells_cover_the_field() {
+        let mut search_state = SearchState::default();
+        let own_corner = SearchState::cell_at(point![-4.6, -3.1], 9.0, 6.0);
+        let opponent_corner = SearchState::cell_at(point![4.6, 3.1], 9.0, 6.0);
+        search_state.set_observed(own_corner);
+        search_state.set_observed(opponent_corner);
+
+        assert_eq!(own_corner, (0, 0));
+        assert_eq!(
+            opponent_corner,
+            (SEARCH_GRID_LENGTH - 1, SEARCH_GRID_WIDTH - 1)
+        );
+        assert!(search_state.is_observed(own_corner));
+        assert!(search_state.is_observed(opponent_corner));
+        assert!(!search_state.is_observed(SearchState::cell_at(Point2::origin(), 9.0, 6.0)));
+        assert_eq!(search_state.observed_cells.count_ones(), 2);
+    }
+
+    #[test]
+    fn hulk_visual_referee_message_size() {
+        let test_message = HulkMessage::VisualReferee(VisualRefereeMessage {
+            player_number: PlayerNumber::Five,
+            kicking_team: Some(Team::Opponent),
         });
-        assert!(bincode::serialize(&test_message).unwrap().len() <= 128)
+        assert!(codec::encode(&test_message).unwrap().len() <= codec::MAXIMUM_PACKET_SIZE)
     }
 }
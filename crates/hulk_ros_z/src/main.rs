@@ -127,11 +127,13 @@ async fn spawn_all(ctx: Arc<Context>) -> Result<RunningStack> {
     join_set.spawn(booster_sdk_interface::run_boxed(ctx.clone()));
     join_set.spawn(button_event_bridge::run_boxed(ctx.clone()));
     join_set.spawn(button_event_handler::run_boxed(ctx.clone()));
+    join_set.spawn(camera_calibration::run_boxed(ctx.clone()));
     join_set.spawn(camera_matrix_calculator::run_boxed(ctx.clone()));
     join_set.spawn(detection::run_boxed(ctx.clone()));
     join_set.spawn(fake_odometry::run_boxed(ctx.clone()));
     join_set.spawn(fall_down_state_receiver::run_boxed(ctx.clone()));
     join_set.spawn(field_border_detection::run_boxed(ctx.clone()));
+    join_set.spawn(free_kick_signal_filter::run_boxed(ctx.clone()));
     join_set.spawn(game_controller_filter::run_boxed(ctx.clone()));
     join_set.spawn(game_controller_state_filter::run_boxed(ctx.clone()));
     join_set.spawn(global_parameter_provider::run_boxed(ctx.clone()));
@@ -139,6 +141,7 @@ async fn spawn_all(ctx: Arc<Context>) -> Result<RunningStack> {
     join_set.spawn(head_motion::run_boxed(ctx.clone()));
     join_set.spawn(image_receiver::run_boxed(ctx.clone()));
     join_set.spawn(image_segmenter::run_boxed(ctx.clone()));
+    join_set.spawn(keyframe_motion_player::run_boxed(ctx.clone()));
     join_set.spawn(kinematics_provider::run_boxed(ctx.clone()));
     join_set.spawn(led_handler::run_boxed(ctx.clone()));
     join_set.spawn(line_detection::run_boxed(ctx.clone()));
@@ -154,10 +157,13 @@ async fn spawn_all(ctx: Arc<Context>) -> Result<RunningStack> {
     join_set.spawn(odometer_bridge::run_boxed(ctx.clone()));
     join_set.spawn(player_state_receiver::run_boxed(ctx.clone()));
     join_set.spawn(primary_state_filter::run_boxed(ctx.clone()));
+    join_set.spawn(ready_signal_detection_filter::run_boxed(ctx.clone()));
+    join_set.spawn(referee_pose_interpretation::run_boxed(ctx.clone()));
     join_set.spawn(rule_obstacle_composer::run_boxed(ctx.clone()));
     join_set.spawn(safe_pose_checker::run_boxed(ctx.clone()));
     join_set.spawn(search_suggestor::run_boxed(ctx.clone()));
     join_set.spawn(segment_filter::run_boxed(ctx.clone()));
+    join_set.spawn(stereo_depth::run_boxed(ctx.clone()));
     join_set.spawn(support_foot_estimator::run_boxed(ctx.clone()));
     join_set.spawn(team_ball_receiver::run_boxed(ctx.clone()));
     join_set.spawn(time_to_reach_kick_position::run_boxed(ctx.clone()));
@@ -7,9 +7,13 @@ use color_eyre::{
 use context_attribute::context;
 use framework::{AdditionalOutput, MainOutput, deserialize_not_implemented};
 use hardware::PathsInterface;
+use inference_session::{
+    ExecutionProviderChain,
+    downscaling::{InputDownscaling, downscale_nv12},
+};
 use ndarray::{ArrayView2, ArrayView3, Axis};
 use ort::{
-    execution_providers::{CUDAExecutionProvider, TensorRTExecutionProvider},
+    execution_providers::TensorRTExecutionProvider,
     inputs,
     session::{Session, SessionOutputs, builder::GraphOptimizationLevel},
     value::TensorRef,
@@ -18,13 +22,15 @@ use ros2::sensor_msgs::image::Image;
 use serde::{Deserialize, Serialize};
 use types::{
     bounding_box::BoundingBox,
+    execution_provider::{ExecutionProviderKind, InferenceStatus},
     object_detection::{NUMBER_OF_VALUES_PER_OBJECT, Object, RobocupObjectLabel, YOLOObjectLabel},
     parameters::DetectionParameters,
-    pose_detection::{NUMBER_OF_VALUES_PER_POSE, Pose},
+    pose_detection::{NUMBER_OF_VALUES_PER_POSE, POSE_KEYPOINT_OFFSET, Pose},
 };
 
 const MODEL_FILE_NAME: &str = "yolo26m-seg=f11+yolo26m~cheek+yolo26m-pose~badge.onnx";
 pub const NUMBER_OF_DETECTIONS: usize = 300;
+const INPUT_DIMENSION_ALIGNMENT: u32 = 32;
 
 #[derive(Clone, Copy, Debug)]
 enum TaskHead {
@@ -58,11 +64,17 @@ struct ModelOutputs<'a> {
 pub struct ObjectDetection {
     #[serde(skip, default = "deserialize_not_implemented")]
     session: Session,
+    #[serde(skip)]
+    execution_provider: ExecutionProviderKind,
+    #[serde(skip)]
+    input_downscaling: InputDownscaling,
 }
 
 #[context]
 pub struct CreationContext {
     hardware_interface: HardwareInterface,
+
+    parameters: Parameter<DetectionParameters, "hydra">,
 }
 
 #[context]
@@ -73,6 +85,7 @@ pub struct CycleContext {
     post_processing_duration: AdditionalOutput<Duration, "post_processing_duration">,
     non_maximum_suppression_duration:
         AdditionalOutput<Duration, "non_maximum_suppression_duration">,
+    inference_status: AdditionalOutput<InferenceStatus, "inference_status">,
 
     parameters: Parameter<DetectionParameters, "hydra">,
 }
@@ -93,17 +106,26 @@ impl ObjectDetection {
             .with_device_id(0)
             .with_fp16(true)
             .with_engine_cache(true)
-            .with_engine_cache_path(neural_network_folder.display())
-            .build();
-        let cuda = CUDAExecutionProvider::default().build();
+            .with_engine_cache_path(neural_network_folder.display());
+
+        let mut session_builder = Session::builder()?;
+        let execution_provider = ExecutionProviderChain::default()
+            .with_tensor_rt(tensor_rt)
+            .register(
+                &context.parameters.execution_providers,
+                &mut session_builder,
+            )?;
 
-        let session = Session::builder()?
-            .with_execution_providers([tensor_rt, cuda])?
+        let session = session_builder
             .with_optimization_level(GraphOptimizationLevel::Level3)?
             .with_intra_threads(2)?
             .commit_from_file(neural_network_folder.join(MODEL_FILE_NAME))?;
 
-        Ok(Self { session })
+        Ok(Self {
+            session,
+            execution_provider,
+            input_downscaling: InputDownscaling::default(),
+        })
     }
 
     pub fn cycle(&mut self, mut context: CycleContext) -> Result<MainOutputs> {
@@ -123,6 +145,11 @@ impl ObjectDetection {
             );
         }
 
+        let downscaling_factor =
+            self.input_downscaling
+                .factor_for(image.width, image.height, INPUT_DIMENSION_ALIGNMENT);
+        let image = downscale_nv12(image, downscaling_factor)?;
+
         let nv12_data = ArrayView3::from_shape(
             [image.height as usize / 2, image.width as usize / 2, 6],
             &image.data,
@@ -135,6 +162,11 @@ impl ObjectDetection {
             .run(inputs!["raw_bytes_input" => TensorRef::from_array_view(nv12_data)?])?;
 
         let inference_duration = inference_start.elapsed();
+        self.input_downscaling.update(
+            downscaling_factor,
+            inference_duration,
+            &context.parameters.input_downscaling,
+        );
         let post_processing_start = Instant::now();
 
         let outputs = extract_outputs(&outputs)?;
@@ -144,6 +176,7 @@ impl ObjectDetection {
                 .parameters
                 .object_detection_parameters
                 .confidence_threshold,
+            downscaling_factor as f32,
         )?;
         let candidate_human_poses = extract_candidate_pose_detections(
             &outputs,
@@ -151,6 +184,7 @@ impl ObjectDetection {
                 .parameters
                 .pose_detection_parameters
                 .confidence_threshold,
+            downscaling_factor as f32,
         )?;
 
         let post_processing_duration = post_processing_start.elapsed();
@@ -185,6 +219,13 @@ impl ObjectDetection {
             .non_maximum_suppression_duration
             .fill_if_subscribed(|| non_maximum_suppression_duration);
 
+        context
+            .inference_status
+            .fill_if_subscribed(|| InferenceStatus {
+                execution_provider: self.execution_provider,
+                input_downscaling_factor: downscaling_factor,
+            });
+
         Ok(MainOutputs {
             detected_objects: detected_objects.into(),
             detected_poses: detected_poses.into(),
@@ -223,6 +264,7 @@ fn extract_outputs<'a>(outputs: &'a SessionOutputs<'a>) -> Result<ModelOutputs<'
 fn extract_candidate_object_detections(
     outputs: &ModelOutputs,
     confidence_threshold: f32,
+    scale: f32,
 ) -> Result<Vec<Object<RobocupObjectLabel>>> {
     Ok(outputs
         .objects
@@ -233,13 +275,14 @@ fn extract_candidate_object_detections(
                 return None;
             }
 
-            let object_values: [f32; NUMBER_OF_VALUES_PER_OBJECT] = row
+            let mut object_values: [f32; NUMBER_OF_VALUES_PER_OBJECT] = row
                 .as_slice()
                 .expect("slice is not contiguous")
                 .try_into()
                 .unwrap_or_else(|_| {
                     panic!("slice is not of length {}", NUMBER_OF_VALUES_PER_OBJECT)
                 });
+            scale_bounding_box(&mut object_values, scale);
 
             Some(Object::from(object_values))
         })
@@ -249,6 +292,7 @@ fn extract_candidate_object_detections(
 fn extract_candidate_pose_detections(
     outputs: &ModelOutputs,
     confidence_threshold: f32,
+    scale: f32,
 ) -> Result<Vec<Pose<YOLOObjectLabel>>> {
     Ok(outputs
         .poses
@@ -259,17 +303,29 @@ fn extract_candidate_pose_detections(
                 return None;
             }
 
-            let pose_values: [f32; NUMBER_OF_VALUES_PER_POSE] = row
+            let mut pose_values: [f32; NUMBER_OF_VALUES_PER_POSE] = row
                 .as_slice()
                 .expect("slice is not contiguous")
                 .try_into()
                 .unwrap_or_else(|_| panic!("slice is not of length {}", NUMBER_OF_VALUES_PER_POSE));
+            scale_bounding_box(&mut pose_values, scale);
+            for keypoint in pose_values[POSE_KEYPOINT_OFFSET..].chunks_exact_mut(3) {
+                keypoint[0] *= scale;
+                keypoint[1] *= scale;
+            }
 
             Some(Pose::from(&pose_values))
         })
         .collect())
 }
 
+/// Maps the corners of a detection from the downscaled network input back to image pixels.
+fn scale_bounding_box(values: &mut [f32], scale: f32) {
+    for corner_coordinate in &mut values[..4] {
+        *corner_coordinate *= scale;
+    }
+}
+
 trait HasBoundingBox {
     fn bounding_box(&self) -> &BoundingBox;
 }
@@ -0,0 +1,221 @@
+use std::{borrow::Cow, collections::BTreeSet, time::Duration};
+
+use color_eyre::{Result, eyre::bail};
+use ros2::sensor_msgs::image::Image;
+use types::execution_provider::InputDownscalingParameters;
+
+/// Adapts a power-of-two input downscaling factor to the measured inference duration.
+///
+/// The first inference of every factor is not measured, since a new input shape may make the
+/// execution provider build a new engine, e.g. TensorRT, which takes far longer than inference.
+#[derive(Clone, Debug)]
+pub struct InputDownscaling {
+    factor: usize,
+    warmed_up_factors: BTreeSet<usize>,
+}
+
+impl Default for InputDownscaling {
+    fn default() -> Self {
+        Self {
+            factor: 1,
+            warmed_up_factors: BTreeSet::new(),
+        }
+    }
+}
+
+impl InputDownscaling {
+    pub fn factor(&self) -> usize {
+        self.factor
+    }
+
+    /// Returns the largest factor not exceeding the current one which keeps both image
+    /// dimensions multiples of `alignment` after downscaling.
+    pub fn factor_for(&self, width: u32, height: u32, alignment: u32) -> usize {
+        let is_aligned = |factor: usize| {
+            let alignment = factor * alignment as usize;
+            (width as usize).is_multiple_of(alignment)
+                && (height as usize).is_multiple_of(alignment)
+        };
+        let mut factor = self.factor;
+        while factor > 1 && !is_aligned(factor) {
+            factor /= 2;
+        }
+        factor
+    }
+
+    /// Adapts the factor to the duration of an inference with inputs downscaled by `used_factor`.
+    pub fn update(
+        &mut self,
+        used_factor: usize,
+        inference_duration: Duration,
+        parameters: &InputDownscalingParameters,
+    ) {
+        let maximum_factor = parameters.maximum_factor.max(1);
+        let is_warm_up = self.warmed_up_factors.insert(used_factor);
+        self.factor = if !parameters.enable {
+            1
+        } else if is_warm_up || used_factor != self.factor {
+            self.factor.min(maximum_factor)
+        } else if inference_duration > parameters.target_inference_duration {
+            (self.factor * 2).min(maximum_factor)
+        } else if self.factor > 1 && inference_duration * 4 < parameters.target_inference_duration {
+            // halving the factor quadruples the number of pixels
+            self.factor / 2
+        } else {
+            self.factor.min(maximum_factor)
+        };
+    }
+}
+
+/// Subsamples an NV12 image by an integer factor in both dimensions, borrowing the image for
+/// factors of one.
+pub fn downscale_nv12(image: &Image, factor: usize) -> Result<Cow<'_, Image>> {
+    if image.encoding != "nv12" {
+        bail!("unsupported image encoding: {}", image.encoding);
+    }
+    if factor <= 1 {
+        return Ok(Cow::Borrowed(image));
+    }
+    let width = image.width as usize;
+    let height = image.height as usize;
+    if !width.is_multiple_of(2 * factor) || !height.is_multiple_of(2 * factor) {
+        bail!("cannot downscale {width}x{height} image by {factor}");
+    }
+    if image.data.len() != width * height * 3 / 2 {
+        bail!(
+            "nv12 image of {width}x{height} has {} instead of {} bytes",
+            image.data.len(),
+            width * height * 3 / 2
+        );
+    }
+
+    let (luminance, chrominance) = image.data.split_at(width * height);
+    let scaled_width = width / factor;
+    let scaled_height = height / factor;
+    let mut data = Vec::with_capacity(scaled_width * scaled_height * 3 / 2);
+    for y in 0..scaled_height {
+        let row = &luminance[y * factor * width..];
+        data.extend((0..scaled_width).map(|x| row[x * factor]));
+    }
+    // chrominance rows are `width` bytes of interleaved U and V samples at half resolution
+    for y in 0..scaled_height / 2 {
+        let row = &chrominance[y * factor * width..];
+        for x in 0..scaled_width / 2 {
+            data.extend_from_slice(&row[x * factor * 2..x * factor * 2 + 2]);
+        }
+    }
+
+    Ok(Cow::Owned(Image {
+        height: scaled_height as u32,
+        width: scaled_width as u32,
+        step: scaled_width as u32,
+        data: data.into(),
+        encoding: image.encoding.clone(),
+        header: image.header.clone(),
+        is_bigendian: image.is_bigendian,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parameters() -> InputDownscalingParameters {
+        InputDownscalingParameters {
+            enable: true,
+            maximum_factor: 4,
+            target_inference_duration: Duration::from_millis(100),
+        }
+    }
+
+    #[test]
+    fn factor_follows_inference_duration() {
+        let parameters = parameters();
+        let mut downscaling = InputDownscaling::default();
+        let mut update = |duration| {
+            let factor = downscaling.factor();
+            downscaling.update(factor, Duration::from_millis(duration), &parameters);
+            downscaling.factor()
+        };
+
+        // the first inference of each factor includes building the engine
+        assert_eq!(update(5000), 1);
+        assert_eq!(update(300), 2);
+        assert_eq!(update(5000), 2);
+        assert_eq!(update(150), 4);
+        assert_eq!(update(5000), 4);
+        assert_eq!(update(150), 4);
+        assert_eq!(update(40), 4);
+        assert_eq!(update(20), 2);
+        assert_eq!(update(20), 1);
+    }
+
+    #[test]
+    fn disabled_downscaling_resets_the_factor() {
+        let mut downscaling = InputDownscaling {
+            factor: 4,
+            warmed_up_factors: BTreeSet::from([4]),
+        };
+
+        downscaling.update(
+            4,
+            Duration::from_millis(300),
+            &InputDownscalingParameters {
+                enable: false,
+                ..parameters()
+            },
+        );
+
+        assert_eq!(downscaling.factor(), 1);
+    }
+
+    #[test]
+    fn durations_of_other_factors_are_ignored() {
+        let parameters = parameters();
+        let mut downscaling = InputDownscaling::default();
+        downscaling.update(1, Duration::ZERO, &parameters);
+        downscaling.update(1, Duration::from_millis(300), &parameters);
+        assert_eq!(downscaling.factor(), 2);
+
+        // the image could not be downscaled by 2, so this measurement says nothing about 2
+        downscaling.update(1, Duration::from_millis(300), &parameters);
+
+        assert_eq!(downscaling.factor(), 2);
+    }
+
+    #[test]
+    fn factor_keeps_dimensions_aligned() {
+        let downscaling = InputDownscaling {
+            factor: 4,
+            ..Default::default()
+        };
+        assert_eq!(downscaling.factor_for(1024, 512, 32), 4);
+        assert_eq!(downscaling.factor_for(640, 480, 32), 1);
+        assert_eq!(downscaling.factor_for(640, 448, 32), 2);
+    }
+
+    #[test]
+    fn nv12_is_subsampled() {
+        let width = 4;
+        let height = 4;
+        let luminance = (0..16).collect::<Vec<u8>>();
+        let chrominance = [100, 101, 102, 103, 104, 105, 106, 107];
+        let image = Image {
+            width,
+            height,
+            step: width,
+            encoding: "nv12".to_string(),
+            data: luminance.into_iter().chain(chrominance).collect(),
+            ..Default::default()
+        };
+
+        let scaled = downscale_nv12(&image, 2).unwrap();
+
+        assert_eq!((scaled.width, scaled.height), (2, 2));
+        assert_eq!(&*scaled.data, &[0, 2, 8, 10, 100, 101]);
+        assert!(matches!(
+            downscale_nv12(&image, 1).unwrap(),
+            Cow::Borrowed(_)
+        ));
+    }
+}
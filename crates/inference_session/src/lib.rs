@@ -0,0 +1,60 @@
+use color_eyre::{Result, eyre::bail};
+use ort::{
+    execution_providers::{
+        CPUExecutionProvider, CUDAExecutionProvider, ExecutionProvider, TensorRTExecutionProvider,
+    },
+    session::builder::SessionBuilder,
+};
+use types::execution_provider::ExecutionProviderKind;
+
+pub mod downscaling;
+
+/// Execution providers an ONNX session may run on, tried in the order given by the parameters.
+#[derive(Clone, Debug, Default)]
+pub struct ExecutionProviderChain {
+    tensor_rt: TensorRTExecutionProvider,
+    cuda: CUDAExecutionProvider,
+    cpu: CPUExecutionProvider,
+}
+
+impl ExecutionProviderChain {
+    pub fn with_tensor_rt(mut self, tensor_rt: TensorRTExecutionProvider) -> Self {
+        self.tensor_rt = tensor_rt;
+        self
+    }
+
+    fn execution_provider(&self, kind: ExecutionProviderKind) -> &dyn ExecutionProvider {
+        match kind {
+            ExecutionProviderKind::TensorRt => &self.tensor_rt,
+            ExecutionProviderKind::Cuda => &self.cuda,
+            ExecutionProviderKind::Cpu => &self.cpu,
+        }
+    }
+
+    /// Registers every available execution provider of `kinds` on the session and returns the one
+    /// ONNX Runtime will prefer, i.e. the first which could be registered.
+    ///
+    /// Providers which are not compiled into the loaded ONNX Runtime or fail to register (e.g.
+    /// CUDA without a GPU) are skipped. If none of them remains, this is an error instead of the
+    /// implicit CPU fallback of ONNX Runtime, so leaving out `Cpu` requires hardware acceleration.
+    pub fn register(
+        &self,
+        kinds: &[ExecutionProviderKind],
+        session_builder: &mut SessionBuilder,
+    ) -> Result<ExecutionProviderKind> {
+        let mut selected = None;
+        for &kind in kinds {
+            let execution_provider = self.execution_provider(kind);
+            if !execution_provider.supported_by_platform() || !execution_provider.is_available()? {
+                continue;
+            }
+            if execution_provider.register(session_builder).is_ok() {
+                selected.get_or_insert(kind);
+            }
+        }
+        match selected {
+            Some(kind) => Ok(kind),
+            None => bail!("none of the execution providers {kinds:?} is available"),
+        }
+    }
+}
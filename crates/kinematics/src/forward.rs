@@ -62,6 +62,13 @@ pub fn left_forearm_to_left_upper_arm(
     )
 }
 
+pub fn left_forearm_to_robot(angles: &ArmJoints<f32>) -> Isometry3<LeftForearm, Robot> {
+    left_inner_shoulder_to_robot(angles)
+        * left_outer_shoulder_to_left_inner_shoulder(angles)
+        * left_upper_arm_to_left_outer_shoulder(angles)
+        * left_forearm_to_left_upper_arm(angles)
+}
+
 // right arm
 pub fn right_inner_shoulder_to_robot(
     angles: &ArmJoints<f32>,
@@ -98,6 +105,14 @@ pub fn right_forearm_to_right_upper_arm(
         Orientation3::new(Vector3::z_axis() * angles.elbow),
     )
 }
+
+pub fn right_forearm_to_robot(angles: &ArmJoints<f32>) -> Isometry3<RightForearm, Robot> {
+    right_inner_shoulder_to_robot(angles)
+        * right_outer_shoulder_to_right_inner_shoulder(angles)
+        * right_upper_arm_to_right_outer_shoulder(angles)
+        * right_forearm_to_right_upper_arm(angles)
+}
+
 // left leg
 pub fn left_pelvis_to_robot(angles: &LegJoints<f32>) -> Isometry3<LeftPelvis, Robot> {
     Isometry3::from_parts(
@@ -134,9 +149,8 @@ pub fn left_ankle_to_left_tibia(angles: &LegJoints<f32>) -> Isometry3<LeftAnkle,
     )
 }
 
-// TODO: how is ankle down/up moving?
 pub fn left_foot_to_left_ankle(angles: &LegJoints<f32>) -> Isometry3<LeftFoot, LeftAnkle> {
-    Isometry3::from_rotation(Vector3::x_axis() * angles.ankle_up)
+    Isometry3::from_rotation(Vector3::x_axis() * angles.ankle_down)
 }
 
 pub fn left_sole_to_robot(angles: &LegJoints<f32>) -> Isometry3<LeftSole, Robot> {
@@ -167,7 +181,7 @@ pub fn right_hip_to_right_pelvis(angles: &LegJoints<f32>) -> Isometry3<RightHip,
 pub fn right_thigh_to_right_hip(angles: &LegJoints<f32>) -> Isometry3<RightThigh, RightHip> {
     Isometry3::from_parts(
         RobotDimensions::RIGHT_HIP_TO_RIGHT_THIGH,
-        Orientation3::new(Vector3::y_axis() * angles.hip_yaw),
+        Orientation3::new(Vector3::z_axis() * angles.hip_yaw),
     )
 }
 
@@ -198,3 +212,81 @@ pub fn right_sole_to_robot(angles: &LegJoints<f32>) -> Isometry3<RightSole, Robo
         * right_foot_to_right_ankle(angles)
         * Isometry3::from(RobotDimensions::RIGHT_FOOT_TO_RIGHT_SOLE)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(actual: f32, expected: f32) {
+        assert!(
+            (actual - expected).abs() < 1e-5,
+            "{actual} is not close to {expected}"
+        );
+    }
+
+    #[test]
+    fn mirrored_leg_angles_result_in_mirrored_soles() {
+        let angles = LegJoints {
+            hip_pitch: -0.6,
+            hip_roll: 0.1,
+            hip_yaw: 0.2,
+            knee: 1.1,
+            ankle_up: -0.4,
+            ankle_down: -0.1,
+        };
+        let mirrored = LegJoints {
+            hip_roll: -angles.hip_roll,
+            hip_yaw: -angles.hip_yaw,
+            ankle_down: -angles.ankle_down,
+            ..angles
+        };
+
+        let left_sole = left_sole_to_robot(&angles).translation();
+        let right_sole = right_sole_to_robot(&mirrored).translation();
+
+        assert_close(right_sole.x(), left_sole.x());
+        assert_close(right_sole.y(), -left_sole.y());
+        assert_close(right_sole.z(), left_sole.z());
+    }
+
+    #[test]
+    fn ankle_up_pitches_and_ankle_down_rolls_the_foot() {
+        let straight = left_sole_to_robot(&LegJoints::default()).translation();
+
+        let pitched = left_sole_to_robot(&LegJoints {
+            ankle_up: 0.3,
+            ..Default::default()
+        })
+        .translation();
+        assert!(pitched.x() < straight.x());
+        assert_close(pitched.y(), straight.y());
+
+        let rolled = left_sole_to_robot(&LegJoints {
+            ankle_down: 0.3,
+            ..Default::default()
+        })
+        .translation();
+        assert_close(rolled.x(), straight.x());
+        assert!(rolled.y() > straight.y());
+    }
+
+    #[test]
+    fn hip_yaw_turns_the_right_leg_about_the_vertical_axis() {
+        let right_tibia = |angles: &LegJoints<f32>| {
+            (right_pelvis_to_robot(angles)
+                * right_hip_to_right_pelvis(angles)
+                * right_thigh_to_right_hip(angles)
+                * right_tibia_to_right_thigh(angles))
+            .translation()
+        };
+        let straight = right_tibia(&LegJoints::default());
+
+        let yawed = right_tibia(&LegJoints {
+            hip_yaw: 0.4,
+            ..Default::default()
+        });
+
+        assert_close(yawed.z(), straight.z());
+        assert!(yawed.y() < straight.y());
+    }
+}
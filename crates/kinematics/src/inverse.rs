@@ -0,0 +1,472 @@
+use std::array;
+
+use coordinate_systems::{LeftForearm, LeftSole, RightForearm, RightSole, Robot};
+use linear_algebra::{Isometry3, Point3};
+use nalgebra::{DMatrix, DVector};
+use path_serde::{PathDeserialize, PathIntrospect, PathSerialize};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    forward::{
+        left_forearm_to_robot, left_sole_to_robot, right_forearm_to_robot, right_sole_to_robot,
+    },
+    joints::{arm::ArmJoints, leg::LegJoints, limits::JointLimits},
+};
+
+const JACOBIAN_STEP: f32 = 1e-3;
+
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    PartialEq,
+    Serialize,
+    Deserialize,
+    PathSerialize,
+    PathDeserialize,
+    PathIntrospect,
+    ros_z::Message,
+)]
+pub struct InverseKinematicsParameters {
+    pub maximum_number_of_iterations: usize,
+    /// Damping of the least squares step, keeps steps bounded close to singular configurations
+    /// like a fully stretched knee
+    pub damping: f32,
+    /// Maximum change of a single joint per iteration in radians
+    pub maximum_step: f32,
+    /// Meters of position error that are considered equivalent to one radian of orientation error
+    pub orientation_weight: f32,
+    pub position_tolerance: f32,
+    pub orientation_tolerance: f32,
+}
+
+impl Default for InverseKinematicsParameters {
+    fn default() -> Self {
+        Self {
+            maximum_number_of_iterations: 100,
+            damping: 0.01,
+            maximum_step: 0.2,
+            orientation_weight: 0.1,
+            position_tolerance: 0.0005,
+            orientation_tolerance: 0.005,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct InverseKinematicsSolution<Joints> {
+    pub joints: Joints,
+    /// Remaining distance between the reached and the requested position in meters
+    pub position_error: f32,
+    /// Remaining angle between the reached and the requested orientation in radians
+    pub orientation_error: f32,
+    pub is_reached: bool,
+}
+
+pub fn left_leg_angles(
+    sole_to_robot: Isometry3<LeftSole, Robot>,
+    seed: LegJoints<f32>,
+    limits: &JointLimits,
+    parameters: &InverseKinematicsParameters,
+) -> InverseKinematicsSolution<LegJoints<f32>> {
+    let (angles, error) = solve(
+        leg_to_array(seed),
+        leg_to_array(limits.minimum.left_leg),
+        leg_to_array(limits.maximum.left_leg),
+        |angles| PoseError::between(sole_to_robot, left_sole_to_robot(&leg_from_array(*angles))),
+        parameters,
+    );
+    error.into_solution(leg_from_array(angles), parameters)
+}
+
+pub fn right_leg_angles(
+    sole_to_robot: Isometry3<RightSole, Robot>,
+    seed: LegJoints<f32>,
+    limits: &JointLimits,
+    parameters: &InverseKinematicsParameters,
+) -> InverseKinematicsSolution<LegJoints<f32>> {
+    let (angles, error) = solve(
+        leg_to_array(seed),
+        leg_to_array(limits.minimum.right_leg),
+        leg_to_array(limits.maximum.right_leg),
+        |angles| PoseError::between(sole_to_robot, right_sole_to_robot(&leg_from_array(*angles))),
+        parameters,
+    );
+    error.into_solution(leg_from_array(angles), parameters)
+}
+
+/// The arm has only four joints, so only the position of a point on the forearm, e.g. the hand,
+/// is controlled. The remaining freedom is resolved towards the seed.
+pub fn left_arm_angles(
+    target: Point3<Robot>,
+    point_in_forearm: Point3<LeftForearm>,
+    seed: ArmJoints<f32>,
+    limits: &JointLimits,
+    parameters: &InverseKinematicsParameters,
+) -> InverseKinematicsSolution<ArmJoints<f32>> {
+    let (angles, error) = solve(
+        arm_to_array(seed),
+        arm_to_array(limits.minimum.left_arm),
+        arm_to_array(limits.maximum.left_arm),
+        |angles| {
+            PoseError::between_points(
+                target,
+                left_forearm_to_robot(&arm_from_array(*angles)) * point_in_forearm,
+            )
+        },
+        parameters,
+    );
+    error.into_solution(arm_from_array(angles), parameters)
+}
+
+/// See [`left_arm_angles`].
+pub fn right_arm_angles(
+    target: Point3<Robot>,
+    point_in_forearm: Point3<RightForearm>,
+    seed: ArmJoints<f32>,
+    limits: &JointLimits,
+    parameters: &InverseKinematicsParameters,
+) -> InverseKinematicsSolution<ArmJoints<f32>> {
+    let (angles, error) = solve(
+        arm_to_array(seed),
+        arm_to_array(limits.minimum.right_arm),
+        arm_to_array(limits.maximum.right_arm),
+        |angles| {
+            PoseError::between_points(
+                target,
+                right_forearm_to_robot(&arm_from_array(*angles)) * point_in_forearm,
+            )
+        },
+        parameters,
+    );
+    error.into_solution(arm_from_array(angles), parameters)
+}
+
+#[derive(Clone, Copy, Debug)]
+struct PoseError {
+    position: nalgebra::Vector3<f32>,
+    orientation: nalgebra::Vector3<f32>,
+}
+
+impl PoseError {
+    fn between<Frame>(target: Isometry3<Frame, Robot>, current: Isometry3<Frame, Robot>) -> Self {
+        Self {
+            position: target.inner.translation.vector - current.inner.translation.vector,
+            orientation: (target.inner.rotation * current.inner.rotation.inverse()).scaled_axis(),
+        }
+    }
+
+    fn between_points(target: Point3<Robot>, current: Point3<Robot>) -> Self {
+        Self {
+            position: (target - current).inner,
+            orientation: nalgebra::Vector3::zeros(),
+        }
+    }
+
+    fn is_within_tolerance(&self, parameters: &InverseKinematicsParameters) -> bool {
+        self.position.norm() <= parameters.position_tolerance
+            && self.orientation.norm() <= parameters.orientation_tolerance
+    }
+
+    fn weighted(&self, orientation_weight: f32) -> DVector<f32> {
+        DVector::from_iterator(
+            6,
+            self.position.iter().copied().chain(
+                self.orientation
+                    .iter()
+                    .map(|value| value * orientation_weight),
+            ),
+        )
+    }
+
+    fn into_solution<Joints>(
+        self,
+        joints: Joints,
+        parameters: &InverseKinematicsParameters,
+    ) -> InverseKinematicsSolution<Joints> {
+        InverseKinematicsSolution {
+            joints,
+            position_error: self.position.norm(),
+            orientation_error: self.orientation.norm(),
+            is_reached: self.is_within_tolerance(parameters),
+        }
+    }
+}
+
+/// Damped least squares iteration with a numerically differentiated Jacobian. Joint limits are
+/// enforced by clamping after every step.
+fn solve<const N: usize>(
+    seed: [f32; N],
+    minimum: [f32; N],
+    maximum: [f32; N],
+    pose_error: impl Fn(&[f32; N]) -> PoseError,
+    parameters: &InverseKinematicsParameters,
+) -> ([f32; N], PoseError) {
+    let clamp = |angles: [f32; N]| -> [f32; N] {
+        array::from_fn(|index| angles[index].clamp(minimum[index], maximum[index]))
+    };
+
+    let mut angles = clamp(seed);
+    let mut error = pose_error(&angles);
+    for _ in 0..parameters.maximum_number_of_iterations {
+        if error.is_within_tolerance(parameters) {
+            break;
+        }
+        let residual = error.weighted(parameters.orientation_weight);
+        let mut jacobian = DMatrix::zeros(6, N);
+        for joint in 0..N {
+            let mut perturbed = angles;
+            perturbed[joint] += JACOBIAN_STEP;
+            let derivative = (pose_error(&perturbed).weighted(parameters.orientation_weight)
+                - &residual)
+                / JACOBIAN_STEP;
+            jacobian.set_column(joint, &derivative);
+        }
+
+        let Some(mut step) = damped_least_squares_step(&jacobian, &residual, parameters.damping)
+        else {
+            break;
+        };
+        // joints pushed against their limits would block the remaining ones, so they are
+        // excluded and the step is recomputed without them
+        for _ in 0..N {
+            let blocked_joints: Vec<_> = (0..N)
+                .filter(|&joint| {
+                    (angles[joint] <= minimum[joint] && step[joint] < 0.0)
+                        || (angles[joint] >= maximum[joint] && step[joint] > 0.0)
+                })
+                .collect();
+            if blocked_joints.is_empty() {
+                break;
+            }
+            for joint in blocked_joints {
+                jacobian.column_mut(joint).fill(0.0);
+            }
+            let Some(unblocked_step) =
+                damped_least_squares_step(&jacobian, &residual, parameters.damping)
+            else {
+                break;
+            };
+            step = unblocked_step;
+        }
+        let largest_step = step.amax();
+        if largest_step > parameters.maximum_step {
+            step *= parameters.maximum_step / largest_step;
+        }
+
+        angles = clamp(array::from_fn(|index| angles[index] + step[index]));
+        error = pose_error(&angles);
+    }
+    (angles, error)
+}
+
+fn damped_least_squares_step(
+    jacobian: &DMatrix<f32>,
+    residual: &DVector<f32>,
+    damping: f32,
+) -> Option<DVector<f32>> {
+    let damped = jacobian * jacobian.transpose()
+        + DMatrix::identity(jacobian.nrows(), jacobian.nrows()) * damping.powi(2);
+    let cholesky = damped.cholesky()?;
+    Some(-(jacobian.transpose() * cholesky.solve(residual)))
+}
+
+fn leg_to_array(joints: LegJoints<f32>) -> [f32; 6] {
+    [
+        joints.hip_pitch,
+        joints.hip_roll,
+        joints.hip_yaw,
+        joints.knee,
+        joints.ankle_up,
+        joints.ankle_down,
+    ]
+}
+
+fn leg_from_array(
+    [hip_pitch, hip_roll, hip_yaw, knee, ankle_up, ankle_down]: [f32; 6],
+) -> LegJoints<f32> {
+    LegJoints {
+        hip_pitch,
+        hip_roll,
+        hip_yaw,
+        knee,
+        ankle_up,
+        ankle_down,
+    }
+}
+
+fn arm_to_array(joints: ArmJoints<f32>) -> [f32; 4] {
+    [
+        joints.shoulder_pitch,
+        joints.shoulder_roll,
+        joints.shoulder_yaw,
+        joints.elbow,
+    ]
+}
+
+fn arm_from_array(
+    [shoulder_pitch, shoulder_roll, shoulder_yaw, elbow]: [f32; 4],
+) -> ArmJoints<f32> {
+    ArmJoints {
+        shoulder_pitch,
+        shoulder_roll,
+        shoulder_yaw,
+        elbow,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use linear_algebra::{distance, point};
+
+    use super::*;
+
+    fn bent_leg() -> LegJoints<f32> {
+        LegJoints {
+            hip_pitch: -0.3,
+            knee: 0.6,
+            ankle_up: -0.3,
+            ..Default::default()
+        }
+    }
+
+    fn leg_poses() -> [LegJoints<f32>; 4] {
+        [
+            bent_leg(),
+            LegJoints {
+                hip_pitch: -0.6,
+                hip_roll: 0.1,
+                hip_yaw: 0.2,
+                knee: 1.1,
+                ankle_up: -0.4,
+                ankle_down: -0.1,
+            },
+            LegJoints {
+                hip_pitch: -0.9,
+                hip_roll: 0.0,
+                hip_yaw: -0.3,
+                knee: 0.4,
+                ankle_up: 0.2,
+                ankle_down: 0.15,
+            },
+            LegJoints {
+                hip_pitch: 0.2,
+                hip_roll: 0.05,
+                hip_yaw: 0.0,
+                knee: 0.3,
+                ankle_up: -0.5,
+                ankle_down: 0.0,
+            },
+        ]
+    }
+
+    #[test]
+    fn leg_angles_reproduce_forward_kinematics() {
+        let limits = JointLimits::default();
+        let parameters = InverseKinematicsParameters::default();
+
+        for pose in leg_poses() {
+            let target = left_sole_to_robot(&pose);
+            let solution = left_leg_angles(target, bent_leg(), &limits, &parameters);
+            assert!(solution.is_reached, "{pose:?}: {solution:?}");
+            let reached = left_sole_to_robot(&solution.joints);
+            assert!(distance(reached.translation(), target.translation()) < 0.001);
+
+            let mirrored = LegJoints {
+                hip_roll: -pose.hip_roll,
+                hip_yaw: -pose.hip_yaw,
+                ankle_down: -pose.ankle_down,
+                ..pose
+            };
+            let target = right_sole_to_robot(&mirrored);
+            let solution = right_leg_angles(target, bent_leg(), &limits, &parameters);
+            assert!(solution.is_reached, "{mirrored:?}: {solution:?}");
+            let reached = right_sole_to_robot(&solution.joints);
+            assert!(distance(reached.translation(), target.translation()) < 0.001);
+        }
+    }
+
+    #[test]
+    fn arm_angles_reproduce_forward_kinematics() {
+        let limits = JointLimits::default();
+        let parameters = InverseKinematicsParameters::default();
+        let hand = point![0.0, 0.2, 0.0];
+        let poses = [
+            ArmJoints {
+                shoulder_pitch: -0.5,
+                shoulder_roll: -1.2,
+                shoulder_yaw: 0.3,
+                elbow: -0.8,
+            },
+            ArmJoints {
+                shoulder_pitch: -1.2,
+                shoulder_roll: -0.4,
+                shoulder_yaw: -0.5,
+                elbow: -1.5,
+            },
+        ];
+        let seed = ArmJoints {
+            shoulder_roll: -1.0,
+            elbow: -0.5,
+            ..Default::default()
+        };
+
+        for pose in poses {
+            let target = left_forearm_to_robot(&pose) * hand;
+            let solution = left_arm_angles(target, hand, seed, &limits, &parameters);
+            assert!(solution.is_reached, "{pose:?}: {solution:?}");
+            assert!(distance(left_forearm_to_robot(&solution.joints) * hand, target) < 0.001);
+        }
+    }
+
+    #[test]
+    fn right_arm_angles_reproduce_forward_kinematics() {
+        let limits = JointLimits::default();
+        let parameters = InverseKinematicsParameters::default();
+        let hand = point![0.0, -0.2, 0.0];
+        let poses = [
+            ArmJoints {
+                shoulder_pitch: -0.5,
+                shoulder_roll: 1.2,
+                shoulder_yaw: 0.3,
+                elbow: 0.8,
+            },
+            ArmJoints {
+                shoulder_pitch: -1.2,
+                shoulder_roll: 0.4,
+                shoulder_yaw: -0.5,
+                elbow: 1.5,
+            },
+        ];
+        let seed = ArmJoints {
+            shoulder_roll: 1.0,
+            elbow: 0.5,
+            ..Default::default()
+        };
+
+        for pose in poses {
+            let target = right_forearm_to_robot(&pose) * hand;
+            let solution = right_arm_angles(target, hand, seed, &limits, &parameters);
+            assert!(solution.is_reached, "{pose:?}: {solution:?}");
+            assert!(distance(right_forearm_to_robot(&solution.joints) * hand, target) < 0.001);
+        }
+    }
+
+    #[test]
+    fn unreachable_targets_respect_joint_limits() {
+        let limits = JointLimits::default();
+        let parameters = InverseKinematicsParameters::default();
+        let target = Isometry3::from_translation(0.0, 0.1, -2.0);
+
+        let solution = left_leg_angles(target, bent_leg(), &limits, &parameters);
+
+        assert!(!solution.is_reached);
+        assert!(solution.position_error > 1.0);
+        assert_eq!(
+            solution
+                .joints
+                .clamp(limits.minimum.left_leg, limits.maximum.left_leg),
+            solution.joints
+        );
+    }
+}
@@ -2,6 +2,7 @@ pub mod arm;
 pub mod body;
 pub mod head;
 pub mod leg;
+pub mod limits;
 pub mod mirror;
 
 use std::{
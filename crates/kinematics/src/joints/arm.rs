@@ -67,6 +67,21 @@ where
     }
 }
 
+impl ArmJoints<f32> {
+    pub fn clamp(self, min: Self, max: Self) -> Self {
+        Self {
+            shoulder_pitch: self
+                .shoulder_pitch
+                .clamp(min.shoulder_pitch, max.shoulder_pitch),
+            shoulder_roll: self
+                .shoulder_roll
+                .clamp(min.shoulder_roll, max.shoulder_roll),
+            shoulder_yaw: self.shoulder_yaw.clamp(min.shoulder_yaw, max.shoulder_yaw),
+            elbow: self.elbow.clamp(min.elbow, max.elbow),
+        }
+    }
+}
+
 impl<T> IntoIterator for ArmJoints<T> {
     type Item = T;
 
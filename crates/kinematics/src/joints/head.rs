@@ -57,6 +57,15 @@ where
     }
 }
 
+impl HeadJoints<f32> {
+    pub fn clamp(self, min: Self, max: Self) -> Self {
+        Self {
+            yaw: self.yaw.clamp(min.yaw, max.yaw),
+            pitch: self.pitch.clamp(min.pitch, max.pitch),
+        }
+    }
+}
+
 impl<T> IntoIterator for HeadJoints<T> {
     type Item = T;
 
@@ -0,0 +1,125 @@
+use path_serde::{PathDeserialize, PathIntrospect, PathSerialize};
+use serde::{Deserialize, Serialize};
+
+use super::{Joints, arm::ArmJoints, head::HeadJoints, leg::LegJoints};
+
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    PartialEq,
+    Serialize,
+    Deserialize,
+    PathSerialize,
+    PathDeserialize,
+    PathIntrospect,
+    ros_z::Message,
+)]
+pub struct JointLimits {
+    pub minimum: Joints<f32>,
+    pub maximum: Joints<f32>,
+}
+
+impl JointLimits {
+    pub fn clamp(&self, joints: Joints<f32>) -> Joints<f32> {
+        Joints {
+            head: joints.head.clamp(self.minimum.head, self.maximum.head),
+            left_arm: joints
+                .left_arm
+                .clamp(self.minimum.left_arm, self.maximum.left_arm),
+            right_arm: joints
+                .right_arm
+                .clamp(self.minimum.right_arm, self.maximum.right_arm),
+            left_leg: joints
+                .left_leg
+                .clamp(self.minimum.left_leg, self.maximum.left_leg),
+            right_leg: joints
+                .right_leg
+                .clamp(self.minimum.right_leg, self.maximum.right_leg),
+        }
+    }
+
+    pub fn contains(&self, joints: Joints<f32>) -> bool {
+        joints
+            .into_iter()
+            .zip(self.minimum)
+            .zip(self.maximum)
+            .all(|((position, minimum), maximum)| (minimum..=maximum).contains(&position))
+    }
+}
+
+/// Position limits of the Booster T1 as stated in its robot description
+impl Default for JointLimits {
+    fn default() -> Self {
+        Self {
+            minimum: Joints {
+                head: HeadJoints {
+                    yaw: -1.57,
+                    pitch: -0.35,
+                },
+                left_arm: ArmJoints {
+                    shoulder_pitch: -3.31,
+                    shoulder_roll: -1.74,
+                    shoulder_yaw: -2.27,
+                    elbow: -2.44,
+                },
+                right_arm: ArmJoints {
+                    shoulder_pitch: -3.31,
+                    shoulder_roll: -1.57,
+                    shoulder_yaw: -2.27,
+                    elbow: 0.0,
+                },
+                left_leg: LegJoints {
+                    hip_pitch: -1.8,
+                    hip_roll: -0.2,
+                    hip_yaw: -1.0,
+                    knee: 0.0,
+                    ankle_up: -0.87,
+                    ankle_down: -0.44,
+                },
+                right_leg: LegJoints {
+                    hip_pitch: -1.8,
+                    hip_roll: -1.57,
+                    hip_yaw: -1.0,
+                    knee: 0.0,
+                    ankle_up: -0.87,
+                    ankle_down: -0.44,
+                },
+            },
+            maximum: Joints {
+                head: HeadJoints {
+                    yaw: 1.57,
+                    pitch: 1.22,
+                },
+                left_arm: ArmJoints {
+                    shoulder_pitch: 1.22,
+                    shoulder_roll: 1.57,
+                    shoulder_yaw: 2.27,
+                    elbow: 0.0,
+                },
+                right_arm: ArmJoints {
+                    shoulder_pitch: 1.22,
+                    shoulder_roll: 1.74,
+                    shoulder_yaw: 2.27,
+                    elbow: 2.44,
+                },
+                left_leg: LegJoints {
+                    hip_pitch: 1.57,
+                    hip_roll: 1.57,
+                    hip_yaw: 1.0,
+                    knee: 2.34,
+                    ankle_up: 0.35,
+                    ankle_down: 0.44,
+                },
+                right_leg: LegJoints {
+                    hip_pitch: 1.57,
+                    hip_roll: 0.2,
+                    hip_yaw: 1.0,
+                    knee: 2.34,
+                    ankle_up: 0.35,
+                    ankle_down: 0.44,
+                },
+            },
+        }
+    }
+}
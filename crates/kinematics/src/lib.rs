@@ -1,6 +1,9 @@
 pub mod forward;
+pub mod inverse;
 pub mod joints;
 pub mod joints_velocity;
 pub mod robot_dimensions;
 pub mod robot_kinematics;
 pub mod robot_masses;
+pub mod stability;
+pub mod whole_body;
@@ -2,9 +2,11 @@ use coordinate_systems::{
     Head, LeftAnkle, LeftFoot, LeftForearm, LeftHip, LeftInnerShoulder, LeftOuterShoulder,
     LeftPelvis, LeftThigh, LeftTibia, LeftUpperArm, Neck, RightAnkle, RightFoot, RightForearm,
     RightHip, RightInnerShoulder, RightOuterShoulder, RightPelvis, RightThigh, RightTibia,
-    RightUpperArm, Torso,
+    RightUpperArm, Robot, Torso,
 };
-use linear_algebra::{Point3, point};
+use linear_algebra::{Isometry3, Point3, Vector3, point};
+
+use crate::robot_kinematics::RobotKinematics;
 
 #[derive(Debug)]
 pub struct RobotMass<Frame> {
@@ -134,3 +136,74 @@ pub const TOTAL_MASS: f32 = TORSO.mass
     + RIGHT_TIBIA.mass
     + RIGHT_ANKLE.mass
     + RIGHT_FOOT.mass;
+
+impl<Frame> RobotMass<Frame> {
+    fn weighted_center_in_robot(&self, frame_to_robot: Isometry3<Frame, Robot>) -> Vector3<Robot> {
+        (frame_to_robot * self.center).coords() * self.mass
+    }
+}
+
+pub fn center_of_mass(kinematics: &RobotKinematics) -> Point3<Robot> {
+    let head = &kinematics.head;
+    let left_arm = &kinematics.left_arm;
+    let right_arm = &kinematics.right_arm;
+    let left_leg = &kinematics.left_leg;
+    let right_leg = &kinematics.right_leg;
+    let weighted_centers = [
+        TORSO.weighted_center_in_robot(kinematics.torso.torso_to_robot),
+        NECK.weighted_center_in_robot(head.neck_to_robot),
+        HEAD.weighted_center_in_robot(head.head_to_robot),
+        LEFT_INNER_SHOULDER.weighted_center_in_robot(left_arm.inner_shoulder_to_robot),
+        LEFT_OUTER_SHOULDER.weighted_center_in_robot(left_arm.outer_shoulder_to_robot),
+        LEFT_UPPER_ARM.weighted_center_in_robot(left_arm.upper_arm_to_robot),
+        LEFT_FOREARM.weighted_center_in_robot(left_arm.forearm_to_robot),
+        RIGHT_INNER_SHOULDER.weighted_center_in_robot(right_arm.inner_shoulder_to_robot),
+        RIGHT_OUTER_SHOULDER.weighted_center_in_robot(right_arm.outer_shoulder_to_robot),
+        RIGHT_UPPER_ARM.weighted_center_in_robot(right_arm.upper_arm_to_robot),
+        RIGHT_FOREARM.weighted_center_in_robot(right_arm.forearm_to_robot),
+        LEFT_PELVIS.weighted_center_in_robot(left_leg.pelvis_to_robot),
+        LEFT_HIP.weighted_center_in_robot(left_leg.hip_to_robot),
+        LEFT_THIGH.weighted_center_in_robot(left_leg.thigh_to_robot),
+        LEFT_TIBIA.weighted_center_in_robot(left_leg.tibia_to_robot),
+        LEFT_ANKLE.weighted_center_in_robot(left_leg.ankle_to_robot),
+        LEFT_FOOT.weighted_center_in_robot(left_leg.foot_to_robot),
+        RIGHT_PELVIS.weighted_center_in_robot(right_leg.pelvis_to_robot),
+        RIGHT_HIP.weighted_center_in_robot(right_leg.hip_to_robot),
+        RIGHT_THIGH.weighted_center_in_robot(right_leg.thigh_to_robot),
+        RIGHT_TIBIA.weighted_center_in_robot(right_leg.tibia_to_robot),
+        RIGHT_ANKLE.weighted_center_in_robot(right_leg.ankle_to_robot),
+        RIGHT_FOOT.weighted_center_in_robot(right_leg.foot_to_robot),
+    ];
+    (weighted_centers.into_iter().sum::<Vector3<Robot>>() / TOTAL_MASS).as_point()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::joints::Joints;
+
+    use super::*;
+
+    #[test]
+    fn center_of_mass_of_zero_pose_is_centered_between_hips_and_shoulders() {
+        let center_of_mass = center_of_mass(&RobotKinematics::from(&Joints::default()));
+
+        assert!(center_of_mass.y().abs() < 0.005, "{center_of_mass:?}");
+        assert!(center_of_mass.x().abs() < 0.02, "{center_of_mass:?}");
+        assert!(
+            (-0.062..0.1845).contains(&center_of_mass.z()),
+            "{center_of_mass:?}"
+        );
+    }
+
+    #[test]
+    fn center_of_mass_follows_forearms_bent_forward() {
+        let mut joints = Joints::default();
+        let stretched = center_of_mass(&RobotKinematics::from(&joints));
+        joints.left_arm.elbow = -1.5;
+        joints.right_arm.elbow = 1.5;
+        let bent = center_of_mass(&RobotKinematics::from(&joints));
+
+        assert!(bent.x() > stretched.x());
+        assert!((bent.y() - stretched.y()).abs() < 0.001);
+    }
+}
@@ -0,0 +1,136 @@
+use coordinate_systems::{Ground, LeftSole, RightSole};
+use geometry::{
+    convex_hull::reduce_to_convex_hull, line_segment::LineSegment, polygon::is_inside_polygon,
+};
+use linear_algebra::{Isometry3, Point2, Point3, point};
+use path_serde::{PathDeserialize, PathIntrospect, PathSerialize};
+use serde::{Deserialize, Serialize};
+
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    PartialEq,
+    Serialize,
+    Deserialize,
+    PathSerialize,
+    PathDeserialize,
+    PathIntrospect,
+    ros_z::Message,
+)]
+pub struct SoleDimensions {
+    /// Distance from the sole origin to the tip of the foot
+    pub front: f32,
+    /// Distance from the sole origin to the heel
+    pub back: f32,
+    pub width: f32,
+}
+
+impl SoleDimensions {
+    fn corners<Frame>(&self) -> [Point3<Frame>; 4] {
+        let half_width = self.width / 2.0;
+        [
+            point![self.front, half_width, 0.0],
+            point![self.front, -half_width, 0.0],
+            point![-self.back, -half_width, 0.0],
+            point![-self.back, half_width, 0.0],
+        ]
+    }
+}
+
+/// Convex hull of all sole corners touching the ground. Corners count as touching if they are at
+/// most `contact_height_tolerance` above the lowest corner.
+pub fn support_polygon(
+    left_sole_to_ground: Isometry3<LeftSole, Ground>,
+    right_sole_to_ground: Isometry3<RightSole, Ground>,
+    sole_dimensions: &SoleDimensions,
+    contact_height_tolerance: f32,
+) -> Vec<Point2<Ground>> {
+    let corners: Vec<Point3<Ground>> = sole_dimensions
+        .corners()
+        .into_iter()
+        .map(|corner| left_sole_to_ground * corner)
+        .chain(
+            sole_dimensions
+                .corners()
+                .into_iter()
+                .map(|corner| right_sole_to_ground * corner),
+        )
+        .collect();
+    let lowest_height = corners
+        .iter()
+        .map(|corner| corner.z())
+        .fold(f32::INFINITY, f32::min);
+    let contact_points: Vec<_> = corners
+        .iter()
+        .filter(|corner| corner.z() <= lowest_height + contact_height_tolerance)
+        .map(|corner| corner.xy())
+        .collect();
+    reduce_to_convex_hull(&contact_points)
+}
+
+/// Distance of the ground projection of the center of mass to the border of the support polygon.
+/// Positive values are inside the polygon, negative values outside.
+pub fn stability_margin(center_of_mass: Point3<Ground>, support_polygon: &[Point2<Ground>]) -> f32 {
+    let projected_center_of_mass = center_of_mass.xy();
+    let distance_to_border = support_polygon
+        .iter()
+        .zip(support_polygon.iter().cycle().skip(1))
+        .map(|(start, end)| {
+            let closest_point =
+                LineSegment::new(*start, *end).closest_point(projected_center_of_mass);
+            (projected_center_of_mass - closest_point).norm()
+        })
+        .fold(f32::INFINITY, f32::min);
+    if is_inside_polygon(support_polygon, &projected_center_of_mass) {
+        distance_to_border
+    } else {
+        -distance_to_border
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use linear_algebra::vector;
+
+    use super::*;
+
+    const SOLE_DIMENSIONS: SoleDimensions = SoleDimensions {
+        front: 0.12,
+        back: 0.08,
+        width: 0.09,
+    };
+
+    fn sole_to_ground<Sole>(x: f32, y: f32, z: f32) -> Isometry3<Sole, Ground> {
+        Isometry3::from(vector![x, y, z])
+    }
+
+    #[test]
+    fn double_support_spans_both_feet() {
+        let polygon = support_polygon(
+            sole_to_ground(0.0, 0.1, 0.0),
+            sole_to_ground(0.0, -0.1, 0.0),
+            &SOLE_DIMENSIONS,
+            0.005,
+        );
+
+        assert_eq!(polygon.len(), 4);
+        let margin = stability_margin(point![0.04, 0.0, 0.6], &polygon);
+        assert!((margin - 0.08).abs() < 1e-5, "{margin}");
+        assert!(stability_margin(point![0.0, 0.2, 0.6], &polygon) < 0.0);
+    }
+
+    #[test]
+    fn lifted_foot_is_not_part_of_support_polygon() {
+        let polygon = support_polygon(
+            sole_to_ground(0.0, 0.1, 0.0),
+            sole_to_ground(0.1, -0.1, 0.05),
+            &SOLE_DIMENSIONS,
+            0.005,
+        );
+
+        assert!(polygon.iter().all(|corner| corner.y() > 0.0));
+        assert!(stability_margin(point![0.0, 0.1, 0.6], &polygon) > 0.04);
+        assert!(stability_margin(point![0.0, 0.0, 0.6], &polygon) < 0.0);
+    }
+}
@@ -0,0 +1,256 @@
+use coordinate_systems::{Ground, LeftForearm, LeftSole, RightForearm, RightSole, Robot};
+use linear_algebra::{Isometry3, Point3};
+use path_serde::{PathDeserialize, PathIntrospect, PathSerialize};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    inverse::{
+        InverseKinematicsParameters, left_arm_angles, left_leg_angles, right_arm_angles,
+        right_leg_angles,
+    },
+    joints::{Joints, limits::JointLimits},
+    robot_kinematics::RobotKinematics,
+    robot_masses::center_of_mass,
+    stability::{SoleDimensions, stability_margin, support_polygon},
+};
+
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    PartialEq,
+    Serialize,
+    Deserialize,
+    PathSerialize,
+    PathDeserialize,
+    PathIntrospect,
+    ros_z::Message,
+)]
+pub struct WholeBodyParameters {
+    pub inverse_kinematics: InverseKinematicsParameters,
+    pub sole_dimensions: SoleDimensions,
+    pub contact_height_tolerance: f32,
+    /// Minimum distance of the projected center of mass to the border of the support polygon
+    pub minimum_stability_margin: f32,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct HandTarget<Forearm> {
+    pub position: Point3<Robot>,
+    pub point_in_forearm: Point3<Forearm>,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct WholeBodyTarget {
+    pub left_sole_to_robot: Isometry3<LeftSole, Robot>,
+    pub right_sole_to_robot: Isometry3<RightSole, Robot>,
+    /// Arms keep their seed angles if no target is given
+    pub left_hand: Option<HandTarget<LeftForearm>>,
+    pub right_hand: Option<HandTarget<RightForearm>>,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct WholeBodySolution {
+    pub joints: Joints<f32>,
+    /// Whether all limbs reached their targets within the tolerances
+    pub is_reached: bool,
+    pub center_of_mass: Point3<Robot>,
+    pub stability_margin: f32,
+    pub is_stable: bool,
+}
+
+/// Solves all limbs independently and evaluates the static stability of the resulting pose. The
+/// head keeps its seed angles.
+pub fn solve_whole_body(
+    target: &WholeBodyTarget,
+    seed: Joints<f32>,
+    robot_to_ground: Isometry3<Robot, Ground>,
+    limits: &JointLimits,
+    parameters: &WholeBodyParameters,
+) -> WholeBodySolution {
+    let inverse_kinematics = &parameters.inverse_kinematics;
+    let left_leg = left_leg_angles(
+        target.left_sole_to_robot,
+        seed.left_leg,
+        limits,
+        inverse_kinematics,
+    );
+    let right_leg = right_leg_angles(
+        target.right_sole_to_robot,
+        seed.right_leg,
+        limits,
+        inverse_kinematics,
+    );
+    let left_arm = target.left_hand.map(|hand| {
+        left_arm_angles(
+            hand.position,
+            hand.point_in_forearm,
+            seed.left_arm,
+            limits,
+            inverse_kinematics,
+        )
+    });
+    let right_arm = target.right_hand.map(|hand| {
+        right_arm_angles(
+            hand.position,
+            hand.point_in_forearm,
+            seed.right_arm,
+            limits,
+            inverse_kinematics,
+        )
+    });
+
+    let joints = limits.clamp(Joints {
+        head: seed.head,
+        left_arm: left_arm.map_or(seed.left_arm, |solution| solution.joints),
+        right_arm: right_arm.map_or(seed.right_arm, |solution| solution.joints),
+        left_leg: left_leg.joints,
+        right_leg: right_leg.joints,
+    });
+    let is_reached = left_leg.is_reached
+        && right_leg.is_reached
+        && left_arm.is_none_or(|solution| solution.is_reached)
+        && right_arm.is_none_or(|solution| solution.is_reached);
+
+    let kinematics = RobotKinematics::from(&joints);
+    let center_of_mass = center_of_mass(&kinematics);
+    let support_polygon = support_polygon(
+        robot_to_ground * kinematics.left_leg.sole_to_robot,
+        robot_to_ground * kinematics.right_leg.sole_to_robot,
+        &parameters.sole_dimensions,
+        parameters.contact_height_tolerance,
+    );
+    let stability_margin = stability_margin(robot_to_ground * center_of_mass, &support_polygon);
+
+    WholeBodySolution {
+        joints,
+        is_reached,
+        center_of_mass,
+        stability_margin,
+        is_stable: stability_margin >= parameters.minimum_stability_margin,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use linear_algebra::{distance, point, vector};
+
+    use crate::{
+        forward::{left_forearm_to_robot, left_sole_to_robot, right_sole_to_robot},
+        joints::{arm::ArmJoints, leg::LegJoints},
+    };
+
+    use super::*;
+
+    fn parameters() -> WholeBodyParameters {
+        WholeBodyParameters {
+            inverse_kinematics: InverseKinematicsParameters::default(),
+            sole_dimensions: SoleDimensions {
+                front: 0.12,
+                back: 0.08,
+                width: 0.09,
+            },
+            contact_height_tolerance: 0.005,
+            minimum_stability_margin: 0.02,
+        }
+    }
+
+    fn standing_pose() -> Joints<f32> {
+        let leg = LegJoints {
+            hip_pitch: -0.2,
+            knee: 0.4,
+            ankle_up: -0.2,
+            ..Default::default()
+        };
+        Joints {
+            left_arm: ArmJoints {
+                shoulder_roll: -1.3,
+                elbow: -0.3,
+                ..Default::default()
+            },
+            right_arm: ArmJoints {
+                shoulder_roll: 1.3,
+                elbow: 0.3,
+                ..Default::default()
+            },
+            left_leg: leg,
+            right_leg: leg,
+            ..Default::default()
+        }
+    }
+
+    fn robot_to_ground(joints: &Joints<f32>) -> Isometry3<Robot, Ground> {
+        let sole_height = left_sole_to_robot(&joints.left_leg).translation().z();
+        Isometry3::from(vector![0.0, 0.0, -sole_height])
+    }
+
+    #[test]
+    fn crouching_with_reaching_arm_round_trips_and_stays_stable() {
+        let parameters = parameters();
+        let limits = JointLimits::default();
+        let mut pose = standing_pose();
+        pose.left_leg.hip_pitch = -0.5;
+        pose.left_leg.knee = 1.0;
+        pose.left_leg.ankle_up = -0.5;
+        pose.right_leg = pose.left_leg;
+        pose.left_arm.shoulder_pitch = -0.8;
+        pose.left_arm.elbow = -0.6;
+        let hand = point![0.0, 0.2, 0.0];
+        let target = WholeBodyTarget {
+            left_sole_to_robot: left_sole_to_robot(&pose.left_leg),
+            right_sole_to_robot: right_sole_to_robot(&pose.right_leg),
+            left_hand: Some(HandTarget {
+                position: left_forearm_to_robot(&pose.left_arm) * hand,
+                point_in_forearm: hand,
+            }),
+            right_hand: None,
+        };
+
+        let solution = solve_whole_body(
+            &target,
+            standing_pose(),
+            robot_to_ground(&pose),
+            &limits,
+            &parameters,
+        );
+
+        assert!(solution.is_reached, "{solution:?}");
+        assert!(solution.is_stable, "{solution:?}");
+        assert!(limits.contains(solution.joints));
+        assert_eq!(solution.joints.right_arm, standing_pose().right_arm);
+        assert!(
+            distance(
+                left_forearm_to_robot(&solution.joints.left_arm) * hand,
+                target.left_hand.unwrap().position
+            ) < 0.001
+        );
+    }
+
+    #[test]
+    fn lifting_a_foot_without_shifting_weight_is_unstable() {
+        let parameters = parameters();
+        let limits = JointLimits::default();
+        let mut pose = standing_pose();
+        pose.right_leg.hip_pitch = -0.6;
+        pose.right_leg.knee = 1.2;
+        pose.right_leg.ankle_up = -0.6;
+        let target = WholeBodyTarget {
+            left_sole_to_robot: left_sole_to_robot(&pose.left_leg),
+            right_sole_to_robot: right_sole_to_robot(&pose.right_leg),
+            left_hand: None,
+            right_hand: None,
+        };
+
+        let solution = solve_whole_body(
+            &target,
+            standing_pose(),
+            robot_to_ground(&pose),
+            &limits,
+            &parameters,
+        );
+
+        assert!(solution.is_reached, "{solution:?}");
+        assert!(!solution.is_stable);
+        assert!(solution.stability_margin < 0.0);
+    }
+}
@@ -6,7 +6,8 @@ use hardware::PathsInterface;
 use kinematics::joints::Joints;
 use serde::{Deserialize, Serialize};
 use types::{
-    cycle_time::CycleTime, motion_command::MotionCommand, parameters::RLWalkingParameters,
+    cycle_time::CycleTime, execution_provider::ExecutionProviderKind,
+    motion_command::MotionCommand, parameters::RLWalkingParameters,
 };
 use walking_inference::{
     inference::WalkingInference,
@@ -22,6 +23,7 @@ pub struct RLWalking {
 #[context]
 pub struct CreationContext {
     prepare_motor_command_parameters: Parameter<MotorCommandParameters, "prepare_motor_command">,
+    walking_parameters: Parameter<RLWalkingParameters, "rl_walking">,
 
     hardware_interface: HardwareInterface,
 }
@@ -32,6 +34,8 @@ pub struct CycleContext {
     common_motor_command_parameters: Parameter<MotorCommandParameters, "common_motor_command">,
 
     walking_inference_inputs: AdditionalOutput<WalkingInferenceInputs, "walking_inference_inputs">,
+    walking_inference_execution_provider:
+        AdditionalOutput<ExecutionProviderKind, "walking_inference_execution_provider">,
 
     imu_state: Input<ImuState, "imu_state">,
     serial_motor_states: Input<Joints<MotorState>, "serial_motor_states">,
@@ -53,6 +57,7 @@ impl RLWalking {
         let walking_inference = WalkingInference::new(
             &neural_network_folder,
             context.prepare_motor_command_parameters,
+            &context.walking_parameters.execution_providers,
         )?;
 
         Ok(Self {
@@ -80,6 +85,9 @@ impl RLWalking {
         context
             .walking_inference_inputs
             .fill_if_subscribed(|| walking_inference_inputs.clone());
+        context
+            .walking_inference_execution_provider
+            .fill_if_subscribed(|| self.walking_inference.execution_provider);
 
         let walking_target_joint_positions =
             context.common_motor_command_parameters.default_positions
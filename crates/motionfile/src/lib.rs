@@ -1,5 +1,6 @@
 mod condition;
 pub mod fallen_abort_condition;
+pub mod motion2;
 pub mod motion_file;
 pub mod motion_interpolator;
 pub mod no_ground_contact_condition;
@@ -11,6 +12,7 @@ pub use condition::{Condition, ContinuousConditionType, DiscreteConditionType, R
 pub use fallen_abort_condition::FallenAbort;
 pub use motion_file::*;
 pub use motion_interpolator::{InterpolatorState, MotionInterpolator};
+pub use motion2::Motion2;
 pub use no_ground_contact_condition::NoGroundContactAbort;
 pub use spline_interpolator::SplineInterpolator;
 pub use stabilized_condition::StabilizedCondition;
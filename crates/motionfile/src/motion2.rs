@@ -0,0 +1,302 @@
+use std::{fs::File, path::Path, time::Duration};
+
+use color_eyre::eyre::{Result, WrapErr, bail, eyre};
+use kinematics::joints::Joints;
+use serde::Deserialize;
+use serde_json::from_reader;
+use splines::Interpolation;
+
+use crate::{KeyFrame, MotionFile, MotionFileFrame};
+
+const NAO_NUMBER_OF_JOINTS: usize = 26;
+
+/// Index in motor order of [`Joints`] for every joint of a NAO motion, `None` for the wrist yaws
+/// and hands. NAO motions list the head, the left arm, the left and right leg from the hip
+/// yaw-pitch to the ankle roll and then the right arm. The angles are used as they are.
+const NAO_JOINTS: [Option<usize>; NAO_NUMBER_OF_JOINTS] = [
+    // head yaw and pitch
+    Some(0),
+    Some(1),
+    // left shoulder pitch and roll, elbow yaw and roll, wrist yaw, hand
+    Some(2),
+    Some(3),
+    Some(4),
+    Some(5),
+    None,
+    None,
+    // left hip yaw-pitch, roll and pitch, knee, ankle pitch and roll
+    Some(12),
+    Some(11),
+    Some(10),
+    Some(13),
+    Some(14),
+    Some(15),
+    // right hip yaw-pitch, roll and pitch, knee, ankle pitch and roll
+    Some(18),
+    Some(17),
+    Some(16),
+    Some(19),
+    Some(20),
+    Some(21),
+    // right shoulder pitch and roll, elbow yaw and roll, wrist yaw, hand
+    Some(6),
+    Some(7),
+    Some(8),
+    Some(9),
+    None,
+    None,
+];
+
+/// Keyframe motion in the `.motion2` format. Every frame holds one position per joint listed in
+/// the header and a relative duration, the durations are scaled to sum up to the header time.
+#[derive(Clone, Debug, Deserialize)]
+pub struct Motion2 {
+    pub header: Motion2Header,
+    pub position: Vec<Motion2Frame>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct Motion2Header {
+    /// Joint index for each entry of the frame parameters, in motor order of [`Joints`] or in NAO
+    /// order if all 26 NAO joints are listed
+    pub joints: Vec<usize>,
+    /// Total duration of the motion in milliseconds
+    pub time: f32,
+    pub title: String,
+    pub version: String,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct Motion2Frame {
+    pub parameters: Vec<f32>,
+    /// Relative duration of the frame
+    pub time: f32,
+}
+
+impl Motion2 {
+    pub fn from_path(motion_file_path: impl AsRef<Path>) -> Result<Self> {
+        let file = File::open(&motion_file_path).wrap_err_with(|| {
+            format!("failed to open motion file {:?}", motion_file_path.as_ref())
+        })?;
+        from_reader(file).wrap_err_with(|| {
+            format!(
+                "failed to parse motion file {:?}",
+                motion_file_path.as_ref()
+            )
+        })
+    }
+
+    fn joints(&self, frame: &Motion2Frame) -> Result<Joints<f32>> {
+        if frame.parameters.len() != self.header.joints.len() {
+            bail!(
+                "frame has {} parameters but the header lists {} joints",
+                frame.parameters.len(),
+                self.header.joints.len()
+            );
+        }
+        let is_nao_motion = self.header.joints.len() == NAO_NUMBER_OF_JOINTS;
+        let mut positions = Joints::<Option<f32>>::default()
+            .into_iter()
+            .collect::<Vec<_>>();
+        let number_of_joints = positions.len();
+        for (&joint_index, &parameter) in self.header.joints.iter().zip(&frame.parameters) {
+            let motor_index = if is_nao_motion {
+                match NAO_JOINTS.get(joint_index) {
+                    Some(Some(motor_index)) => *motor_index,
+                    Some(None) => continue,
+                    None => bail!(
+                        "joint index {joint_index} exceeds the {NAO_NUMBER_OF_JOINTS} NAO joints"
+                    ),
+                }
+            } else {
+                joint_index
+            };
+            let position = positions.get_mut(motor_index).ok_or_else(|| {
+                eyre!("joint index {joint_index} exceeds the {number_of_joints} joints")
+            })?;
+            if position.replace(parameter).is_some() {
+                bail!("joint index {joint_index} is listed more than once");
+            }
+        }
+        positions
+            .into_iter()
+            .enumerate()
+            .map(|(joint_index, position)| {
+                position.ok_or_else(|| eyre!("joint index {joint_index} is missing"))
+            })
+            .collect::<Result<Vec<_>>>()
+            .map(Joints::from_iter)
+    }
+}
+
+impl TryFrom<Motion2> for MotionFile<Joints<f32>> {
+    type Error = color_eyre::Report;
+
+    /// The first frame is the initial position, all following frames form the keyframes of a
+    /// single motion frame named after the header title.
+    fn try_from(motion2: Motion2) -> Result<Self> {
+        let Some((initial_frame, frames)) = motion2.position.split_first() else {
+            bail!("motion {:?} has no frames", motion2.header.title);
+        };
+        if frames.is_empty() {
+            bail!("motion {:?} has no keyframes", motion2.header.title);
+        }
+        let time_sum: f32 = motion2.position.iter().map(|frame| frame.time).sum();
+        if time_sum <= 0.0 || motion2.header.time <= 0.0 {
+            bail!("motion {:?} has no duration", motion2.header.title);
+        }
+
+        let initial_positions = motion2
+            .joints(initial_frame)
+            .wrap_err("invalid initial frame")?;
+        let keyframes = frames
+            .iter()
+            .enumerate()
+            .map(|(index, frame)| {
+                Ok(KeyFrame {
+                    duration: Duration::from_secs_f32(
+                        frame.time / time_sum * motion2.header.time / 1000.0,
+                    ),
+                    positions: motion2
+                        .joints(frame)
+                        .wrap_err_with(|| format!("invalid frame {}", index + 1))?,
+                })
+            })
+            .collect::<Result<_>>()?;
+
+        Ok(MotionFile {
+            interpolation_mode: Interpolation::Linear,
+            initial_positions,
+            motion: vec![MotionFileFrame {
+                name: Some(motion2.header.title),
+                entry_condition: None,
+                interrupt_conditions: Vec::new(),
+                keyframes,
+                exit_condition: None,
+            }],
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn motion2(joints: Vec<usize>, frames: &[(f32, f32)]) -> Motion2 {
+        serde_json::from_value(json!({
+            "header": {
+                "joints": joints,
+                "time": 1000,
+                "title": "test",
+                "version": "2.0",
+            },
+            "position": frames
+                .iter()
+                .map(|(time, position)| json!({
+                    "parameters": vec![position; joints.len()],
+                    "time": time,
+                }))
+                .collect::<Vec<_>>(),
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn frame_durations_are_scaled_to_header_time() {
+        let joints = (0..22).rev().collect();
+        let motion_file =
+            MotionFile::try_from(motion2(joints, &[(100.0, 0.0), (100.0, 0.5), (200.0, 1.0)]))
+                .unwrap();
+
+        assert_eq!(motion_file.initial_positions, Joints::fill(0.0));
+        let keyframes = &motion_file.motion[0].keyframes;
+        assert_eq!(keyframes.len(), 2);
+        assert_eq!(keyframes[0].duration, Duration::from_millis(250));
+        assert_eq!(keyframes[0].positions, Joints::fill(0.5));
+        assert_eq!(keyframes[1].duration, Duration::from_millis(500));
+        assert_eq!(keyframes[1].positions, Joints::fill(1.0));
+    }
+
+    #[test]
+    fn parameters_are_mapped_through_header_joints() {
+        let mut motion2 = motion2((0..22).collect(), &[(1.0, 0.0), (1.0, 0.0)]);
+        motion2.header.joints.swap(0, 21);
+        motion2.position[1].parameters[0] = 1.0;
+
+        let motion_file = MotionFile::try_from(motion2).unwrap();
+
+        let positions = motion_file.motion[0].keyframes[0].positions;
+        assert_eq!(positions.right_leg.ankle_down, 1.0);
+        assert_eq!(positions.head.yaw, 0.0);
+    }
+
+    #[test]
+    fn nao_joints_are_mapped_to_motor_order() {
+        let mut motion2 = motion2((0..26).collect(), &[(1.0, 0.0), (1.0, 0.0)]);
+        // left elbow yaw, left wrist yaw, right hip pitch, right elbow roll
+        for (nao_index, position) in [(4, 0.1), (6, 0.2), (16, 0.3), (23, 0.4)] {
+            motion2.position[1].parameters[nao_index] = position;
+        }
+
+        let motion_file = MotionFile::try_from(motion2).unwrap();
+
+        let positions = motion_file.motion[0].keyframes[0].positions;
+        assert_eq!(positions.left_arm.shoulder_yaw, 0.1);
+        assert_eq!(positions.right_leg.hip_pitch, 0.3);
+        assert_eq!(positions.right_arm.elbow, 0.4);
+        assert_eq!(
+            positions
+                .into_iter()
+                .filter(|position| *position != 0.0)
+                .count(),
+            3
+        );
+    }
+
+    #[test]
+    fn shipped_nao_kick_is_loaded() {
+        let motion2 = Motion2::from_path(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/../../etc/motions/kick_L.motion2"
+        ))
+        .unwrap();
+        let initial_parameters = motion2.position[0].parameters.clone();
+        let number_of_frames = motion2.position.len();
+
+        let motion_file = MotionFile::try_from(motion2).unwrap();
+
+        assert_eq!(motion_file.motion[0].name.as_deref(), Some("kick_L"));
+        let initial_positions = motion_file.initial_positions;
+        assert_eq!(
+            initial_positions.left_arm.shoulder_pitch,
+            initial_parameters[2]
+        );
+        assert_eq!(initial_positions.left_leg.hip_pitch, initial_parameters[10]);
+        assert_eq!(initial_positions.right_arm.elbow, initial_parameters[23]);
+        let keyframes = &motion_file.motion[0].keyframes;
+        assert_eq!(keyframes.len(), number_of_frames - 1);
+        let keyframe_duration: Duration = keyframes.iter().map(|keyframe| keyframe.duration).sum();
+        // the frame times sum up to 9960 of which the initial frame takes 1500
+        assert!((keyframe_duration.as_secs_f32() - 2.7 * 8460.0 / 9960.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn out_of_range_joint_indices_are_rejected() {
+        let mut motion2 = motion2((0..22).collect(), &[(1.0, 0.0), (1.0, 0.0)]);
+        motion2.header.joints[21] = 22;
+
+        let error = MotionFile::try_from(motion2).unwrap_err();
+
+        assert!(format!("{error:?}").contains("joint index 22 exceeds the 22 joints"));
+    }
+
+    #[test]
+    fn incomplete_joint_sets_are_rejected() {
+        assert!(
+            MotionFile::try_from(motion2((0..21).collect(), &[(1.0, 0.0), (1.0, 0.0)])).is_err()
+        );
+        assert!(MotionFile::try_from(motion2((0..22).collect(), &[(1.0, 0.0)])).is_err());
+    }
+}
@@ -8,9 +8,18 @@ use ordered_float::NotNan;
 use path_serde::{PathDeserialize, PathIntrospect, PathSerialize};
 use ros_z::{Message, time::Time};
 use serde::{Deserialize, Serialize};
-use types::multivariate_normal_distribution::MultivariateNormalDistribution;
+use types::{
+    multivariate_normal_distribution::MultivariateNormalDistribution,
+    parameters::{BallFilterNoise, BallMotionModelParameters},
+};
 
-use crate::hypothesis::{BallHypothesis, BallMode};
+use crate::hypothesis::{
+    BallHypothesis,
+    motion_model::{ModelDynamics, TransitionRates},
+};
+
+/// Keeps single implausible friction observations from dominating the learned friction.
+const MAXIMUM_OBSERVED_FRICTION_PER_SECOND: f32 = 5.0;
 
 #[derive(
     Debug,
@@ -25,6 +34,8 @@ use crate::hypothesis::{BallHypothesis, BallMode};
 )]
 pub struct BallFilter {
     pub hypotheses: Vec<BallHypothesis>,
+    /// Friction learned from the velocity decay of rolling balls, starts at the configured one
+    pub learned_friction_per_second: Option<f32>,
 }
 
 impl BallFilter {
@@ -42,23 +53,55 @@ impl BallFilter {
         }
     }
 
+    pub fn friction_per_second(&self, parameters: &BallMotionModelParameters) -> f32 {
+        self.learned_friction_per_second
+            .unwrap_or(parameters.friction_per_second)
+    }
+
+    pub fn learn_friction(
+        &mut self,
+        observed_friction_per_second: f32,
+        parameters: &BallMotionModelParameters,
+    ) {
+        let friction_per_second = self.friction_per_second(parameters);
+        let observed_friction_per_second =
+            observed_friction_per_second.clamp(0.0, MAXIMUM_OBSERVED_FRICTION_PER_SECOND);
+        self.learned_friction_per_second = Some(
+            friction_per_second
+                + parameters.friction_learning_rate
+                    * (observed_friction_per_second - friction_per_second),
+        );
+    }
+
     pub fn predict(
         &mut self,
         delta_time: Duration,
         last_to_current_odometry: Isometry2<Ground, Ground>,
-        velocity_decay: f32,
-        moving_process_noise: Matrix4<f32>,
-        resting_process_noise: Matrix2<f32>,
-        log_likelihood_of_zero_velocity_threshold: f32,
+        noise: &BallFilterNoise,
+        parameters: &BallMotionModelParameters,
+        is_kick_likely: impl Fn(&BallHypothesis) -> bool,
     ) {
+        let dynamics = ModelDynamics {
+            friction_per_second: self.friction_per_second(parameters),
+            resting_process_noise: Matrix2::from_diagonal(&noise.process_noise_resting),
+            rolling_process_noise: Matrix4::from_diagonal(&noise.process_noise_moving),
+            kicked_process_noise: Matrix4::from_diagonal(&parameters.kicked_process_noise),
+        };
         for hypothesis in self.hypotheses.iter_mut() {
+            let transition_rates = TransitionRates {
+                resting_rolling_switch: parameters.resting_rolling_switch_rate,
+                kick: if is_kick_likely(hypothesis) {
+                    parameters.likely_kick_rate
+                } else {
+                    parameters.kick_rate
+                },
+                kicked_to_rolling: parameters.kicked_to_rolling_rate,
+            };
             hypothesis.predict(
                 delta_time,
                 last_to_current_odometry,
-                velocity_decay,
-                moving_process_noise,
-                resting_process_noise,
-                log_likelihood_of_zero_velocity_threshold,
+                &transition_rates,
+                &dynamics,
             )
         }
     }
@@ -127,12 +170,7 @@ impl BallFilter {
             )
         }
 
-        let new_hypothesis = BallHypothesis {
-            mode: BallMode::Moving(new_hypothesis),
-            last_seen: detection_time,
-            validity: 1.0,
-        };
-
-        self.hypotheses.push(new_hypothesis)
+        self.hypotheses
+            .push(BallHypothesis::new(new_hypothesis, detection_time))
     }
 }
@@ -1,10 +1,7 @@
-use std::{f32::consts::PI, time::Duration};
+use std::time::Duration;
 
-use filtering::kalman_filter::KalmanFilter;
-use moving::{MovingPredict, MovingUpdate};
-use nalgebra::{Matrix2, Matrix4};
+use nalgebra::Matrix2;
 use path_serde::{PathDeserialize, PathIntrospect, PathSerialize};
-use resting::{RestingPredict, RestingUpdate};
 use ros_z::{Message, time::Time};
 use serde::{Deserialize, Serialize};
 
@@ -12,11 +9,17 @@ use coordinate_systems::Ground;
 use linear_algebra::{IntoFramed, Isometry2, Vector2, vector};
 
 use types::{
-    ball_position::BallPosition, multivariate_normal_distribution::MultivariateNormalDistribution,
+    ball_position::{BallPosition, BallRestPrediction},
+    multivariate_normal_distribution::MultivariateNormalDistribution,
+    parameters::BallMotionModelParameters,
 };
 
-pub mod moving;
-pub mod resting;
+use motion_model::{ModelDynamics, MotionModels, TransitionRates};
+
+pub mod motion_model;
+
+/// The friction is only learned while the ball clearly rolls freely.
+const ROLLING_PROBABILITY_FOR_FRICTION_LEARNING: f32 = 0.9;
 
 #[derive(
     Clone, Debug, Serialize, Deserialize, PathSerialize, PathDeserialize, PathIntrospect, Message,
@@ -26,27 +29,49 @@ pub enum BallMode {
     Moving(MultivariateNormalDistribution<4>),
 }
 
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, Message)]
+pub struct SpeedSample {
+    pub time: Time,
+    pub speed: f32,
+}
+
 #[derive(
     Clone, Debug, Serialize, Deserialize, PathSerialize, PathDeserialize, PathIntrospect, Message,
 )]
 pub struct BallHypothesis {
-    pub mode: BallMode,
+    pub models: MotionModels,
     #[path_serde(skip)]
     pub last_seen: Time,
     pub validity: f32,
+    /// Start of the window in which the velocity decay of the rolling model is observed
+    #[path_serde(skip)]
+    pub rolling_speed_reference: Option<SpeedSample>,
 }
 
 impl BallHypothesis {
     pub fn new(hypothesis: MultivariateNormalDistribution<4>, last_seen: Time) -> Self {
         Self {
-            mode: BallMode::Moving(hypothesis),
+            models: MotionModels::new(hypothesis),
             last_seen,
             validity: 1.0,
+            rolling_speed_reference: None,
+        }
+    }
+
+    pub fn mode(&self) -> BallMode {
+        let combined = self.models.combined();
+        if self.models.resting.probability >= 0.5 {
+            BallMode::Resting(MultivariateNormalDistribution {
+                mean: combined.mean.xy(),
+                covariance: combined.covariance.fixed_view::<2, 2>(0, 0).into_owned(),
+            })
+        } else {
+            BallMode::Moving(combined)
         }
     }
 
     pub fn position(&self) -> BallPosition<Ground> {
-        match self.mode {
+        match self.mode() {
             BallMode::Resting(resting) => BallPosition {
                 position: resting.mean.framed().as_point(),
                 velocity: Vector2::zeros(),
@@ -61,94 +86,130 @@ impl BallHypothesis {
     }
 
     pub fn position_covariance(&self) -> Matrix2<f32> {
-        match self.mode {
-            BallMode::Resting(resting) => resting.covariance,
-            BallMode::Moving(moving) => moving.covariance.fixed_view::<2, 2>(0, 0).into_owned(),
+        self.models
+            .combined()
+            .covariance
+            .fixed_view::<2, 2>(0, 0)
+            .into_owned()
+    }
+
+    /// Rolls the ball out with the given friction, `None` if it never stops.
+    pub fn rest_prediction(
+        &self,
+        friction_per_second: f32,
+        stopping_speed: f32,
+    ) -> Option<BallRestPrediction<Ground>> {
+        let ball = self.position();
+        let speed = ball.velocity.norm();
+        if speed <= stopping_speed {
+            return Some(BallRestPrediction {
+                rest_position: ball.position,
+                time_to_stop: Duration::ZERO,
+            });
         }
+        if friction_per_second <= 0.0 || stopping_speed <= 0.0 {
+            return None;
+        }
+        Some(BallRestPrediction {
+            rest_position: ball.position
+                + ball.velocity * ((1.0 - stopping_speed / speed) / friction_per_second),
+            time_to_stop: Duration::from_secs_f32(
+                (speed / stopping_speed).ln() / friction_per_second,
+            ),
+        })
     }
 
     pub fn predict(
         &mut self,
         delta_time: Duration,
         last_to_current_odometry: Isometry2<Ground, Ground>,
-        velocity_decay: f32,
-        moving_process_noise: Matrix4<f32>,
-        resting_process_noise: Matrix2<f32>,
-        log_likelihood_of_zero_velocity_threshold: f32,
+        transition_rates: &TransitionRates,
+        dynamics: &ModelDynamics,
     ) {
-        match &mut self.mode {
-            BallMode::Resting(resting) => {
-                RestingPredict::predict(resting, last_to_current_odometry, resting_process_noise)
-            }
-            BallMode::Moving(moving) => {
-                MovingPredict::predict(
-                    moving,
-                    delta_time,
-                    last_to_current_odometry,
-                    velocity_decay,
-                    moving_process_noise,
-                );
-
-                let velocity_covariance = moving.covariance.fixed_view::<2, 2>(0, 0);
-                let velocity = nalgebra::vector![moving.mean.z, moving.mean.w];
-
-                let exponent = -velocity.dot(
-                    &velocity_covariance
-                        .cholesky()
-                        .expect("covariance not invertible")
-                        .solve(&velocity),
-                ) / 2.;
-                let determinant = velocity_covariance.determinant();
-
-                let log_likelihood_of_zero_velocity =
-                    exponent - (2. * PI * determinant.sqrt()).ln();
-
-                if log_likelihood_of_zero_velocity > log_likelihood_of_zero_velocity_threshold {
-                    self.mode = BallMode::Resting(MultivariateNormalDistribution {
-                        mean: moving.mean.xy(),
-                        covariance: moving.covariance.fixed_view::<2, 2>(0, 0).into_owned(),
-                    })
-                }
-            }
-        }
+        self.models.predict(
+            delta_time,
+            last_to_current_odometry,
+            transition_rates,
+            dynamics,
+        );
     }
 
+    /// Returns the friction observed in the rolling model once a learning window is complete.
     pub fn update(
         &mut self,
         detection_time: Time,
         measurement: MultivariateNormalDistribution<2>,
         validity_bonus: f32,
-    ) {
+        parameters: &BallMotionModelParameters,
+    ) -> Option<f32> {
         self.last_seen = detection_time;
         self.validity += validity_bonus;
+        self.models.update(measurement);
+
+        let rolling = self.models.rolling.estimate.mean;
+        let speed = nalgebra::vector![rolling.z, rolling.w].norm();
+        if self.models.rolling.probability < ROLLING_PROBABILITY_FOR_FRICTION_LEARNING
+            || speed < parameters.friction_learning_minimum_speed
+        {
+            self.rolling_speed_reference = None;
+            return None;
+        }
 
-        match &mut self.mode {
-            BallMode::Resting(resting) => RestingUpdate::update(resting, measurement),
-            BallMode::Moving(moving) => MovingUpdate::update(moving, measurement),
+        let sample = SpeedSample {
+            time: detection_time,
+            speed,
+        };
+        let reference = *self.rolling_speed_reference.get_or_insert(sample);
+        let window = detection_time.duration_since(reference.time);
+        if window < parameters.friction_learning_window || window.is_zero() {
+            return None;
         }
+        self.rolling_speed_reference = Some(sample);
+        Some((reference.speed / speed).ln() / window.as_secs_f32())
     }
 
     pub fn merge(&mut self, other: BallHypothesis) {
-        match (&mut self.mode, other.mode) {
-            (BallMode::Resting(resting), BallMode::Resting(distribution)) => {
-                KalmanFilter::update(
-                    resting,
-                    Matrix2::identity(),
-                    distribution.mean,
-                    distribution.covariance,
-                );
-                self.validity = self.validity.max(other.validity);
-            }
-            (BallMode::Moving(moving), BallMode::Moving(distribution)) => {
-                KalmanFilter::update(
-                    moving,
-                    Matrix4::identity(),
-                    distribution.mean,
-                    distribution.covariance,
-                );
-                self.validity = self.validity.max(other.validity);
-            }
-            _ => (), // deny merge
+        self.models.merge(&other.models);
+        self.validity = self.validity.max(other.validity);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use approx::assert_relative_eq;
+    use nalgebra::Matrix4;
+
+    use super::*;
+
+    #[test]
+    fn rolling_ball_stops_where_friction_has_consumed_its_speed() {
+        let mut models = MotionModels::new(MultivariateNormalDistribution {
+            mean: nalgebra::vector![1.0, 2.0, 1.0, 0.0],
+            covariance: Matrix4::identity() * 0.01,
+        });
+        models.resting.probability = 0.0;
+        models.rolling.probability = 1.0;
+        models.kicked.probability = 0.0;
+        let hypothesis = BallHypothesis {
+            models,
+            ..BallHypothesis::new(
+                MultivariateNormalDistribution {
+                    mean: nalgebra::Vector4::zeros(),
+                    covariance: Matrix4::identity(),
+                },
+                Time::zero(),
+            )
         };
+
+        let prediction = hypothesis.rest_prediction(0.5, 0.05).unwrap();
+
+        assert_relative_eq!(prediction.rest_position.x(), 2.9, epsilon = 1e-5);
+        assert_relative_eq!(prediction.rest_position.y(), 2.0, epsilon = 1e-5);
+        assert_relative_eq!(
+            prediction.time_to_stop.as_secs_f32(),
+            20.0_f32.ln() / 0.5,
+            epsilon = 1e-4
+        );
+        assert!(hypothesis.rest_prediction(0.0, 0.05).is_none());
     }
 }
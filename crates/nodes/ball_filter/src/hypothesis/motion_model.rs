@@ -0,0 +1,465 @@
+use std::{f32::consts::PI, time::Duration};
+
+use nalgebra::{Matrix2, Matrix2x4, Matrix3, Matrix4, Matrix4x2, Vector4, matrix};
+use path_serde::{PathDeserialize, PathIntrospect, PathSerialize};
+use ros_z::Message;
+use serde::{Deserialize, Serialize};
+
+use coordinate_systems::Ground;
+use filtering::kalman_filter::KalmanFilter;
+use linear_algebra::Isometry2;
+use types::multivariate_normal_distribution::MultivariateNormalDistribution;
+
+/// Probabilities of the resting, rolling and kicked model of a newly spawned hypothesis.
+const INITIAL_PROBABILITIES: [f32; 3] = [0.5, 0.4, 0.1];
+/// Keeps every model alive, a model with zero probability never recovers.
+const MINIMUM_PROBABILITY: f32 = 1e-4;
+/// Velocity variance per second of the resting model, keeps its covariance invertible.
+const RESTING_VELOCITY_NOISE: f32 = 1e-4;
+
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    Serialize,
+    Deserialize,
+    PathSerialize,
+    PathDeserialize,
+    PathIntrospect,
+    Message,
+)]
+pub struct ModelEstimate {
+    pub estimate: MultivariateNormalDistribution<4>,
+    pub probability: f32,
+}
+
+/// Estimates of the ball state `[x, y, vx, vy]` under each motion model of an interacting
+/// multiple model filter.
+#[derive(
+    Clone, Debug, Serialize, Deserialize, PathSerialize, PathDeserialize, PathIntrospect, Message,
+)]
+pub struct MotionModels {
+    pub resting: ModelEstimate,
+    pub rolling: ModelEstimate,
+    pub kicked: ModelEstimate,
+}
+
+pub struct ModelDynamics {
+    pub friction_per_second: f32,
+    pub resting_process_noise: Matrix2<f32>,
+    pub rolling_process_noise: Matrix4<f32>,
+    pub kicked_process_noise: Matrix4<f32>,
+}
+
+/// Rates per second at which the ball switches between the motion models.
+pub struct TransitionRates {
+    pub resting_rolling_switch: f32,
+    pub kick: f32,
+    pub kicked_to_rolling: f32,
+}
+
+impl TransitionRates {
+    /// Row `i` holds the probabilities of switching from model `i` to each model.
+    fn probabilities(&self, delta_time: Duration) -> Matrix3<f32> {
+        let dt = delta_time.as_secs_f32();
+        let probability = |rate: f32| 1.0 - (-rate.max(0.0) * dt).exp();
+        let switch = probability(self.resting_rolling_switch);
+        let kick = probability(self.kick);
+        let settle = probability(self.kicked_to_rolling);
+        let stay = (1.0 - switch - kick).max(0.0);
+        matrix![
+            stay, switch, kick;
+            switch, stay, kick;
+            0.0, settle, 1.0 - settle;
+        ]
+    }
+}
+
+impl MotionModels {
+    pub fn new(estimate: MultivariateNormalDistribution<4>) -> Self {
+        let [resting, rolling, kicked] = INITIAL_PROBABILITIES.map(|probability| ModelEstimate {
+            estimate,
+            probability,
+        });
+        Self {
+            resting,
+            rolling,
+            kicked,
+        }
+    }
+
+    fn to_array(&self) -> [ModelEstimate; 3] {
+        [self.resting, self.rolling, self.kicked]
+    }
+
+    fn from_array([resting, rolling, kicked]: [ModelEstimate; 3]) -> Self {
+        Self {
+            resting,
+            rolling,
+            kicked,
+        }
+    }
+
+    pub fn predict(
+        &mut self,
+        delta_time: Duration,
+        last_to_current_odometry: Isometry2<Ground, Ground>,
+        transition_rates: &TransitionRates,
+        dynamics: &ModelDynamics,
+    ) {
+        self.mix(transition_rates.probabilities(delta_time));
+
+        let dt = delta_time.as_secs_f32();
+        let rotation = last_to_current_odometry.inner.rotation.to_rotation_matrix();
+        let rotation = rotation.matrix();
+        let translation = last_to_current_odometry.inner.translation.vector;
+        let state_rotation = matrix![
+            rotation.m11, rotation.m12, 0.0, 0.0;
+            rotation.m21, rotation.m22, 0.0, 0.0;
+            0.0, 0.0, rotation.m11, rotation.m12;
+            0.0, 0.0, rotation.m21, rotation.m22;
+        ];
+
+        let resting_prediction = Matrix4::from_diagonal(&Vector4::new(1.0, 1.0, 0.0, 0.0));
+        let resting_process_noise = Matrix4::from_diagonal(&Vector4::new(
+            dynamics.resting_process_noise.m11,
+            dynamics.resting_process_noise.m22,
+            RESTING_VELOCITY_NOISE,
+            RESTING_VELOCITY_NOISE,
+        ));
+        let rolling_prediction = rolling_prediction(dt, dynamics.friction_per_second);
+
+        for (model, state_prediction, process_noise) in [
+            (&mut self.resting, resting_prediction, resting_process_noise),
+            (
+                &mut self.rolling,
+                rolling_prediction,
+                dynamics.rolling_process_noise,
+            ),
+            (
+                &mut self.kicked,
+                rolling_prediction,
+                dynamics.kicked_process_noise,
+            ),
+        ] {
+            KalmanFilter::predict(
+                &mut model.estimate,
+                state_prediction * state_rotation,
+                Matrix4x2::identity(),
+                translation,
+                process_noise * dt,
+            );
+        }
+    }
+
+    /// Blends the estimates of all models into the starting point of each model according to
+    /// how likely the ball switched into it.
+    fn mix(&mut self, transition_probabilities: Matrix3<f32>) {
+        let models = self.to_array();
+        let mixed = std::array::from_fn(|to| {
+            let probability: f32 = models
+                .iter()
+                .enumerate()
+                .map(|(from, model)| transition_probabilities[(from, to)] * model.probability)
+                .sum();
+            if probability <= 0.0 {
+                return ModelEstimate {
+                    probability: 0.0,
+                    ..models[to]
+                };
+            }
+            let components = models.iter().enumerate().map(|(from, model)| {
+                (
+                    transition_probabilities[(from, to)] * model.probability / probability,
+                    &model.estimate,
+                )
+            });
+            ModelEstimate {
+                estimate: mixture(components),
+                probability,
+            }
+        });
+        *self = Self::from_array(mixed);
+        self.normalize();
+    }
+
+    pub fn update(&mut self, measurement: MultivariateNormalDistribution<2>) {
+        let log_likelihoods = self
+            .to_array()
+            .map(|model| measurement_log_likelihood(&model.estimate, &measurement));
+
+        for model in [&mut self.resting, &mut self.rolling, &mut self.kicked] {
+            KalmanFilter::update(
+                &mut model.estimate,
+                Matrix2x4::identity(),
+                measurement.mean,
+                measurement.covariance,
+            );
+        }
+
+        if let [Some(resting), Some(rolling), Some(kicked)] = log_likelihoods {
+            let maximum = resting.max(rolling).max(kicked);
+            self.resting.probability *= (resting - maximum).exp();
+            self.rolling.probability *= (rolling - maximum).exp();
+            self.kicked.probability *= (kicked - maximum).exp();
+            self.normalize();
+        }
+    }
+
+    pub fn merge(&mut self, other: &MotionModels) {
+        for (model, other) in [&mut self.resting, &mut self.rolling, &mut self.kicked]
+            .into_iter()
+            .zip(other.to_array())
+        {
+            KalmanFilter::update(
+                &mut model.estimate,
+                Matrix4::identity(),
+                other.estimate.mean,
+                other.estimate.covariance,
+            );
+            model.probability = (model.probability + other.probability) / 2.0;
+        }
+        self.normalize();
+    }
+
+    /// Moment matched estimate over all models.
+    pub fn combined(&self) -> MultivariateNormalDistribution<4> {
+        mixture(
+            self.to_array()
+                .iter()
+                .map(|model| (model.probability, &model.estimate)),
+        )
+    }
+
+    fn normalize(&mut self) {
+        let total = self.resting.probability + self.rolling.probability + self.kicked.probability;
+        for model in [&mut self.resting, &mut self.rolling, &mut self.kicked] {
+            model.probability = if total > 0.0 {
+                (model.probability / total).max(MINIMUM_PROBABILITY)
+            } else {
+                1.0 / 3.0
+            };
+        }
+        let total = self.resting.probability + self.rolling.probability + self.kicked.probability;
+        for model in [&mut self.resting, &mut self.rolling, &mut self.kicked] {
+            model.probability /= total;
+        }
+    }
+}
+
+/// The velocity decays exponentially, which the simulator approximates by scaling the velocity
+/// with `1 - ball_friction_per_second * dt` each tick.
+fn rolling_prediction(dt: f32, friction_per_second: f32) -> Matrix4<f32> {
+    let decay = (-friction_per_second * dt).exp();
+    let travel = if friction_per_second > f32::EPSILON {
+        (1.0 - decay) / friction_per_second
+    } else {
+        dt
+    };
+    matrix![
+        1.0, 0.0, travel, 0.0;
+        0.0, 1.0, 0.0, travel;
+        0.0, 0.0, decay, 0.0;
+        0.0, 0.0, 0.0, decay;
+    ]
+}
+
+fn mixture<'a>(
+    components: impl Iterator<Item = (f32, &'a MultivariateNormalDistribution<4>)> + Clone,
+) -> MultivariateNormalDistribution<4> {
+    let mean = components
+        .clone()
+        .fold(Vector4::zeros(), |mean, (weight, component)| {
+            mean + component.mean * weight
+        });
+    let covariance = components.fold(Matrix4::zeros(), |covariance, (weight, component)| {
+        let spread = component.mean - mean;
+        covariance + (component.covariance + spread * spread.transpose()) * weight
+    });
+    MultivariateNormalDistribution { mean, covariance }
+}
+
+fn measurement_log_likelihood(
+    estimate: &MultivariateNormalDistribution<4>,
+    measurement: &MultivariateNormalDistribution<2>,
+) -> Option<f32> {
+    let residual = measurement.mean - estimate.mean.xy();
+    let residual_covariance = estimate.covariance.fixed_view::<2, 2>(0, 0) + measurement.covariance;
+    let mahalanobis_distance = residual.dot(&residual_covariance.cholesky()?.solve(&residual));
+    Some(-mahalanobis_distance / 2.0 - (2.0 * PI * residual_covariance.determinant().sqrt()).ln())
+}
+
+#[cfg(test)]
+mod tests {
+    use approx::assert_relative_eq;
+    use nalgebra::{Vector2, vector};
+
+    use types::parameters::BallFilterParameters;
+
+    use super::*;
+
+    const CYCLE_TIME: Duration = Duration::from_millis(10);
+
+    fn dynamics() -> ModelDynamics {
+        ModelDynamics {
+            friction_per_second: 0.6,
+            resting_process_noise: Matrix2::from_diagonal(&vector![0.1, 0.1]),
+            rolling_process_noise: Matrix4::from_diagonal(&vector![0.5, 0.5, 2.0, 2.0]),
+            kicked_process_noise: Matrix4::from_diagonal(&vector![1.0, 1.0, 200.0, 200.0]),
+        }
+    }
+
+    fn transition_rates(kick: f32) -> TransitionRates {
+        TransitionRates {
+            resting_rolling_switch: 0.5,
+            kick,
+            kicked_to_rolling: 5.0,
+        }
+    }
+
+    fn step(models: &mut MotionModels, position: nalgebra::Vector2<f32>, kick_rate: f32) {
+        models.predict(
+            CYCLE_TIME,
+            Isometry2::identity(),
+            &transition_rates(kick_rate),
+            &dynamics(),
+        );
+        models.update(MultivariateNormalDistribution {
+            mean: position,
+            covariance: Matrix2::identity() * 0.0004,
+        });
+    }
+
+    #[test]
+    fn rolling_prediction_matches_simulated_friction() {
+        let mut position = vector![0.0, 0.0];
+        let mut velocity = vector![2.0, 1.0];
+        let dt = CYCLE_TIME.as_secs_f32();
+        for _ in 0..100 {
+            position += velocity * dt;
+            velocity *= 1.0 - 0.6 * dt;
+        }
+
+        let predicted = rolling_prediction(1.0, 0.6) * vector![0.0, 0.0, 2.0, 1.0];
+
+        assert_relative_eq!(predicted.xy(), position, epsilon = 0.01);
+        assert_relative_eq!(vector![predicted.z, predicted.w], velocity, epsilon = 0.01);
+    }
+
+    #[test]
+    fn kicked_model_takes_over_when_a_resting_ball_starts_rolling() {
+        let mut models = MotionModels::new(MultivariateNormalDistribution {
+            mean: Vector4::zeros(),
+            covariance: Matrix4::from_diagonal(&vector![0.5, 0.5, 40.0, 40.0]),
+        });
+        for _ in 0..100 {
+            step(&mut models, vector![0.0, 0.0], 0.05);
+        }
+        assert!(models.resting.probability > 0.9);
+
+        let dt = CYCLE_TIME.as_secs_f32();
+        let mut position = vector![0.0, 0.0];
+        let mut velocity = vector![2.0, 0.0];
+        for _ in 0..10 {
+            position += velocity * dt;
+            velocity *= 1.0 - 0.6 * dt;
+            step(&mut models, position, 10.0);
+        }
+        assert!(models.kicked.probability > 0.5);
+        assert!(models.resting.probability < 0.05);
+        assert!(models.combined().mean.z > 0.5);
+
+        for _ in 0..100 {
+            position += velocity * dt;
+            velocity *= 1.0 - 0.6 * dt;
+            step(&mut models, position, 0.05);
+        }
+        assert!(models.rolling.probability > 0.9);
+        assert_relative_eq!(models.combined().mean.z, velocity.x, epsilon = 0.1);
+    }
+
+    #[test]
+    fn shipped_process_noise_grows_covariance_like_the_per_cycle_baseline() {
+        let parameters: BallFilterParameters = json5::from_str(include_str!(
+            "../../../../../etc/parameters/ros_z/base/ball_filter.json5"
+        ))
+        .unwrap();
+        let dynamics = ModelDynamics {
+            friction_per_second: 0.0,
+            resting_process_noise: Matrix2::from_diagonal(&parameters.noise.process_noise_resting),
+            rolling_process_noise: Matrix4::from_diagonal(&parameters.noise.process_noise_moving),
+            kicked_process_noise: Matrix4::from_diagonal(
+                &parameters.motion_models.kicked_process_noise,
+            ),
+        };
+        let certain = MultivariateNormalDistribution {
+            mean: Vector4::zeros(),
+            covariance: Matrix4::zeros(),
+        };
+        let mut models = MotionModels::new(certain);
+        let without_transitions = TransitionRates {
+            resting_rolling_switch: 0.0,
+            kick: 0.0,
+            kicked_to_rolling: 0.0,
+        };
+        // the noise used to be added once per 10 ms cycle, independent of the elapsed time
+        let mut baseline_resting = certain;
+        let mut baseline_rolling = certain;
+        let mut baseline_kicked = certain;
+        let dt = CYCLE_TIME.as_secs_f32();
+
+        for _ in 0..100 {
+            models.predict(
+                CYCLE_TIME,
+                Isometry2::identity(),
+                &without_transitions,
+                &dynamics,
+            );
+            KalmanFilter::predict(
+                &mut baseline_resting,
+                Matrix4::from_diagonal(&Vector4::new(1.0, 1.0, 0.0, 0.0)),
+                Matrix4x2::identity(),
+                Vector2::zeros(),
+                Matrix4::from_diagonal(&vector![0.001, 0.001, 0.0, 0.0]),
+            );
+            KalmanFilter::predict(
+                &mut baseline_rolling,
+                rolling_prediction(dt, 0.0),
+                Matrix4x2::identity(),
+                Vector2::zeros(),
+                Matrix4::from_diagonal(&vector![0.005, 0.005, 0.02, 0.02]),
+            );
+            KalmanFilter::predict(
+                &mut baseline_kicked,
+                rolling_prediction(dt, 0.0),
+                Matrix4x2::identity(),
+                Vector2::zeros(),
+                Matrix4::from_diagonal(&vector![0.01, 0.01, 2.0, 2.0]),
+            );
+        }
+
+        assert_relative_eq!(
+            models
+                .resting
+                .estimate
+                .covariance
+                .fixed_view::<2, 2>(0, 0)
+                .clone_owned(),
+            baseline_resting
+                .covariance
+                .fixed_view::<2, 2>(0, 0)
+                .clone_owned(),
+            max_relative = 0.01
+        );
+        assert_relative_eq!(
+            models.rolling.estimate.covariance,
+            baseline_rolling.covariance,
+            max_relative = 0.01
+        );
+        assert_relative_eq!(
+            models.kicked.estimate.covariance,
+            baseline_kicked.covariance,
+            max_relative = 0.01
+        );
+    }
+}
@@ -2,8 +2,8 @@ use std::{future::Future, pin::Pin, sync::Arc, time::Duration};
 
 use color_eyre::Result;
 use hungarian_algorithm::AssignmentProblem;
-use linear_algebra::{IntoFramed, Isometry2};
-use nalgebra::{Matrix2, Matrix4};
+use linear_algebra::{IntoFramed, Isometry2, Point2, distance};
+use nalgebra::Matrix4;
 use ndarray::Array2;
 use ordered_float::NotNan;
 use ros_z::qos::QosDurability;
@@ -11,22 +11,28 @@ use ros_z::qos::QosDurability;
 use booster::Odometer;
 use coordinate_systems::{Ground, Pixel};
 use geometry::circle::Circle;
-use projection::{Projection, camera_matrix::CameraMatrix};
+use projection::{Projection, camera_matrix::CameraMatrix, rolling_shutter::RowCameraMatrices};
 use ros_z::{context::Context, prelude::*, time::Time};
 use ros_z_streams::CreateFutureMapBuilder;
 use types::{
     ball_detection::BallPercept,
-    ball_position::{BallPosition, HypotheticalBallPosition},
+    ball_position::{BallPosition, BallRestPrediction, HypotheticalBallPosition},
     field_dimensions::FieldDimensions,
+    motion_command::MotionCommand,
     multivariate_normal_distribution::MultivariateNormalDistribution,
     object_detection::{Object, RobocupObjectLabel},
+    obstacles::{Obstacle, ObstacleKind},
     parameters::BallFilterParameters,
+    stereo_depth::StereoObject,
     time_wrapper::TimeWrapper,
 };
 
 pub use crate::{
     filter::BallFilter,
-    hypothesis::{BallHypothesis, BallMode},
+    hypothesis::{
+        BallHypothesis, BallMode,
+        motion_model::{ModelEstimate, MotionModels},
+    },
 };
 
 mod filter;
@@ -55,6 +61,30 @@ pub async fn run(ctx: Arc<Context>) -> Result<()> {
         .with_stamp(|wrapper: &TimeWrapper<CameraMatrix>| wrapper.time)
         .build()
         .await?;
+    let row_camera_matrices_cache = node
+        .subscriber::<TimeWrapper<RowCameraMatrices>>("row_camera_matrices")
+        .cache(10)
+        .with_stamp(|wrapper: &TimeWrapper<RowCameraMatrices>| wrapper.time)
+        .build()
+        .await?;
+    let motion_command_cache = node
+        .subscriber::<MotionCommand>("behavior/motion_command")
+        .cache(1)
+        .build()
+        .await?;
+    let obstacles_cache = node
+        .subscriber::<Vec<Obstacle>>("obstacles")
+        .cache(1)
+        .build()
+        .await?;
+    // stereo objects are announced only once the detected objects reached the stereo depth
+    // node, waiting a bit longer keeps them in the same item as the detections. Without stereo
+    // depth this would only delay every cycle, so enabling it at runtime requires a restart.
+    let stereo_objects_transit_lag = if parameters.snapshot().typed().use_stereo_depth {
+        Duration::from_millis(20)
+    } else {
+        Duration::from_millis(1)
+    };
     let mut future_map = node
         .create_future_map_builder()
         .create_future_subscriber::<Odometer>("inputs/odometer", Duration::from_millis(1))
@@ -64,6 +94,11 @@ pub async fn run(ctx: Arc<Context>) -> Result<()> {
             Duration::from_millis(1),
         )
         .await?
+        .create_future_subscriber::<Vec<StereoObject<RobocupObjectLabel>>>(
+            "stereo_objects",
+            stereo_objects_transit_lag,
+        )
+        .await?
         .build();
     let filter_state_pub = node
         .publisher::<BallFilter>("ball_filter/ball_filter_state")
@@ -85,6 +120,10 @@ pub async fn run(ctx: Arc<Context>) -> Result<()> {
         .publisher::<Option<BallPosition<Ground>>>("ball_filter/ball_position")
         .build()
         .await?;
+    let ball_rest_prediction_pub = node
+        .publisher::<Option<BallRestPrediction<Ground>>>("ball_filter/ball_rest_prediction")
+        .build()
+        .await?;
     let hypothetical_ball_positions_pub = node
         .publisher::<Vec<HypotheticalBallPosition<Ground>>>(
             "ball_filter/hypothetical_ball_positions",
@@ -112,7 +151,14 @@ pub async fn run(ctx: Arc<Context>) -> Result<()> {
             .map(|(time, _)| *time);
         let mut ball_percepts = Vec::new();
 
-        for (time, (odometer, detected_objects)) in future_map_item.persistent {
+        let motion_command = motion_command_cache.get_latest();
+        let obstacles = obstacles_cache.get_latest();
+        let kicking_robots = kicking_robot_positions(
+            motion_command.as_deref(),
+            obstacles.as_deref().map(Vec::as_slice).unwrap_or_default(),
+        );
+
+        for (time, (odometer, detected_objects, stereo_objects)) in future_map_item.persistent {
             if let Some(odometer) = odometer {
                 predict_hypotheses_from_odometry(
                     &mut ball_filter,
@@ -120,6 +166,7 @@ pub async fn run(ctx: Arc<Context>) -> Result<()> {
                     odometer,
                     &mut last_odometer,
                     &mut last_prediction_time,
+                    &kicking_robots,
                     parameters,
                 );
             }
@@ -129,9 +176,17 @@ pub async fn run(ctx: Arc<Context>) -> Result<()> {
                 let camera_matrix = timed_camera_matrix
                     .as_ref()
                     .map(|camera_matrix| &camera_matrix.inner);
+                // row camera matrices are stamped with the image the objects are detected in
+                let timed_row_camera_matrices = row_camera_matrices_cache
+                    .get_nearest(time)
+                    .filter(|row_camera_matrices| row_camera_matrices.time == time);
                 let Some(projected_balls) = project_detected_balls(
                     Some(&detected_objects),
+                    stereo_objects.as_deref(),
                     camera_matrix,
+                    timed_row_camera_matrices
+                        .as_ref()
+                        .map(|row_camera_matrices| &row_camera_matrices.inner),
                     parameters,
                     field_dimensions.ball_radius,
                 ) else {
@@ -204,6 +259,15 @@ pub async fn run(ctx: Arc<Context>) -> Result<()> {
             .await?;
 
         ball_position_pub.publish(&filtered_ball).await?;
+        let ball_rest_prediction = best_hypothesis.and_then(|hypothesis| {
+            hypothesis.rest_prediction(
+                ball_filter.friction_per_second(&parameters.motion_models),
+                parameters.motion_models.stopping_speed,
+            )
+        });
+        ball_rest_prediction_pub
+            .publish(&ball_rest_prediction)
+            .await?;
         let hypothetical_ball_positions =
             hypothetical_ball_positions(&ball_filter, parameters.validity_output_threshold);
         hypothetical_ball_positions_pub
@@ -218,6 +282,7 @@ fn predict_hypotheses_from_odometry(
     odometer: Odometer,
     last_odometer: &mut Option<Odometer>,
     last_prediction_time: &mut Option<Time>,
+    kicking_robots: &[Point2<Ground>],
     filter_parameters: &BallFilterParameters,
 ) {
     let last_to_current = match *last_odometer {
@@ -236,13 +301,32 @@ fn predict_hypotheses_from_odometry(
     ball_filter.predict(
         delta_time,
         last_to_current,
-        filter_parameters.velocity_decay_factor,
-        Matrix4::from_diagonal(&filter_parameters.noise.process_noise_moving),
-        Matrix2::from_diagonal(&filter_parameters.noise.process_noise_resting),
-        filter_parameters.log_likelihood_of_zero_velocity_threshold,
+        &filter_parameters.noise,
+        &filter_parameters.motion_models,
+        |hypothesis| {
+            let ball = hypothesis.position().position;
+            kicking_robots.iter().any(|robot| {
+                distance(*robot, ball) < filter_parameters.motion_models.kicking_robot_distance
+            })
+        },
     );
 }
 
+/// Robots which may kick the ball: ourselves while executing a kick and every other robot.
+fn kicking_robot_positions(
+    motion_command: Option<&MotionCommand>,
+    obstacles: &[Obstacle],
+) -> Vec<Point2<Ground>> {
+    let own_kick =
+        matches!(motion_command, Some(MotionCommand::VisualKick { .. })).then(Point2::origin);
+    obstacles
+        .iter()
+        .filter(|obstacle| obstacle.kind == ObstacleKind::Robot)
+        .map(|obstacle| obstacle.position)
+        .chain(own_kick)
+        .collect()
+}
+
 fn advance_all_hypotheses(
     ball_filter: &mut BallFilter,
     time: Time,
@@ -271,6 +355,7 @@ fn advance_all_hypotheses(
         let assignment = AssignmentProblem::from_costs(match_matrix).solve();
 
         let mut used_percepts = vec![];
+        let mut observed_frictions = vec![];
 
         for (hypothesis, assigned_percept) in
             ball_filter.hypotheses.iter_mut().zip(assignment.iter())
@@ -285,10 +370,19 @@ fn advance_all_hypotheses(
                 let validity_increase = assigned_percept.cost.exp();
                 let percept = ball_percepts[assigned_percept.to];
                 used_percepts.push(assigned_percept.to);
-                hypothesis.update(time, percept.percept_in_ground, validity_increase);
+                observed_frictions.extend(hypothesis.update(
+                    time,
+                    percept.percept_in_ground,
+                    validity_increase,
+                    &filter_parameters.motion_models,
+                ));
             }
         }
 
+        for observed_friction in observed_frictions {
+            ball_filter.learn_friction(observed_friction, &filter_parameters.motion_models);
+        }
+
         let unused_percepts = {
             let mut all_percepts = ball_percepts.to_vec();
             used_percepts.sort_unstable();
@@ -328,8 +422,8 @@ fn remove_invalid_and_merge_hypotheses(
 
     let should_merge_hypotheses =
         |hypothesis1: &BallHypothesis, hypothesis2: &BallHypothesis| match (
-            &hypothesis1.mode,
-            &hypothesis2.mode,
+            hypothesis1.mode(),
+            hypothesis2.mode(),
         ) {
             (BallMode::Resting(ball1), BallMode::Resting(ball2)) => {
                 (ball1.mean - ball2.mean).norm() < filter_parameters.hypothesis_merge_distance
@@ -391,7 +485,9 @@ fn mahalanobis_matrix_of_hypotheses_and_percepts(
 
 fn project_detected_balls(
     detections: Option<&[Object<RobocupObjectLabel>]>,
+    stereo_objects: Option<&[StereoObject<RobocupObjectLabel>]>,
     camera_matrix: Option<&CameraMatrix>,
+    row_camera_matrices: Option<&RowCameraMatrices>,
     parameters: &BallFilterParameters,
     ball_radius: f32,
 ) -> Option<Vec<BallPercept>> {
@@ -406,6 +502,9 @@ fn project_detected_balls(
                     return None;
                 }
                 let area = detection.bounding_box.area;
+                let camera_matrix = row_camera_matrices.map_or(camera_matrix, |camera_matrices| {
+                    camera_matrices.at_row(area.center().y())
+                });
                 let position = camera_matrix
                     .pixel_to_ground_with_z(area.center(), ball_radius)
                     .ok()?;
@@ -430,11 +529,28 @@ fn project_detected_balls(
                         .ok()?
                 };
 
+                let monocular_percept = MultivariateNormalDistribution {
+                    mean: position.inner.coords,
+                    covariance: projected_covariance,
+                };
+                let percept_in_ground = stereo_objects
+                    .filter(|_| parameters.use_stereo_depth)
+                    .and_then(|stereo_objects| {
+                        stereo_objects
+                            .iter()
+                            .find(|stereo_object| stereo_object.object.bounding_box.area == area)
+                    })
+                    .and_then(|stereo_object| {
+                        monocular_percept.fuse(&stereo_ball_center(
+                            stereo_object,
+                            camera_matrix,
+                            ball_radius,
+                        ))
+                    })
+                    .unwrap_or(monocular_percept);
+
                 Some(BallPercept {
-                    percept_in_ground: MultivariateNormalDistribution {
-                        mean: position.inner.coords,
-                        covariance: projected_covariance,
-                    },
+                    percept_in_ground,
                     image_location: circle,
                 })
             })
@@ -442,6 +558,31 @@ fn project_detected_balls(
     )
 }
 
+/// The stereo position lies on the visible surface of the ball, its center is one radius further
+/// along the viewing ray.
+fn stereo_ball_center(
+    stereo_object: &StereoObject<RobocupObjectLabel>,
+    camera_matrix: &CameraMatrix,
+    ball_radius: f32,
+) -> MultivariateNormalDistribution<2> {
+    let surface = stereo_object.position_in_ground.mean;
+    let camera_position = camera_matrix
+        .ground_to_camera
+        .inverse()
+        .inner
+        .translation
+        .vector;
+    let center = surface + (surface - camera_position).normalize() * ball_radius;
+    MultivariateNormalDistribution {
+        mean: center.xy(),
+        covariance: stereo_object
+            .position_in_ground
+            .covariance
+            .fixed_view::<2, 2>(0, 0)
+            .into_owned(),
+    }
+}
+
 fn decide_validity_decay_for_hypothesis(
     hypothesis: &BallHypothesis,
     camera_matrix: Option<&CameraMatrix>,
@@ -501,29 +642,27 @@ fn is_visible_to_camera(
 #[cfg(test)]
 mod tests {
     use linear_algebra::point;
-    use nalgebra::vector;
+    use nalgebra::{Matrix2, vector};
     use types::multivariate_normal_distribution::MultivariateNormalDistribution;
 
     use super::*;
 
     #[test]
     fn hypothesis_update_matching() {
-        let hypothesis1 = BallHypothesis {
-            mode: BallMode::Moving(MultivariateNormalDistribution {
+        let hypothesis1 = BallHypothesis::new(
+            MultivariateNormalDistribution {
                 mean: nalgebra::vector![0.0, 1.0, 0.0, 0.0],
                 covariance: Matrix4::identity(),
-            }),
-            last_seen: Time::zero(),
-            validity: 0.0,
-        };
-        let hypothesis2 = BallHypothesis {
-            mode: BallMode::Moving(MultivariateNormalDistribution {
+            },
+            Time::zero(),
+        );
+        let hypothesis2 = BallHypothesis::new(
+            MultivariateNormalDistribution {
                 mean: nalgebra::vector![0.0, -1.0, 0.0, 0.0],
                 covariance: Matrix4::identity(),
-            }),
-            last_seen: Time::zero(),
-            validity: 0.0,
-        };
+            },
+            Time::zero(),
+        );
 
         let percept1 = BallPercept {
             percept_in_ground: MultivariateNormalDistribution {
@@ -1,11 +1,26 @@
-use std::slice::from_ref;
+use std::{slice::from_ref, time::Duration};
 
-use serde::{Serialize, ser::SerializeStruct};
+use ros_z::time::Time;
+use serde::{Deserialize, Serialize, ser::SerializeStruct};
 use types::behavior_tree::{NodeTrace, Status};
 
 type ConditionFunction<Blackboard> = Box<dyn Fn(&mut Blackboard) -> bool + Send + Sync>;
 type ActionFunction<Blackboard> = Box<dyn Fn(&mut Blackboard) -> Status + Send + Sync>;
 
+/// Time source of the nodes with timers, taken from the blackboard so that the timers follow the
+/// node clock and stay deterministic in the simulator.
+pub trait Clock {
+    fn now(&self) -> Time;
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ParallelPolicy {
+    /// Satisfied as soon as one child returns the status
+    RequireOne,
+    /// Satisfied once all children return the status
+    RequireAll,
+}
+
 pub enum Node<Blackboard> {
     Action {
         name: &'static str,
@@ -15,11 +30,47 @@ pub enum Node<Blackboard> {
         name: &'static str,
         condition: ConditionFunction<Blackboard>,
     },
+    /// Fails without ticking the child for `duration` after the child stopped succeeding,
+    /// either because it failed or because its branch was left.
+    Cooldown {
+        name: &'static str,
+        duration: Duration,
+        child: Box<Node<Blackboard>>,
+        is_active: bool,
+        blocked_since: Option<Time>,
+    },
     Failure,
     Negation {
         name: &'static str,
         child: Box<Node<Blackboard>>,
     },
+    /// Ticks the child until it succeeds or fails once and returns that result afterwards until
+    /// its branch is left.
+    Once {
+        name: &'static str,
+        child: Box<Node<Blackboard>>,
+        result: Option<Status>,
+    },
+    /// Ticks all children every cycle and decides by the policies, failure takes precedence.
+    Parallel {
+        name: &'static str,
+        children: Vec<Node<Blackboard>>,
+        success_policy: ParallelPolicy,
+        failure_policy: ParallelPolicy,
+    },
+    /// Restarts a failed child in the next cycle until it failed `attempts` times in a row.
+    Retry {
+        name: &'static str,
+        attempts: usize,
+        child: Box<Node<Blackboard>>,
+        failures: usize,
+    },
+    /// Sequence which resumes at a running child instead of starting over with the first one.
+    RunningMemory {
+        name: &'static str,
+        children: Vec<Node<Blackboard>>,
+        running_child: Option<usize>,
+    },
     Selection {
         name: &'static str,
         children: Vec<Node<Blackboard>>,
@@ -32,23 +83,23 @@ pub enum Node<Blackboard> {
         name: &'static str,
         tree: Box<Node<Blackboard>>,
     },
+    /// Fails without ticking the child once its branch was entered longer than `duration` ago.
+    Timeout {
+        name: &'static str,
+        duration: Duration,
+        child: Box<Node<Blackboard>>,
+        started: Option<Time>,
+    },
 }
 
-impl<Blackboard> Node<Blackboard> {
-    pub fn tick_with_trace(&self, blackboard: &mut Blackboard) -> (Status, NodeTrace) {
-        let name = match self {
-            Node::Action { name, .. }
-            | Node::Condition { name, .. }
-            | Node::Negation { name, .. }
-            | Node::Selection { name, .. }
-            | Node::Sequence { name, .. }
-            | Node::Subtree { name, .. } => name,
-            Node::Failure => &"Failure",
-        };
+impl<Blackboard: Clock> Node<Blackboard> {
+    pub fn tick_with_trace(&mut self, blackboard: &mut Blackboard) -> (Status, NodeTrace) {
+        let now = blackboard.now();
         let mut trace = NodeTrace {
-            name: name.to_string(),
+            name: self.name().to_string(),
             status: Status::Failure,
             children: Vec::new(),
+            role: None,
         };
 
         let status = match self {
@@ -60,6 +111,30 @@ impl<Blackboard> Node<Blackboard> {
                     Status::Failure
                 }
             }
+            Node::Cooldown {
+                duration,
+                child,
+                is_active,
+                blocked_since,
+                ..
+            } => {
+                if blocked_since.is_some_and(|since| now.duration_since(since) < *duration) {
+                    Status::Failure
+                } else {
+                    *blocked_since = None;
+                    let (child_status, child_trace) = child.tick_with_trace(blackboard);
+                    trace.children.push(child_trace);
+                    match child_status {
+                        Status::Success | Status::Running => *is_active = true,
+                        Status::Failure if *is_active => {
+                            *is_active = false;
+                            *blocked_since = Some(now);
+                        }
+                        _ => {}
+                    }
+                    child_status
+                }
+            }
             Node::Failure => Status::Failure,
             Node::Negation { child, .. } => {
                 let (child_status, child_trace) = child.tick_with_trace(blackboard);
@@ -70,30 +145,131 @@ impl<Blackboard> Node<Blackboard> {
                     _ => child_status,
                 }
             }
+            Node::Once { child, result, .. } => match result {
+                Some(result) => result.clone(),
+                None => {
+                    let (child_status, child_trace) = child.tick_with_trace(blackboard);
+                    trace.children.push(child_trace);
+                    if matches!(child_status, Status::Success | Status::Failure) {
+                        *result = Some(child_status.clone());
+                    }
+                    child_status
+                }
+            },
+            Node::Parallel {
+                children,
+                success_policy,
+                failure_policy,
+                ..
+            } => {
+                let mut successes = 0;
+                let mut failures = 0;
+                for child in children.iter_mut() {
+                    let (child_status, child_trace) = child.tick_with_trace(blackboard);
+                    trace.children.push(child_trace);
+                    match child_status {
+                        Status::Success => successes += 1,
+                        Status::Failure => failures += 1,
+                        _ => {}
+                    }
+                }
+                let is_satisfied = |policy: ParallelPolicy, count: usize| match policy {
+                    ParallelPolicy::RequireOne => count > 0,
+                    ParallelPolicy::RequireAll => count == children.len(),
+                };
+                if is_satisfied(*failure_policy, failures) {
+                    Status::Failure
+                } else if is_satisfied(*success_policy, successes) {
+                    Status::Success
+                } else {
+                    Status::Running
+                }
+            }
+            Node::Retry {
+                attempts,
+                child,
+                failures,
+                ..
+            } => {
+                let (child_status, child_trace) = child.tick_with_trace(blackboard);
+                trace.children.push(child_trace);
+                match child_status {
+                    Status::Failure => {
+                        *failures += 1;
+                        if *failures < *attempts {
+                            child.reset(now);
+                            Status::Running
+                        } else {
+                            *failures = 0;
+                            Status::Failure
+                        }
+                    }
+                    Status::Success => {
+                        *failures = 0;
+                        Status::Success
+                    }
+                    _ => child_status,
+                }
+            }
+            Node::RunningMemory {
+                children,
+                running_child,
+                ..
+            } => {
+                let first_child = running_child.take().unwrap_or(0);
+                // Skipped children are traced as idle so the trace still matches the layout
+                trace.children.extend(
+                    children[..first_child]
+                        .iter()
+                        .map(|child| child.static_layout_trace()),
+                );
+                let mut memory_status = Status::Success;
+                for (index, child) in children.iter_mut().enumerate().skip(first_child) {
+                    let (child_status, child_trace) = child.tick_with_trace(blackboard);
+                    trace.children.push(child_trace);
+
+                    match child_status {
+                        Status::Running => {
+                            *running_child = Some(index);
+                            memory_status = child_status;
+                            break;
+                        }
+                        Status::Failure => {
+                            memory_status = child_status;
+                            break;
+                        }
+                        _ => {}
+                    }
+                }
+                reset_unticked(children, trace.children.len(), now);
+                memory_status
+            }
             Node::Selection { children, .. } => {
                 let mut selection_status = Status::Failure;
-                for child in children {
+                for child in children.iter_mut() {
                     let (child_status, child_trace) = child.tick_with_trace(blackboard);
                     trace.children.push(child_trace);
 
-                    if matches!(child_status, Status::Success) {
+                    if matches!(child_status, Status::Success | Status::Running) {
                         selection_status = child_status;
                         break;
                     }
                 }
+                reset_unticked(children, trace.children.len(), now);
                 selection_status
             }
             Node::Sequence { children, .. } => {
                 let mut sequence_status = Status::Success;
-                for child in children {
+                for child in children.iter_mut() {
                     let (child_status, child_trace) = child.tick_with_trace(blackboard);
                     trace.children.push(child_trace);
 
-                    if matches!(child_status, Status::Failure) {
+                    if matches!(child_status, Status::Failure | Status::Running) {
                         sequence_status = child_status;
                         break;
                     }
                 }
+                reset_unticked(children, trace.children.len(), now);
                 sequence_status
             }
             Node::Subtree { tree, .. } => {
@@ -101,36 +277,137 @@ impl<Blackboard> Node<Blackboard> {
                 trace.children.push(child_trace);
                 child_status
             }
+            Node::Timeout {
+                duration,
+                child,
+                started,
+                ..
+            } => {
+                let started = *started.get_or_insert(now);
+                if now.duration_since(started) >= *duration {
+                    child.reset(now);
+                    Status::Failure
+                } else {
+                    let (child_status, child_trace) = child.tick_with_trace(blackboard);
+                    trace.children.push(child_trace);
+                    child_status
+                }
+            }
         };
 
         trace.status = status.clone();
         (status, trace)
     }
+}
 
-    pub fn static_layout_trace(&self) -> NodeTrace {
-        let name = match self {
+/// Children after the ones ticked this cycle have left their branch.
+fn reset_unticked<Blackboard>(
+    children: &mut [Node<Blackboard>],
+    number_of_ticked_children: usize,
+    now: Time,
+) {
+    for child in children.iter_mut().skip(number_of_ticked_children) {
+        child.reset(now);
+    }
+}
+
+impl<Blackboard> Node<Blackboard> {
+    fn name(&self) -> &'static str {
+        match self {
             Node::Action { name, .. }
             | Node::Condition { name, .. }
+            | Node::Cooldown { name, .. }
             | Node::Negation { name, .. }
+            | Node::Once { name, .. }
+            | Node::Parallel { name, .. }
+            | Node::Retry { name, .. }
+            | Node::RunningMemory { name, .. }
             | Node::Selection { name, .. }
             | Node::Sequence { name, .. }
-            | Node::Subtree { name, .. } => name,
-            Node::Failure => &"Failure",
-        };
+            | Node::Subtree { name, .. }
+            | Node::Timeout { name, .. } => name,
+            Node::Failure => "Failure",
+        }
+    }
 
-        let children = match self {
-            Node::Selection { children, .. } | Node::Sequence { children, .. } => {
-                children.iter().map(|c| c.static_layout_trace()).collect()
+    fn children(&self) -> &[Node<Blackboard>] {
+        match self {
+            Node::Parallel { children, .. }
+            | Node::RunningMemory { children, .. }
+            | Node::Selection { children, .. }
+            | Node::Sequence { children, .. } => children,
+            Node::Cooldown { child, .. }
+            | Node::Negation { child, .. }
+            | Node::Once { child, .. }
+            | Node::Retry { child, .. }
+            | Node::Subtree { tree: child, .. }
+            | Node::Timeout { child, .. } => from_ref(child.as_ref()),
+            Node::Action { .. } | Node::Condition { .. } | Node::Failure => &[],
+        }
+    }
+
+    /// Clears the memory of this node and its descendants once their branch is left, cooldowns
+    /// of children which were active start at `now`.
+    pub fn reset(&mut self, now: Time) {
+        match self {
+            Node::Action { .. } | Node::Condition { .. } | Node::Failure => {}
+            Node::Cooldown {
+                child,
+                is_active,
+                blocked_since,
+                ..
+            } => {
+                if *is_active {
+                    *is_active = false;
+                    *blocked_since = Some(now);
+                }
+                child.reset(now);
             }
-            Node::Negation { child, .. } => vec![child.static_layout_trace()],
-            Node::Subtree { tree, .. } => vec![tree.static_layout_trace()],
-            _ => vec![],
-        };
+            Node::Negation { child, .. } | Node::Subtree { tree: child, .. } => child.reset(now),
+            Node::Once { child, result, .. } => {
+                *result = None;
+                child.reset(now);
+            }
+            Node::Parallel { children, .. }
+            | Node::Selection { children, .. }
+            | Node::Sequence { children, .. } => {
+                for child in children {
+                    child.reset(now);
+                }
+            }
+            Node::Retry {
+                child, failures, ..
+            } => {
+                *failures = 0;
+                child.reset(now);
+            }
+            Node::RunningMemory {
+                children,
+                running_child,
+                ..
+            } => {
+                *running_child = None;
+                for child in children {
+                    child.reset(now);
+                }
+            }
+            Node::Timeout { child, started, .. } => {
+                *started = None;
+                child.reset(now);
+            }
+        }
+    }
 
+    pub fn static_layout_trace(&self) -> NodeTrace {
         NodeTrace {
-            name: name.to_string(),
+            name: self.name().to_string(),
             status: Status::Idle,
-            children,
+            children: self
+                .children()
+                .iter()
+                .map(|child| child.static_layout_trace())
+                .collect(),
+            role: None,
         }
     }
 }
@@ -171,6 +448,19 @@ macro_rules! condition {
     };
 }
 
+#[macro_export]
+macro_rules! cooldown {
+    ($duration:expr, $child:expr $(,)?) => {
+        $crate::behavior_tree::Node::Cooldown {
+            name: "Cooldown",
+            duration: $duration,
+            child: Box::new($child),
+            is_active: false,
+            blocked_since: None,
+        }
+    };
+}
+
 #[macro_export]
 macro_rules! negation {
     ($child:expr) => {
@@ -181,6 +471,53 @@ macro_rules! negation {
     };
 }
 
+#[macro_export]
+macro_rules! once {
+    ($child:expr $(,)?) => {
+        $crate::behavior_tree::Node::Once {
+            name: "Once",
+            child: Box::new($child),
+            result: None,
+        }
+    };
+}
+
+/// `parallel!(success_policy, failure_policy; children...)`
+#[macro_export]
+macro_rules! parallel {
+    ($success_policy:expr, $failure_policy:expr; $($child:expr),* $(,)?) => {
+        $crate::behavior_tree::Node::Parallel {
+            name: "Parallel",
+            children: vec![$($child),*],
+            success_policy: $success_policy,
+            failure_policy: $failure_policy,
+        }
+    };
+}
+
+#[macro_export]
+macro_rules! retry {
+    ($attempts:expr, $child:expr $(,)?) => {
+        $crate::behavior_tree::Node::Retry {
+            name: "Retry",
+            attempts: $attempts,
+            child: Box::new($child),
+            failures: 0,
+        }
+    };
+}
+
+#[macro_export]
+macro_rules! running_memory {
+    ($($child:expr),* $(,)?) => {
+        $crate::behavior_tree::Node::RunningMemory {
+            name: "RunningMemory",
+            children: vec![$($child),*],
+            running_child: None,
+        }
+    };
+}
+
 #[macro_export]
 macro_rules! selection {
     ($($child:expr),* $(,)?) => {
@@ -217,26 +554,48 @@ macro_rules! subtree {
     };
 }
 
+#[macro_export]
+macro_rules! timeout {
+    ($duration:expr, $child:expr $(,)?) => {
+        $crate::behavior_tree::Node::Timeout {
+            name: "Timeout",
+            duration: $duration,
+            child: Box::new($child),
+            started: None,
+        }
+    };
+}
+
 impl<Blackboard> Serialize for Node<Blackboard> {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
         S: serde::Serializer,
     {
-        let (node_type, name, children) = match self {
-            Node::Action { name, .. } => ("Action", *name, None),
-            Node::Condition { name, .. } => ("Condition", *name, None),
-            Node::Failure => ("Failure", "Failure", None),
-            Node::Negation { name, child } => ("Negation", *name, Some(from_ref(child.as_ref()))),
-            Node::Selection { name, children } => ("Selection", *name, Some(children.as_slice())),
-            Node::Sequence { name, children } => ("Sequence", *name, Some(children.as_slice())),
-            Node::Subtree { name, tree } => ("Subtree", *name, Some(from_ref(tree.as_ref()))),
+        let node_type = match self {
+            Node::Action { .. } => "Action",
+            Node::Condition { .. } => "Condition",
+            Node::Cooldown { .. } => "Cooldown",
+            Node::Failure => "Failure",
+            Node::Negation { .. } => "Negation",
+            Node::Once { .. } => "Once",
+            Node::Parallel { .. } => "Parallel",
+            Node::Retry { .. } => "Retry",
+            Node::RunningMemory { .. } => "RunningMemory",
+            Node::Selection { .. } => "Selection",
+            Node::Sequence { .. } => "Sequence",
+            Node::Subtree { .. } => "Subtree",
+            Node::Timeout { .. } => "Timeout",
+        };
+        let children = match self {
+            Node::Action { .. } | Node::Condition { .. } | Node::Failure => None,
+            _ => Some(self.children()),
         };
 
         let num_fields = if children.is_some() { 3 } else { 2 };
         let mut state = serializer.serialize_struct("Node", num_fields)?;
 
         state.serialize_field("type", node_type)?;
-        state.serialize_field("name", name)?;
+        state.serialize_field("name", self.name())?;
 
         if let Some(c) = children {
             state.serialize_field("children", c)?;
@@ -245,3 +604,239 @@ impl<Blackboard> Serialize for Node<Blackboard> {
         state.end()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+    use crate::{cooldown, once, parallel, retry, running_memory, sequence, timeout};
+
+    #[derive(Default)]
+    struct FakeBlackboard {
+        now: Time,
+        results: HashMap<&'static str, Status>,
+        ticks: HashMap<&'static str, usize>,
+    }
+
+    impl Clock for FakeBlackboard {
+        fn now(&self) -> Time {
+            self.now
+        }
+    }
+
+    impl FakeBlackboard {
+        fn at(&mut self, milliseconds: u64) -> &mut Self {
+            self.now = Time::zero() + Duration::from_millis(milliseconds);
+            self
+        }
+
+        fn returns(&mut self, name: &'static str, status: Status) -> &mut Self {
+            self.results.insert(name, status);
+            self
+        }
+
+        fn ticks(&self, name: &'static str) -> usize {
+            self.ticks.get(name).copied().unwrap_or_default()
+        }
+    }
+
+    fn leaf(name: &'static str) -> Node<FakeBlackboard> {
+        Node::Action {
+            name,
+            action: Box::new(move |blackboard: &mut FakeBlackboard| {
+                *blackboard.ticks.entry(name).or_default() += 1;
+                blackboard.results[name].clone()
+            }),
+        }
+    }
+
+    fn tick(node: &mut Node<FakeBlackboard>, blackboard: &mut FakeBlackboard) -> Status {
+        node.tick_with_trace(blackboard).0
+    }
+
+    #[test]
+    fn cooldown_blocks_the_child_after_it_stopped_succeeding() {
+        let mut blackboard = FakeBlackboard::default();
+        let mut node = cooldown!(Duration::from_secs(1), leaf("kick"));
+
+        blackboard.at(0).returns("kick", Status::Success);
+        assert_eq!(tick(&mut node, &mut blackboard), Status::Success);
+        blackboard.at(100).returns("kick", Status::Failure);
+        assert_eq!(tick(&mut node, &mut blackboard), Status::Failure);
+
+        blackboard.at(500).returns("kick", Status::Success);
+        let (status, trace) = node.tick_with_trace(&mut blackboard);
+        assert_eq!(status, Status::Failure);
+        assert!(trace.children.is_empty());
+        assert_eq!(blackboard.ticks("kick"), 2);
+
+        blackboard.at(1100);
+        assert_eq!(tick(&mut node, &mut blackboard), Status::Success);
+        assert_eq!(blackboard.ticks("kick"), 3);
+    }
+
+    #[test]
+    fn cooldown_starts_when_its_branch_is_left() {
+        let mut blackboard = FakeBlackboard::default();
+        let mut node = sequence!(
+            leaf("is_close"),
+            cooldown!(Duration::from_secs(1), leaf("kick"))
+        );
+
+        blackboard
+            .at(0)
+            .returns("is_close", Status::Success)
+            .returns("kick", Status::Running);
+        assert_eq!(tick(&mut node, &mut blackboard), Status::Running);
+        blackboard.at(100).returns("is_close", Status::Failure);
+        assert_eq!(tick(&mut node, &mut blackboard), Status::Failure);
+
+        blackboard.at(600).returns("is_close", Status::Success);
+        assert_eq!(tick(&mut node, &mut blackboard), Status::Failure);
+        assert_eq!(blackboard.ticks("kick"), 1);
+
+        blackboard.at(1100);
+        assert_eq!(tick(&mut node, &mut blackboard), Status::Running);
+        assert_eq!(blackboard.ticks("kick"), 2);
+    }
+
+    #[test]
+    fn timeout_fails_once_its_branch_was_entered_too_long_ago() {
+        let mut blackboard = FakeBlackboard::default();
+        let mut node = timeout!(Duration::from_secs(1), leaf("search"));
+
+        blackboard.at(500).returns("search", Status::Running);
+        assert_eq!(tick(&mut node, &mut blackboard), Status::Running);
+        blackboard.at(1400);
+        assert_eq!(tick(&mut node, &mut blackboard), Status::Running);
+        blackboard.at(1500);
+        assert_eq!(tick(&mut node, &mut blackboard), Status::Failure);
+        assert_eq!(blackboard.ticks("search"), 2);
+
+        node.reset(Time::zero() + Duration::from_millis(1600));
+        blackboard.at(2000);
+        assert_eq!(tick(&mut node, &mut blackboard), Status::Running);
+        assert_eq!(blackboard.ticks("search"), 3);
+    }
+
+    #[test]
+    fn once_keeps_the_first_result_until_reset() {
+        let mut blackboard = FakeBlackboard::default();
+        let mut node = once!(leaf("announce"));
+
+        blackboard.returns("announce", Status::Running);
+        assert_eq!(tick(&mut node, &mut blackboard), Status::Running);
+        blackboard.returns("announce", Status::Success);
+        assert_eq!(tick(&mut node, &mut blackboard), Status::Success);
+        blackboard.returns("announce", Status::Failure);
+        assert_eq!(tick(&mut node, &mut blackboard), Status::Success);
+        assert_eq!(blackboard.ticks("announce"), 2);
+
+        node.reset(blackboard.now);
+        assert_eq!(tick(&mut node, &mut blackboard), Status::Failure);
+        assert_eq!(blackboard.ticks("announce"), 3);
+    }
+
+    #[test]
+    fn parallel_ticks_all_children_and_decides_by_its_policies() {
+        let mut blackboard = FakeBlackboard::default();
+        let mut require_all = parallel!(
+            ParallelPolicy::RequireAll, ParallelPolicy::RequireOne;
+            leaf("walk"),
+            leaf("look")
+        );
+        let mut require_one = parallel!(
+            ParallelPolicy::RequireOne, ParallelPolicy::RequireAll;
+            leaf("walk"),
+            leaf("look")
+        );
+
+        blackboard
+            .returns("walk", Status::Success)
+            .returns("look", Status::Running);
+        assert_eq!(tick(&mut require_all, &mut blackboard), Status::Running);
+        assert_eq!(tick(&mut require_one, &mut blackboard), Status::Success);
+
+        blackboard.returns("look", Status::Success);
+        assert_eq!(tick(&mut require_all, &mut blackboard), Status::Success);
+
+        blackboard.returns("walk", Status::Failure);
+        assert_eq!(tick(&mut require_all, &mut blackboard), Status::Failure);
+        assert_eq!(tick(&mut require_one, &mut blackboard), Status::Success);
+
+        blackboard.returns("look", Status::Failure);
+        assert_eq!(tick(&mut require_one, &mut blackboard), Status::Failure);
+
+        assert_eq!(blackboard.ticks("walk"), 6);
+        assert_eq!(blackboard.ticks("look"), 6);
+    }
+
+    #[test]
+    fn retry_restarts_the_child_until_it_failed_too_often() {
+        let mut blackboard = FakeBlackboard::default();
+        let mut node = retry!(3, once!(leaf("stand_up")));
+
+        blackboard.returns("stand_up", Status::Failure);
+        assert_eq!(tick(&mut node, &mut blackboard), Status::Running);
+        assert_eq!(tick(&mut node, &mut blackboard), Status::Running);
+        assert_eq!(tick(&mut node, &mut blackboard), Status::Failure);
+        assert_eq!(blackboard.ticks("stand_up"), 3);
+
+        assert_eq!(tick(&mut node, &mut blackboard), Status::Running);
+        blackboard.returns("stand_up", Status::Success);
+        assert_eq!(tick(&mut node, &mut blackboard), Status::Success);
+        blackboard.returns("stand_up", Status::Failure);
+        assert_eq!(tick(&mut node, &mut blackboard), Status::Success);
+    }
+
+    #[test]
+    fn running_memory_resumes_at_the_running_child() {
+        let mut blackboard = FakeBlackboard::default();
+        let mut node = running_memory!(leaf("prepare"), leaf("walk"), leaf("kick"));
+
+        blackboard
+            .returns("prepare", Status::Success)
+            .returns("walk", Status::Running)
+            .returns("kick", Status::Success);
+        assert_eq!(tick(&mut node, &mut blackboard), Status::Running);
+
+        blackboard
+            .returns("prepare", Status::Failure)
+            .returns("walk", Status::Success);
+        let (status, trace) = node.tick_with_trace(&mut blackboard);
+        assert_eq!(status, Status::Success);
+        assert_eq!(blackboard.ticks("prepare"), 1);
+        assert_eq!(blackboard.ticks("kick"), 1);
+        assert_eq!(
+            trace
+                .children
+                .iter()
+                .map(|child| (child.name.as_str(), child.status.clone()))
+                .collect::<Vec<_>>(),
+            vec![
+                ("prepare", Status::Idle),
+                ("walk", Status::Success),
+                ("kick", Status::Success),
+            ]
+        );
+
+        assert_eq!(tick(&mut node, &mut blackboard), Status::Failure);
+        assert_eq!(blackboard.ticks("prepare"), 2);
+    }
+
+    #[test]
+    fn static_layout_covers_the_children_of_decorators() {
+        let node: Node<FakeBlackboard> = timeout!(
+            Duration::from_secs(5),
+            cooldown!(Duration::from_secs(1), leaf("kick"))
+        );
+
+        let layout = node.static_layout_trace();
+
+        assert_eq!(layout.name, "Timeout");
+        assert_eq!(layout.children[0].name, "Cooldown");
+        assert_eq!(layout.children[0].children[0].name, "kick");
+        assert_eq!(layout.children[0].children[0].status, Status::Idle);
+    }
+}
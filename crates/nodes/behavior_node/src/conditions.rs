@@ -4,7 +4,6 @@ use linear_algebra::{point, vector};
 use types::{
     filtered_game_controller_state::FilteredGameControllerState, primary_state::PrimaryState,
 };
-use voronoi::Ownership;
 
 use crate::node::Blackboard;
 
@@ -92,55 +91,6 @@ pub fn is_close_to_ball_aligned(blackboard: &mut Blackboard) -> bool {
     is_close_and_aligned
 }
 
-pub fn is_closest_to_ball(blackboard: &mut Blackboard) -> bool {
-    let own_player_number = blackboard.world_state.robot.player_number;
-
-    let raw_is_closest =
-        if let (Some(ball), Some(voronoi_map)) = (&blackboard.ball, &blackboard.voronoi_map) {
-            let ownership_at_ball = voronoi_map.ownership_at(ball.position);
-            match ownership_at_ball {
-                Some(Ownership::Robot(player_number)) if player_number == own_player_number => true,
-                Some(Ownership::Blocked) => voronoi_map
-                    .nearest_non_blocked_ownership(ball.position)
-                    .is_some_and(|ownership| ownership == Ownership::Robot(own_player_number)),
-                _ => false,
-            }
-        } else {
-            false
-        };
-
-    let now = blackboard.world_state.now;
-    if raw_is_closest {
-        blackboard.closest_to_ball_entered_area_since =
-            blackboard.closest_to_ball_entered_area_since.or(Some(now));
-        blackboard.closest_to_ball_left_area_since = None;
-    } else {
-        blackboard.closest_to_ball_left_area_since =
-            blackboard.closest_to_ball_left_area_since.or(Some(now));
-        blackboard.closest_to_ball_entered_area_since = None;
-    }
-
-    let is_closest = if blackboard.last_closest_to_ball {
-        raw_is_closest
-            || blackboard
-                .closest_to_ball_left_area_since
-                .is_some_and(|since| {
-                    now.duration_since(since) < blackboard.parameters.closest_to_ball_exit_duration
-                })
-    } else {
-        raw_is_closest
-            && blackboard
-                .closest_to_ball_entered_area_since
-                .is_some_and(|since| {
-                    now.duration_since(since)
-                        >= blackboard.parameters.closest_to_ball_enter_duration
-                })
-    };
-
-    blackboard.last_closest_to_ball = is_closest;
-    is_closest
-}
-
 pub fn is_fallen(blackboard: &mut Blackboard) -> bool {
     blackboard
         .world_state
@@ -148,10 +98,6 @@ pub fn is_fallen(blackboard: &mut Blackboard) -> bool {
         .is_some_and(|fall_down_state| fall_down_state.is_recovery_available)
 }
 
-pub fn is_goalkeeper(blackboard: &mut Blackboard) -> bool {
-    blackboard.world_state.robot.player_number == blackboard.parameters.goal_keeper_number
-}
-
 pub fn is_primary_state(blackboard: &mut Blackboard, primary_state: PrimaryState) -> bool {
     blackboard.world_state.robot.primary_state == primary_state
 }
@@ -188,15 +134,3 @@ pub fn hulks_is_kicking_team(blackboard: &mut Blackboard) -> bool {
         })
     )
 }
-
-pub fn is_last_hulk_standing(blackboard: &mut Blackboard) -> bool {
-    let own_player_number = blackboard.world_state.robot.player_number;
-
-    blackboard
-        .world_state
-        .player_states
-        .iter()
-        .all(|(player_number, player_state)| {
-            player_number == own_player_number || player_state.is_none()
-        })
-}
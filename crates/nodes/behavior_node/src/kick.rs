@@ -1,6 +1,6 @@
 use coordinate_systems::Field;
 use geometry::line::Line;
-use linear_algebra::{Orientation2, Point, Point2, Rotation2, point};
+use linear_algebra::{Orientation2, Point, Rotation2, point};
 use types::{
     behavior_tree::Status,
     motion_command::{BodyMotion, KickPower, MotionCommand},
@@ -11,7 +11,9 @@ use crate::{
     action,
     actions::stand,
     behavior_tree::Node,
-    condition, negation,
+    condition,
+    kick_target::evaluate_kick_targets,
+    negation,
     node::Blackboard,
     selection, sequence, subtree,
     switch_motion_type::{is_last_motion_type, switch_motion_type},
@@ -67,10 +69,14 @@ pub fn select_kick_target(blackboard: &mut Blackboard) -> Status {
         blackboard.world_state.robot.ground_to_field,
         &blackboard.ball,
     ) {
-        let goal_position: Point2<Field> = point!(blackboard.field_dimensions.length / 2.0, 0.0);
+        blackboard.kick_target_candidates = evaluate_kick_targets(blackboard);
+        let target_in_field = blackboard.kick_target_candidates.first().map_or_else(
+            || point!(blackboard.field_dimensions.length / 2.0, 0.0),
+            |candidate| candidate.target,
+        );
         let field_to_ground = ground_to_field.inverse();
 
-        let target_position = field_to_ground * goal_position;
+        let target_position = field_to_ground * target_in_field;
 
         let ball_in_ground = field_to_ground * ball.position;
         let kick_direction = Orientation2::from_vector(target_position - ball_in_ground);
@@ -98,6 +104,7 @@ pub fn kick_power_subtree() -> Node<Blackboard> {
             condition!(is_last_motion_type, MotionType::Kick),
             action!(use_last_kick_power)
         ),
+        action!(use_kick_target_power),
         sequence!(
             negation!(condition!(is_close_to_target)),
             condition!(allow_schlong),
@@ -143,6 +150,14 @@ pub fn use_last_kick_power(blackboard: &mut Blackboard) -> Status {
     Status::Failure
 }
 
+/// Kicks with the power the selected kick target was evaluated for.
+pub fn use_kick_target_power(blackboard: &mut Blackboard) -> Status {
+    if let Some(candidate) = blackboard.kick_target_candidates.first() {
+        return use_kick_power(blackboard, candidate.kick_power);
+    }
+    Status::Failure
+}
+
 pub fn use_kick_power(blackboard: &mut Blackboard, kick_power: KickPower) -> Status {
     if let Some(BodyMotion::VisualKick {
         kick_power: motion_kick_power,
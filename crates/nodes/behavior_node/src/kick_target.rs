@@ -0,0 +1,550 @@
+use std::f32::consts::{PI, SQRT_2};
+
+use coordinate_systems::Field;
+use hsl_network_messages::PlayerNumber;
+use linear_algebra::{Point2, Vector2, distance, point, vector};
+use types::{
+    field_dimensions::FieldDimensions,
+    kick_target::{KickTargetCandidate, KickTargetKind},
+    motion_command::KickPower,
+    obstacles::ObstacleKind,
+    parameters::{KickTargetParameters, KickingParameters},
+};
+use voronoi::VoronoiGrid;
+
+use crate::node::Blackboard;
+
+/// Obstacle in field coordinates with the radius the ball has to pass it at.
+#[derive(Clone, Copy, Debug)]
+struct Opponent {
+    position: Point2<Field>,
+    radius: f32,
+}
+
+/// Samples shots at the goal openings next to the goalkeeper, passes to teammates and kicks into
+/// the zones of teammates and scores them, the best candidate comes first.
+pub fn evaluate_kick_targets(blackboard: &Blackboard) -> Vec<KickTargetCandidate> {
+    let (Some(ball), Some(ground_to_field)) = (
+        &blackboard.ball,
+        blackboard.world_state.robot.ground_to_field,
+    ) else {
+        return Vec::new();
+    };
+    let parameters = &blackboard.parameters.kick_target;
+    let field_dimensions = &blackboard.field_dimensions;
+    let own_player_number = blackboard.world_state.robot.player_number;
+
+    let teammates: Vec<(PlayerNumber, Point2<Field>)> = blackboard
+        .world_state
+        .player_states
+        .iter()
+        .filter(|(player_number, _)| *player_number != own_player_number)
+        .filter_map(|(player_number, player_state)| {
+            player_state
+                .as_ref()
+                .map(|player_state| (player_number, player_state.pose.position()))
+        })
+        .collect();
+    let opponents: Vec<Opponent> = blackboard
+        .world_state
+        .obstacles
+        .iter()
+        .filter(|obstacle| obstacle.kind == ObstacleKind::Robot)
+        .map(|obstacle| Opponent {
+            position: ground_to_field * obstacle.position,
+            radius: obstacle.radius_at_foot_height + field_dimensions.ball_radius,
+        })
+        .filter(|opponent| {
+            teammates.iter().all(|(_, teammate)| {
+                distance(*teammate, opponent.position) > parameters.teammate_obstacle_distance
+            })
+        })
+        .collect();
+
+    let goal_openings = goal_openings(field_dimensions, &opponents)
+        .into_iter()
+        .map(|(left, right)| (KickTargetKind::Goal, TargetArea::Segment { left, right }));
+    let passes = teammates.iter().map(|(player_number, position)| {
+        (
+            KickTargetKind::Pass(*player_number),
+            TargetArea::Point(*position),
+        )
+    });
+    let zones = blackboard
+        .voronoi_map
+        .iter()
+        .flat_map(|voronoi_map| zone_targets(voronoi_map, &teammates));
+
+    let mut candidates: Vec<_> = goal_openings
+        .chain(passes)
+        .chain(zones)
+        .filter_map(|(kind, area)| {
+            evaluate_candidate(
+                kind,
+                area,
+                ball.position,
+                &opponents,
+                parameters,
+                &blackboard.parameters.kicking,
+            )
+        })
+        .collect();
+    candidates.sort_by(|a, b| b.score.total_cmp(&a.score));
+    candidates
+}
+
+#[derive(Clone, Copy, Debug)]
+enum TargetArea {
+    Segment {
+        left: Point2<Field>,
+        right: Point2<Field>,
+    },
+    Point(Point2<Field>),
+}
+
+/// Parts of the opponent goal next to the goalkeeper, the whole goal if nobody guards it.
+fn goal_openings(
+    field_dimensions: &FieldDimensions,
+    opponents: &[Opponent],
+) -> Vec<(Point2<Field>, Point2<Field>)> {
+    let goal_line = field_dimensions.length / 2.0;
+    let post_offset = field_dimensions.goal_inner_width / 2.0;
+    let left_post = point![goal_line, post_offset];
+    let right_post = point![goal_line, -post_offset];
+
+    let goalkeeper = opponents
+        .iter()
+        .filter(|opponent| {
+            opponent.position.x() > goal_line - field_dimensions.penalty_area_length
+                && opponent.position.y().abs() < field_dimensions.penalty_area_width / 2.0
+        })
+        .min_by(|a, b| {
+            distance(a.position, point![goal_line, 0.0])
+                .total_cmp(&distance(b.position, point![goal_line, 0.0]))
+        });
+    let Some(goalkeeper) = goalkeeper else {
+        return vec![(left_post, right_post)];
+    };
+
+    let keeper_left = (goalkeeper.position.y() + goalkeeper.radius).min(post_offset);
+    let keeper_right = (goalkeeper.position.y() - goalkeeper.radius).max(-post_offset);
+    [
+        (left_post, point![goal_line, keeper_left]),
+        (point![goal_line, keeper_right], right_post),
+    ]
+    .into_iter()
+    .filter(|(left, right)| left.y() > right.y())
+    .collect()
+}
+
+fn zone_targets(
+    voronoi_map: &VoronoiGrid,
+    teammates: &[(PlayerNumber, Point2<Field>)],
+) -> Vec<(KickTargetKind, TargetArea)> {
+    teammates
+        .iter()
+        .filter_map(|(player_number, _)| {
+            let centroid = voronoi_map.centroid_for_player(*player_number)?;
+            Some((
+                KickTargetKind::Zone(*player_number),
+                TargetArea::Point(centroid),
+            ))
+        })
+        .collect()
+}
+
+fn evaluate_candidate(
+    kind: KickTargetKind,
+    area: TargetArea,
+    ball: Point2<Field>,
+    opponents: &[Opponent],
+    parameters: &KickTargetParameters,
+    kicking: &KickingParameters,
+) -> Option<KickTargetCandidate> {
+    let (nominal, left, right) = match area {
+        TargetArea::Segment { left, right } => (
+            point![(left.x() + right.x()) / 2.0, (left.y() + right.y()) / 2.0],
+            left,
+            right,
+        ),
+        TargetArea::Point(position) => {
+            let direction = position - ball;
+            let sideways = vector![-direction.y(), direction.x()]
+                * (parameters.target_width / 2.0 / direction.norm().max(f32::EPSILON));
+            (position, position + sideways, position - sideways)
+        }
+    };
+    let target_distance = distance(ball, nominal);
+    if target_distance < f32::EPSILON {
+        return None;
+    }
+    let nominal_bearing = bearing(nominal - ball);
+    let relative_bearing =
+        |position: Point2<Field>| normalize_angle(bearing(position - ball) - nominal_bearing);
+
+    let blocked: Vec<(f32, f32)> = opponents
+        .iter()
+        .filter_map(|opponent| {
+            let opponent_distance = distance(ball, opponent.position);
+            if opponent_distance >= target_distance {
+                return None;
+            }
+            let half_width = (opponent.radius / opponent_distance).min(1.0).asin();
+            let center = relative_bearing(opponent.position);
+            Some((center - half_width, center + half_width))
+        })
+        .collect();
+    let (free_start, free_end) = widest_free_interval(
+        relative_bearing(right).min(relative_bearing(left)),
+        relative_bearing(right).max(relative_bearing(left)),
+        blocked,
+    )?;
+    let opening_angle = free_end - free_start;
+
+    let aim_bearing = nominal_bearing + (free_start + free_end) / 2.0;
+    let target = ball + vector![aim_bearing.cos(), aim_bearing.sin()] * target_distance;
+
+    let kick_power = if kicking.allow_schlong
+        && target_distance > kicking.target_distance_kick_power_threshold
+    {
+        KickPower::Schlong
+    } else {
+        KickPower::Rumpelstilzchen
+    };
+    let accuracy_parameters = match kick_power {
+        KickPower::Rumpelstilzchen => parameters.rumpelstilzchen_accuracy,
+        KickPower::Schlong => parameters.schlong_accuracy,
+    };
+    let accuracy = error_function(
+        opening_angle / 2.0 / (accuracy_parameters.angular_standard_deviation * SQRT_2),
+    ) * (accuracy_parameters.range / target_distance).min(1.0);
+
+    let interception_risk = opponents
+        .iter()
+        .map(|opponent| interception_risk(ball, target, opponent, parameters))
+        .fold(0.0, f32::max);
+
+    let value = match kind {
+        KickTargetKind::Goal => parameters.goal_value,
+        KickTargetKind::Pass(_) => parameters.pass_value,
+        KickTargetKind::Zone(_) => parameters.zone_value,
+    };
+
+    Some(KickTargetCandidate {
+        kind,
+        target,
+        kick_power,
+        opening_angle,
+        accuracy,
+        interception_risk,
+        score: value * accuracy * (1.0 - interception_risk),
+    })
+}
+
+/// Widest part of `[start, end]` not covered by any of the blocked intervals.
+fn widest_free_interval(start: f32, end: f32, mut blocked: Vec<(f32, f32)>) -> Option<(f32, f32)> {
+    blocked.sort_by(|a, b| a.0.total_cmp(&b.0));
+    let mut widest: Option<(f32, f32)> = None;
+    let mut free_start = start;
+    for (blocked_start, blocked_end) in blocked.into_iter().chain(std::iter::once((end, end))) {
+        let free_end = blocked_start.min(end);
+        if free_end > free_start
+            && widest.is_none_or(|(widest_start, widest_end)| {
+                free_end - free_start > widest_end - widest_start
+            })
+        {
+            widest = Some((free_start, free_end));
+        }
+        free_start = free_start.max(blocked_end);
+    }
+    widest
+}
+
+/// An opponent intercepts if it can walk onto the path of the ball before the ball passes by.
+fn interception_risk(
+    ball: Point2<Field>,
+    target: Point2<Field>,
+    opponent: &Opponent,
+    parameters: &KickTargetParameters,
+) -> f32 {
+    let path = target - ball;
+    let length = path.norm();
+    let direction = path / length;
+    let to_opponent = opponent.position - ball;
+    let along = to_opponent.dot(&direction);
+    if along <= 0.0 || along >= length {
+        return 0.0;
+    }
+    let perpendicular = (to_opponent - direction * along).norm();
+    let reach = parameters.opponent_speed * along / parameters.ball_speed + opponent.radius;
+    let margin = perpendicular - reach;
+    if margin <= 0.0 {
+        1.0
+    } else {
+        (-margin / parameters.interception_risk_falloff).exp()
+    }
+}
+
+fn bearing(vector: Vector2<Field>) -> f32 {
+    vector.y().atan2(vector.x())
+}
+
+fn normalize_angle(angle: f32) -> f32 {
+    (angle + PI).rem_euclid(2.0 * PI) - PI
+}
+
+/// Abramowitz and Stegun 7.1.26, accurate to 1.5e-7.
+fn error_function(x: f32) -> f32 {
+    let t = 1.0 / (1.0 + 0.327_591_1 * x.abs());
+    let polynomial = t
+        * (0.254_829_6
+            + t * (-0.284_496_7 + t * (1.421_413_8 + t * (-1.453_152_1 + t * 1.061_405_4))));
+    (1.0 - polynomial * (-x * x).exp()).copysign(x)
+}
+
+#[cfg(test)]
+mod tests {
+    use approx::assert_relative_eq;
+    use types::parameters::KickAccuracyParameters;
+
+    use super::*;
+
+    fn parameters() -> KickTargetParameters {
+        KickTargetParameters {
+            goal_value: 1.0,
+            pass_value: 0.6,
+            zone_value: 0.4,
+            target_width: 0.5,
+            rumpelstilzchen_accuracy: KickAccuracyParameters {
+                angular_standard_deviation: 0.1,
+                range: 3.0,
+            },
+            schlong_accuracy: KickAccuracyParameters {
+                angular_standard_deviation: 0.15,
+                range: 7.0,
+            },
+            ball_speed: 1.5,
+            opponent_speed: 0.4,
+            interception_risk_falloff: 0.3,
+            teammate_obstacle_distance: 0.3,
+        }
+    }
+
+    fn kicking(allow_schlong: bool) -> KickingParameters {
+        KickingParameters {
+            allow_schlong,
+            target_distance_kick_power_threshold: 4.0,
+            ..Default::default()
+        }
+    }
+
+    fn open_goal(field_dimensions: &FieldDimensions) -> TargetArea {
+        let (left, right) = goal_openings(field_dimensions, &[])[0];
+        TargetArea::Segment { left, right }
+    }
+
+    #[test]
+    fn unguarded_goal_is_one_opening_between_the_posts() {
+        let field_dimensions = FieldDimensions::SPL_2025;
+
+        let openings = goal_openings(&field_dimensions, &[]);
+
+        assert_eq!(openings, vec![(point![4.5, 0.75], point![4.5, -0.75])]);
+    }
+
+    #[test]
+    fn goalkeeper_splits_the_goal_into_the_openings_next_to_it() {
+        let field_dimensions = FieldDimensions::SPL_2025;
+        let goalkeeper = Opponent {
+            position: point![4.3, 0.3],
+            radius: 0.25,
+        };
+        let midfielder = Opponent {
+            position: point![0.0, 0.0],
+            radius: 0.25,
+        };
+
+        let openings = goal_openings(&field_dimensions, &[midfielder, goalkeeper]);
+
+        assert_eq!(openings.len(), 2);
+        assert_relative_eq!(openings[0].0.y(), 0.75);
+        assert_relative_eq!(openings[0].1.y(), 0.55);
+        assert_relative_eq!(openings[1].0.y(), 0.05);
+        assert_relative_eq!(openings[1].1.y(), -0.75);
+    }
+
+    #[test]
+    fn widest_free_interval_skips_blocked_parts() {
+        assert_eq!(
+            widest_free_interval(-1.0, 1.0, vec![(-0.2, 0.4)]),
+            Some((-1.0, -0.2))
+        );
+        assert_eq!(
+            widest_free_interval(-1.0, 1.0, vec![(0.5, 2.0), (-2.0, -0.5)]),
+            Some((-0.5, 0.5))
+        );
+        assert_eq!(widest_free_interval(-1.0, 1.0, vec![(-1.5, 1.5)]), None);
+    }
+
+    #[test]
+    fn open_shot_at_the_goal_is_certain() {
+        let field_dimensions = FieldDimensions::SPL_2025;
+
+        let candidate = evaluate_candidate(
+            KickTargetKind::Goal,
+            open_goal(&field_dimensions),
+            point![3.0, 0.0],
+            &[],
+            &parameters(),
+            &kicking(true),
+        )
+        .unwrap();
+
+        assert_relative_eq!(
+            candidate.opening_angle,
+            2.0 * 0.5_f32.atan(),
+            epsilon = 1e-4
+        );
+        assert_relative_eq!(candidate.target, point![4.5, 0.0], epsilon = 1e-4);
+        assert_eq!(candidate.kick_power, KickPower::Rumpelstilzchen);
+        assert_relative_eq!(candidate.interception_risk, 0.0);
+        assert_relative_eq!(candidate.score, 1.0, epsilon = 1e-3);
+    }
+
+    #[test]
+    fn opponent_in_front_of_the_goal_narrows_the_opening_and_intercepts() {
+        let field_dimensions = FieldDimensions::SPL_2025;
+        let ball = point![3.0, 0.0];
+        let blocker = Opponent {
+            position: point![3.75, 0.0],
+            radius: 0.3,
+        };
+
+        let open = evaluate_candidate(
+            KickTargetKind::Goal,
+            open_goal(&field_dimensions),
+            ball,
+            &[],
+            &parameters(),
+            &kicking(true),
+        )
+        .unwrap();
+        let blocked = evaluate_candidate(
+            KickTargetKind::Goal,
+            open_goal(&field_dimensions),
+            ball,
+            &[blocker],
+            &parameters(),
+            &kicking(true),
+        )
+        .unwrap();
+
+        assert!(blocked.opening_angle < open.opening_angle / 10.0);
+        assert!(blocked.accuracy < open.accuracy);
+        assert_relative_eq!(blocked.interception_risk, 1.0);
+        assert!(blocked.score < open.score);
+    }
+
+    #[test]
+    fn shot_at_an_open_goal_outscores_a_pass() {
+        let field_dimensions = FieldDimensions::SPL_2025;
+        let ball = point![3.0, 0.0];
+
+        let shot = evaluate_candidate(
+            KickTargetKind::Goal,
+            open_goal(&field_dimensions),
+            ball,
+            &[],
+            &parameters(),
+            &kicking(true),
+        )
+        .unwrap();
+        let pass = evaluate_candidate(
+            KickTargetKind::Pass(PlayerNumber::Two),
+            TargetArea::Point(point![3.0, 2.0]),
+            ball,
+            &[],
+            &parameters(),
+            &kicking(true),
+        )
+        .unwrap();
+
+        assert_relative_eq!(pass.opening_angle, 2.0 * 0.125_f32.atan(), epsilon = 1e-4);
+        assert!(pass.score < parameters().pass_value);
+        assert!(shot.score > pass.score);
+    }
+
+    #[test]
+    fn far_targets_are_kicked_with_schlong_only_if_allowed() {
+        let field_dimensions = FieldDimensions::SPL_2025;
+        let ball = point![-3.0, 0.0];
+
+        let with_schlong = evaluate_candidate(
+            KickTargetKind::Goal,
+            open_goal(&field_dimensions),
+            ball,
+            &[],
+            &parameters(),
+            &kicking(true),
+        )
+        .unwrap();
+        let without_schlong = evaluate_candidate(
+            KickTargetKind::Goal,
+            open_goal(&field_dimensions),
+            ball,
+            &[],
+            &parameters(),
+            &kicking(false),
+        )
+        .unwrap();
+
+        assert_eq!(with_schlong.kick_power, KickPower::Schlong);
+        assert_eq!(without_schlong.kick_power, KickPower::Rumpelstilzchen);
+        assert!(with_schlong.accuracy > without_schlong.accuracy);
+    }
+
+    #[test]
+    fn target_at_the_ball_is_no_candidate() {
+        let ball = point![1.0, 1.0];
+
+        let candidate = evaluate_candidate(
+            KickTargetKind::Zone(PlayerNumber::Four),
+            TargetArea::Point(ball),
+            ball,
+            &[],
+            &parameters(),
+            &kicking(true),
+        );
+
+        assert!(candidate.is_none());
+    }
+
+    #[test]
+    fn interception_risk_falls_off_beside_the_path() {
+        let parameters = parameters();
+        let ball = point![0.0, 0.0];
+        let target = point![3.0, 0.0];
+        let opponent_at = |x: f32, y: f32| Opponent {
+            position: point![x, y],
+            radius: 0.2,
+        };
+
+        let behind_the_ball = interception_risk(ball, target, &opponent_at(-0.5, 0.0), &parameters);
+        let on_the_path = interception_risk(ball, target, &opponent_at(1.5, 0.1), &parameters);
+        let beside_the_path = interception_risk(ball, target, &opponent_at(1.5, 1.0), &parameters);
+        let far_beside_the_path =
+            interception_risk(ball, target, &opponent_at(1.5, 2.0), &parameters);
+
+        assert_relative_eq!(behind_the_ball, 0.0);
+        assert_relative_eq!(on_the_path, 1.0);
+        assert!(beside_the_path < 1.0);
+        assert!(far_beside_the_path < beside_the_path);
+    }
+
+    #[test]
+    fn error_function_matches_reference_values() {
+        assert_relative_eq!(error_function(0.0), 0.0, epsilon = 1e-5);
+        assert_relative_eq!(error_function(1.0), 0.842_700_8, epsilon = 1e-5);
+        assert_relative_eq!(error_function(-0.5), -0.520_499_9, epsilon = 1e-5);
+    }
+}
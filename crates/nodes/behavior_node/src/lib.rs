@@ -3,13 +3,17 @@ pub mod behavior_tree;
 pub mod conditions;
 pub mod head;
 pub mod kick;
+pub mod kick_target;
 pub mod motion_assembler;
 pub mod node;
+pub mod registry;
+pub mod role_assignment;
 pub mod search;
 pub mod send_message;
 pub mod substates;
 pub mod switch_motion_type;
 pub mod tree;
+pub mod tree_description;
 pub mod voronoi;
 pub mod walk;
 
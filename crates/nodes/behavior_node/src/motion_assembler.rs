@@ -8,7 +8,7 @@ use crate::node::Blackboard;
 
 pub fn assemble_motion_command(blackboard: &Blackboard, status: Status) -> Result<MotionCommand> {
     match status {
-        Status::Success => {
+        Status::Success | Status::Running => {
             if blackboard.is_injected_motion_command
                 && let Some(injected_motion_command) =
                     &blackboard.parameters.injected_motion_command
@@ -1,34 +1,54 @@
-use std::{net::SocketAddr, pin::Pin, sync::Arc, time::Duration};
+use std::{
+    fs::metadata,
+    net::SocketAddr,
+    path::PathBuf,
+    pin::Pin,
+    sync::Arc,
+    time::{Duration, SystemTime},
+};
 
 use booster::FallDownState;
 use color_eyre::Result;
 
 use coordinate_systems::{Field, Ground};
-use hsl_network_messages::PlayerNumber;
+use hsl_network_messages::{PlayerNumber, SearchState};
 use linear_algebra::{Isometry2, Point2, Pose2, Vector2};
 use ros_z::{prelude::*, qos::QosDurability, time::Time};
 use serde::{Deserialize, Serialize};
-use tracing::info;
+use tracing::{info, warn};
 use types::{
     ball_position::HypotheticalBallPosition,
     behavior_tree::NodeTrace,
     field_dimensions::{FieldDimensions, Side},
     filtered_game_controller_state::FilteredGameControllerState,
+    kick_target::KickTargetCandidate,
     messages::OutgoingMessage,
     motion_command::{BodyMotion, HeadMotion, MotionCommand},
     motion_type::MotionType,
     obstacles::Obstacle,
     parameters::BehaviorParameters,
+    path::TimedPath,
     path_obstacles::PathObstacle,
     players::Players,
     primary_state::PrimaryState,
+    role::Role,
     rule_obstacles::RuleObstacle,
     time_wrapper::TimeWrapper,
     world_state::{BallState, PlayerState, RobotState, WorldState},
 };
 use voronoi::VoronoiGrid;
 
-use crate::{motion_assembler::assemble_motion_command, tree::create_tree};
+use crate::{
+    behavior_tree::{Clock, Node},
+    motion_assembler::assemble_motion_command,
+    registry::{Registry, default_registry},
+    tree::create_tree,
+    tree_description::{TreeDescription, find_tree_file},
+};
+
+/// Period of the timer the node cycles on.
+pub const CYCLE_TIME: Duration = Duration::from_millis(10);
+const TREE_FILE_CHECK_INTERVAL: Duration = Duration::from_secs(1);
 
 #[derive(Debug, Clone, Serialize, Deserialize, Message)]
 pub struct LastBall {
@@ -45,9 +65,11 @@ pub struct Blackboard {
     pub world_state: WorldState,
 
     pub path_obstacles_output: Vec<PathObstacle>,
+    pub planned_path_output: Option<TimedPath>,
     pub time_since_last_switch: Duration,
     pub direction_difference: f32,
     pub voronoi_inputs: Vec<Pose2<Field>>,
+    pub kick_target_candidates: Vec<KickTargetCandidate>,
 
     pub ball: Option<LastBall>,
     pub last_ball: Option<LastBall>,
@@ -58,15 +80,49 @@ pub struct Blackboard {
     pub last_motion_type: Option<MotionType>,
     pub last_sent_game_controller_return_message_time: Option<Time>,
     pub last_sent_hsl_message_time: Option<Time>,
-    pub last_closest_to_ball: bool,
-    pub closest_to_ball_entered_area_since: Option<Time>,
-    pub closest_to_ball_left_area_since: Option<Time>,
+    pub last_sent_state_message_ball_seen: bool,
+    pub last_sent_state_message_role: Option<Role>,
+    pub player_state_times: Players<Option<Time>>,
+    pub team_roles: Players<Option<Role>>,
 
     pub is_injected_motion_command: bool,
     pub walk_position: Option<Point2<Ground>>,
     pub body_motion: Option<BodyMotion>,
     pub head_motion: Option<HeadMotion>,
     pub voronoi_map: Option<VoronoiGrid>,
+    pub role: Option<Role>,
+    pub role_position: Option<Point2<Field>>,
+}
+
+impl Clock for Blackboard {
+    fn now(&self) -> Time {
+        self.world_state.now
+    }
+}
+
+/// Tree description file of the parameter layers, compared to detect changes.
+#[derive(Debug, Default, PartialEq, Eq)]
+struct TreeSource {
+    path: Option<PathBuf>,
+    modified: Option<SystemTime>,
+}
+
+impl TreeSource {
+    fn find(parameter_layers: &[PathBuf]) -> Self {
+        let path = find_tree_file(parameter_layers);
+        let modified = path
+            .as_ref()
+            .and_then(|path| metadata(path).and_then(|metadata| metadata.modified()).ok());
+        Self { path, modified }
+    }
+
+    /// Without a description in any layer the tree compiled into the node is used.
+    fn load(&self, registry: &Registry) -> Result<Node<Blackboard>> {
+        match &self.path {
+            Some(path) => TreeDescription::load(path)?.build(registry),
+            None => Ok(create_tree()),
+        }
+    }
 }
 
 pub fn run_boxed(ctx: Arc<Context>) -> Pin<Box<dyn Future<Output = Result<()>> + Send>> {
@@ -115,6 +171,28 @@ fn validate_behavior_parameters(
         }
     }
 
+    let walk_speed_extents = &parameters.path_planning.walk_speed_extents;
+    for (field, speed) in [
+        ("forward", walk_speed_extents.forward),
+        ("backward", walk_speed_extents.backward),
+        ("outward", walk_speed_extents.outward),
+        ("inward", walk_speed_extents.inward),
+        ("outward_rotation", walk_speed_extents.outward_rotation),
+        ("inward_rotation", walk_speed_extents.inward_rotation),
+    ] {
+        if !speed.is_finite() || speed < 0.0 {
+            errors.push(format!(
+                "path_planning.walk_speed_extents.{field} must be finite and non-negative (got {speed})"
+            ));
+        }
+    }
+    if walk_speed_extents.forward == 0.0 {
+        errors.push(format!(
+            "path_planning.walk_speed_extents.forward must be strictly positive (got {})",
+            walk_speed_extents.forward
+        ));
+    }
+
     if errors.is_empty() {
         Ok(())
     } else {
@@ -215,6 +293,11 @@ pub async fn run(ctx: Arc<Context>) -> Result<()> {
         .cache(1)
         .build()
         .await?;
+    let search_state_cache = node
+        .subscriber::<SearchState>("search_state")
+        .cache(1)
+        .build()
+        .await?;
     let additional_behavior_trace_pub = node
         .publisher::<NodeTrace>("behavior/trace")
         .build()
@@ -239,13 +322,21 @@ pub async fn run(ctx: Arc<Context>) -> Result<()> {
         .publisher::<MotionCommand>("behavior/motion_command")
         .build()
         .await?;
+    let additional_planned_path_pub = node
+        .publisher::<Option<TimedPath>>("behavior/planned_path")
+        .build()
+        .await?;
 
-    let tree = create_tree();
+    let registry = default_registry();
+    let parameter_layers = node.runtime_parameter_inputs().parameter_layers.clone();
+    let mut tree_source = TreeSource::find(&parameter_layers);
+    let mut tree = tree_source.load(&registry)?;
+    let mut last_tree_source_check = node.clock().now();
     let static_layout = tree.static_layout_trace();
     additional_behavior_tree_layout_pub
         .publish_if_subscribed(|| async { static_layout })
         .await?;
-    let mut timer = node.create_timer(Duration::from_millis(10));
+    let mut timer = node.create_timer(CYCLE_TIME);
 
     let mut blackboard = Blackboard {
         field_dimensions: field_dimensions_cache
@@ -256,9 +347,11 @@ pub async fn run(ctx: Arc<Context>) -> Result<()> {
         world_state: WorldState::default(),
 
         path_obstacles_output: Vec::new(),
+        planned_path_output: None,
         time_since_last_switch: Duration::ZERO,
         direction_difference: 0.0,
         voronoi_inputs: Vec::new(),
+        kick_target_candidates: Vec::new(),
 
         ball: None,
         last_ball: None,
@@ -269,28 +362,35 @@ pub async fn run(ctx: Arc<Context>) -> Result<()> {
         last_motion_type: None,
         last_sent_game_controller_return_message_time: None,
         last_sent_hsl_message_time: None,
-        last_closest_to_ball: false,
-        closest_to_ball_entered_area_since: None,
-        closest_to_ball_left_area_since: None,
+        last_sent_state_message_ball_seen: false,
+        last_sent_state_message_role: None,
+        player_state_times: Players::new(None),
+        team_roles: Players::new(None),
 
         is_injected_motion_command: false,
         walk_position: None,
         body_motion: None,
         head_motion: None,
         voronoi_map: None,
+        role: None,
+        role_position: None,
     };
 
     loop {
         blackboard.path_obstacles_output.clear();
+        blackboard.planned_path_output = None;
         blackboard.time_since_last_switch = Duration::ZERO;
         blackboard.direction_difference = 0.0;
         blackboard.voronoi_inputs.clear();
+        blackboard.kick_target_candidates.clear();
 
         blackboard.is_injected_motion_command = false;
         blackboard.walk_position = None;
         blackboard.body_motion = None;
         blackboard.head_motion = None;
         blackboard.voronoi_map = None;
+        blackboard.role = None;
+        blackboard.role_position = None;
 
         let player_number = player_number_cache
             .get_latest()
@@ -298,15 +398,15 @@ pub async fn run(ctx: Arc<Context>) -> Result<()> {
             .unwrap_or_default();
         blackboard.parameters = parameters.snapshot().typed().clone();
 
-        let player_states = player_states_cache
+        let received_player_states = player_states_cache
             .get_latest()
-            .map(|player_states| {
-                player_states
-                    .as_ref()
-                    .clone()
-                    .map(|player_state| player_state.map(|state| state.inner))
-            })
+            .map(|player_states| player_states.as_ref().clone())
             .unwrap_or_default();
+        blackboard.player_state_times = received_player_states
+            .clone()
+            .map(|player_state| player_state.map(|state| state.time));
+        let player_states =
+            received_player_states.map(|player_state| player_state.map(|state| state.inner));
 
         blackboard.world_state.robot = RobotState {
             ground_to_field: ground_to_field_cache
@@ -349,6 +449,10 @@ pub async fn run(ctx: Arc<Context>) -> Result<()> {
         blackboard.world_state.suggested_search_position = suggested_search_position_cache
             .get_latest()
             .map(|position| *position);
+        blackboard.world_state.search_state = search_state_cache
+            .get_latest()
+            .map(|search_state| *search_state)
+            .unwrap_or_default();
 
         if let Some(ball) = blackboard.world_state.ball {
             blackboard.ball = Some(LastBall {
@@ -365,7 +469,37 @@ pub async fn run(ctx: Arc<Context>) -> Result<()> {
             blackboard.ball = None;
         }
 
-        let (status, trace) = tree.tick_with_trace(&mut blackboard);
+        if blackboard
+            .world_state
+            .now
+            .duration_since(last_tree_source_check)
+            >= TREE_FILE_CHECK_INTERVAL
+        {
+            last_tree_source_check = blackboard.world_state.now;
+            let current_tree_source = TreeSource::find(&parameter_layers);
+            if current_tree_source != tree_source {
+                match current_tree_source.load(&registry) {
+                    Ok(reloaded_tree) => {
+                        info!(path = ?current_tree_source.path, "reloaded behavior tree");
+                        tree = reloaded_tree;
+                        let static_layout = tree.static_layout_trace();
+                        additional_behavior_tree_layout_pub
+                            .publish_if_subscribed(|| async { static_layout })
+                            .await?;
+                    }
+                    Err(error) => {
+                        warn!(
+                            path = ?current_tree_source.path,
+                            "keeping the current behavior tree: {error:#}"
+                        );
+                    }
+                }
+                tree_source = current_tree_source;
+            }
+        }
+
+        let (status, mut trace) = tree.tick_with_trace(&mut blackboard);
+        trace.role = blackboard.role;
         let motion_command: MotionCommand = assemble_motion_command(&blackboard, status)?;
 
         let previous_motion_command = blackboard.last_motion_command.clone();
@@ -379,6 +513,7 @@ pub async fn run(ctx: Arc<Context>) -> Result<()> {
             }
             MotionCommand::Stand { .. } => Some(MotionType::Stand),
             MotionCommand::StandUp => Some(MotionType::StandUp),
+            MotionCommand::KeyframeMotion { .. } => Some(MotionType::KeyframeMotion),
             MotionCommand::Prepare => Some(MotionType::Prepare),
         };
 
@@ -416,6 +551,9 @@ pub async fn run(ctx: Arc<Context>) -> Result<()> {
         additional_black_board_pub
             .publish_if_subscribed(|| async { blackboard.clone() })
             .await?;
+        additional_planned_path_pub
+            .publish_if_subscribed(|| async { blackboard.planned_path_output.clone() })
+            .await?;
         motion_command_pub.publish(&motion_command).await?;
         timer.tick().await;
     }
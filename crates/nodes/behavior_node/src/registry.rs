@@ -0,0 +1,266 @@
+use std::{
+    collections::{BTreeMap, HashSet},
+    sync::{LazyLock, Mutex},
+};
+
+use serde::de::{
+    DeserializeOwned, IntoDeserializer,
+    value::{Error as ArgumentError, StrDeserializer},
+};
+use types::behavior_tree::Status;
+
+use crate::{
+    actions::{damping, injected_motion_command, prepare, remote_control, stand, stand_up},
+    behavior_tree::Node,
+    conditions::{
+        has_ball_position, has_hypothetical_ball_position, has_new_ball_position,
+        hulks_is_kicking_team, is_ball_interception_candidate, is_close_to_ball,
+        is_close_to_ball_aligned, is_fallen, is_primary_state, is_remote_controlled,
+        is_remote_kick_mode,
+    },
+    head::{
+        look_around, look_at_ball, look_at_ball_subtree, look_at_hypothetical_ball_position,
+        look_straight_ahead, search_for_lost_ball, search_for_lost_ball_subtree,
+    },
+    kick::{
+        allow_schlong, intercept, is_close_to_target, kick, kick_alternatives_subtree,
+        kick_power_subtree, kick_subtree, select_kick_target, set_kick_target_in_front,
+        use_kick_power, use_kick_target_power, use_last_kick_power,
+    },
+    node::Blackboard,
+    role_assignment::{assign_roles, has_role},
+    search::{has_suggested_search_position, leuchtturm, walk_to_search_position},
+    substates::{
+        is_in_sub_state, is_sub_state, set_block_position_corner, set_block_position_field,
+        set_block_position_penalty_kick, sub_state_subtree,
+    },
+    switch_motion_type::{is_allowed_to_switch, is_last_motion_type},
+    voronoi::calculate_voronoi_grid,
+    walk::{
+        walk_alternatives_subtree, walk_to_ball, walk_to_ball_subtree, walk_to_block_position,
+        walk_to_centroid, walk_to_kickoff_pose, walk_to_role_position,
+    },
+};
+
+type LeafConstructor = Box<dyn Fn(Option<&str>) -> Result<Node<Blackboard>, String> + Send + Sync>;
+
+/// Named actions, conditions and subtrees a tree description can refer to.
+#[derive(Default)]
+pub struct Registry {
+    leaves: BTreeMap<&'static str, LeafConstructor>,
+    subtrees: BTreeMap<&'static str, fn() -> Node<Blackboard>>,
+}
+
+impl Registry {
+    pub fn action(&mut self, name: &'static str, action: fn(&mut Blackboard) -> Status) {
+        self.insert_leaf(
+            name,
+            Box::new(move |argument| {
+                expect_no_argument(name, argument)?;
+                Ok(Node::Action {
+                    name,
+                    action: Box::new(action),
+                })
+            }),
+        );
+    }
+
+    pub fn action_with_argument<T>(
+        &mut self,
+        name: &'static str,
+        action: fn(&mut Blackboard, T) -> Status,
+    ) where
+        T: DeserializeOwned + Clone + Send + Sync + 'static,
+    {
+        self.insert_leaf(
+            name,
+            Box::new(move |argument| {
+                let (argument, name) = parse_argument::<T>(name, argument)?;
+                Ok(Node::Action {
+                    name,
+                    action: Box::new(move |blackboard| action(blackboard, argument.clone())),
+                })
+            }),
+        );
+    }
+
+    pub fn condition(&mut self, name: &'static str, condition: fn(&mut Blackboard) -> bool) {
+        self.insert_leaf(
+            name,
+            Box::new(move |argument| {
+                expect_no_argument(name, argument)?;
+                Ok(Node::Condition {
+                    name,
+                    condition: Box::new(condition),
+                })
+            }),
+        );
+    }
+
+    pub fn condition_with_argument<T>(
+        &mut self,
+        name: &'static str,
+        condition: fn(&mut Blackboard, T) -> bool,
+    ) where
+        T: DeserializeOwned + Clone + Send + Sync + 'static,
+    {
+        self.insert_leaf(
+            name,
+            Box::new(move |argument| {
+                let (argument, name) = parse_argument::<T>(name, argument)?;
+                Ok(Node::Condition {
+                    name,
+                    condition: Box::new(move |blackboard| condition(blackboard, argument.clone())),
+                })
+            }),
+        );
+    }
+
+    pub fn subtree(&mut self, name: &'static str, subtree: fn() -> Node<Blackboard>) {
+        self.subtrees.insert(name, subtree);
+    }
+
+    /// Creates the node of a leaf written as `name` or `name(argument)`.
+    pub fn create_leaf(&self, leaf: &str) -> Result<Node<Blackboard>, String> {
+        let (name, argument) = match leaf.split_once('(') {
+            Some((name, rest)) => {
+                let argument = rest
+                    .strip_suffix(')')
+                    .ok_or_else(|| format!("leaf `{leaf}` is missing a closing parenthesis"))?;
+                (name.trim(), Some(argument.trim()))
+            }
+            None => (leaf.trim(), None),
+        };
+        let constructor = self
+            .leaves
+            .get(name)
+            .ok_or_else(|| format!("unknown leaf `{name}`"))?;
+        constructor(argument)
+    }
+
+    pub fn create_subtree(&self, name: &str) -> Option<Node<Blackboard>> {
+        self.subtrees.get(name).map(|subtree| subtree())
+    }
+
+    fn insert_leaf(&mut self, name: &'static str, constructor: LeafConstructor) {
+        let previous = self.leaves.insert(name, constructor);
+        assert!(previous.is_none(), "leaf `{name}` is registered twice");
+    }
+}
+
+/// Registry with all actions, conditions and Rust subtrees of the behavior node.
+pub fn default_registry() -> Registry {
+    let mut registry = Registry::default();
+
+    registry.action("assign_roles", assign_roles);
+    registry.action("calculate_voronoi_grid", calculate_voronoi_grid);
+    registry.action("damping", damping);
+    registry.action("injected_motion_command", injected_motion_command);
+    registry.action("intercept", intercept);
+    registry.action("kick", kick);
+    registry.action("leuchtturm", leuchtturm);
+    registry.action("look_around", look_around);
+    registry.action("look_at_ball", look_at_ball);
+    registry.action(
+        "look_at_hypothetical_ball_position",
+        look_at_hypothetical_ball_position,
+    );
+    registry.action("look_straight_ahead", look_straight_ahead);
+    registry.action("prepare", prepare);
+    registry.action("remote_control", remote_control);
+    registry.action("search_for_lost_ball", search_for_lost_ball);
+    registry.action("select_kick_target", select_kick_target);
+    registry.action("set_block_position_corner", set_block_position_corner);
+    registry.action("set_block_position_field", set_block_position_field);
+    registry.action(
+        "set_block_position_penalty_kick",
+        set_block_position_penalty_kick,
+    );
+    registry.action("set_kick_target_in_front", set_kick_target_in_front);
+    registry.action("stand", stand);
+    registry.action("stand_up", stand_up);
+    registry.action_with_argument("use_kick_power", use_kick_power);
+    registry.action("use_kick_target_power", use_kick_target_power);
+    registry.action("use_last_kick_power", use_last_kick_power);
+    registry.action("walk_to_ball", walk_to_ball);
+    registry.action("walk_to_block_position", walk_to_block_position);
+    registry.action("walk_to_centroid", walk_to_centroid);
+    registry.action("walk_to_kickoff_pose", walk_to_kickoff_pose);
+    registry.action("walk_to_role_position", walk_to_role_position);
+    registry.action("walk_to_search_position", walk_to_search_position);
+
+    registry.condition("allow_schlong", allow_schlong);
+    registry.condition("has_ball_position", has_ball_position);
+    registry.condition(
+        "has_hypothetical_ball_position",
+        has_hypothetical_ball_position,
+    );
+    registry.condition("has_new_ball_position", has_new_ball_position);
+    registry.condition_with_argument("has_role", has_role);
+    registry.condition(
+        "has_suggested_search_position",
+        has_suggested_search_position,
+    );
+    registry.condition("hulks_is_kicking_team", hulks_is_kicking_team);
+    registry.condition("is_allowed_to_switch", is_allowed_to_switch);
+    registry.condition(
+        "is_ball_interception_candidate",
+        is_ball_interception_candidate,
+    );
+    registry.condition("is_close_to_ball", is_close_to_ball);
+    registry.condition("is_close_to_ball_aligned", is_close_to_ball_aligned);
+    registry.condition("is_close_to_target", is_close_to_target);
+    registry.condition("is_fallen", is_fallen);
+    registry.condition("is_in_sub_state", is_in_sub_state);
+    registry.condition_with_argument("is_last_motion_type", is_last_motion_type);
+    registry.condition_with_argument("is_primary_state", is_primary_state);
+    registry.condition("is_remote_controlled", is_remote_controlled);
+    registry.condition("is_remote_kick_mode", is_remote_kick_mode);
+    registry.condition_with_argument("is_sub_state", is_sub_state);
+
+    registry.subtree("kick_alternatives_subtree", kick_alternatives_subtree);
+    registry.subtree("kick_power_subtree", kick_power_subtree);
+    registry.subtree("kick_subtree", kick_subtree);
+    registry.subtree("look_at_ball_subtree", look_at_ball_subtree);
+    registry.subtree("search_for_lost_ball_subtree", search_for_lost_ball_subtree);
+    registry.subtree("sub_state_subtree", sub_state_subtree);
+    registry.subtree("walk_alternatives_subtree", walk_alternatives_subtree);
+    registry.subtree("walk_to_ball_subtree", walk_to_ball_subtree);
+
+    registry
+}
+
+fn expect_no_argument(name: &str, argument: Option<&str>) -> Result<(), String> {
+    match argument {
+        Some(argument) => Err(format!(
+            "leaf `{name}` takes no argument (got `{argument}`)"
+        )),
+        None => Ok(()),
+    }
+}
+
+/// Parses the argument of a leaf like `is_primary_state(Playing)` and names the node after it.
+fn parse_argument<T: DeserializeOwned>(
+    name: &str,
+    argument: Option<&str>,
+) -> Result<(T, &'static str), String> {
+    let argument = argument.ok_or_else(|| format!("leaf `{name}` requires an argument"))?;
+    let deserializer: StrDeserializer<ArgumentError> = argument.into_deserializer();
+    let value = T::deserialize(deserializer)
+        .map_err(|error| format!("invalid argument `{argument}` for leaf `{name}`: {error}"))?;
+    Ok((value, intern(format!("{name}({argument})"))))
+}
+
+/// Node names are static, so the names built from descriptions are leaked once per distinct
+/// name instead of once per reload.
+pub(crate) fn intern(name: String) -> &'static str {
+    static NAMES: LazyLock<Mutex<HashSet<&'static str>>> = LazyLock::new(Default::default);
+
+    let mut names = NAMES.lock().expect("interned node names are poisoned");
+    if let Some(interned) = names.get(name.as_str()) {
+        return interned;
+    }
+    let interned = Box::leak(name.into_boxed_str());
+    names.insert(interned);
+    interned
+}
@@ -0,0 +1,496 @@
+use std::time::Duration;
+
+use coordinate_systems::Field;
+use hsl_network_messages::PlayerNumber;
+use hungarian_algorithm::AssignmentProblem;
+use linear_algebra::{Orientation2, Point2, Pose2, point, vector};
+use ndarray::Array2;
+use ordered_float::NotNan;
+use path_planner::walk_time::{WalkTimeModel, kick_pose};
+use types::{
+    behavior_tree::Status, parameters::RoleAssignmentParameters, players::Players, role::Role,
+};
+
+use crate::node::Blackboard;
+
+/// Team member as far as the role assignment is concerned.
+#[derive(Clone, Copy, Debug)]
+enum Member {
+    /// Recently heard from, without a pose while the own robot is not localized
+    Active(Option<Pose2<Field>>),
+    /// Not heard from for a while, most likely because the message budget keeps it quiet
+    Silent,
+}
+
+/// Role together with the pose a robot takes it over at.
+#[derive(Clone, Copy, Debug)]
+struct RoleSlot {
+    role: Role,
+    pose: Option<Pose2<Field>>,
+}
+
+/// Assigns a role to every robot of the team and keeps the own one on the blackboard.
+///
+/// Every robot solves the assignment for the whole team from the same shared information, so
+/// all of them agree on the roles as long as messages arrive. Silent teammates keep their last
+/// role instead of being reassigned, so the assignment freezes instead of every robot claiming
+/// the striker when the message budget runs low.
+pub fn assign_roles(blackboard: &mut Blackboard) -> Status {
+    let own_player_number = blackboard.world_state.robot.player_number;
+    let parameters = &blackboard.parameters.role_assignment;
+    let walk_time_model = WalkTimeModel::new(
+        blackboard
+            .parameters
+            .path_planning
+            .walk_speed_extents
+            .clone(),
+    );
+    let now = blackboard.world_state.now;
+
+    let mut team = Players::new(None);
+    team[own_player_number] = Some(Member::Active(
+        blackboard
+            .world_state
+            .robot
+            .ground_to_field
+            .map(|ground_to_field| ground_to_field.as_pose()),
+    ));
+    for (player_number, player_state) in blackboard.world_state.player_states.iter() {
+        let Some(player_state) = player_state else {
+            continue;
+        };
+        if player_number == own_player_number {
+            continue;
+        }
+        let silence = blackboard.player_state_times[player_number]
+            .map_or(Duration::ZERO, |time| now.duration_since(time));
+        team[player_number] = teammate(player_state.pose, silence, parameters);
+    }
+
+    let assignment = assign_team_roles(
+        &team,
+        blackboard.ball.as_ref().map(|ball| ball.position),
+        &blackboard.team_roles,
+        blackboard.parameters.goal_keeper_number,
+        |role, index, count, ball| role_pose(role, index, count, ball, blackboard),
+        parameters,
+        &walk_time_model,
+    );
+
+    blackboard.team_roles = assignment.map(|slot| slot.map(|slot| slot.role));
+    blackboard.role = blackboard.team_roles[own_player_number];
+    blackboard.role_position = assignment[own_player_number]
+        .and_then(|slot| slot.pose)
+        .map(|pose| pose.position());
+    Status::Success
+}
+
+fn teammate(
+    pose: Pose2<Field>,
+    silence: Duration,
+    parameters: &RoleAssignmentParameters,
+) -> Option<Member> {
+    if silence > parameters.teammate_loss_timeout {
+        None
+    } else if silence > parameters.teammate_silence_timeout {
+        Some(Member::Silent)
+    } else {
+        Some(Member::Active(Some(pose)))
+    }
+}
+
+fn assign_team_roles(
+    team: &Players<Option<Member>>,
+    ball: Option<Point2<Field>>,
+    previous_roles: &Players<Option<Role>>,
+    goal_keeper_number: PlayerNumber,
+    role_pose: impl Fn(Role, usize, usize, Point2<Field>) -> Pose2<Field>,
+    parameters: &RoleAssignmentParameters,
+    walk_time_model: &WalkTimeModel,
+) -> Players<Option<RoleSlot>> {
+    let mut assignment = Players::new(None);
+    let mut members: Vec<(PlayerNumber, Member)> = team
+        .iter()
+        .filter_map(|(player_number, member)| Some((player_number, (*member)?)))
+        .collect();
+
+    // The keeper only plays in the field when it is the last robot left
+    let keeper_is_present = members
+        .iter()
+        .any(|(player_number, _)| *player_number == goal_keeper_number);
+    if keeper_is_present && members.len() > 1 {
+        assignment[goal_keeper_number] = Some(RoleSlot {
+            role: Role::Keeper,
+            pose: None,
+        });
+        members.retain(|(player_number, _)| *player_number != goal_keeper_number);
+    }
+
+    let roles = field_roles(members.len(), !keeper_is_present);
+    let mut slots: Vec<RoleSlot> = roles
+        .iter()
+        .enumerate()
+        .map(|(index, role)| {
+            let same_role_index = roles[..index].iter().filter(|other| *other == role).count();
+            let same_role_count = roles.iter().filter(|other| *other == role).count();
+            RoleSlot {
+                role: *role,
+                pose: ball.map(|ball| role_pose(*role, same_role_index, same_role_count, ball)),
+            }
+        })
+        .collect();
+
+    // Robots whose cost cannot be estimated keep their role, the rest share the others
+    let (keeping, assignable): (Vec<_>, Vec<_>) =
+        members.into_iter().partition(|(_, member)| match member {
+            Member::Active(Some(_)) => ball.is_none(),
+            Member::Active(None) | Member::Silent => true,
+        });
+    for (player_number, _) in keeping {
+        let role = previous_roles[player_number]
+            .filter(|role| *role != Role::Keeper)
+            .unwrap_or(parameters.fallback_roles[player_number]);
+        let index = slots
+            .iter()
+            .position(|slot| slot.role == role)
+            .unwrap_or(slots.len() - 1);
+        assignment[player_number] = Some(slots.remove(index));
+    }
+    if assignable.is_empty() {
+        return assignment;
+    }
+
+    let costs = Array2::from_shape_fn((assignable.len(), slots.len()), |(i, j)| {
+        let (player_number, member) = assignable[i];
+        let slot = slots[j];
+        let (Member::Active(Some(pose)), Some(target)) = (member, slot.pose) else {
+            return NotNan::default();
+        };
+        let switch_penalty = if previous_roles[player_number] == Some(slot.role) {
+            0.0
+        } else {
+            parameters.role_switch_penalty.as_secs_f32()
+        };
+        // Same model the path planner uses, so the robot with the fastest path gets the role
+        let time = walk_time_model.time_to_reach(pose, target) + switch_penalty;
+        NotNan::new(-time).expect("time to reach role is NaN")
+    });
+    for ((player_number, _), assigned) in assignable
+        .iter()
+        .zip(AssignmentProblem::from_costs(costs).solve())
+    {
+        assignment[*player_number] = assigned.map(|assigned| slots[assigned.to]);
+    }
+    assignment
+}
+
+/// Roles of the field players by priority, the striker is always filled first.
+fn field_roles(number_of_field_players: usize, keeper_is_missing: bool) -> Vec<Role> {
+    let keeper_replacement =
+        (keeper_is_missing && number_of_field_players > 1).then_some(Role::KeeperReplacement);
+    [Role::Striker]
+        .into_iter()
+        .chain(keeper_replacement)
+        .chain([Role::Defender, Role::Supporter].into_iter().cycle())
+        .take(number_of_field_players)
+        .collect()
+}
+
+fn role_pose(
+    role: Role,
+    index: usize,
+    count: usize,
+    ball: Point2<Field>,
+    blackboard: &Blackboard,
+) -> Pose2<Field> {
+    let field_dimensions = &blackboard.field_dimensions;
+    let role_positions = &blackboard.parameters.role_positions;
+    let own_goal = point![-field_dimensions.length / 2.0, 0.0];
+    let opponent_goal = point![field_dimensions.length / 2.0, 0.0];
+    let facing_ball = |position: Point2<Field>| {
+        Pose2::from_parts(position, Orientation2::from_vector(ball - position))
+    };
+
+    match role {
+        Role::Striker => kick_pose(
+            ball,
+            opponent_goal,
+            blackboard.parameters.kicking.kick_position_ball_distance,
+        ),
+        Role::Keeper | Role::KeeperReplacement => {
+            facing_ball(own_goal + vector![role_positions.keeper_x_offset, 0.0])
+        }
+        Role::Defender => {
+            let direction = (ball - own_goal).normalize();
+            let sideways =
+                vector![-direction.y(), direction.x()] * role_positions.defender_y_offset;
+            let side = match (count, index) {
+                (1, _) => 0.0,
+                (_, 0) => 1.0,
+                _ => -1.0,
+            };
+            facing_ball(
+                own_goal
+                    + direction * role_positions.defender_passive_ring_radius
+                    + sideways * side,
+            )
+        }
+        Role::Supporter => {
+            let behind_ball = ball
+                - (opponent_goal - ball).normalize()
+                    * role_positions.striker_supporter_distance_to_ball;
+            facing_ball(point![
+                behind_ball
+                    .x()
+                    .max(role_positions.striker_supporter_minimum_x),
+                behind_ball.y()
+            ])
+        }
+    }
+}
+
+pub fn has_role(blackboard: &mut Blackboard, role: Role) -> bool {
+    blackboard.role == Some(role)
+}
+
+#[cfg(test)]
+mod tests {
+    use types::walk_volume_extents::WalkVolumeExtents;
+
+    use super::*;
+
+    fn parameters() -> RoleAssignmentParameters {
+        RoleAssignmentParameters {
+            role_switch_penalty: Duration::from_secs(2),
+            teammate_silence_timeout: Duration::from_secs(5),
+            teammate_loss_timeout: Duration::from_secs(20),
+            fallback_roles: Players {
+                one: Role::Keeper,
+                two: Role::Defender,
+                three: Role::Striker,
+                four: Role::Supporter,
+                five: Role::Defender,
+            },
+        }
+    }
+
+    fn walk_time_model() -> WalkTimeModel {
+        WalkTimeModel::new(WalkVolumeExtents {
+            forward: 0.3,
+            backward: 0.15,
+            outward: 0.1,
+            inward: 0.1,
+            outward_rotation: 0.8,
+            inward_rotation: 0.8,
+        })
+    }
+
+    fn role_pose(role: Role, index: usize, _count: usize, ball: Point2<Field>) -> Pose2<Field> {
+        let position = match role {
+            Role::Striker => point![ball.x() - 0.2, ball.y()],
+            Role::Keeper | Role::KeeperReplacement => point![-4.0, 0.0],
+            Role::Defender => point![-3.0, 1.0 - 2.0 * index as f32],
+            Role::Supporter => point![ball.x() - 1.5, ball.y() + 1.0],
+        };
+        Pose2::new(position, 0.0)
+    }
+
+    fn active(x: f32, y: f32) -> Option<Member> {
+        Some(Member::Active(Some(Pose2::new(point![x, y], 0.0))))
+    }
+
+    fn assign(
+        team: &Players<Option<Member>>,
+        ball: Option<Point2<Field>>,
+        previous_roles: &Players<Option<Role>>,
+        parameters: &RoleAssignmentParameters,
+    ) -> Players<Option<Role>> {
+        assign_team_roles(
+            team,
+            ball,
+            previous_roles,
+            PlayerNumber::One,
+            role_pose,
+            parameters,
+            &walk_time_model(),
+        )
+        .map(|slot| slot.map(|slot| slot.role))
+    }
+
+    #[test]
+    fn closest_robot_becomes_striker_and_keeper_stays_in_goal() {
+        let team = Players {
+            one: active(-4.5, 0.0),
+            two: active(-2.0, 0.0),
+            three: active(2.0, 0.0),
+            ..Players::new(None)
+        };
+
+        let roles = assign(
+            &team,
+            Some(point![2.5, 0.0]),
+            &Players::new(None),
+            &parameters(),
+        );
+
+        assert_eq!(roles.one, Some(Role::Keeper));
+        assert_eq!(roles.two, Some(Role::Defender));
+        assert_eq!(roles.three, Some(Role::Striker));
+        assert_eq!(roles.four, None);
+        assert_eq!(roles.five, None);
+    }
+
+    #[test]
+    fn missing_keeper_is_replaced_and_a_lone_keeper_plays_striker() {
+        let without_keeper = Players {
+            two: active(-3.5, 0.0),
+            three: active(2.0, 0.0),
+            ..Players::new(None)
+        };
+        let only_keeper = Players {
+            one: active(-4.5, 0.0),
+            ..Players::new(None)
+        };
+
+        let roles = assign(
+            &without_keeper,
+            Some(point![2.5, 0.0]),
+            &Players::new(None),
+            &parameters(),
+        );
+        let keeper_roles = assign(
+            &only_keeper,
+            Some(point![2.5, 0.0]),
+            &Players::new(None),
+            &parameters(),
+        );
+
+        assert_eq!(roles.two, Some(Role::KeeperReplacement));
+        assert_eq!(roles.three, Some(Role::Striker));
+        assert_eq!(keeper_roles.one, Some(Role::Striker));
+    }
+
+    #[test]
+    fn role_switch_penalty_keeps_roles_unless_switching_saves_more() {
+        let team = Players {
+            two: active(-1.0, 0.5),
+            three: active(0.0, -0.5),
+            ..Players::new(None)
+        };
+        let ball = point![1.0, 0.0];
+        let walk_time_model = walk_time_model();
+        let time_to = |member: Option<Member>, role: Role| {
+            let Some(Member::Active(Some(pose))) = member else {
+                unreachable!()
+            };
+            walk_time_model.time_to_reach(pose, role_pose(role, 0, 1, ball))
+        };
+        let (current, swapped) = (
+            Players {
+                two: Some(Role::Striker),
+                three: Some(Role::Defender),
+                ..Players::new(None)
+            },
+            Players {
+                two: Some(Role::Defender),
+                three: Some(Role::Striker),
+                ..Players::new(None)
+            },
+        );
+        let saving = time_to(team.two, Role::Striker) + time_to(team.three, Role::Defender)
+            - time_to(team.two, Role::Defender)
+            - time_to(team.three, Role::Striker);
+        assert!(saving > 0.0, "three is expected to reach the ball faster");
+        let with_penalty = |seconds: f32| RoleAssignmentParameters {
+            role_switch_penalty: Duration::from_secs_f32(seconds),
+            ..parameters()
+        };
+
+        let without_history = assign(&team, Some(ball), &Players::new(None), &parameters());
+        let small_saving = assign(&team, Some(ball), &current, &with_penalty(saving));
+        let large_saving = assign(&team, Some(ball), &current, &with_penalty(saving / 4.0));
+
+        assert_eq!(without_history, swapped);
+        assert_eq!(small_saving, current);
+        assert_eq!(large_saving, swapped);
+    }
+
+    #[test]
+    fn silent_teammates_keep_their_last_role() {
+        let team = Players {
+            two: active(-3.0, 0.0),
+            three: Some(Member::Silent),
+            four: active(2.0, 0.0),
+            ..Players::new(None)
+        };
+        let previous_roles = Players {
+            three: Some(Role::Striker),
+            ..Players::new(None)
+        };
+
+        let roles = assign(
+            &team,
+            Some(point![2.5, 0.0]),
+            &previous_roles,
+            &parameters(),
+        );
+
+        assert_eq!(roles.three, Some(Role::Striker));
+        assert_ne!(roles.two, Some(Role::Striker));
+        assert_ne!(roles.four, Some(Role::Striker));
+        assert!(roles.two.is_some() && roles.four.is_some());
+    }
+
+    #[test]
+    fn silent_teammate_without_a_previous_role_takes_its_fallback_role() {
+        let team = Players {
+            two: active(2.0, 0.0),
+            three: Some(Member::Silent),
+            ..Players::new(None)
+        };
+
+        let roles = assign(
+            &team,
+            Some(point![2.5, 0.0]),
+            &Players::new(None),
+            &parameters(),
+        );
+
+        assert_eq!(roles.three, Some(Role::Striker));
+        assert_eq!(roles.two, Some(Role::KeeperReplacement));
+    }
+
+    #[test]
+    fn roles_are_kept_without_a_ball() {
+        let team = Players {
+            two: active(-3.0, 0.0),
+            three: active(2.0, 0.0),
+            ..Players::new(None)
+        };
+        let previous_roles = Players {
+            two: Some(Role::Striker),
+            three: Some(Role::KeeperReplacement),
+            ..Players::new(None)
+        };
+
+        let roles = assign(&team, None, &previous_roles, &parameters());
+
+        assert_eq!(roles, previous_roles);
+    }
+
+    #[test]
+    fn teammates_fall_silent_before_they_are_lost() {
+        let parameters = parameters();
+        let pose = Pose2::new(point![1.0, 2.0], 0.0);
+
+        assert!(matches!(
+            teammate(pose, Duration::from_secs(1), &parameters),
+            Some(Member::Active(Some(_)))
+        ));
+        assert!(matches!(
+            teammate(pose, Duration::from_secs(6), &parameters),
+            Some(Member::Silent)
+        ));
+        assert!(teammate(pose, Duration::from_secs(21), &parameters).is_none());
+    }
+}
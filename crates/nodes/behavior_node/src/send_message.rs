@@ -1,9 +1,15 @@
 use std::{net::SocketAddr, time::Duration};
 
 use booster::FallDownStateType;
-use hsl_network_messages::{GameControllerReturnMessage, HulkMessage, StateMessage};
+use hsl_network_messages::{
+    GameControllerReturnMessage, Half, HulkMessage, StateMessage,
+    budget::{MessageBudgetPlanner, MessagePriority},
+};
 use ros_z::time::Time;
-use types::{messages::OutgoingMessage, parameters::HslNetworkParameters};
+use types::{
+    filtered_game_controller_state::FilteredGameControllerState, messages::OutgoingMessage,
+    parameters::HslNetworkParameters,
+};
 
 use crate::node::Blackboard;
 
@@ -62,22 +68,20 @@ impl Blackboard {
 
     pub fn state_message(&mut self) -> Option<OutgoingMessage> {
         let now = self.world_state.now;
-        let remaining_amount_of_messages = self
-            .world_state
-            .filtered_game_controller_state
-            .as_ref()
-            .map(|state| state.remaining_number_of_messages);
-
-        if !self.is_state_message_cooldown_elapsed(now, &self.parameters.hsl_network) {
-            return None;
-        }
-        if remaining_amount_of_messages.is_none_or(|remaining_amount_of_messages| {
-            remaining_amount_of_messages
-                < self
-                    .parameters
-                    .hsl_network
-                    .remaining_amount_of_messages_to_stop_sending
-        }) {
+        let hsl_network_parameters = &self.parameters.hsl_network;
+        let game_controller_state = self.world_state.filtered_game_controller_state.as_ref()?;
+        let planner = MessageBudgetPlanner {
+            minimum_interval: hsl_network_parameters.minimum_state_message_interval,
+            reserved_messages: hsl_network_parameters.remaining_amount_of_messages_to_stop_sending,
+        };
+        let send_interval = planner.send_interval(
+            game_controller_state.remaining_number_of_messages,
+            remaining_game_time(game_controller_state, hsl_network_parameters.half_duration),
+            sending_players(game_controller_state),
+            self.state_message_priority(),
+        )?;
+
+        if !is_cooldown_elapsed(now, self.last_sent_hsl_message_time, send_interval) {
             return None;
         }
 
@@ -100,26 +104,46 @@ impl Blackboard {
             player_number: self.world_state.robot.player_number,
             pose,
             ball_position,
+            search_state: self.world_state.search_state,
         });
 
         self.last_sent_hsl_message_time = Some(now);
+        self.last_sent_state_message_ball_seen = ball_position.is_some();
+        self.last_sent_state_message_role = self.role;
 
         Some(OutgoingMessage::Hsl(message))
     }
 
-    fn is_state_message_cooldown_elapsed(
-        &self,
-        now: Time,
-        hsl_network_parameters: &HslNetworkParameters,
-    ) -> bool {
-        is_cooldown_elapsed(
-            now,
-            self.last_sent_hsl_message_time,
-            hsl_network_parameters.hsl_state_message_send_interval,
-        )
+    /// News since the last state message are sent earlier than routine updates.
+    fn state_message_priority(&self) -> MessagePriority {
+        if self.world_state.ball.is_some() && !self.last_sent_state_message_ball_seen {
+            MessagePriority::Urgent
+        } else if self.role != self.last_sent_state_message_role {
+            MessagePriority::Important
+        } else {
+            MessagePriority::Routine
+        }
+    }
+}
+
+fn remaining_game_time(
+    game_controller_state: &FilteredGameControllerState,
+    half_duration: Duration,
+) -> Duration {
+    match game_controller_state.half {
+        Half::First => game_controller_state.remaining_time_in_half + half_duration,
+        Half::Second => game_controller_state.remaining_time_in_half,
     }
 }
 
+fn sending_players(game_controller_state: &FilteredGameControllerState) -> usize {
+    game_controller_state
+        .penalties
+        .iter()
+        .filter(|(_, penalty)| penalty.is_none())
+        .count()
+}
+
 fn is_cooldown_elapsed(now: Time, last: Option<Time>, cooldown: Duration) -> bool {
     match last {
         None => true,
@@ -1,4 +1,4 @@
-use types::{motion_type::MotionType, primary_state::PrimaryState};
+use types::{motion_type::MotionType, primary_state::PrimaryState, role::Role};
 
 use crate::{
     action,
@@ -6,14 +6,14 @@ use crate::{
     behavior_tree::Node,
     condition,
     conditions::{
-        has_ball_position, is_ball_interception_candidate, is_close_to_ball, is_closest_to_ball,
-        is_fallen, is_goalkeeper, is_last_hulk_standing, is_primary_state, is_remote_controlled,
-        is_remote_kick_mode,
+        has_ball_position, is_ball_interception_candidate, is_close_to_ball, is_fallen,
+        is_primary_state, is_remote_controlled, is_remote_kick_mode,
     },
     head::{look_around, look_at_ball_subtree, look_straight_ahead, search_for_lost_ball_subtree},
     kick::{intercept, kick, kick_power_subtree, kick_subtree, set_kick_target_in_front},
     negation,
     node::Blackboard,
+    role_assignment::{assign_roles, has_role},
     search::{has_suggested_search_position, leuchtturm, walk_to_search_position},
     selection, sequence,
     substates::{is_in_sub_state, sub_state_subtree},
@@ -22,6 +22,7 @@ use crate::{
     voronoi::calculate_voronoi_grid,
     walk::{
         walk_alternatives_subtree, walk_to_ball_subtree, walk_to_centroid, walk_to_kickoff_pose,
+        walk_to_role_position,
     },
 };
 
@@ -83,19 +84,31 @@ fn ready_subtree() -> Node<Blackboard> {
 }
 
 fn playing_subtree() -> Node<Blackboard> {
-    selection!(
-        sequence!(condition!(is_last_hulk_standing), subtree!(striker_subtree)),
-        sequence!(condition!(is_goalkeeper), subtree!(goalkeeper_subtree)),
-        sequence!(
-            negation!(condition!(has_ball_position)),
-            subtree!(search_subtree)
-        ),
-        sequence!(
-            action!(calculate_voronoi_grid),
-            condition!(is_closest_to_ball),
-            subtree!(striker_subtree)
-        ),
-        subtree!(supporter_subtree),
+    sequence!(
+        action!(assign_roles),
+        selection!(
+            sequence!(
+                condition!(has_role, Role::Keeper),
+                subtree!(goalkeeper_subtree)
+            ),
+            sequence!(
+                negation!(condition!(has_ball_position)),
+                subtree!(search_subtree)
+            ),
+            sequence!(
+                action!(calculate_voronoi_grid),
+                condition!(has_role, Role::Striker),
+                subtree!(striker_subtree)
+            ),
+            sequence!(
+                selection!(
+                    condition!(has_role, Role::KeeperReplacement),
+                    condition!(has_role, Role::Defender),
+                ),
+                subtree!(defender_subtree)
+            ),
+            subtree!(supporter_subtree),
+        )
     )
 }
 
@@ -139,6 +152,13 @@ fn striker_subtree() -> Node<Blackboard> {
     )
 }
 
+fn defender_subtree() -> Node<Blackboard> {
+    sequence!(
+        subtree!(look_at_ball_subtree),
+        selection!(action!(walk_to_role_position), action!(stand)),
+    )
+}
+
 fn supporter_subtree() -> Node<Blackboard> {
     sequence!(
         subtree!(look_at_ball_subtree),
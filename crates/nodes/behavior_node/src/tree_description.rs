@@ -0,0 +1,395 @@
+use std::{
+    collections::BTreeMap,
+    fs::read_to_string,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use color_eyre::{
+    Result,
+    eyre::{Context, bail},
+};
+use serde::{Deserialize, Serialize};
+use types::motion_type::MotionType;
+
+use crate::{
+    behavior_tree::{Node, ParallelPolicy},
+    node::Blackboard,
+    registry::{Registry, intern},
+    switch_motion_type::switch_motion_type,
+};
+
+/// Name of the tree description inside a parameter layer.
+pub const TREE_FILE_NAME: &str = "behavior_tree.json5";
+
+/// Behavior tree as written in a parameter layer.
+///
+/// Leaves are names of registered actions and conditions, optionally with an argument like
+/// `is_primary_state(Playing)`. Subtrees are looked up in `subtrees` first and in the Rust
+/// subtrees of the registry afterwards.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct TreeDescription {
+    pub root: NodeDescription,
+    #[serde(default)]
+    pub subtrees: BTreeMap<String, NodeDescription>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(untagged)]
+pub enum NodeDescription {
+    Node(Box<CompositeDescription>),
+    Leaf(String),
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CompositeDescription {
+    Cooldown {
+        duration: Duration,
+        child: NodeDescription,
+    },
+    Failure,
+    Negation(NodeDescription),
+    Once(NodeDescription),
+    Parallel {
+        success_policy: ParallelPolicy,
+        failure_policy: ParallelPolicy,
+        children: Vec<NodeDescription>,
+    },
+    Retry {
+        attempts: usize,
+        child: NodeDescription,
+    },
+    RunningMemory(Vec<NodeDescription>),
+    Selection(Vec<NodeDescription>),
+    Sequence(Vec<NodeDescription>),
+    Subtree(String),
+    SwitchMotionType {
+        motion_type: MotionType,
+        action: NodeDescription,
+        alternatives: NodeDescription,
+    },
+    Timeout {
+        duration: Duration,
+        child: NodeDescription,
+    },
+}
+
+impl TreeDescription {
+    pub fn from_json5(content: &str) -> Result<Self> {
+        json5::from_str(content).wrap_err("failed to parse behavior tree description")
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = read_to_string(path)
+            .wrap_err_with(|| format!("failed to read behavior tree {}", path.display()))?;
+        Self::from_json5(&content).wrap_err_with(|| format!("invalid file {}", path.display()))
+    }
+
+    /// Builds the tree, rejecting unknown leaves and subtrees with all problems at once.
+    pub fn build(&self, registry: &Registry) -> Result<Node<Blackboard>> {
+        let mut builder = TreeBuilder {
+            registry,
+            subtrees: &self.subtrees,
+            expanding: Vec::new(),
+            errors: Vec::new(),
+        };
+        let tree = builder.build(&self.root);
+        if !builder.errors.is_empty() {
+            bail!("invalid behavior tree: {}", builder.errors.join("; "));
+        }
+        Ok(tree)
+    }
+}
+
+/// Finds the tree description of the highest priority layer, layers later in the list override
+/// earlier ones.
+pub fn find_tree_file(parameter_layers: &[PathBuf]) -> Option<PathBuf> {
+    parameter_layers
+        .iter()
+        .rev()
+        .map(|layer| layer.join(TREE_FILE_NAME))
+        .find(|path| path.is_file())
+}
+
+struct TreeBuilder<'a> {
+    registry: &'a Registry,
+    subtrees: &'a BTreeMap<String, NodeDescription>,
+    expanding: Vec<&'a str>,
+    errors: Vec<String>,
+}
+
+impl<'a> TreeBuilder<'a> {
+    fn build(&mut self, description: &'a NodeDescription) -> Node<Blackboard> {
+        let composite = match description {
+            NodeDescription::Leaf(leaf) => {
+                return self.registry.create_leaf(leaf).unwrap_or_else(|error| {
+                    self.errors.push(error);
+                    Node::Failure
+                });
+            }
+            NodeDescription::Node(composite) => composite.as_ref(),
+        };
+
+        match composite {
+            CompositeDescription::Cooldown { duration, child } => Node::Cooldown {
+                name: "Cooldown",
+                duration: *duration,
+                child: Box::new(self.build(child)),
+                is_active: false,
+                blocked_since: None,
+            },
+            CompositeDescription::Failure => Node::Failure,
+            CompositeDescription::Negation(child) => Node::Negation {
+                name: "Negation",
+                child: Box::new(self.build(child)),
+            },
+            CompositeDescription::Once(child) => Node::Once {
+                name: "Once",
+                child: Box::new(self.build(child)),
+                result: None,
+            },
+            CompositeDescription::Parallel {
+                success_policy,
+                failure_policy,
+                children,
+            } => Node::Parallel {
+                name: "Parallel",
+                children: self.build_all(children),
+                success_policy: *success_policy,
+                failure_policy: *failure_policy,
+            },
+            CompositeDescription::Retry { attempts, child } => Node::Retry {
+                name: "Retry",
+                attempts: *attempts,
+                child: Box::new(self.build(child)),
+                failures: 0,
+            },
+            CompositeDescription::RunningMemory(children) => Node::RunningMemory {
+                name: "RunningMemory",
+                children: self.build_all(children),
+                running_child: None,
+            },
+            CompositeDescription::Selection(children) => Node::Selection {
+                name: "Selection",
+                children: self.build_all(children),
+            },
+            CompositeDescription::Sequence(children) => Node::Sequence {
+                name: "Sequence",
+                children: self.build_all(children),
+            },
+            CompositeDescription::Subtree(name) => Node::Subtree {
+                name: intern(format!("subtree_{name}")),
+                tree: Box::new(self.build_subtree(name)),
+            },
+            CompositeDescription::SwitchMotionType {
+                motion_type,
+                action,
+                alternatives,
+            } => switch_motion_type(*motion_type, self.build(action), self.build(alternatives)),
+            CompositeDescription::Timeout { duration, child } => Node::Timeout {
+                name: "Timeout",
+                duration: *duration,
+                child: Box::new(self.build(child)),
+                started: None,
+            },
+        }
+    }
+
+    fn build_all(&mut self, descriptions: &'a [NodeDescription]) -> Vec<Node<Blackboard>> {
+        descriptions
+            .iter()
+            .map(|description| self.build(description))
+            .collect()
+    }
+
+    fn build_subtree(&mut self, name: &'a str) -> Node<Blackboard> {
+        if self.expanding.contains(&name) {
+            self.errors.push(format!(
+                "subtree `{name}` contains itself ({} -> {name})",
+                self.expanding.join(" -> ")
+            ));
+            return Node::Failure;
+        }
+        if let Some(description) = self.subtrees.get(name) {
+            self.expanding.push(name);
+            let subtree = self.build(description);
+            self.expanding.pop();
+            return subtree;
+        }
+        self.registry.create_subtree(name).unwrap_or_else(|| {
+            self.errors.push(format!("unknown subtree `{name}`"));
+            Node::Failure
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::{create_dir_all, remove_dir_all, write};
+
+    use serde_json::{Value, to_value};
+
+    use crate::{registry::default_registry, tree::create_tree};
+
+    use super::*;
+
+    const SHIPPED_TREE: &str =
+        include_str!("../../../../etc/parameters/ros_z/base/behavior_tree.json5");
+
+    fn build(content: &str) -> Result<Node<Blackboard>> {
+        TreeDescription::from_json5(content)?.build(&default_registry())
+    }
+
+    /// Names of parameterized leaves differ between macros and descriptions, the structure
+    /// has to match.
+    fn without_names(mut node: Value) -> Value {
+        if let Some(node) = node.as_object_mut() {
+            node.remove("name");
+            if let Some(Value::Array(children)) = node.get_mut("children") {
+                for child in children {
+                    *child = without_names(child.take());
+                }
+            }
+        }
+        node
+    }
+
+    #[test]
+    fn shipped_tree_builds_against_the_default_registry() {
+        build(SHIPPED_TREE).unwrap();
+    }
+
+    #[test]
+    fn shipped_tree_matches_the_compiled_tree() {
+        let loaded = build(SHIPPED_TREE).unwrap();
+        let compiled = create_tree();
+
+        assert_eq!(
+            without_names(to_value(&loaded).unwrap()),
+            without_names(to_value(&compiled).unwrap())
+        );
+    }
+
+    #[test]
+    fn unknown_leaves_and_subtrees_are_rejected_all_at_once() {
+        let error = build(
+            r#"{
+                root: {
+                    selection: [
+                        "walk_to_nowhere",
+                        "is_primary_state(Dancing)",
+                        "stand(fast)",
+                        "has_role",
+                        { subtree: "missing_subtree" },
+                        "stand",
+                    ],
+                },
+            }"#,
+        )
+        .unwrap_err()
+        .to_string();
+
+        assert!(error.contains("unknown leaf `walk_to_nowhere`"), "{error}");
+        assert!(error.contains("invalid argument `Dancing`"), "{error}");
+        assert!(error.contains("leaf `stand` takes no argument"), "{error}");
+        assert!(
+            error.contains("leaf `has_role` requires an argument"),
+            "{error}"
+        );
+        assert!(
+            error.contains("unknown subtree `missing_subtree`"),
+            "{error}"
+        );
+    }
+
+    #[test]
+    fn recursive_subtrees_are_rejected() {
+        let error = build(
+            r#"{
+                root: { subtree: "a" },
+                subtrees: {
+                    a: { sequence: ["stand", { subtree: "b" }] },
+                    b: { selection: [{ subtree: "a" }] },
+                },
+            }"#,
+        )
+        .unwrap_err()
+        .to_string();
+
+        assert!(
+            error.contains("subtree `a` contains itself (a -> b -> a)"),
+            "{error}"
+        );
+    }
+
+    #[test]
+    fn parameterized_leaves_are_named_after_their_argument() {
+        let tree =
+            build(r#"{ root: { sequence: ["is_primary_state(Playing)", "stand"] } }"#).unwrap();
+
+        let layout = tree.static_layout_trace();
+
+        assert_eq!(layout.children[0].name, "is_primary_state(Playing)");
+        assert_eq!(layout.children[1].name, "stand");
+    }
+
+    #[test]
+    fn decorators_and_description_subtrees_are_built() {
+        let tree = build(
+            r#"{
+                root: {
+                    parallel: {
+                        success_policy: "RequireAll",
+                        failure_policy: "RequireOne",
+                        children: [
+                            { timeout: { duration: { secs: 5, nanos: 0 }, child: { subtree: "search" } } },
+                            { cooldown: { duration: { secs: 1, nanos: 0 }, child: "kick" } },
+                            { retry: { attempts: 3, child: { once: "stand_up" } } },
+                            { running_memory: ["look_around", "stand"] },
+                        ],
+                    },
+                },
+                subtrees: { search: { sequence: ["look_around"] } },
+            }"#,
+        )
+        .unwrap();
+
+        let layout = tree.static_layout_trace();
+        let names = |trace: &types::behavior_tree::NodeTrace| {
+            trace
+                .children
+                .iter()
+                .map(|child| child.name.clone())
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(layout.name, "Parallel");
+        assert_eq!(
+            names(&layout),
+            ["Timeout", "Cooldown", "Retry", "RunningMemory"]
+        );
+        assert_eq!(names(&layout.children[0]), ["subtree_search"]);
+        assert_eq!(names(&layout.children[0].children[0]), ["Sequence"]);
+        assert_eq!(names(&layout.children[2]), ["Once"]);
+    }
+
+    #[test]
+    fn tree_file_of_the_highest_priority_layer_is_found() {
+        let directory =
+            std::env::temp_dir().join(format!("behavior_tree_layers_{}", std::process::id()));
+        let layers = ["base", "location", "robot"].map(|layer| directory.join(layer));
+        for layer in &layers {
+            create_dir_all(layer).unwrap();
+        }
+        write(layers[0].join(TREE_FILE_NAME), SHIPPED_TREE).unwrap();
+        write(layers[1].join(TREE_FILE_NAME), SHIPPED_TREE).unwrap();
+
+        let found = find_tree_file(&layers);
+        let none_found = find_tree_file(&layers[2..]);
+        remove_dir_all(&directory).unwrap();
+
+        assert_eq!(found, Some(layers[1].join(TREE_FILE_NAME)));
+        assert_eq!(none_found, None);
+    }
+}
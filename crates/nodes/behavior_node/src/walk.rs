@@ -1,13 +1,16 @@
 use coordinate_systems::{Field, Ground};
 use filtering::hysteresis::less_than_with_relative_hysteresis;
 use hsl_network_messages::PlayerNumber;
-use linear_algebra::{Isometry2, Orientation2, Point, Point2, Pose2, point};
-use path_planner::path_planner::PathPlanner;
+use linear_algebra::{Isometry2, Orientation2, Point, Pose2, point};
+use path_planner::{
+    path_planner::{ObstaclePrediction, PathPlanner},
+    walk_time::{WalkTimeModel, kick_pose},
+};
 use types::{
     behavior_tree::Status,
     motion_command::{BodyMotion, MotionCommand, OrientationMode},
     motion_type::MotionType,
-    path::{Path, direct_path},
+    path::{Path, TimedPath, direct_path},
 };
 
 use crate::{
@@ -24,9 +27,10 @@ use crate::{
 
 pub fn plan(
     blackboard: &mut Blackboard,
-    target_in_ground: Point2<Ground>,
+    target_pose: Pose2<Ground>,
     ground_to_field: Isometry2<Ground, Field>,
 ) -> Path {
+    let target_in_ground = target_pose.position();
     let parameters: &types::parameters::PathPlanningParameters =
         &blackboard.parameters.path_planning;
     let field_dimensions = blackboard.field_dimensions;
@@ -35,11 +39,8 @@ pub fn plan(
         obstacle_escape_spline_segments: parameters.obstacle_escape_spline_segments,
         ..Default::default()
     };
-    planner.with_last_motion(
-        &blackboard.last_motion_command,
-        parameters.rotation_penalty_factor,
-    );
-    planner.with_obstacles(&blackboard.world_state.obstacles, parameters.robot_radius);
+    let walk_time_model = WalkTimeModel::new(parameters.walk_speed_extents.clone());
+    planner.with_walk_time_model(walk_time_model.clone(), Some(target_pose.orientation()));
     planner.with_rule_obstacles(
         ground_to_field.inverse(),
         &blackboard.world_state.rule_obstacles,
@@ -72,11 +73,31 @@ pub fn plan(
             target_in_field.y().clamp(-y_max, y_max)
         ];
 
+    if parameters.predict_obstacle_motion {
+        planner.with_predicted_obstacles(
+            &blackboard.world_state.obstacles,
+            parameters.robot_radius,
+            Point::origin(),
+            clamped_target_in_robot,
+            &ObstaclePrediction {
+                walking_speed: parameters.walk_speed_extents.forward,
+                horizon: parameters.obstacle_prediction_horizon,
+                radius_growth_per_second: parameters.obstacle_prediction_radius_growth,
+            },
+        );
+    } else {
+        planner.with_obstacles(&blackboard.world_state.obstacles, parameters.robot_radius);
+    }
+
     let path = planner
         .plan(Point::origin(), clamped_target_in_robot)
-        .unwrap();
+        .unwrap()
+        .unwrap_or_else(|| direct_path(Point::origin(), target_in_ground));
     blackboard.path_obstacles_output = planner.obstacles;
-    path.unwrap_or_else(|| direct_path(Point::origin(), target_in_ground))
+    let segment_times = walk_time_model.segment_times(&path, Some(target_pose.orientation()));
+    blackboard.planned_path_output =
+        Some(TimedPath::from_segment_times(path.clone(), segment_times));
+    path
 }
 
 pub fn walk_to(
@@ -117,7 +138,7 @@ pub fn walk_to(
             blackboard.body_motion = Some(BodyMotion::Stand);
             Status::Success
         } else {
-            let path = plan(blackboard, target_pose.position(), ground_to_field);
+            let path = plan(blackboard, target_pose, ground_to_field);
             blackboard.body_motion = Some(BodyMotion::Walk {
                 path,
                 orientation_mode,
@@ -140,14 +161,14 @@ pub fn walk_to_ball(blackboard: &mut Blackboard) -> Status {
         let field_to_ground = ground_to_field.inverse();
         let ball_in_ground = field_to_ground * ball.position;
         let goal_position = field_to_ground * point!(blackboard.field_dimensions.length / 2.0, 0.0);
-        let orientation = Orientation2::from_vector(goal_position - ball_in_ground);
-
-        let target_position = ball_in_ground
-            - (goal_position - ball_in_ground).normalize()
-                * blackboard.parameters.kicking.kick_position_ball_distance;
+        let kick_pose = kick_pose(
+            ball_in_ground,
+            goal_position,
+            blackboard.parameters.kicking.kick_position_ball_distance,
+        );
         walk_to(
             blackboard,
-            Pose2::from_parts(target_position, orientation),
+            kick_pose,
             blackboard.parameters.walk_speed.kicking,
             OrientationMode::AlignWithPath,
             blackboard
@@ -211,6 +232,36 @@ pub fn walk_to_block_position(blackboard: &mut Blackboard) -> Status {
     }
 }
 
+pub fn walk_to_role_position(blackboard: &mut Blackboard) -> Status {
+    if let (Some(role_position), Some(ball), Some(ground_to_field)) = (
+        blackboard.role_position,
+        &blackboard.last_ball,
+        blackboard.world_state.robot.ground_to_field,
+    ) {
+        let field_to_ground = ground_to_field.inverse();
+        let role_position = field_to_ground * role_position;
+        let ball_position = field_to_ground * ball.position;
+        let orientation = Orientation2::from_vector(ball_position - role_position);
+
+        walk_to(
+            blackboard,
+            Pose2::from_parts(role_position, orientation),
+            blackboard.parameters.walk_speed.blocking,
+            OrientationMode::LookAt {
+                target: ball_position,
+                tolerance: blackboard.parameters.walk_and_stand.orientation_tolerance,
+            },
+            blackboard
+                .parameters
+                .walk_and_stand
+                .normal_distance_to_be_aligned,
+            blackboard.parameters.walk_and_stand.hysteresis,
+        )
+    } else {
+        Status::Failure
+    }
+}
+
 pub fn walk_to_kickoff_pose(blackboard: &mut Blackboard) -> Status {
     if let (Some(ground_to_field), player_number) = (
         blackboard.world_state.robot.ground_to_field,
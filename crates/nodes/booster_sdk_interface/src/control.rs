@@ -18,12 +18,15 @@ pub enum DesiredMode {
     Damping,
     Prepare,
     Soccer,
+    /// Joint-level control, e.g. by the keyframe motion player
+    Custom,
 }
 
 pub fn desired_mode_for(command: &MotionCommand) -> DesiredMode {
     match command {
         MotionCommand::Damping => DesiredMode::Damping,
         MotionCommand::Prepare | MotionCommand::StandUp => DesiredMode::Prepare,
+        MotionCommand::KeyframeMotion { .. } => DesiredMode::Custom,
         MotionCommand::Stand { .. }
         | MotionCommand::VisualKick { .. }
         | MotionCommand::Walk { .. }
@@ -101,6 +104,7 @@ pub fn step_from_motion_command(command: &MotionCommand, parameters: &WalkingPar
         | MotionCommand::Damping
         | MotionCommand::Prepare
         | MotionCommand::StandUp
+        | MotionCommand::KeyframeMotion { .. }
         | MotionCommand::VisualKick { .. } => Step::ZERO,
     }
 }
@@ -213,6 +217,16 @@ mod tests {
         );
     }
 
+    #[test]
+    fn keyframe_motion_requests_custom_mode() {
+        assert_eq!(
+            desired_mode_for(&MotionCommand::KeyframeMotion {
+                name: "celebrate".to_string()
+            }),
+            DesiredMode::Custom
+        );
+    }
+
     #[test]
     fn damping_requests_damping_mode() {
         assert_eq!(
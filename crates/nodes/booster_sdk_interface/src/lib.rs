@@ -11,16 +11,21 @@ use std::{
     time::{Duration, Instant},
 };
 
-use booster_sdk::client::{BoosterClient, light_control::LightControlClient};
 use color_eyre::{Result, eyre::WrapErr};
 use kinematics::joints::head::HeadJoints;
 use ros_z::prelude::*;
 use serde::{Deserialize, Serialize};
+use tokio::sync::mpsc::{UnboundedSender, unbounded_channel};
 use tracing::{error, info};
 use types::motion_command::MotionCommand;
 
+use crate::services::{BoosterServices, SdkServices};
+
 mod control;
 mod kick_transport;
+#[cfg(feature = "mock")]
+pub mod mock;
+pub mod services;
 
 const MOTION_COMMAND_TOPIC: &str = "behavior/motion_command";
 
@@ -54,6 +59,7 @@ enum MotionCommandKind {
     Prepare,
     Stand,
     StandUp,
+    KeyframeMotion,
     VisualKick,
     Walk,
 }
@@ -65,6 +71,7 @@ impl MotionCommandKind {
             MotionCommand::Prepare => Self::Prepare,
             MotionCommand::Stand { .. } => Self::Stand,
             MotionCommand::StandUp => Self::StandUp,
+            MotionCommand::KeyframeMotion { .. } => Self::KeyframeMotion,
             MotionCommand::VisualKick { .. } => Self::VisualKick,
             MotionCommand::Walk { .. } | MotionCommand::WalkWithVelocity { .. } => Self::Walk,
         }
@@ -72,7 +79,7 @@ impl MotionCommandKind {
 }
 
 #[derive(Debug, Clone, Copy)]
-enum RpcActionKind {
+pub enum RpcActionKind {
     ChangeMode,
     GetUp,
     MoveRobot,
@@ -94,8 +101,9 @@ impl RpcActionKind {
     }
 }
 
+/// Bookkeeping of the SDK requests sent by the interface loop, shared with the spawned requests.
 #[derive(Default)]
-struct RpcDiagnostics {
+pub struct RpcDiagnostics {
     next_sequence: AtomicU64,
     change_mode_in_flight: AtomicUsize,
     get_up_in_flight: AtomicUsize,
@@ -103,9 +111,31 @@ struct RpcDiagnostics {
     rotate_head_in_flight: AtomicUsize,
     kick_publish_in_flight: AtomicUsize,
     visual_kick_in_flight: AtomicUsize,
+    failed: AtomicUsize,
+    timed_out: AtomicUsize,
 }
 
 impl RpcDiagnostics {
+    /// Number of requests sent so far
+    pub fn started(&self) -> u64 {
+        self.next_sequence.load(Ordering::Relaxed)
+    }
+
+    /// Number of requests of `kind` which did not finish yet
+    pub fn in_flight(&self, kind: RpcActionKind) -> usize {
+        self.counter(kind).load(Ordering::Relaxed)
+    }
+
+    /// Number of requests answered with an error
+    pub fn failed(&self) -> usize {
+        self.failed.load(Ordering::Relaxed)
+    }
+
+    /// Number of requests which exceeded the `sdk_request_timeout`
+    pub fn timed_out(&self) -> usize {
+        self.timed_out.load(Ordering::Relaxed)
+    }
+
     fn begin(self: &Arc<Self>, kind: RpcActionKind) -> RpcAttempt {
         let sequence = self.next_sequence.fetch_add(1, Ordering::Relaxed) + 1;
         let counter = self.counter(kind);
@@ -146,6 +176,15 @@ impl RpcAttempt {
             .counter(self.kind)
             .fetch_sub(1, Ordering::Relaxed)
             .saturating_sub(1);
+        match status {
+            "error" => {
+                self.diagnostics.failed.fetch_add(1, Ordering::Relaxed);
+            }
+            "timeout" => {
+                self.diagnostics.timed_out.fetch_add(1, Ordering::Relaxed);
+            }
+            _ => {}
+        }
         let elapsed_ms = self.started_at.elapsed().as_secs_f64() * 1000.0;
         info!(
             target: "booster_interface::rpc",
@@ -192,6 +231,12 @@ impl InterfaceState {
     }
 }
 
+/// Mode change which did not succeed, the interface falls back to the previous mode and retries.
+struct FailedModeChange {
+    requested: control::DesiredMode,
+    previous: control::DesiredMode,
+}
+
 fn due(last: std::time::Instant, now: std::time::Instant, interval: Duration) -> bool {
     now.duration_since(last) >= interval
 }
@@ -210,6 +255,17 @@ pub fn run_boxed(ctx: Arc<Context>) -> Pin<Box<dyn Future<Output = Result<()>> +
 }
 
 async fn run(ctx: Arc<Context>) -> Result<()> {
+    let services = SdkServices::new(ctx.session()).await?;
+    run_with_services(ctx, services, Arc::new(RpcDiagnostics::default())).await
+}
+
+/// Runs the interface loop against `services`, e.g. the mock services in tests, and records the
+/// sent requests in `rpc_diagnostics`.
+pub async fn run_with_services(
+    ctx: Arc<Context>,
+    services: impl BoosterServices,
+    rpc_diagnostics: Arc<RpcDiagnostics>,
+) -> Result<()> {
     let node = ctx
         .create_node("booster_interface")
         .build()
@@ -218,13 +274,6 @@ async fn run(ctx: Arc<Context>) -> Result<()> {
     let parameters = node
         .bind_parameter_as::<Parameters>("booster_interface")
         .wrap_err("failed to bind booster_interface parameters")?;
-    let light_control_client =
-        Arc::new(LightControlClient::new().wrap_err("failed to create LightControlClient")?);
-    let booster_client = BoosterClient::with_options(booster_rpc_options())
-        .wrap_err("failed to create BoosterClient")?;
-    let kick_ball_publisher = kick_transport::KickBallPublisher::new(ctx.session())
-        .await
-        .wrap_err("failed to create kick ball publisher")?;
 
     let motion_command_cache = node
         .subscriber::<MotionCommand>(MOTION_COMMAND_TOPIC)
@@ -243,11 +292,10 @@ async fn run(ctx: Arc<Context>) -> Result<()> {
         .build()
         .await
         .wrap_err("failed to build commands/led_command subscriber")?;
-    let booster_client = Arc::new(booster_client);
-    let kick_ball_publisher = Arc::new(kick_ball_publisher);
-    let rpc_diagnostics = Arc::new(RpcDiagnostics::default());
+    let services = Arc::new(services);
+    let (failed_mode_change_sender, mut failed_mode_changes) = unbounded_channel();
 
-    tokio::time::sleep(booster_effect_startup_wait()).await;
+    tokio::time::sleep(services.startup_wait()).await;
 
     let mut state = InterfaceState::new(std::time::Instant::now());
     let mut tick = node.create_timer(std::time::Duration::from_millis(10));
@@ -257,8 +305,8 @@ async fn run(ctx: Arc<Context>) -> Result<()> {
             led_command = led_command_sub.recv() => {
                 let led_command = led_command?;
                 info!(target: "booster_interface::input", ?led_command, "received led command");
-                let light_control_client = light_control_client.clone();
-                tokio::spawn(handle_led_command(light_control_client, led_command));
+                let services = services.clone();
+                tokio::spawn(handle_led_command(services, led_command));
             }
             _ = tick.tick() => {
                 let parameters_snapshot = parameters.snapshot();
@@ -274,6 +322,13 @@ async fn run(ctx: Arc<Context>) -> Result<()> {
                 let timeout = parameters.sdk_request_timeout;
                 let motion_kind = MotionCommandKind::from_command(motion_command);
 
+                while let Ok(failed_mode_change) = failed_mode_changes.try_recv() {
+                    let FailedModeChange { requested, previous } = failed_mode_change;
+                    if state.assumed_mode == requested {
+                        state.assumed_mode = previous;
+                    }
+                }
+
                 if state.visual_kick_active && motion_kind != MotionCommandKind::VisualKick {
                     if state.assumed_mode == control::DesiredMode::Soccer {
                         let attempt = rpc_diagnostics.begin(RpcActionKind::VisualKick);
@@ -285,10 +340,10 @@ async fn run(ctx: Arc<Context>) -> Result<()> {
                             in_flight = attempt.in_flight_at_start,
                             "booster rpc scheduled"
                         );
-                        let booster_client = booster_client.clone();
+                        let services = services.clone();
                         tokio::spawn(async move {
                             let _ = await_sdk_call(
-                                booster_client.visual_kick(false),
+                                services.visual_kick(false),
                                 timeout,
                                 "stop visual kick",
                                 attempt,
@@ -334,16 +389,17 @@ async fn run(ctx: Arc<Context>) -> Result<()> {
                         in_flight = attempt.in_flight_at_start,
                         "booster rpc scheduled"
                     );
-                    let booster_client = booster_client.clone();
-                    tokio::spawn(async move {
-                        let _ = await_sdk_call(
-                            booster_client.change_mode(mode),
-                            timeout,
-                            format!("request booster mode {mode:?}"),
-                            attempt,
-                        )
-                        .await;
-                    });
+                    tokio::spawn(request_mode(
+                        services.clone(),
+                        mode,
+                        timeout,
+                        attempt,
+                        failed_mode_change_sender.clone(),
+                        FailedModeChange {
+                            requested: desired_mode,
+                            previous: state.assumed_mode,
+                        },
+                    ));
                     state.assumed_mode = desired_mode;
                 }
 
@@ -359,10 +415,10 @@ async fn run(ctx: Arc<Context>) -> Result<()> {
                         in_flight = attempt.in_flight_at_start,
                         "booster rpc scheduled"
                     );
-                    let booster_client = booster_client.clone();
+                    let services = services.clone();
                     tokio::spawn(async move {
                         let _ = await_sdk_call(
-                            booster_client.get_up(),
+                            services.get_up(),
                             timeout,
                             "request get_up",
                             attempt,
@@ -391,10 +447,10 @@ async fn run(ctx: Arc<Context>) -> Result<()> {
                                 in_flight = attempt.in_flight_at_start,
                                 "booster rpc scheduled"
                             );
-                            let kick_ball_publisher = kick_ball_publisher.clone();
+                            let services = services.clone();
                             tokio::spawn(async move {
                                 let _ = await_sdk_call(
-                                    kick_ball_publisher.publish(&kick),
+                                    services.publish_kick(kick),
                                     timeout,
                                     "publish visual kick command",
                                     attempt,
@@ -415,10 +471,10 @@ async fn run(ctx: Arc<Context>) -> Result<()> {
                             in_flight = attempt.in_flight_at_start,
                             "booster rpc scheduled"
                         );
-                        let booster_client = booster_client.clone();
+                        let services = services.clone();
                         tokio::spawn(async move {
                             let _ = await_sdk_call(
-                                booster_client.visual_kick(true),
+                                services.visual_kick(true),
                                 timeout,
                                 "start visual kick",
                                 attempt,
@@ -445,10 +501,10 @@ async fn run(ctx: Arc<Context>) -> Result<()> {
                         in_flight = attempt.in_flight_at_start,
                         "booster rpc scheduled"
                     );
-                    let booster_client = booster_client.clone();
+                    let services = services.clone();
                     tokio::spawn(async move {
                         let _ = await_sdk_call(
-                            booster_client.move_robot(step.forward, step.left, step.turn),
+                            services.move_robot(step.forward, step.left, step.turn),
                             timeout,
                             "send move_robot",
                             attempt,
@@ -476,10 +532,10 @@ async fn run(ctx: Arc<Context>) -> Result<()> {
                         in_flight = attempt.in_flight_at_start,
                         "booster rpc scheduled"
                     );
-                    let booster_client = booster_client.clone();
+                    let services = services.clone();
                     tokio::spawn(async move {
                         let _ = await_sdk_call(
-                            booster_client.rotate_head(head_joints.pitch, head_joints.yaw),
+                            services.rotate_head(head_joints.pitch, head_joints.yaw),
                             timeout,
                             "rotate head",
                             attempt,
@@ -495,18 +551,39 @@ async fn run(ctx: Arc<Context>) -> Result<()> {
     }
 }
 
+async fn request_mode(
+    services: Arc<impl BoosterServices>,
+    mode: booster_sdk::types::RobotMode,
+    timeout: Duration,
+    attempt: RpcAttempt,
+    failed_mode_change_sender: UnboundedSender<FailedModeChange>,
+    failed_mode_change: FailedModeChange,
+) {
+    let result = await_sdk_call(
+        services.change_mode(mode),
+        timeout,
+        format!("request booster mode {mode:?}"),
+        attempt,
+    )
+    .await;
+    if result.is_none() {
+        info!(target: "booster_interface::rpc", ?mode, "retrying failed mode change");
+        let _ = failed_mode_change_sender.send(failed_mode_change);
+    }
+}
+
 async fn handle_led_command(
-    light_control_client: Arc<LightControlClient>,
+    services: Arc<impl BoosterServices>,
     led_command: LedCommand,
 ) -> Result<()> {
     match led_command {
         LedCommand::SetParam { r, g, b } => {
-            if let Err(err) = light_control_client.set_led_light_color(r, g, b).await {
+            if let Err(err) = services.set_led_color(r, g, b).await {
                 error!(target: "booster_interface::led", error = %err, "failed to set leds");
             }
         }
         LedCommand::Stop => {
-            if let Err(err) = light_control_client.stop_led_light_control().await {
+            if let Err(err) = services.stop_led_control().await {
                 error!(target: "booster_interface::led", error = %err, "failed to stop led control");
             }
         }
@@ -520,6 +597,7 @@ fn sdk_mode_for(desired_mode: control::DesiredMode) -> booster_sdk::types::Robot
         control::DesiredMode::Damping => booster_sdk::types::RobotMode::Damping,
         control::DesiredMode::Prepare => booster_sdk::types::RobotMode::Prepare,
         control::DesiredMode::Soccer => booster_sdk::types::RobotMode::Soccer,
+        control::DesiredMode::Custom => booster_sdk::types::RobotMode::Custom,
     }
 }
 
@@ -664,5 +742,26 @@ mod tests {
         .await;
 
         assert!(result.is_none());
+        assert_eq!(diagnostics.timed_out(), 1);
+        assert_eq!(diagnostics.failed(), 0);
+        assert_eq!(diagnostics.in_flight(RpcActionKind::GetUp), 0);
+    }
+
+    #[tokio::test]
+    async fn sdk_call_counts_failures() {
+        let diagnostics = std::sync::Arc::new(RpcDiagnostics::default());
+        let attempt = diagnostics.begin(RpcActionKind::ChangeMode);
+        let result = await_sdk_call(
+            std::future::ready(std::result::Result::<(), _>::Err("rejected")),
+            Duration::from_millis(100),
+            "failing test operation",
+            attempt,
+        )
+        .await;
+
+        assert!(result.is_none());
+        assert_eq!(diagnostics.started(), 1);
+        assert_eq!(diagnostics.failed(), 1);
+        assert_eq!(diagnostics.timed_out(), 0);
     }
 }
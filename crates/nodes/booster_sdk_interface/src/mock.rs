@@ -0,0 +1,308 @@
+//! Local stand-in for the Booster SDK services to run the interface without a robot.
+//!
+//! The mock answers requests after a configurable latency, fails or stalls requests on demand and
+//! publishes robot state on the topics the bridges read from the robot.
+
+use std::{
+    collections::VecDeque,
+    future::pending,
+    sync::{Arc, Mutex, MutexGuard},
+    time::Duration,
+};
+
+use booster::{
+    CommandType, FallDownState, FallDownStateType, ImuState, Kick, LowState, MotorState, Odometer,
+};
+use booster_sdk::types::RobotMode;
+use cdr::{CdrLe, Infinite};
+use color_eyre::{
+    Result,
+    eyre::{WrapErr, bail, eyre},
+};
+use linear_algebra::vector;
+use tokio::time::{interval, sleep};
+
+use crate::services::BoosterServices;
+
+const LOW_STATE_TOPIC: &str = "rt/low_state";
+const ODOMETER_TOPIC: &str = "rt/odometer_state";
+const FALL_DOWN_TOPIC: &str = "rt/fall_down";
+const NUMBER_OF_JOINTS: usize = 22;
+const GRAVITY: f32 = 9.81;
+
+#[derive(Clone, Debug)]
+pub enum Call {
+    ChangeMode(RobotMode),
+    GetUp,
+    MoveRobot { forward: f32, left: f32, turn: f32 },
+    RotateHead { pitch: f32, yaw: f32 },
+    VisualKick(bool),
+    PublishKick(Kick),
+    SetLedColor { r: u8, g: u8, b: u8 },
+    StopLedControl,
+}
+
+impl Call {
+    pub fn service(&self) -> Service {
+        match self {
+            Call::ChangeMode(_) => Service::ChangeMode,
+            Call::GetUp => Service::GetUp,
+            Call::MoveRobot { .. } => Service::MoveRobot,
+            Call::RotateHead { .. } => Service::RotateHead,
+            Call::VisualKick(_) => Service::VisualKick,
+            Call::PublishKick(_) => Service::PublishKick,
+            Call::SetLedColor { .. } | Call::StopLedControl => Service::LightControl,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Service {
+    ChangeMode,
+    GetUp,
+    MoveRobot,
+    RotateHead,
+    VisualKick,
+    PublishKick,
+    LightControl,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Fault {
+    /// The service answers with an error
+    Fail,
+    /// The service never answers
+    Stall,
+}
+
+struct MockState {
+    calls: Vec<Call>,
+    faults: VecDeque<(Service, Fault)>,
+    mode: RobotMode,
+    fall_down_state: FallDownStateType,
+    step: (f32, f32, f32),
+    odometer: Odometer,
+}
+
+impl MockState {
+    fn take_fault(&mut self, service: Service) -> Option<Fault> {
+        let index = self
+            .faults
+            .iter()
+            .position(|(faulty_service, _)| *faulty_service == service)?;
+        self.faults.remove(index).map(|(_, fault)| fault)
+    }
+
+    fn apply(&mut self, call: &Call) -> Result<()> {
+        match *call {
+            Call::ChangeMode(mode) => {
+                self.mode = mode;
+                self.step = (0.0, 0.0, 0.0);
+            }
+            Call::GetUp => {
+                if self.mode != RobotMode::Prepare {
+                    bail!("get_up requires prepare mode, robot is in {:?}", self.mode);
+                }
+                self.fall_down_state = FallDownStateType::IsReady;
+            }
+            Call::MoveRobot {
+                forward,
+                left,
+                turn,
+            } => {
+                if self.mode != RobotMode::Soccer {
+                    bail!(
+                        "move_robot requires soccer mode, robot is in {:?}",
+                        self.mode
+                    );
+                }
+                self.step = (forward, left, turn);
+            }
+            Call::RotateHead { .. }
+            | Call::VisualKick(_)
+            | Call::PublishKick(_)
+            | Call::SetLedColor { .. }
+            | Call::StopLedControl => {}
+        }
+        Ok(())
+    }
+
+    fn integrate_odometer(&mut self, elapsed: Duration) {
+        let (forward, left, turn) = self.step;
+        let seconds = elapsed.as_secs_f32();
+        let (sin, cos) = self.odometer.theta.sin_cos();
+        self.odometer.x += (forward * cos - left * sin) * seconds;
+        self.odometer.y += (forward * sin + left * cos) * seconds;
+        self.odometer.theta += turn * seconds;
+    }
+
+    fn low_state(&self) -> LowState {
+        let motor_state = |command_type| MotorState {
+            command_type,
+            temperature: 40,
+            ..Default::default()
+        };
+        LowState {
+            imu_state: ImuState {
+                linear_acceleration: vector![0.0, 0.0, GRAVITY],
+                ..Default::default()
+            },
+            motor_state_parallel: vec![motor_state(CommandType::Parallel); NUMBER_OF_JOINTS],
+            motor_state_serial: vec![motor_state(CommandType::Serial); NUMBER_OF_JOINTS],
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct MockServices {
+    latency: Duration,
+    state: Arc<Mutex<MockState>>,
+}
+
+impl MockServices {
+    pub fn new(latency: Duration) -> Self {
+        Self {
+            latency,
+            state: Arc::new(Mutex::new(MockState {
+                calls: Vec::new(),
+                faults: VecDeque::new(),
+                mode: RobotMode::Damping,
+                fall_down_state: FallDownStateType::IsReady,
+                step: (0.0, 0.0, 0.0),
+                odometer: Odometer::default(),
+            })),
+        }
+    }
+
+    /// Lets the next call of `service` run into `fault`, faults of a service are used up in
+    /// the order they were injected.
+    pub fn inject_fault(&self, service: Service, fault: Fault) {
+        self.state().faults.push_back((service, fault));
+    }
+
+    /// All calls received so far, including failed and stalled ones
+    pub fn calls(&self) -> Vec<Call> {
+        self.state().calls.clone()
+    }
+
+    pub fn mode(&self) -> RobotMode {
+        self.state().mode
+    }
+
+    pub fn odometer(&self) -> Odometer {
+        self.state().odometer
+    }
+
+    pub fn fall_down(&self) {
+        let mut state = self.state();
+        state.fall_down_state = FallDownStateType::HasFallen;
+        state.step = (0.0, 0.0, 0.0);
+    }
+
+    /// Publishes low state, odometer and fall down state every `period`, never returns unless
+    /// publishing fails.
+    pub async fn publish_robot_state(
+        &self,
+        session: &zenoh::Session,
+        period: Duration,
+    ) -> Result<()> {
+        let declare_publisher = async |topic: &'static str| {
+            session
+                .declare_publisher(topic)
+                .await
+                .map_err(|error| eyre!(error).wrap_err(format!("failed to declare `{topic}`")))
+        };
+        let low_state_publisher = declare_publisher(LOW_STATE_TOPIC).await?;
+        let odometer_publisher = declare_publisher(ODOMETER_TOPIC).await?;
+        let fall_down_publisher = declare_publisher(FALL_DOWN_TOPIC).await?;
+
+        let mut ticks = interval(period);
+        loop {
+            ticks.tick().await;
+            let (low_state, odometer, fall_down_state) = {
+                let mut state = self.state();
+                state.integrate_odometer(period);
+                let fall_down_state = FallDownState {
+                    fall_down_state: state.fall_down_state,
+                    is_recovery_available: state.fall_down_state == FallDownStateType::HasFallen,
+                };
+                (state.low_state(), state.odometer, fall_down_state)
+            };
+            for (publisher, payload) in [
+                (&low_state_publisher, serialize(&low_state)?),
+                (&odometer_publisher, serialize(&odometer)?),
+                (&fall_down_publisher, serialize(&fall_down_state)?),
+            ] {
+                publisher
+                    .put(payload)
+                    .await
+                    .map_err(|error| eyre!(error).wrap_err("failed to publish robot state"))?;
+            }
+        }
+    }
+
+    async fn handle(&self, call: Call) -> Result<()> {
+        sleep(self.latency).await;
+        let fault = {
+            let mut state = self.state();
+            state.calls.push(call.clone());
+            state.take_fault(call.service())
+        };
+        match fault {
+            Some(Fault::Fail) => bail!("injected failure of {call:?}"),
+            Some(Fault::Stall) => pending().await,
+            None => self.state().apply(&call),
+        }
+    }
+
+    fn state(&self) -> MutexGuard<'_, MockState> {
+        self.state.lock().unwrap()
+    }
+}
+
+fn serialize(message: &impl serde::Serialize) -> Result<Vec<u8>> {
+    cdr::serialize::<_, _, CdrLe>(message, Infinite).wrap_err("failed to serialize robot state")
+}
+
+impl BoosterServices for MockServices {
+    fn startup_wait(&self) -> Duration {
+        Duration::ZERO
+    }
+
+    async fn change_mode(&self, mode: RobotMode) -> Result<()> {
+        self.handle(Call::ChangeMode(mode)).await
+    }
+
+    async fn get_up(&self) -> Result<()> {
+        self.handle(Call::GetUp).await
+    }
+
+    async fn move_robot(&self, forward: f32, left: f32, turn: f32) -> Result<()> {
+        self.handle(Call::MoveRobot {
+            forward,
+            left,
+            turn,
+        })
+        .await
+    }
+
+    async fn rotate_head(&self, pitch: f32, yaw: f32) -> Result<()> {
+        self.handle(Call::RotateHead { pitch, yaw }).await
+    }
+
+    async fn visual_kick(&self, enabled: bool) -> Result<()> {
+        self.handle(Call::VisualKick(enabled)).await
+    }
+
+    async fn publish_kick(&self, kick: Kick) -> Result<()> {
+        self.handle(Call::PublishKick(kick)).await
+    }
+
+    async fn set_led_color(&self, r: u8, g: u8, b: u8) -> Result<()> {
+        self.handle(Call::SetLedColor { r, g, b }).await
+    }
+
+    async fn stop_led_control(&self) -> Result<()> {
+        self.handle(Call::StopLedControl).await
+    }
+}
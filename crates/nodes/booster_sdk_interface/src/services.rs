@@ -0,0 +1,123 @@
+use std::{future::Future, time::Duration};
+
+use booster::Kick;
+use booster_sdk::{
+    client::{BoosterClient, light_control::LightControlClient},
+    types::RobotMode,
+};
+use color_eyre::{
+    Result,
+    eyre::{WrapErr, eyre},
+};
+
+use crate::kick_transport::KickBallPublisher;
+
+/// Services of the robot the interface sends its requests to.
+///
+/// On the robot these are the Booster SDK RPCs, in tests the `MockServices` of the `mock` feature.
+pub trait BoosterServices: Send + Sync + 'static {
+    /// Time the services need after construction until requests take effect
+    fn startup_wait(&self) -> Duration;
+    fn change_mode(&self, mode: RobotMode) -> impl Future<Output = Result<()>> + Send;
+    fn get_up(&self) -> impl Future<Output = Result<()>> + Send;
+    fn move_robot(
+        &self,
+        forward: f32,
+        left: f32,
+        turn: f32,
+    ) -> impl Future<Output = Result<()>> + Send;
+    fn rotate_head(&self, pitch: f32, yaw: f32) -> impl Future<Output = Result<()>> + Send;
+    fn visual_kick(&self, enabled: bool) -> impl Future<Output = Result<()>> + Send;
+    fn publish_kick(&self, kick: Kick) -> impl Future<Output = Result<()>> + Send;
+    fn set_led_color(&self, r: u8, g: u8, b: u8) -> impl Future<Output = Result<()>> + Send;
+    fn stop_led_control(&self) -> impl Future<Output = Result<()>> + Send;
+}
+
+pub struct SdkServices {
+    booster_client: BoosterClient,
+    light_control_client: LightControlClient,
+    kick_ball_publisher: KickBallPublisher,
+}
+
+impl SdkServices {
+    pub async fn new(session: &zenoh::Session) -> Result<Self> {
+        let light_control_client =
+            LightControlClient::new().wrap_err("failed to create LightControlClient")?;
+        let booster_client = BoosterClient::with_options(crate::booster_rpc_options())
+            .wrap_err("failed to create BoosterClient")?;
+        let kick_ball_publisher = KickBallPublisher::new(session)
+            .await
+            .wrap_err("failed to create kick ball publisher")?;
+        Ok(Self {
+            booster_client,
+            light_control_client,
+            kick_ball_publisher,
+        })
+    }
+}
+
+impl BoosterServices for SdkServices {
+    fn startup_wait(&self) -> Duration {
+        crate::booster_effect_startup_wait()
+    }
+
+    async fn change_mode(&self, mode: RobotMode) -> Result<()> {
+        self.booster_client
+            .change_mode(mode)
+            .await
+            .map(|_| ())
+            .map_err(|error| eyre!("{error}"))
+    }
+
+    async fn get_up(&self) -> Result<()> {
+        self.booster_client
+            .get_up()
+            .await
+            .map(|_| ())
+            .map_err(|error| eyre!("{error}"))
+    }
+
+    async fn move_robot(&self, forward: f32, left: f32, turn: f32) -> Result<()> {
+        self.booster_client
+            .move_robot(forward, left, turn)
+            .await
+            .map(|_| ())
+            .map_err(|error| eyre!("{error}"))
+    }
+
+    async fn rotate_head(&self, pitch: f32, yaw: f32) -> Result<()> {
+        self.booster_client
+            .rotate_head(pitch, yaw)
+            .await
+            .map(|_| ())
+            .map_err(|error| eyre!("{error}"))
+    }
+
+    async fn visual_kick(&self, enabled: bool) -> Result<()> {
+        self.booster_client
+            .visual_kick(enabled)
+            .await
+            .map(|_| ())
+            .map_err(|error| eyre!("{error}"))
+    }
+
+    async fn publish_kick(&self, kick: Kick) -> Result<()> {
+        self.kick_ball_publisher.publish(&kick).await
+    }
+
+    async fn set_led_color(&self, r: u8, g: u8, b: u8) -> Result<()> {
+        self.light_control_client
+            .set_led_light_color(r, g, b)
+            .await
+            .map(|_| ())
+            .map_err(|error| eyre!("{error}"))
+    }
+
+    async fn stop_led_control(&self) -> Result<()> {
+        self.light_control_client
+            .stop_led_light_control()
+            .await
+            .map(|_| ())
+            .map_err(|error| eyre!("{error}"))
+    }
+}
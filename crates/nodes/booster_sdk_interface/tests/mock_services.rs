@@ -0,0 +1,360 @@
+use std::{sync::Arc, time::Duration};
+
+use booster::{FallDownState, FallDownStateType, ImuState, Odometer};
+use booster_sdk::types::RobotMode;
+use booster_sdk_interface::{
+    LedCommand, RpcDiagnostics,
+    mock::{Call, Fault, MockServices, Service},
+    run_with_services,
+};
+use linear_algebra::{Orientation2, point, vector};
+use ros_z::prelude::*;
+use tokio::time::{sleep, timeout};
+use types::motion_command::{HeadMotion, KickPower, MotionCommand};
+
+const PARAMETER_LAYER: &str = concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/../../../etc/parameters/ros_z/base"
+);
+
+async fn start_interface(services: &MockServices) -> (Arc<Context>, Arc<RpcDiagnostics>) {
+    let context = ContextBuilder::default()
+        .with_mode("peer")
+        .disable_multicast_scouting()
+        .with_parameter_layer(PARAMETER_LAYER)
+        .build()
+        .await
+        .expect("context should build");
+    let context = Arc::new(context);
+    let rpc_diagnostics = Arc::new(RpcDiagnostics::default());
+    tokio::spawn(run_with_services(
+        context.clone(),
+        services.clone(),
+        rpc_diagnostics.clone(),
+    ));
+    (context, rpc_diagnostics)
+}
+
+/// Publishes the robot state of the mock and runs the bridges forwarding it to the other nodes.
+fn start_robot_state(services: &MockServices, context: &Arc<Context>) {
+    {
+        let services = services.clone();
+        let context = context.clone();
+        tokio::spawn(async move {
+            services
+                .publish_robot_state(context.session(), Duration::from_millis(10))
+                .await
+        });
+    }
+    tokio::spawn(fall_down_state_receiver::run_boxed(context.clone()));
+    tokio::spawn(low_state_bridge::run_boxed(context.clone()));
+    tokio::spawn(odometer_bridge::run_boxed(context.clone()));
+}
+
+async fn motion_command_publisher(context: &Context) -> Publisher<MotionCommand> {
+    test_node(context)
+        .await
+        .publisher::<MotionCommand>("behavior/motion_command")
+        .build()
+        .await
+        .unwrap()
+}
+
+async fn led_command_publisher(context: &Context) -> Publisher<LedCommand> {
+    test_node(context)
+        .await
+        .publisher::<LedCommand>("commands/led_command")
+        .build()
+        .await
+        .unwrap()
+}
+
+async fn test_node(context: &Context) -> Node {
+    context
+        .create_node("mock_services_test")
+        .build()
+        .await
+        .unwrap()
+}
+
+/// Keeps publishing via `publish` until the mock satisfies `condition`, late subscribers of the
+/// interface would miss a single message.
+async fn publish_until(
+    publish: impl AsyncFn(),
+    services: &MockServices,
+    condition: impl Fn(&MockServices) -> bool,
+) {
+    timeout(Duration::from_secs(5), async {
+        while !condition(services) {
+            publish().await;
+            sleep(Duration::from_millis(20)).await;
+        }
+    })
+    .await
+    .expect("mock did not reach the expected state");
+}
+
+/// Receives via `receive` until a message satisfies `condition`.
+async fn receive_until<T>(receive: impl AsyncFn() -> T, condition: impl Fn(&T) -> bool) -> T {
+    timeout(Duration::from_secs(5), async {
+        loop {
+            let message = receive().await;
+            if condition(&message) {
+                break message;
+            }
+        }
+    })
+    .await
+    .expect("no matching message received")
+}
+
+fn walk() -> MotionCommand {
+    MotionCommand::WalkWithVelocity {
+        head: HeadMotion::ZeroAngles,
+        velocity: vector![0.3, 0.0],
+        angular_velocity: 0.0,
+    }
+}
+
+fn visual_kick() -> MotionCommand {
+    MotionCommand::VisualKick {
+        head: HeadMotion::ZeroAngles,
+        ball_position: point![0.2, 0.0],
+        kick_direction: Orientation2::identity(),
+        target_position: point![4.0, 0.0],
+        robot_theta_to_field: Orientation2::identity(),
+        kick_power: KickPower::Schlong,
+    }
+}
+
+fn has_call(services: &MockServices, predicate: impl Fn(&Call) -> bool) -> bool {
+    services.calls().iter().any(predicate)
+}
+
+fn count_calls(services: &MockServices, predicate: impl Fn(&Call) -> bool) -> usize {
+    services
+        .calls()
+        .iter()
+        .filter(|call| predicate(call))
+        .count()
+}
+
+fn mode_changes(services: &MockServices, mode: RobotMode) -> usize {
+    count_calls(
+        services,
+        |call| matches!(call, Call::ChangeMode(requested) if *requested == mode),
+    )
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn walking_switches_to_soccer_mode_and_moves() {
+    let services = MockServices::new(Duration::from_millis(5));
+    let (context, rpc_diagnostics) = start_interface(&services).await;
+    let publisher = motion_command_publisher(&context).await;
+
+    publish_until(
+        async || publisher.publish(&walk()).await.unwrap(),
+        &services,
+        |services| {
+            has_call(
+                services,
+                |call| matches!(call, Call::MoveRobot { forward, .. } if *forward == 0.3),
+            )
+        },
+    )
+    .await;
+
+    assert_eq!(services.mode(), RobotMode::Soccer);
+    assert_eq!(mode_changes(&services, RobotMode::Soccer), 1);
+    assert!(rpc_diagnostics.started() >= 2);
+    assert_eq!(rpc_diagnostics.failed(), 0);
+    assert_eq!(rpc_diagnostics.timed_out(), 0);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn failed_mode_changes_are_retried() {
+    let services = MockServices::new(Duration::from_millis(5));
+    services.inject_fault(Service::ChangeMode, Fault::Fail);
+    let (context, rpc_diagnostics) = start_interface(&services).await;
+    let publisher = motion_command_publisher(&context).await;
+
+    publish_until(
+        async || publisher.publish(&MotionCommand::Prepare).await.unwrap(),
+        &services,
+        |services| services.mode() == RobotMode::Prepare,
+    )
+    .await;
+
+    assert_eq!(mode_changes(&services, RobotMode::Prepare), 2);
+    assert_eq!(rpc_diagnostics.failed(), 1);
+    assert_eq!(rpc_diagnostics.timed_out(), 0);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn timed_out_mode_changes_are_retried() {
+    let services = MockServices::new(Duration::from_millis(5));
+    services.inject_fault(Service::ChangeMode, Fault::Stall);
+    let (context, rpc_diagnostics) = start_interface(&services).await;
+    let publisher = motion_command_publisher(&context).await;
+
+    publish_until(
+        async || publisher.publish(&MotionCommand::Prepare).await.unwrap(),
+        &services,
+        |services| services.mode() == RobotMode::Prepare,
+    )
+    .await;
+
+    assert_eq!(mode_changes(&services, RobotMode::Prepare), 2);
+    assert_eq!(rpc_diagnostics.timed_out(), 1);
+    assert_eq!(rpc_diagnostics.failed(), 0);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn led_commands_reach_the_light_control() {
+    let services = MockServices::new(Duration::from_millis(5));
+    let (context, _) = start_interface(&services).await;
+    let publisher = led_command_publisher(&context).await;
+    let command = LedCommand::SetParam { r: 255, g: 0, b: 0 };
+
+    publish_until(
+        async || publisher.publish(&command).await.unwrap(),
+        &services,
+        |services| {
+            has_call(services, |call| {
+                matches!(call, Call::SetLedColor { r: 255, g: 0, b: 0 })
+            })
+        },
+    )
+    .await;
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn visual_kicks_are_started_published_and_stopped() {
+    let services = MockServices::new(Duration::from_millis(5));
+    let (context, rpc_diagnostics) = start_interface(&services).await;
+    let publisher = motion_command_publisher(&context).await;
+
+    publish_until(
+        async || publisher.publish(&visual_kick()).await.unwrap(),
+        &services,
+        |services| {
+            has_call(services, |call| matches!(call, Call::VisualKick(true)))
+                && has_call(
+                    services,
+                    |call| matches!(call, Call::PublishKick(kick) if kick.kick_power == 6.0),
+                )
+        },
+    )
+    .await;
+    publish_until(
+        async || publisher.publish(&walk()).await.unwrap(),
+        &services,
+        |services| has_call(services, |call| matches!(call, Call::VisualKick(false))),
+    )
+    .await;
+
+    assert_eq!(services.mode(), RobotMode::Soccer);
+    assert_eq!(
+        count_calls(&services, |call| matches!(call, Call::VisualKick(true))),
+        1
+    );
+    assert_eq!(rpc_diagnostics.failed(), 0);
+    assert_eq!(rpc_diagnostics.timed_out(), 0);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn fallen_robot_gets_up_after_preparing() {
+    let services = MockServices::new(Duration::from_millis(5));
+    let (context, rpc_diagnostics) = start_interface(&services).await;
+    start_robot_state(&services, &context);
+    let fall_down_state_subscriber = test_node(&context)
+        .await
+        .subscriber::<FallDownState>("inputs/fall_down_state")
+        .build()
+        .await
+        .unwrap();
+    let publisher = motion_command_publisher(&context).await;
+
+    services.fall_down();
+    let fall_down_state = receive_until(
+        async || fall_down_state_subscriber.recv().await.unwrap(),
+        |fall_down_state| fall_down_state.fall_down_state == FallDownStateType::HasFallen,
+    )
+    .await;
+    assert!(fall_down_state.is_recovery_available);
+
+    publish_until(
+        async || publisher.publish(&MotionCommand::Prepare).await.unwrap(),
+        &services,
+        |services| services.mode() == RobotMode::Prepare,
+    )
+    .await;
+    publish_until(
+        async || publisher.publish(&MotionCommand::StandUp).await.unwrap(),
+        &services,
+        |services| has_call(services, |call| matches!(call, Call::GetUp)),
+    )
+    .await;
+    let fall_down_state = receive_until(
+        async || fall_down_state_subscriber.recv().await.unwrap(),
+        |fall_down_state| fall_down_state.fall_down_state == FallDownStateType::IsReady,
+    )
+    .await;
+
+    assert!(!fall_down_state.is_recovery_available);
+    assert_eq!(
+        count_calls(&services, |call| matches!(call, Call::GetUp)),
+        1
+    );
+    assert_eq!(rpc_diagnostics.failed(), 0);
+    assert_eq!(rpc_diagnostics.timed_out(), 0);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn bridged_low_state_carries_the_imu_of_the_mock() {
+    let services = MockServices::new(Duration::from_millis(5));
+    let (context, _) = start_interface(&services).await;
+    start_robot_state(&services, &context);
+    let imu_state_subscriber = test_node(&context)
+        .await
+        .subscriber::<ImuState>("inputs/imu_state")
+        .build()
+        .await
+        .unwrap();
+
+    let imu_state = receive_until(
+        async || imu_state_subscriber.recv().await.unwrap(),
+        |_| true,
+    )
+    .await;
+
+    assert!((imu_state.linear_acceleration.z() - 9.81).abs() < 1e-3);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn bridged_odometer_follows_the_commanded_walk() {
+    let services = MockServices::new(Duration::from_millis(5));
+    let (context, _) = start_interface(&services).await;
+    start_robot_state(&services, &context);
+    let odometer_subscriber = test_node(&context)
+        .await
+        .subscriber::<Odometer>("inputs/odometer")
+        .build()
+        .await
+        .unwrap();
+    let publisher = motion_command_publisher(&context).await;
+
+    publish_until(
+        async || publisher.publish(&walk()).await.unwrap(),
+        &services,
+        |services| services.odometer().x > 0.05,
+    )
+    .await;
+    let odometer = receive_until(
+        async || odometer_subscriber.recv().await.unwrap(),
+        |odometer| odometer.x > 0.05,
+    )
+    .await;
+
+    assert!(odometer.y.abs() < 1e-3);
+}
@@ -0,0 +1,117 @@
+use calibration::measurement::Measurement;
+use kinematics::joints::head::HeadJoints;
+use ros_z::time::Time;
+use types::calibration::CameraCalibrationState;
+
+use crate::Parameters;
+
+/// Moves the head through all head angles and collects measurements once the head settled at
+/// each of them.
+pub struct Capture {
+    head_angle_index: usize,
+    head_angle_start: Time,
+    measurements: Vec<Measurement>,
+}
+
+impl Capture {
+    pub fn new(now: Time) -> Self {
+        Self {
+            head_angle_index: 0,
+            head_angle_start: now,
+            measurements: Vec::new(),
+        }
+    }
+
+    pub fn head_joints(&self, parameters: &Parameters) -> Option<HeadJoints<f32>> {
+        parameters.head_angles.get(self.head_angle_index).copied()
+    }
+
+    pub fn is_collecting(&self, now: Time, parameters: &Parameters) -> bool {
+        now.duration_since(self.head_angle_start) >= parameters.settle_duration
+    }
+
+    pub fn add_measurements(&mut self, measurements: impl IntoIterator<Item = Measurement>) {
+        self.measurements.extend(measurements);
+    }
+
+    /// Advances to the next head angle once the capture duration elapsed, returns whether all
+    /// head angles are done.
+    pub fn update(&mut self, now: Time, parameters: &Parameters) -> bool {
+        if now.duration_since(self.head_angle_start)
+            >= parameters.settle_duration + parameters.capture_duration
+        {
+            self.head_angle_index += 1;
+            self.head_angle_start = now;
+        }
+        self.head_angle_index >= parameters.head_angles.len()
+    }
+
+    pub fn state(&self, parameters: &Parameters) -> CameraCalibrationState {
+        CameraCalibrationState::Capturing {
+            head_angle_index: self.head_angle_index,
+            number_of_head_angles: parameters.head_angles.len(),
+            number_of_measurements: self.measurements.len(),
+        }
+    }
+
+    pub fn into_measurements(self) -> Vec<Measurement> {
+        self.measurements
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, UNIX_EPOCH};
+
+    use linear_algebra::point;
+
+    use super::*;
+
+    fn time(seconds: f32) -> Time {
+        Time::from_wallclock(UNIX_EPOCH + Duration::from_secs_f32(seconds))
+    }
+
+    #[test]
+    fn head_angles_are_visited_one_after_another() {
+        let parameters = Parameters {
+            robot_position: point![-1.5, 0.0],
+            robot_orientation: 0.0,
+            head_angles: vec![
+                HeadJoints {
+                    yaw: 0.5,
+                    pitch: 0.3,
+                },
+                HeadJoints {
+                    yaw: -0.5,
+                    pitch: 0.3,
+                },
+            ],
+            settle_duration: Duration::from_secs(1),
+            capture_duration: Duration::from_secs(2),
+            minimum_number_of_measurements: 1,
+            maximum_line_angle_deviation: 0.2,
+            maximum_line_distance: 0.3,
+            maximum_circle_point_distance: 0.1,
+            minimum_number_of_circle_points: 4,
+        };
+        let mut capture = Capture::new(time(0.0));
+
+        assert!(!capture.update(time(0.5), &parameters));
+        assert!(!capture.is_collecting(time(0.5), &parameters));
+        assert_eq!(
+            capture.head_joints(&parameters),
+            Some(parameters.head_angles[0])
+        );
+        assert!(capture.is_collecting(time(1.5), &parameters));
+
+        assert!(!capture.update(time(3.0), &parameters));
+        assert_eq!(
+            capture.head_joints(&parameters),
+            Some(parameters.head_angles[1])
+        );
+        assert!(!capture.is_collecting(time(3.5), &parameters));
+
+        assert!(capture.update(time(6.0), &parameters));
+        assert_eq!(capture.head_joints(&parameters), None);
+    }
+}
@@ -0,0 +1,284 @@
+use std::{boxed::Box, future::Future, pin::Pin, sync::Arc, time::Duration};
+
+use color_eyre::{
+    Result,
+    eyre::{WrapErr, bail, eyre},
+};
+use serde::{Deserialize, Serialize};
+use tracing::error;
+
+use calibration::{
+    corrections::Corrections,
+    measurement::{Measurement, Residuals},
+};
+use coordinate_systems::{Field, Ground};
+use kinematics::joints::head::HeadJoints;
+use linear_algebra::{Isometry2, Point2, Vector3, vector};
+use projection::camera_matrix::CameraMatrix;
+use ros_z::{
+    parameter::{NodeParameterWriteJson, RemoteParameterClient},
+    prelude::*,
+    qos::QosDurability,
+    topic_name::qualify_topic_name,
+};
+use types::{
+    calibration::{CameraCalibrationCommand, CameraCalibrationState},
+    field_dimensions::FieldDimensions,
+    line_data::LineData,
+    parameters::CameraMatrixParameters,
+    time_wrapper::TimeWrapper,
+};
+
+use crate::{capture::Capture, measurements::collect_measurements};
+
+pub mod capture;
+pub mod measurements;
+
+const CAMERA_MATRIX_CALCULATOR_NODE: &str = "camera_matrix_calculator";
+
+#[derive(Debug, Clone, Serialize, Deserialize, Message)]
+#[serde(deny_unknown_fields)]
+pub struct Parameters {
+    /// Known position of the robot on the field while calibrating
+    pub robot_position: Point2<Field>,
+    pub robot_orientation: f32,
+    pub head_angles: Vec<HeadJoints<f32>>,
+    /// Time for the head to reach a head angle before measurements are collected
+    pub settle_duration: Duration,
+    pub capture_duration: Duration,
+    pub minimum_number_of_measurements: usize,
+    pub maximum_line_angle_deviation: f32,
+    pub maximum_line_distance: f32,
+    pub maximum_circle_point_distance: f32,
+    pub minimum_number_of_circle_points: usize,
+}
+
+pub fn run_boxed(ctx: Arc<Context>) -> Pin<Box<dyn Future<Output = Result<()>> + Send>> {
+    Box::pin(run(ctx))
+}
+
+async fn run(ctx: Arc<Context>) -> Result<()> {
+    let node = Arc::new(
+        ctx.create_node("camera_calibration")
+            .build()
+            .await
+            .wrap_err("failed to create camera_calibration node")?,
+    );
+    let parameters = node
+        .bind_parameter_as::<Parameters>("camera_calibration")
+        .wrap_err("failed to bind camera_calibration parameters")?;
+    let camera_matrix_parameters = RemoteParameterClient::new(
+        node.clone(),
+        qualify_topic_name(CAMERA_MATRIX_CALCULATOR_NODE, node.namespace(), node.name())?,
+    )?;
+
+    let command_sub = node
+        .subscriber::<CameraCalibrationCommand>("camera_calibration/command")
+        .build()
+        .await?;
+    let line_data_sub = node
+        .subscriber::<TimeWrapper<Option<LineData>>>("line_data")
+        .build()
+        .await?;
+    let camera_matrix_cache = node
+        .subscriber::<TimeWrapper<CameraMatrix>>("camera_matrix")
+        .cache(10)
+        .with_stamp(|w: &TimeWrapper<CameraMatrix>| w.time)
+        .build()
+        .await?;
+    let uncalibrated_camera_matrix_cache = node
+        .subscriber::<TimeWrapper<CameraMatrix>>("uncalibrated_camera_matrix")
+        .cache(10)
+        .with_stamp(|w: &TimeWrapper<CameraMatrix>| w.time)
+        .build()
+        .await?;
+    let field_dimensions_cache = node
+        .subscriber::<FieldDimensions>("field_dimensions")
+        .qos(QosProfile {
+            durability: QosDurability::TransientLocal,
+            ..Default::default()
+        })
+        .cache(1)
+        .build()
+        .await?;
+
+    let state_pub = node
+        .publisher::<CameraCalibrationState>("camera_calibration/state")
+        .build()
+        .await?;
+    let head_joints_pub = node
+        .publisher::<Option<HeadJoints<f32>>>("camera_calibration/head_joints")
+        .build()
+        .await?;
+
+    let mut capture: Option<Capture> = None;
+    let mut state = CameraCalibrationState::Idle;
+    loop {
+        let mut line_data = None;
+        tokio::select! {
+            command = command_sub.recv() => {
+                capture = match command? {
+                    CameraCalibrationCommand::Start => Some(Capture::new(node.clock().now())),
+                    CameraCalibrationCommand::Abort => None,
+                };
+                state = CameraCalibrationState::Idle;
+            }
+            item = line_data_sub.recv() => {
+                line_data = Some(item?);
+            }
+        }
+
+        let parameters_snapshot = parameters.snapshot();
+        let parameters = parameters_snapshot.typed();
+        let now = node.clock().now();
+        let field_dimensions = field_dimensions_cache.get_latest();
+
+        if let Some(capture) = &mut capture {
+            if capture.is_collecting(now, parameters) {
+                let measurements = line_data.zip(field_dimensions.as_deref()).and_then(
+                    |(TimeWrapper { time, inner }, field_dimensions)| {
+                        Some(collect_measurements(
+                            &inner?.lines,
+                            &camera_matrix_cache.get_nearest(time)?.inner,
+                            &uncalibrated_camera_matrix_cache.get_nearest(time)?.inner,
+                            ground_to_field(parameters),
+                            field_dimensions,
+                            parameters,
+                        ))
+                    },
+                );
+                capture.add_measurements(measurements.into_iter().flatten());
+            }
+            state = capture.state(parameters);
+        }
+
+        if let Some(finished_capture) = capture.take_if(|capture| capture.update(now, parameters)) {
+            let measurements = finished_capture.into_measurements();
+            let number_of_measurements = measurements.len();
+            let result = match field_dimensions.as_deref() {
+                Some(field_dimensions) => {
+                    calibrate(
+                        measurements,
+                        *field_dimensions,
+                        &camera_matrix_parameters,
+                        parameters,
+                    )
+                    .await
+                }
+                None => Err(eyre!("field dimensions are not available")),
+            };
+            state = result.unwrap_or_else(|error| {
+                error!(target: "camera_calibration", "failed to calibrate: {error:?}");
+                CameraCalibrationState::Failed {
+                    number_of_measurements,
+                }
+            });
+        }
+
+        head_joints_pub
+            .publish(
+                &capture
+                    .as_ref()
+                    .and_then(|capture| capture.head_joints(parameters)),
+            )
+            .await?;
+        state_pub.publish(&state).await?;
+    }
+}
+
+fn ground_to_field(parameters: &Parameters) -> Isometry2<Ground, Field> {
+    Isometry2::from_parts(
+        parameters.robot_position.coords(),
+        parameters.robot_orientation,
+    )
+}
+
+/// Solves for the corrections starting from the current ones and stores the result in the
+/// robot specific parameter layer of the camera matrix calculator.
+async fn calibrate(
+    measurements: Vec<Measurement>,
+    field_dimensions: FieldDimensions,
+    camera_matrix_parameters: &RemoteParameterClient,
+    parameters: &Parameters,
+) -> Result<CameraCalibrationState> {
+    let number_of_measurements = measurements.len();
+    if number_of_measurements < parameters.minimum_number_of_measurements {
+        return Ok(CameraCalibrationState::Failed {
+            number_of_measurements,
+        });
+    }
+
+    let snapshot = camera_matrix_parameters.get_snapshot().await?;
+    if !snapshot.success {
+        bail!(
+            "failed to get parameters of {}: {}",
+            camera_matrix_parameters.target_node_fqn(),
+            snapshot.message
+        );
+    }
+    let Some(robot_layer) = snapshot.layers.last() else {
+        bail!(
+            "{} has no parameter layers",
+            camera_matrix_parameters.target_node_fqn()
+        );
+    };
+    let current: CameraMatrixParameters = serde_json::from_str(&snapshot.value_json)
+        .wrap_err("failed to parse camera matrix parameters")?;
+
+    let initial_corrections = Corrections {
+        correction_in_robot: nalgebra::Rotation3::from_euler_angles(
+            current.correction_in_robot.x(),
+            current.correction_in_robot.y(),
+            current.correction_in_robot.z(),
+        ),
+        correction_in_camera: nalgebra::Rotation3::from_euler_angles(
+            current.correction_in_camera.x(),
+            current.correction_in_camera.y(),
+            current.correction_in_camera.z(),
+        ),
+    };
+    let Some(corrections) =
+        calibration::solve::<Residuals>(initial_corrections, measurements, field_dimensions)
+    else {
+        return Ok(CameraCalibrationState::Failed {
+            number_of_measurements,
+        });
+    };
+
+    let correction_in_robot = euler_angles(corrections.correction_in_robot);
+    let correction_in_camera = euler_angles(corrections.correction_in_camera);
+    let response = camera_matrix_parameters
+        .set_json_atomically(
+            vec![
+                NodeParameterWriteJson {
+                    path: "correction_in_robot".to_string(),
+                    value_json: serde_json::to_string(&correction_in_robot)?,
+                    target_layer: robot_layer.clone(),
+                },
+                NodeParameterWriteJson {
+                    path: "correction_in_camera".to_string(),
+                    value_json: serde_json::to_string(&correction_in_camera)?,
+                    target_layer: robot_layer.clone(),
+                },
+            ],
+            Some(snapshot.revision),
+        )
+        .await?;
+    if !response.success {
+        bail!(
+            "failed to store corrections in {}: {}",
+            camera_matrix_parameters.target_node_fqn(),
+            response.message
+        );
+    }
+
+    Ok(CameraCalibrationState::Finished {
+        correction_in_robot,
+        correction_in_camera,
+    })
+}
+
+fn euler_angles<Frame>(rotation: nalgebra::Rotation3<f32>) -> Vector3<Frame> {
+    let (roll, pitch, yaw) = rotation.euler_angles();
+    vector![roll, pitch, yaw]
+}
@@ -0,0 +1,272 @@
+use calibration::{
+    center_circle::{
+        circle_points::CenterCirclePoints, measurement::Measurement as CenterCircleMeasurement,
+    },
+    goal_and_penalty_box::{LineType, Measurement as LineMeasurement},
+    measurement::Measurement,
+};
+use coordinate_systems::{Field, Ground};
+use geometry::line_segment::LineSegment;
+use linear_algebra::{Isometry2, Point2, distance, point};
+use projection::{Projection, camera_matrix::CameraMatrix};
+use types::field_dimensions::{FieldDimensions, Half};
+
+use crate::Parameters;
+
+const LINE_TYPES: [LineType; 7] = [
+    LineType::Goal,
+    LineType::FrontGoalArea,
+    LineType::LeftGoalArea,
+    LineType::RightGoalArea,
+    LineType::FrontPenaltyArea,
+    LineType::LeftPenaltyArea,
+    LineType::RightPenaltyArea,
+];
+
+/// Builds calibration measurements from the lines detected in a single image.
+///
+/// The lines are projected back into the image with the `camera_matrix` they were detected with,
+/// while the measurements refer to the `uncalibrated_camera_matrix` the corrections are solved
+/// for.
+pub fn collect_measurements(
+    lines: &[LineSegment<Ground>],
+    camera_matrix: &CameraMatrix,
+    uncalibrated_camera_matrix: &CameraMatrix,
+    ground_to_field: Isometry2<Ground, Field>,
+    field_dimensions: &FieldDimensions,
+    parameters: &Parameters,
+) -> Vec<Measurement> {
+    let field_to_ground = ground_to_field.inverse();
+    let mut measurements: Vec<_> = lines
+        .iter()
+        .filter_map(|line| {
+            let line_type = associate_line(ground_to_field * *line, field_dimensions, parameters)?;
+            let line_segment = line
+                .try_map(|point| camera_matrix.ground_to_pixel(point))
+                .ok()?;
+            Some(Measurement::Line(LineMeasurement {
+                line_type,
+                line_segment,
+                camera_matrix: uncalibrated_camera_matrix.clone(),
+                field_to_ground,
+            }))
+        })
+        .collect();
+    measurements.extend(
+        center_circle_points(lines, field_to_ground, field_dimensions, parameters).and_then(
+            |circle_and_points| {
+                Some(Measurement::CenterCircle(CenterCircleMeasurement {
+                    matrix: uncalibrated_camera_matrix.clone(),
+                    circle_and_points: CenterCirclePoints {
+                        center: camera_matrix
+                            .ground_to_pixel(circle_and_points.center)
+                            .ok()?,
+                        points: circle_and_points
+                            .points
+                            .into_iter()
+                            .map(|point| camera_matrix.ground_to_pixel(point).ok())
+                            .collect::<Option<_>>()?,
+                    },
+                }))
+            },
+        ),
+    );
+    measurements
+}
+
+/// Finds the goal or penalty box line in the opponent half the detected line belongs to.
+fn associate_line(
+    line: LineSegment<Field>,
+    field_dimensions: &FieldDimensions,
+    parameters: &Parameters,
+) -> Option<LineType> {
+    LINE_TYPES
+        .into_iter()
+        .filter_map(|line_type| {
+            let expected_line = line_type.line_segment(field_dimensions, Half::Opponent);
+            let distance = expected_line
+                .signed_distance_to_point(line.0)
+                .abs()
+                .max(expected_line.signed_distance_to_point(line.1).abs());
+            let is_parallel = expected_line.signed_acute_angle(line).abs()
+                < parameters.maximum_line_angle_deviation;
+            let is_within_expected_line =
+                (0.0..=1.0).contains(&expected_line.projection_factor(line.center()));
+            (is_parallel && is_within_expected_line && distance < parameters.maximum_line_distance)
+                .then_some((line_type, distance))
+        })
+        .min_by(|(_, left), (_, right)| left.total_cmp(right))
+        .map(|(line_type, _)| line_type)
+}
+
+/// The center circle is detected as several short lines, their end points lie on the circle.
+fn center_circle_points(
+    lines: &[LineSegment<Ground>],
+    field_to_ground: Isometry2<Field, Ground>,
+    field_dimensions: &FieldDimensions,
+    parameters: &Parameters,
+) -> Option<CenterCirclePoints<Ground>> {
+    let expected_center = field_to_ground * Point2::origin();
+    let radius = field_dimensions.center_circle_diameter / 2.0;
+    let points: Vec<_> = lines
+        .iter()
+        .flat_map(|line| [line.0, line.1])
+        .filter(|point| {
+            (distance(*point, expected_center) - radius).abs()
+                < parameters.maximum_circle_point_distance
+        })
+        .collect();
+    if points.len() < parameters.minimum_number_of_circle_points {
+        return None;
+    }
+
+    Some(CenterCirclePoints {
+        center: fit_circle_center(&points)?,
+        points,
+    })
+}
+
+/// Algebraic least squares fit of a circle (Kåsa fit), returns `None` for collinear points.
+fn fit_circle_center(points: &[Point2<Ground>]) -> Option<Point2<Ground>> {
+    let mean = points
+        .iter()
+        .fold(nalgebra::Vector2::zeros(), |sum, point| {
+            sum + point.inner.coords
+        })
+        / points.len() as f32;
+
+    let mut normal_matrix = nalgebra::Matrix3::zeros();
+    let mut right_hand_side = nalgebra::Vector3::zeros();
+    for point in points {
+        let centered = point.inner.coords - mean;
+        let row = nalgebra::vector![centered.x, centered.y, 1.0];
+        normal_matrix += row * row.transpose();
+        right_hand_side -= row * centered.norm_squared();
+    }
+    let solution = normal_matrix.lu().solve(&right_hand_side)?;
+    if !solution.iter().all(|value| value.is_finite()) {
+        return None;
+    }
+
+    Some(point![mean.x - solution.x / 2.0, mean.y - solution.y / 2.0])
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{f32::consts::TAU, time::Duration};
+
+    use approx::assert_relative_eq;
+    use coordinate_systems::{Camera, Head};
+    use linear_algebra::{IntoTransform, Isometry3, vector};
+
+    use super::*;
+
+    fn parameters() -> Parameters {
+        Parameters {
+            robot_position: point![-1.5, 0.0],
+            robot_orientation: 0.0,
+            head_angles: Vec::new(),
+            settle_duration: Duration::from_secs(1),
+            capture_duration: Duration::from_secs(2),
+            minimum_number_of_measurements: 1,
+            maximum_line_angle_deviation: 0.2,
+            maximum_line_distance: 0.3,
+            maximum_circle_point_distance: 0.1,
+            minimum_number_of_circle_points: 4,
+        }
+    }
+
+    fn camera_matrix() -> CameraMatrix {
+        let head_to_camera: Isometry3<Head, Camera> =
+            (nalgebra::Isometry3::rotation(nalgebra::Vector3::x() * std::f32::consts::FRAC_PI_4)
+                * nalgebra::Isometry3::rotation(
+                    nalgebra::Vector3::y() * -std::f32::consts::FRAC_PI_2,
+                )
+                * nalgebra::Isometry3::rotation(
+                    nalgebra::Vector3::x() * std::f32::consts::FRAC_PI_2,
+                ))
+            .framed_transform();
+        CameraMatrix::from_normalized_focal_and_center(
+            nalgebra::vector![0.6, 0.8],
+            nalgebra::point![0.5, 0.5],
+            vector![640.0, 480.0],
+            Isometry3::from_translation(0.0, 0.0, -1.0),
+            Isometry3::identity(),
+            head_to_camera,
+        )
+    }
+
+    #[test]
+    fn lines_are_associated_with_the_closest_expected_line() {
+        let field_dimensions = FieldDimensions::SPL_2025;
+        let parameters = parameters();
+        let front_goal_area_x =
+            field_dimensions.length / 2.0 - field_dimensions.goal_box_area_length;
+
+        let associate = |line| associate_line(line, &field_dimensions, &parameters);
+        assert_eq!(
+            associate(LineSegment::new(
+                point![front_goal_area_x + 0.1, -0.5],
+                point![front_goal_area_x + 0.05, 0.8],
+            )),
+            Some(LineType::FrontGoalArea)
+        );
+        assert_eq!(
+            associate(LineSegment::new(
+                point![front_goal_area_x + 0.1, 1.5],
+                point![front_goal_area_x + 0.1, 2.5],
+            )),
+            None
+        );
+        assert_eq!(
+            associate(LineSegment::new(
+                point![front_goal_area_x + 0.1, 1.15],
+                point![field_dimensions.length / 2.0, 1.1],
+            )),
+            Some(LineType::LeftGoalArea)
+        );
+        assert_eq!(
+            associate(LineSegment::new(point![1.0, 0.5], point![1.0, -0.5])),
+            None
+        );
+    }
+
+    #[test]
+    fn center_circle_is_fitted_through_line_end_points() {
+        let field_dimensions = FieldDimensions::SPL_2025;
+        let parameters = parameters();
+        let field_to_ground = Isometry2::from_parts(vector![1.5, 0.0], 0.0);
+        let radius = field_dimensions.center_circle_diameter / 2.0;
+        let arc_points: Vec<Point2<Ground>> = (0..6)
+            .map(|index| {
+                let angle = (index as f32 / 12.0 - 0.2) * TAU;
+                point![1.5, 0.0] + vector![angle.cos(), angle.sin()] * radius
+            })
+            .collect();
+        let lines: Vec<_> = arc_points
+            .windows(2)
+            .map(|points| LineSegment::new(points[0], points[1]))
+            .chain([LineSegment::new(point![0.5, 1.0], point![3.0, 1.0])])
+            .collect();
+
+        let circle_and_points =
+            center_circle_points(&lines, field_to_ground, &field_dimensions, &parameters).unwrap();
+
+        assert_eq!(circle_and_points.points.len(), 10);
+        assert_relative_eq!(circle_and_points.center, point![1.5, 0.0], epsilon = 1e-3);
+
+        let camera_matrix = camera_matrix();
+        let measurements = collect_measurements(
+            &lines,
+            &camera_matrix,
+            &camera_matrix,
+            field_to_ground.inverse(),
+            &field_dimensions,
+            &parameters,
+        );
+        assert!(matches!(
+            measurements.as_slice(),
+            [Measurement::CenterCircle(_)]
+        ));
+    }
+}
@@ -1,15 +1,23 @@
-use std::{boxed::Box, future::Future, pin::Pin};
+use std::{boxed::Box, collections::VecDeque, future::Future, pin::Pin};
 use std::{f32::consts::FRAC_PI_2, sync::Arc};
 
 use color_eyre::Result;
 
 use coordinate_systems::{Camera, Ground, Head, Robot};
 use kinematics::{robot_dimensions::RobotDimensions, robot_kinematics::RobotKinematics};
-use linear_algebra::{IntoTransform, Isometry3, Vector3, vector};
-use projection::camera_matrix::CameraMatrix;
-use ros_z::prelude::*;
+use linear_algebra::{IntoTransform, Isometry3, Rotation3, Vector3, vector};
+use projection::{
+    camera_matrix::CameraMatrix,
+    rolling_shutter::{RowCameraMatrices, interpolate_in_time},
+};
+use ros_z::{prelude::*, time::Time};
 use ros2::sensor_msgs::camera_info::CameraInfo;
-use types::{parameters::CameraMatrixParameters, time_wrapper::TimeWrapper};
+use types::{
+    exposure_timing::ExposureTiming, parameters::CameraMatrixParameters, time_wrapper::TimeWrapper,
+};
+
+/// Covers the exposure of an image, which lies a few tens of milliseconds before its reception.
+const CAMERA_MATRIX_HISTORY_CAPACITY: usize = 100;
 
 pub fn run_boxed(ctx: Arc<Context>) -> Pin<Box<dyn Future<Output = Result<()>> + Send>> {
     Box::pin(run(ctx))
@@ -35,43 +43,122 @@ async fn run(ctx: Arc<Context>) -> Result<()> {
         .cache(1)
         .build()
         .await?;
+    let exposure_timing_sub = node
+        .subscriber::<TimeWrapper<ExposureTiming>>("inputs/exposure_timing")
+        .build()
+        .await?;
 
     let camera_matrix_pub = node
         .publisher::<TimeWrapper<CameraMatrix>>("camera_matrix")
         .build()
         .await?;
+    let uncalibrated_camera_matrix_pub = node
+        .publisher::<TimeWrapper<CameraMatrix>>("uncalibrated_camera_matrix")
+        .build()
+        .await?;
+    let row_camera_matrices_pub = node
+        .publisher::<TimeWrapper<RowCameraMatrices>>("row_camera_matrices")
+        .build()
+        .await?;
+
+    let mut camera_matrix_history = VecDeque::with_capacity(CAMERA_MATRIX_HISTORY_CAPACITY);
 
     loop {
-        let parameters_snapshot = parameters.snapshot();
-        let parameters = parameters_snapshot.typed();
-
-        let timed_robot_to_ground = robot_to_ground_sub.recv().await?;
-        let time_stamp = timed_robot_to_ground.time;
-        let maybe_robot_to_ground = timed_robot_to_ground.inner;
-        let Some(robot_to_ground) = maybe_robot_to_ground else {
-            continue;
-        };
-
-        let (Some(timed_robot_kinematics), Some(camera_info)) = (
-            robot_kinematics_cache.get_nearest(time_stamp),
-            camera_info_cache.get_nearest(time_stamp),
-        ) else {
-            continue;
-        };
-        let robot_kinematics = &timed_robot_kinematics.inner;
-
-        let camera_matrix =
-            compute_camera_matrix(parameters, robot_kinematics, &robot_to_ground, &camera_info);
-
-        camera_matrix_pub
-            .publish(&TimeWrapper {
-                time: time_stamp,
-                inner: camera_matrix,
-            })
-            .await?;
+        tokio::select! {
+            timed_robot_to_ground = robot_to_ground_sub.recv() => {
+                let parameters_snapshot = parameters.snapshot();
+                let parameters = parameters_snapshot.typed();
+
+                let timed_robot_to_ground = timed_robot_to_ground?;
+                let time_stamp = timed_robot_to_ground.time;
+                let maybe_robot_to_ground = timed_robot_to_ground.inner;
+                let Some(robot_to_ground) = maybe_robot_to_ground else {
+                    continue;
+                };
+
+                let (Some(timed_robot_kinematics), Some(camera_info)) = (
+                    robot_kinematics_cache.get_nearest(time_stamp),
+                    camera_info_cache.get_nearest(time_stamp),
+                ) else {
+                    continue;
+                };
+                let robot_kinematics = &timed_robot_kinematics.inner;
+
+                let uncalibrated_camera_matrix = compute_camera_matrix(
+                    parameters,
+                    robot_kinematics,
+                    &robot_to_ground,
+                    &camera_info,
+                );
+                let camera_matrix = uncalibrated_camera_matrix.to_corrected(
+                    Rotation3::from_euler_angles(
+                        parameters.correction_in_robot.x(),
+                        parameters.correction_in_robot.y(),
+                        parameters.correction_in_robot.z(),
+                    ),
+                    Rotation3::from_euler_angles(
+                        parameters.correction_in_camera.x(),
+                        parameters.correction_in_camera.y(),
+                        parameters.correction_in_camera.z(),
+                    ),
+                );
+
+                if camera_matrix_history.len() == CAMERA_MATRIX_HISTORY_CAPACITY {
+                    camera_matrix_history.pop_front();
+                }
+                camera_matrix_history.push_back((time_stamp, camera_matrix.clone()));
+
+                camera_matrix_pub
+                    .publish(&TimeWrapper {
+                        time: time_stamp,
+                        inner: camera_matrix,
+                    })
+                    .await?;
+                uncalibrated_camera_matrix_pub
+                    .publish(&TimeWrapper {
+                        time: time_stamp,
+                        inner: uncalibrated_camera_matrix,
+                    })
+                    .await?;
+            }
+            timed_exposure_timing = exposure_timing_sub.recv() => {
+                let timed_exposure_timing = timed_exposure_timing?;
+                let exposure_timing = timed_exposure_timing.inner;
+                let Some(row_camera_matrices) =
+                    RowCameraMatrices::sample(exposure_timing.number_of_rows, |row| {
+                        camera_matrix_at(&camera_matrix_history, exposure_timing.row_time(row))
+                    })
+                else {
+                    continue;
+                };
+
+                row_camera_matrices_pub
+                    .publish(&TimeWrapper {
+                        time: timed_exposure_timing.time,
+                        inner: row_camera_matrices,
+                    })
+                    .await?;
+            }
+        }
     }
 }
 
+fn camera_matrix_at(
+    camera_matrix_history: &VecDeque<(Time, CameraMatrix)>,
+    time: Time,
+) -> Option<CameraMatrix> {
+    let after_index = camera_matrix_history.partition_point(|(stamp, _)| *stamp <= time);
+    let as_sample = |(stamp, camera_matrix): &(Time, CameraMatrix)| (*stamp, camera_matrix);
+    interpolate_in_time(
+        time,
+        after_index
+            .checked_sub(1)
+            .and_then(|index| camera_matrix_history.get(index))
+            .map(as_sample),
+        camera_matrix_history.get(after_index).map(as_sample),
+    )
+}
+
 fn compute_camera_matrix(
     parameters: &CameraMatrixParameters,
     robot_kinematics: &RobotKinematics,
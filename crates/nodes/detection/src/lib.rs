@@ -1,9 +1,13 @@
 use std::{boxed::Box, future::Future, pin::Pin, sync::Arc, time::Duration};
 
 use color_eyre::{Result, eyre::bail};
+use inference_session::{
+    ExecutionProviderChain,
+    downscaling::{InputDownscaling, downscale_nv12},
+};
 use ndarray::{ArrayView2, ArrayView3, Axis};
 use ort::{
-    execution_providers::{CUDAExecutionProvider, TensorRTExecutionProvider},
+    execution_providers::TensorRTExecutionProvider,
     inputs,
     session::{Session, SessionOutputs, builder::GraphOptimizationLevel},
     value::TensorRef,
@@ -15,13 +19,15 @@ use ros_z::prelude::*;
 use tokio::time::Instant;
 use types::{
     bounding_box::BoundingBox,
+    execution_provider::{ExecutionProviderKind, InferenceStatus},
     object_detection::{NUMBER_OF_VALUES_PER_OBJECT, Object, RobocupObjectLabel, YOLOObjectLabel},
     parameters::DetectionParameters,
-    pose_detection::{NUMBER_OF_VALUES_PER_POSE, Pose},
+    pose_detection::{NUMBER_OF_VALUES_PER_POSE, POSE_KEYPOINT_OFFSET, Pose},
     time_wrapper::TimeWrapper,
 };
 
 pub const NUMBER_OF_DETECTIONS: usize = 300;
+const INPUT_DIMENSION_ALIGNMENT: u32 = 32;
 
 #[derive(Clone, Copy, Debug)]
 enum TaskHead {
@@ -76,6 +82,10 @@ async fn run(ctx: Arc<Context>) -> Result<()> {
         .publisher::<Duration>("non_maximum_suppression_duration")
         .build()
         .await?;
+    let inference_status_pub = node
+        .publisher::<InferenceStatus>("inference_status")
+        .build()
+        .await?;
     let detected_objects_pub = node
         .announcing_publisher::<Vec<Object<RobocupObjectLabel>>>("detected_objects")
         .await?;
@@ -86,23 +96,8 @@ async fn run(ctx: Arc<Context>) -> Result<()> {
     let initial_parameters_snapshot = node_parameters.snapshot();
     let parameters = initial_parameters_snapshot.typed();
 
-    let model_path = parameters
-        .neural_networks_folder
-        .join(&parameters.model_name);
-
-    let tensor_rt = TensorRTExecutionProvider::default()
-        .with_device_id(0)
-        .with_fp16(true)
-        .with_engine_cache(true)
-        .with_engine_cache_path(parameters.neural_networks_folder.display())
-        .build();
-    let cuda = CUDAExecutionProvider::default().build();
-
-    let mut session = Session::builder()?
-        .with_execution_providers([tensor_rt, cuda])?
-        .with_optimization_level(GraphOptimizationLevel::Level3)?
-        .with_intra_threads(2)?
-        .commit_from_file(model_path)?;
+    let (mut session, execution_provider) = create_session(parameters)?;
+    let mut input_downscaling = InputDownscaling::default();
 
     loop {
         let parameters_snapshot = node_parameters.snapshot();
@@ -119,6 +114,9 @@ async fn run(ctx: Arc<Context>) -> Result<()> {
 
         let image = timed_image.inner;
         check_image(&image)?;
+        let downscaling_factor =
+            input_downscaling.factor_for(image.width, image.height, INPUT_DIMENSION_ALIGNMENT);
+        let image = downscale_nv12(&image, downscaling_factor)?;
 
         let inference_start = Instant::now();
 
@@ -130,6 +128,11 @@ async fn run(ctx: Arc<Context>) -> Result<()> {
             session.run(inputs!["raw_bytes_input" => TensorRef::from_array_view(nv12_data)?])?;
 
         let inference_duration = inference_start.elapsed();
+        input_downscaling.update(
+            downscaling_factor,
+            inference_duration,
+            &parameters.input_downscaling,
+        );
 
         let post_processing_start = Instant::now();
 
@@ -137,10 +140,12 @@ async fn run(ctx: Arc<Context>) -> Result<()> {
         let candidate_detections = extract_candidate_object_detections(
             &outputs,
             parameters.object_detection_parameters.confidence_threshold,
+            downscaling_factor as f32,
         )?;
         let candidate_human_poses = extract_candidate_pose_detections(
             &outputs,
             parameters.pose_detection_parameters.confidence_threshold,
+            downscaling_factor as f32,
         )?;
         let post_processing_duration = post_processing_start.elapsed();
         let non_maximum_suppression_start = Instant::now();
@@ -165,12 +170,41 @@ async fn run(ctx: Arc<Context>) -> Result<()> {
         non_maximum_suppression_duration_pub
             .publish(&non_maximum_suppression_duration)
             .await?;
+        inference_status_pub
+            .publish(&InferenceStatus {
+                execution_provider,
+                input_downscaling_factor: downscaling_factor,
+            })
+            .await?;
 
         detected_objects_pending.publish(&detected_objects).await?;
         detected_poses_pending.publish(&detected_poses).await?;
     }
 }
 
+fn create_session(parameters: &DetectionParameters) -> Result<(Session, ExecutionProviderKind)> {
+    let model_path = parameters
+        .neural_networks_folder
+        .join(&parameters.model_name);
+
+    let tensor_rt = TensorRTExecutionProvider::default()
+        .with_device_id(0)
+        .with_fp16(true)
+        .with_engine_cache(true)
+        .with_engine_cache_path(parameters.neural_networks_folder.display());
+    let mut session_builder = Session::builder()?;
+    let execution_provider = ExecutionProviderChain::default()
+        .with_tensor_rt(tensor_rt)
+        .register(&parameters.execution_providers, &mut session_builder)?;
+
+    let session = session_builder
+        .with_optimization_level(GraphOptimizationLevel::Level3)?
+        .with_intra_threads(2)?
+        .commit_from_file(model_path)?;
+
+    Ok((session, execution_provider))
+}
+
 fn check_image(image: &Image) -> Result<()> {
     if image.encoding != "nv12" {
         bail!("unsupported image encoding: {}", image.encoding);
@@ -218,6 +252,7 @@ fn extract_outputs<'a>(outputs: &'a SessionOutputs<'a>) -> Result<ModelOutputs<'
 fn extract_candidate_object_detections(
     outputs: &ModelOutputs,
     confidence_threshold: f32,
+    scale: f32,
 ) -> Result<Vec<Object<RobocupObjectLabel>>> {
     Ok(outputs
         .objects
@@ -228,13 +263,14 @@ fn extract_candidate_object_detections(
                 return None;
             }
 
-            let object_values: [f32; NUMBER_OF_VALUES_PER_OBJECT] = row
+            let mut object_values: [f32; NUMBER_OF_VALUES_PER_OBJECT] = row
                 .as_slice()
                 .expect("slice is not contiguous")
                 .try_into()
                 .unwrap_or_else(|_| {
                     panic!("slice is not of length {}", NUMBER_OF_VALUES_PER_OBJECT)
                 });
+            scale_bounding_box(&mut object_values, scale);
 
             Some(Object::from(object_values))
         })
@@ -244,6 +280,7 @@ fn extract_candidate_object_detections(
 fn extract_candidate_pose_detections(
     outputs: &ModelOutputs,
     confidence_threshold: f32,
+    scale: f32,
 ) -> Result<Vec<Pose<YOLOObjectLabel>>> {
     Ok(outputs
         .poses
@@ -254,17 +291,29 @@ fn extract_candidate_pose_detections(
                 return None;
             }
 
-            let pose_values: [f32; NUMBER_OF_VALUES_PER_POSE] = row
+            let mut pose_values: [f32; NUMBER_OF_VALUES_PER_POSE] = row
                 .as_slice()
                 .expect("slice is not contiguous")
                 .try_into()
                 .unwrap_or_else(|_| panic!("slice is not of length {}", NUMBER_OF_VALUES_PER_POSE));
+            scale_bounding_box(&mut pose_values, scale);
+            for keypoint in pose_values[POSE_KEYPOINT_OFFSET..].chunks_exact_mut(3) {
+                keypoint[0] *= scale;
+                keypoint[1] *= scale;
+            }
 
             Some(Pose::from(&pose_values))
         })
         .collect())
 }
 
+/// Maps the corners of a detection from the downscaled network input back to image pixels.
+fn scale_bounding_box(values: &mut [f32], scale: f32) {
+    for corner_coordinate in &mut values[..4] {
+        *corner_coordinate *= scale;
+    }
+}
+
 trait HasBoundingBox {
     fn bounding_box(&self) -> &BoundingBox;
 }
@@ -0,0 +1,311 @@
+use std::{collections::VecDeque, time::Duration};
+
+use hsl_network_messages::{PlayerNumber, Team, VisualRefereeMessage};
+use ros_z::time::Time;
+use types::{
+    field_dimensions::GlobalFieldSide, players::Players,
+    pose_detection::FreeKickSignalDetectionResult, pose_kinds::PoseKind, time_wrapper::TimeWrapper,
+};
+
+use crate::Parameters;
+
+/// Combines the own free kick signal detections with the ones of the teammates into a team-wide
+/// decision on the kicking team.
+#[derive(Default)]
+pub struct FreeKickSignalFilter {
+    detections: Players<Option<TimeWrapper<Team>>>,
+    detected_free_kick_signal_queue: VecDeque<Team>,
+    last_time_message_sent: Option<Time>,
+}
+
+impl FreeKickSignalFilter {
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+
+    pub fn detections(&self) -> &Players<Option<TimeWrapper<Team>>> {
+        &self.detections
+    }
+
+    pub fn update_own_detections(
+        &mut self,
+        detections: impl IntoIterator<Item = (Time, Option<PoseKind>)>,
+        hulks_global_field_side: GlobalFieldSide,
+        own_player_number: PlayerNumber,
+        parameters: &Parameters,
+    ) -> FreeKickSignalDetectionResult {
+        let mut did_detect_any_free_kick_pose_this_cycle = false;
+        let mut latest_detection_time = None;
+        for (time, pose_kind) in detections {
+            latest_detection_time = Some(time);
+            if let Some(kicking_team) =
+                kicking_team_from_free_kick_pose(pose_kind, hulks_global_field_side)
+            {
+                self.detected_free_kick_signal_queue
+                    .push_front(kicking_team);
+                did_detect_any_free_kick_pose_this_cycle = true;
+            }
+        }
+        self.detected_free_kick_signal_queue
+            .truncate(parameters.pose_queue_length);
+
+        let (own_detected_kicking_team, number_of_detections) =
+            most_detections(self.detected_free_kick_signal_queue.iter().copied());
+        if let (true, Some(time), Some(kicking_team)) = (
+            number_of_detections >= parameters.minimum_number_poses_before_message,
+            latest_detection_time,
+            own_detected_kicking_team,
+        ) {
+            self.detections[own_player_number] = Some(TimeWrapper {
+                time,
+                inner: kicking_team,
+            });
+        }
+
+        FreeKickSignalDetectionResult {
+            own_detected_kicking_team,
+            did_detect_any_free_kick_pose_this_cycle,
+        }
+    }
+
+    /// Teammates report the ready signal with the same message, only the ones with a kicking team
+    /// refer to a free kick signal.
+    pub fn update_other_detection(&mut self, time: Time, message: VisualRefereeMessage) {
+        if let Some(kicking_team) = message.kicking_team {
+            self.detections[message.player_number] = Some(TimeWrapper {
+                time,
+                inner: kicking_team,
+            });
+        }
+    }
+
+    pub fn detected_kicking_team(&self, now: Time, parameters: &Parameters) -> Option<Team> {
+        let (kicking_team, number_of_detections) =
+            most_detections(self.valid_detections(now, parameters.message_grace_period, None));
+        (number_of_detections >= parameters.minimum_free_kick_signal_detections)
+            .then_some(kicking_team)
+            .flatten()
+    }
+
+    /// Returns the kicking team to report, if any. Nothing is reported once enough teammates
+    /// agree on the own detection, so that the message budget is not spent needlessly.
+    pub fn message_to_send(
+        &self,
+        now: Time,
+        own_player_number: PlayerNumber,
+        remaining_number_of_messages: u16,
+        parameters: &Parameters,
+    ) -> Option<Team> {
+        let own_kicking_team = self.detections[own_player_number]
+            .as_ref()
+            .filter(|detection| {
+                now.duration_since(detection.time) < parameters.message_grace_period
+            })?
+            .inner;
+        let is_message_interval_elapsed = self
+            .last_time_message_sent
+            .is_none_or(|time| now.duration_since(time) >= parameters.message_interval);
+        let number_of_agreeing_teammates = self
+            .valid_detections(
+                now,
+                parameters.message_grace_period,
+                Some(own_player_number),
+            )
+            .filter(|kicking_team| *kicking_team == own_kicking_team)
+            .count();
+
+        (is_message_interval_elapsed
+            && remaining_number_of_messages
+                > parameters.remaining_amount_of_messages_to_stop_sending
+            && number_of_agreeing_teammates < parameters.minimum_free_kick_signal_detections)
+            .then_some(own_kicking_team)
+    }
+
+    pub fn message_sent(&mut self, now: Time) {
+        self.last_time_message_sent = Some(now);
+    }
+
+    fn valid_detections(
+        &self,
+        now: Time,
+        grace_period: Duration,
+        excluded_player_number: Option<PlayerNumber>,
+    ) -> impl Iterator<Item = Team> + '_ {
+        self.detections
+            .iter()
+            .filter(move |(player_number, _)| Some(*player_number) != excluded_player_number)
+            .filter_map(move |(_, detection)| {
+                detection
+                    .as_ref()
+                    .filter(|detection| now.duration_since(detection.time) < grace_period)
+                    .map(|detection| detection.inner)
+            })
+    }
+}
+
+/// The referee points towards the half of the defending team.
+fn kicking_team_from_free_kick_pose(
+    pose_kind: Option<PoseKind>,
+    hulks_global_field_side: GlobalFieldSide,
+) -> Option<Team> {
+    match pose_kind {
+        Some(PoseKind::FreeKick { global_field_side }) => {
+            if global_field_side == hulks_global_field_side {
+                Some(Team::Opponent)
+            } else {
+                Some(Team::Hulks)
+            }
+        }
+        _ => None,
+    }
+}
+
+fn most_detections(detections: impl Iterator<Item = Team>) -> (Option<Team>, usize) {
+    let (hulks, opponent) =
+        detections.fold(
+            (0, 0),
+            |(hulks, opponent), kicking_team| match kicking_team {
+                Team::Hulks => (hulks + 1, opponent),
+                Team::Opponent => (hulks, opponent + 1),
+            },
+        );
+    match (hulks, opponent) {
+        (0, 0) => (None, 0),
+        (hulks, opponent) if hulks > opponent => (Some(Team::Hulks), hulks),
+        (_, opponent) => (Some(Team::Opponent), opponent),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::UNIX_EPOCH;
+
+    use super::*;
+
+    fn parameters() -> Parameters {
+        Parameters {
+            pose_queue_length: 4,
+            minimum_number_poses_before_message: 3,
+            minimum_free_kick_signal_detections: 2,
+            message_grace_period: Duration::from_secs(3),
+            message_interval: Duration::from_secs(1),
+            remaining_amount_of_messages_to_stop_sending: 20,
+        }
+    }
+
+    fn time(seconds: f32) -> Time {
+        Time::from_wallclock(UNIX_EPOCH + Duration::from_secs_f32(seconds))
+    }
+
+    fn free_kick_poses(
+        global_field_side: GlobalFieldSide,
+        count: usize,
+    ) -> Vec<(Time, Option<PoseKind>)> {
+        (0..count)
+            .map(|index| {
+                (
+                    time(index as f32 * 0.1),
+                    Some(PoseKind::FreeKick { global_field_side }),
+                )
+            })
+            .collect()
+    }
+
+    fn message(player_number: PlayerNumber, kicking_team: Option<Team>) -> VisualRefereeMessage {
+        VisualRefereeMessage {
+            player_number,
+            kicking_team,
+        }
+    }
+
+    #[test]
+    fn pointing_to_own_half_means_opponent_kicks() {
+        let parameters = parameters();
+        let mut filter = FreeKickSignalFilter::default();
+
+        let result = filter.update_own_detections(
+            free_kick_poses(GlobalFieldSide::Home, 3),
+            GlobalFieldSide::Home,
+            PlayerNumber::One,
+            &parameters,
+        );
+
+        assert_eq!(result.own_detected_kicking_team, Some(Team::Opponent));
+        assert_eq!(
+            filter.detections()[PlayerNumber::One]
+                .as_ref()
+                .map(|detection| detection.inner),
+            Some(Team::Opponent)
+        );
+    }
+
+    #[test]
+    fn team_majority_decides_kicking_team() {
+        let parameters = parameters();
+        let mut filter = FreeKickSignalFilter::default();
+        filter.update_own_detections(
+            free_kick_poses(GlobalFieldSide::Away, 3),
+            GlobalFieldSide::Home,
+            PlayerNumber::One,
+            &parameters,
+        );
+        assert_eq!(filter.detected_kicking_team(time(0.3), &parameters), None);
+
+        filter.update_other_detection(time(0.3), message(PlayerNumber::Two, None));
+        filter.update_other_detection(
+            time(0.3),
+            message(PlayerNumber::Three, Some(Team::Opponent)),
+        );
+        assert_eq!(filter.detected_kicking_team(time(0.3), &parameters), None);
+
+        filter.update_other_detection(time(0.3), message(PlayerNumber::Four, Some(Team::Hulks)));
+        assert_eq!(
+            filter.detected_kicking_team(time(0.3), &parameters),
+            Some(Team::Hulks)
+        );
+        assert_eq!(filter.detected_kicking_team(time(3.5), &parameters), None);
+    }
+
+    #[test]
+    fn messages_stop_once_teammates_agree() {
+        let parameters = parameters();
+        let mut filter = FreeKickSignalFilter::default();
+        filter.update_own_detections(
+            free_kick_poses(GlobalFieldSide::Away, 3),
+            GlobalFieldSide::Home,
+            PlayerNumber::One,
+            &parameters,
+        );
+
+        assert_eq!(
+            filter.message_to_send(time(0.2), PlayerNumber::One, 20, &parameters),
+            None
+        );
+        assert_eq!(
+            filter.message_to_send(time(0.2), PlayerNumber::One, 21, &parameters),
+            Some(Team::Hulks)
+        );
+        filter.message_sent(time(0.2));
+        assert_eq!(
+            filter.message_to_send(time(0.5), PlayerNumber::One, 21, &parameters),
+            None
+        );
+
+        filter.update_other_detection(time(1.0), message(PlayerNumber::Two, Some(Team::Opponent)));
+        filter.update_other_detection(
+            time(1.0),
+            message(PlayerNumber::Three, Some(Team::Opponent)),
+        );
+        assert_eq!(
+            filter.message_to_send(time(1.2), PlayerNumber::One, 21, &parameters),
+            Some(Team::Hulks)
+        );
+
+        filter.update_other_detection(time(1.0), message(PlayerNumber::Four, Some(Team::Hulks)));
+        filter.update_other_detection(time(1.0), message(PlayerNumber::Five, Some(Team::Hulks)));
+        assert_eq!(
+            filter.message_to_send(time(1.2), PlayerNumber::One, 21, &parameters),
+            None
+        );
+    }
+}
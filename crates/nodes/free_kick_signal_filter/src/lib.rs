@@ -0,0 +1,173 @@
+use std::{boxed::Box, future::Future, pin::Pin, sync::Arc, time::Duration};
+
+use color_eyre::{Result, eyre::WrapErr};
+use serde::{Deserialize, Serialize};
+
+use hsl_network_messages::{HulkMessage, PlayerNumber, SubState, Team, VisualRefereeMessage};
+use ros_z::{prelude::*, qos::QosDurability};
+use ros_z_streams::CreateFutureMapBuilder;
+use types::{
+    filtered_game_controller_state::FilteredGameControllerState,
+    filtered_game_state::FilteredGameState,
+    messages::{IncomingMessage, OutgoingMessage},
+    players::Players,
+    pose_detection::FreeKickSignalDetectionResult,
+    pose_kinds::PoseKind,
+    time_wrapper::TimeWrapper,
+};
+
+use crate::filter::FreeKickSignalFilter;
+
+pub mod filter;
+
+#[derive(Debug, Clone, Serialize, Deserialize, Message)]
+#[serde(deny_unknown_fields)]
+pub struct Parameters {
+    pub pose_queue_length: usize,
+    pub minimum_number_poses_before_message: usize,
+    /// Number of players, including this one, that have to agree on the kicking team
+    pub minimum_free_kick_signal_detections: usize,
+    pub message_grace_period: Duration,
+    pub message_interval: Duration,
+    pub remaining_amount_of_messages_to_stop_sending: u16,
+}
+
+pub fn run_boxed(ctx: Arc<Context>) -> Pin<Box<dyn Future<Output = Result<()>> + Send>> {
+    Box::pin(run(ctx))
+}
+
+async fn run(ctx: Arc<Context>) -> Result<()> {
+    let node = ctx
+        .create_node("free_kick_signal_filter")
+        .build()
+        .await
+        .wrap_err("failed to create free_kick_signal_filter node")?;
+    let parameters = node
+        .bind_parameter_as::<Parameters>("free_kick_signal_filter")
+        .wrap_err("failed to bind free_kick_signal_filter parameters")?;
+
+    let player_number_cache = node
+        .subscriber::<PlayerNumber>("player_number")
+        .qos(QosProfile {
+            durability: QosDurability::TransientLocal,
+            ..Default::default()
+        })
+        .cache(1)
+        .build()
+        .await?;
+    let filtered_game_controller_state_cache = node
+        .subscriber::<FilteredGameControllerState>("filtered_game_controller_state")
+        .cache(1)
+        .build()
+        .await?;
+    let filtered_message_sub = node
+        .subscriber::<TimeWrapper<IncomingMessage>>("filtered_message")
+        .build()
+        .await?;
+    let mut referee_pose_kinds = node
+        .create_future_map_builder()
+        .create_future_subscriber::<Option<PoseKind>>(
+            "referee_pose_kind",
+            Duration::from_millis(50),
+        )
+        .await?
+        .build();
+
+    let detected_free_kick_kicking_team_pub = node
+        .publisher::<Option<Team>>("detected_free_kick_kicking_team")
+        .build()
+        .await?;
+    let own_free_kick_signal_detection_result_pub = node
+        .publisher::<FreeKickSignalDetectionResult>("own_free_kick_signal_detection_result")
+        .build()
+        .await?;
+    let detections_pub = node
+        .publisher::<Players<Option<TimeWrapper<Team>>>>("free_kick_signal_filter/detections")
+        .build()
+        .await?;
+    let outgoing_message_pub = node
+        .publisher::<OutgoingMessage>("outputs/message")
+        .build()
+        .await?;
+
+    let mut filter = FreeKickSignalFilter::default();
+    loop {
+        let mut own_detections = Vec::new();
+        tokio::select! {
+            item = referee_pose_kinds.recv() => {
+                own_detections = item?
+                    .persistent
+                    .into_iter()
+                    .map(|(time, (pose_kind,))| (time, pose_kind.flatten()))
+                    .collect();
+            }
+            message = filtered_message_sub.recv() => {
+                if let TimeWrapper {
+                    time,
+                    inner: IncomingMessage::Hsl(HulkMessage::VisualReferee(message)),
+                } = message?
+                {
+                    filter.update_other_detection(time, message);
+                }
+            }
+        }
+
+        let parameters_snapshot = parameters.snapshot();
+        let parameters = parameters_snapshot.typed();
+        let now = node.clock().now();
+        let (Some(player_number), Some(game_controller_state)) = (
+            player_number_cache.get_latest(),
+            filtered_game_controller_state_cache.get_latest(),
+        ) else {
+            continue;
+        };
+
+        // the game controller does not announce the kicking team of throw-ins
+        if !matches!(
+            *game_controller_state,
+            FilteredGameControllerState {
+                game_state: FilteredGameState::Playing { .. },
+                sub_state: Some(SubState::ThrowIn),
+                ..
+            }
+        ) {
+            filter.reset();
+            detected_free_kick_kicking_team_pub.publish(&None).await?;
+            own_free_kick_signal_detection_result_pub
+                .publish(&FreeKickSignalDetectionResult::default())
+                .await?;
+            continue;
+        }
+
+        let own_free_kick_signal_detection_result = filter.update_own_detections(
+            own_detections,
+            game_controller_state.global_field_side,
+            *player_number,
+            parameters,
+        );
+        if let Some(kicking_team) = filter.message_to_send(
+            now,
+            *player_number,
+            game_controller_state.remaining_number_of_messages,
+            parameters,
+        ) {
+            outgoing_message_pub
+                .publish(&OutgoingMessage::Hsl(HulkMessage::VisualReferee(
+                    VisualRefereeMessage {
+                        player_number: *player_number,
+                        kicking_team: Some(kicking_team),
+                    },
+                )))
+                .await?;
+            filter.message_sent(now);
+        }
+
+        detected_free_kick_kicking_team_pub
+            .publish(&filter.detected_kicking_team(now, parameters))
+            .await?;
+        own_free_kick_signal_detection_result_pub
+            .publish(&own_free_kick_signal_detection_result)
+            .await?;
+        detections_pub.publish(filter.detections()).await?;
+    }
+}
@@ -92,7 +92,15 @@ pub struct GameControllerFilter {
 }
 
 impl GameControllerFilter {
-    fn update_game_controller_state(&mut self, time: &Time, message: &GameControllerStateMessage) {
+    pub fn game_controller_state(&self) -> Option<&GameControllerState> {
+        self.game_controller_state.as_ref()
+    }
+
+    pub fn update_game_controller_state(
+        &mut self,
+        time: &Time,
+        message: &GameControllerStateMessage,
+    ) {
         let game_state_changed = match &self.game_controller_state {
             Some(game_controller_state) => game_controller_state.game_state != message.game_state,
             None => true,
@@ -104,6 +112,7 @@ impl GameControllerFilter {
             game_state: message.game_state,
             stopped: message.stopped,
             game_phase: message.game_phase,
+            half: message.half,
             remaining_time_in_half: message.remaining_time_in_half,
             kicking_team: message.kicking_team,
             last_game_state_change: self.last_game_state_change.unwrap().to_wallclock(),
@@ -64,6 +64,11 @@ async fn run(ctx: Arc<Context>) -> Result<()> {
         .cache(1)
         .build()
         .await?;
+    let detected_free_kick_kicking_team_cache = node
+        .subscriber::<Option<Team>>("detected_free_kick_kicking_team")
+        .cache(1)
+        .build()
+        .await?;
 
     let whistle_in_set_ball_position_pub = node
         .publisher::<Option<Point2<Field>>>("whistle_in_set_ball_position")
@@ -93,6 +98,9 @@ async fn run(ctx: Arc<Context>) -> Result<()> {
         };
 
         let filtered_whistle = filtered_whistle_cache.get_latest().unwrap_or_default();
+        let detected_free_kick_kicking_team = detected_free_kick_kicking_team_cache
+            .get_latest()
+            .and_then(|kicking_team| *kicking_team);
 
         let current_ball_state_time = ball_state_cache.latest_stamp();
         let current_ball_state = ball_state_cache.get_latest().and_then(|maybe| *maybe);
@@ -110,6 +118,7 @@ async fn run(ctx: Arc<Context>) -> Result<()> {
                 &latest_known_ball_state,
                 &filtered_whistle,
                 &current_ball_state,
+                detected_free_kick_kicking_team,
             );
 
         whistle_in_set_ball_position_pub
@@ -138,7 +147,7 @@ pub struct GameControllerStateFilter {
 
 impl GameControllerStateFilter {
     #[allow(clippy::too_many_arguments)]
-    fn compute_filtered_game_controller_state(
+    pub fn compute_filtered_game_controller_state(
         &mut self,
         now: Time,
         parameters: &GameStateFilterParameters,
@@ -148,6 +157,7 @@ impl GameControllerStateFilter {
         latest_known_ball_state: &Option<(Time, BallState)>,
         filtered_whistle: &FilteredWhistle,
         current_ball_state: &Option<BallState>,
+        detected_free_kick_kicking_team: Option<Team>,
     ) -> FilteredGameControllerState {
         let (new_own_penalties_last_cycle, new_opponent_penalties_last_cycle) = self
             .last_game_controller_state
@@ -168,8 +178,6 @@ impl GameControllerStateFilter {
             .chain(new_opponent_penalties_last_cycle.iter())
             .any(|(_, penalty)| matches!(penalty, Penalty::MotionInSet { .. }));
 
-        let fake_detected_free_kick_kicking_team = None;
-
         let latest_ball_state = latest_known_ball_state.filter(|(ball_state_time, _)| {
             let is_not_in_penalty_kick =
                 game_controller_state.sub_state != Some(SubState::PenaltyKick);
@@ -189,8 +197,7 @@ impl GameControllerStateFilter {
             &latest_ball_state,
             &new_own_penalties_last_cycle,
             &new_opponent_penalties_last_cycle,
-            // detected_free_kick_kicking_team,
-            fake_detected_free_kick_kicking_team,
+            detected_free_kick_kicking_team,
             filtered_whistle,
         );
 
@@ -206,10 +213,12 @@ impl GameControllerStateFilter {
             did_receive_motion_in_set_penalty,
             kicking_team,
         );
+        self.last_game_controller_state = Some(game_controller_state.clone());
 
         FilteredGameControllerState {
             game_state: game_states.own,
             opponent_game_state: game_states.opponent,
+            half: game_controller_state.half,
             remaining_time_in_half: game_controller_state.remaining_time_in_half,
             game_phase: game_controller_state.game_phase,
             kicking_team,
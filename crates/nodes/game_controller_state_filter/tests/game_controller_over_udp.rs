@@ -0,0 +1,378 @@
+use std::{
+    net::{Ipv4Addr as Ipv4AddrStd, SocketAddr},
+    path::Path,
+    time::{Duration, SystemTime},
+};
+
+use game_controller_filter::GameControllerFilter;
+use game_controller_simulator::{
+    game::Game,
+    script::{Event, Script},
+    socket::{GameControllerSockets, Received},
+};
+use game_controller_state_filter::GameControllerStateFilter;
+use hsl_network::endpoint::{Endpoint, Ipv4Addr, Ports};
+use hsl_network_messages::{
+    GameControllerReturnMessage, GameControllerStateMessage, GameState, Penalty, PlayerNumber, Team,
+};
+use linear_algebra::vector;
+use ros_z::time::Time;
+use tokio::time::timeout;
+use types::{
+    field_dimensions::FieldDimensions,
+    filtered_game_controller_state::FilteredGameControllerState,
+    filtered_game_state::FilteredGameState,
+    filtered_whistle::FilteredWhistle,
+    game_controller_state::GameControllerState,
+    messages::{IncomingMessage, OutgoingMessage},
+    parameters::GameStateFilterParameters,
+};
+
+const RECEIVE_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Robot side of the connection to the simulated GameController, running the received state
+/// messages through both filters.
+struct Robot {
+    endpoint: Endpoint,
+    game_controller_filter: GameControllerFilter,
+    game_controller_state_filter: GameControllerStateFilter,
+    now: Time,
+}
+
+impl Robot {
+    async fn receive_state(&self) -> (SocketAddr, GameControllerStateMessage) {
+        let message = timeout(RECEIVE_TIMEOUT, self.endpoint.read())
+            .await
+            .expect("no state message received")
+            .unwrap();
+        match message {
+            IncomingMessage::GameController(source, message) => (source, message),
+            IncomingMessage::Hsl(message) => panic!("unexpected team message {message:?}"),
+        }
+    }
+
+    async fn receive_game_controller_state(&mut self) -> &GameControllerState {
+        let (_, message) = self.receive_state().await;
+        self.now = self.now + Duration::from_millis(500);
+        self.game_controller_filter
+            .update_game_controller_state(&self.now, &message);
+        self.game_controller_filter.game_controller_state().unwrap()
+    }
+
+    async fn receive(&mut self, filtered_whistle: &FilteredWhistle) -> FilteredGameControllerState {
+        self.receive_game_controller_state().await;
+        self.game_controller_state_filter
+            .compute_filtered_game_controller_state(
+                self.now,
+                &parameters(),
+                &FieldDimensions::default(),
+                &PlayerNumber::Three,
+                self.game_controller_filter.game_controller_state().unwrap(),
+                &None,
+                filtered_whistle,
+                &None,
+                None,
+            )
+    }
+}
+
+fn parameters() -> GameStateFilterParameters {
+    GameStateFilterParameters {
+        game_controller_controller_delay: Duration::from_secs(3),
+        playing_message_delay: Duration::from_secs(15),
+        ready_message_delay: Duration::from_secs(15),
+        kick_off_grace_period: Duration::from_secs(10),
+        tentative_finish_duration: Duration::from_secs(4),
+        distance_to_consider_ball_moved_in_kick_off: 0.3,
+        whistle_acceptance_goal_distance: vector![0.5, 0.5],
+        duration_to_keep_observed_ball: Duration::from_secs(14),
+        duration_to_keep_new_penalties: Duration::from_secs(30),
+    }
+}
+
+/// Script shipped with the GameController simulator, its header is shared by all tests.
+fn kick_off_script() -> Script {
+    Script::load(
+        Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("../../../tools/game_controller_simulator/scripts/kick_off.json5"),
+    )
+    .unwrap()
+}
+
+async fn connect() -> (GameControllerSockets, Game, SocketAddr, Robot) {
+    let game_controller = GameControllerSockets::bind(0, None).await.unwrap();
+    let game = Game::new(&kick_off_script());
+    let endpoint = Endpoint::new(Ports {
+        game_controller_state: 0,
+        game_controller_return: game_controller.return_address().unwrap().port(),
+        hsl: 0,
+        hsl_broadcast_address: Ipv4Addr {
+            octets: [127, 0, 0, 1],
+        },
+    })
+    .await
+    .unwrap();
+    let state_destination = SocketAddr::from((
+        Ipv4AddrStd::LOCALHOST,
+        endpoint.game_controller_state_address().unwrap().port(),
+    ));
+    let robot = Robot {
+        endpoint,
+        game_controller_filter: GameControllerFilter::default(),
+        game_controller_state_filter: GameControllerStateFilter::default(),
+        now: Time::from_nanos(1_000_000_000),
+    };
+    (game_controller, game, state_destination, robot)
+}
+
+#[tokio::test]
+async fn game_states_are_filtered_from_state_messages() {
+    let (game_controller, mut game, state_destination, mut robot) = connect().await;
+
+    let mut step = async |event| {
+        game.apply(event);
+        game_controller
+            .send_state(state_destination, &game.next_packet())
+            .await
+            .unwrap();
+        robot.receive(&FilteredWhistle::default()).await
+    };
+
+    let filtered = step(Event::GameState(GameState::Set)).await;
+    assert_eq!(filtered.game_state, FilteredGameState::Set);
+
+    let filtered = step(Event::GameState(GameState::Playing)).await;
+    assert_eq!(
+        filtered.game_state,
+        FilteredGameState::Playing {
+            ball_is_free: true,
+            kick_off: false,
+        }
+    );
+
+    let filtered = step(Event::Stop).await;
+    assert_eq!(filtered.game_state, FilteredGameState::Stop);
+
+    let filtered = step(Event::Resume).await;
+    assert_eq!(
+        filtered.game_state,
+        FilteredGameState::Playing {
+            ball_is_free: true,
+            kick_off: false,
+        }
+    );
+}
+
+#[tokio::test]
+async fn new_penalties_are_reported_once() {
+    let (game_controller, mut game, state_destination, mut robot) = connect().await;
+    let penalty = Penalty::Pushing {
+        remaining: Duration::from_secs(45),
+    };
+
+    game_controller
+        .send_state(state_destination, &game.next_packet())
+        .await
+        .unwrap();
+    let filtered = robot.receive(&FilteredWhistle::default()).await;
+    assert!(filtered.new_own_penalties_last_cycle.is_empty());
+
+    game.apply(Event::Penalize {
+        team: Team::Hulks,
+        player_number: PlayerNumber::Two,
+        penalty,
+    });
+    game_controller
+        .send_state(state_destination, &game.next_packet())
+        .await
+        .unwrap();
+    let filtered = robot.receive(&FilteredWhistle::default()).await;
+    assert_eq!(
+        filtered
+            .new_own_penalties_last_cycle
+            .get(&PlayerNumber::Two),
+        Some(&penalty)
+    );
+    assert!(filtered.new_opponent_penalties_last_cycle.is_empty());
+
+    game_controller
+        .send_state(state_destination, &game.next_packet())
+        .await
+        .unwrap();
+    let filtered = robot.receive(&FilteredWhistle::default()).await;
+    assert!(filtered.new_own_penalties_last_cycle.is_empty());
+}
+
+#[tokio::test]
+async fn motion_in_set_penalties_revoke_a_whistle_in_set() {
+    let (game_controller, mut game, state_destination, mut robot) = connect().await;
+    let whistle = FilteredWhistle {
+        is_detected: true,
+        last_detection: Some(SystemTime::now()),
+    };
+
+    game.apply(Event::GameState(GameState::Set));
+    game_controller
+        .send_state(state_destination, &game.next_packet())
+        .await
+        .unwrap();
+    let filtered = robot.receive(&FilteredWhistle::default()).await;
+    assert_eq!(filtered.game_state, FilteredGameState::Set);
+
+    game_controller
+        .send_state(state_destination, &game.next_packet())
+        .await
+        .unwrap();
+    let filtered = robot.receive(&whistle).await;
+    assert!(matches!(
+        filtered.game_state,
+        FilteredGameState::Playing { .. }
+    ));
+
+    game.apply(Event::Penalize {
+        team: Team::Opponent,
+        player_number: PlayerNumber::Four,
+        penalty: Penalty::MotionInSet {
+            remaining: Duration::from_secs(15),
+        },
+    });
+    game_controller
+        .send_state(state_destination, &game.next_packet())
+        .await
+        .unwrap();
+    let filtered = robot.receive(&FilteredWhistle::default()).await;
+    assert_eq!(filtered.game_state, FilteredGameState::Set);
+}
+
+#[tokio::test]
+async fn state_messages_update_the_game_controller_state() {
+    let (game_controller, mut game, state_destination, mut robot) = connect().await;
+
+    game.apply(Event::KickOff {
+        kicking_team: Team::Hulks,
+    });
+    game.apply(Event::GameState(GameState::Ready));
+    game_controller
+        .send_state(state_destination, &game.next_packet())
+        .await
+        .unwrap();
+    let game_controller_state = robot.receive_game_controller_state().await;
+    assert_eq!(game_controller_state.game_state, GameState::Ready);
+    assert_eq!(game_controller_state.kicking_team, Some(Team::Hulks));
+    assert_eq!(
+        game_controller_state.remaining_time_in_half,
+        Duration::from_secs(600)
+    );
+
+    let penalty = Penalty::PickUp {
+        remaining: Duration::from_secs(45),
+    };
+    game.apply(Event::Penalize {
+        team: Team::Hulks,
+        player_number: PlayerNumber::Two,
+        penalty,
+    });
+    game_controller
+        .send_state(state_destination, &game.next_packet())
+        .await
+        .unwrap();
+    let game_controller_state = robot.receive_game_controller_state().await;
+    assert_eq!(
+        game_controller_state.penalties[PlayerNumber::Two],
+        Some(penalty)
+    );
+    assert_eq!(game_controller_state.penalties[PlayerNumber::One], None);
+}
+
+#[tokio::test]
+async fn packets_of_unsupported_versions_are_discarded() {
+    let (game_controller, mut game, state_destination, robot) = connect().await;
+
+    game.apply(Event::GameState(GameState::Set));
+    let mut packet = game.next_packet();
+    packet[4] += 1;
+    game_controller
+        .send_state(state_destination, &packet)
+        .await
+        .unwrap();
+    game.apply(Event::GameState(GameState::Playing));
+    game_controller
+        .send_state(state_destination, &game.next_packet())
+        .await
+        .unwrap();
+
+    let (_, message) = robot.receive_state().await;
+    assert_eq!(message.game_state, GameState::Playing);
+}
+
+#[tokio::test]
+async fn return_messages_reach_the_game_controller() {
+    let (game_controller, mut game, state_destination, robot) = connect().await;
+
+    game_controller
+        .send_state(state_destination, &game.next_packet())
+        .await
+        .unwrap();
+    let (source, _) = robot.receive_state().await;
+    robot
+        .endpoint
+        .write(OutgoingMessage::GameController(
+            source,
+            GameControllerReturnMessage {
+                player_number: PlayerNumber::Four,
+                fallen: true,
+                ..Default::default()
+            },
+        ))
+        .await;
+
+    let received = timeout(RECEIVE_TIMEOUT, game_controller.receive())
+        .await
+        .expect("no return message received")
+        .unwrap();
+    let Received::Return { message, .. } = received else {
+        panic!("unexpected team message {received:?}");
+    };
+    assert_eq!(message.player_number, PlayerNumber::Four);
+    assert!(message.fallen);
+}
+
+#[tokio::test]
+async fn kick_off_script_is_played_back() {
+    let (game_controller, mut game, state_destination, mut robot) = connect().await;
+    let script = kick_off_script();
+    let mut elapsed = Duration::ZERO;
+    let mut game_controller_states = Vec::new();
+
+    for step in &script.steps {
+        game.advance(step.at - elapsed);
+        elapsed = step.at;
+        game.apply(step.event);
+        game_controller
+            .send_state(state_destination, &game.next_packet())
+            .await
+            .unwrap();
+        game_controller_states.push(robot.receive_game_controller_state().await.clone());
+    }
+
+    let [_kick_off, ready, set, playing, penalized, unpenalized, goal] =
+        game_controller_states.as_slice()
+    else {
+        panic!("unexpected number of steps in the kick-off script");
+    };
+    assert_eq!(ready.game_state, GameState::Ready);
+    assert_eq!(ready.kicking_team, Some(Team::Hulks));
+    assert_eq!(set.game_state, GameState::Set);
+    assert_eq!(playing.game_state, GameState::Playing);
+    assert_eq!(
+        penalized.penalties[PlayerNumber::Two],
+        Some(Penalty::PickUp {
+            remaining: Duration::from_secs(45)
+        })
+    );
+    assert_eq!(unpenalized.penalties[PlayerNumber::Two], None);
+    assert_eq!(goal.game_state, GameState::Ready);
+    assert_eq!(goal.kicking_team, Some(Team::Opponent));
+    assert_eq!(goal.hulks_team.score, 1);
+    assert_eq!(goal.remaining_time_in_half, Duration::from_secs(510));
+}
@@ -48,6 +48,11 @@ async fn run(ctx: Arc<Context>) -> Result<()> {
         .cache(1)
         .build()
         .await?;
+    let calibration_head_joints_cache = node
+        .subscriber::<Option<HeadJoints<f32>>>("camera_calibration/head_joints")
+        .cache(1)
+        .build()
+        .await?;
     let head_joints_command_pub = node
         .publisher::<HeadJoints<f32>>("head_joints_command")
         .build()
@@ -80,8 +85,16 @@ async fn run(ctx: Arc<Context>) -> Result<()> {
         let last_cycle_duration = cycle_duration_since_last_update(&mut last_update, now);
         let parameters_snapshot = parameters.snapshot();
         let parameters = &parameters_snapshot.typed().parameters;
+        // the camera calibration moves the head like injected head joints, unless they are set
+        let calibration_head_joints = calibration_head_joints_cache
+            .get_latest()
+            .and_then(|joints| *joints);
+        let parameters = HeadMotionParameters {
+            injected_head_joints: parameters.injected_head_joints.or(calibration_head_joints),
+            ..parameters.clone()
+        };
         let head_joints = state.update(
-            parameters,
+            &parameters,
             look_around_target_joints,
             look_at,
             &motor_states,
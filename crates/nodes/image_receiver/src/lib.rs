@@ -6,24 +6,58 @@ use std::{boxed::Box, future::Future, pin::Pin};
 
 use color_eyre::Result;
 use image::RgbImage;
+use serde::{Deserialize, Serialize};
 
 use ros_z::prelude::*;
 use ros_z::qos::QosDurability;
 use ros_z::time::Time;
 use ros2::sensor_msgs::{camera_info::CameraInfo, image::Image};
 use types::ycbcr422_image::YCbCr422Image;
-use types::{stereo_image_pair::StereoImagePair, time_wrapper::TimeWrapper};
+use types::{
+    exposure_timing::ExposureTiming, stereo_image_pair::StereoImagePair, time_wrapper::TimeWrapper,
+};
 use x5_receiver::receiver::{Side, X5Receiver};
 use x5_receiver::types::X5CameraFrame;
 
 const X5_ADDRESS: SocketAddr = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(192, 168, 127, 10)), 7654);
 
+#[derive(Debug, Clone, Serialize, Deserialize, Message)]
+#[serde(deny_unknown_fields)]
+pub struct Parameters {
+    pub rolling_shutter: RollingShutterParameters,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Message)]
+#[serde(deny_unknown_fields)]
+pub struct RollingShutterParameters {
+    /// Exposure duration of a single row
+    pub exposure_duration: Duration,
+    /// Time between the start of the exposure of the first and the last row
+    pub readout_duration: Duration,
+    /// Time between the readout of the last row and the reception of the image
+    pub transmission_latency: Duration,
+}
+
+impl RollingShutterParameters {
+    fn exposure_timing(&self, reception_time: Time, number_of_rows: u32) -> ExposureTiming {
+        ExposureTiming {
+            first_row: reception_time
+                - self.transmission_latency
+                - self.readout_duration
+                - self.exposure_duration / 2,
+            readout_duration: self.readout_duration,
+            number_of_rows,
+        }
+    }
+}
+
 pub fn run_boxed(ctx: Arc<Context>) -> Pin<Box<dyn Future<Output = Result<()>> + Send>> {
     Box::pin(run(ctx))
 }
 
 async fn run(ctx: Arc<Context>) -> Result<()> {
     let node = ctx.create_node("image_receiver").build().await?;
+    let parameters = node.bind_parameter_as::<Parameters>("image_receiver")?;
 
     let left_image_pub = node
         .publisher::<TimeWrapper<Image>>("inputs/left_image")
@@ -41,6 +75,18 @@ async fn run(ctx: Arc<Context>) -> Result<()> {
         })
         .build()
         .await?;
+    let right_camera_info_pub = node
+        .publisher::<CameraInfo>("inputs/right_camera_info")
+        .qos(QosProfile {
+            durability: QosDurability::TransientLocal,
+            ..Default::default()
+        })
+        .build()
+        .await?;
+    let exposure_timing_pub = node
+        .publisher::<TimeWrapper<ExposureTiming>>("inputs/exposure_timing")
+        .build()
+        .await?;
     let ycbcr422_image_pub = node
         .publisher::<TimeWrapper<YCbCr422Image>>("inputs/ycbcr422_image")
         .build()
@@ -63,6 +109,17 @@ async fn run(ctx: Arc<Context>) -> Result<()> {
             left_image = left_frame_receiver.recv() => {
                 let now = node.clock().now();
                 let received = ReceivedImage::new(now, left_image);
+                let exposure_timing = parameters
+                    .snapshot()
+                    .typed()
+                    .rolling_shutter
+                    .exposure_timing(now, received.image.height);
+                exposure_timing_pub
+                    .publish(&TimeWrapper {
+                        time: now,
+                        inner: exposure_timing,
+                    })
+                    .await?;
                 handle_left_image(
                     &left_image_pub,
                     &ycbcr422_image_pub,
@@ -87,6 +144,9 @@ async fn run(ctx: Arc<Context>) -> Result<()> {
                 camera_info_pub
                     .publish(&camera_info.left_camera_info())
                     .await?;
+                right_camera_info_pub
+                    .publish(&camera_info.right_camera_info())
+                    .await?;
             }
         }
     }
@@ -181,8 +241,7 @@ async fn maybe_publish_stereo_image_pair(
         return Ok(());
     }
 
-    let time = image.image_time;
-    if let Some(stereo_image_pair) = stereo_image_pairer.insert(side, image) {
+    if let Some((time, stereo_image_pair)) = stereo_image_pairer.insert(side, image) {
         stereo_image_pair_pub
             .publish(&TimeWrapper {
                 time,
@@ -202,15 +261,21 @@ enum CameraSide {
 
 #[derive(Default)]
 struct StereoImagePairer {
-    pending_left: BTreeMap<u32, Image>,
-    pending_right: BTreeMap<u32, Image>,
+    pending_left: BTreeMap<u32, ReceivedImage>,
+    pending_right: BTreeMap<u32, ReceivedImage>,
     latest_frame_identifier: u32,
 }
 
 impl StereoImagePairer {
     const MAX_UNMATCHED_FRAME_AGE: u32 = 8;
 
-    fn insert(&mut self, side: CameraSide, image: ReceivedImage) -> Option<StereoImagePair> {
+    /// Returns the pair stamped with the time of the left image, which is also the time of the
+    /// detections in it.
+    fn insert(
+        &mut self,
+        side: CameraSide,
+        image: ReceivedImage,
+    ) -> Option<(Time, StereoImagePair)> {
         self.update_latest_frame_identifier(image.frame_identifier);
 
         let (remove_from, insert_in) = match side {
@@ -219,23 +284,27 @@ impl StereoImagePairer {
         };
 
         let Some(other) = remove_from.remove(&image.frame_identifier) else {
-            insert_in.insert(image.frame_identifier, image.image);
+            insert_in.insert(image.frame_identifier, image);
             self.expire_old_frames();
             return None;
         };
 
         self.expire_old_frames();
 
+        let frame_identifier = image.frame_identifier;
         let (left, right) = match side {
-            CameraSide::Left => (image.image, other),
-            CameraSide::Right => (other, image.image),
+            CameraSide::Left => (image, other),
+            CameraSide::Right => (other, image),
         };
 
-        Some(StereoImagePair {
-            frame_identifier: image.frame_identifier,
-            left,
-            right,
-        })
+        Some((
+            left.image_time,
+            StereoImagePair {
+                frame_identifier,
+                left: left.image,
+                right: right.image,
+            },
+        ))
     }
 
     fn clear(&mut self) {
@@ -0,0 +1,352 @@
+use std::{collections::BTreeMap, fs::read_to_string, path::Path};
+
+use color_eyre::{Result, eyre::WrapErr};
+use linear_algebra::point;
+use types::{
+    color::{Intensity, YCbCr444},
+    field_border::FieldBorder,
+    field_color::{
+        FieldColorAdaptationParameters, FieldColorFeatures, FieldColorParameters, FieldColorTree,
+    },
+    image_segments::{EdgeType, ScanGrid},
+    parameters::ImageSegmenterParameters,
+};
+
+use crate::FieldColorDetection;
+
+pub fn load_decision_tree(path: impl AsRef<Path>) -> Result<FieldColorTree> {
+    let path = path.as_ref();
+    let contents = read_to_string(path)
+        .wrap_err_with(|| format!("failed to read field color tree {}", path.display()))?;
+    serde_json::from_str(&contents)
+        .wrap_err_with(|| format!("failed to parse field color tree {}", path.display()))
+}
+
+/// Classifies segment colors with an exported decision tree, or with the static ranges if there
+/// is none.
+pub struct FieldColorClassifier<'a> {
+    pub static_ranges: &'a FieldColorParameters,
+    pub decision_tree: Option<&'a FieldColorTree>,
+    pub field_probability_threshold: f32,
+    pub adaptation: &'a FieldColorAdaptationParameters,
+}
+
+impl<'a> FieldColorClassifier<'a> {
+    /// Uses `adapted_decision_tree` while adaptation is enabled and `exported_decision_tree`
+    /// otherwise.
+    pub fn new(
+        parameters: &'a ImageSegmenterParameters,
+        exported_decision_tree: Option<&'a FieldColorTree>,
+        adapted_decision_tree: Option<&'a FieldColorTree>,
+    ) -> Self {
+        let classifier = &parameters.field_color_classifier;
+        let decision_tree = if classifier.adaptation.enable {
+            adapted_decision_tree.or(exported_decision_tree)
+        } else {
+            exported_decision_tree
+        };
+        Self {
+            static_ranges: &parameters.field_color_detection,
+            decision_tree: decision_tree.filter(|_| classifier.use_decision_tree),
+            field_probability_threshold: classifier.field_probability_threshold,
+            adaptation: &classifier.adaptation,
+        }
+    }
+
+    /// Corrects the leaves of the decision tree with the segments of `scan_grid`, which count as
+    /// field below the field border and as non-field above it, and returns the adapted tree for
+    /// the next image.
+    ///
+    /// Segments below the border which are brighter than both neighbours are skipped, these are
+    /// field lines or balls rather than field.
+    pub fn adapt(
+        &self,
+        scan_grid: &ScanGrid,
+        field_border: Option<&FieldBorder>,
+    ) -> Option<FieldColorTree> {
+        let decision_tree = self.decision_tree.filter(|_| self.adaptation.enable)?;
+        let Some(field_border) = field_border else {
+            return Some(decision_tree.clone());
+        };
+
+        let samples: Vec<_> = scan_grid
+            .vertical_scan_lines
+            .iter()
+            .flat_map(|scan_line| {
+                let x = scan_line.position as f32;
+                scan_line.segments.iter().filter_map(move |segment| {
+                    if segment.length() < self.adaptation.minimum_segment_length {
+                        return None;
+                    }
+                    let is_field = match (
+                        field_border.is_inside_field(point![x, segment.start as f32]),
+                        field_border.is_inside_field(point![x, segment.end as f32]),
+                    ) {
+                        (true, true) => {
+                            let is_bright = segment.start_edge_type == EdgeType::Rising
+                                && segment.end_edge_type == EdgeType::Falling;
+                            if is_bright {
+                                return None;
+                            }
+                            true
+                        }
+                        (false, false) => false,
+                        _ => return None,
+                    };
+                    Some((segment.color, is_field, segment.length() as f32))
+                })
+            })
+            .collect();
+        if samples.len() < self.adaptation.minimum_samples_per_image {
+            return Some(decision_tree.clone());
+        }
+
+        // sample count, field weight and total weight per leaf
+        let mut leaves = BTreeMap::<usize, (usize, f32, f32)>::new();
+        for (color, is_field, weight) in samples {
+            let (leaf, _) = decision_tree.leaf(&FieldColorFeatures::from(color));
+            let (count, field_weight, total_weight) = leaves.entry(leaf).or_default();
+            *count += 1;
+            if is_field {
+                *field_weight += weight;
+            }
+            *total_weight += weight;
+        }
+
+        let mut adapted_decision_tree = decision_tree.clone();
+        for (leaf, (count, field_weight, total_weight)) in leaves {
+            if count >= self.adaptation.minimum_samples_per_leaf {
+                adapted_decision_tree.adapt_leaf(
+                    leaf,
+                    field_weight / total_weight,
+                    self.adaptation.learning_rate,
+                );
+            }
+        }
+        Some(adapted_decision_tree)
+    }
+}
+
+impl FieldColorDetection for FieldColorClassifier<'_> {
+    fn get_intensity(&self, color: YCbCr444) -> Intensity {
+        let Some(decision_tree) = self.decision_tree else {
+            return self.static_ranges.get_intensity(color);
+        };
+        if decision_tree.field_probability(&FieldColorFeatures::from(color))
+            >= self.field_probability_threshold
+        {
+            Intensity::High
+        } else {
+            Intensity::Low
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use linear_algebra::point;
+    use types::{
+        field_color::{FieldColorFeature, FieldColorTreeNode},
+        image_segments::{ScanLine, Segment},
+    };
+
+    use super::*;
+
+    const GREEN: YCbCr444 = YCbCr444 {
+        y: 90,
+        cb: 100,
+        cr: 100,
+    };
+    const DARK_GREEN: YCbCr444 = YCbCr444 {
+        y: 60,
+        cb: 104,
+        cr: 98,
+    };
+
+    fn adaptation() -> FieldColorAdaptationParameters {
+        FieldColorAdaptationParameters {
+            enable: true,
+            minimum_segment_length: 4,
+            minimum_samples_per_image: 2,
+            minimum_samples_per_leaf: 2,
+            learning_rate: 0.5,
+        }
+    }
+
+    fn luminance_tree() -> FieldColorTree {
+        FieldColorTree::try_from(vec![
+            FieldColorTreeNode::Split {
+                feature: FieldColorFeature::Luminance,
+                threshold: 75.0,
+                left: 1,
+                right: 2,
+            },
+            FieldColorTreeNode::Leaf {
+                field_probability: 0.1,
+            },
+            FieldColorTreeNode::Leaf {
+                field_probability: 0.95,
+            },
+        ])
+        .unwrap()
+    }
+
+    fn field_border() -> FieldBorder {
+        FieldBorder {
+            border_lines: vec![geometry::line_segment::LineSegment(
+                point![0.0, 100.0],
+                point![640.0, 100.0],
+            )],
+        }
+    }
+
+    fn segment(start: u16, end: u16, start_edge_type: EdgeType, color: YCbCr444) -> Segment {
+        Segment {
+            start,
+            end,
+            start_edge_type,
+            end_edge_type: EdgeType::ImageBorder,
+            color,
+            field_color: Intensity::Low,
+        }
+    }
+
+    fn scan_grid(segments: Vec<Segment>) -> ScanGrid {
+        let scan_line = |position| ScanLine {
+            position,
+            segments: segments.clone(),
+        };
+        ScanGrid {
+            horizontal_scan_lines: Vec::new(),
+            vertical_scan_lines: vec![scan_line(10), scan_line(20), scan_line(30)],
+        }
+    }
+
+    #[test]
+    fn decision_tree_rejects_children_before_their_parent() {
+        let nodes = vec![
+            FieldColorTreeNode::Leaf {
+                field_probability: 0.0,
+            },
+            FieldColorTreeNode::Split {
+                feature: FieldColorFeature::Luminance,
+                threshold: 10.0,
+                left: 0,
+                right: 0,
+            },
+        ];
+        assert!(FieldColorTree::try_from(nodes).is_err());
+    }
+
+    #[test]
+    fn exported_decision_tree_format_parses() {
+        let exported = r#"[
+            {"Split": {"feature": "GreenChromaticity", "threshold": 0.42, "left": 1, "right": 2}},
+            {"Leaf": {"field_probability": 0.12}},
+            {"Leaf": {"field_probability": 0.93}}
+        ]"#;
+
+        let tree: FieldColorTree = serde_json::from_str(exported).unwrap();
+
+        assert_eq!(tree.field_probability(&GREEN.into()), 0.93);
+    }
+
+    #[test]
+    fn decision_tree_follows_thresholds() {
+        let tree = luminance_tree();
+        assert_eq!(tree.field_probability(&GREEN.into()), 0.95);
+        assert_eq!(tree.field_probability(&DARK_GREEN.into()), 0.1);
+    }
+
+    #[test]
+    fn shipped_decision_tree_separates_field_from_white() {
+        let tree = load_decision_tree(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/../../../etc/field_color/field_color_tree.json"
+        ))
+        .expect("failed to load the shipped field color tree");
+        let field = YCbCr444 {
+            y: 96,
+            cb: 96,
+            cr: 112,
+        };
+        let white = YCbCr444 {
+            y: 200,
+            cb: 128,
+            cr: 128,
+        };
+
+        assert!(tree.field_probability(&field.into()) > 0.9);
+        assert!(tree.field_probability(&white.into()) < 0.1);
+    }
+
+    #[test]
+    fn adaptation_corrects_leaves_with_the_field_border() {
+        let static_ranges = FieldColorParameters::default();
+        let adaptation = adaptation();
+        let exported_tree = luminance_tree();
+        // dark field below the border, a green wall above it
+        let scan_grid = scan_grid(vec![
+            segment(0, 50, EdgeType::ImageBorder, GREEN),
+            segment(300, 400, EdgeType::Falling, DARK_GREEN),
+        ]);
+
+        let mut adapted_tree = exported_tree.clone();
+        for _ in 0..10 {
+            let classifier = FieldColorClassifier {
+                static_ranges: &static_ranges,
+                decision_tree: Some(&adapted_tree),
+                field_probability_threshold: 0.5,
+                adaptation: &adaptation,
+            };
+            adapted_tree = classifier
+                .adapt(&scan_grid, Some(&field_border()))
+                .expect("adaptation dropped the tree");
+        }
+
+        let classifier = FieldColorClassifier {
+            static_ranges: &static_ranges,
+            decision_tree: Some(&adapted_tree),
+            field_probability_threshold: 0.5,
+            adaptation: &adaptation,
+        };
+        assert_eq!(classifier.get_intensity(DARK_GREEN), Intensity::High);
+        assert_eq!(classifier.get_intensity(GREEN), Intensity::Low);
+        assert_eq!(
+            classifier.adapt(&scan_grid, None),
+            Some(adapted_tree.clone())
+        );
+
+        let disabled = FieldColorAdaptationParameters {
+            enable: false,
+            ..adaptation
+        };
+        let classifier = FieldColorClassifier {
+            adaptation: &disabled,
+            ..classifier
+        };
+        assert_eq!(classifier.adapt(&scan_grid, Some(&field_border())), None);
+    }
+
+    #[test]
+    fn bright_segments_below_field_border_are_not_learned() {
+        let static_ranges = FieldColorParameters::default();
+        let adaptation = adaptation();
+        let exported_tree = luminance_tree();
+        let line = Segment {
+            end_edge_type: EdgeType::Falling,
+            ..segment(300, 320, EdgeType::Rising, GREEN)
+        };
+        let classifier = FieldColorClassifier {
+            static_ranges: &static_ranges,
+            decision_tree: Some(&exported_tree),
+            field_probability_threshold: 0.5,
+            adaptation: &adaptation,
+        };
+
+        assert_eq!(
+            classifier.adapt(&scan_grid(vec![line]), Some(&field_border())),
+            Some(exported_tree.clone())
+        );
+    }
+}
@@ -2,26 +2,35 @@ use std::{
     boxed::Box,
     future::Future,
     ops::{Add, Range},
+    path::PathBuf,
     pin::Pin,
     sync::Arc,
 };
 
 use color_eyre::Result;
 use itertools::iproduct;
+use tracing::warn;
 
 use coordinate_systems::{Ground, Pixel};
 use linear_algebra::{Framed, Point2, Vector2, point, vector};
-use projection::{Projection, camera_matrix::CameraMatrix, horizon::Horizon};
+use projection::{
+    Projection, camera_matrix::CameraMatrix, horizon::Horizon, rolling_shutter::RowCameraMatrices,
+};
 use ros_z::prelude::*;
 use types::{
     color::{Hsv, Intensity, RgChromaticity, Rgb, YCbCr444},
-    field_color::FieldColorParameters,
+    field_border::FieldBorder,
+    field_color::{FieldColorParameters, FieldColorTree},
     image_segments::{Direction, EdgeType, ImageSegments, ScanGrid, ScanLine, Segment},
     parameters::{ImageSegmenterParameters, MedianModeParameters},
     time_wrapper::TimeWrapper,
     ycbcr422_image::YCbCr422Image,
 };
 
+use crate::field_color_classifier::{FieldColorClassifier, load_decision_tree};
+
+mod field_color_classifier;
+
 pub fn run_boxed(ctx: Arc<Context>) -> Pin<Box<dyn Future<Output = Result<()>> + Send>> {
     Box::pin(run(ctx))
 }
@@ -34,17 +43,33 @@ async fn run(ctx: Arc<Context>) -> Result<()> {
         .subscriber::<TimeWrapper<YCbCr422Image>>("inputs/ycbcr422_image")
         .build()
         .await?;
+    let row_camera_matrices_cache = node
+        .subscriber::<TimeWrapper<RowCameraMatrices>>("row_camera_matrices")
+        .cache(10)
+        .with_stamp(|w: &TimeWrapper<RowCameraMatrices>| w.time)
+        .build()
+        .await?;
     let camera_matrix_cache = node
         .subscriber::<TimeWrapper<CameraMatrix>>("camera_matrix")
         .cache(10)
         .with_stamp(|w: &TimeWrapper<CameraMatrix>| w.time)
         .build()
         .await?;
+    let field_border_cache = node
+        .subscriber::<TimeWrapper<Option<FieldBorder>>>("field_border")
+        .cache(1)
+        .build()
+        .await?;
     let image_segments_pub = node
         .publisher::<TimeWrapper<ImageSegments>>("image_segments")
         .build()
         .await?;
 
+    // the tree of the last requested path, `None` if it failed to load
+    let mut decision_tree: Option<(PathBuf, Option<FieldColorTree>)> = None;
+    // the tree with leaves adapted to the current field, reset whenever a tree is loaded
+    let mut adapted_decision_tree = None;
+
     loop {
         let parameters_snapshot = parameters.snapshot();
         let parameters = parameters_snapshot.typed();
@@ -53,14 +78,54 @@ async fn run(ctx: Arc<Context>) -> Result<()> {
         let time_stamp = timed_image.time;
         let image = timed_image.inner;
 
-        let Some(timed_camera_matrix) = camera_matrix_cache.get_nearest(time_stamp) else {
+        // without exposure timing there are no row camera matrices, every row uses the camera
+        // matrix of the image then
+        let Some(camera_matrices) = row_camera_matrices_cache
+            .get_nearest(time_stamp)
+            .map(|camera_matrices| camera_matrices.inner.clone())
+            .or_else(|| {
+                camera_matrix_cache
+                    .get_nearest(time_stamp)
+                    .map(|camera_matrix| RowCameraMatrices::global(camera_matrix.inner.clone()))
+            })
+        else {
             continue;
         };
-        let camera_matrix = &timed_camera_matrix.inner;
+        let camera_matrices = &camera_matrices;
+
+        // the horizon only bounds the scan lines, the center of the image is close enough
+        let horizon = camera_matrices
+            .center()
+            .horizon
+            .unwrap_or(Horizon::ABOVE_IMAGE);
+
+        let classifier_parameters = &parameters.field_color_classifier;
+        if classifier_parameters.use_decision_tree
+            && decision_tree
+                .as_ref()
+                .is_none_or(|(path, _)| *path != classifier_parameters.decision_tree_path)
+        {
+            let path = classifier_parameters.decision_tree_path.clone();
+            let tree = load_decision_tree(&path)
+                .inspect_err(|error| {
+                    warn!("falling back to static field color ranges: {error:#}");
+                })
+                .ok();
+            decision_tree = Some((path, tree));
+            adapted_decision_tree = None;
+        }
+        let field_color = FieldColorClassifier::new(
+            parameters,
+            decision_tree.as_ref().and_then(|(_, tree)| tree.as_ref()),
+            adapted_decision_tree.as_ref(),
+        );
 
-        let horizon = camera_matrix.horizon.unwrap_or(Horizon::ABOVE_IMAGE);
+        let scan_grid = new_grid(&image, camera_matrices, &horizon, parameters, &field_color);
 
-        let scan_grid = new_grid(&image, camera_matrix, &horizon, parameters);
+        let field_border = field_border_cache
+            .get_latest()
+            .and_then(|field_border| field_border.inner.clone());
+        adapted_decision_tree = field_color.adapt(&scan_grid, field_border.as_ref());
 
         image_segments_pub
             .publish(&TimeWrapper {
@@ -81,9 +146,10 @@ fn padding_size(median_mode: MedianModeParameters) -> u32 {
 
 fn new_grid(
     image: &YCbCr422Image,
-    camera_matrix: &CameraMatrix,
+    camera_matrices: &RowCameraMatrices,
     horizon: &Horizon,
     parameters: &ImageSegmenterParameters,
+    field_color: &impl FieldColorDetection,
 ) -> ScanGrid {
     let horizontal_padding_size = padding_size(parameters.horizontal_median_mode);
     let vertical_padding_size = padding_size(parameters.vertical_median_mode);
@@ -93,22 +159,25 @@ fn new_grid(
     let horizontal_scan_lines = match parameters.horizontal_median_mode {
         MedianModeParameters::Disabled => collect_horizontal_scan_lines::<MedianMode<0>>(
             image,
-            camera_matrix,
+            camera_matrices,
             parameters,
+            field_color,
             horizontal_padding_size,
             horizon_y_maximum,
         ),
         MedianModeParameters::ThreePixels => collect_horizontal_scan_lines::<MedianMode<3>>(
             image,
-            camera_matrix,
+            camera_matrices,
             parameters,
+            field_color,
             horizontal_padding_size,
             horizon_y_maximum,
         ),
         MedianModeParameters::FivePixels => collect_horizontal_scan_lines::<MedianMode<5>>(
             image,
-            camera_matrix,
+            camera_matrices,
             parameters,
+            field_color,
             horizontal_padding_size,
             horizon_y_maximum,
         ),
@@ -118,18 +187,21 @@ fn new_grid(
             image,
             horizon,
             parameters,
+            field_color,
             vertical_padding_size,
         ),
         MedianModeParameters::ThreePixels => collect_vertical_scan_lines::<MedianMode<3>>(
             image,
             horizon,
             parameters,
+            field_color,
             vertical_padding_size,
         ),
         MedianModeParameters::FivePixels => collect_vertical_scan_lines::<MedianMode<5>>(
             image,
             horizon,
             parameters,
+            field_color,
             vertical_padding_size,
         ),
     };
@@ -143,6 +215,7 @@ fn collect_vertical_scan_lines<MedianMode: MedianSampling>(
     image: &YCbCr422Image,
     horizon: &Horizon,
     parameters: &ImageSegmenterParameters,
+    field_color: &impl FieldColorDetection,
     vertical_padding_size: u32,
 ) -> Vec<ScanLine> {
     (vertical_padding_size..image.width() - vertical_padding_size)
@@ -151,7 +224,7 @@ fn collect_vertical_scan_lines<MedianMode: MedianSampling>(
             let horizon_y = horizon.y_at_x(x as f32).clamp(0.0, image.height() as f32);
             new_vertical_scan_line::<MedianMode>(
                 image,
-                &parameters.field_color_detection,
+                field_color,
                 x,
                 parameters.vertical_stride,
                 parameters.vertical_edge_threshold as i16,
@@ -163,8 +236,9 @@ fn collect_vertical_scan_lines<MedianMode: MedianSampling>(
 
 fn collect_horizontal_scan_lines<MedianMode: MedianSampling>(
     image: &YCbCr422Image,
-    camera_matrix: &CameraMatrix,
+    camera_matrices: &RowCameraMatrices,
     parameters: &ImageSegmenterParameters,
+    field_color: &impl FieldColorDetection,
     horizontal_padding_size: u32,
     horizon_y_maximum: u32,
 ) -> Vec<ScanLine> {
@@ -175,7 +249,7 @@ fn collect_horizontal_scan_lines<MedianMode: MedianSampling>(
     while y + horizontal_padding_size < image.height() {
         horizontal_scan_lines.push(new_horizontal_scan_line::<MedianMode>(
             image,
-            &parameters.field_color_detection,
+            field_color,
             y,
             parameters.horizontal_stride,
             parameters.horizontal_edge_threshold as i16,
@@ -183,7 +257,7 @@ fn collect_horizontal_scan_lines<MedianMode: MedianSampling>(
 
         y = next_horizontal_segment_y(
             image,
-            camera_matrix,
+            camera_matrices,
             parameters.vertical_stride_in_ground,
             y,
         )
@@ -195,10 +269,11 @@ fn collect_horizontal_scan_lines<MedianMode: MedianSampling>(
 
 fn next_horizontal_segment_y(
     image: &YCbCr422Image,
-    camera_matrix: &CameraMatrix,
+    camera_matrices: &RowCameraMatrices,
     vertical_stride: Framed<Ground, f32>,
     y: u32,
 ) -> Option<u32> {
+    let camera_matrix = camera_matrices.at_row(y as f32);
     let center_at_y = point![image.width() / 2, y].map(|x| x as f32);
     let center_in_ground = camera_matrix.pixel_to_ground(center_at_y).ok()?;
 
@@ -258,7 +333,7 @@ where
 
 fn new_horizontal_scan_line<MedianMode: MedianSampling>(
     image: &YCbCr422Image,
-    field_color: &FieldColorParameters,
+    field_color: &impl FieldColorDetection,
     position: u32,
     stride: usize,
     edge_threshold: i16,
@@ -315,7 +390,7 @@ fn new_horizontal_scan_line<MedianMode: MedianSampling>(
 #[allow(clippy::too_many_arguments)]
 fn new_vertical_scan_line<MedianMode: MedianSampling>(
     image: &YCbCr422Image,
-    field_color: &FieldColorParameters,
+    field_color: &impl FieldColorDetection,
     position: u32,
     stride: usize,
     edge_threshold: i16,
@@ -422,7 +497,10 @@ impl MedianSampling for MedianMode<5> {
     }
 }
 
-fn set_field_color_in_segment(mut segment: Segment, field_color: &FieldColorParameters) -> Segment {
+fn set_field_color_in_segment(
+    mut segment: Segment,
+    field_color: &impl FieldColorDetection,
+) -> Segment {
     segment.field_color = field_color.get_intensity(segment.color);
     segment
 }
@@ -0,0 +1,189 @@
+use std::{boxed::Box, future::Future, path::PathBuf, pin::Pin, sync::Arc, time::Duration};
+
+use color_eyre::{Result, eyre::WrapErr};
+use serde::{Deserialize, Serialize};
+use tracing::{error, info};
+
+use booster::{
+    CommandType, FallDownState, ImuState, JointsMotorState, LowCommand, MotorCommandParameters,
+    MotorState,
+};
+use filtering::low_pass_filter::LowPassFilter;
+use kinematics::joints::Joints;
+use nalgebra::Vector3;
+use ros_z::{prelude::*, time::Time};
+use types::{
+    condition_input::ConditionInput,
+    fall_state::FallState,
+    keyframe_motion::{KeyframeMotionState, KeyframeMotionStatus},
+    motion_command::MotionCommand,
+};
+
+use crate::{
+    joint_control_transport::JointControlPublisher,
+    player::{FallStateTracker, Playback, load_motion},
+};
+
+mod joint_control_transport;
+pub mod player;
+
+const MOTION_COMMAND_TOPIC: &str = "behavior/motion_command";
+
+#[derive(Debug, Clone, Serialize, Deserialize, Message)]
+#[serde(deny_unknown_fields)]
+pub struct Parameters {
+    pub motions_folder: PathBuf,
+    pub angular_velocity_smoothing_factor: f32,
+    pub motor_command: MotorCommandParameters,
+}
+
+pub fn run_boxed(ctx: Arc<Context>) -> Pin<Box<dyn Future<Output = Result<()>> + Send>> {
+    Box::pin(run(ctx))
+}
+
+async fn run(ctx: Arc<Context>) -> Result<()> {
+    let node = ctx
+        .create_node("keyframe_motion_player")
+        .build()
+        .await
+        .wrap_err("failed to create keyframe_motion_player node")?;
+    let parameters = node
+        .bind_parameter_as::<Parameters>("keyframe_motion_player")
+        .wrap_err("failed to bind keyframe_motion_player parameters")?;
+
+    let motion_command_cache = node
+        .subscriber::<MotionCommand>(MOTION_COMMAND_TOPIC)
+        .cache(1)
+        .build()
+        .await?;
+    let motor_states_cache = node
+        .subscriber::<Joints<MotorState>>("inputs/serial_motor_states")
+        .cache(1)
+        .build()
+        .await?;
+    let imu_state_cache = node
+        .subscriber::<ImuState>("inputs/imu_state")
+        .cache(1)
+        .build()
+        .await?;
+    let fall_down_state_cache = node
+        .subscriber::<FallDownState>("inputs/fall_down_state")
+        .cache(1)
+        .build()
+        .await?;
+    let status_pub = node
+        .publisher::<KeyframeMotionStatus>("keyframe_motion_player/status")
+        .build()
+        .await?;
+    let low_command_pub = node
+        .publisher::<LowCommand>("keyframe_motion_player/low_command")
+        .build()
+        .await?;
+    let joint_control_publisher = JointControlPublisher::new(ctx.session()).await?;
+
+    let mut playback: Option<Playback> = None;
+    let mut failed_motion: Option<String> = None;
+    let mut fall_state_tracker = FallStateTracker::default();
+    let mut angular_velocity_filter = LowPassFilter::with_smoothing_factor(
+        Vector3::zeros(),
+        parameters
+            .snapshot()
+            .typed()
+            .angular_velocity_smoothing_factor,
+    );
+    let mut last_update = None;
+    let mut tick = node.create_timer(Duration::from_millis(10));
+
+    loop {
+        tick.tick().await;
+        let now = node.clock().now();
+        let cycle_duration = cycle_duration_since_last_update(&mut last_update, now);
+        let parameters_snapshot = parameters.snapshot();
+        let parameters = parameters_snapshot.typed();
+
+        let imu_state = imu_state_cache.get_latest();
+        if let Some(imu_state) = &imu_state {
+            angular_velocity_filter.update(imu_state.angular_velocity.inner);
+        }
+        let fall_state = match (fall_down_state_cache.get_latest(), &imu_state) {
+            (Some(fall_down_state), Some(imu_state)) => fall_state_tracker.update(
+                fall_down_state.fall_down_state,
+                imu_state.roll_pitch_yaw.inner,
+                now.to_wallclock(),
+            ),
+            _ => FallState::Upright,
+        };
+
+        let requested_motion = match motion_command_cache.get_latest().as_deref() {
+            Some(MotionCommand::KeyframeMotion { name }) => Some(name.clone()),
+            _ => None,
+        };
+        let Some(name) = requested_motion else {
+            playback = None;
+            failed_motion = None;
+            continue;
+        };
+
+        if failed_motion.as_ref() == Some(&name) {
+            status_pub
+                .publish(&KeyframeMotionStatus {
+                    name,
+                    state: KeyframeMotionState::Aborted,
+                })
+                .await?;
+            continue;
+        }
+        if playback.as_ref().map(Playback::name) != Some(name.as_str()) {
+            let Some(motor_states) = motor_states_cache.get_latest() else {
+                continue;
+            };
+            match load_motion(&parameters.motions_folder, &name).and_then(|motion_file| {
+                Playback::start(name.clone(), motion_file, motor_states.positions())
+            }) {
+                Ok(started) => {
+                    info!(target: "keyframe_motion_player", motion = name, "starting keyframe motion");
+                    playback = Some(started);
+                    failed_motion = None;
+                }
+                Err(error) => {
+                    error!(target: "keyframe_motion_player", motion = name, "failed to start keyframe motion: {error:?}");
+                    playback = None;
+                    failed_motion = Some(name);
+                    continue;
+                }
+            }
+        }
+        let Some(playback) = playback.as_mut() else {
+            continue;
+        };
+
+        let previous_state = playback.status().state;
+        let condition_input = ConditionInput {
+            filtered_angular_velocity: angular_velocity_filter.state(),
+            fall_state,
+            // Booster robots have no foot contact sensors
+            ground_contact: true,
+        };
+        let positions = playback.advance(cycle_duration, &condition_input);
+        let status = playback.status();
+        if matches!(previous_state, KeyframeMotionState::Playing { .. })
+            && !matches!(status.state, KeyframeMotionState::Playing { .. })
+        {
+            info!(target: "keyframe_motion_player", motion = status.name, state = ?status.state, "keyframe motion ended");
+        }
+
+        let low_command =
+            LowCommand::new(&positions, &parameters.motor_command, CommandType::Serial);
+        joint_control_publisher.publish(&low_command).await?;
+        low_command_pub.publish(&low_command).await?;
+        status_pub.publish(&status).await?;
+    }
+}
+
+fn cycle_duration_since_last_update(last_update: &mut Option<Time>, now: Time) -> Duration {
+    let duration = last_update.map_or(Duration::ZERO, |last_update| {
+        now.duration_since(last_update)
+    });
+    *last_update = Some(now);
+    duration
+}
@@ -0,0 +1,297 @@
+use std::{path::Path, time::Duration, time::SystemTime};
+
+use color_eyre::{
+    Result,
+    eyre::{WrapErr, bail},
+};
+use nalgebra::Vector3;
+
+use booster::FallDownStateType;
+use kinematics::joints::Joints;
+use motionfile::{InterpolatorState, Motion2, MotionFile, MotionInterpolator};
+use types::{
+    condition_input::ConditionInput,
+    fall_state::{FallState, FallenKind, FallingDirection, Side},
+    keyframe_motion::{KeyframeMotionState, KeyframeMotionStatus},
+};
+
+/// Loads the motion `name` from `<name>.json` or, if there is none, from `<name>.motion2` in the
+/// motions folder. Only JSON motion files can specify entry, exit and interrupt conditions.
+pub fn load_motion(motions_folder: &Path, name: &str) -> Result<MotionFile<Joints<f32>>> {
+    let json_path = motions_folder.join(format!("{name}.json"));
+    if json_path.exists() {
+        return MotionFile::from_path(json_path);
+    }
+    let motion2_path = motions_folder.join(format!("{name}.motion2"));
+    if !motion2_path.exists() {
+        bail!("there is no motion {name:?} in {motions_folder:?}");
+    }
+    MotionFile::try_from(Motion2::from_path(&motion2_path)?)
+        .wrap_err_with(|| format!("failed to convert motion file {motion2_path:?}"))
+}
+
+pub struct Playback {
+    name: String,
+    interpolator: MotionInterpolator<Joints<f32>>,
+    state: InterpolatorState<Joints<f32>>,
+}
+
+impl Playback {
+    /// Starts the motion from the current joint positions instead of its initial positions so
+    /// that the first keyframe is approached without a jump.
+    pub fn start(
+        name: String,
+        motion_file: MotionFile<Joints<f32>>,
+        current_positions: Joints<f32>,
+    ) -> Result<Self> {
+        let mut interpolator = MotionInterpolator::try_from(motion_file)?;
+        interpolator.set_initial_positions(current_positions);
+        Ok(Self {
+            name,
+            interpolator,
+            state: InterpolatorState::INITIAL,
+        })
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn advance(
+        &mut self,
+        cycle_duration: Duration,
+        condition_input: &ConditionInput,
+    ) -> Joints<f32> {
+        self.interpolator
+            .advance_state(&mut self.state, cycle_duration, condition_input);
+        self.interpolator.value(self.state)
+    }
+
+    pub fn status(&self) -> KeyframeMotionStatus {
+        let state = match self.state {
+            InterpolatorState::Finished => KeyframeMotionState::Finished,
+            InterpolatorState::Aborted { .. } => KeyframeMotionState::Aborted,
+            state => KeyframeMotionState::Playing {
+                frame_index: state.current_frame_index().unwrap_or_default(),
+                remaining_duration: self
+                    .interpolator
+                    .estimated_remaining_duration(state)
+                    .unwrap_or_default(),
+            },
+        };
+        KeyframeMotionStatus {
+            name: self.name.clone(),
+            state,
+        }
+    }
+}
+
+/// Derives the fall state expected by motion conditions from the fall down state of the Booster
+/// SDK. Directions and fallen kinds are estimated from the torso orientation.
+#[derive(Default)]
+pub struct FallStateTracker {
+    fall_state: FallState,
+}
+
+impl FallStateTracker {
+    pub fn update(
+        &mut self,
+        fall_down_state: FallDownStateType,
+        roll_pitch_yaw: Vector3<f32>,
+        now: SystemTime,
+    ) -> FallState {
+        self.fall_state = match (fall_down_state, self.fall_state) {
+            (FallDownStateType::IsReady, _) => FallState::Upright,
+            (FallDownStateType::IsFalling, FallState::Falling { start_time, .. }) => {
+                FallState::Falling {
+                    start_time,
+                    direction: falling_direction(roll_pitch_yaw),
+                }
+            }
+            (FallDownStateType::IsFalling, _) => FallState::Falling {
+                start_time: now,
+                direction: falling_direction(roll_pitch_yaw),
+            },
+            (FallDownStateType::HasFallen, _) => FallState::Fallen {
+                kind: fallen_kind(roll_pitch_yaw),
+            },
+            (FallDownStateType::IsGettingUp, state @ FallState::StandingUp { .. }) => state,
+            (FallDownStateType::IsGettingUp, FallState::Fallen { kind }) => FallState::StandingUp {
+                start_time: now,
+                kind,
+            },
+            (FallDownStateType::IsGettingUp, _) => FallState::StandingUp {
+                start_time: now,
+                kind: fallen_kind(roll_pitch_yaw),
+            },
+        };
+        self.fall_state
+    }
+}
+
+fn falling_direction(roll_pitch_yaw: Vector3<f32>) -> FallingDirection {
+    let side = if roll_pitch_yaw.x > 0.0 {
+        Side::Right
+    } else {
+        Side::Left
+    };
+    if roll_pitch_yaw.y >= 0.0 {
+        FallingDirection::Forward { side }
+    } else {
+        FallingDirection::Backward { side }
+    }
+}
+
+fn fallen_kind(roll_pitch_yaw: Vector3<f32>) -> FallenKind {
+    const LYING_PITCH: f32 = std::f32::consts::FRAC_PI_4;
+    if roll_pitch_yaw.y > LYING_PITCH {
+        FallenKind::FacingDown
+    } else if roll_pitch_yaw.y < -LYING_PITCH {
+        FallenKind::FacingUp
+    } else {
+        FallenKind::Sitting
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use motionfile::{ContinuousConditionType, FallenAbort, KeyFrame, MotionFileFrame};
+    use splines::Interpolation;
+
+    use super::*;
+
+    fn motion_file(interrupt_conditions: Vec<ContinuousConditionType>) -> MotionFile<Joints<f32>> {
+        MotionFile {
+            interpolation_mode: Interpolation::Linear,
+            initial_positions: Joints::fill(0.0),
+            motion: vec![MotionFileFrame {
+                name: None,
+                entry_condition: None,
+                interrupt_conditions,
+                keyframes: vec![KeyFrame {
+                    duration: Duration::from_secs(1),
+                    positions: Joints::fill(1.0),
+                }],
+                exit_condition: None,
+            }],
+        }
+    }
+
+    #[test]
+    fn playback_blends_from_current_positions() {
+        let mut playback = Playback::start(
+            "test".to_string(),
+            motion_file(Vec::new()),
+            Joints::fill(0.5),
+        )
+        .unwrap();
+        let condition_input = ConditionInput::default();
+
+        let positions = playback.advance(Duration::ZERO, &condition_input);
+        assert_eq!(positions, Joints::fill(0.5));
+
+        let positions = playback.advance(Duration::from_millis(500), &condition_input);
+        assert!((positions.left_leg.knee - 0.75).abs() < 1e-4);
+        assert_eq!(
+            playback.status().state,
+            KeyframeMotionState::Playing {
+                frame_index: 0,
+                remaining_duration: Duration::from_millis(500),
+            }
+        );
+
+        for _ in 0..3 {
+            playback.advance(Duration::from_millis(500), &condition_input);
+        }
+        assert_eq!(playback.status().state, KeyframeMotionState::Finished);
+    }
+
+    #[test]
+    fn playback_aborts_on_interrupt_condition() {
+        let mut playback = Playback::start(
+            "test".to_string(),
+            motion_file(vec![FallenAbort {}.into()]),
+            Joints::fill(0.0),
+        )
+        .unwrap();
+
+        playback.advance(Duration::from_millis(100), &ConditionInput::default());
+        playback.advance(
+            Duration::from_millis(100),
+            &ConditionInput {
+                fall_state: FallState::Fallen {
+                    kind: FallenKind::FacingUp,
+                },
+                ..Default::default()
+            },
+        );
+
+        assert_eq!(playback.status().state, KeyframeMotionState::Aborted);
+    }
+
+    #[test]
+    fn motion2_files_are_loaded_without_json_files() {
+        let motions_folder = tempfile::tempdir().unwrap();
+        let joints = (0..22).collect::<Vec<_>>();
+        fs::write(
+            motions_folder.path().join("wave.motion2"),
+            format!(
+                r#"{{
+                    "header": {{ "joints": {joints:?}, "time": 500, "title": "wave", "version": "2.0" }},
+                    "position": [
+                        {{ "parameters": {zeros:?}, "time": 1 }},
+                        {{ "parameters": {zeros:?}, "time": 1 }}
+                    ]
+                }}"#,
+                zeros = vec![0.0; 22],
+            ),
+        )
+        .unwrap();
+
+        let motion_file = load_motion(motions_folder.path(), "wave").unwrap();
+        assert_eq!(
+            motion_file.motion[0].keyframes[0].duration,
+            Duration::from_millis(250)
+        );
+        assert!(load_motion(motions_folder.path(), "jump").is_err());
+
+        fs::write(motions_folder.path().join("wave.json"), "{}").unwrap();
+        let error = load_motion(motions_folder.path(), "wave").unwrap_err();
+        assert!(format!("{error:?}").contains("wave.json"));
+    }
+
+    #[test]
+    fn fall_state_keeps_falling_start_time() {
+        let mut tracker = FallStateTracker::default();
+        let start = SystemTime::UNIX_EPOCH;
+        let leaning_backward = Vector3::new(0.0, -0.5, 0.0);
+
+        tracker.update(FallDownStateType::IsFalling, leaning_backward, start);
+        let fall_state = tracker.update(
+            FallDownStateType::IsFalling,
+            leaning_backward,
+            start + Duration::from_secs(1),
+        );
+        assert_eq!(
+            fall_state,
+            FallState::Falling {
+                start_time: start,
+                direction: FallingDirection::Backward { side: Side::Left },
+            }
+        );
+
+        let fall_state = tracker.update(
+            FallDownStateType::HasFallen,
+            Vector3::new(0.0, -1.5, 0.0),
+            start,
+        );
+        assert_eq!(
+            fall_state,
+            FallState::Fallen {
+                kind: FallenKind::FacingUp
+            }
+        );
+    }
+}
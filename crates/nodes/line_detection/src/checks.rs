@@ -2,7 +2,7 @@ use std::ops::Range;
 
 use coordinate_systems::Pixel;
 use linear_algebra::{Point2, Vector2, distance, vector};
-use projection::{Projection, camera_matrix::CameraMatrix};
+use projection::{Projection, rolling_shutter::RowCameraMatrices};
 use types::{
     image_segments::{EdgeType, GenericSegment},
     ycbcr422_image::YCbCr422Image,
@@ -14,13 +14,15 @@ pub fn is_non_field_segment(segment: &GenericSegment) -> bool {
 
 pub fn is_in_length_range(
     segment: &GenericSegment,
-    camera_matrix: &CameraMatrix,
+    camera_matrices: &RowCameraMatrices,
     allowed_projected_segment_length: &Range<f32>,
 ) -> bool {
-    let Ok(start) = camera_matrix.pixel_to_ground(segment.start.cast()) else {
+    let pixel_to_ground =
+        |pixel: Point2<Pixel>| camera_matrices.at_row(pixel.y()).pixel_to_ground(pixel);
+    let Ok(start) = pixel_to_ground(segment.start.cast()) else {
         return false;
     };
-    let Ok(end) = camera_matrix.pixel_to_ground(segment.end.cast()) else {
+    let Ok(end) = pixel_to_ground(segment.end.cast()) else {
         return false;
     };
     allowed_projected_segment_length.contains(&distance(start, end))
@@ -112,24 +114,30 @@ fn get_gradient(
 mod tests {
     use linear_algebra::{IntoTransform, point};
     use nalgebra::{Isometry3, Translation, UnitQuaternion};
+    use projection::camera_matrix::CameraMatrix;
 
     use super::*;
 
     #[test]
     fn check_fixed_segment_size() {
         let image_size = vector![1.0, 1.0];
-        let camera_matrix = CameraMatrix::from_normalized_focal_and_center(
-            nalgebra::vector![2.0, 2.0],
-            nalgebra::point![1.0, 1.0],
-            image_size,
-            Isometry3 {
-                rotation: UnitQuaternion::from_euler_angles(0.0, std::f32::consts::PI / 4.0, 0.0),
-                translation: Translation::from(nalgebra::point![0.0, 0.0, 0.5]),
-            }
-            .framed_transform(),
-            Isometry3::identity().framed_transform(),
-            Isometry3::identity().framed_transform(),
-        );
+        let camera_matrices =
+            RowCameraMatrices::global(CameraMatrix::from_normalized_focal_and_center(
+                nalgebra::vector![2.0, 2.0],
+                nalgebra::point![1.0, 1.0],
+                image_size,
+                Isometry3 {
+                    rotation: UnitQuaternion::from_euler_angles(
+                        0.0,
+                        std::f32::consts::PI / 4.0,
+                        0.0,
+                    ),
+                    translation: Translation::from(nalgebra::point![0.0, 0.0, 0.5]),
+                }
+                .framed_transform(),
+                Isometry3::identity().framed_transform(),
+                Isometry3::identity().framed_transform(),
+            ));
 
         let segment = GenericSegment {
             start: point![40, 2],
@@ -137,7 +145,7 @@ mod tests {
             start_edge_type: EdgeType::ImageBorder,
             end_edge_type: EdgeType::ImageBorder,
         };
-        assert!(!is_in_length_range(&segment, &camera_matrix, &(0.0..0.3)));
+        assert!(!is_in_length_range(&segment, &camera_matrices, &(0.0..0.3)));
 
         let segment = GenericSegment {
             start: point![40, 364],
@@ -145,7 +153,7 @@ mod tests {
             start_edge_type: EdgeType::ImageBorder,
             end_edge_type: EdgeType::ImageBorder,
         };
-        assert!(is_in_length_range(&segment, &camera_matrix, &(0.0..0.3)));
+        assert!(is_in_length_range(&segment, &camera_matrices, &(0.0..0.3)));
     }
 
     #[test]
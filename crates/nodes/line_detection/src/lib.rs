@@ -16,7 +16,7 @@ use coordinate_systems::{Ground, Pixel};
 use geometry::{line::Line2, line_segment::LineSegment};
 use linear_algebra::{Point2, distance};
 use ordered_float::NotNan;
-use projection::{Projection, camera_matrix::CameraMatrix};
+use projection::{Projection, camera_matrix::CameraMatrix, rolling_shutter::RowCameraMatrices};
 use rand::SeedableRng;
 use rand_chacha::ChaChaRng;
 use ransac::{Ransac, RansacResult};
@@ -38,6 +38,12 @@ async fn run(ctx: Arc<Context>) -> Result<()> {
     let node = ctx.create_node("line_detection").build().await?;
 
     let parameters = node.bind_parameter_as::<LineDetectionParameters>("line_detection")?;
+    let row_camera_matrices_cache = node
+        .subscriber::<TimeWrapper<RowCameraMatrices>>("row_camera_matrices")
+        .cache(10)
+        .with_stamp(|w: &TimeWrapper<RowCameraMatrices>| w.time)
+        .build()
+        .await?;
     let camera_matrix_cache = node
         .subscriber::<TimeWrapper<CameraMatrix>>("camera_matrix")
         .cache(10)
@@ -67,7 +73,7 @@ async fn run(ctx: Arc<Context>) -> Result<()> {
         .build()
         .await?;
     let line_data_pub = node
-        .publisher::<TimeWrapper<Option<LineData>>>("line_detection/lines_in_image")
+        .publisher::<TimeWrapper<Option<LineData>>>("line_data")
         .build()
         .await?;
 
@@ -81,20 +87,31 @@ async fn run(ctx: Arc<Context>) -> Result<()> {
         let time_stamp = timed_filtered_segments.time;
         let filtered_segments = timed_filtered_segments.inner;
 
-        let (Some(timed_camera_matrix), Some(timed_image)) = (
-            camera_matrix_cache.get_nearest(time_stamp),
-            image_cache.get_nearest(time_stamp),
-        ) else {
+        // without exposure timing there are no row camera matrices, every row uses the camera
+        // matrix of the image then
+        let camera_matrices = row_camera_matrices_cache
+            .get_nearest(time_stamp)
+            .map(|camera_matrices| camera_matrices.inner.clone())
+            .or_else(|| {
+                camera_matrix_cache
+                    .get_nearest(time_stamp)
+                    .map(|camera_matrix| RowCameraMatrices::global(camera_matrix.inner.clone()))
+            });
+        let (Some(camera_matrices), Some(timed_image)) =
+            (camera_matrices, image_cache.get_nearest(time_stamp))
+        else {
             continue;
         };
         let image = &timed_image.inner;
-        let camera_matrix = &timed_camera_matrix.inner;
+        let camera_matrices = &camera_matrices;
+        // lines span many rows, they are drawn with the camera matrix of the image center
+        let camera_matrix = camera_matrices.center();
 
         let DetectLinesResult(discarded_lines, used_segments, lines_in_ground, filtered_segments) =
             detect_lines(
                 parameters,
                 &filtered_segments,
-                camera_matrix,
+                camera_matrices,
                 image,
                 &mut random_state,
             );
@@ -152,7 +169,7 @@ struct DetectLinesResult(
 fn detect_lines(
     parameters: &LineDetectionParameters,
     filtered_segments: &FilteredSegments,
-    camera_matrix: &CameraMatrix,
+    camera_matrices: &RowCameraMatrices,
     image: &YCbCr422Image,
     random_state: &mut ChaChaRng,
 ) -> DetectLinesResult {
@@ -183,7 +200,7 @@ fn detect_lines(
             !parameters.check_line_segments_projection
                 || is_in_length_range(
                     segment,
-                    camera_matrix,
+                    camera_matrices,
                     &parameters.allowed_projected_segment_length,
                 )
         })
@@ -202,9 +219,11 @@ fn detect_lines(
         filtered_segments
             .iter()
             .filter_map(|segment| {
+                let center: Point2<Pixel> = segment.center().cast();
                 Some((
-                    camera_matrix
-                        .pixel_to_ground(segment.center().cast())
+                    camera_matrices
+                        .at_row(center.y())
+                        .pixel_to_ground(center)
                         .ok()?,
                     segment.start,
                 ))
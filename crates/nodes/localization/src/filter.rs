@@ -0,0 +1,839 @@
+use std::{f32::consts::FRAC_PI_2, time::Duration};
+
+use color_eyre::{
+    Result,
+    eyre::{Context, OptionExt},
+};
+use geometry::line_segment::LineSegment;
+use linear_algebra::{IntoTransform, Isometry2, Pose2, distance, point};
+use nalgebra::{Matrix2, Matrix3, Rotation2, Vector2, Vector3};
+use ordered_float::NotNan;
+
+use booster::{FallDownStateType, ImuState, Odometer};
+use coordinate_systems::{Field, Ground};
+use filtering::pose_filter::PoseFilter;
+use hsl_network_messages::{GamePhase, Penalty, PlayerNumber, SubState, Team};
+use ros_z::time::Time;
+use types::{
+    field_dimensions::FieldDimensions,
+    field_marks::{Direction, FieldMark, field_marks_from_field_dimensions},
+    initial_pose::InitialPose,
+    line_data::LineData,
+    localization::{ScoredPose, Update},
+    multivariate_normal_distribution::MultivariateNormalDistribution,
+    primary_state::PrimaryState,
+    support_foot::Side,
+};
+
+use crate::{
+    Parameters,
+    landmarks::{
+        Landmark, find_correspondence, get_landmark_translation_measurement,
+        landmarks_from_field_dimensions,
+    },
+    line_fitting::{
+        FieldMarkCorrespondence, FitErrorsPerHypothesis, FitErrorsPerMeasurement,
+        get_2d_translation_measurement, get_fitted_field_mark_correspondence,
+        get_translation_and_rotation_measurement,
+        goal_support_structure_line_marks_from_field_dimensions,
+    },
+};
+
+pub struct Localization {
+    field_dimensions: FieldDimensions,
+    field_marks: Vec<FieldMark>,
+    landmarks: Vec<Landmark<Field>>,
+    last_primary_state: PrimaryState,
+    hypotheses: Vec<ScoredPose>,
+    hypotheses_when_entered_playing: Vec<ScoredPose>,
+    is_penalized_with_motion_in_set_or_initial: bool,
+    time_when_penalized_clicked: Option<Time>,
+    last_odometer: Option<Odometer>,
+}
+
+pub struct CycleInputs {
+    pub now: Time,
+    pub player_number: PlayerNumber,
+    pub primary_state: PrimaryState,
+    pub game_phase: Option<GamePhase>,
+    pub sub_state: Option<SubState>,
+    pub kicking_team: Option<Team>,
+    pub penalty: Option<Penalty>,
+    pub odometer: Option<Odometer>,
+    pub imu_state: ImuState,
+    pub fall_down_state: Option<FallDownStateType>,
+    pub line_data: Option<LineData>,
+    pub landmarks: Vec<Landmark<Ground>>,
+}
+
+#[derive(Default)]
+pub struct DebugOutputs {
+    pub correspondence_lines: Vec<LineSegment<Field>>,
+    pub fit_errors: FitErrorsPerMeasurement,
+    pub measured_lines_in_field: Vec<LineSegment<Field>>,
+    pub landmark_correspondence_lines: Vec<LineSegment<Field>>,
+    pub updates: Vec<Vec<Update>>,
+    pub gyro_movement: f32,
+}
+
+pub struct CycleOutputs {
+    pub ground_to_field: Option<Isometry2<Ground, Field>>,
+    pub is_localization_converged: bool,
+    pub debug: DebugOutputs,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum PenaltyExitStrategy {
+    KeepCurrent,
+    RestorePlaying,
+    ResetToPenalized,
+}
+
+struct MeasurementNoise {
+    line: Matrix2<f32>,
+    circle: Matrix2<f32>,
+    landmark: Matrix2<f32>,
+}
+
+impl Localization {
+    pub fn new(field_dimensions: FieldDimensions) -> Self {
+        Self {
+            field_marks: field_marks_from_field_dimensions(&field_dimensions)
+                .into_iter()
+                .chain(goal_support_structure_line_marks_from_field_dimensions(
+                    &field_dimensions,
+                ))
+                .collect(),
+            landmarks: landmarks_from_field_dimensions(&field_dimensions),
+            field_dimensions,
+            last_primary_state: PrimaryState::Damping,
+            hypotheses: Vec::new(),
+            hypotheses_when_entered_playing: Vec::new(),
+            is_penalized_with_motion_in_set_or_initial: false,
+            time_when_penalized_clicked: None,
+            last_odometer: None,
+        }
+    }
+
+    pub fn hypotheses(&self) -> &[ScoredPose] {
+        &self.hypotheses
+    }
+
+    pub fn cycle(&mut self, inputs: &CycleInputs, parameters: &Parameters) -> Result<CycleOutputs> {
+        self.handle_state_transition(inputs, parameters);
+        if self.hypotheses.is_empty() && primary_state_uses_localization(inputs.primary_state) {
+            self.seed_from_initial_pose(inputs.player_number, parameters);
+        }
+        self.apply_sub_state_adjustments(inputs);
+        self.last_primary_state = inputs.primary_state;
+
+        let current_odometry_to_last_odometry = self.odometry_delta(inputs);
+        let mut debug = DebugOutputs {
+            gyro_movement: inputs.imu_state.angular_velocity.norm(),
+            ..Default::default()
+        };
+
+        let ground_to_field = match inputs.primary_state {
+            PrimaryState::Initial => Some(
+                generate_initial_pose(
+                    &parameters.initial_poses[inputs.player_number],
+                    &self.field_dimensions,
+                )
+                .as_transform(),
+            ),
+            PrimaryState::Ready | PrimaryState::Set | PrimaryState::Playing => {
+                Some(self.update_active_state(
+                    inputs,
+                    parameters,
+                    current_odometry_to_last_odometry,
+                    &mut debug,
+                )?)
+            }
+            PrimaryState::Damping
+            | PrimaryState::Prepare
+            | PrimaryState::Stop
+            | PrimaryState::Penalized
+            | PrimaryState::Finished => None,
+        };
+
+        Ok(CycleOutputs {
+            ground_to_field,
+            is_localization_converged: self.hypotheses.len() == 1,
+            debug,
+        })
+    }
+
+    fn odometry_delta(&mut self, inputs: &CycleInputs) -> nalgebra::Isometry2<f32> {
+        let Some(odometer) = inputs.odometer else {
+            return nalgebra::Isometry2::identity();
+        };
+        let odometer_with_imu_yaw = Odometer {
+            theta: inputs.imu_state.roll_pitch_yaw.z(),
+            ..odometer
+        };
+        let current_odometry_to_last_odometry = self
+            .last_odometer
+            .map(|last_odometer| odometer_with_imu_yaw.to(last_odometer).inner)
+            .unwrap_or_default();
+        self.last_odometer = Some(odometer_with_imu_yaw);
+        current_odometry_to_last_odometry
+    }
+
+    fn handle_state_transition(&mut self, inputs: &CycleInputs, parameters: &Parameters) {
+        match (
+            self.last_primary_state,
+            inputs.primary_state,
+            inputs.game_phase,
+        ) {
+            (last_state, PrimaryState::Initial, _)
+                if last_state != PrimaryState::Initial && last_state != PrimaryState::Penalized =>
+            {
+                self.seed_from_initial_pose(inputs.player_number, parameters);
+            }
+            (
+                _,
+                PrimaryState::Set,
+                Some(GamePhase::PenaltyShootout {
+                    kicking_team: Team::Hulks,
+                }),
+            ) => self.seed_from_single_pose(
+                Pose2::from(point![
+                    -self.field_dimensions.penalty_area_length
+                        + (self.field_dimensions.length / 2.0),
+                    0.0,
+                ]),
+                parameters,
+            ),
+            (
+                _,
+                PrimaryState::Set | PrimaryState::Playing,
+                Some(GamePhase::PenaltyShootout {
+                    kicking_team: Team::Opponent,
+                }),
+            ) => self.seed_from_single_pose(
+                Pose2::from(point![-self.field_dimensions.length / 2.0, 0.0]),
+                parameters,
+            ),
+            (PrimaryState::Set, PrimaryState::Playing, _) => {
+                self.hypotheses_when_entered_playing
+                    .clone_from(&self.hypotheses);
+            }
+            (
+                PrimaryState::Playing | PrimaryState::Ready | PrimaryState::Set,
+                PrimaryState::Penalized,
+                _,
+            ) => {
+                self.time_when_penalized_clicked = Some(inputs.now);
+                self.is_penalized_with_motion_in_set_or_initial =
+                    matches!(inputs.penalty, Some(Penalty::MotionInSet { .. }));
+            }
+            (PrimaryState::Penalized, _, _) if inputs.primary_state != PrimaryState::Penalized => {
+                match penalty_exit_strategy(
+                    self.is_penalized_with_motion_in_set_or_initial,
+                    self.time_when_penalized_clicked,
+                    inputs.now,
+                    parameters.tentative_penalized_duration,
+                ) {
+                    PenaltyExitStrategy::KeepCurrent => {}
+                    PenaltyExitStrategy::RestorePlaying => {
+                        self.hypotheses
+                            .clone_from(&self.hypotheses_when_entered_playing);
+                    }
+                    PenaltyExitStrategy::ResetToPenalized => {
+                        self.seed_penalized_hypotheses(parameters);
+                    }
+                }
+                self.is_penalized_with_motion_in_set_or_initial = false;
+            }
+            _ => {}
+        }
+    }
+
+    fn apply_sub_state_adjustments(&mut self, inputs: &CycleInputs) {
+        if let (PlayerNumber::One, Some(SubState::PenaltyKick)) =
+            (inputs.player_number, inputs.sub_state)
+            && matches!(inputs.kicking_team, Some(Team::Opponent))
+        {
+            for hypothesis in &mut self.hypotheses {
+                hypothesis.state.mean.x = -self.field_dimensions.length / 2.0;
+            }
+        }
+    }
+
+    fn seed_hypotheses(&mut self, hypotheses: Vec<ScoredPose>) {
+        self.hypotheses = hypotheses;
+        self.hypotheses_when_entered_playing
+            .clone_from(&self.hypotheses);
+    }
+
+    fn seed_from_single_pose(&mut self, pose: Pose2<Field>, parameters: &Parameters) {
+        self.seed_hypotheses(vec![ScoredPose::from_isometry(
+            pose,
+            parameters.initial_hypothesis_covariance,
+            parameters.initial_hypothesis_score,
+        )]);
+    }
+
+    fn seed_from_initial_pose(&mut self, player_number: PlayerNumber, parameters: &Parameters) {
+        self.seed_from_single_pose(
+            generate_initial_pose(
+                &parameters.initial_poses[player_number],
+                &self.field_dimensions,
+            ),
+            parameters,
+        );
+    }
+
+    fn seed_penalized_hypotheses(&mut self, parameters: &Parameters) {
+        self.seed_hypotheses(
+            generate_penalized_poses(&self.field_dimensions, parameters.penalized_distance)
+                .into_iter()
+                .map(|pose| {
+                    ScoredPose::from_isometry(
+                        pose,
+                        parameters.penalized_hypothesis_covariance,
+                        parameters.initial_hypothesis_score,
+                    )
+                })
+                .collect(),
+        );
+    }
+
+    fn update_active_state(
+        &mut self,
+        inputs: &CycleInputs,
+        parameters: &Parameters,
+        current_odometry_to_last_odometry: nalgebra::Isometry2<f32>,
+        debug: &mut DebugOutputs,
+    ) -> Result<Isometry2<Ground, Field>> {
+        debug.updates = vec![Vec::new(); self.hypotheses.len()];
+        let measurement_noise = measurement_noise(parameters, debug.gyro_movement);
+
+        for scored_state in &mut self.hypotheses {
+            predict(
+                &mut scored_state.state,
+                current_odometry_to_last_odometry,
+                &parameters.odometry_noise,
+            )
+            .wrap_err("failed to predict pose filter")?;
+            scored_state.score *= parameters.hypothesis_prediction_score_reduction_factor;
+        }
+
+        let measurements_allowed = !matches!(
+            inputs.fall_down_state,
+            Some(
+                FallDownStateType::IsFalling
+                    | FallDownStateType::HasFallen
+                    | FallDownStateType::IsGettingUp
+            )
+        );
+        if measurements_allowed {
+            if parameters.use_line_measurements
+                && let Some(line_data) = &inputs.line_data
+            {
+                let fit_errors =
+                    self.apply_line_measurements(line_data, parameters, &measurement_noise, debug)?;
+                if !fit_errors.is_empty() {
+                    debug.fit_errors.push(fit_errors);
+                }
+            }
+            if parameters.use_landmark_measurements && !inputs.landmarks.is_empty() {
+                self.apply_landmark_measurements(
+                    &inputs.landmarks,
+                    parameters,
+                    &measurement_noise,
+                    debug,
+                )?;
+            }
+        }
+
+        let best_hypothesis = self
+            .best_hypothesis()
+            .ok_or_eyre("localization has no pose hypotheses after update")?;
+        let best_score = best_hypothesis.score;
+        let ground_to_field = best_hypothesis.state.as_isometry();
+        self.hypotheses.retain(|scored_state| {
+            scored_state.score >= parameters.hypothesis_retain_factor * best_score
+        });
+
+        Ok(ground_to_field.framed_transform())
+    }
+
+    fn apply_line_measurements(
+        &mut self,
+        line_data: &LineData,
+        parameters: &Parameters,
+        measurement_noise: &MeasurementNoise,
+        debug: &mut DebugOutputs,
+    ) -> Result<FitErrorsPerHypothesis> {
+        let mut fit_errors_per_hypothesis = Vec::with_capacity(self.hypotheses.len());
+
+        for (hypothesis_index, scored_state) in self.hypotheses.iter_mut().enumerate() {
+            let ground_to_field: Isometry2<Ground, Field> =
+                scored_state.state.as_isometry().framed_transform();
+            let measured_lines_in_field: Vec<_> = line_data
+                .lines
+                .iter()
+                .map(|&measured_line_in_ground| ground_to_field * measured_line_in_ground)
+                .collect();
+            debug
+                .measured_lines_in_field
+                .extend_from_slice(&measured_lines_in_field);
+
+            if measured_lines_in_field.is_empty() {
+                continue;
+            }
+
+            let (field_mark_correspondences, fit_error, fit_errors) =
+                get_fitted_field_mark_correspondence(
+                    &measured_lines_in_field,
+                    &self.field_marks,
+                    parameters.gradient_convergence_threshold,
+                    parameters.gradient_descent_step_size,
+                    parameters.line_length_acceptance_factor,
+                    parameters.maximum_amount_of_gradient_descent_iterations,
+                    parameters.maximum_amount_of_outer_iterations,
+                    true,
+                );
+
+            debug.correspondence_lines.extend(
+                field_mark_correspondences
+                    .iter()
+                    .flat_map(correspondence_lines),
+            );
+
+            if field_mark_correspondences.is_empty() {
+                continue;
+            }
+
+            fit_errors_per_hypothesis.push(fit_errors);
+
+            let clamped_fit_error = fit_error.max(parameters.minimum_fit_error);
+            let number_of_measurements_weight = 1.0 / field_mark_correspondences.len() as f32;
+
+            for field_mark_correspondence in field_mark_correspondences {
+                let update = match field_mark_correspondence.field_mark {
+                    FieldMark::Line { .. } => get_translation_and_rotation_measurement(
+                        ground_to_field,
+                        field_mark_correspondence,
+                    ),
+                    FieldMark::Circle { .. } => {
+                        get_2d_translation_measurement(ground_to_field, field_mark_correspondence)
+                    }
+                };
+                let line_length = field_mark_correspondence.measured_line_in_field.length();
+                let line_length_weight = if line_length == 0.0 {
+                    1.0
+                } else {
+                    1.0 / line_length
+                };
+                let line_center_point = field_mark_correspondence.measured_line_in_field.center();
+                let line_distance_to_robot =
+                    distance(line_center_point, ground_to_field.as_pose().position());
+
+                debug.updates[hypothesis_index].push(Update {
+                    ground_to_field: debug_update_pose(
+                        ground_to_field,
+                        field_mark_correspondence.field_mark,
+                        update,
+                    ),
+                    line_center_point,
+                    fit_error: clamped_fit_error,
+                    number_of_measurements_weight,
+                    line_distance_to_robot,
+                    line_length_weight,
+                });
+
+                let uncertainty_weight = clamped_fit_error
+                    * number_of_measurements_weight
+                    * line_length_weight
+                    * line_distance_to_robot;
+
+                match field_mark_correspondence.field_mark {
+                    FieldMark::Line { direction, .. } => scored_state
+                        .state
+                        .update_with_1d_translation_and_rotation(
+                            update,
+                            measurement_noise.line * uncertainty_weight,
+                            |state| match direction {
+                                Direction::PositiveX => nalgebra::vector![state.y, state.z],
+                                Direction::PositiveY => nalgebra::vector![state.x, state.z],
+                            },
+                        )
+                        .context("failed to update pose filter with line correspondence")?,
+                    FieldMark::Circle { .. } => scored_state
+                        .state
+                        .update_with_2d_translation(
+                            update,
+                            measurement_noise.circle * uncertainty_weight,
+                            |state| nalgebra::vector![state.x, state.y],
+                        )
+                        .context("failed to update pose filter with circle correspondence")?,
+                }
+
+                if field_mark_correspondence.fit_error_sum() < parameters.good_matching_threshold {
+                    scored_state.score += parameters.score_per_good_match;
+                }
+            }
+
+            scored_state.score += parameters.hypothesis_score_base_increase;
+        }
+
+        Ok(fit_errors_per_hypothesis)
+    }
+
+    fn apply_landmark_measurements(
+        &mut self,
+        measured_landmarks: &[Landmark<Ground>],
+        parameters: &Parameters,
+        measurement_noise: &MeasurementNoise,
+        debug: &mut DebugOutputs,
+    ) -> Result<()> {
+        for scored_state in &mut self.hypotheses {
+            for measured in measured_landmarks {
+                // each landmark update moves the hypothesis
+                let ground_to_field: Isometry2<Ground, Field> =
+                    scored_state.state.as_isometry().framed_transform();
+                let measured_in_field = Landmark {
+                    kind: measured.kind,
+                    position: ground_to_field * measured.position,
+                };
+                let Some(reference) = find_correspondence(
+                    measured_in_field,
+                    &self.landmarks,
+                    parameters.landmark_matching_distance,
+                ) else {
+                    if measured.kind.is_distinctive() {
+                        scored_state.score *=
+                            parameters.unmatched_distinctive_landmark_score_factor;
+                    }
+                    continue;
+                };
+                debug
+                    .landmark_correspondence_lines
+                    .push(LineSegment(measured_in_field.position, reference.position));
+
+                let update = get_landmark_translation_measurement(
+                    ground_to_field,
+                    measured.position,
+                    reference.position,
+                );
+                let distance_to_robot = measured
+                    .position
+                    .coords()
+                    .norm()
+                    .max(parameters.minimum_landmark_distance);
+                scored_state
+                    .state
+                    .update_with_2d_translation(
+                        update,
+                        measurement_noise.landmark * distance_to_robot,
+                        |state| nalgebra::vector![state.x, state.y],
+                    )
+                    .context("failed to update pose filter with landmark correspondence")?;
+                scored_state.score += parameters.score_per_landmark_match;
+            }
+        }
+        Ok(())
+    }
+
+    fn best_hypothesis(&self) -> Option<&ScoredPose> {
+        self.hypotheses
+            .iter()
+            .max_by_key(|scored_filter| NotNan::new(scored_filter.score).unwrap())
+    }
+}
+
+fn measurement_noise(parameters: &Parameters, gyro_movement: f32) -> MeasurementNoise {
+    MeasurementNoise {
+        line: Matrix2::from_diagonal(
+            &(parameters.line_measurement_noise
+                + parameters.additional_moving_noise_line * gyro_movement),
+        ),
+        circle: Matrix2::from_diagonal(
+            &(parameters.circle_measurement_noise
+                + parameters.additional_moving_noise_circle * gyro_movement),
+        ),
+        landmark: Matrix2::from_diagonal(
+            &(parameters.landmark_measurement_noise
+                + parameters.additional_moving_noise_landmark * gyro_movement),
+        ),
+    }
+}
+
+fn correspondence_lines(
+    field_mark_correspondence: &FieldMarkCorrespondence,
+) -> [LineSegment<Field>; 2] {
+    let (points_0, points_1) = field_mark_correspondence.correspondence_points;
+    [
+        LineSegment(points_0.measured, points_0.reference),
+        LineSegment(points_1.measured, points_1.reference),
+    ]
+}
+
+fn debug_update_pose(
+    ground_to_field: Isometry2<Ground, Field>,
+    field_mark: FieldMark,
+    update: Vector2<f32>,
+) -> Isometry2<Ground, Field> {
+    match field_mark {
+        FieldMark::Line {
+            direction: Direction::PositiveX,
+            ..
+        } => nalgebra::Isometry2::new(
+            nalgebra::vector![ground_to_field.translation().x(), update.x],
+            update.y,
+        ),
+        FieldMark::Line {
+            direction: Direction::PositiveY,
+            ..
+        } => nalgebra::Isometry2::new(
+            nalgebra::vector![update.x, ground_to_field.translation().y()],
+            update.y,
+        ),
+        FieldMark::Circle { .. } => {
+            nalgebra::Isometry2::new(update, ground_to_field.orientation().angle())
+        }
+    }
+    .framed_transform()
+}
+
+pub fn primary_state_uses_localization(primary_state: PrimaryState) -> bool {
+    matches!(
+        primary_state,
+        PrimaryState::Initial | PrimaryState::Ready | PrimaryState::Set | PrimaryState::Playing
+    )
+}
+
+fn penalty_exit_strategy(
+    is_penalized_with_motion_in_set_or_initial: bool,
+    time_when_penalized_clicked: Option<Time>,
+    now: Time,
+    tentative_penalized_duration: Duration,
+) -> PenaltyExitStrategy {
+    if is_penalized_with_motion_in_set_or_initial {
+        return PenaltyExitStrategy::RestorePlaying;
+    }
+
+    if time_when_penalized_clicked
+        .is_none_or(|time| now.duration_since(time) > tentative_penalized_duration)
+    {
+        PenaltyExitStrategy::ResetToPenalized
+    } else {
+        PenaltyExitStrategy::KeepCurrent
+    }
+}
+
+pub fn predict(
+    state: &mut MultivariateNormalDistribution<3>,
+    current_odometry_to_last_odometry: nalgebra::Isometry2<f32>,
+    odometry_noise: &Vector3<f32>,
+) -> Result<()> {
+    let process_noise = odometry_process_noise(
+        current_odometry_to_last_odometry,
+        state.mean.z,
+        odometry_noise,
+    );
+
+    state.predict(
+        |state| {
+            let last_ground_to_field =
+                nalgebra::Isometry2::new(nalgebra::vector![state.x, state.y], state.z);
+            let current_ground_to_field = last_ground_to_field * current_odometry_to_last_odometry;
+
+            nalgebra::vector![
+                current_ground_to_field.translation.vector.x,
+                current_ground_to_field.translation.vector.y,
+                current_ground_to_field.rotation.angle()
+            ]
+        },
+        process_noise,
+    )?;
+    Ok(())
+}
+
+fn odometry_process_noise(
+    current_odometry_to_last_odometry: nalgebra::Isometry2<f32>,
+    current_orientation_angle: f32,
+    odometry_noise: &Vector3<f32>,
+) -> Matrix3<f32> {
+    let odometry_translation = current_odometry_to_last_odometry.translation.vector;
+    let translation_noise_in_odometry_frame = odometry_translation
+        .abs()
+        .component_mul(&odometry_noise.xy());
+    let rotation_to_field = Rotation2::new(current_orientation_angle);
+    let translation_process_noise = rotation_to_field.matrix()
+        * Matrix2::from_diagonal(&translation_noise_in_odometry_frame)
+        * rotation_to_field.matrix().transpose();
+
+    let mut process_noise = Matrix3::zeros();
+    process_noise
+        .fixed_view_mut::<2, 2>(0, 0)
+        .copy_from(&translation_process_noise);
+    process_noise[(2, 2)] =
+        current_odometry_to_last_odometry.rotation.angle().abs() * odometry_noise.z;
+    process_noise
+}
+
+pub fn generate_initial_pose(
+    initial_pose: &InitialPose,
+    field_dimensions: &FieldDimensions,
+) -> Pose2<Field> {
+    match initial_pose.side {
+        Side::Left => Pose2::new(
+            point![
+                initial_pose.center_line_offset_x,
+                field_dimensions.width * 0.5
+            ],
+            -FRAC_PI_2,
+        ),
+        Side::Right => Pose2::new(
+            point![
+                initial_pose.center_line_offset_x,
+                -field_dimensions.width * 0.5
+            ],
+            FRAC_PI_2,
+        ),
+    }
+}
+
+pub fn generate_penalized_poses(
+    field_dimensions: &FieldDimensions,
+    penalized_distance: f32,
+) -> Vec<Pose2<Field>> {
+    vec![
+        Pose2::new(
+            point![
+                -field_dimensions.length * 0.5 + field_dimensions.penalty_marker_distance,
+                -field_dimensions.width * 0.5 - penalized_distance
+            ],
+            FRAC_PI_2,
+        ),
+        Pose2::new(
+            point![
+                -field_dimensions.length * 0.5 + field_dimensions.penalty_marker_distance,
+                field_dimensions.width * 0.5 + penalized_distance
+            ],
+            -FRAC_PI_2,
+        ),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use approx::assert_relative_eq;
+    use nalgebra::{matrix, vector};
+
+    use crate::{field_dimensions, landmarks::LandmarkKind};
+
+    use super::*;
+
+    fn parameters() -> Parameters {
+        json5::from_str(include_str!(
+            "../../../../etc/parameters/ros_z/base/localization.json5"
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn penalty_exit_strategy_depends_on_penalty_and_duration() {
+        let penalized = Time::from_nanos(1_000_000_000);
+        let tentative_duration = Duration::from_secs(10);
+        let after = |secs| penalized + Duration::from_secs(secs);
+
+        assert_eq!(
+            penalty_exit_strategy(true, Some(penalized), after(1), tentative_duration),
+            PenaltyExitStrategy::RestorePlaying
+        );
+        assert_eq!(
+            penalty_exit_strategy(false, Some(penalized), after(11), tentative_duration),
+            PenaltyExitStrategy::ResetToPenalized
+        );
+        assert_eq!(
+            penalty_exit_strategy(false, Some(penalized), after(9), tentative_duration),
+            PenaltyExitStrategy::KeepCurrent
+        );
+        assert_eq!(
+            penalty_exit_strategy(false, None, after(9), tentative_duration),
+            PenaltyExitStrategy::ResetToPenalized
+        );
+    }
+
+    #[test]
+    fn standing_still_adds_no_odometry_process_noise() {
+        let process_noise = odometry_process_noise(
+            nalgebra::Isometry2::identity(),
+            0.3,
+            &vector![0.02, 0.02, 0.001],
+        );
+
+        assert_relative_eq!(process_noise, Matrix3::zeros(), epsilon = 0.0001);
+    }
+
+    #[test]
+    fn rotational_process_noise_scales_with_actual_turn() {
+        let process_noise = odometry_process_noise(
+            nalgebra::Isometry2::new(vector![0.0, 0.0], 0.2),
+            0.0,
+            &vector![0.02, 0.02, 0.001],
+        );
+
+        assert_relative_eq!(
+            process_noise,
+            matrix![
+                0.0, 0.0, 0.0;
+                0.0, 0.0, 0.0;
+                0.0, 0.0, 0.0002
+            ],
+            epsilon = 0.0001
+        );
+    }
+
+    #[test]
+    fn goal_post_resolves_penalized_hypotheses() {
+        let parameters = parameters();
+        let field_dimensions = field_dimensions();
+        let mut localization = Localization::new(field_dimensions);
+        localization.seed_penalized_hypotheses(&parameters);
+        localization.last_primary_state = PrimaryState::Playing;
+        let [right_sideline_pose, _] =
+            generate_penalized_poses(&field_dimensions, parameters.penalized_distance)[..]
+        else {
+            panic!("expected two penalized poses");
+        };
+
+        // seen from the right sideline, the goal is to the left of the robot
+        let goal_post_in_field = point![-7.025, -1.35];
+        let ground_to_field: Isometry2<Ground, Field> = right_sideline_pose.as_transform();
+        let goal_post_in_ground = ground_to_field.inverse() * goal_post_in_field;
+
+        let mut inputs = CycleInputs {
+            now: Time::from_nanos(0),
+            player_number: PlayerNumber::Two,
+            primary_state: PrimaryState::Playing,
+            game_phase: None,
+            sub_state: None,
+            kicking_team: None,
+            penalty: None,
+            odometer: None,
+            imu_state: ImuState::default(),
+            fall_down_state: None,
+            line_data: None,
+            landmarks: vec![Landmark {
+                kind: LandmarkKind::GoalPost,
+                position: goal_post_in_ground,
+            }],
+        };
+        for _ in 0..3 {
+            inputs.now = inputs.now + Duration::from_millis(10);
+            localization.cycle(&inputs, &parameters).unwrap();
+        }
+
+        assert_eq!(localization.hypotheses().len(), 1);
+        assert_relative_eq!(
+            localization.hypotheses()[0].state.mean.xy(),
+            right_sideline_pose.position().inner.coords,
+            epsilon = 0.01
+        );
+    }
+}
@@ -0,0 +1,277 @@
+use geometry::{line_segment::LineSegment, rectangle::Rectangle};
+use linear_algebra::{Isometry2, Point2, distance, point};
+use nalgebra::Vector2;
+
+use coordinate_systems::{Field, Ground};
+use projection::{Projection, camera_matrix::CameraMatrix};
+use types::{
+    field_dimensions::FieldDimensions,
+    field_marks::{Direction, FieldMark, field_marks_from_field_dimensions},
+    object_detection::{Object, RobocupObjectLabel},
+};
+
+const ON_LINE_TOLERANCE: f32 = 0.001;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LandmarkKind {
+    LSpot,
+    TSpot,
+    XSpot,
+    PenaltySpot,
+    GoalPost,
+}
+
+impl LandmarkKind {
+    fn from_label(label: RobocupObjectLabel) -> Option<Self> {
+        match label {
+            RobocupObjectLabel::LSpot => Some(Self::LSpot),
+            RobocupObjectLabel::TSpot => Some(Self::TSpot),
+            RobocupObjectLabel::XSpot => Some(Self::XSpot),
+            RobocupObjectLabel::PenaltySpot => Some(Self::PenaltySpot),
+            RobocupObjectLabel::GoalPost => Some(Self::GoalPost),
+            RobocupObjectLabel::Ball | RobocupObjectLabel::Robot => None,
+        }
+    }
+
+    /// Distinctive landmarks only exist close to the goals. Seeing one without a counterpart
+    /// nearby is strong evidence against a pose hypothesis, e.g. for the mirrored hypothesis
+    /// after a penalty.
+    pub fn is_distinctive(self) -> bool {
+        matches!(self, Self::PenaltySpot | Self::GoalPost)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Landmark<Frame> {
+    pub kind: LandmarkKind,
+    pub position: Point2<Frame>,
+}
+
+/// Reference landmarks at the intersections of the field lines, the center circle and the goal
+/// posts.
+pub fn landmarks_from_field_dimensions(field_dimensions: &FieldDimensions) -> Vec<Landmark<Field>> {
+    let lines: Vec<_> = field_marks_from_field_dimensions(field_dimensions)
+        .into_iter()
+        .filter_map(|field_mark| match field_mark {
+            FieldMark::Line { line, direction } => Some((line, direction)),
+            FieldMark::Circle { .. } => None,
+        })
+        .collect();
+    let is_penalty_marker = |line: &LineSegment<Field>| {
+        line.length() <= field_dimensions.penalty_marker_size + ON_LINE_TOLERANCE
+    };
+
+    let line_intersections = lines
+        .iter()
+        .filter(|(_, direction)| matches!(direction, Direction::PositiveX))
+        .flat_map(|(horizontal, _)| {
+            lines
+                .iter()
+                .filter(|(_, direction)| matches!(direction, Direction::PositiveY))
+                .filter_map(move |(vertical, _)| {
+                    let intersection = point![vertical.0.x(), horizontal.0.y()];
+                    let horizontal_contact = contact(horizontal, intersection)?;
+                    let vertical_contact = contact(vertical, intersection)?;
+                    let kind = match (horizontal_contact, vertical_contact) {
+                        (Contact::Endpoint, Contact::Endpoint) => LandmarkKind::LSpot,
+                        (Contact::Endpoint, Contact::Interior)
+                        | (Contact::Interior, Contact::Endpoint) => LandmarkKind::TSpot,
+                        (Contact::Interior, Contact::Interior)
+                            if is_penalty_marker(horizontal) && is_penalty_marker(vertical) =>
+                        {
+                            LandmarkKind::PenaltySpot
+                        }
+                        (Contact::Interior, Contact::Interior) => LandmarkKind::XSpot,
+                    };
+                    Some(Landmark {
+                        kind,
+                        position: intersection,
+                    })
+                })
+        });
+
+    let center_circle_radius = field_dimensions.center_circle_diameter / 2.0;
+    let center_circle_intersections = [-1.0, 1.0].map(|sign| Landmark {
+        kind: LandmarkKind::XSpot,
+        position: point![0.0, sign * center_circle_radius],
+    });
+
+    let goal_post_x = field_dimensions.length / 2.0 + field_dimensions.goal_post_diameter / 2.0
+        - field_dimensions.line_width / 2.0;
+    let goal_post_y =
+        field_dimensions.goal_inner_width / 2.0 + field_dimensions.goal_post_diameter / 2.0;
+    let goal_posts =
+        [(-1.0, -1.0), (-1.0, 1.0), (1.0, -1.0), (1.0, 1.0)].map(|(x_sign, y_sign)| Landmark {
+            kind: LandmarkKind::GoalPost,
+            position: point![x_sign * goal_post_x, y_sign * goal_post_y],
+        });
+
+    line_intersections
+        .chain(center_circle_intersections)
+        .chain(goal_posts)
+        .collect()
+}
+
+enum Contact {
+    Endpoint,
+    Interior,
+}
+
+fn contact(line: &LineSegment<Field>, point: Point2<Field>) -> Option<Contact> {
+    if distance(line.closest_point(point), point) > ON_LINE_TOLERANCE {
+        return None;
+    }
+    if distance(line.0, point) <= ON_LINE_TOLERANCE || distance(line.1, point) <= ON_LINE_TOLERANCE
+    {
+        Some(Contact::Endpoint)
+    } else {
+        Some(Contact::Interior)
+    }
+}
+
+/// Projects landmark detections onto the ground. Spots lie on the ground at the center of their
+/// bounding box, goal posts stand on the ground at the bottom center of theirs.
+pub fn project_landmarks(
+    detected_objects: &[Object<RobocupObjectLabel>],
+    camera_matrix: &CameraMatrix,
+    confidence_threshold: f32,
+) -> Vec<Landmark<Ground>> {
+    detected_objects
+        .iter()
+        .filter(|object| object.bounding_box.confidence >= confidence_threshold)
+        .filter_map(|object| {
+            let kind = LandmarkKind::from_label(object.label)?;
+            let Rectangle { min, max } = object.bounding_box.area;
+            let position_in_image = match kind {
+                LandmarkKind::GoalPost => point![min.x() + (max.x() - min.x()) / 2.0, max.y()],
+                _ => object.bounding_box.area.center(),
+            };
+            let position = camera_matrix.pixel_to_ground(position_in_image).ok()?;
+            Some(Landmark { kind, position })
+        })
+        .collect()
+}
+
+/// Finds the closest reference landmark of the same kind within the matching distance.
+pub fn find_correspondence(
+    measured_in_field: Landmark<Field>,
+    references: &[Landmark<Field>],
+    matching_distance: f32,
+) -> Option<Landmark<Field>> {
+    references
+        .iter()
+        .filter(|reference| reference.kind == measured_in_field.kind)
+        .map(|reference| {
+            (
+                reference,
+                distance(reference.position, measured_in_field.position),
+            )
+        })
+        .filter(|(_, distance)| *distance <= matching_distance)
+        .min_by(|(_, left), (_, right)| left.total_cmp(right))
+        .map(|(reference, _)| *reference)
+}
+
+/// Robot position in the field for which the measured landmark coincides with its reference,
+/// keeping the orientation of the hypothesis.
+pub fn get_landmark_translation_measurement(
+    ground_to_field: Isometry2<Ground, Field>,
+    measured_in_ground: Point2<Ground>,
+    reference: Point2<Field>,
+) -> Vector2<f32> {
+    let measured_offset_in_field =
+        ground_to_field * measured_in_ground - ground_to_field.as_pose().position();
+    (reference - measured_offset_in_field).inner.coords
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::FRAC_PI_2;
+
+    use approx::assert_relative_eq;
+
+    use crate::field_dimensions;
+
+    use super::*;
+
+    fn count(landmarks: &[Landmark<Field>], kind: LandmarkKind) -> usize {
+        landmarks
+            .iter()
+            .filter(|landmark| landmark.kind == kind)
+            .count()
+    }
+
+    #[test]
+    fn reference_landmarks_are_classified_by_line_intersections() {
+        let landmarks = landmarks_from_field_dimensions(&field_dimensions());
+
+        assert_eq!(count(&landmarks, LandmarkKind::LSpot), 12);
+        assert_eq!(count(&landmarks, LandmarkKind::TSpot), 10);
+        assert_eq!(count(&landmarks, LandmarkKind::XSpot), 2);
+        assert_eq!(count(&landmarks, LandmarkKind::PenaltySpot), 2);
+        assert_eq!(count(&landmarks, LandmarkKind::GoalPost), 4);
+        assert!(landmarks.contains(&Landmark {
+            kind: LandmarkKind::PenaltySpot,
+            position: point![-4.9, 0.0],
+        }));
+        assert!(landmarks.contains(&Landmark {
+            kind: LandmarkKind::TSpot,
+            position: point![0.0, 4.5],
+        }));
+    }
+
+    #[test]
+    fn correspondence_requires_same_kind_within_matching_distance() {
+        let references = landmarks_from_field_dimensions(&field_dimensions());
+        let measured = |kind, position| Landmark { kind, position };
+
+        assert_eq!(
+            find_correspondence(
+                measured(LandmarkKind::PenaltySpot, point![-4.7, 0.1]),
+                &references,
+                0.5
+            ),
+            Some(Landmark {
+                kind: LandmarkKind::PenaltySpot,
+                position: point![-4.9, 0.0],
+            })
+        );
+        assert_eq!(
+            find_correspondence(
+                measured(LandmarkKind::PenaltySpot, point![-4.0, 0.0]),
+                &references,
+                0.5
+            ),
+            None
+        );
+        assert_eq!(
+            find_correspondence(
+                measured(LandmarkKind::LSpot, point![0.0, 4.4]),
+                &references,
+                0.5
+            ),
+            None
+        );
+        assert_eq!(
+            find_correspondence(
+                measured(LandmarkKind::PenaltySpot, point![f32::NAN, 0.0]),
+                &references,
+                0.5
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn landmark_measurement_moves_robot_onto_reference() {
+        let ground_to_field = Isometry2::from_parts(point![1.0, 2.0].coords(), FRAC_PI_2);
+
+        let update = get_landmark_translation_measurement(
+            ground_to_field,
+            point![1.0, 0.0],
+            point![1.5, 3.0],
+        );
+
+        assert_relative_eq!(update, nalgebra::vector![1.5, 2.0], epsilon = 0.0001);
+    }
+}
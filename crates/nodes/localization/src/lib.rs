@@ -10,17 +10,30 @@ use coordinate_systems::{Field, Ground};
 use geometry::line_segment::LineSegment;
 use hsl_network_messages::PlayerNumber;
 use linear_algebra::Isometry2;
+use projection::camera_matrix::CameraMatrix;
 use ros_z::{prelude::*, qos::QosDurability};
+use ros_z_streams::CreateFutureMapBuilder;
 use types::{
     field_dimensions::FieldDimensions,
     filtered_game_controller_state::FilteredGameControllerState,
     initial_pose::InitialPose,
     line_data::LineData,
     localization::{ScoredPose, Update},
+    object_detection::{Object, RobocupObjectLabel},
     players::Players,
     primary_state::PrimaryState,
+    time_wrapper::TimeWrapper,
 };
 
+use crate::{
+    filter::{CycleInputs, Localization},
+    landmarks::project_landmarks,
+};
+
+pub mod filter;
+pub mod landmarks;
+pub mod line_fitting;
+
 #[derive(Debug, Clone, Serialize, Deserialize, Message)]
 #[serde(deny_unknown_fields)]
 pub struct Parameters {
@@ -47,6 +60,14 @@ pub struct Parameters {
     pub score_per_good_match: f32,
     pub tentative_penalized_duration: Duration,
     pub use_line_measurements: bool,
+    pub landmark_confidence_threshold: f32,
+    pub landmark_matching_distance: f32,
+    pub landmark_measurement_noise: na::Vector2<f32>,
+    pub minimum_landmark_distance: f32,
+    pub additional_moving_noise_landmark: na::Vector2<f32>,
+    pub score_per_landmark_match: f32,
+    pub unmatched_distinctive_landmark_score_factor: f32,
+    pub use_landmark_measurements: bool,
 }
 
 pub fn run_boxed(ctx: Arc<Context>) -> Pin<Box<dyn Future<Output = Result<()>> + Send>> {
@@ -56,69 +77,96 @@ pub fn run_boxed(ctx: Arc<Context>) -> Pin<Box<dyn Future<Output = Result<()>> +
 async fn run(ctx: Arc<Context>) -> Result<()> {
     let node = ctx.create_node("localization").build().await?;
 
-    let _parameters = node.bind_parameter_as::<Parameters>("localization")?;
-    let _filtered_game_controller_state_sub = node
+    let parameters = node.bind_parameter_as::<Parameters>("localization")?;
+    let filtered_game_controller_state_sub = node
         .subscriber::<FilteredGameControllerState>("filtered_game_controller_state")
+        .cache(1)
         .build()
         .await?;
-    let _primary_state_sub = node
+    let primary_state_sub = node
         .subscriber::<PrimaryState>("primary_state")
         .qos(QosProfile {
             durability: QosDurability::TransientLocal,
             ..Default::default()
         })
+        .cache(1)
         .build()
         .await?;
-    let _odometer_sub = node
-        .subscriber::<Odometer>("inputs/odometer")
-        .build()
-        .await?;
-    let _fall_down_state_sub = node
+    let fall_down_state_sub = node
         .subscriber::<FallDownState>("inputs/fall_down_state")
+        .cache(1)
         .build()
         .await?;
-    let _imu_state_sub = node
+    let imu_state_sub = node
         .subscriber::<ImuState>("inputs/imu_state")
+        .cache(1)
+        .build()
+        .await?;
+    let line_data_sub = node
+        .subscriber::<TimeWrapper<Option<LineData>>>("line_data")
+        .cache(1)
+        .with_stamp(|wrapper: &TimeWrapper<Option<LineData>>| wrapper.time)
         .build()
         .await?;
-    let _line_data_sub = node.subscriber::<LineData>("line_data").build().await?;
-    let _field_dimensions_sub = node
+    let camera_matrix_cache = node
+        .subscriber::<TimeWrapper<CameraMatrix>>("camera_matrix")
+        .cache(10)
+        .with_stamp(|wrapper: &TimeWrapper<CameraMatrix>| wrapper.time)
+        .build()
+        .await?;
+    let field_dimensions_sub = node
         .subscriber::<FieldDimensions>("field_dimensions")
         .qos(QosProfile {
             durability: QosDurability::TransientLocal,
             ..Default::default()
         })
+        .cache(1)
         .build()
         .await?;
-    let _player_number_sub = node
+    let player_number_sub = node
         .subscriber::<PlayerNumber>("player_number")
         .qos(QosProfile {
             durability: QosDurability::TransientLocal,
             ..Default::default()
         })
+        .cache(1)
         .build()
         .await?;
-    let _correspondence_lines_pub = node
+    let mut future_map = node
+        .create_future_map_builder()
+        .create_future_subscriber::<Odometer>("inputs/odometer", Duration::from_millis(1))
+        .await?
+        .create_future_subscriber::<Vec<Object<RobocupObjectLabel>>>(
+            "detected_objects",
+            Duration::from_millis(1),
+        )
+        .await?
+        .build();
+    let correspondence_lines_pub = node
         .publisher::<Vec<LineSegment<Field>>>("localization/correspondence_lines")
         .build()
         .await?;
-    let _fit_errors_pub = node
+    let landmark_correspondence_lines_pub = node
+        .publisher::<Vec<LineSegment<Field>>>("localization/landmark_correspondence_lines")
+        .build()
+        .await?;
+    let fit_errors_pub = node
         .publisher::<Vec<Vec<Vec<Vec<f32>>>>>("localization/fit_errors")
         .build()
         .await?;
-    let _measured_lines_in_field_pub = node
+    let measured_lines_in_field_pub = node
         .publisher::<Vec<LineSegment<Field>>>("localization/measured_lines_in_field")
         .build()
         .await?;
-    let _pose_hypotheses_pub = node
+    let pose_hypotheses_pub = node
         .publisher::<Vec<ScoredPose>>("localization/pose_hypotheses")
         .build()
         .await?;
-    let _updates_pub = node
+    let updates_pub = node
         .publisher::<Vec<Vec<Update>>>("localization/updates")
         .build()
         .await?;
-    let _gyro_movement_pub = node
+    let gyro_movement_pub = node
         .publisher::<f32>("localization/gyro_movement")
         .build()
         .await?;
@@ -131,14 +179,118 @@ async fn run(ctx: Arc<Context>) -> Result<()> {
         .build()
         .await?;
 
-    let ground_to_field = Isometry2::identity();
-    let is_localization_converged = true;
+    let mut localization = None;
+    let mut last_line_data_time = None;
 
     loop {
-        ground_to_field_pub.publish(&ground_to_field).await?;
+        let parameters_snapshot = parameters.snapshot();
+        let parameters = parameters_snapshot.typed();
+
+        let future_map_item = future_map.recv().await?;
+
+        let (Some(field_dimensions), Some(player_number), Some(primary_state)) = (
+            field_dimensions_sub.get_latest(),
+            player_number_sub.get_latest(),
+            primary_state_sub.get_latest(),
+        ) else {
+            continue;
+        };
+        let localization = localization.get_or_insert_with(|| Localization::new(*field_dimensions));
+
+        let mut odometer = None;
+        let mut landmarks = Vec::new();
+        for (time, (new_odometer, detected_objects)) in future_map_item.persistent {
+            odometer = new_odometer.or(odometer);
+            if let Some(detected_objects) = detected_objects
+                && let Some(camera_matrix) = camera_matrix_cache.get_nearest(time)
+            {
+                landmarks.extend(project_landmarks(
+                    &detected_objects,
+                    &camera_matrix.inner,
+                    parameters.landmark_confidence_threshold,
+                ));
+            }
+        }
+
+        let line_data = line_data_sub
+            .get_latest()
+            .filter(|line_data| last_line_data_time < Some(line_data.time))
+            .and_then(|line_data| {
+                last_line_data_time = Some(line_data.time);
+                line_data.inner.clone()
+            });
+
+        let game_controller_state = filtered_game_controller_state_sub.get_latest();
+        let inputs = CycleInputs {
+            now: node.clock().now(),
+            player_number: *player_number,
+            primary_state: *primary_state,
+            game_phase: game_controller_state.as_ref().map(|state| state.game_phase),
+            sub_state: game_controller_state
+                .as_ref()
+                .and_then(|state| state.sub_state),
+            kicking_team: game_controller_state
+                .as_ref()
+                .and_then(|state| state.kicking_team),
+            penalty: game_controller_state
+                .as_ref()
+                .and_then(|state| state.penalties[*player_number]),
+            odometer,
+            imu_state: imu_state_sub
+                .get_latest()
+                .map(|imu_state| *imu_state)
+                .unwrap_or_default(),
+            fall_down_state: fall_down_state_sub
+                .get_latest()
+                .map(|fall_down_state| fall_down_state.fall_down_state),
+            line_data,
+            landmarks,
+        };
+
+        let outputs = localization.cycle(&inputs, parameters)?;
+
+        correspondence_lines_pub
+            .publish(&outputs.debug.correspondence_lines)
+            .await?;
+        landmark_correspondence_lines_pub
+            .publish(&outputs.debug.landmark_correspondence_lines)
+            .await?;
+        fit_errors_pub.publish(&outputs.debug.fit_errors).await?;
+        measured_lines_in_field_pub
+            .publish(&outputs.debug.measured_lines_in_field)
+            .await?;
+        updates_pub.publish(&outputs.debug.updates).await?;
+        gyro_movement_pub
+            .publish(&outputs.debug.gyro_movement)
+            .await?;
+        pose_hypotheses_pub
+            .publish(&localization.hypotheses().to_vec())
+            .await?;
+        // while not localizing (e.g. penalized), subscribers keep the last pose
+        if let Some(ground_to_field) = outputs.ground_to_field {
+            ground_to_field_pub.publish(&ground_to_field).await?;
+        }
         is_localization_converged_pub
-            .publish(&is_localization_converged)
+            .publish(&outputs.is_localization_converged)
             .await?;
-        node.clock().timer(Duration::from_millis(5)).tick().await;
+    }
+}
+
+#[cfg(test)]
+fn field_dimensions() -> FieldDimensions {
+    FieldDimensions {
+        length: 14.0,
+        width: 9.0,
+        line_width: 0.05,
+        penalty_marker_size: 0.1,
+        goal_box_area_length: 1.0,
+        goal_box_area_width: 4.0,
+        penalty_area_length: 3.0,
+        penalty_area_width: 6.0,
+        penalty_marker_distance: 2.1,
+        center_circle_diameter: 3.0,
+        goal_inner_width: 2.6,
+        goal_post_diameter: 0.1,
+        ..Default::default()
     }
 }
@@ -0,0 +1,601 @@
+use geometry::line_segment::LineSegment;
+use linear_algebra::{IntoTransform, Isometry2, distance, point};
+use nalgebra::{Matrix2, Rotation2, Vector2, matrix};
+use ordered_float::NotNan;
+
+use coordinate_systems::{Field, Ground};
+use types::{
+    field_dimensions::FieldDimensions,
+    field_marks::{CorrespondencePoints, Direction, FieldMark},
+};
+
+pub type FitErrorsPerGradientStep = Vec<f32>;
+pub type FitErrorsPerOuterIteration = Vec<FitErrorsPerGradientStep>;
+pub type FitErrorsPerHypothesis = Vec<FitErrorsPerOuterIteration>;
+pub type FitErrorsPerMeasurement = Vec<FitErrorsPerHypothesis>;
+
+#[derive(Clone, Copy, Debug)]
+pub struct FieldMarkCorrespondence {
+    pub measured_line_in_field: LineSegment<Field>,
+    pub field_mark: FieldMark,
+    pub correspondence_points: (CorrespondencePoints, CorrespondencePoints),
+}
+
+impl FieldMarkCorrespondence {
+    pub fn fit_error_sum(&self) -> f32 {
+        (self.correspondence_points.0.measured - self.correspondence_points.0.reference).norm()
+            + (self.correspondence_points.1.measured - self.correspondence_points.1.reference)
+                .norm()
+    }
+}
+
+pub fn goal_support_structure_line_marks_from_field_dimensions(
+    field_dimensions: &FieldDimensions,
+) -> Vec<FieldMark> {
+    let goal_width = field_dimensions.goal_inner_width + field_dimensions.goal_post_diameter;
+    let goal_depth = field_dimensions.goal_depth;
+    vec![
+        FieldMark::Line {
+            line: LineSegment(
+                point![
+                    -field_dimensions.length / 2.0 - goal_depth,
+                    -goal_width / 2.0
+                ],
+                point![
+                    -field_dimensions.length / 2.0 - goal_depth,
+                    goal_width / 2.0
+                ],
+            ),
+            direction: Direction::PositiveY,
+        },
+        FieldMark::Line {
+            line: LineSegment(
+                point![
+                    -field_dimensions.length / 2.0 - goal_depth,
+                    -goal_width / 2.0
+                ],
+                point![-field_dimensions.length / 2.0, -goal_width / 2.0],
+            ),
+            direction: Direction::PositiveX,
+        },
+        FieldMark::Line {
+            line: LineSegment(
+                point![
+                    -field_dimensions.length / 2.0 - goal_depth,
+                    goal_width / 2.0
+                ],
+                point![-field_dimensions.length / 2.0, goal_width / 2.0],
+            ),
+            direction: Direction::PositiveX,
+        },
+        FieldMark::Line {
+            line: LineSegment(
+                point![
+                    field_dimensions.length / 2.0 + goal_depth,
+                    -goal_width / 2.0
+                ],
+                point![field_dimensions.length / 2.0 + goal_depth, goal_width / 2.0],
+            ),
+            direction: Direction::PositiveY,
+        },
+        FieldMark::Line {
+            line: LineSegment(
+                point![field_dimensions.length / 2.0, -goal_width / 2.0],
+                point![
+                    field_dimensions.length / 2.0 + goal_depth,
+                    -goal_width / 2.0
+                ],
+            ),
+            direction: Direction::PositiveX,
+        },
+        FieldMark::Line {
+            line: LineSegment(
+                point![field_dimensions.length / 2.0, goal_width / 2.0],
+                point![field_dimensions.length / 2.0 + goal_depth, goal_width / 2.0],
+            ),
+            direction: Direction::PositiveX,
+        },
+    ]
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn get_fitted_field_mark_correspondence(
+    measured_lines_in_field: &[LineSegment<Field>],
+    field_marks: &[FieldMark],
+    gradient_convergence_threshold: f32,
+    gradient_descent_step_size: f32,
+    line_length_acceptance_factor: f32,
+    maximum_amount_of_gradient_descent_iterations: usize,
+    maximum_amount_of_outer_iterations: usize,
+    collect_fit_errors: bool,
+) -> (
+    Vec<FieldMarkCorrespondence>,
+    f32,
+    FitErrorsPerOuterIteration,
+) {
+    if measured_lines_in_field.is_empty() || field_marks.is_empty() {
+        return (Vec::new(), f32::INFINITY, Vec::new());
+    }
+
+    let mut fit_errors = Vec::new();
+    let mut correction = nalgebra::Isometry2::identity();
+
+    for _ in 0..maximum_amount_of_outer_iterations {
+        let correspondence_points = get_correspondence_points(get_field_mark_correspondence(
+            measured_lines_in_field,
+            correction,
+            field_marks,
+            line_length_acceptance_factor,
+        ));
+        if correspondence_points.is_empty() {
+            return (Vec::new(), f32::INFINITY, fit_errors);
+        }
+
+        let weight_matrices = weight_matrices(&correspondence_points, correction);
+        let mut fit_errors_per_iteration = Vec::new();
+
+        for _ in 0..maximum_amount_of_gradient_descent_iterations {
+            let translation_gradient: Vector2<f32> = correspondence_points
+                .iter()
+                .zip(weight_matrices.iter())
+                .map(|(correspondence_points, weight_matrix)| {
+                    2.0 * weight_matrix
+                        * ((correction * correspondence_points.measured.inner)
+                            - correspondence_points.reference.inner)
+                })
+                .sum::<Vector2<f32>>()
+                / correspondence_points.len() as f32;
+            let rotation = correction.rotation.angle();
+            let rotation_derivative =
+                matrix![-rotation.sin(), -rotation.cos(); rotation.cos(), -rotation.sin()];
+            let rotation_gradient: f32 = correspondence_points
+                .iter()
+                .zip(weight_matrices.iter())
+                .map(|(correspondence_points, weight_matrix)| {
+                    (2.0 * correspondence_points.measured.inner.coords.transpose()
+                        * rotation_derivative.transpose()
+                        * weight_matrix
+                        * ((correction * correspondence_points.measured.inner)
+                            - correspondence_points.reference.inner))
+                        .x
+                })
+                .sum::<f32>()
+                / correspondence_points.len() as f32;
+
+            correction = nalgebra::Isometry2::new(
+                correction.translation.vector - (gradient_descent_step_size * translation_gradient),
+                rotation - gradient_descent_step_size * rotation_gradient,
+            );
+
+            if collect_fit_errors {
+                fit_errors_per_iteration.push(get_fit_error(
+                    &correspondence_points,
+                    &weight_matrices,
+                    correction,
+                ));
+            }
+
+            let gradient_norm = nalgebra::vector![
+                translation_gradient.x,
+                translation_gradient.y,
+                rotation_gradient
+            ]
+            .norm();
+            if gradient_norm < gradient_convergence_threshold {
+                break;
+            }
+        }
+
+        if collect_fit_errors {
+            fit_errors.push(fit_errors_per_iteration);
+        }
+    }
+
+    let field_mark_correspondences = get_field_mark_correspondence(
+        measured_lines_in_field,
+        correction,
+        field_marks,
+        line_length_acceptance_factor,
+    );
+    let correspondence_points = get_correspondence_points(field_mark_correspondences.clone());
+    if correspondence_points.is_empty() {
+        return (Vec::new(), f32::INFINITY, fit_errors);
+    }
+
+    let fit_error = get_fit_error(
+        &correspondence_points,
+        &weight_matrices(&correspondence_points, correction),
+        correction,
+    );
+
+    (field_mark_correspondences, fit_error, fit_errors)
+}
+
+fn weight_matrices(
+    correspondence_points: &[CorrespondencePoints],
+    correction: nalgebra::Isometry2<f32>,
+) -> Vec<Matrix2<f32>> {
+    correspondence_points
+        .iter()
+        .map(|correspondence_points| {
+            let normal = (correction * correspondence_points.measured.inner)
+                - correspondence_points.reference.inner;
+            if normal.norm() > 0.0 {
+                let normal_versor = normal.normalize();
+                normal_versor * normal_versor.transpose()
+            } else {
+                Matrix2::zeros()
+            }
+        })
+        .collect()
+}
+
+fn get_fit_error(
+    correspondence_points: &[CorrespondencePoints],
+    weight_matrices: &[Matrix2<f32>],
+    correction: nalgebra::Isometry2<f32>,
+) -> f32 {
+    if correspondence_points.is_empty() {
+        return f32::INFINITY;
+    }
+
+    correspondence_points
+        .iter()
+        .zip(weight_matrices.iter())
+        .map(|(correspondence_points, weight_matrix)| {
+            ((correction * correspondence_points.measured.inner
+                - correspondence_points.reference.inner)
+                .transpose()
+                * weight_matrix
+                * (correction * correspondence_points.measured.inner
+                    - correspondence_points.reference.inner))
+                .x
+        })
+        .sum::<f32>()
+        / correspondence_points.len() as f32
+}
+
+fn get_field_mark_correspondence(
+    measured_lines_in_field: &[LineSegment<Field>],
+    correction: nalgebra::Isometry2<f32>,
+    field_marks: &[FieldMark],
+    line_length_acceptance_factor: f32,
+) -> Vec<FieldMarkCorrespondence> {
+    measured_lines_in_field
+        .iter()
+        .filter_map(|&measured_line_in_field| {
+            let (correspondences, _weight, field_mark, transformed_line) = field_marks
+                .iter()
+                .filter_map(|field_mark| {
+                    let transformed_line = correction.framed_transform() * measured_line_in_field;
+                    let field_mark_length = match field_mark {
+                        FieldMark::Line { line, .. } => line.length(),
+                        FieldMark::Circle { radius, .. } => *radius,
+                    };
+                    if field_mark_length <= 0.0 {
+                        return None;
+                    }
+
+                    let measured_line_length = transformed_line.length();
+                    if measured_line_length > field_mark_length * line_length_acceptance_factor {
+                        return None;
+                    }
+
+                    let correspondences = field_mark.to_correspondence_points(transformed_line);
+                    let angle_weight = correspondences
+                        .measured_direction
+                        .dot(&correspondences.reference_direction)
+                        .abs()
+                        + measured_line_length / field_mark_length;
+                    let length_weight = measured_line_length / field_mark_length;
+                    let weight = angle_weight + length_weight;
+
+                    (weight > 0.0).then_some((
+                        correspondences,
+                        weight,
+                        field_mark,
+                        transformed_line,
+                    ))
+                })
+                .min_by_key(
+                    |(correspondence_points, weight, _field_mark, _transformed_line)| {
+                        (NotNan::new(
+                            distance(
+                                correspondence_points.correspondence_points.0.measured,
+                                correspondence_points.correspondence_points.0.reference,
+                            ) + distance(
+                                correspondence_points.correspondence_points.1.measured,
+                                correspondence_points.correspondence_points.1.reference,
+                            ),
+                        )
+                        .unwrap())
+                            / *weight
+                    },
+                )?;
+            let inverse_transformation = correction.inverse().framed_transform();
+            Some(FieldMarkCorrespondence {
+                measured_line_in_field: inverse_transformation * transformed_line,
+                field_mark: *field_mark,
+                correspondence_points: (
+                    CorrespondencePoints {
+                        measured: inverse_transformation
+                            * correspondences.correspondence_points.0.measured,
+                        reference: correspondences.correspondence_points.0.reference,
+                    },
+                    CorrespondencePoints {
+                        measured: inverse_transformation
+                            * correspondences.correspondence_points.1.measured,
+                        reference: correspondences.correspondence_points.1.reference,
+                    },
+                ),
+            })
+        })
+        .collect()
+}
+
+fn get_correspondence_points(
+    field_mark_correspondences: Vec<FieldMarkCorrespondence>,
+) -> Vec<CorrespondencePoints> {
+    field_mark_correspondences
+        .iter()
+        .flat_map(|field_mark_correspondence| {
+            [
+                field_mark_correspondence.correspondence_points.0,
+                field_mark_correspondence.correspondence_points.1,
+            ]
+        })
+        .collect()
+}
+
+pub fn get_translation_and_rotation_measurement(
+    ground_to_field: Isometry2<Ground, Field>,
+    field_mark_correspondence: FieldMarkCorrespondence,
+) -> Vector2<f32> {
+    let (field_mark_line, field_mark_line_direction) = match field_mark_correspondence.field_mark {
+        FieldMark::Line { line, direction } => (line, direction),
+        FieldMark::Circle { .. } => unreachable!("line measurement requested for circle mark"),
+    };
+    let measured_line_in_field = match field_mark_line_direction {
+        Direction::PositiveX
+            if field_mark_correspondence.measured_line_in_field.1.x()
+                < field_mark_correspondence.measured_line_in_field.0.x() =>
+        {
+            LineSegment(
+                field_mark_correspondence.measured_line_in_field.1,
+                field_mark_correspondence.measured_line_in_field.0,
+            )
+        }
+        Direction::PositiveY
+            if field_mark_correspondence.measured_line_in_field.1.y()
+                < field_mark_correspondence.measured_line_in_field.0.y() =>
+        {
+            LineSegment(
+                field_mark_correspondence.measured_line_in_field.1,
+                field_mark_correspondence.measured_line_in_field.0,
+            )
+        }
+        _ => field_mark_correspondence.measured_line_in_field,
+    };
+    let measured_line_in_field_vector = measured_line_in_field.1 - measured_line_in_field.0;
+    let signed_distance_to_line =
+        measured_line_in_field.signed_distance_to_point(ground_to_field.as_pose().position());
+    match field_mark_line_direction {
+        Direction::PositiveX => nalgebra::vector![
+            field_mark_line.0.y() + signed_distance_to_line,
+            (-measured_line_in_field_vector.y()).atan2(measured_line_in_field_vector.x())
+                + ground_to_field.orientation().angle()
+        ],
+        Direction::PositiveY => nalgebra::vector![
+            field_mark_line.0.x() - signed_distance_to_line,
+            measured_line_in_field_vector
+                .x()
+                .atan2(measured_line_in_field_vector.y())
+                + ground_to_field.orientation().angle()
+        ],
+    }
+}
+
+pub fn get_2d_translation_measurement(
+    ground_to_field: Isometry2<Ground, Field>,
+    field_mark_correspondence: FieldMarkCorrespondence,
+) -> Vector2<f32> {
+    let measured_line_vector = field_mark_correspondence.correspondence_points.1.measured
+        - field_mark_correspondence.correspondence_points.0.measured;
+    let reference_line_vector = field_mark_correspondence.correspondence_points.1.reference
+        - field_mark_correspondence.correspondence_points.0.reference;
+    let measured_line_point_0_to_robot_vector = ground_to_field.as_pose().position()
+        - field_mark_correspondence.correspondence_points.0.measured;
+    let measured_rotation = f32::atan2(
+        measured_line_point_0_to_robot_vector.y() * measured_line_vector.x()
+            - measured_line_point_0_to_robot_vector.x() * measured_line_vector.y(),
+        measured_line_point_0_to_robot_vector.x() * measured_line_vector.x()
+            + measured_line_point_0_to_robot_vector.y() * measured_line_vector.y(),
+    );
+
+    let reference_line_point_0_to_robot_vector = Rotation2::new(measured_rotation)
+        * reference_line_vector.normalize().inner
+        * measured_line_point_0_to_robot_vector.norm();
+    let reference_robot_point = field_mark_correspondence
+        .correspondence_points
+        .0
+        .reference
+        .inner
+        + reference_line_point_0_to_robot_vector;
+    reference_robot_point.coords
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::FRAC_PI_4;
+
+    use approx::assert_relative_eq;
+    use linear_algebra::Point2;
+
+    use super::*;
+
+    fn correspondence(
+        measured_line_in_field: LineSegment<Field>,
+        field_mark: FieldMark,
+    ) -> FieldMarkCorrespondence {
+        FieldMarkCorrespondence {
+            measured_line_in_field,
+            field_mark,
+            correspondence_points: (
+                CorrespondencePoints {
+                    measured: Point2::origin(),
+                    reference: Point2::origin(),
+                },
+                CorrespondencePoints {
+                    measured: Point2::origin(),
+                    reference: Point2::origin(),
+                },
+            ),
+        }
+    }
+
+    #[test]
+    fn empty_measurements_produce_no_correspondence() {
+        let (correspondences, fit_error, fit_errors) = get_fitted_field_mark_correspondence(
+            &[],
+            &[FieldMark::Line {
+                line: LineSegment(point![0.0, 0.0], point![1.0, 0.0]),
+                direction: Direction::PositiveX,
+            }],
+            0.001,
+            0.1,
+            1.5,
+            8,
+            4,
+            true,
+        );
+
+        assert!(correspondences.is_empty());
+        assert!(fit_error.is_infinite());
+        assert!(fit_errors.is_empty());
+    }
+
+    #[test]
+    fn zero_length_field_marks_are_ignored() {
+        let correspondences = get_field_mark_correspondence(
+            &[LineSegment(point![0.0, 0.0], point![1.0, 0.0])],
+            nalgebra::Isometry2::identity(),
+            &[FieldMark::Circle {
+                center: Point2::origin(),
+                radius: 0.0,
+            }],
+            1.5,
+        );
+
+        assert!(correspondences.is_empty());
+    }
+
+    #[test]
+    fn fitting_line_results_in_zero_measurement() {
+        let ground_to_field = Isometry2::identity();
+        let vertical_mark = FieldMark::Line {
+            line: LineSegment(point![0.0, -3.0], point![0.0, 3.0]),
+            direction: Direction::PositiveY,
+        };
+        let horizontal_mark = FieldMark::Line {
+            line: LineSegment(point![-3.0, 0.0], point![3.0, 0.0]),
+            direction: Direction::PositiveX,
+        };
+
+        for field_mark_correspondence in [
+            correspondence(
+                LineSegment(point![0.0, 0.0], point![0.0, 1.0]),
+                vertical_mark,
+            ),
+            correspondence(
+                LineSegment(point![0.0, 1.0], point![0.0, 0.0]),
+                vertical_mark,
+            ),
+            correspondence(
+                LineSegment(point![0.0, 0.0], point![1.0, 0.0]),
+                horizontal_mark,
+            ),
+        ] {
+            let update = get_translation_and_rotation_measurement(
+                ground_to_field,
+                field_mark_correspondence,
+            );
+            assert_relative_eq!(update, Vector2::zeros());
+        }
+    }
+
+    #[test]
+    fn translated_line_results_in_translation_measurement() {
+        let update = get_translation_and_rotation_measurement(
+            Isometry2::identity(),
+            correspondence(
+                LineSegment(point![1.0, 0.0], point![1.0, 1.0]),
+                FieldMark::Line {
+                    line: LineSegment(point![0.0, -3.0], point![0.0, 3.0]),
+                    direction: Direction::PositiveY,
+                },
+            ),
+        );
+        assert_relative_eq!(update, nalgebra::vector![-1.0, 0.0]);
+    }
+
+    #[test]
+    fn rotated_line_results_in_rotation_measurement() {
+        let update = get_translation_and_rotation_measurement(
+            Isometry2::identity(),
+            correspondence(
+                LineSegment(point![-1.0, -1.0], point![1.0, 1.0]),
+                FieldMark::Line {
+                    line: LineSegment(point![0.0, -3.0], point![0.0, 3.0]),
+                    direction: Direction::PositiveY,
+                },
+            ),
+        );
+        assert_relative_eq!(update, nalgebra::vector![0.0, FRAC_PI_4]);
+    }
+
+    #[test]
+    fn correct_correspondence_points() {
+        let correspondences = get_field_mark_correspondence(
+            &[LineSegment(point![0.0, 0.0], point![1.0, 0.0])],
+            nalgebra::Isometry2::identity(),
+            &[FieldMark::Line {
+                line: LineSegment(point![0.0, 0.0], point![1.0, 0.0]),
+                direction: Direction::PositiveX,
+            }],
+            1.5,
+        );
+        assert_eq!(correspondences.len(), 1);
+        assert_relative_eq!(
+            correspondences[0].correspondence_points.0.measured,
+            point![0.0, 0.0]
+        );
+        assert_relative_eq!(
+            correspondences[0].correspondence_points.0.reference,
+            point![0.0, 0.0]
+        );
+    }
+
+    #[test]
+    fn circle_mark_correspondence_translates() {
+        let field_mark_correspondence = FieldMarkCorrespondence {
+            measured_line_in_field: LineSegment(Point2::origin(), Point2::origin()),
+            field_mark: FieldMark::Circle {
+                center: Point2::origin(),
+                radius: 0.0,
+            },
+            correspondence_points: (
+                CorrespondencePoints {
+                    measured: point![0.0, 1.0],
+                    reference: point![0.0, 0.0],
+                },
+                CorrespondencePoints {
+                    measured: point![1.0, 1.0],
+                    reference: point![1.0, 0.0],
+                },
+            ),
+        };
+        let update =
+            get_2d_translation_measurement(Isometry2::identity(), field_mark_correspondence);
+        assert_relative_eq!(update, nalgebra::vector![0.0, -1.0], epsilon = 0.0001);
+    }
+}
@@ -3,7 +3,7 @@ use std::{boxed::Box, future::Future, pin::Pin};
 
 use color_eyre::Result;
 
-use hsl_network_messages::{HulkMessage, PlayerNumber, StateMessage};
+use hsl_network_messages::{HulkMessage, PlayerNumber, StateMessage, VisualRefereeMessage};
 use ros_z::{prelude::*, qos::QosDurability};
 use types::{messages::IncomingMessage, time_wrapper::TimeWrapper};
 
@@ -40,7 +40,8 @@ async fn run(ctx: Arc<Context>) -> Result<()> {
                 let time_wrapped_message = received_time_wrapped_message?;
 
                 let should_filter_message_out = matches!(time_wrapped_message.inner, IncomingMessage::Hsl(
-                        HulkMessage::State(StateMessage { player_number, .. }),
+                        HulkMessage::State(StateMessage { player_number, .. })
+                        | HulkMessage::VisualReferee(VisualRefereeMessage { player_number, .. }),
                     ) if player_number == current_player_number);
 
                 if !should_filter_message_out {
@@ -6,7 +6,7 @@ use filtering::kalman_filter::KalmanFilter;
 use geometry::rectangle::Rectangle;
 use hsl_network_messages::PlayerNumber;
 use itertools::{chain, iproduct};
-use na::Matrix2;
+use na::{Matrix2, Matrix2x4, Matrix4, Matrix4x2, Vector4};
 use nalgebra as na;
 use serde::{Deserialize, Serialize};
 
@@ -26,6 +26,7 @@ use types::{
     players::Players,
     pose_detection::Pose,
     primary_state::PrimaryState,
+    stereo_depth::StereoObject,
     time_wrapper::TimeWrapper,
     world_state::PlayerState,
 };
@@ -34,6 +35,7 @@ use types::{
 struct ObstacleFilter {
     hypotheses: Vec<Hypothesis>,
     last_primary_state: PrimaryState,
+    last_prediction: Option<Time>,
 }
 
 impl Default for ObstacleFilter {
@@ -41,6 +43,7 @@ impl Default for ObstacleFilter {
         Self {
             hypotheses: Vec::new(),
             last_primary_state: PrimaryState::Damping,
+            last_prediction: None,
         }
     }
 }
@@ -128,6 +131,11 @@ async fn run(ctx: Arc<Context>) -> Result<()> {
             Duration::from_millis(50),
         )
         .await?
+        .create_future_subscriber::<Vec<StereoObject<RobocupObjectLabel>>>(
+            "stereo_objects",
+            Duration::from_millis(50),
+        )
+        .await?
         .build();
 
     let obstacle_filter_hypotheses_pub = node
@@ -148,9 +156,12 @@ async fn run(ctx: Arc<Context>) -> Result<()> {
                 let Some(field_dimensions) = field_dimensions_cache.get_latest() else {
                     continue;
                 };
-                for (detection_time, (detected_objects, detected_poses)) in item.persistent {
+                for (detection_time, (detected_objects, detected_poses, stereo_objects)) in
+                    item.persistent
+                {
                     let detected_objects = detected_objects.unwrap_or_default();
                     let detected_poses = detected_poses.unwrap_or_default();
+                    let stereo_objects = stereo_objects.unwrap_or_default();
                     let camera_matrix = camera_matrix_cache.get_nearest(detection_time);
                     let current_odometry_to_last_odometry =
                         current_odometry_to_last_odometry_cache.get_nearest(detection_time);
@@ -161,6 +172,7 @@ async fn run(ctx: Arc<Context>) -> Result<()> {
                         parameters,
                         &detected_objects,
                         &detected_poses,
+                        &stereo_objects,
                         camera_matrix.as_ref().map(|wrapper| &wrapper.inner),
                         current_odometry_to_last_odometry
                             .as_ref()
@@ -258,22 +270,34 @@ impl ObstacleFilter {
         parameters: &ObstacleFilterParameters,
         detected_objects: &[Object<RobocupObjectLabel>],
         detected_poses: &[Pose<YOLOObjectLabel>],
+        stereo_objects: &[StereoObject<RobocupObjectLabel>],
         camera_matrix: Option<&CameraMatrix>,
         current_odometry_to_last_odometry: Option<&na::Isometry2<f32>>,
     ) {
         let current_odometry_to_last_odometry = current_odometry_to_last_odometry
             .copied()
             .unwrap_or_default();
+        let elapsed = self
+            .last_prediction
+            .map(|last_prediction| detection_time.duration_since(last_prediction))
+            .unwrap_or_default();
+        self.last_prediction = Some(detection_time);
         self.predict_hypotheses_with_odometry(
             current_odometry_to_last_odometry.inverse(),
+            elapsed,
             Matrix2::from_diagonal(&parameters.process_noise),
+            Matrix2::from_diagonal(&parameters.velocity_process_noise),
         );
 
         if let Some(camera_matrix) = camera_matrix
             && parameters.use_detected_objects
         {
-            let measured_object_positions =
-                measured_object_positions(parameters, detected_objects, camera_matrix);
+            let measured_object_positions = measured_object_positions(
+                parameters,
+                detected_objects,
+                stereo_objects,
+                camera_matrix,
+            );
             let measured_pose_positions =
                 measured_pose_positions(parameters, detected_poses, camera_matrix);
 
@@ -281,11 +305,12 @@ impl ObstacleFilter {
                 measured_object_positions.chain(measured_pose_positions)
             {
                 self.update_hypotheses_with_measurement(
+                    parameters,
                     position,
                     kind,
                     detection_time,
                     parameters.object_detection_measurement_matching_distance,
-                    Matrix2::from_diagonal(&measurement_noise),
+                    measurement_noise,
                     MeasurementKind::Own,
                 );
             }
@@ -301,6 +326,7 @@ impl ObstacleFilter {
     ) {
         let player_position = measured_player_position(player_state, ground_to_field);
         self.update_hypotheses_with_measurement(
+            parameters,
             player_position,
             ObstacleKind::Robot,
             player_state_time,
@@ -368,11 +394,19 @@ impl ObstacleFilter {
                     ),
                     _ => panic!("Unexpected obstacle radius"),
                 };
+                let velocity = if hypothesis.obstacle_kind == ObstacleKind::GoalPost {
+                    na::Vector2::zeros()
+                } else {
+                    hypothesis
+                        .velocity()
+                        .cap_magnitude(parameters.maximum_obstacle_velocity)
+                };
                 Obstacle {
-                    position: hypothesis.state.mean.framed().as_point(),
+                    position: hypothesis.position().framed().as_point(),
                     kind: hypothesis.obstacle_kind,
                     radius_at_hip_height,
                     radius_at_foot_height,
+                    velocity: velocity.framed(),
                 }
             });
         let goal_posts = calculate_goal_post_positions(ground_to_field, field_dimensions);
@@ -383,28 +417,55 @@ impl ObstacleFilter {
         chain!(obstacles, goal_post_obstacles).collect()
     }
 
+    /// Moves every hypothesis along its velocity for `elapsed` and into the current odometry frame.
     fn predict_hypotheses_with_odometry(
         &mut self,
         last_odometry_to_current_odometry: na::Isometry2<f32>,
+        elapsed: Duration,
         process_noise: Matrix2<f32>,
+        velocity_process_noise: Matrix2<f32>,
     ) {
+        let rotation = *last_odometry_to_current_odometry
+            .rotation
+            .to_rotation_matrix()
+            .matrix();
+        let odometry_translation = last_odometry_to_current_odometry.translation.vector;
+        let control_input_model = Matrix4x2::identity();
         for hypothesis in &mut self.hypotheses {
-            let state_prediction = last_odometry_to_current_odometry
-                .rotation
-                .to_rotation_matrix();
-            let control_input_model = Matrix2::identity();
-            let odometry_translation = last_odometry_to_current_odometry.translation.vector;
+            // goal posts never move, a velocity would only let them drift
+            let elapsed = if hypothesis.obstacle_kind == ObstacleKind::GoalPost {
+                0.0
+            } else {
+                elapsed.as_secs_f32()
+            };
+            let mut state_prediction = Matrix4::zeros();
+            state_prediction
+                .fixed_view_mut::<2, 2>(0, 0)
+                .copy_from(&rotation);
+            state_prediction
+                .fixed_view_mut::<2, 2>(0, 2)
+                .copy_from(&(rotation * elapsed));
+            state_prediction
+                .fixed_view_mut::<2, 2>(2, 2)
+                .copy_from(&rotation);
+            let mut noise = Matrix4::zeros();
+            noise.fixed_view_mut::<2, 2>(0, 0).copy_from(&process_noise);
+            noise
+                .fixed_view_mut::<2, 2>(2, 2)
+                .copy_from(&(velocity_process_noise * elapsed));
             hypothesis.state.predict(
-                *state_prediction.matrix(),
+                state_prediction,
                 control_input_model,
                 odometry_translation,
-                process_noise,
-            )
+                noise,
+            );
         }
     }
 
+    #[allow(clippy::too_many_arguments)]
     fn update_hypotheses_with_measurement(
         &mut self,
+        parameters: &ObstacleFilterParameters,
         detected_position: Point2<Ground>,
         detected_obstacle_kind: ObstacleKind,
         detection_time: Time,
@@ -416,7 +477,7 @@ impl ObstacleFilter {
             .hypotheses
             .iter_mut()
             .filter(|hypothesis| {
-                (hypothesis.state.mean - detected_position.inner.coords).norm() < matching_distance
+                (hypothesis.position() - detected_position.inner.coords).norm() < matching_distance
             })
             .peekable();
         if matching_hypotheses.peek().is_none() {
@@ -425,12 +486,13 @@ impl ObstacleFilter {
                 detected_obstacle_kind,
                 detection_time,
                 measurement_noise,
+                parameters.maximum_obstacle_velocity,
             );
             return;
         }
         matching_hypotheses.for_each(|hypothesis| {
             hypothesis.state.update(
-                Matrix2::identity(),
+                Matrix2x4::identity(),
                 detected_position.inner.coords,
                 if kind == MeasurementKind::NetworkRobot {
                     measurement_noise
@@ -456,11 +518,21 @@ impl ObstacleFilter {
         obstacle_kind: ObstacleKind,
         detection_time: Time,
         initial_covariance: Matrix2<f32>,
+        maximum_velocity: f32,
     ) {
+        let initial_velocity_variance = if obstacle_kind == ObstacleKind::GoalPost {
+            0.0
+        } else {
+            maximum_velocity.powi(2)
+        };
+        let mut covariance = Matrix4::from_diagonal_element(initial_velocity_variance);
+        covariance
+            .fixed_view_mut::<2, 2>(0, 0)
+            .copy_from(&initial_covariance);
         let new_hypothesis = Hypothesis {
             state: MultivariateNormalDistribution {
-                mean: detected_position.inner.coords,
-                covariance: initial_covariance,
+                mean: Vector4::new(detected_position.x(), detected_position.y(), 0.0, 0.0),
+                covariance,
             },
             obstacle_kind,
             measurement_count: 1,
@@ -479,15 +551,15 @@ impl ObstacleFilter {
                 deduplicated_hypotheses
                     .iter_mut()
                     .find(|existing_hypothesis| {
-                        (existing_hypothesis.state.mean - hypothesis.state.mean).norm()
+                        (existing_hypothesis.position() - hypothesis.position()).norm()
                             < merge_distance
                     });
             match hypothesis_in_merge_distance {
                 Some(existing_hypothesis) => {
                     existing_hypothesis.state.update(
-                        Matrix2::identity(),
-                        hypothesis.state.mean,
-                        hypothesis.state.covariance,
+                        Matrix2x4::identity(),
+                        hypothesis.position(),
+                        hypothesis.position_covariance(),
                     );
                     existing_hypothesis.obstacle_kind = match existing_hypothesis.obstacle_kind {
                         ObstacleKind::Robot | ObstacleKind::GoalPost | ObstacleKind::Person => {
@@ -507,8 +579,9 @@ impl ObstacleFilter {
 fn measured_object_positions(
     parameters: &ObstacleFilterParameters,
     detected_objects: &[Object<RobocupObjectLabel>],
+    stereo_objects: &[StereoObject<RobocupObjectLabel>],
     camera_matrix: &CameraMatrix,
-) -> impl Iterator<Item = (ObstacleKind, Point2<Ground>, na::Vector2<f32>)> {
+) -> impl Iterator<Item = (ObstacleKind, Point2<Ground>, Matrix2<f32>)> {
     detected_objects.iter().filter_map(|detected_object| {
         let Object {
             label,
@@ -532,8 +605,34 @@ fn measured_object_positions(
 
         let obstacle_center: Point2<Ground> =
             camera_matrix.pixel_to_ground(bottom_center_position).ok()?;
+        let monocular_measurement = MultivariateNormalDistribution {
+            mean: obstacle_center.inner.coords,
+            covariance: Matrix2::from_diagonal(&measurement_noise),
+        };
+
+        // the stereo position is taken at the center of the bounding box, its ground projection
+        // is the visible front of the obstacle just like the bottom center of the bounding box
+        let measurement = stereo_objects
+            .iter()
+            .filter(|_| parameters.use_stereo_depth)
+            .find(|stereo_object| stereo_object.object.bounding_box.area == bounding_box.area)
+            .and_then(|stereo_object| {
+                monocular_measurement.fuse(&MultivariateNormalDistribution {
+                    mean: stereo_object.position_in_ground.mean.xy(),
+                    covariance: stereo_object
+                        .position_in_ground
+                        .covariance
+                        .fixed_view::<2, 2>(0, 0)
+                        .into_owned(),
+                })
+            })
+            .unwrap_or(monocular_measurement);
 
-        Some((kind, obstacle_center, measurement_noise))
+        Some((
+            kind,
+            measurement.mean.framed().as_point(),
+            measurement.covariance,
+        ))
     })
 }
 
@@ -541,7 +640,7 @@ fn measured_pose_positions(
     parameters: &ObstacleFilterParameters,
     detected_poses: &[Pose<YOLOObjectLabel>],
     camera_matrix: &CameraMatrix,
-) -> impl Iterator<Item = (ObstacleKind, Point2<Ground>, na::Vector2<f32>)> {
+) -> impl Iterator<Item = (ObstacleKind, Point2<Ground>, Matrix2<f32>)> {
     detected_poses.iter().filter_map(|detected_pose| {
         let Object {
             label,
@@ -564,7 +663,11 @@ fn measured_pose_positions(
             let obstacle_center: Point2<Ground> =
                 camera_matrix.pixel_to_ground(feet_center_point).ok()?;
 
-            Some((kind, obstacle_center, measurement_noise))
+            Some((
+                kind,
+                obstacle_center,
+                Matrix2::from_diagonal(&measurement_noise),
+            ))
         } else if bounding_box.confidence > parameters.person_object_confidence_threshold {
             let bottom_center_position = {
                 let Rectangle { min, max } = bounding_box.area;
@@ -575,7 +678,11 @@ fn measured_pose_positions(
             let obstacle_center: Point2<Ground> =
                 camera_matrix.pixel_to_ground(bottom_center_position).ok()?;
 
-            Some((kind, obstacle_center, measurement_noise))
+            Some((
+                kind,
+                obstacle_center,
+                Matrix2::from_diagonal(&measurement_noise),
+            ))
         } else {
             None
         }
@@ -666,6 +773,7 @@ mod tests {
             ObstacleKind::Robot,
             detection_time,
             nalgebra::Matrix2::identity(),
+            1.0,
         );
 
         assert_eq!(filter.hypotheses.len(), 1);
@@ -674,6 +782,89 @@ mod tests {
         assert_eq!(filter.hypotheses[0].last_update, detection_time);
     }
 
+    fn track(
+        parameters: &ObstacleFilterParameters,
+        kind: ObstacleKind,
+        position_at: impl Fn(f32) -> Point2<Ground>,
+    ) -> ObstacleFilter {
+        let mut filter = ObstacleFilter::default();
+        for step in 0..20 {
+            filter.predict_hypotheses_with_odometry(
+                na::Isometry2::identity(),
+                Duration::from_millis(100),
+                nalgebra::Matrix2::identity() * 1e-4,
+                Matrix2::from_diagonal(&parameters.velocity_process_noise),
+            );
+            filter.update_hypotheses_with_measurement(
+                parameters,
+                position_at(0.1 * step as f32),
+                kind,
+                Time::from_nanos(step * 100_000_000),
+                0.5,
+                nalgebra::Matrix2::identity() * 1e-4,
+                MeasurementKind::NetworkRobot,
+            );
+        }
+        filter
+    }
+
+    #[test]
+    fn repeated_measurements_of_a_moving_robot_estimate_its_velocity() {
+        let parameters = ObstacleFilterParameters {
+            velocity_process_noise: na::vector![0.1, 0.1],
+            maximum_obstacle_velocity: 1.0,
+            ..Default::default()
+        };
+
+        let filter = track(&parameters, ObstacleKind::Robot, |time| {
+            linear_algebra::point![1.0 + 0.5 * time, 0.0]
+        });
+
+        assert_eq!(filter.hypotheses.len(), 1);
+        let velocity = filter.hypotheses[0].velocity();
+        assert!((velocity.x - 0.5).abs() < 0.05, "{velocity:?}");
+        assert!(velocity.y.abs() < 0.05, "{velocity:?}");
+    }
+
+    #[test]
+    fn prediction_moves_hypotheses_along_their_velocity() {
+        let parameters = ObstacleFilterParameters {
+            velocity_process_noise: na::vector![0.1, 0.1],
+            maximum_obstacle_velocity: 1.0,
+            ..Default::default()
+        };
+        let mut filter = track(&parameters, ObstacleKind::Robot, |time| {
+            linear_algebra::point![1.0, 0.5 * time]
+        });
+        let position = filter.hypotheses[0].position();
+
+        filter.predict_hypotheses_with_odometry(
+            na::Isometry2::identity(),
+            Duration::from_secs(1),
+            nalgebra::Matrix2::zeros(),
+            nalgebra::Matrix2::zeros(),
+        );
+
+        let moved = filter.hypotheses[0].position() - position;
+        assert!(moved.x.abs() < 0.05, "{moved:?}");
+        assert!((moved.y - 0.5).abs() < 0.05, "{moved:?}");
+    }
+
+    #[test]
+    fn goal_posts_never_get_a_velocity() {
+        let parameters = ObstacleFilterParameters {
+            velocity_process_noise: na::vector![0.1, 0.1],
+            maximum_obstacle_velocity: 1.0,
+            ..Default::default()
+        };
+
+        let filter = track(&parameters, ObstacleKind::GoalPost, |time| {
+            linear_algebra::point![2.0 + 0.1 * time, 1.0]
+        });
+
+        assert_eq!(filter.hypotheses[0].velocity(), na::Vector2::zeros());
+    }
+
     #[test]
     fn measured_player_position_is_transformed_from_field_to_ground() {
         use linear_algebra::IntoTransform;
@@ -106,6 +106,7 @@ mod tests {
                     player_number: PlayerNumber::Two,
                     pose,
                     ball_position: None,
+                    search_state: Default::default(),
                 })),
             },
         );
@@ -0,0 +1,233 @@
+use std::{collections::VecDeque, time::Duration};
+
+use hsl_network_messages::{PlayerNumber, VisualRefereeMessage};
+use ros_z::time::Time;
+use types::{players::Players, pose_detection::ReadySignalDetectionResult, pose_kinds::PoseKind};
+
+use crate::Parameters;
+
+/// Combines the own ready signal detections with the ones of the teammates into a team-wide
+/// decision.
+#[derive(Default)]
+pub struct ReadySignalDetectionFilter {
+    detection_times: Players<Option<Time>>,
+    detected_ready_signal_queue: VecDeque<bool>,
+    last_time_message_sent: Option<Time>,
+}
+
+impl ReadySignalDetectionFilter {
+    pub fn reset(&mut self) {
+        *self = Self {
+            last_time_message_sent: self.last_time_message_sent,
+            ..Default::default()
+        };
+    }
+
+    pub fn detection_times(&self) -> Players<Option<Time>> {
+        self.detection_times
+    }
+
+    pub fn update_own_detections(
+        &mut self,
+        detections: impl IntoIterator<Item = (Time, Option<PoseKind>)>,
+        own_player_number: PlayerNumber,
+        parameters: &Parameters,
+    ) -> ReadySignalDetectionResult {
+        let mut did_detect_any_ready_pose_this_cycle = false;
+        let mut latest_detection_time = None;
+        for (time, pose_kind) in detections {
+            let detected_ready_pose = pose_kind == Some(PoseKind::Ready);
+            self.detected_ready_signal_queue
+                .push_front(detected_ready_pose);
+            did_detect_any_ready_pose_this_cycle |= detected_ready_pose;
+            latest_detection_time = Some(time);
+        }
+        self.detected_ready_signal_queue
+            .truncate(parameters.pose_queue_length);
+
+        let detected_ready_pose_count = self
+            .detected_ready_signal_queue
+            .iter()
+            .filter(|detected| **detected)
+            .count();
+        let detected_own_ready_signal =
+            detected_ready_pose_count >= parameters.minimum_number_poses_before_message;
+        if detected_own_ready_signal {
+            self.detection_times[own_player_number] =
+                latest_detection_time.or(self.detection_times[own_player_number]);
+        }
+
+        ReadySignalDetectionResult {
+            detected_own_ready_signal,
+            did_detect_any_ready_pose_this_cycle,
+        }
+    }
+
+    /// Teammates report free kick signals with the same message, only the ones without a kicking
+    /// team refer to the ready signal.
+    pub fn update_other_detection(&mut self, time: Time, message: VisualRefereeMessage) {
+        if message.kicking_team.is_none() {
+            self.detection_times[message.player_number] = Some(time);
+        }
+    }
+
+    pub fn is_ready_signal_detected(&self, now: Time, parameters: &Parameters) -> bool {
+        self.number_of_valid_detections(now, parameters.message_grace_period, None)
+            >= parameters.minimum_ready_signal_detections
+    }
+
+    /// Messages are only sent if the own detection is needed to reach the team-wide consensus, so
+    /// that the message budget is not spent once enough teammates reported the signal.
+    pub fn should_send_message(
+        &self,
+        now: Time,
+        own_player_number: PlayerNumber,
+        remaining_number_of_messages: u16,
+        parameters: &Parameters,
+    ) -> bool {
+        let own_detection_is_valid = self.detection_times[own_player_number]
+            .is_some_and(|time| now.duration_since(time) < parameters.message_grace_period);
+        let is_message_interval_elapsed = self
+            .last_time_message_sent
+            .is_none_or(|time| now.duration_since(time) >= parameters.message_interval);
+        let is_consensus_reached_without_own_detection = self.number_of_valid_detections(
+            now,
+            parameters.message_grace_period,
+            Some(own_player_number),
+        ) >= parameters
+            .minimum_ready_signal_detections;
+
+        own_detection_is_valid
+            && is_message_interval_elapsed
+            && remaining_number_of_messages
+                > parameters.remaining_amount_of_messages_to_stop_sending
+            && !is_consensus_reached_without_own_detection
+    }
+
+    pub fn message_sent(&mut self, now: Time) {
+        self.last_time_message_sent = Some(now);
+    }
+
+    fn number_of_valid_detections(
+        &self,
+        now: Time,
+        grace_period: Duration,
+        excluded_player_number: Option<PlayerNumber>,
+    ) -> usize {
+        self.detection_times
+            .iter()
+            .filter(|(player_number, detection_time)| {
+                Some(*player_number) != excluded_player_number
+                    && detection_time.is_some_and(|detection_time| {
+                        now.duration_since(detection_time) < grace_period
+                    })
+            })
+            .count()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::UNIX_EPOCH;
+
+    use hsl_network_messages::Team;
+
+    use super::*;
+
+    fn parameters() -> Parameters {
+        Parameters {
+            pose_queue_length: 4,
+            minimum_number_poses_before_message: 3,
+            minimum_ready_signal_detections: 2,
+            message_grace_period: Duration::from_secs(3),
+            message_interval: Duration::from_secs(1),
+            remaining_amount_of_messages_to_stop_sending: 20,
+        }
+    }
+
+    fn time(seconds: f32) -> Time {
+        Time::from_wallclock(UNIX_EPOCH + Duration::from_secs_f32(seconds))
+    }
+
+    fn ready_poses(times: &[f32]) -> Vec<(Time, Option<PoseKind>)> {
+        times
+            .iter()
+            .map(|seconds| (time(*seconds), Some(PoseKind::Ready)))
+            .collect()
+    }
+
+    #[test]
+    fn own_detection_requires_enough_ready_poses() {
+        let parameters = parameters();
+        let mut filter = ReadySignalDetectionFilter::default();
+
+        let result =
+            filter.update_own_detections(ready_poses(&[0.0, 0.1]), PlayerNumber::One, &parameters);
+        assert!(!result.detected_own_ready_signal);
+        assert!(result.did_detect_any_ready_pose_this_cycle);
+
+        let result =
+            filter.update_own_detections(ready_poses(&[0.2]), PlayerNumber::One, &parameters);
+        assert!(result.detected_own_ready_signal);
+        assert_eq!(filter.detection_times()[PlayerNumber::One], Some(time(0.2)));
+    }
+
+    #[test]
+    fn consensus_combines_own_and_teammate_detections() {
+        let parameters = parameters();
+        let mut filter = ReadySignalDetectionFilter::default();
+        filter.update_own_detections(
+            ready_poses(&[0.0, 0.1, 0.2]),
+            PlayerNumber::One,
+            &parameters,
+        );
+        assert!(!filter.is_ready_signal_detected(time(0.3), &parameters));
+
+        filter.update_other_detection(
+            time(0.3),
+            VisualRefereeMessage {
+                player_number: PlayerNumber::Two,
+                kicking_team: Some(Team::Hulks),
+            },
+        );
+        assert!(!filter.is_ready_signal_detected(time(0.3), &parameters));
+
+        filter.update_other_detection(
+            time(0.3),
+            VisualRefereeMessage {
+                player_number: PlayerNumber::Three,
+                kicking_team: None,
+            },
+        );
+        assert!(filter.is_ready_signal_detected(time(0.3), &parameters));
+        assert!(!filter.is_ready_signal_detected(time(3.5), &parameters));
+    }
+
+    #[test]
+    fn messages_respect_interval_budget_and_consensus() {
+        let parameters = parameters();
+        let mut filter = ReadySignalDetectionFilter::default();
+        filter.update_own_detections(
+            ready_poses(&[0.0, 0.1, 0.2]),
+            PlayerNumber::One,
+            &parameters,
+        );
+
+        assert!(!filter.should_send_message(time(0.2), PlayerNumber::One, 20, &parameters));
+        assert!(filter.should_send_message(time(0.2), PlayerNumber::One, 21, &parameters));
+        filter.message_sent(time(0.2));
+        assert!(!filter.should_send_message(time(0.5), PlayerNumber::One, 21, &parameters));
+        assert!(filter.should_send_message(time(1.2), PlayerNumber::One, 21, &parameters));
+
+        for player_number in [PlayerNumber::Two, PlayerNumber::Three] {
+            filter.update_other_detection(
+                time(1.0),
+                VisualRefereeMessage {
+                    player_number,
+                    kicking_team: None,
+                },
+            );
+        }
+        assert!(!filter.should_send_message(time(1.2), PlayerNumber::One, 21, &parameters));
+    }
+}
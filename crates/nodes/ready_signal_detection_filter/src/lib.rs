@@ -0,0 +1,164 @@
+use std::{boxed::Box, future::Future, pin::Pin, sync::Arc, time::Duration};
+
+use color_eyre::{Result, eyre::WrapErr};
+use serde::{Deserialize, Serialize};
+
+use hsl_network_messages::{HulkMessage, PlayerNumber, VisualRefereeMessage};
+use ros_z::{prelude::*, qos::QosDurability, time::Time};
+use ros_z_streams::CreateFutureMapBuilder;
+use types::{
+    filtered_game_controller_state::FilteredGameControllerState,
+    filtered_game_state::FilteredGameState,
+    messages::{IncomingMessage, OutgoingMessage},
+    players::Players,
+    pose_detection::ReadySignalDetectionResult,
+    pose_kinds::PoseKind,
+    time_wrapper::TimeWrapper,
+};
+
+use crate::filter::ReadySignalDetectionFilter;
+
+pub mod filter;
+
+#[derive(Debug, Clone, Serialize, Deserialize, Message)]
+#[serde(deny_unknown_fields)]
+pub struct Parameters {
+    pub pose_queue_length: usize,
+    pub minimum_number_poses_before_message: usize,
+    /// Number of players, including this one, that have to detect the ready signal
+    pub minimum_ready_signal_detections: usize,
+    pub message_grace_period: Duration,
+    pub message_interval: Duration,
+    pub remaining_amount_of_messages_to_stop_sending: u16,
+}
+
+pub fn run_boxed(ctx: Arc<Context>) -> Pin<Box<dyn Future<Output = Result<()>> + Send>> {
+    Box::pin(run(ctx))
+}
+
+async fn run(ctx: Arc<Context>) -> Result<()> {
+    let node = ctx
+        .create_node("ready_signal_detection_filter")
+        .build()
+        .await
+        .wrap_err("failed to create ready_signal_detection_filter node")?;
+    let parameters = node
+        .bind_parameter_as::<Parameters>("ready_signal_detection_filter")
+        .wrap_err("failed to bind ready_signal_detection_filter parameters")?;
+
+    let player_number_cache = node
+        .subscriber::<PlayerNumber>("player_number")
+        .qos(QosProfile {
+            durability: QosDurability::TransientLocal,
+            ..Default::default()
+        })
+        .cache(1)
+        .build()
+        .await?;
+    let filtered_game_controller_state_cache = node
+        .subscriber::<FilteredGameControllerState>("filtered_game_controller_state")
+        .cache(1)
+        .build()
+        .await?;
+    let filtered_message_sub = node
+        .subscriber::<TimeWrapper<IncomingMessage>>("filtered_message")
+        .build()
+        .await?;
+    let mut referee_pose_kinds = node
+        .create_future_map_builder()
+        .create_future_subscriber::<Option<PoseKind>>(
+            "referee_pose_kind",
+            Duration::from_millis(50),
+        )
+        .await?
+        .build();
+
+    let ready_signal_detected_pub = node
+        .publisher::<bool>("ready_signal_detected")
+        .build()
+        .await?;
+    let own_ready_signal_detection_result_pub = node
+        .publisher::<ReadySignalDetectionResult>("own_ready_signal_detection_result")
+        .build()
+        .await?;
+    let detection_times_pub = node
+        .publisher::<Players<Option<Time>>>("ready_signal_detection_filter/detection_times")
+        .build()
+        .await?;
+    let outgoing_message_pub = node
+        .publisher::<OutgoingMessage>("outputs/message")
+        .build()
+        .await?;
+
+    let mut filter = ReadySignalDetectionFilter::default();
+    loop {
+        let mut own_detections = Vec::new();
+        tokio::select! {
+            item = referee_pose_kinds.recv() => {
+                own_detections = item?
+                    .persistent
+                    .into_iter()
+                    .map(|(time, (pose_kind,))| (time, pose_kind.flatten()))
+                    .collect();
+            }
+            message = filtered_message_sub.recv() => {
+                if let TimeWrapper {
+                    time,
+                    inner: IncomingMessage::Hsl(HulkMessage::VisualReferee(message)),
+                } = message?
+                {
+                    filter.update_other_detection(time, message);
+                }
+            }
+        }
+
+        let parameters_snapshot = parameters.snapshot();
+        let parameters = parameters_snapshot.typed();
+        let now = node.clock().now();
+        let (Some(player_number), Some(game_controller_state)) = (
+            player_number_cache.get_latest(),
+            filtered_game_controller_state_cache.get_latest(),
+        ) else {
+            continue;
+        };
+
+        // the referee shows the ready signal while the robots wait in initial
+        if game_controller_state.game_state != FilteredGameState::Initial {
+            filter.reset();
+            ready_signal_detected_pub.publish(&false).await?;
+            own_ready_signal_detection_result_pub
+                .publish(&ReadySignalDetectionResult::default())
+                .await?;
+            continue;
+        }
+
+        let own_ready_signal_detection_result =
+            filter.update_own_detections(own_detections, *player_number, parameters);
+        if filter.should_send_message(
+            now,
+            *player_number,
+            game_controller_state.remaining_number_of_messages,
+            parameters,
+        ) {
+            outgoing_message_pub
+                .publish(&OutgoingMessage::Hsl(HulkMessage::VisualReferee(
+                    VisualRefereeMessage {
+                        player_number: *player_number,
+                        kicking_team: None,
+                    },
+                )))
+                .await?;
+            filter.message_sent(now);
+        }
+
+        ready_signal_detected_pub
+            .publish(&filter.is_ready_signal_detected(now, parameters))
+            .await?;
+        own_ready_signal_detection_result_pub
+            .publish(&own_ready_signal_detection_result)
+            .await?;
+        detection_times_pub
+            .publish(&filter.detection_times())
+            .await?;
+    }
+}
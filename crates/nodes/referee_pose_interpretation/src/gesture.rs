@@ -0,0 +1,282 @@
+use linear_algebra::{Point2, center, distance};
+use serde::{Deserialize, Serialize};
+
+use coordinate_systems::Pixel;
+use types::pose_detection::{Keypoint, Keypoints};
+
+#[derive(Clone, Debug, Serialize, Deserialize, ros_z::Message)]
+#[serde(deny_unknown_fields)]
+pub struct GestureParameters {
+    pub minimum_keypoint_confidence: f32,
+    /// Height of a hand above its shoulder, relative to the torso length, to count as raised
+    pub minimum_raised_elevation: f32,
+    /// Height difference between hand and shoulder, relative to the torso length, for an arm
+    /// to count as horizontal
+    pub maximum_horizontal_elevation: f32,
+    /// Sideways distance between hand and shoulder, relative to the torso length, for an arm
+    /// to count as outstretched
+    pub minimum_outstretched_reach: f32,
+}
+
+/// Direction in the image, independent of the anatomical side of the arm
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ImageDirection {
+    Left,
+    Right,
+}
+
+impl ImageDirection {
+    pub fn sign(self) -> f32 {
+        match self {
+            ImageDirection::Left => -1.0,
+            ImageDirection::Right => 1.0,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Gesture {
+    /// Both arms raised above the head
+    ArmsUp,
+    /// A single arm held out horizontally
+    ArmOutstretched { direction: ImageDirection },
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct GestureDetection {
+    pub gesture: Gesture,
+    pub confidence: f32,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum ArmConfiguration {
+    Raised,
+    Outstretched(ImageDirection),
+    Other,
+}
+
+struct Arm {
+    shoulder: Keypoint,
+    elbow: Keypoint,
+    hand: Keypoint,
+}
+
+impl Arm {
+    fn confidence(&self) -> f32 {
+        self.shoulder
+            .confidence
+            .min(self.elbow.confidence)
+            .min(self.hand.confidence)
+    }
+
+    fn configuration(
+        &self,
+        shoulder_center: Point2<Pixel>,
+        torso_length: f32,
+        parameters: &GestureParameters,
+    ) -> ArmConfiguration {
+        // image y points downwards
+        let elevation = (self.shoulder.point.y() - self.hand.point.y()) / torso_length;
+        let elbow_elevation = (self.shoulder.point.y() - self.elbow.point.y()) / torso_length;
+        let reach = (self.hand.point.x() - self.shoulder.point.x()) / torso_length;
+        let is_pointing_outwards = (self.hand.point.x() - shoulder_center.x()).signum()
+            == reach.signum()
+            && (self.hand.point.x() - self.elbow.point.x()).signum() == reach.signum();
+
+        if elevation > parameters.minimum_raised_elevation && elbow_elevation > 0.0 {
+            ArmConfiguration::Raised
+        } else if elevation.abs() < parameters.maximum_horizontal_elevation
+            && reach.abs() > parameters.minimum_outstretched_reach
+            && is_pointing_outwards
+        {
+            ArmConfiguration::Outstretched(if reach < 0.0 {
+                ImageDirection::Left
+            } else {
+                ImageDirection::Right
+            })
+        } else {
+            ArmConfiguration::Other
+        }
+    }
+}
+
+/// Classifies the arm configuration of a person from the pose keypoints. Distances are
+/// normalized by the torso length so that the classification does not depend on the distance
+/// to the person.
+pub fn classify_gesture(
+    keypoints: &Keypoints,
+    parameters: &GestureParameters,
+) -> Option<GestureDetection> {
+    let torso = [
+        keypoints.left_shoulder,
+        keypoints.right_shoulder,
+        keypoints.left_hip,
+        keypoints.right_hip,
+    ];
+    if torso
+        .iter()
+        .any(|keypoint| keypoint.confidence < parameters.minimum_keypoint_confidence)
+    {
+        return None;
+    }
+    let shoulder_center = center(
+        keypoints.left_shoulder.point,
+        keypoints.right_shoulder.point,
+    );
+    let hip_center = center(keypoints.left_hip.point, keypoints.right_hip.point);
+    let torso_length = distance(shoulder_center, hip_center);
+    if torso_length <= f32::EPSILON {
+        return None;
+    }
+    let torso_confidence = torso
+        .iter()
+        .map(|keypoint| keypoint.confidence)
+        .fold(1.0, f32::min);
+
+    let arms = [
+        Arm {
+            shoulder: keypoints.left_shoulder,
+            elbow: keypoints.left_elbow,
+            hand: keypoints.left_hand,
+        },
+        Arm {
+            shoulder: keypoints.right_shoulder,
+            elbow: keypoints.right_elbow,
+            hand: keypoints.right_hand,
+        },
+    ];
+    let [first, second] = arms.map(|arm| {
+        let confidence = arm.confidence();
+        let configuration = (confidence >= parameters.minimum_keypoint_confidence)
+            .then(|| arm.configuration(shoulder_center, torso_length, parameters));
+        (configuration, confidence)
+    });
+
+    let (gesture, arm_confidence) = match (first, second) {
+        (
+            (Some(ArmConfiguration::Raised), first_confidence),
+            (Some(ArmConfiguration::Raised), second_confidence),
+        ) => (Gesture::ArmsUp, first_confidence.min(second_confidence)),
+        ((Some(ArmConfiguration::Outstretched(direction)), confidence), (other, _))
+        | ((other, _), (Some(ArmConfiguration::Outstretched(direction)), confidence))
+            if matches!(other, None | Some(ArmConfiguration::Other)) =>
+        {
+            (Gesture::ArmOutstretched { direction }, confidence)
+        }
+        _ => return None,
+    };
+
+    Some(GestureDetection {
+        gesture,
+        confidence: torso_confidence.min(arm_confidence),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use linear_algebra::point;
+
+    use super::*;
+
+    fn keypoint(x: f32, y: f32) -> Keypoint {
+        Keypoint {
+            point: point![x, y],
+            confidence: 0.9,
+        }
+    }
+
+    fn parameters() -> GestureParameters {
+        GestureParameters {
+            minimum_keypoint_confidence: 0.5,
+            minimum_raised_elevation: 0.5,
+            maximum_horizontal_elevation: 0.3,
+            minimum_outstretched_reach: 0.6,
+        }
+    }
+
+    /// Person facing the camera, i.e. the anatomical left is on the right of the image
+    fn keypoints(left_arm: [(f32, f32); 2], right_arm: [(f32, f32); 2]) -> Keypoints {
+        let [left_elbow, left_hand] = left_arm.map(|(x, y)| keypoint(x, y));
+        let [right_elbow, right_hand] = right_arm.map(|(x, y)| keypoint(x, y));
+        Keypoints {
+            left_eye: keypoint(105.0, 40.0),
+            right_eye: keypoint(95.0, 40.0),
+            nose: keypoint(100.0, 45.0),
+            left_ear: keypoint(110.0, 42.0),
+            right_ear: keypoint(90.0, 42.0),
+            left_shoulder: keypoint(120.0, 70.0),
+            right_shoulder: keypoint(80.0, 70.0),
+            left_elbow,
+            right_elbow,
+            left_hand,
+            right_hand,
+            left_hip: keypoint(115.0, 170.0),
+            right_hip: keypoint(85.0, 170.0),
+            left_knee: keypoint(115.0, 230.0),
+            right_knee: keypoint(85.0, 230.0),
+            left_foot: keypoint(115.0, 290.0),
+            right_foot: keypoint(85.0, 290.0),
+        }
+    }
+
+    const HANGING_LEFT: [(f32, f32); 2] = [(125.0, 120.0), (125.0, 165.0)];
+    const HANGING_RIGHT: [(f32, f32); 2] = [(75.0, 120.0), (75.0, 165.0)];
+
+    #[test]
+    fn both_arms_above_head_are_arms_up() {
+        let keypoints = keypoints(
+            [(130.0, 20.0), (130.0, -20.0)],
+            [(70.0, 20.0), (70.0, -20.0)],
+        );
+
+        let detection = classify_gesture(&keypoints, &parameters()).unwrap();
+
+        assert_eq!(detection.gesture, Gesture::ArmsUp);
+        assert_eq!(detection.confidence, 0.9);
+    }
+
+    #[test]
+    fn single_horizontal_arm_is_outstretched() {
+        let pointing_right = keypoints([(170.0, 72.0), (220.0, 75.0)], HANGING_RIGHT);
+        assert_eq!(
+            classify_gesture(&pointing_right, &parameters())
+                .unwrap()
+                .gesture,
+            Gesture::ArmOutstretched {
+                direction: ImageDirection::Right
+            }
+        );
+
+        let pointing_left = keypoints(HANGING_LEFT, [(30.0, 68.0), (-20.0, 65.0)]);
+        assert_eq!(
+            classify_gesture(&pointing_left, &parameters())
+                .unwrap()
+                .gesture,
+            Gesture::ArmOutstretched {
+                direction: ImageDirection::Left
+            }
+        );
+    }
+
+    #[test]
+    fn ambiguous_configurations_are_not_classified() {
+        let parameters = parameters();
+        assert_eq!(
+            classify_gesture(&keypoints(HANGING_LEFT, HANGING_RIGHT), &parameters),
+            None
+        );
+        assert_eq!(
+            classify_gesture(
+                &keypoints(
+                    [(170.0, 72.0), (220.0, 75.0)],
+                    [(30.0, 68.0), (-20.0, 65.0)]
+                ),
+                &parameters
+            ),
+            None
+        );
+        let mut occluded = keypoints([(130.0, 20.0), (130.0, -20.0)], HANGING_RIGHT);
+        occluded.left_hip.confidence = 0.1;
+        assert_eq!(classify_gesture(&occluded, &parameters), None);
+    }
+}
@@ -0,0 +1,171 @@
+use std::{boxed::Box, future::Future, pin::Pin, sync::Arc, time::Duration};
+
+use color_eyre::{Result, eyre::WrapErr};
+use serde::{Deserialize, Serialize};
+
+use coordinate_systems::{Field, Ground};
+use linear_algebra::Isometry2;
+use projection::camera_matrix::CameraMatrix;
+use ros_z::{prelude::*, qos::QosDurability};
+use ros_z_streams::{CreateAnnouncingPublisher, CreateFutureMapBuilder};
+use types::{
+    field_dimensions::FieldDimensions,
+    filtered_game_controller_state::FilteredGameControllerState,
+    object_detection::YOLOObjectLabel,
+    pose_detection::{Pose, RefereePoseCandidate},
+    pose_kinds::PoseKind,
+    time_wrapper::TimeWrapper,
+};
+
+use crate::{
+    gesture::{GestureDetection, GestureParameters, classify_gesture},
+    referee::{RefereeParameters, field_direction_sign, find_referee, pose_kind},
+    temporal_vote::{TemporalVote, TemporalVoteParameters},
+};
+
+pub mod gesture;
+pub mod referee;
+pub mod temporal_vote;
+
+#[derive(Debug, Clone, Serialize, Deserialize, Message)]
+#[serde(deny_unknown_fields)]
+pub struct Parameters {
+    pub referee: RefereeParameters,
+    pub gesture: GestureParameters,
+    pub temporal_vote: TemporalVoteParameters,
+}
+
+pub fn run_boxed(ctx: Arc<Context>) -> Pin<Box<dyn Future<Output = Result<()>> + Send>> {
+    Box::pin(run(ctx))
+}
+
+async fn run(ctx: Arc<Context>) -> Result<()> {
+    let node = ctx
+        .create_node("referee_pose_interpretation")
+        .build()
+        .await
+        .wrap_err("failed to create referee_pose_interpretation node")?;
+    let parameters = node
+        .bind_parameter_as::<Parameters>("referee_pose_interpretation")
+        .wrap_err("failed to bind referee_pose_interpretation parameters")?;
+
+    let field_dimensions_cache = node
+        .subscriber::<FieldDimensions>("field_dimensions")
+        .qos(QosProfile {
+            durability: QosDurability::TransientLocal,
+            ..Default::default()
+        })
+        .cache(1)
+        .build()
+        .await?;
+    let camera_matrix_cache = node
+        .subscriber::<TimeWrapper<CameraMatrix>>("camera_matrix")
+        .cache(10)
+        .with_stamp(|wrapper| wrapper.time)
+        .build()
+        .await?;
+    let ground_to_field_cache = node
+        .subscriber::<Isometry2<Ground, Field>>("ground_to_field")
+        .cache(10)
+        .build()
+        .await?;
+    let filtered_game_controller_state_cache = node
+        .subscriber::<FilteredGameControllerState>("filtered_game_controller_state")
+        .cache(1)
+        .build()
+        .await?;
+
+    let mut detected_poses = node
+        .create_future_map_builder()
+        .create_future_subscriber::<Vec<Pose<YOLOObjectLabel>>>(
+            "detected_poses",
+            Duration::from_millis(50),
+        )
+        .await?
+        .build();
+
+    let referee_pose_candidate_pub = node
+        .publisher::<Option<RefereePoseCandidate>>(
+            "referee_pose_interpretation/referee_pose_candidate",
+        )
+        .build()
+        .await?;
+    let referee_pose_kind_pub = node
+        .announcing_publisher::<Option<PoseKind>>("referee_pose_kind")
+        .await?;
+
+    let mut temporal_vote = TemporalVote::default();
+    loop {
+        let item = detected_poses.recv().await?;
+        let parameters_snapshot = parameters.snapshot();
+        let parameters = parameters_snapshot.typed();
+
+        for (detection_time, (poses,)) in item.persistent {
+            let referee_pose_kind_pending = referee_pose_kind_pub.announce(detection_time).await?;
+
+            let camera_matrix = camera_matrix_cache.get_nearest(detection_time);
+            let ground_to_field = ground_to_field_cache.get_nearest(detection_time);
+            let field_dimensions = field_dimensions_cache.get_latest();
+            let hulks_global_field_side = filtered_game_controller_state_cache
+                .get_latest()
+                .map(|state| state.global_field_side);
+
+            let (referee, observation) = match (
+                poses,
+                camera_matrix,
+                ground_to_field,
+                field_dimensions,
+                hulks_global_field_side,
+            ) {
+                (
+                    Some(poses),
+                    Some(camera_matrix),
+                    Some(ground_to_field),
+                    Some(field_dimensions),
+                    Some(hulks_global_field_side),
+                ) => {
+                    let camera_matrix = &camera_matrix.inner;
+                    let ground_to_field = *ground_to_field;
+                    let referee = find_referee(
+                        &poses,
+                        camera_matrix,
+                        ground_to_field,
+                        &field_dimensions,
+                        &parameters.referee,
+                    );
+                    let observation = referee.as_ref().and_then(|referee| {
+                        let GestureDetection {
+                            gesture,
+                            confidence,
+                        } = classify_gesture(&referee.pose.keypoints, &parameters.gesture)?;
+                        let confidence = confidence * referee.pose.object.bounding_box.confidence;
+                        let pose_kind = pose_kind(
+                            gesture,
+                            |direction| {
+                                field_direction_sign(
+                                    &referee.pose,
+                                    direction,
+                                    camera_matrix,
+                                    ground_to_field,
+                                    &parameters.referee,
+                                )
+                            },
+                            hulks_global_field_side,
+                        )?;
+                        Some((pose_kind, confidence))
+                    });
+                    (referee, observation)
+                }
+                _ => (None, None),
+            };
+
+            let referee_pose_kind =
+                temporal_vote.update(detection_time, observation, &parameters.temporal_vote);
+
+            referee_pose_candidate_pub.publish(&referee).await?;
+            referee_pose_kind_pending
+                .publish(&referee_pose_kind)
+                .await?;
+        }
+    }
+}
@@ -0,0 +1,163 @@
+use serde::{Deserialize, Serialize};
+
+use coordinate_systems::{Field, Ground, Pixel};
+use geometry::rectangle::Rectangle;
+use linear_algebra::{Isometry2, Point2, center, distance, point};
+use projection::{Projection, camera_matrix::CameraMatrix};
+use types::{
+    field_dimensions::{FieldDimensions, GlobalFieldSide},
+    object_detection::YOLOObjectLabel,
+    pose_detection::{Pose, RefereePoseCandidate},
+    pose_kinds::PoseKind,
+};
+
+use crate::gesture::{Gesture, ImageDirection};
+
+#[derive(Clone, Debug, Serialize, Deserialize, ros_z::Message)]
+#[serde(deny_unknown_fields)]
+pub struct RefereeParameters {
+    /// Distance of the referee to the sideline, outside of the field, at the center line
+    pub referee_distance_to_sideline: f32,
+    pub maximum_distance_to_referee_position: f32,
+    pub feet_keypoint_confidence_threshold: f32,
+    pub bounding_box_confidence_threshold: f32,
+    /// Length of the image offset used to determine which field half an arm points to
+    pub pointing_direction_pixel_offset: f32,
+}
+
+/// Both sidelines are considered since the referee may stand on either of them.
+pub fn referee_positions(
+    field_dimensions: &FieldDimensions,
+    parameters: &RefereeParameters,
+) -> [Point2<Field>; 2] {
+    let y = field_dimensions.width / 2.0 + parameters.referee_distance_to_sideline;
+    [point![0.0, y], point![0.0, -y]]
+}
+
+/// Point in the image where the person touches the ground, i.e. the center of the feet or the
+/// bottom center of the bounding box if the feet are not visible.
+pub fn ground_contact_pixel(
+    pose: &Pose<YOLOObjectLabel>,
+    parameters: &RefereeParameters,
+) -> Option<Point2<Pixel>> {
+    let keypoints = &pose.keypoints;
+    if keypoints.left_foot.confidence > parameters.feet_keypoint_confidence_threshold
+        && keypoints.right_foot.confidence > parameters.feet_keypoint_confidence_threshold
+    {
+        Some(center(
+            keypoints.left_foot.point,
+            keypoints.right_foot.point,
+        ))
+    } else if pose.object.bounding_box.confidence > parameters.bounding_box_confidence_threshold {
+        let Rectangle { min, max } = pose.object.bounding_box.area;
+        Some(point![(min.x() + max.x()) / 2.0, max.y()])
+    } else {
+        None
+    }
+}
+
+/// Selects the detected person closest to one of the referee positions.
+pub fn find_referee(
+    poses: &[Pose<YOLOObjectLabel>],
+    camera_matrix: &CameraMatrix,
+    ground_to_field: Isometry2<Ground, Field>,
+    field_dimensions: &FieldDimensions,
+    parameters: &RefereeParameters,
+) -> Option<RefereePoseCandidate> {
+    let referee_positions = referee_positions(field_dimensions, parameters);
+    poses
+        .iter()
+        .filter(|pose| pose.object.label == YOLOObjectLabel::Person)
+        .filter_map(|pose| {
+            let ground_position = camera_matrix
+                .pixel_to_ground(ground_contact_pixel(pose, parameters)?)
+                .ok()?;
+            let field_position = ground_to_field * ground_position;
+            let distance_to_referee_position = referee_positions
+                .iter()
+                .map(|referee_position| distance(field_position, *referee_position))
+                .fold(f32::INFINITY, f32::min);
+            Some(RefereePoseCandidate {
+                pose: *pose,
+                distance_to_referee_position,
+            })
+        })
+        .filter(|candidate| {
+            candidate.distance_to_referee_position
+                <= parameters.maximum_distance_to_referee_position
+        })
+        .min_by(|left, right| {
+            left.distance_to_referee_position
+                .total_cmp(&right.distance_to_referee_position)
+        })
+}
+
+/// Field side (along the field x-axis) an image direction at the referee's feet points to, as
+/// the sign of the x-component in the field frame.
+pub fn field_direction_sign(
+    referee: &Pose<YOLOObjectLabel>,
+    direction: ImageDirection,
+    camera_matrix: &CameraMatrix,
+    ground_to_field: Isometry2<Ground, Field>,
+    parameters: &RefereeParameters,
+) -> Option<f32> {
+    let feet = ground_contact_pixel(referee, parameters)?;
+    let pointed_at = point![
+        feet.x() + direction.sign() * parameters.pointing_direction_pixel_offset,
+        feet.y()
+    ];
+    let feet = ground_to_field * camera_matrix.pixel_to_ground(feet).ok()?;
+    let pointed_at = ground_to_field * camera_matrix.pixel_to_ground(pointed_at).ok()?;
+    let x = (pointed_at - feet).x();
+    (x != 0.0).then(|| x.signum())
+}
+
+/// The free kick arm points towards the half of the team defending the free kick.
+pub fn pose_kind(
+    gesture: Gesture,
+    field_direction_sign: impl FnOnce(ImageDirection) -> Option<f32>,
+    hulks_global_field_side: GlobalFieldSide,
+) -> Option<PoseKind> {
+    match gesture {
+        Gesture::ArmsUp => Some(PoseKind::Ready),
+        Gesture::ArmOutstretched { direction } => {
+            let points_to_own_half = field_direction_sign(direction)? < 0.0;
+            let global_field_side = if points_to_own_half {
+                hulks_global_field_side
+            } else {
+                hulks_global_field_side.mirror()
+            };
+            Some(PoseKind::FreeKick { global_field_side })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn free_kick_side_follows_pointing_direction() {
+        let gesture = Gesture::ArmOutstretched {
+            direction: ImageDirection::Left,
+        };
+
+        assert_eq!(
+            pose_kind(gesture, |_| Some(-1.0), GlobalFieldSide::Home),
+            Some(PoseKind::FreeKick {
+                global_field_side: GlobalFieldSide::Home
+            })
+        );
+        assert_eq!(
+            pose_kind(gesture, |_| Some(1.0), GlobalFieldSide::Home),
+            Some(PoseKind::FreeKick {
+                global_field_side: GlobalFieldSide::Away
+            })
+        );
+        assert_eq!(pose_kind(gesture, |_| None, GlobalFieldSide::Home), None);
+        assert_eq!(
+            pose_kind(Gesture::ArmsUp, |_| None, GlobalFieldSide::Away),
+            Some(PoseKind::Ready)
+        );
+    }
+}
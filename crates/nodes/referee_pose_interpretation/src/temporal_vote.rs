@@ -0,0 +1,144 @@
+use std::{collections::VecDeque, time::Duration};
+
+use serde::{Deserialize, Serialize};
+
+use ros_z::time::Time;
+
+#[derive(Clone, Debug, Serialize, Deserialize, ros_z::Message)]
+#[serde(deny_unknown_fields)]
+pub struct TemporalVoteParameters {
+    pub window: Duration,
+    /// Number of detections of the same class within the window to report it
+    pub minimum_detections: usize,
+    /// Share of the confidence of all observations within the window that has to support the
+    /// reported class
+    pub minimum_share: f32,
+}
+
+/// Smoothes per-frame classifications by a confidence-weighted vote over a time window. Frames
+/// without a classification count against every class, so that a gesture has to be held
+/// consistently to be reported.
+#[derive(Debug)]
+pub struct TemporalVote<T> {
+    observations: VecDeque<(Time, Option<(T, f32)>)>,
+}
+
+impl<T> Default for TemporalVote<T> {
+    fn default() -> Self {
+        Self {
+            observations: VecDeque::new(),
+        }
+    }
+}
+
+impl<T: Clone + PartialEq> TemporalVote<T> {
+    pub fn update(
+        &mut self,
+        time: Time,
+        observation: Option<(T, f32)>,
+        parameters: &TemporalVoteParameters,
+    ) -> Option<T> {
+        let insertion_index = self
+            .observations
+            .partition_point(|(observation_time, _)| *observation_time <= time);
+        self.observations
+            .insert(insertion_index, (time, observation));
+        let newest = self.observations.back().map_or(time, |(newest, _)| *newest);
+        while self
+            .observations
+            .front()
+            .is_some_and(|(oldest, _)| newest.duration_since(*oldest) > parameters.window)
+        {
+            self.observations.pop_front();
+        }
+        self.vote(parameters)
+    }
+
+    fn vote(&self, parameters: &TemporalVoteParameters) -> Option<T> {
+        let mut tallies: Vec<(T, usize, f32)> = Vec::new();
+        let mut total_weight = 0.0;
+        for (_, observation) in &self.observations {
+            let Some((class, confidence)) = observation else {
+                total_weight += 1.0;
+                continue;
+            };
+            total_weight += confidence;
+            match tallies.iter_mut().find(|(tallied, ..)| tallied == class) {
+                Some((_, count, weight)) => {
+                    *count += 1;
+                    *weight += confidence;
+                }
+                None => tallies.push((class.clone(), 1, *confidence)),
+            }
+        }
+
+        let (class, count, weight) = tallies
+            .into_iter()
+            .max_by(|(_, _, left), (_, _, right)| left.total_cmp(right))?;
+        (count >= parameters.minimum_detections
+            && weight >= parameters.minimum_share * total_weight)
+            .then_some(class)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parameters() -> TemporalVoteParameters {
+        TemporalVoteParameters {
+            window: Duration::from_secs(1),
+            minimum_detections: 3,
+            minimum_share: 0.6,
+        }
+    }
+
+    fn time(milliseconds: u64) -> Time {
+        Time::from_wallclock(std::time::UNIX_EPOCH + Duration::from_millis(milliseconds))
+    }
+
+    #[test]
+    fn consistent_observations_are_reported() {
+        let parameters = parameters();
+        let mut vote = TemporalVote::default();
+
+        assert_eq!(vote.update(time(0), Some(("up", 0.9)), &parameters), None);
+        assert_eq!(vote.update(time(100), Some(("up", 0.9)), &parameters), None);
+        assert_eq!(
+            vote.update(time(200), Some(("up", 0.9)), &parameters),
+            Some("up")
+        );
+        assert_eq!(
+            vote.update(time(300), Some(("side", 0.9)), &parameters),
+            Some("up")
+        );
+    }
+
+    #[test]
+    fn flickering_observations_are_not_reported() {
+        let parameters = parameters();
+        let mut vote = TemporalVote::default();
+
+        for (index, class) in ["up", "side", "up", "side", "up", "side"]
+            .into_iter()
+            .enumerate()
+        {
+            let observation =
+                vote.update(time(index as u64 * 100), Some((class, 0.9)), &parameters);
+            assert_eq!(observation, None);
+        }
+    }
+
+    #[test]
+    fn old_observations_leave_the_window() {
+        let parameters = parameters();
+        let mut vote = TemporalVote::default();
+        for milliseconds in [0, 100, 200] {
+            vote.update(time(milliseconds), Some(("up", 0.9)), &parameters);
+        }
+
+        for milliseconds in [1300, 1400] {
+            assert_eq!(vote.update(time(milliseconds), None, &parameters), None);
+        }
+    }
+}
@@ -0,0 +1,84 @@
+use std::{
+    fs,
+    path::Path,
+    time::{Duration, UNIX_EPOCH},
+};
+
+use serde::Deserialize;
+
+use referee_pose_interpretation::{
+    Parameters,
+    gesture::{Gesture, classify_gesture},
+    temporal_vote::TemporalVote,
+};
+use ros_z::time::Time;
+use types::pose_detection::Keypoints;
+
+const PARAMETERS: &str =
+    include_str!("../../../../etc/parameters/ros_z/base/referee_pose_interpretation.json5");
+
+/// Keypoint sequence in the layout of the pose detection output: 17 keypoints with x, y and
+/// confidence each. Frames without a detection of the referee are `null`. `provenance` records
+/// where the keypoints come from.
+#[derive(Deserialize)]
+struct KeypointSequence {
+    description: String,
+    provenance: String,
+    frame_interval_milliseconds: u64,
+    checkpoints: Vec<Checkpoint>,
+    frames: Vec<Option<Vec<f32>>>,
+}
+
+#[derive(Deserialize)]
+struct Checkpoint {
+    frame: usize,
+    gesture: Option<Gesture>,
+}
+
+fn run_sequence(path: &Path, parameters: &Parameters) {
+    let sequence: KeypointSequence =
+        serde_json::from_str(&fs::read_to_string(path).unwrap()).unwrap();
+    let mut vote = TemporalVote::default();
+
+    let gestures: Vec<_> = sequence
+        .frames
+        .iter()
+        .enumerate()
+        .map(|(index, frame)| {
+            let observation = frame.as_ref().and_then(|values| {
+                let values: &[f32; 51] = values.as_slice().try_into().unwrap();
+                let detection = classify_gesture(&Keypoints::from(values), &parameters.gesture)?;
+                Some((detection.gesture, detection.confidence))
+            });
+            let time = Time::from_wallclock(
+                UNIX_EPOCH
+                    + Duration::from_millis(index as u64 * sequence.frame_interval_milliseconds),
+            );
+            vote.update(time, observation, &parameters.temporal_vote)
+        })
+        .collect();
+
+    for checkpoint in sequence.checkpoints {
+        assert_eq!(
+            gestures[checkpoint.frame], checkpoint.gesture,
+            "{path:?} ({}, {}) at frame {}",
+            sequence.description, sequence.provenance, checkpoint.frame
+        );
+    }
+}
+
+#[test]
+fn keypoint_sequences_are_classified_as_expected() {
+    let parameters: Parameters = json5::from_str(PARAMETERS).unwrap();
+    let sequences_folder = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/keypoint_sequences");
+    let mut paths: Vec<_> = fs::read_dir(sequences_folder)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .collect();
+    paths.sort();
+
+    assert!(!paths.is_empty());
+    for path in paths {
+        run_sequence(&path, &parameters);
+    }
+}
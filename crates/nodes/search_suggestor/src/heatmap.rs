@@ -2,11 +2,11 @@ use std::{ops::Range, time::SystemTime};
 
 use coordinate_systems::{Field, Ground};
 use geometry::direction::{Direction, Rotate90Degrees};
-use hsl_network_messages::{HulkMessage, StateMessage, SubState, Team};
-use itertools::Itertools;
-use linear_algebra::{Isometry2, Point2, Vector2, vector};
+use hsl_network_messages::{HulkMessage, SearchState, StateMessage, SubState, Team};
+use linear_algebra::{Isometry2, Point2, Vector2, point};
 use nalgebra::clamp;
 use ndarray::Array2;
+use projection::{Projection, camera_matrix::CameraMatrix};
 use ros_z::time::Time;
 use serde::{Deserialize, Serialize};
 use types::{
@@ -99,17 +99,34 @@ impl Heatmap {
         );
     }
 
-    pub(crate) fn get_maximum_position(&self, minimum_validity: f32) -> Option<(usize, usize)> {
-        let linear_maximum_heat_heatmap_position =
-            self.map.iter().position_max_by(|a, b| a.total_cmp(b))?;
-        let maximum_heat_heatmap_position = (
-            linear_maximum_heat_heatmap_position / self.map.dim().1,
-            linear_maximum_heat_heatmap_position % self.map.dim().1,
-        );
-        if self.map[maximum_heat_heatmap_position] > minimum_validity {
-            return Some(maximum_heat_heatmap_position);
-        }
-        None
+    pub(crate) fn update_with_team_search_state(
+        &mut self,
+        field_dimensions: FieldDimensions,
+        search_state: &SearchState,
+        team_observation_decay: f32,
+    ) {
+        let cells_per_meter = self.cells_per_meter;
+        self.map.indexed_iter_mut().for_each(|(tile, value)| {
+            let tile_center = tile_center_in_field(field_dimensions, cells_per_meter, tile);
+            let cell =
+                SearchState::cell_at(tile_center, field_dimensions.length, field_dimensions.width);
+            if search_state.is_observed(cell) {
+                *value *= 1.0 - team_observation_decay;
+            }
+        });
+    }
+
+    pub(crate) fn get_maximum_position(
+        &self,
+        minimum_validity: f32,
+        is_excluded: impl Fn((usize, usize)) -> bool,
+    ) -> Option<(usize, usize)> {
+        let (maximum_heat_heatmap_position, maximum_heat) = self
+            .map
+            .indexed_iter()
+            .filter(|(tile, _)| !is_excluded(*tile))
+            .max_by(|(_, a), (_, b)| a.total_cmp(b))?;
+        (*maximum_heat > minimum_validity).then_some(maximum_heat_heatmap_position)
     }
 
     pub(crate) fn decay_tiles_in_fov(
@@ -120,13 +137,13 @@ impl Heatmap {
         right_edge: Vector2<Field>,
         decay_distance_factor: f32,
         heatmap_decay_range: Range<f32>,
-    ) {
-        self.map.indexed_iter_mut().for_each(|((x, y), value)| {
-            let tile_center_in_field: Vector2<Field> = vector![
-                ((x as f32 + 1.0 / 2.0) / self.cells_per_meter - field_dimensions.length / 2.0),
-                ((y as f32 + 1.0 / 2.0) / self.cells_per_meter - field_dimensions.width / 2.0)
-            ];
-            let robot_to_tile = tile_center_in_field - robot_position;
+    ) -> Vec<Point2<Field>> {
+        let cells_per_meter = self.cells_per_meter;
+        let mut observed_tiles = Vec::new();
+        self.map.indexed_iter_mut().for_each(|(tile, value)| {
+            let tile_center_in_field =
+                tile_center_in_field(field_dimensions, cells_per_meter, tile);
+            let robot_to_tile = tile_center_in_field.coords() - robot_position;
             let is_inside_sight = get_direction(left_edge, robot_to_tile)
                 == Direction::Counterclockwise
                 && get_direction(right_edge, robot_to_tile) == Direction::Clockwise;
@@ -135,8 +152,40 @@ impl Heatmap {
                 clamp(distance_to_tile / heatmap_decay_range.end, 0.0, 1.0);
             if is_inside_sight && heatmap_decay_range.contains(&distance_to_tile) {
                 *value *= 1.0 - decay_distance_factor * (1.0 - relative_distance_to_tile);
+                observed_tiles.push(tile_center_in_field);
+            }
+        });
+        observed_tiles
+    }
+
+    /// Decays the tiles whose center is visible in the image of the camera, returns the
+    /// centers of the decayed tiles.
+    pub(crate) fn decay_tiles_in_view(
+        &mut self,
+        field_dimensions: FieldDimensions,
+        camera_matrix: &CameraMatrix,
+        ground_to_field: Isometry2<Ground, Field>,
+        decay_distance_factor: f32,
+        heatmap_decay_range: Range<f32>,
+    ) -> Vec<Point2<Field>> {
+        let cells_per_meter = self.cells_per_meter;
+        let field_to_ground = ground_to_field.inverse();
+        let mut observed_tiles = Vec::new();
+        self.map.indexed_iter_mut().for_each(|(tile, value)| {
+            let tile_center_in_field =
+                tile_center_in_field(field_dimensions, cells_per_meter, tile);
+            let tile_center_in_ground = field_to_ground * tile_center_in_field;
+            let distance_to_tile = tile_center_in_ground.coords().norm();
+            let relative_distance_to_tile =
+                clamp(distance_to_tile / heatmap_decay_range.end, 0.0, 1.0);
+            if is_in_view(camera_matrix, tile_center_in_ground)
+                && heatmap_decay_range.contains(&distance_to_tile)
+            {
+                *value *= 1.0 - decay_distance_factor * (1.0 - relative_distance_to_tile);
+                observed_tiles.push(tile_center_in_field);
             }
         });
+        observed_tiles
     }
 
     fn field_to_heatmap(
@@ -169,6 +218,7 @@ impl Heatmap {
                     last_seen: Time::from_wallclock(time) - ball.age,
                 })
             }
+            HulkMessage::VisualReferee(_) => None,
         };
         if let Some(ball_position) = ball {
             let heatmap_point = self.field_to_heatmap(field_dimensions, ball_position.position);
@@ -177,6 +227,26 @@ impl Heatmap {
     }
 }
 
+pub(crate) fn tile_center_in_field(
+    field_dimensions: FieldDimensions,
+    cells_per_meter: f32,
+    (x, y): (usize, usize),
+) -> Point2<Field> {
+    point![
+        ((x as f32 + 1.0 / 2.0) / cells_per_meter - field_dimensions.length / 2.0),
+        ((y as f32 + 1.0 / 2.0) / cells_per_meter - field_dimensions.width / 2.0)
+    ]
+}
+
+fn is_in_view(camera_matrix: &CameraMatrix, point_in_ground: Point2<Ground>) -> bool {
+    camera_matrix
+        .ground_to_pixel(point_in_ground)
+        .is_ok_and(|pixel| {
+            (0.0..camera_matrix.image_size.x()).contains(&pixel.x())
+                && (0.0..camera_matrix.image_size.y()).contains(&pixel.y())
+        })
+}
+
 fn get_rule_hypotheses(
     primary_state: PrimaryState,
     filtered_game_controller_state: &FilteredGameControllerState,
@@ -245,3 +315,102 @@ fn get_direction(base_vector: Vector2<Field>, vector_to_test: Vector2<Field>) ->
         f => panic!("directed cathetus was not a real number: {f}"),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::{FRAC_PI_2, FRAC_PI_4};
+
+    use linear_algebra::{IntoTransform, Isometry3, vector};
+    use ndarray::array;
+
+    use super::*;
+
+    fn heatmap(map: Array2<f32>) -> Heatmap {
+        Heatmap {
+            map,
+            cells_per_meter: 1.0,
+            last_maximum_heatmap_position: None,
+            has_decided_for_heatmap_tile: false,
+        }
+    }
+
+    /// Camera one meter above the ground looking forward and 45 degrees down.
+    fn camera_looking_down() -> CameraMatrix {
+        let head_to_camera = (nalgebra::Isometry3::rotation(nalgebra::Vector3::x() * FRAC_PI_4)
+            * nalgebra::Isometry3::rotation(nalgebra::Vector3::y() * -FRAC_PI_2)
+            * nalgebra::Isometry3::rotation(nalgebra::Vector3::x() * FRAC_PI_2)
+            * nalgebra::Isometry3::translation(0.0, 0.0, -1.0))
+        .framed_transform();
+        CameraMatrix::from_normalized_focal_and_center(
+            nalgebra::vector![1.0, 1.0],
+            nalgebra::point![0.5, 0.5],
+            vector![640.0, 480.0],
+            Isometry3::identity(),
+            Isometry3::identity(),
+            head_to_camera,
+        )
+    }
+
+    #[test]
+    fn maximum_position_skips_excluded_tiles() {
+        let heatmap = heatmap(array![[0.1, 0.4], [0.3, 0.2]]);
+
+        assert_eq!(heatmap.get_maximum_position(0.0, |_| false), Some((0, 1)));
+        assert_eq!(
+            heatmap.get_maximum_position(0.0, |tile| tile == (0, 1)),
+            Some((1, 0))
+        );
+        assert_eq!(
+            heatmap.get_maximum_position(0.35, |tile| tile == (0, 1)),
+            None
+        );
+        assert_eq!(heatmap.get_maximum_position(0.0, |_| true), None);
+    }
+
+    #[test]
+    fn team_search_state_decays_only_observed_cells() {
+        let field_dimensions = FieldDimensions::SPL_2025;
+        let mut heatmap = heatmap(Array2::ones((9, 6)));
+        let mut search_state = SearchState::default();
+        search_state.set_observed((0, 0));
+
+        heatmap.update_with_team_search_state(field_dimensions, &search_state, 0.5);
+
+        for (tile, value) in heatmap.map.indexed_iter() {
+            let tile_center = tile_center_in_field(field_dimensions, 1.0, tile);
+            let cell =
+                SearchState::cell_at(tile_center, field_dimensions.length, field_dimensions.width);
+            let expected = if cell == (0, 0) { 0.5 } else { 1.0 };
+            assert_eq!(*value, expected, "tile {tile:?}");
+        }
+        assert_eq!(heatmap.map[(0, 0)], 0.5);
+    }
+
+    #[test]
+    fn only_tiles_in_the_image_are_decayed() {
+        let field_dimensions = FieldDimensions::SPL_2025;
+        let mut heatmap = heatmap(Array2::ones((9, 6)));
+        let ground_to_field = Isometry2::identity();
+
+        let observed_tiles = heatmap.decay_tiles_in_view(
+            field_dimensions,
+            &camera_looking_down(),
+            ground_to_field,
+            0.5,
+            0.0..5.0,
+        );
+
+        let in_front = point![1.0, 0.5];
+        assert!(observed_tiles.contains(&in_front), "{observed_tiles:?}");
+        assert!(
+            observed_tiles
+                .iter()
+                .all(|tile| tile.x() > 0.0 && tile.x() < 4.0),
+            "{observed_tiles:?}"
+        );
+        let in_front_tile = heatmap.field_to_heatmap(field_dimensions, in_front);
+        assert!(heatmap.map[in_front_tile] < 1.0);
+        let behind_tile = heatmap.field_to_heatmap(field_dimensions, point![-1.0, 0.5]);
+        assert_eq!(heatmap.map[behind_tile], 1.0);
+    }
+}
@@ -3,9 +3,11 @@ use color_eyre::{
     eyre::{WrapErr as _, ensure},
 };
 use coordinate_systems::{Field, Ground};
-use linear_algebra::{Isometry2, Point2, Vector2, point, vector};
+use hsl_network_messages::{HulkMessage, PlayerNumber, SearchState};
+use linear_algebra::{Isometry2, Point2, Vector2, distance, vector};
 use ndarray::{Array2, array};
 use ndarray_conv::{ConvExt, ConvMode, PaddingMode};
+use projection::camera_matrix::CameraMatrix;
 use ros_z::{prelude::*, qos::QosDurability};
 use std::{boxed::Box, f32::consts, future::Future, pin::Pin, sync::Arc, time::Duration};
 use types::{
@@ -18,7 +20,9 @@ use types::{
     time_wrapper::TimeWrapper,
 };
 mod heatmap;
-use heatmap::Heatmap;
+mod team_search;
+use heatmap::{Heatmap, tile_center_in_field};
+use team_search::TeamSearch;
 
 pub fn run_boxed(ctx: Arc<Context>) -> Pin<Box<dyn Future<Output = Result<()>> + Send>> {
     Box::pin(run(ctx))
@@ -51,6 +55,20 @@ async fn run(ctx: Arc<Context>) -> Result<()> {
         .cache(10)
         .build()
         .await?;
+    let camera_matrix_cache = node
+        .subscriber::<TimeWrapper<CameraMatrix>>("camera_matrix")
+        .cache(1)
+        .build()
+        .await?;
+    let player_number_cache = node
+        .subscriber::<PlayerNumber>("player_number")
+        .qos(QosProfile {
+            durability: QosDurability::TransientLocal,
+            ..Default::default()
+        })
+        .cache(1)
+        .build()
+        .await?;
     let primary_state_cache = node
         .subscriber::<PrimaryState>("primary_state")
         .qos(QosProfile {
@@ -76,6 +94,10 @@ async fn run(ctx: Arc<Context>) -> Result<()> {
         .publisher::<Point2<Field>>("suggested_search_position")
         .build()
         .await?;
+    let search_state_pub = node
+        .publisher::<SearchState>("search_state")
+        .build()
+        .await?;
 
     let field_dimensions = field_dimensions_sub.recv().await?;
     let initial_parameters_snapshot = parameters.snapshot();
@@ -101,10 +123,12 @@ async fn run(ctx: Arc<Context>) -> Result<()> {
         last_maximum_heatmap_position: None,
         has_decided_for_heatmap_tile: false,
     };
+    let mut team_search = TeamSearch::new();
 
     loop {
         let parameters_snapshot = parameters.snapshot();
         let parameters = parameters_snapshot.typed();
+        let now = node.clock().now();
 
         let ground_to_field = ground_to_field_cache
             .get_latest()
@@ -133,11 +157,16 @@ async fn run(ctx: Arc<Context>) -> Result<()> {
             }
         }
         while network_message_sub.is_ready() {
-            heatmap.update_with_team_ball(
-                field_dimensions,
-                network_message_sub.recv().await?,
-                parameters,
-            );
+            let network_message = network_message_sub.recv().await?;
+            if let IncomingMessage::Hsl(HulkMessage::State(state_message)) = network_message.inner {
+                heatmap.update_with_team_search_state(
+                    field_dimensions,
+                    &state_message.search_state,
+                    parameters.team_observation_decay,
+                );
+                team_search.update_with_teammate(network_message.time, &state_message);
+            }
+            heatmap.update_with_team_ball(field_dimensions, network_message, parameters);
         }
         while filtered_game_controller_state_sub.is_ready() {
             if let Some(primary_state) = primary_state {
@@ -153,22 +182,24 @@ async fn run(ctx: Arc<Context>) -> Result<()> {
         }
 
         if !ball_was_seen && let Some(ground_to_field) = ground_to_field {
-            let robot_position = ground_to_field.as_pose().position().coords();
-            let body_orientation = ground_to_field.orientation().angle();
-            let fov_angle_offset = 45.0 * consts::PI / 180.0;
-            let left_angle = body_orientation - fov_angle_offset;
-            let right_angle = body_orientation + fov_angle_offset;
-            let left_edge: Vector2<Field> = vector!(left_angle.cos(), left_angle.sin());
-            let right_edge: Vector2<Field> = vector!(right_angle.cos(), right_angle.sin());
-
-            heatmap.decay_tiles_in_fov(
-                field_dimensions,
-                robot_position,
-                left_edge,
-                right_edge,
-                parameters.decay_distance_factor,
-                parameters.heatmap_decay_range.clone(),
-            );
+            let observed_tiles = match camera_matrix_cache.get_latest() {
+                Some(camera_matrix) => heatmap.decay_tiles_in_view(
+                    field_dimensions,
+                    &camera_matrix.inner,
+                    ground_to_field,
+                    parameters.decay_distance_factor,
+                    parameters.heatmap_decay_range.clone(),
+                ),
+                None => decay_tiles_in_body_fov(
+                    &mut heatmap,
+                    field_dimensions,
+                    ground_to_field,
+                    parameters,
+                ),
+            };
+            for tile_center in observed_tiles {
+                team_search.observe(field_dimensions, tile_center, now);
+            }
         }
 
         let kernel = create_kernel(parameters.heatmap_convolution_kernel_weight);
@@ -178,32 +209,60 @@ async fn run(ctx: Arc<Context>) -> Result<()> {
             .wrap_err("heatmap convolution failed")?;
         heatmap.map /= heatmap.map.sum();
 
+        let claimed_search_positions = player_number_cache
+            .get_latest()
+            .map(|player_number| {
+                team_search.claimed_search_positions(
+                    *player_number,
+                    now,
+                    parameters.teammate_search_state_timeout,
+                )
+            })
+            .unwrap_or_default();
+        let cells_per_meter = heatmap.cells_per_meter;
+        let is_claimed = |tile| {
+            let tile_center = tile_center_in_field(field_dimensions, cells_per_meter, tile);
+            claimed_search_positions.iter().any(|search_position| {
+                distance(*search_position, tile_center)
+                    < parameters.teammate_search_exclusion_radius
+            })
+        };
+
         if !heatmap.has_decided_for_heatmap_tile {
-            let suggested_search_index = heatmap.get_maximum_position(parameters.minimum_validity);
+            let suggested_search_index =
+                heatmap.get_maximum_position(parameters.minimum_validity, &is_claimed);
             if suggested_search_index.is_some() {
                 heatmap.has_decided_for_heatmap_tile = true;
             }
             heatmap.last_maximum_heatmap_position = suggested_search_index;
         } else if let Some(last_maximum_heatmap_index) = heatmap.last_maximum_heatmap_position {
             let global_max_value = heatmap
-                .get_maximum_position(0.0)
+                .get_maximum_position(0.0, &is_claimed)
                 .map_or(0.0, |idx| heatmap.map[idx]);
             let current_tile_value = heatmap.map[last_maximum_heatmap_index];
 
-            if current_tile_value < global_max_value * parameters.tile_switch_hysteresis {
+            if is_claimed(last_maximum_heatmap_index)
+                || current_tile_value < global_max_value * parameters.tile_switch_hysteresis
+            {
                 heatmap.has_decided_for_heatmap_tile = false;
             }
         }
 
-        if let Some((x, y)) = heatmap.last_maximum_heatmap_position {
-            let suggested_search_position = point![
-                ((x as f32 + 1.0 / 2.0) / heatmap.cells_per_meter - field_dimensions.length / 2.0),
-                ((y as f32 + 1.0 / 2.0) / heatmap.cells_per_meter - field_dimensions.width / 2.0)
-            ];
+        let suggested_search_position = heatmap
+            .last_maximum_heatmap_position
+            .map(|tile| tile_center_in_field(field_dimensions, cells_per_meter, tile));
+        if let Some(suggested_search_position) = suggested_search_position {
             suggested_search_position_pub
                 .publish(&suggested_search_position)
                 .await?;
         }
+        search_state_pub
+            .publish(&team_search.search_state(
+                now,
+                parameters.observed_cell_memory,
+                suggested_search_position,
+            ))
+            .await?;
 
         additional_heatmap_pub
             .publish_if_subscribed(|| async { heatmap.to_message() })
@@ -213,6 +272,31 @@ async fn run(ctx: Arc<Context>) -> Result<()> {
     }
 }
 
+/// Decays the tiles in front of the body when no camera matrix is available.
+fn decay_tiles_in_body_fov(
+    heatmap: &mut Heatmap,
+    field_dimensions: FieldDimensions,
+    ground_to_field: Isometry2<Ground, Field>,
+    parameters: &SearchSuggestorParameters,
+) -> Vec<Point2<Field>> {
+    let robot_position = ground_to_field.as_pose().position().coords();
+    let body_orientation = ground_to_field.orientation().angle();
+    let fov_angle_offset = 45.0 * consts::PI / 180.0;
+    let left_angle = body_orientation - fov_angle_offset;
+    let right_angle = body_orientation + fov_angle_offset;
+    let left_edge: Vector2<Field> = vector!(left_angle.cos(), left_angle.sin());
+    let right_edge: Vector2<Field> = vector!(right_angle.cos(), right_angle.sin());
+
+    heatmap.decay_tiles_in_fov(
+        field_dimensions,
+        robot_position,
+        left_edge,
+        right_edge,
+        parameters.decay_distance_factor,
+        parameters.heatmap_decay_range.clone(),
+    )
+}
+
 fn create_kernel(alpha: f32) -> Array2<f32> {
     array![
         [alpha, alpha, alpha],
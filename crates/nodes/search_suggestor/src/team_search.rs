@@ -0,0 +1,157 @@
+use std::{collections::BTreeMap, time::Duration};
+
+use coordinate_systems::Field;
+use hsl_network_messages::{
+    PlayerNumber, SEARCH_GRID_LENGTH, SEARCH_GRID_WIDTH, SearchState, StateMessage,
+};
+use linear_algebra::Point2;
+use ndarray::Array2;
+use ros_z::time::Time;
+use types::field_dimensions::FieldDimensions;
+
+/// Search state shared within the team, so that robots cover complementary regions.
+///
+/// Robots with a lower player number take precedence: their search positions are avoided by
+/// all teammates with a higher number.
+pub(crate) struct TeamSearch {
+    last_observed: Array2<Option<Time>>,
+    teammate_search_positions: BTreeMap<PlayerNumber, (Time, Point2<Field>)>,
+}
+
+impl TeamSearch {
+    pub(crate) fn new() -> Self {
+        Self {
+            last_observed: Array2::from_elem((SEARCH_GRID_LENGTH, SEARCH_GRID_WIDTH), None),
+            teammate_search_positions: BTreeMap::new(),
+        }
+    }
+
+    pub(crate) fn observe(
+        &mut self,
+        field_dimensions: FieldDimensions,
+        position: Point2<Field>,
+        now: Time,
+    ) {
+        let cell = SearchState::cell_at(position, field_dimensions.length, field_dimensions.width);
+        self.last_observed[cell] = Some(now);
+    }
+
+    pub(crate) fn update_with_teammate(&mut self, time: Time, message: &StateMessage) {
+        match message.search_state.search_position {
+            Some(search_position) => {
+                self.teammate_search_positions
+                    .insert(message.player_number, (time, search_position));
+            }
+            None => {
+                self.teammate_search_positions
+                    .remove(&message.player_number);
+            }
+        }
+    }
+
+    pub(crate) fn claimed_search_positions(
+        &self,
+        own_player_number: PlayerNumber,
+        now: Time,
+        timeout: Duration,
+    ) -> Vec<Point2<Field>> {
+        self.teammate_search_positions
+            .range(..own_player_number)
+            .filter(|(_, (time, _))| now.duration_since(*time) < timeout)
+            .map(|(_, (_, search_position))| *search_position)
+            .collect()
+    }
+
+    pub(crate) fn search_state(
+        &self,
+        now: Time,
+        observed_cell_memory: Duration,
+        search_position: Option<Point2<Field>>,
+    ) -> SearchState {
+        let mut search_state = SearchState {
+            observed_cells: 0,
+            search_position,
+        };
+        for (cell, last_observed) in self.last_observed.indexed_iter() {
+            if last_observed.is_some_and(|time| now.duration_since(time) < observed_cell_memory) {
+                search_state.set_observed(cell);
+            }
+        }
+        search_state
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use linear_algebra::{Pose2, point};
+
+    use super::*;
+
+    fn message(
+        player_number: PlayerNumber,
+        search_position: Option<Point2<Field>>,
+    ) -> StateMessage {
+        StateMessage {
+            player_number,
+            pose: Pose2::new(point![0.0, 0.0], 0.0),
+            ball_position: None,
+            search_state: SearchState {
+                observed_cells: 0,
+                search_position,
+            },
+        }
+    }
+
+    #[test]
+    fn only_lower_player_numbers_claim_search_positions() {
+        let mut team_search = TeamSearch::new();
+        let now = Time::from_nanos(1_000_000_000);
+        team_search.update_with_teammate(now, &message(PlayerNumber::Two, Some(point![1.0, 0.0])));
+        team_search.update_with_teammate(now, &message(PlayerNumber::Four, Some(point![2.0, 0.0])));
+
+        let claimed =
+            team_search.claimed_search_positions(PlayerNumber::Three, now, Duration::from_secs(1));
+
+        assert_eq!(claimed, vec![point![1.0, 0.0]]);
+    }
+
+    #[test]
+    fn stale_and_withdrawn_search_positions_are_not_claimed() {
+        let mut team_search = TeamSearch::new();
+        let sent = Time::from_nanos(1_000_000_000);
+        team_search.update_with_teammate(sent, &message(PlayerNumber::One, Some(point![1.0, 0.0])));
+        team_search.update_with_teammate(sent, &message(PlayerNumber::Two, Some(point![2.0, 0.0])));
+        team_search.update_with_teammate(sent, &message(PlayerNumber::Two, None));
+
+        let timeout = Duration::from_secs(1);
+        let fresh = sent + Duration::from_millis(500);
+        let stale = sent + Duration::from_secs(2);
+
+        assert_eq!(
+            team_search.claimed_search_positions(PlayerNumber::Five, fresh, timeout),
+            vec![point![1.0, 0.0]]
+        );
+        assert!(
+            team_search
+                .claimed_search_positions(PlayerNumber::Five, stale, timeout)
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn observed_cells_are_forgotten_after_the_memory() {
+        let field_dimensions = FieldDimensions::SPL_2025;
+        let mut team_search = TeamSearch::new();
+        let observed = Time::from_nanos(1_000_000_000);
+        let position = point![-4.0, -2.5];
+        team_search.observe(field_dimensions, position, observed);
+
+        let memory = Duration::from_secs(5);
+        let recent = team_search.search_state(observed + Duration::from_secs(1), memory, None);
+        let forgotten = team_search.search_state(observed + Duration::from_secs(6), memory, None);
+
+        assert!(recent.is_observed((0, 0)));
+        assert_eq!(recent.observed_cells.count_ones(), 1);
+        assert_eq!(forgotten.observed_cells, 0);
+    }
+}
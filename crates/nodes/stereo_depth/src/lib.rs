@@ -0,0 +1,176 @@
+use std::{boxed::Box, future::Future, pin::Pin, sync::Arc, time::Duration};
+
+use color_eyre::{Result, eyre::WrapErr};
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use projection::camera_matrix::CameraMatrix;
+use ros_z::{prelude::*, qos::QosDurability};
+use ros_z_streams::{CreateAnnouncingPublisher, CreateFutureMapBuilder};
+use ros2::sensor_msgs::camera_info::CameraInfo;
+use types::{
+    object_detection::{Object, RobocupObjectLabel},
+    stereo_depth::StereoObject,
+    stereo_image_pair::StereoImagePair,
+    time_wrapper::TimeWrapper,
+};
+
+use crate::{
+    luminance::Luminance,
+    matching::{MatchingParameters, disparity_in_area},
+    triangulation::{StereoCamera, camera_to_ground},
+};
+
+pub mod luminance;
+pub mod matching;
+pub mod triangulation;
+
+#[derive(Debug, Clone, Serialize, Deserialize, Message)]
+#[serde(deny_unknown_fields)]
+pub struct Parameters {
+    pub enable: bool,
+    /// Labels of the detected objects which are located in the stereo image pair
+    pub labels: Vec<RobocupObjectLabel>,
+    pub matching: MatchingParameters,
+    /// Standard deviation of the bounding box center in pixels
+    pub pixel_noise: f32,
+}
+
+pub fn run_boxed(ctx: Arc<Context>) -> Pin<Box<dyn Future<Output = Result<()>> + Send>> {
+    Box::pin(run(ctx))
+}
+
+async fn run(ctx: Arc<Context>) -> Result<()> {
+    let node = ctx
+        .create_node("stereo_depth")
+        .build()
+        .await
+        .wrap_err("failed to create stereo_depth node")?;
+    let parameters = node
+        .bind_parameter_as::<Parameters>("stereo_depth")
+        .wrap_err("failed to bind stereo_depth parameters")?;
+
+    let left_camera_info_cache = node
+        .subscriber::<CameraInfo>("inputs/camera_info")
+        .qos(QosProfile {
+            durability: QosDurability::TransientLocal,
+            ..Default::default()
+        })
+        .cache(1)
+        .build()
+        .await?;
+    let right_camera_info_cache = node
+        .subscriber::<CameraInfo>("inputs/right_camera_info")
+        .qos(QosProfile {
+            durability: QosDurability::TransientLocal,
+            ..Default::default()
+        })
+        .cache(1)
+        .build()
+        .await?;
+    let camera_matrix_cache = node
+        .subscriber::<TimeWrapper<CameraMatrix>>("camera_matrix")
+        .cache(10)
+        .with_stamp(|wrapper| wrapper.time)
+        .build()
+        .await?;
+    let stereo_image_pair_cache = node
+        .subscriber::<TimeWrapper<StereoImagePair>>("inputs/stereo_image_pair")
+        .cache(5)
+        .with_stamp(|wrapper| wrapper.time)
+        .build()
+        .await?;
+
+    let mut detected_objects = node
+        .create_future_map_builder()
+        .create_future_subscriber::<Vec<Object<RobocupObjectLabel>>>(
+            "detected_objects",
+            Duration::from_millis(50),
+        )
+        .await?
+        .build();
+
+    let stereo_objects_pub = node
+        .announcing_publisher::<Vec<StereoObject<RobocupObjectLabel>>>("stereo_objects")
+        .await?;
+
+    loop {
+        let item = detected_objects.recv().await?;
+        let parameters_snapshot = parameters.snapshot();
+        let parameters = parameters_snapshot.typed();
+
+        for (detection_time, (detected_objects,)) in item.persistent {
+            let stereo_objects_pending = stereo_objects_pub.announce(detection_time).await?;
+
+            let stereo_camera = left_camera_info_cache
+                .get_latest()
+                .zip(right_camera_info_cache.get_latest())
+                .and_then(|(left, right)| StereoCamera::from_camera_infos(&left, &right));
+            // the stereo image pair is stamped with the time of the left image the objects are
+            // detected in
+            let stereo_image_pair = stereo_image_pair_cache
+                .get_nearest(detection_time)
+                .filter(|stereo_image_pair| stereo_image_pair.time == detection_time);
+            let camera_matrix = camera_matrix_cache.get_nearest(detection_time);
+
+            let stereo_objects = match (
+                parameters.enable,
+                detected_objects,
+                stereo_camera,
+                stereo_image_pair,
+                camera_matrix,
+            ) {
+                (
+                    true,
+                    Some(detected_objects),
+                    Some(stereo_camera),
+                    Some(stereo_image_pair),
+                    Some(camera_matrix),
+                ) => locate_objects(
+                    &detected_objects,
+                    &stereo_image_pair.inner,
+                    &stereo_camera,
+                    &camera_matrix.inner,
+                    parameters,
+                )
+                .unwrap_or_else(|error| {
+                    warn!("failed to locate objects in stereo image pair: {error:#}");
+                    Vec::new()
+                }),
+                _ => Vec::new(),
+            };
+
+            stereo_objects_pending.publish(&stereo_objects).await?;
+        }
+    }
+}
+
+fn locate_objects(
+    detected_objects: &[Object<RobocupObjectLabel>],
+    stereo_image_pair: &StereoImagePair,
+    stereo_camera: &StereoCamera,
+    camera_matrix: &CameraMatrix,
+    parameters: &Parameters,
+) -> Result<Vec<StereoObject<RobocupObjectLabel>>> {
+    let left = Luminance::from_image(&stereo_image_pair.left)?;
+    let right = Luminance::from_image(&stereo_image_pair.right)?;
+
+    Ok(detected_objects
+        .iter()
+        .filter(|object| parameters.labels.contains(&object.label))
+        .filter_map(|object| {
+            let area = object.bounding_box.area;
+            let disparity = disparity_in_area(&left, &right, area, &parameters.matching)?;
+            let position_in_camera =
+                stereo_camera.triangulate(area.center(), disparity, parameters.pixel_noise)?;
+            Some(StereoObject {
+                object: *object,
+                position_in_ground: camera_to_ground(
+                    position_in_camera,
+                    camera_matrix.ground_to_camera,
+                ),
+                disparity: disparity.disparity,
+            })
+        })
+        .collect())
+}
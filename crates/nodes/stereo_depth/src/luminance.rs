@@ -0,0 +1,71 @@
+use std::borrow::Cow;
+
+use color_eyre::{Result, eyre::bail};
+use ros2::sensor_msgs::image::Image;
+
+/// Single channel intensity view of an image, borrowed from the Y plane where possible.
+pub struct Luminance<'image> {
+    pub width: usize,
+    pub height: usize,
+    stride: usize,
+    data: Cow<'image, [u8]>,
+}
+
+impl<'image> Luminance<'image> {
+    pub fn new(width: usize, height: usize, data: Vec<u8>) -> Self {
+        Self {
+            width,
+            height,
+            stride: width,
+            data: Cow::Owned(data),
+        }
+    }
+
+    pub fn from_image(image: &'image Image) -> Result<Self> {
+        let width = image.width as usize;
+        let height = image.height as usize;
+        let stride = image.step as usize;
+        let data = match image.encoding.as_str() {
+            "nv12" | "mono8" => {
+                if image.data.len() < stride * height {
+                    bail!("image buffer is too small for {width}x{height}");
+                }
+                Cow::Borrowed(&image.data[..stride * height])
+            }
+            "rgb8" | "bgr8" => {
+                if image.data.len() < stride * height {
+                    bail!("image buffer is too small for {width}x{height}");
+                }
+                return Ok(Self::new(
+                    width,
+                    height,
+                    image
+                        .data
+                        .chunks_exact(stride)
+                        .take(height)
+                        .flat_map(|row| {
+                            row.chunks_exact(3).take(width).map(|pixel| {
+                                ((u16::from(pixel[0])
+                                    + 2 * u16::from(pixel[1])
+                                    + u16::from(pixel[2]))
+                                    / 4) as u8
+                            })
+                        })
+                        .collect(),
+                ));
+            }
+            encoding => bail!("unsupported image encoding: {encoding}"),
+        };
+
+        Ok(Self {
+            width,
+            height,
+            stride,
+            data,
+        })
+    }
+
+    pub fn at(&self, x: usize, y: usize) -> u8 {
+        self.data[y * self.stride + x]
+    }
+}
@@ -0,0 +1,234 @@
+use serde::{Deserialize, Serialize};
+
+use coordinate_systems::Pixel;
+use geometry::rectangle::Rectangle;
+use linear_algebra::point;
+
+use crate::luminance::Luminance;
+
+/// Scales the median absolute deviation to the standard deviation of a normal distribution.
+const MEDIAN_ABSOLUTE_DEVIATION_TO_STANDARD_DEVIATION: f32 = 1.4826;
+
+#[derive(Clone, Debug, Serialize, Deserialize, ros_z::Message)]
+#[serde(deny_unknown_fields)]
+pub struct MatchingParameters {
+    /// Half size of the square patches compared between both images
+    pub patch_radius: usize,
+    /// Distance between the sampled patch centers in pixels
+    pub sample_spacing: usize,
+    /// Fraction of the bounding box around its center in which patches are sampled, keeps the
+    /// background in the corners of the bounding box out of the estimate
+    pub sampled_area_fraction: f32,
+    pub maximum_disparity: usize,
+    /// The best matching cost has to be below this fraction of the second best one
+    pub uniqueness_ratio: f32,
+    /// Minimum mean absolute horizontal gradient within a patch, untextured patches are skipped
+    pub minimum_texture: f32,
+    pub minimum_number_of_samples: usize,
+    /// Standard deviation of a single sub-pixel disparity
+    pub disparity_noise: f32,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DisparityEstimate {
+    pub disparity: f32,
+    pub standard_deviation: f32,
+    pub number_of_samples: usize,
+}
+
+/// Block matching along the rows of a rectified image pair within the given area of the left
+/// image, robustly combined to a single disparity.
+pub fn disparity_in_area(
+    left: &Luminance,
+    right: &Luminance,
+    area: Rectangle<Pixel>,
+    parameters: &MatchingParameters,
+) -> Option<DisparityEstimate> {
+    let center = area.center();
+    let half_size = (area.max - area.min) * parameters.sampled_area_fraction / 2.0;
+    let radius = parameters.patch_radius;
+    let spacing = parameters.sample_spacing.max(1);
+
+    let to_index = |value: f32, size: usize| value.clamp(0.0, size as f32) as usize;
+    let minimum_x = to_index(center.x() - half_size.x(), left.width).max(radius);
+    let maximum_x =
+        to_index(center.x() + half_size.x(), left.width).min(left.width.saturating_sub(radius + 1));
+    let minimum_y = to_index(center.y() - half_size.y(), left.height).max(radius);
+    let maximum_y = to_index(center.y() + half_size.y(), left.height)
+        .min(left.height.saturating_sub(radius + 1));
+    if minimum_x > maximum_x || minimum_y > maximum_y {
+        return None;
+    }
+
+    let mut disparities: Vec<f32> = (minimum_y..=maximum_y)
+        .step_by(spacing)
+        .flat_map(|y| {
+            (minimum_x..=maximum_x)
+                .step_by(spacing)
+                .map(move |x| (x, y))
+        })
+        .filter_map(|(x, y)| match_patch(left, right, point![x as f32, y as f32], parameters))
+        .collect();
+    if disparities.len() < parameters.minimum_number_of_samples.max(1) {
+        return None;
+    }
+
+    let disparity = median(&mut disparities);
+    let mut absolute_deviations: Vec<f32> = disparities
+        .iter()
+        .map(|sample| (sample - disparity).abs())
+        .collect();
+    let spread = median(&mut absolute_deviations) * MEDIAN_ABSOLUTE_DEVIATION_TO_STANDARD_DEVIATION;
+    let number_of_samples = disparities.len();
+    let standard_deviation =
+        (spread.powi(2) / number_of_samples as f32 + parameters.disparity_noise.powi(2)).sqrt();
+
+    Some(DisparityEstimate {
+        disparity,
+        standard_deviation,
+        number_of_samples,
+    })
+}
+
+/// Returns the sub-pixel disparity of the patch centered at `center` in the left image.
+fn match_patch(
+    left: &Luminance,
+    right: &Luminance,
+    center: linear_algebra::Point2<Pixel>,
+    parameters: &MatchingParameters,
+) -> Option<f32> {
+    let radius = parameters.patch_radius;
+    let (x, y) = (center.x() as usize, center.y() as usize);
+    let rows = y - radius..=y + radius;
+    let columns = x - radius..=x + radius;
+
+    let texture: u32 = rows
+        .clone()
+        .flat_map(|row| {
+            columns.clone().skip(1).map(move |column| {
+                u32::from(left.at(column, row).abs_diff(left.at(column - 1, row)))
+            })
+        })
+        .sum();
+    let number_of_gradients = (2 * radius + 1) * (2 * radius);
+    if (texture as f32) < parameters.minimum_texture * number_of_gradients as f32 {
+        return None;
+    }
+
+    let maximum_disparity = parameters.maximum_disparity.min(x - radius);
+    let costs: Vec<u32> = (0..=maximum_disparity)
+        .map(|disparity| {
+            rows.clone()
+                .flat_map(|row| {
+                    columns.clone().map(move |column| {
+                        u32::from(
+                            left.at(column, row)
+                                .abs_diff(right.at(column - disparity, row)),
+                        )
+                    })
+                })
+                .sum()
+        })
+        .collect();
+
+    let (best_disparity, best_cost) = costs
+        .iter()
+        .copied()
+        .enumerate()
+        .min_by_key(|(_, cost)| *cost)?;
+    if best_disparity == 0 || best_disparity == maximum_disparity {
+        return None;
+    }
+    let second_best_cost = costs
+        .iter()
+        .enumerate()
+        .filter(|(disparity, _)| disparity.abs_diff(best_disparity) > 1)
+        .map(|(_, cost)| *cost)
+        .min()?;
+    if best_cost as f32 >= parameters.uniqueness_ratio * second_best_cost as f32 {
+        return None;
+    }
+
+    let previous = costs[best_disparity - 1] as f32;
+    let next = costs[best_disparity + 1] as f32;
+    let curvature = previous - 2.0 * best_cost as f32 + next;
+    let offset = if curvature > 0.0 {
+        (previous - next) / (2.0 * curvature)
+    } else {
+        0.0
+    };
+
+    Some(best_disparity as f32 + offset)
+}
+
+fn median(values: &mut [f32]) -> f32 {
+    let middle = values.len() / 2;
+    let (_, median, _) = values.select_nth_unstable_by(middle, f32::total_cmp);
+    *median
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WIDTH: usize = 160;
+    const HEIGHT: usize = 120;
+
+    fn texture(x: usize, y: usize) -> u8 {
+        let value = (x * 7919 + y * 104_729) ^ (x * y * 31);
+        (value % 251) as u8
+    }
+
+    fn parameters() -> MatchingParameters {
+        MatchingParameters {
+            patch_radius: 3,
+            sample_spacing: 4,
+            sampled_area_fraction: 0.8,
+            maximum_disparity: 40,
+            uniqueness_ratio: 0.9,
+            minimum_texture: 2.0,
+            minimum_number_of_samples: 3,
+            disparity_noise: 0.25,
+        }
+    }
+
+    #[test]
+    fn disparity_of_shifted_image_is_found() {
+        let shift = 12;
+        let left = Luminance::new(
+            WIDTH,
+            HEIGHT,
+            (0..HEIGHT)
+                .flat_map(|y| (0..WIDTH).map(move |x| texture(x, y)))
+                .collect(),
+        );
+        let right = Luminance::new(
+            WIDTH,
+            HEIGHT,
+            (0..HEIGHT)
+                .flat_map(|y| (0..WIDTH).map(move |x| texture(x + shift, y)))
+                .collect(),
+        );
+        let area = Rectangle {
+            min: point![60.0, 40.0],
+            max: point![100.0, 80.0],
+        };
+
+        let estimate = disparity_in_area(&left, &right, area, &parameters()).unwrap();
+
+        assert!((estimate.disparity - shift as f32).abs() < 0.1);
+        assert!(estimate.number_of_samples >= 3);
+    }
+
+    #[test]
+    fn untextured_areas_have_no_disparity() {
+        let left = Luminance::new(WIDTH, HEIGHT, vec![128; WIDTH * HEIGHT]);
+        let right = Luminance::new(WIDTH, HEIGHT, vec![128; WIDTH * HEIGHT]);
+        let area = Rectangle {
+            min: point![60.0, 40.0],
+            max: point![100.0, 80.0],
+        };
+
+        assert_eq!(disparity_in_area(&left, &right, area, &parameters()), None);
+    }
+}
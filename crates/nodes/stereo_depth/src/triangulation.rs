@@ -0,0 +1,175 @@
+use nalgebra::{Matrix3, Vector3};
+
+use coordinate_systems::{Camera, Ground, Pixel};
+use linear_algebra::{Isometry3, Point2, point};
+use projection::intrinsic::ImageModel;
+use ros2::sensor_msgs::camera_info::CameraInfo;
+use types::multivariate_normal_distribution::MultivariateNormalDistribution;
+
+use crate::matching::DisparityEstimate;
+
+/// Rectified stereo camera described by the projection matrices of both camera infos.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct StereoCamera {
+    pub focals: nalgebra::Vector2<f32>,
+    pub optical_center: Point2<Pixel>,
+    pub baseline: f32,
+}
+
+impl StereoCamera {
+    /// The right projection matrix contains `-focal_x * baseline` in its fourth column, returns
+    /// `None` for a degenerate baseline or if the images are not rectified.
+    pub fn from_camera_infos(left: &CameraInfo, right: &CameraInfo) -> Option<Self> {
+        if ImageModel::from_camera_info(left) != ImageModel::Rectified
+            || ImageModel::from_camera_info(right) != ImageModel::Rectified
+        {
+            return None;
+        }
+        let focals = nalgebra::vector![left.p[0] as f32, left.p[5] as f32];
+        let baseline = -(right.p[3] / right.p[0]) as f32;
+        if !baseline.is_finite() || baseline <= 0.0 || focals.min() <= 0.0 {
+            return None;
+        }
+        Some(Self {
+            focals,
+            optical_center: point![left.p[2] as f32, left.p[6] as f32],
+            baseline,
+        })
+    }
+
+    /// Position of the left image pixel in the left camera frame together with its uncertainty
+    /// from the disparity and pixel noise.
+    pub fn triangulate(
+        &self,
+        pixel: Point2<Pixel>,
+        disparity: DisparityEstimate,
+        pixel_noise: f32,
+    ) -> Option<MultivariateNormalDistribution<3>> {
+        if disparity.disparity <= 0.0 {
+            return None;
+        }
+        let inverse_disparity = 1.0 / disparity.disparity;
+        let normalized_x = (pixel.x() - self.optical_center.x()) / self.focals.x;
+        let normalized_y = (pixel.y() - self.optical_center.y()) / self.focals.y;
+        let depth = self.focals.x * self.baseline * inverse_disparity;
+        let mean = Vector3::new(normalized_x * depth, normalized_y * depth, depth);
+
+        // derivatives of the position with respect to pixel x, pixel y and disparity
+        let jacobian = Matrix3::from_columns(&[
+            Vector3::new(depth / self.focals.x, 0.0, 0.0),
+            Vector3::new(0.0, depth / self.focals.y, 0.0),
+            -mean * inverse_disparity,
+        ]);
+        let noise = Matrix3::from_diagonal(&Vector3::new(
+            pixel_noise.powi(2),
+            pixel_noise.powi(2),
+            disparity.standard_deviation.powi(2),
+        ));
+
+        Some(MultivariateNormalDistribution {
+            mean,
+            covariance: jacobian * noise * jacobian.transpose(),
+        })
+    }
+}
+
+pub fn camera_to_ground(
+    position_in_camera: MultivariateNormalDistribution<3>,
+    ground_to_camera: Isometry3<Ground, Camera>,
+) -> MultivariateNormalDistribution<3> {
+    let camera_to_ground = ground_to_camera.inverse().inner;
+    let rotation = camera_to_ground.rotation.to_rotation_matrix().into_inner();
+    MultivariateNormalDistribution {
+        mean: (camera_to_ground * nalgebra::Point3::from(position_in_camera.mean)).coords,
+        covariance: rotation * position_in_camera.covariance * rotation.transpose(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use approx::assert_relative_eq;
+
+    use super::*;
+
+    fn stereo_camera() -> StereoCamera {
+        StereoCamera {
+            focals: nalgebra::vector![500.0, 500.0],
+            optical_center: point![320.0, 240.0],
+            baseline: 0.06,
+        }
+    }
+
+    #[test]
+    fn baseline_is_read_from_right_projection_matrix() {
+        let left = CameraInfo {
+            p: [
+                500.0, 0.0, 320.0, 0.0, 0.0, 500.0, 240.0, 0.0, 0.0, 0.0, 1.0, 0.0,
+            ],
+            ..Default::default()
+        };
+        let right = CameraInfo {
+            p: [
+                500.0, 0.0, 320.0, -30.0, 0.0, 500.0, 240.0, 0.0, 0.0, 0.0, 1.0, 0.0,
+            ],
+            ..Default::default()
+        };
+
+        assert_eq!(
+            StereoCamera::from_camera_infos(&left, &right),
+            Some(stereo_camera())
+        );
+        assert_eq!(StereoCamera::from_camera_infos(&left, &left), None);
+
+        let distorted_left = CameraInfo {
+            distortion_model: "plumb_bob".to_string(),
+            d: vec![-0.3, 0.1, 0.0, 0.0, 0.0],
+            ..left
+        };
+        assert_eq!(
+            StereoCamera::from_camera_infos(&distorted_left, &right),
+            None
+        );
+    }
+
+    #[test]
+    fn depth_uncertainty_grows_quadratically_with_distance() {
+        let stereo_camera = stereo_camera();
+        let estimate = |disparity| DisparityEstimate {
+            disparity,
+            standard_deviation: 0.5,
+            number_of_samples: 10,
+        };
+
+        let near = stereo_camera
+            .triangulate(point![320.0, 240.0], estimate(30.0), 1.0)
+            .unwrap();
+        let far = stereo_camera
+            .triangulate(point![320.0, 240.0], estimate(10.0), 1.0)
+            .unwrap();
+
+        assert_relative_eq!(near.mean, Vector3::new(0.0, 0.0, 1.0));
+        assert_relative_eq!(far.mean, Vector3::new(0.0, 0.0, 3.0));
+        assert_relative_eq!(
+            far.covariance[(2, 2)].sqrt(),
+            9.0 * near.covariance[(2, 2)].sqrt(),
+            max_relative = 1e-4
+        );
+    }
+
+    #[test]
+    fn off_center_pixels_are_triangulated_along_their_ray() {
+        let position = stereo_camera()
+            .triangulate(
+                point![420.0, 190.0],
+                DisparityEstimate {
+                    disparity: 15.0,
+                    standard_deviation: 0.5,
+                    number_of_samples: 10,
+                },
+                1.0,
+            )
+            .unwrap();
+
+        assert_relative_eq!(position.mean, Vector3::new(0.4, -0.2, 2.0), epsilon = 1e-5);
+    }
+}
@@ -1,10 +1,15 @@
 use std::{boxed::Box, future::Future, pin::Pin};
-use std::{future::pending, sync::Arc, time::Duration};
+use std::{sync::Arc, time::Duration};
 
 use color_eyre::Result;
 
-use ros_z::prelude::*;
-use types::world_state::BallState;
+use coordinate_systems::{Field, Ground};
+use linear_algebra::{Isometry2, point};
+use path_planner::walk_time::{WalkTimeModel, kick_pose};
+use ros_z::{prelude::*, qos::QosDurability};
+use types::{
+    field_dimensions::FieldDimensions, parameters::BehaviorParameters, world_state::BallState,
+};
 
 pub fn run_boxed(ctx: Arc<Context>) -> Pin<Box<dyn Future<Output = Result<()>> + Send>> {
     Box::pin(run(ctx))
@@ -15,13 +20,59 @@ async fn run(ctx: Arc<Context>) -> Result<()> {
         .create_node("time_to_reach_kick_position")
         .build()
         .await?;
-    let _ball_state_sub = node.subscriber::<BallState>("ball_state").build().await?;
-    let _time_to_reach_kick_position_pub = node
+
+    // shares the kick position and walking speeds with the striker of the behavior
+    let parameters = node.bind_parameter_as::<BehaviorParameters>("behavior_node")?;
+    let field_dimensions_cache = node
+        .subscriber::<FieldDimensions>("field_dimensions")
+        .qos(QosProfile {
+            durability: QosDurability::TransientLocal,
+            ..Default::default()
+        })
+        .cache(1)
+        .build()
+        .await?;
+    let ground_to_field_cache = node
+        .subscriber::<Isometry2<Ground, Field>>("ground_to_field")
+        .cache(1)
+        .build()
+        .await?;
+    let ball_state_sub = node
+        .subscriber::<Option<BallState>>("ball_state")
+        .build()
+        .await?;
+    let time_to_reach_kick_position_pub = node
         .publisher::<Duration>("time_to_reach_kick_position")
         .build()
         .await?;
 
-    pending::<()>().await;
+    loop {
+        let ball_state = ball_state_sub.recv().await?;
+        let parameters_snapshot = parameters.snapshot();
+        let parameters = parameters_snapshot.typed();
+
+        let (Some(ball_state), Some(field_dimensions), Some(ground_to_field)) = (
+            ball_state,
+            field_dimensions_cache.get_latest(),
+            ground_to_field_cache.get_latest(),
+        ) else {
+            continue;
+        };
+
+        let kick_pose = kick_pose(
+            ball_state.ball_in_field,
+            point![field_dimensions.length / 2.0, 0.0],
+            parameters.kicking.kick_position_ball_distance,
+        );
+        let walk_time_model =
+            WalkTimeModel::new(parameters.path_planning.walk_speed_extents.clone());
+        let time_to_reach_kick_position =
+            walk_time_model.time_to_reach(ground_to_field.as_pose(), kick_pose);
 
-    Ok(())
+        time_to_reach_kick_position_pub
+            .publish(
+                &Duration::try_from_secs_f32(time_to_reach_kick_position).unwrap_or(Duration::MAX),
+            )
+            .await?;
+    }
 }
@@ -1,2 +1,3 @@
 pub mod a_star;
 pub mod path_planner;
+pub mod walk_time;
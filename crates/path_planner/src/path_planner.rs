@@ -1,3 +1,5 @@
+use std::time::Duration;
+
 use color_eyre::{Result, eyre::eyre};
 use geometry::{
     arc::Arc,
@@ -13,14 +15,16 @@ use smallvec::SmallVec;
 use coordinate_systems::{Field, Ground};
 use types::{
     field_dimensions::FieldDimensions,
-    motion_command::MotionCommand,
     obstacles::Obstacle,
     path::{Path, PathSegment, traits::EndPoints},
     path_obstacles::{PathObstacle, PathObstacleShape},
     rule_obstacles::RuleObstacle,
 };
 
-use crate::a_star::{DynamicMap, a_star_search};
+use crate::{
+    a_star::{DynamicMap, a_star_search},
+    walk_time::WalkTimeModel,
+};
 
 #[derive(Debug, Clone)]
 pub struct PathNode {
@@ -41,40 +45,36 @@ impl From<Point2<Ground>> for PathNode {
     }
 }
 
+/// How far moving obstacles are predicted ahead when placing them on the map.
+#[derive(Clone, Copy, Debug)]
+pub struct ObstaclePrediction {
+    pub walking_speed: f32,
+    pub horizon: Duration,
+    /// Added to the radius per second of prediction to cover velocity uncertainty
+    pub radius_growth_per_second: f32,
+}
+
 #[derive(Debug, Default)]
 pub struct PathPlanner {
     /// The first node is always the start, the second the destination
     pub nodes: Vec<PathNode>,
     pub obstacles: Vec<PathObstacle>,
-    pub last_path_direction: Option<Orientation2<Ground>>,
-    pub rotation_penalty_factor: f32,
     pub obstacle_escape_spline_segments: u32,
+    /// Costs edges by expected walking time instead of distance when set
+    pub walk_time_model: Option<WalkTimeModel>,
+    pub target_orientation: Option<Orientation2<Ground>>,
 }
 
 impl PathPlanner {
-    pub fn with_last_motion(
+    /// Plans the fastest instead of the shortest path, starting facing forward and arriving at
+    /// `target_orientation`.
+    pub fn with_walk_time_model(
         &mut self,
-        last_motion_command: &MotionCommand,
-        rotation_penalty_factor: f32,
+        walk_time_model: WalkTimeModel,
+        target_orientation: Option<Orientation2<Ground>>,
     ) {
-        self.last_path_direction = match last_motion_command {
-            MotionCommand::Walk { path, .. } => path.segments.first().map(|segment| {
-                let direction = match segment {
-                    PathSegment::LineSegment(line_segment) => line_segment.1.coords(),
-                    PathSegment::Arc(arc) => {
-                        arc.start.as_unit_vector().rotate_90_degrees(arc.direction)
-                    }
-                };
-                if direction.norm_squared() < f32::EPSILON {
-                    Orientation2::identity()
-                } else {
-                    Orientation2::from_vector(direction)
-                }
-            }),
-            _ => None,
-        };
-
-        self.rotation_penalty_factor = rotation_penalty_factor;
+        self.walk_time_model = Some(walk_time_model);
+        self.target_orientation = target_orientation;
     }
 
     pub fn with_obstacles(&mut self, obstacles: &[Obstacle], own_robot_radius: f32) {
@@ -87,6 +87,61 @@ impl PathPlanner {
         self.obstacles.extend(new_obstacles);
     }
 
+    /// Blocks the area every moving obstacle sweeps until we reach `destination` walking straight
+    /// from `start`, at most for the prediction horizon. The swept capsule is built from a circle
+    /// at both ends and the two line segments connecting their sides.
+    pub fn with_predicted_obstacles(
+        &mut self,
+        obstacles: &[Obstacle],
+        own_robot_radius: f32,
+        start: Point2<Ground>,
+        destination: Point2<Ground>,
+        prediction: &ObstaclePrediction,
+    ) {
+        let travel_time = if prediction.walking_speed > 0.0 {
+            distance(start, destination) / prediction.walking_speed
+        } else {
+            0.0
+        };
+        let horizon = prediction.horizon.as_secs_f32().min(travel_time);
+
+        for obstacle in obstacles {
+            let sweep = obstacle.velocity * horizon;
+            let Some(direction) = sweep.try_normalize(f32::EPSILON) else {
+                self.with_obstacles(std::slice::from_ref(obstacle), own_robot_radius);
+                continue;
+            };
+            let radius = obstacle.radius_at_hip_height
+                + own_robot_radius
+                + prediction.radius_growth_per_second * horizon;
+            let start_center = obstacle.position;
+            let end_center = obstacle.position + sweep;
+            // the sides stay slightly inside the end circles, paths around the circles must not
+            // touch them
+            let side_offset =
+                direction.rotate_90_degrees(Direction::Counterclockwise) * radius * 0.9;
+
+            self.obstacles.extend([
+                PathObstacle::from(PathObstacleShape::Circle(Circle {
+                    center: start_center,
+                    radius,
+                })),
+                PathObstacle::from(PathObstacleShape::Circle(Circle {
+                    center: end_center,
+                    radius,
+                })),
+                PathObstacle::from(PathObstacleShape::LineSegment(LineSegment(
+                    start_center + side_offset,
+                    end_center + side_offset,
+                ))),
+                PathObstacle::from(PathObstacleShape::LineSegment(LineSegment(
+                    start_center - side_offset,
+                    end_center - side_offset,
+                ))),
+            ]);
+        }
+    }
+
     pub fn with_rule_obstacles(
         &mut self,
         field_to_robot: Isometry2<Field, Ground>,
@@ -499,18 +554,18 @@ impl PathPlanner {
 impl DynamicMap for PathPlanner {
     fn get_pathing_distance(&self, index1: usize, index2: usize) -> f32 {
         let direction = self.nodes[index2].position - self.nodes[index1].position;
-        let mut distance = direction.norm();
-
-        if index1 == 0
-            && distance > 0.0
-            && let Some(current_rotation) = self.last_path_direction
-        {
-            let rotation = current_rotation.rotation_to(Orientation2::from_vector(direction));
-
-            distance += rotation.angle().abs() * self.rotation_penalty_factor;
+        if let Some(walk_time_model) = &self.walk_time_model {
+            return walk_time_model.segment_time(
+                direction,
+                (index1 == 0).then(Orientation2::identity),
+                if index2 == 1 {
+                    self.target_orientation
+                } else {
+                    None
+                },
+            );
         }
-
-        distance
+        direction.norm()
     }
 
     fn get_available_exits(&mut self, index: usize) -> SmallVec<[(usize, f32); 10]> {
@@ -556,7 +611,13 @@ impl DynamicMap for PathPlanner {
                             .filter(|(index, _)| *index != obstacle_index)
                             .all(|(_, obstacle)| !obstacle.shape.overlaps_arc(arc))
                         {
-                            vector.push((*other_node, arc.length()));
+                            let cost = match &self.walk_time_model {
+                                Some(walk_time_model) => {
+                                    arc.length() * walk_time_model.seconds_per_meter(0.0)
+                                }
+                                None => arc.length(),
+                            };
+                            vector.push((*other_node, cost));
                         }
                     }
                 }
@@ -836,6 +897,97 @@ mod tests {
         );
     }
 
+    #[test]
+    fn predicted_obstacle_walking_into_the_path_is_avoided() {
+        let prediction = ObstaclePrediction {
+            walking_speed: 0.5,
+            horizon: Duration::from_secs(4),
+            radius_growth_per_second: 0.0,
+        };
+        let crossing_robot =
+            Obstacle::robot(point![1.0, -1.0], 0.2, 0.2).with_velocity(vector![0.0, 0.5]);
+
+        let mut static_planner = PathPlanner::default();
+        static_planner.with_obstacles(&[crossing_robot], 0.2);
+        let static_path = static_planner
+            .plan(point![0.0, 0.0], point![2.0, 0.0])
+            .expect("Path error")
+            .expect("Path was none");
+        assert_eq!(static_path.segments.len(), 1);
+
+        let mut predicting_planner = PathPlanner::default();
+        predicting_planner.with_predicted_obstacles(
+            &[crossing_robot],
+            0.2,
+            point![0.0, 0.0],
+            point![2.0, 0.0],
+            &prediction,
+        );
+        assert_eq!(predicting_planner.obstacles.len(), 4);
+        let PathObstacleShape::Circle(swept_to) = predicting_planner.obstacles[1].shape else {
+            panic!("the end of the swept capsule must be a circle");
+        };
+        assert_relative_eq!(swept_to.center, point![1.0, 1.0], epsilon = 0.01);
+
+        let predicted_path = predicting_planner
+            .plan(point![0.0, 0.0], point![2.0, 0.0])
+            .expect("Path error")
+            .expect("Path was none");
+        assert!(predicted_path.segments.len() > 1);
+    }
+
+    #[test]
+    fn standing_obstacle_is_not_swept() {
+        let prediction = ObstaclePrediction {
+            walking_speed: 0.5,
+            horizon: Duration::from_secs(4),
+            radius_growth_per_second: 0.1,
+        };
+        let standing_robot = Obstacle::robot(point![1.0, 0.5], 0.2, 0.2);
+
+        let mut planner = PathPlanner::default();
+        planner.with_predicted_obstacles(
+            &[standing_robot],
+            0.2,
+            point![0.0, 0.0],
+            point![2.0, 0.0],
+            &prediction,
+        );
+
+        assert_eq!(planner.obstacles.len(), 1);
+        let PathObstacleShape::Circle(circle) = planner.obstacles[0].shape else {
+            panic!("a standing obstacle must be a circle");
+        };
+        assert_relative_eq!(circle.radius, 0.4);
+    }
+
+    #[test]
+    fn walk_time_model_costs_edges_by_walking_time() {
+        let mut planner = PathPlanner::default();
+        planner.with_walk_time_model(
+            WalkTimeModel::new(types::walk_volume_extents::WalkVolumeExtents {
+                forward: 0.5,
+                backward: 0.25,
+                outward: 0.2,
+                inward: 0.2,
+                outward_rotation: 1.0,
+                inward_rotation: 1.0,
+            }),
+            Some(Orientation2::identity()),
+        );
+        planner
+            .plan(point![0.0, 0.0], point![0.0, 2.0])
+            .expect("Path error")
+            .expect("Path was none");
+
+        // Turning towards the target, walking forward and turning back beats walking sideways
+        assert_relative_eq!(
+            planner.get_pathing_distance(0, 1),
+            PI / 2.0 + 4.0 + PI / 2.0,
+            epsilon = 1e-4
+        );
+    }
+
     #[test]
     fn path_start_surrounded() {
         let mut map = PathPlanner::default();
@@ -0,0 +1,219 @@
+use coordinate_systems::Ground;
+use linear_algebra::{Orientation2, Point2, Pose2, Vector2, vector};
+use types::{
+    path::{Path, PathSegment},
+    walk_volume_extents::WalkVolumeExtents,
+};
+
+/// Expected walking time of the walk, which is much slower sideways and backwards than forward.
+///
+/// The extents are speeds in m/s and rad/s. Steps alternate between outward and inward, so
+/// sideways and rotational speeds are the mean of both directions.
+#[derive(Clone, Debug, Default)]
+pub struct WalkTimeModel {
+    pub speeds: WalkVolumeExtents,
+}
+
+impl WalkTimeModel {
+    pub fn new(speeds: WalkVolumeExtents) -> Self {
+        Self { speeds }
+    }
+
+    /// Seconds per meter when walking `angle` away from the orientation of the robot, with the
+    /// speed interpolated on the ellipse through the forward, backward and sideways speeds.
+    pub fn seconds_per_meter(&self, angle: f32) -> f32 {
+        let (sin, cos) = angle.sin_cos();
+        let longitudinal_speed = if cos >= 0.0 {
+            self.speeds.forward
+        } else {
+            self.speeds.backward
+        };
+        let sideways_speed = (self.speeds.outward + self.speeds.inward) / 2.0;
+        (component_time(cos, longitudinal_speed).powi(2)
+            + component_time(sin, sideways_speed).powi(2))
+        .sqrt()
+    }
+
+    pub fn turn_time(&self, angle: f32) -> f32 {
+        let rotation_speed = (self.speeds.outward_rotation + self.speeds.inward_rotation) / 2.0;
+        component_time(angle.abs(), rotation_speed)
+    }
+
+    /// Time to walk `translation` keeping a single orientation, turning from
+    /// `start_orientation` to it before and to `end_orientation` afterwards.
+    ///
+    /// The robot either faces the walking direction or keeps the start or end orientation,
+    /// whichever is fastest. Orientations which are `None` are free and cost no turning.
+    pub fn segment_time<Frame>(
+        &self,
+        translation: Vector2<Frame>,
+        start_orientation: Option<Orientation2<Frame>>,
+        end_orientation: Option<Orientation2<Frame>>,
+    ) -> f32 {
+        let distance = translation.norm();
+        let turn =
+            |from: Option<Orientation2<Frame>>, to: Option<Orientation2<Frame>>| match (from, to) {
+                (Some(from), Some(to)) => self.turn_time(from.rotation_to(to).angle()),
+                _ => 0.0,
+            };
+        if distance <= f32::EPSILON {
+            return turn(start_orientation, end_orientation);
+        }
+
+        let walking_direction = Orientation2::from_vector(translation);
+        [Some(walking_direction), start_orientation, end_orientation]
+            .into_iter()
+            .flatten()
+            .map(|orientation| {
+                turn(start_orientation, Some(orientation))
+                    + distance
+                        * self.seconds_per_meter(orientation.rotation_to(walking_direction).angle())
+                    + turn(Some(orientation), end_orientation)
+            })
+            .fold(f32::INFINITY, f32::min)
+    }
+
+    /// Time to walk from `start` to `target` and align with its orientation.
+    pub fn time_to_reach<Frame>(&self, start: Pose2<Frame>, target: Pose2<Frame>) -> f32 {
+        self.segment_time(
+            target.position() - start.position(),
+            Some(start.orientation()),
+            Some(target.orientation()),
+        )
+    }
+
+    /// Time per segment of a path starting at the origin of the robot facing forward, costed
+    /// the same way the planner does. Arcs are walked facing along them.
+    pub fn segment_times(
+        &self,
+        path: &Path,
+        target_orientation: Option<Orientation2<Ground>>,
+    ) -> Vec<f32> {
+        let last_index = path.segments.len().saturating_sub(1);
+        path.segments
+            .iter()
+            .enumerate()
+            .map(|(index, segment)| match segment {
+                PathSegment::LineSegment(line_segment) => self.segment_time(
+                    line_segment.1 - line_segment.0,
+                    (index == 0).then(Orientation2::identity),
+                    if index == last_index {
+                        target_orientation
+                    } else {
+                        None
+                    },
+                ),
+                PathSegment::Arc(arc) => arc.length() * self.seconds_per_meter(0.0),
+            })
+            .collect()
+    }
+}
+
+/// Pose `kick_position_ball_distance` behind the ball facing `kick_target`, where the striker
+/// walks to and which the time to reach the kick position is measured to.
+pub fn kick_pose<Frame>(
+    ball: Point2<Frame>,
+    kick_target: Point2<Frame>,
+    kick_position_ball_distance: f32,
+) -> Pose2<Frame> {
+    let kick_direction = (kick_target - ball)
+        .try_normalize(f32::EPSILON)
+        .unwrap_or_else(|| vector![1.0, 0.0]);
+    Pose2::from_parts(
+        ball - kick_direction * kick_position_ball_distance,
+        Orientation2::from_vector(kick_direction),
+    )
+}
+
+/// Time to cover `amount` at `speed`, infinite if the walk cannot move that way at all.
+fn component_time(amount: f32, speed: f32) -> f32 {
+    if amount.abs() <= f32::EPSILON {
+        0.0
+    } else if speed <= 0.0 {
+        f32::INFINITY
+    } else {
+        amount.abs() / speed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::{FRAC_PI_2, PI};
+
+    use approx::assert_relative_eq;
+    use linear_algebra::point;
+
+    use super::*;
+
+    struct SomeFrame;
+
+    fn model() -> WalkTimeModel {
+        WalkTimeModel::new(WalkVolumeExtents {
+            forward: 0.5,
+            backward: 0.25,
+            outward: 0.2,
+            inward: 0.2,
+            outward_rotation: 1.0,
+            inward_rotation: 1.0,
+        })
+    }
+
+    #[test]
+    fn walking_sideways_and_backwards_is_slower() {
+        let model = model();
+
+        assert_relative_eq!(model.seconds_per_meter(0.0), 2.0);
+        assert_relative_eq!(model.seconds_per_meter(FRAC_PI_2), 5.0);
+        assert_relative_eq!(model.seconds_per_meter(PI), 4.0, epsilon = 1e-4);
+    }
+
+    #[test]
+    fn long_sideways_walk_turns_towards_the_walking_direction() {
+        let model = model();
+
+        let time = model.segment_time::<SomeFrame>(
+            vector![0.0, 2.0],
+            Some(Orientation2::identity()),
+            Some(Orientation2::identity()),
+        );
+
+        assert_relative_eq!(time, FRAC_PI_2 + 4.0 + FRAC_PI_2, epsilon = 1e-4);
+    }
+
+    #[test]
+    fn short_sideways_step_keeps_the_orientation() {
+        let model = model();
+
+        let time = model.segment_time::<SomeFrame>(
+            vector![0.0, 0.1],
+            Some(Orientation2::identity()),
+            Some(Orientation2::identity()),
+        );
+
+        assert_relative_eq!(time, 0.5, epsilon = 1e-4);
+    }
+
+    #[test]
+    fn time_to_reach_includes_final_alignment() {
+        let model = model();
+
+        let time = model.time_to_reach::<SomeFrame>(
+            Pose2::from_parts(point![0.0, 0.0], Orientation2::identity()),
+            Pose2::from_parts(point![2.0, 0.0], Orientation2::new(FRAC_PI_2)),
+        );
+
+        assert_relative_eq!(time, 4.0 + FRAC_PI_2, epsilon = 1e-4);
+    }
+
+    #[test]
+    fn kick_pose_is_behind_the_ball_facing_the_target() {
+        let pose = kick_pose::<SomeFrame>(point![1.0, 1.0], point![4.0, 5.0], 0.5);
+
+        assert_relative_eq!(pose.position(), point![0.7, 0.6], epsilon = 1e-5);
+        assert_relative_eq!(
+            pose.orientation().angle(),
+            0.8_f32.atan2(0.6),
+            epsilon = 1e-5
+        );
+    }
+}
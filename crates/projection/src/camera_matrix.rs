@@ -141,6 +141,40 @@ impl CameraMatrix {
             pixel_to_ground: ground_to_pixel.inverse(0.0),
         }
     }
+
+    /// Interpolates the extrinsics towards `other`, `t = 0` is `self` and `t = 1` is `other`.
+    /// The intrinsics are taken from `self`.
+    pub fn interpolate(&self, other: &Self, t: f32) -> Self {
+        let ground_to_robot = Isometry3::wrap(
+            self.ground_to_robot
+                .inner
+                .lerp_slerp(&other.ground_to_robot.inner, t),
+        );
+        let robot_to_head = Isometry3::wrap(
+            self.robot_to_head
+                .inner
+                .lerp_slerp(&other.robot_to_head.inner, t),
+        );
+        let head_to_camera = Isometry3::wrap(
+            self.head_to_camera
+                .inner
+                .lerp_slerp(&other.head_to_camera.inner, t),
+        );
+        let ground_to_camera = head_to_camera * robot_to_head * ground_to_robot;
+
+        Self {
+            ground_to_robot,
+            robot_to_head,
+            head_to_camera,
+            intrinsics: self.intrinsics,
+            field_of_view: self.field_of_view,
+            horizon: Horizon::from_parameters(ground_to_camera, &self.intrinsics),
+            image_size: self.image_size,
+            ground_to_camera,
+            ground_to_pixel: CameraProjection::new(ground_to_camera, self.intrinsics),
+            pixel_to_ground: CameraProjection::new(ground_to_camera, self.intrinsics).inverse(0.0),
+        }
+    }
 }
 
 #[cfg(test)]
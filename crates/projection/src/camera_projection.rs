@@ -59,6 +59,7 @@ impl<From> CameraProjection<From> {
 )]
 pub struct InverseCameraProjection<To> {
     back_project: Transform<Pixel, To, nalgebra::Matrix3<f32>>,
+    intrinsic: Intrinsic,
     z: f32,
 }
 
@@ -78,15 +79,18 @@ impl<To> InverseCameraProjection<To> {
 
         Self {
             back_project: Transform::wrap(inverse),
+            intrinsic: forward.intrinsic,
             z,
         }
     }
 
     pub fn back_project_unchecked(&self, point: Point2<Pixel>) -> Point3<To> {
+        let point = self.intrinsic.undistort(point);
         let point_to = self.back_project.inner * point.inner.to_homogeneous();
         point![point_to.x / point_to.z, point_to.y / point_to.z, self.z]
     }
 
+    /// Back projection of undistorted pixels, see [`Intrinsic::undistort`].
     pub fn as_matrix(&self) -> nalgebra::Matrix3<f32> {
         self.back_project.inner
     }
@@ -95,8 +99,11 @@ impl<To> InverseCameraProjection<To> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use approx::assert_relative_eq;
     use coordinate_systems::Ground;
 
+    use crate::distortion::Distortion;
+
     #[test]
     fn invertable() {
         let camera_projection = CameraProjection::<Ground>::new(
@@ -105,4 +112,26 @@ mod tests {
         );
         camera_projection.inverse(0.0);
     }
+
+    #[test]
+    fn back_projection_inverts_distorted_projection() {
+        let camera_projection = CameraProjection::<Ground>::new(
+            Isometry3::from_translation(0.0, 0.0, 1.0),
+            Intrinsic::new(nalgebra::vector![500.0, 500.0], point![320.0, 240.0]).with_distortion(
+                Distortion::Equidistant {
+                    k1: -0.02,
+                    k2: 0.003,
+                    k3: 0.0,
+                    k4: 0.0,
+                },
+            ),
+        );
+        let point = point![0.8, -0.5, 0.0];
+        let pixel = camera_projection.project(point);
+        assert_relative_eq!(
+            camera_projection.inverse(0.0).back_project_unchecked(pixel),
+            point,
+            epsilon = 1e-4
+        );
+    }
 }
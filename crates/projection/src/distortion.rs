@@ -0,0 +1,228 @@
+use path_serde::{PathDeserialize, PathIntrospect, PathSerialize};
+use ros2::sensor_msgs::camera_info::CameraInfo;
+use serde::{Deserialize, Serialize};
+
+const MAXIMUM_NUMBER_OF_UNDISTORTION_ITERATIONS: usize = 20;
+const UNDISTORTION_TOLERANCE: f32 = 1e-7;
+
+/// Lens distortion applied to normalized image coordinates, i.e. `(x / z, y / z)` of a ray in
+/// the camera frame, following the models of `sensor_msgs/distortion_models.hpp`.
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    Default,
+    PartialEq,
+    Serialize,
+    Deserialize,
+    PathSerialize,
+    PathDeserialize,
+    PathIntrospect,
+    ros_z::Message,
+)]
+pub enum Distortion {
+    #[default]
+    None,
+    /// Brown-Conrady model with radial (`k1`, `k2`, `k3`) and tangential (`p1`, `p2`) terms
+    PlumbBob {
+        k1: f32,
+        k2: f32,
+        p1: f32,
+        p2: f32,
+        k3: f32,
+    },
+    /// Fisheye model, the distorted radius is a polynomial of the angle to the optical axis
+    Equidistant { k1: f32, k2: f32, k3: f32, k4: f32 },
+}
+
+impl Distortion {
+    /// Returns `None` for unsupported distortion models.
+    pub fn from_camera_info(camera_info: &CameraInfo) -> Option<Self> {
+        let coefficient = |index: usize| camera_info.d.get(index).copied().unwrap_or(0.0) as f32;
+        let distortion = match camera_info.distortion_model.as_str() {
+            "" => Self::None,
+            "plumb_bob" => Self::PlumbBob {
+                k1: coefficient(0),
+                k2: coefficient(1),
+                p1: coefficient(2),
+                p2: coefficient(3),
+                k3: coefficient(4),
+            },
+            "equidistant" => Self::Equidistant {
+                k1: coefficient(0),
+                k2: coefficient(1),
+                k3: coefficient(2),
+                k4: coefficient(3),
+            },
+            _ => return None,
+        };
+        Some(distortion)
+    }
+
+    pub fn is_none(&self) -> bool {
+        match *self {
+            Self::None => true,
+            Self::PlumbBob { k1, k2, p1, p2, k3 } => [k1, k2, p1, p2, k3] == [0.0; 5],
+            Self::Equidistant { .. } => false,
+        }
+    }
+
+    pub fn distort(&self, point: nalgebra::Point2<f32>) -> nalgebra::Point2<f32> {
+        match *self {
+            Self::None => point,
+            Self::PlumbBob { k1, k2, p1, p2, k3 } => {
+                let (x, y) = (point.x, point.y);
+                let radius_squared = x * x + y * y;
+                let radial =
+                    1.0 + radius_squared * (k1 + radius_squared * (k2 + radius_squared * k3));
+                nalgebra::point![
+                    x * radial + 2.0 * p1 * x * y + p2 * (radius_squared + 2.0 * x * x),
+                    y * radial + p1 * (radius_squared + 2.0 * y * y) + 2.0 * p2 * x * y
+                ]
+            }
+            Self::Equidistant { k1, k2, k3, k4 } => {
+                let radius = point.coords.norm();
+                if radius < f32::EPSILON {
+                    return point;
+                }
+                let theta = radius.atan();
+                let theta_squared = theta * theta;
+                let distorted_theta = theta
+                    * (1.0
+                        + theta_squared
+                            * (k1
+                                + theta_squared
+                                    * (k2 + theta_squared * (k3 + theta_squared * k4))));
+                point * (distorted_theta / radius)
+            }
+        }
+    }
+
+    /// Inverts [`Self::distort`] iteratively, the result is exact up to the tolerance within the
+    /// image but unspecified for points far outside of it.
+    pub fn undistort(&self, point: nalgebra::Point2<f32>) -> nalgebra::Point2<f32> {
+        match *self {
+            Self::None => point,
+            Self::PlumbBob { k1, k2, p1, p2, k3 } => {
+                let mut undistorted = point;
+                for _ in 0..MAXIMUM_NUMBER_OF_UNDISTORTION_ITERATIONS {
+                    let (x, y) = (undistorted.x, undistorted.y);
+                    let radius_squared = x * x + y * y;
+                    let radial =
+                        1.0 + radius_squared * (k1 + radius_squared * (k2 + radius_squared * k3));
+                    let tangential = nalgebra::vector![
+                        2.0 * p1 * x * y + p2 * (radius_squared + 2.0 * x * x),
+                        p1 * (radius_squared + 2.0 * y * y) + 2.0 * p2 * x * y
+                    ];
+                    let next = (point - tangential) / radial;
+                    let step = (next - undistorted).norm();
+                    undistorted = next;
+                    if step < UNDISTORTION_TOLERANCE {
+                        break;
+                    }
+                }
+                undistorted
+            }
+            Self::Equidistant { k1, k2, k3, k4 } => {
+                let distorted_theta = point.coords.norm();
+                if distorted_theta < f32::EPSILON {
+                    return point;
+                }
+                let mut theta = distorted_theta;
+                for _ in 0..MAXIMUM_NUMBER_OF_UNDISTORTION_ITERATIONS {
+                    let theta_squared = theta * theta;
+                    let error = theta
+                        * (1.0
+                            + theta_squared
+                                * (k1
+                                    + theta_squared
+                                        * (k2 + theta_squared * (k3 + theta_squared * k4))))
+                        - distorted_theta;
+                    let derivative = 1.0
+                        + theta_squared
+                            * (3.0 * k1
+                                + theta_squared
+                                    * (5.0 * k2
+                                        + theta_squared * (7.0 * k3 + theta_squared * 9.0 * k4)));
+                    let step = error / derivative;
+                    theta -= step;
+                    if step.abs() < UNDISTORTION_TOLERANCE {
+                        break;
+                    }
+                }
+                point * (theta.tan() / distorted_theta)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use approx::assert_relative_eq;
+
+    use super::*;
+
+    fn points() -> impl Iterator<Item = nalgebra::Point2<f32>> {
+        (-4..=4)
+            .flat_map(|x| (-3..=3).map(move |y| nalgebra::point![x as f32 * 0.2, y as f32 * 0.2]))
+    }
+
+    #[test]
+    fn plumb_bob_undistortion_inverts_distortion() {
+        let distortion = Distortion::PlumbBob {
+            k1: -0.28,
+            k2: 0.07,
+            p1: 0.001,
+            p2: -0.0005,
+            k3: 0.0,
+        };
+        for point in points() {
+            assert_relative_eq!(
+                distortion.undistort(distortion.distort(point)),
+                point,
+                epsilon = 1e-4
+            );
+        }
+    }
+
+    #[test]
+    fn equidistant_undistortion_inverts_distortion() {
+        let distortion = Distortion::Equidistant {
+            k1: 0.05,
+            k2: -0.01,
+            k3: 0.002,
+            k4: -0.0003,
+        };
+        for point in points() {
+            assert_relative_eq!(
+                distortion.undistort(distortion.distort(point)),
+                point,
+                epsilon = 1e-4
+            );
+        }
+    }
+
+    #[test]
+    fn equidistant_without_coefficients_maps_angle_linearly() {
+        let distortion = Distortion::Equidistant {
+            k1: 0.0,
+            k2: 0.0,
+            k3: 0.0,
+            k4: 0.0,
+        };
+        let distorted = distortion.distort(nalgebra::point![1.0, 0.0]);
+        assert_relative_eq!(
+            distorted,
+            nalgebra::point![std::f32::consts::FRAC_PI_4, 0.0]
+        );
+    }
+
+    #[test]
+    fn unknown_distortion_models_are_rejected() {
+        let camera_info = CameraInfo {
+            distortion_model: "rational_polynomial".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(Distortion::from_camera_info(&camera_info), None);
+    }
+}
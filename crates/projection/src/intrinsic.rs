@@ -4,6 +4,8 @@ use path_serde::{PathDeserialize, PathIntrospect, PathSerialize};
 use ros2::sensor_msgs::camera_info::CameraInfo;
 use serde::{Deserialize, Serialize};
 
+use crate::distortion::Distortion;
+
 #[derive(
     Clone,
     Copy,
@@ -19,6 +21,7 @@ use serde::{Deserialize, Serialize};
 pub struct Intrinsic {
     pub focals: nalgebra::Vector2<f32>,
     pub optical_center: Point2<Pixel>,
+    pub distortion: Distortion,
 }
 
 impl Default for Intrinsic {
@@ -26,6 +29,7 @@ impl Default for Intrinsic {
         Self {
             focals: nalgebra::vector![1.0, 1.0],
             optical_center: point![0.0, 0.0],
+            distortion: Distortion::None,
         }
     }
 }
@@ -35,9 +39,14 @@ impl Intrinsic {
         Self {
             focals: focal_length,
             optical_center,
+            distortion: Distortion::None,
         }
     }
 
+    pub fn with_distortion(self, distortion: Distortion) -> Self {
+        Self { distortion, ..self }
+    }
+
     pub fn as_matrix(&self) -> nalgebra::Matrix3x4<f32> {
         nalgebra::matrix![
             self.focals.x, 0.0, self.optical_center.x(), 0.0;
@@ -46,6 +55,7 @@ impl Intrinsic {
         ]
     }
 
+    /// Pinhole projection without distortion, see [`Self::project`] for the distorted pixel.
     pub fn transform(&self, ray: Vector3<Camera>) -> Vector3<NormalizedDeviceCoordinates> {
         let (x, y, z) = (ray.x(), ray.y(), ray.z());
 
@@ -57,15 +67,38 @@ impl Intrinsic {
     }
 
     pub fn project(&self, ray: Vector3<Camera>) -> Point2<Pixel> {
-        let projected = self.transform(ray);
-        point![projected.x() / projected.z(), projected.y() / projected.z()]
+        let normalized = nalgebra::point![ray.x() / ray.z(), ray.y() / ray.z()];
+        let distorted = self.distortion.distort(normalized);
+        point![
+            self.focals.x * distorted.x + self.optical_center.x(),
+            self.focals.y * distorted.y + self.optical_center.y()
+        ]
     }
 
     pub fn bearing(&self, pixel: Point2<Pixel>) -> Vector3<Camera> {
-        let x = (pixel.x() - self.optical_center.x()) / self.focals.x;
-        let y = (pixel.y() - self.optical_center.y()) / self.focals.y;
+        let undistorted = self.distortion.undistort(self.normalize(pixel));
+
+        vector![undistorted.x, undistorted.y, 1.0]
+    }
+
+    /// Maps a distorted pixel to the pixel an ideal pinhole camera with the same focal lengths
+    /// and optical center would have observed.
+    pub fn undistort(&self, pixel: Point2<Pixel>) -> Point2<Pixel> {
+        if self.distortion.is_none() {
+            return pixel;
+        }
+        let undistorted = self.distortion.undistort(self.normalize(pixel));
+        point![
+            self.focals.x * undistorted.x + self.optical_center.x(),
+            self.focals.y * undistorted.y + self.optical_center.y()
+        ]
+    }
 
-        vector![x, y, 1.0]
+    fn normalize(&self, pixel: Point2<Pixel>) -> nalgebra::Point2<f32> {
+        nalgebra::point![
+            (pixel.x() - self.optical_center.x()) / self.focals.x,
+            (pixel.y() - self.optical_center.y()) / self.focals.y
+        ]
     }
 
     pub fn calculate_field_of_view(
@@ -81,17 +114,46 @@ impl Intrinsic {
     }
 }
 
+/// How the pixels of an image relate to the camera described by its camera info. As in
+/// `sensor_msgs/CameraInfo`, an image with distortion coefficients is a raw image described by
+/// `k` and `d`, all others are rectified images described by `p`. Unsupported distortion models
+/// are treated as rectified.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ImageModel {
+    Raw { distortion: Distortion },
+    Rectified,
+}
+
+impl ImageModel {
+    pub fn from_camera_info(camera_info: &CameraInfo) -> Self {
+        match Distortion::from_camera_info(camera_info) {
+            Some(distortion) if !distortion.is_none() => Self::Raw { distortion },
+            _ => Self::Rectified,
+        }
+    }
+}
+
 impl From<&CameraInfo> for Intrinsic {
     fn from(camera_info: &CameraInfo) -> Self {
-        Intrinsic {
-            focals: nalgebra::vector![camera_info.p[0] as f32, camera_info.p[5] as f32],
-            optical_center: point!(camera_info.p[2] as f32, camera_info.p[6] as f32),
+        match ImageModel::from_camera_info(camera_info) {
+            ImageModel::Raw { distortion } => Intrinsic {
+                focals: nalgebra::vector![camera_info.k[0] as f32, camera_info.k[4] as f32],
+                optical_center: point!(camera_info.k[2] as f32, camera_info.k[5] as f32),
+                distortion,
+            },
+            ImageModel::Rectified => Intrinsic {
+                focals: nalgebra::vector![camera_info.p[0] as f32, camera_info.p[5] as f32],
+                optical_center: point!(camera_info.p[2] as f32, camera_info.p[6] as f32),
+                distortion: Distortion::None,
+            },
         }
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use approx::assert_relative_eq;
+
     use super::*;
 
     #[test]
@@ -109,4 +171,66 @@ mod tests {
         let bearing = intrinsic.bearing(pixel);
         assert_eq!(bearing, vector![0.0, 0.0, 1.0]);
     }
+
+    #[test]
+    fn distorted_projection_is_inverted_by_bearing() {
+        let intrinsic = Intrinsic::new(nalgebra::vector![500.0, 500.0], point![320.0, 240.0])
+            .with_distortion(Distortion::PlumbBob {
+                k1: -0.3,
+                k2: 0.1,
+                p1: 0.001,
+                p2: 0.002,
+                k3: 0.0,
+            });
+        let ray = vector![0.5, -0.3, 1.0];
+        let pixel = intrinsic.project(ray);
+        assert!(
+            (pixel - Intrinsic::new(intrinsic.focals, intrinsic.optical_center).project(ray))
+                .norm()
+                > 1.0
+        );
+        assert_relative_eq!(intrinsic.bearing(pixel), ray, epsilon = 1e-4);
+    }
+
+    #[test]
+    fn camera_info_with_distortion_uses_raw_intrinsics() {
+        let camera_info = CameraInfo {
+            distortion_model: "equidistant".to_string(),
+            d: vec![0.1, 0.01, 0.0, 0.0],
+            k: [400.0, 0.0, 320.0, 0.0, 410.0, 240.0, 0.0, 0.0, 1.0],
+            p: [
+                300.0, 0.0, 330.0, 0.0, 0.0, 310.0, 250.0, 0.0, 0.0, 0.0, 1.0, 0.0,
+            ],
+            ..Default::default()
+        };
+        let intrinsic = Intrinsic::from(&camera_info);
+        assert_eq!(intrinsic.focals, nalgebra::vector![400.0, 410.0]);
+        assert_eq!(intrinsic.optical_center, point![320.0, 240.0]);
+        assert!(matches!(
+            intrinsic.distortion,
+            Distortion::Equidistant { k1, .. } if k1 == 0.1
+        ));
+    }
+
+    #[test]
+    fn camera_info_without_distortion_uses_rectified_intrinsics() {
+        let camera_info = CameraInfo {
+            distortion_model: "plumb_bob".to_string(),
+            d: vec![0.0; 5],
+            k: [400.0, 0.0, 320.0, 0.0, 410.0, 240.0, 0.0, 0.0, 1.0],
+            p: [
+                300.0, 0.0, 330.0, 0.0, 0.0, 310.0, 250.0, 0.0, 0.0, 0.0, 1.0, 0.0,
+            ],
+            ..Default::default()
+        };
+
+        assert_eq!(
+            ImageModel::from_camera_info(&camera_info),
+            ImageModel::Rectified
+        );
+        let intrinsic = Intrinsic::from(&camera_info);
+        assert_eq!(intrinsic.focals, nalgebra::vector![300.0, 310.0]);
+        assert_eq!(intrinsic.optical_center, point![330.0, 250.0]);
+        assert_eq!(intrinsic.distortion, Distortion::None);
+    }
 }
@@ -1,7 +1,9 @@
 pub mod camera_matrix;
 pub mod camera_projection;
+pub mod distortion;
 pub mod horizon;
 pub mod intrinsic;
+pub mod rolling_shutter;
 
 use nalgebra::{Matrix2, matrix};
 use thiserror::Error;
@@ -0,0 +1,150 @@
+use ros_z::time::Time;
+use serde::{Deserialize, Serialize};
+
+use crate::camera_matrix::CameraMatrix;
+
+/// Camera matrices of horizontal bands of a rolling shutter image, each sampled at the exposure
+/// time of the center row of its band.
+#[derive(Clone, Debug, Deserialize, Serialize, ros_z::Message)]
+pub struct RowCameraMatrices {
+    band_height: f32,
+    bands: Vec<CameraMatrix>,
+}
+
+impl RowCameraMatrices {
+    /// Enough bands to keep the remaining motion within a band small compared to the readout of
+    /// the whole image.
+    pub const NUMBER_OF_BANDS: usize = 8;
+
+    /// Uses the same camera matrix for every row, e.g. when the exposure timing is unknown.
+    pub fn global(camera_matrix: CameraMatrix) -> Self {
+        Self {
+            band_height: f32::INFINITY,
+            bands: vec![camera_matrix],
+        }
+    }
+
+    /// Samples `camera_matrix_at_row` at the center row of each band, returns `None` if any
+    /// band has no camera matrix.
+    pub fn sample(
+        number_of_rows: u32,
+        camera_matrix_at_row: impl FnMut(f32) -> Option<CameraMatrix>,
+    ) -> Option<Self> {
+        let band_height = number_of_rows.max(1) as f32 / Self::NUMBER_OF_BANDS as f32;
+        let bands = (0..Self::NUMBER_OF_BANDS)
+            .map(|band| (band as f32 + 0.5) * band_height)
+            .map(camera_matrix_at_row)
+            .collect::<Option<Vec<_>>>()?;
+        Some(Self { band_height, bands })
+    }
+
+    pub fn at_row(&self, row: f32) -> &CameraMatrix {
+        let band = (row / self.band_height).max(0.0) as usize;
+        &self.bands[band.min(self.bands.len() - 1)]
+    }
+
+    /// Camera matrix of the band in the middle of the image, for everything that is not tied to
+    /// a specific row.
+    pub fn center(&self) -> &CameraMatrix {
+        &self.bands[self.bands.len() / 2]
+    }
+}
+
+/// Interpolates between the camera matrices before and after `time`, falls back to the only
+/// available one if `time` is not enclosed.
+pub fn interpolate_in_time(
+    time: Time,
+    before: Option<(Time, &CameraMatrix)>,
+    after: Option<(Time, &CameraMatrix)>,
+) -> Option<CameraMatrix> {
+    match (before, after) {
+        (Some((before_time, before)), Some((after_time, after))) if after_time > before_time => {
+            let t = time.duration_since(before_time).as_secs_f32()
+                / after_time.duration_since(before_time).as_secs_f32();
+            Some(before.interpolate(after, t))
+        }
+        (Some((_, camera_matrix)), _) | (None, Some((_, camera_matrix))) => {
+            Some(camera_matrix.clone())
+        }
+        (None, None) => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use approx::assert_relative_eq;
+    use linear_algebra::{Isometry3, vector};
+
+    use super::*;
+
+    fn camera_matrix_with_height(height: f32) -> CameraMatrix {
+        CameraMatrix::from_normalized_focal_and_center(
+            nalgebra::vector![0.95, 1.27],
+            nalgebra::point![0.5, 0.5],
+            vector![640.0, 480.0],
+            Isometry3::from_translation(0.0, 0.0, -height),
+            Isometry3::identity(),
+            Isometry3::identity(),
+        )
+    }
+
+    #[test]
+    fn camera_matrix_is_interpolated_between_samples() {
+        let start = Time::from_nanos(1_000_000_000);
+        let end = start + Duration::from_millis(10);
+
+        let camera_matrix = interpolate_in_time(
+            start + Duration::from_millis(5),
+            Some((start, &camera_matrix_with_height(0.5))),
+            Some((end, &camera_matrix_with_height(0.7))),
+        )
+        .unwrap();
+
+        assert_relative_eq!(
+            camera_matrix.ground_to_robot.inner.translation.z,
+            -0.6,
+            epsilon = 1e-6
+        );
+        assert_relative_eq!(
+            camera_matrix.ground_to_camera.inner.translation.z,
+            -0.6,
+            epsilon = 1e-6
+        );
+    }
+
+    #[test]
+    fn rows_use_the_camera_matrix_of_their_band() {
+        let row_camera_matrices =
+            RowCameraMatrices::sample(480, |row| Some(camera_matrix_with_height(row))).unwrap();
+
+        assert_eq!(
+            row_camera_matrices
+                .at_row(0.0)
+                .ground_to_robot
+                .inner
+                .translation
+                .z,
+            -30.0
+        );
+        assert_eq!(
+            row_camera_matrices
+                .at_row(479.0)
+                .ground_to_robot
+                .inner
+                .translation
+                .z,
+            -450.0
+        );
+        assert_eq!(
+            row_camera_matrices
+                .center()
+                .ground_to_robot
+                .inner
+                .translation
+                .z,
+            -270.0
+        );
+    }
+}
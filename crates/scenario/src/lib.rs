@@ -11,24 +11,31 @@ pub fn scenario(_attribute: TokenStream, item: TokenStream) -> TokenStream {
         #function_item
 
         fn main() -> color_eyre::Result<()> {
-            use bevyhavior_simulator::behavior_tree_simulator::{AppExt, BehaviorTreeSimulatorPlugin};
+            use bevyhavior_simulator::behavior_tree_simulator::{AppExt, BehaviorTreeSimulatorPlugin, SimulatorScenarioName};
 
-            App::new()
+            let timeline = App::new()
                 .add_plugins(BehaviorTreeSimulatorPlugin::default())
+                .insert_resource(SimulatorScenarioName(stringify!(#function_name)))
                 .add_plugins(#function_name)
-                .run_to_completion_with_viewer()
+                .run_to_completion_with_viewer()?;
+            if let Some(path) = timeline {
+                println!("timeline written to {}", path.display());
+            }
+            Ok(())
         }
 
         #[cfg(test)]
         mod test {
             #[test]
             fn #function_name() -> color_eyre::Result<()> {
-                use bevyhavior_simulator::behavior_tree_simulator::{AppExt, BehaviorTreeSimulatorPlugin};
+                use bevyhavior_simulator::behavior_tree_simulator::{AppExt, BehaviorTreeSimulatorPlugin, SimulatorScenarioName};
 
                 bevy::app::App::new()
                     .add_plugins(BehaviorTreeSimulatorPlugin::default())
+                    .insert_resource(SimulatorScenarioName(stringify!(#function_name)))
                     .add_plugins(super::#function_name)
-                    .run_to_completion()
+                    .run_to_completion()?;
+                Ok(())
             }
         }
     }
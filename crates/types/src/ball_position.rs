@@ -89,3 +89,20 @@ pub struct HypotheticalBallPosition<Frame> {
     pub position: Point2<Frame>,
     pub validity: f32,
 }
+
+/// Where a rolling ball comes to rest and how long it takes to get there.
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    Deserialize,
+    Serialize,
+    PathSerialize,
+    PathDeserialize,
+    PathIntrospect,
+    ros_z::Message,
+)]
+pub struct BallRestPrediction<Frame> {
+    pub rest_position: Point2<Frame>,
+    pub time_to_stop: Duration,
+}
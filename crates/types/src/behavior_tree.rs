@@ -2,12 +2,16 @@ use path_serde::{PathIntrospect, PathSerialize};
 use ros_z::Message;
 use serde::{Deserialize, Serialize};
 
+use crate::role::Role;
+
 #[derive(
     PartialEq, Debug, Clone, Serialize, Deserialize, PathSerialize, PathIntrospect, Message,
 )]
 pub enum Status {
     Success,
     Failure,
+    /// Not finished yet, the tick has to continue at this node in the next cycle
+    Running,
     Idle,
 }
 
@@ -16,4 +20,7 @@ pub struct NodeTrace {
     pub name: String,
     pub status: Status,
     pub children: Vec<NodeTrace>,
+    /// Role the robot played while the tree was ticked, only set on the root
+    #[serde(default)]
+    pub role: Option<Role>,
 }
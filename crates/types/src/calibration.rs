@@ -1,7 +1,7 @@
 use serde::{Deserialize, Serialize};
 
-use coordinate_systems::Ground;
-use linear_algebra::Point2;
+use coordinate_systems::{Camera, Ground, Robot};
+use linear_algebra::{Point2, Vector3};
 use path_serde::{PathDeserialize, PathIntrospect, PathSerialize};
 
 use crate::cycle_time::CycleTime;
@@ -25,3 +25,51 @@ where
     pub dispatch_time: CycleTime,
     pub measurement: Option<Measurement>,
 }
+
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    PartialEq,
+    Eq,
+    Serialize,
+    Deserialize,
+    PathSerialize,
+    PathDeserialize,
+    PathIntrospect,
+    ros_z::Message,
+)]
+pub enum CameraCalibrationCommand {
+    Start,
+    Abort,
+}
+
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    Default,
+    PartialEq,
+    Serialize,
+    Deserialize,
+    PathSerialize,
+    PathDeserialize,
+    PathIntrospect,
+    ros_z::Message,
+)]
+pub enum CameraCalibrationState {
+    #[default]
+    Idle,
+    Capturing {
+        head_angle_index: usize,
+        number_of_head_angles: usize,
+        number_of_measurements: usize,
+    },
+    Finished {
+        correction_in_robot: Vector3<Robot>,
+        correction_in_camera: Vector3<Camera>,
+    },
+    Failed {
+        number_of_measurements: usize,
+    },
+}
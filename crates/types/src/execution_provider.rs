@@ -0,0 +1,62 @@
+use std::time::Duration;
+
+use path_serde::{PathDeserialize, PathIntrospect, PathSerialize};
+use serde::{Deserialize, Serialize};
+
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    Default,
+    Deserialize,
+    Serialize,
+    PathSerialize,
+    PathDeserialize,
+    PathIntrospect,
+    PartialEq,
+    Eq,
+    ros_z::Message,
+)]
+pub enum ExecutionProviderKind {
+    TensorRt,
+    Cuda,
+    #[default]
+    Cpu,
+}
+
+/// Shrinks the network input whenever inference takes longer than the target duration, e.g.
+/// when running on the CPU execution provider.
+#[derive(
+    Clone,
+    Debug,
+    Default,
+    Deserialize,
+    Serialize,
+    PathSerialize,
+    PathDeserialize,
+    PathIntrospect,
+    ros_z::Message,
+)]
+pub struct InputDownscalingParameters {
+    pub enable: bool,
+    pub maximum_factor: usize,
+    pub target_inference_duration: Duration,
+}
+
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    Default,
+    Deserialize,
+    Serialize,
+    PathSerialize,
+    PathDeserialize,
+    PathIntrospect,
+    PartialEq,
+    ros_z::Message,
+)]
+pub struct InferenceStatus {
+    pub execution_provider: ExecutionProviderKind,
+    pub input_downscaling_factor: usize,
+}
@@ -0,0 +1,51 @@
+use std::time::Duration;
+
+use ros_z::{Message, time::Time};
+use serde::{Deserialize, Serialize};
+
+/// Exposure timing of a rolling shutter image, the rows are exposed one after another from top
+/// to bottom.
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, Message)]
+pub struct ExposureTiming {
+    /// Center of the exposure of the first row
+    pub first_row: Time,
+    /// Time between the exposure centers of the first and the last row
+    pub readout_duration: Duration,
+    pub number_of_rows: u32,
+}
+
+impl ExposureTiming {
+    /// Center of the exposure of the given row, rows outside of the image are clamped.
+    pub fn row_time(&self, row: f32) -> Time {
+        let last_row = self.number_of_rows.saturating_sub(1).max(1) as f32;
+        self.first_row
+            + self
+                .readout_duration
+                .mul_f32((row / last_row).clamp(0.0, 1.0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rows_are_exposed_during_the_readout() {
+        let exposure_timing = ExposureTiming {
+            first_row: Time::from_nanos(1_000_000_000),
+            readout_duration: Duration::from_millis(20),
+            number_of_rows: 481,
+        };
+
+        let seconds_after_first_row = |row| {
+            exposure_timing
+                .row_time(row)
+                .duration_since(exposure_timing.first_row)
+                .as_secs_f32()
+        };
+
+        assert_eq!(seconds_after_first_row(0.0), 0.0);
+        assert!((seconds_after_first_row(240.0) - 0.01).abs() < 1e-6);
+        assert!((seconds_after_first_row(1000.0) - 0.02).abs() < 1e-6);
+    }
+}
@@ -1,8 +1,10 @@
-use std::ops::RangeInclusive;
+use std::{ops::RangeInclusive, path::PathBuf};
 
 use path_serde::{PathDeserialize, PathIntrospect, PathSerialize};
 use serde::{Deserialize, Serialize};
 
+use crate::color::{Hsv, RgChromaticity, Rgb, YCbCr444};
+
 #[derive(
     Clone,
     Copy,
@@ -56,3 +58,210 @@ impl Default for FieldColorParameters {
         }
     }
 }
+
+#[derive(
+    Clone,
+    Debug,
+    Default,
+    Deserialize,
+    Serialize,
+    PathSerialize,
+    PathDeserialize,
+    PathIntrospect,
+    ros_z::Message,
+)]
+pub struct FieldColorClassifierParameters {
+    /// Classify with the decision tree at `decision_tree_path` instead of the static ranges.
+    pub use_decision_tree: bool,
+    pub decision_tree_path: PathBuf,
+    /// Minimum field probability of the decision tree for a segment to count as field.
+    pub field_probability_threshold: f32,
+    pub adaptation: FieldColorAdaptationParameters,
+}
+
+#[derive(
+    Clone,
+    Debug,
+    Default,
+    Deserialize,
+    Serialize,
+    PathSerialize,
+    PathDeserialize,
+    PathIntrospect,
+    ros_z::Message,
+)]
+pub struct FieldColorAdaptationParameters {
+    pub enable: bool,
+    /// Segments shorter than this (in pixels) are too noisy to learn from.
+    pub minimum_segment_length: u16,
+    pub minimum_samples_per_image: usize,
+    /// Leaves reached by fewer segments of an image keep their field probability.
+    pub minimum_samples_per_leaf: usize,
+    /// Weight of the newest image in the exponential moving average of each leaf.
+    pub learning_rate: f32,
+}
+
+/// Color feature a [`FieldColorTree`] splits on.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub enum FieldColorFeature {
+    Luminance,
+    Cb,
+    Cr,
+    RedChromaticity,
+    GreenChromaticity,
+    BlueChromaticity,
+    Hue,
+    Saturation,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct FieldColorFeatures {
+    pub luminance: f32,
+    pub cb: f32,
+    pub cr: f32,
+    pub red_chromaticity: f32,
+    pub green_chromaticity: f32,
+    pub blue_chromaticity: f32,
+    pub hue: f32,
+    pub saturation: f32,
+}
+
+impl From<YCbCr444> for FieldColorFeatures {
+    fn from(color: YCbCr444) -> Self {
+        let rgb = Rgb::from(color);
+        let rg_chromaticity = RgChromaticity::from(rgb);
+        let hsv = Hsv::from(rgb);
+        Self {
+            luminance: color.y as f32,
+            cb: color.cb as f32,
+            cr: color.cr as f32,
+            red_chromaticity: rg_chromaticity.red,
+            green_chromaticity: rg_chromaticity.green,
+            blue_chromaticity: 1.0 - rg_chromaticity.red - rg_chromaticity.green,
+            hue: hsv.hue as f32,
+            saturation: hsv.saturation as f32,
+        }
+    }
+}
+
+impl FieldColorFeatures {
+    pub fn get(&self, feature: FieldColorFeature) -> f32 {
+        match feature {
+            FieldColorFeature::Luminance => self.luminance,
+            FieldColorFeature::Cb => self.cb,
+            FieldColorFeature::Cr => self.cr,
+            FieldColorFeature::RedChromaticity => self.red_chromaticity,
+            FieldColorFeature::GreenChromaticity => self.green_chromaticity,
+            FieldColorFeature::BlueChromaticity => self.blue_chromaticity,
+            FieldColorFeature::Hue => self.hue,
+            FieldColorFeature::Saturation => self.saturation,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+pub enum FieldColorTreeNode {
+    /// Continues with `left` if the feature is less than or equal to `threshold`, else `right`.
+    Split {
+        feature: FieldColorFeature,
+        threshold: f32,
+        left: usize,
+        right: usize,
+    },
+    Leaf {
+        field_probability: f32,
+    },
+}
+
+/// Binary decision tree classifying segment colors, as exported by
+/// `tools/field_color_detection/export_decision_tree.py` or
+/// `tools/field_color_detection/train_from_field_color_ranges.py`. The root is the first node.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(try_from = "Vec<FieldColorTreeNode>", into = "Vec<FieldColorTreeNode>")]
+pub struct FieldColorTree {
+    nodes: Vec<FieldColorTreeNode>,
+}
+
+impl TryFrom<Vec<FieldColorTreeNode>> for FieldColorTree {
+    type Error = String;
+
+    fn try_from(nodes: Vec<FieldColorTreeNode>) -> Result<Self, Self::Error> {
+        if nodes.is_empty() {
+            return Err("decision tree has no nodes".to_string());
+        }
+        for (index, node) in nodes.iter().enumerate() {
+            match *node {
+                // Children always come after their parent, which rules out cycles
+                FieldColorTreeNode::Split { left, right, .. } => {
+                    if left <= index
+                        || right <= index
+                        || left >= nodes.len()
+                        || right >= nodes.len()
+                    {
+                        return Err(format!(
+                            "node {index} has invalid children {left} and {right}"
+                        ));
+                    }
+                }
+                FieldColorTreeNode::Leaf { field_probability } => {
+                    if !(0.0..=1.0).contains(&field_probability) {
+                        return Err(format!(
+                            "leaf {index} has probability {field_probability} outside of [0, 1]"
+                        ));
+                    }
+                }
+            }
+        }
+        Ok(Self { nodes })
+    }
+}
+
+impl From<FieldColorTree> for Vec<FieldColorTreeNode> {
+    fn from(tree: FieldColorTree) -> Self {
+        tree.nodes
+    }
+}
+
+impl FieldColorTree {
+    pub fn field_probability(&self, features: &FieldColorFeatures) -> f32 {
+        self.leaf(features).1
+    }
+
+    /// Returns the index and field probability of the leaf `features` end up in.
+    pub fn leaf(&self, features: &FieldColorFeatures) -> (usize, f32) {
+        let mut index = 0;
+        loop {
+            match self.nodes[index] {
+                FieldColorTreeNode::Split {
+                    feature,
+                    threshold,
+                    left,
+                    right,
+                } => {
+                    index = if features.get(feature) <= threshold {
+                        left
+                    } else {
+                        right
+                    };
+                }
+                FieldColorTreeNode::Leaf { field_probability } => {
+                    return (index, field_probability);
+                }
+            }
+        }
+    }
+
+    /// Moves the field probability of the leaf at `index` towards `observed_field_probability`.
+    pub fn adapt_leaf(
+        &mut self,
+        index: usize,
+        observed_field_probability: f32,
+        learning_rate: f32,
+    ) {
+        if let FieldColorTreeNode::Leaf { field_probability } = &mut self.nodes[index] {
+            *field_probability = (*field_probability
+                + learning_rate * (observed_field_probability - *field_probability))
+                .clamp(0.0, 1.0);
+        }
+    }
+}
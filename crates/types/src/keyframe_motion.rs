@@ -0,0 +1,45 @@
+use std::time::Duration;
+
+use path_serde::{PathDeserialize, PathIntrospect, PathSerialize};
+use serde::{Deserialize, Serialize};
+
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    Deserialize,
+    Serialize,
+    PathSerialize,
+    PathDeserialize,
+    PathIntrospect,
+    PartialEq,
+    Eq,
+    ros_z::Message,
+)]
+pub enum KeyframeMotionState {
+    Playing {
+        frame_index: usize,
+        remaining_duration: Duration,
+    },
+    Finished,
+    Aborted,
+}
+
+/// Progress of the keyframe motion requested last. Stays at its result once the motion has
+/// finished or was aborted until another motion is requested.
+#[derive(
+    Clone,
+    Debug,
+    Deserialize,
+    Serialize,
+    PathSerialize,
+    PathDeserialize,
+    PathIntrospect,
+    PartialEq,
+    Eq,
+    ros_z::Message,
+)]
+pub struct KeyframeMotionStatus {
+    pub name: String,
+    pub state: KeyframeMotionState,
+}
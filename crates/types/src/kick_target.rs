@@ -0,0 +1,52 @@
+use hsl_network_messages::PlayerNumber;
+use path_serde::{PathDeserialize, PathIntrospect, PathSerialize};
+use serde::{Deserialize, Serialize};
+
+use coordinate_systems::Field;
+use linear_algebra::Point2;
+
+use crate::motion_command::KickPower;
+
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    Deserialize,
+    Serialize,
+    PathSerialize,
+    PathDeserialize,
+    PathIntrospect,
+    PartialEq,
+    ros_z::Message,
+)]
+pub enum KickTargetKind {
+    Goal,
+    Pass(PlayerNumber),
+    Zone(PlayerNumber),
+}
+
+/// Scored target the ball could be kicked to.
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    Deserialize,
+    Serialize,
+    PathSerialize,
+    PathDeserialize,
+    PathIntrospect,
+    ros_z::Message,
+)]
+pub struct KickTargetCandidate {
+    pub kind: KickTargetKind,
+    /// Center of the widest opening towards the target
+    pub target: Point2<Field>,
+    pub kick_power: KickPower,
+    /// Angle of the widest opening not blocked by obstacles, seen from the ball
+    pub opening_angle: f32,
+    /// Probability that the kick ends up within the opening and reaches the target
+    pub accuracy: f32,
+    /// Probability that an opponent reaches the ball on its way to the target
+    pub interception_risk: f32,
+    pub score: f32,
+}
@@ -10,6 +10,8 @@ pub mod color;
 pub mod condition_input;
 pub mod cycle_time;
 pub mod detected_feet;
+pub mod execution_provider;
+pub mod exposure_timing;
 pub mod fall_state;
 pub mod field_border;
 pub mod field_color;
@@ -27,6 +29,8 @@ pub mod image_segments;
 pub mod initial_look_around;
 pub mod initial_pose;
 pub mod jpeg;
+pub mod keyframe_motion;
+pub mod kick_target;
 pub mod last_filtered_game_controller_state_change;
 pub mod line_data;
 pub mod localization;
@@ -49,9 +53,11 @@ pub mod point_of_interest;
 pub mod pose_detection;
 pub mod pose_kinds;
 pub mod primary_state;
+pub mod role;
 pub mod rule_obstacles;
 pub mod samples;
 pub mod step;
+pub mod stereo_depth;
 pub mod stereo_image_pair;
 pub mod support_foot;
 pub mod time_wrapper;
@@ -52,6 +52,9 @@ pub enum MotionCommand {
         head: HeadMotion,
     },
     StandUp,
+    KeyframeMotion {
+        name: String,
+    },
     VisualKick {
         head: HeadMotion,
         ball_position: Point2<Ground>,
@@ -85,7 +88,9 @@ impl MotionCommand {
             MotionCommand::Prepare => Some(HeadMotion::Center {
                 image_region_target: ImageRegion::Top,
             }),
-            MotionCommand::Damping | MotionCommand::StandUp => None,
+            MotionCommand::Damping
+            | MotionCommand::StandUp
+            | MotionCommand::KeyframeMotion { .. } => None,
         }
     }
 
@@ -95,6 +100,7 @@ impl MotionCommand {
             BodyMotion::Prepare => MotionCommand::Prepare,
             BodyMotion::Stand => MotionCommand::Stand { head },
             BodyMotion::StandUp => MotionCommand::StandUp,
+            BodyMotion::KeyframeMotion { name } => MotionCommand::KeyframeMotion { name },
             BodyMotion::VisualKick {
                 ball_position,
                 kick_direction,
@@ -153,6 +159,9 @@ pub enum BodyMotion {
     Prepare,
     Stand,
     StandUp,
+    KeyframeMotion {
+        name: String,
+    },
     VisualKick {
         ball_position: Point2<Ground>,
         kick_direction: Orientation2<Ground>,
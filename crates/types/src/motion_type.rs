@@ -22,6 +22,7 @@ pub enum MotionType {
     Prepare,
     Stand,
     StandUp,
+    KeyframeMotion,
     Kick,
     Walk,
 }
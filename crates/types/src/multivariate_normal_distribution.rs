@@ -20,3 +20,43 @@ pub struct MultivariateNormalDistribution<const DIMENSION: usize> {
     #[path_serde(leaf)]
     pub covariance: SMatrix<f32, DIMENSION, DIMENSION>,
 }
+
+impl<const DIMENSION: usize> MultivariateNormalDistribution<DIMENSION> {
+    /// Product of two independent estimates of the same quantity, `None` if their combined
+    /// covariance is not positive definite.
+    pub fn fuse(&self, other: &Self) -> Option<Self> {
+        let innovation_covariance = (self.covariance + other.covariance).cholesky()?;
+        // covariances are symmetric, so the gain is the transpose of the solved system
+        let gain = innovation_covariance.solve(&self.covariance).transpose();
+        Some(Self {
+            mean: self.mean + gain * (other.mean - self.mean),
+            covariance: self.covariance - gain * self.covariance,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use nalgebra::{Matrix2, vector};
+
+    use super::*;
+
+    #[test]
+    fn fusion_trusts_each_estimate_along_its_precise_axis() {
+        let monocular = MultivariateNormalDistribution {
+            mean: vector![2.0, 0.0],
+            covariance: Matrix2::from_diagonal(&vector![1.0, 0.01]),
+        };
+        let stereo = MultivariateNormalDistribution {
+            mean: vector![3.0, 1.0],
+            covariance: Matrix2::from_diagonal(&vector![0.01, 1.0]),
+        };
+
+        let fused = monocular.fuse(&stereo).unwrap();
+
+        assert!((fused.mean.x - 3.0).abs() < 0.02);
+        assert!(fused.mean.y.abs() < 0.02);
+        assert!(fused.covariance[(0, 0)] < 0.01);
+        assert!(fused.covariance[(1, 1)] < 0.01);
+    }
+}
@@ -1,3 +1,4 @@
+use nalgebra::{Matrix2, Vector2};
 use ros_z::{Message, time::Time};
 use serde::{Deserialize, Serialize};
 
@@ -7,8 +8,23 @@ use crate::{
 
 #[derive(Debug, Clone, Serialize, Deserialize, Message)]
 pub struct Hypothesis {
-    pub state: MultivariateNormalDistribution<2>,
+    /// Position and velocity `[x, y, vx, vy]` in ground coordinates
+    pub state: MultivariateNormalDistribution<4>,
     pub measurement_count: usize,
     pub last_update: Time,
     pub obstacle_kind: ObstacleKind,
 }
+
+impl Hypothesis {
+    pub fn position(&self) -> Vector2<f32> {
+        self.state.mean.fixed_rows::<2>(0).into()
+    }
+
+    pub fn position_covariance(&self) -> Matrix2<f32> {
+        self.state.covariance.fixed_view::<2, 2>(0, 0).into()
+    }
+
+    pub fn velocity(&self) -> Vector2<f32> {
+        self.state.mean.fixed_rows::<2>(2).into()
+    }
+}
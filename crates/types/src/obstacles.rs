@@ -1,6 +1,6 @@
 use serde::{Deserialize, Serialize};
 
-use linear_algebra::Point2;
+use linear_algebra::{Point2, Vector2};
 use path_serde::{PathDeserialize, PathIntrospect, PathSerialize};
 
 use coordinate_systems::Ground;
@@ -44,6 +44,9 @@ pub struct Obstacle {
     pub position: Point2<Ground>,
     pub radius_at_foot_height: f32,
     pub radius_at_hip_height: f32,
+    /// Estimated velocity, zero for obstacles not known to move
+    #[serde(default)]
+    pub velocity: Vector2<Ground>,
 }
 
 impl Obstacle {
@@ -53,6 +56,7 @@ impl Obstacle {
             position,
             radius_at_foot_height: radius,
             radius_at_hip_height: radius,
+            velocity: Vector2::zeros(),
         }
     }
 
@@ -66,6 +70,7 @@ impl Obstacle {
             position,
             radius_at_foot_height,
             radius_at_hip_height,
+            velocity: Vector2::zeros(),
         }
     }
 
@@ -75,6 +80,11 @@ impl Obstacle {
             position,
             radius_at_foot_height: radius,
             radius_at_hip_height: radius,
+            velocity: Vector2::zeros(),
         }
     }
+
+    pub fn with_velocity(self, velocity: Vector2<Ground>) -> Self {
+        Self { velocity, ..self }
+    }
 }
@@ -10,7 +10,13 @@ use linear_algebra::{Framed, Point2, Vector2, Vector3};
 use path_serde::{PathDeserialize, PathIntrospect, PathSerialize};
 
 use crate::{
-    field_color::FieldColorParameters, motion_command::MotionCommand, players::Players, step::Step,
+    execution_provider::{ExecutionProviderKind, InputDownscalingParameters},
+    field_color::{FieldColorClassifierParameters, FieldColorParameters},
+    motion_command::MotionCommand,
+    players::Players,
+    role::Role,
+    step::Step,
+    walk_volume_extents::WalkVolumeExtents,
 };
 
 #[derive(
@@ -92,13 +98,13 @@ pub struct BehaviorParameters {
     pub walk_with_velocity: WalkWithVelocityParameters,
     pub maximum_lookaround_duration: Duration,
     pub kicking: KickingParameters,
+    pub kick_target: KickTargetParameters,
     pub walk_speed: WalkSpeedParameters,
     pub intercept_ball: InterceptBallParameters,
     pub substates: SubstatesParameters,
     pub voronoi: VoronoiParameters,
     pub hsl_network: HslNetworkParameters,
-    pub closest_to_ball_enter_duration: Duration,
-    pub closest_to_ball_exit_duration: Duration,
+    pub role_assignment: RoleAssignmentParameters,
 }
 
 #[derive(
@@ -266,10 +272,19 @@ pub struct PathPlanningParameters {
     pub field_border_weight: f32,
     pub line_walking_speed: f32,
     pub obstacle_escape_spline_segments: u32,
+    /// Only read by the legacy path planner, the walk time model accounts for turning
+    #[serde(default)]
     pub rotation_penalty_factor: f32,
     pub minimum_robot_radius_at_foot_height: f32,
     pub robot_radius: f32,
     pub half_rotation: Duration,
+    /// Plan around the area moving obstacles sweep until the robot passes them
+    pub predict_obstacle_motion: bool,
+    pub obstacle_prediction_horizon: Duration,
+    /// Growth of the predicted obstacle radius per second of prediction
+    pub obstacle_prediction_radius_growth: f32,
+    /// Walking speeds per direction in m/s and rad/s, paths are planned for the shortest time
+    pub walk_speed_extents: WalkVolumeExtents,
 }
 
 #[derive(
@@ -373,7 +388,10 @@ pub struct HslNetworkParameters {
     pub remaining_amount_of_messages_to_stop_sending: u16,
     pub silence_interval_between_messages: Duration,
     pub hsl_striker_message_receive_timeout: Duration,
-    pub hsl_state_message_send_interval: Duration,
+    /// Shortest time between two state messages, even for urgent news
+    pub minimum_state_message_interval: Duration,
+    /// Length of a half, the message budget is spread over both halves
+    pub half_duration: Duration,
 }
 
 #[derive(
@@ -441,11 +459,48 @@ pub struct BallProjectionParameters {
 )]
 pub struct BallFilterNoise {
     pub detection_noise: Vector2<Pixel>,
+    /// Variance per second of the rolling model
     pub process_noise_moving: nalgebra::Vector4<f32>,
+    /// Variance per second of the resting model
     pub process_noise_resting: nalgebra::Vector2<f32>,
     pub initial_covariance: nalgebra::Vector4<f32>,
 }
 
+#[derive(
+    Clone,
+    Debug,
+    Default,
+    Deserialize,
+    Serialize,
+    PathSerialize,
+    PathDeserialize,
+    PathIntrospect,
+    Message,
+)]
+pub struct BallMotionModelParameters {
+    /// Exponential velocity decay of a rolling ball, the simulator uses `ball_friction_per_second`
+    pub friction_per_second: f32,
+    /// Fraction by which the friction moves towards the decay observed in a rolling ball
+    pub friction_learning_rate: f32,
+    /// Duration over which the velocity decay of a rolling ball is observed
+    pub friction_learning_window: Duration,
+    /// Slower balls are too noisy to learn the friction from
+    pub friction_learning_minimum_speed: f32,
+    /// Speed below which a rolling ball is considered to be at rest
+    pub stopping_speed: f32,
+    /// Switches per second between the resting and the rolling model
+    pub resting_rolling_switch_rate: f32,
+    /// Kicks per second while neither our own kick is executed nor a robot is close to the ball
+    pub kick_rate: f32,
+    /// Kicks per second while our own kick is executed or a robot is close to the ball
+    pub likely_kick_rate: f32,
+    /// Switches per second from the kicked to the rolling model, a kick is a short impulse
+    pub kicked_to_rolling_rate: f32,
+    pub kicking_robot_distance: f32,
+    /// Variance per second of the kicked model, large enough to absorb the velocity of a kick
+    pub kicked_process_noise: nalgebra::Vector4<f32>,
+}
+
 #[derive(
     Clone,
     Debug,
@@ -460,16 +515,17 @@ pub struct BallFilterNoise {
 pub struct BallFilterParameters {
     pub hypothesis_timeout: Duration,
     pub maximum_number_of_hypotheses: usize,
-    pub log_likelihood_of_zero_velocity_threshold: f32,
     pub hypothesis_merge_distance: f32,
     pub visible_validity_exponential_decay_factor: f32,
     pub hidden_validity_exponential_decay_factor: f32,
     pub validity_output_threshold: f32,
     pub validity_discard_threshold: f32,
-    pub velocity_decay_factor: f32,
     pub noise: BallFilterNoise,
+    pub motion_models: BallMotionModelParameters,
     pub maximum_matching_cost: f32,
     pub maximum_matching_cost_validity_penalty_factor: f32,
+    /// Fuse the stereo depth of a detected ball into its monocular ground projection
+    pub use_stereo_depth: bool,
 }
 
 #[derive(
@@ -485,6 +541,8 @@ pub struct BallFilterParameters {
 )]
 pub struct ObstacleFilterParameters {
     pub use_detected_objects: bool,
+    /// Fuse the stereo depth of detected robots and goal posts into their ground projection
+    pub use_stereo_depth: bool,
     pub hypothesis_timeout: Duration,
     pub network_robot_measurement_matching_distance: f32,
     pub object_detection_measurement_matching_distance: f32,
@@ -502,6 +560,10 @@ pub struct ObstacleFilterParameters {
     pub person_obstacle_radius: f32,
     pub unknown_obstacle_radius: f32,
     pub goal_post_obstacle_radius: f32,
+    /// Growth of the velocity variance per second, lets the estimate follow accelerating obstacles
+    pub velocity_process_noise: nalgebra::Vector2<f32>,
+    /// Bounds the published velocity and the initial velocity uncertainty of new hypotheses
+    pub maximum_obstacle_velocity: f32,
 }
 
 #[derive(
@@ -543,6 +605,13 @@ pub struct SearchSuggestorParameters {
     pub tile_switch_hysteresis: f32,
     pub decay_distance_factor: f32,
     pub heatmap_decay_range: Range<f32>,
+    /// How long a looked at field cell is reported to teammates as observed
+    pub observed_cell_memory: Duration,
+    /// Decay of tiles in field cells a teammate reports as observed, per received message
+    pub team_observation_decay: f32,
+    /// Radius around search positions of lower numbered teammates which is not searched
+    pub teammate_search_exclusion_radius: f32,
+    pub teammate_search_state_timeout: Duration,
 }
 
 #[derive(
@@ -589,6 +658,8 @@ pub struct RLWalkingParameters {
     pub hybrid_align_distance: f32,
     pub max_alignment_rate: f32,
     pub deceleration_distance: f32,
+
+    pub execution_providers: Vec<ExecutionProviderKind>,
 }
 
 #[derive(
@@ -660,6 +731,8 @@ pub struct DetectionParameters {
     pub enable: bool,
     pub neural_networks_folder: PathBuf,
     pub model_name: String,
+    pub execution_providers: Vec<ExecutionProviderKind>,
+    pub input_downscaling: InputDownscalingParameters,
     pub object_detection_parameters: ObjectDetectionParameters,
     pub pose_detection_parameters: PoseDetectionParameters,
 }
@@ -747,6 +820,76 @@ pub struct KickingParameters {
     pub kick_position_ball_distance: f32,
 }
 
+#[derive(
+    Clone,
+    Debug,
+    Default,
+    Deserialize,
+    Serialize,
+    PathSerialize,
+    PathDeserialize,
+    PathIntrospect,
+    ros_z::Message,
+)]
+pub struct KickTargetParameters {
+    /// Value of scoring, passes and open zones are weighed against it
+    pub goal_value: f32,
+    pub pass_value: f32,
+    pub zone_value: f32,
+    /// Width of the area around a pass or zone target the ball may end up in
+    pub target_width: f32,
+    pub rumpelstilzchen_accuracy: KickAccuracyParameters,
+    pub schlong_accuracy: KickAccuracyParameters,
+    /// Average speed of a kicked ball, determines how long opponents have to intercept it
+    pub ball_speed: f32,
+    pub opponent_speed: f32,
+    /// Distance beyond the reach of an opponent at which the interception risk dropped to 1/e
+    pub interception_risk_falloff: f32,
+    /// Obstacles this close to a teammate are taken to be the teammate
+    pub teammate_obstacle_distance: f32,
+}
+
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    Default,
+    Deserialize,
+    Serialize,
+    PathSerialize,
+    PathDeserialize,
+    PathIntrospect,
+    ros_z::Message,
+)]
+pub struct KickAccuracyParameters {
+    /// Standard deviation of the kick direction
+    pub angular_standard_deviation: f32,
+    /// Distance the ball travels, farther targets are reached less often
+    pub range: f32,
+}
+
+#[derive(
+    Clone,
+    Debug,
+    Default,
+    Deserialize,
+    Serialize,
+    PathSerialize,
+    PathDeserialize,
+    PathIntrospect,
+    ros_z::Message,
+)]
+pub struct RoleAssignmentParameters {
+    /// Time another role has to save before a robot gives up its current one
+    pub role_switch_penalty: Duration,
+    /// Teammates silent for longer keep their last role instead of being reassigned
+    pub teammate_silence_timeout: Duration,
+    /// Teammates silent for longer are no longer part of the team
+    pub teammate_loss_timeout: Duration,
+    /// Roles of silent teammates which were never assigned one
+    pub fallback_roles: Players<Role>,
+}
+
 #[derive(
     Clone,
     Debug,
@@ -791,6 +934,7 @@ pub struct KickPowerParameters {
     ros_z::Message,
 )]
 pub struct AllowSwitchParameters {
+    pub keyframe_motion: Duration,
     pub kick: Duration,
     pub prepare: Duration,
     pub stand: Duration,
@@ -838,6 +982,7 @@ pub struct ImageSegmenterParameters {
     pub vertical_stride: usize,
     pub vertical_stride_in_ground: Framed<Ground, f32>,
     pub field_color_detection: FieldColorParameters,
+    pub field_color_classifier: FieldColorClassifierParameters,
 }
 
 #[derive(
@@ -1,5 +1,7 @@
 pub mod traits;
 
+use std::time::Duration;
+
 use approx::{AbsDiffEq, RelativeEq};
 use ros_z::Message;
 use serde::{Deserialize, Serialize};
@@ -50,6 +52,44 @@ impl Path {
     }
 }
 
+/// Path with the time at which the robot is expected to reach the end of each segment.
+#[derive(
+    Clone,
+    Debug,
+    Serialize,
+    Deserialize,
+    PartialEq,
+    PathSerialize,
+    PathDeserialize,
+    PathIntrospect,
+    Message,
+)]
+pub struct TimedPath {
+    pub path: Path,
+    pub arrival_times: Vec<Duration>,
+}
+
+impl TimedPath {
+    /// Accumulates the expected walking time of each segment in seconds into arrival times.
+    pub fn from_segment_times(path: Path, segment_times: impl IntoIterator<Item = f32>) -> Self {
+        let arrival_times = segment_times
+            .into_iter()
+            .scan(0.0, |elapsed, segment_time| {
+                *elapsed += segment_time;
+                Some(Duration::try_from_secs_f32(*elapsed).unwrap_or(Duration::MAX))
+            })
+            .collect();
+        Self {
+            path,
+            arrival_times,
+        }
+    }
+
+    pub fn duration(&self) -> Duration {
+        self.arrival_times.last().copied().unwrap_or_default()
+    }
+}
+
 pub fn direct_path(start: Point2<Ground>, destination: Point2<Ground>) -> Path {
     Path {
         segments: vec![PathSegment::LineSegment(LineSegment(start, destination))],
@@ -109,3 +149,28 @@ impl PathSegment {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use linear_algebra::point;
+
+    use super::*;
+
+    #[test]
+    fn timed_path_accumulates_segment_durations() {
+        let path = Path {
+            segments: vec![
+                PathSegment::LineSegment(LineSegment(point![0.0, 0.0], point![1.0, 0.0])),
+                PathSegment::LineSegment(LineSegment(point![1.0, 0.0], point![1.0, 0.5])),
+            ],
+        };
+
+        let timed_path = TimedPath::from_segment_times(path, [2.0, 1.0]);
+
+        assert_eq!(
+            timed_path.arrival_times,
+            vec![Duration::from_secs(2), Duration::from_secs(3)]
+        );
+        assert_eq!(timed_path.duration(), Duration::from_secs(3));
+    }
+}
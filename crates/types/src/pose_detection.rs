@@ -244,6 +244,7 @@ pub struct RefereePoseCandidate {
     PathSerialize,
     PathDeserialize,
     PathIntrospect,
+    ros_z::Message,
 )]
 pub struct ReadySignalDetectionResult {
     pub detected_own_ready_signal: bool,
@@ -260,6 +261,7 @@ pub struct ReadySignalDetectionResult {
     PathSerialize,
     PathDeserialize,
     PathIntrospect,
+    ros_z::Message,
 )]
 pub struct FreeKickSignalDetectionResult {
     pub own_detected_kicking_team: Option<Team>,
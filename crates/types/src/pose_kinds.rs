@@ -15,6 +15,7 @@ use crate::field_dimensions::GlobalFieldSide;
     PathIntrospect,
     PartialEq,
     Eq,
+    ros_z::Message,
 )]
 pub enum PoseKind {
     Ready,
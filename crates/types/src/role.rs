@@ -0,0 +1,28 @@
+use path_serde::{PathDeserialize, PathIntrospect, PathSerialize};
+use ros_z::Message;
+use serde::{Deserialize, Serialize};
+
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    Default,
+    Deserialize,
+    Eq,
+    Hash,
+    PartialEq,
+    Serialize,
+    PathSerialize,
+    PathDeserialize,
+    PathIntrospect,
+    Message,
+)]
+pub enum Role {
+    Keeper,
+    /// Guards the own goal while the keeper is missing
+    KeeperReplacement,
+    #[default]
+    Striker,
+    Supporter,
+    Defender,
+}
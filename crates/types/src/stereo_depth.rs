@@ -0,0 +1,26 @@
+use path_serde::{PathDeserialize, PathIntrospect, PathSerialize};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    multivariate_normal_distribution::MultivariateNormalDistribution, object_detection::Object,
+};
+
+/// Detected object with its position triangulated from the stereo image pair.
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    Serialize,
+    Deserialize,
+    PathIntrospect,
+    PathSerialize,
+    PathDeserialize,
+    ros_z::Message,
+)]
+pub struct StereoObject<T> {
+    pub object: Object<T>,
+    /// Position of the visible surface in the center of the bounding box
+    pub position_in_ground: MultivariateNormalDistribution<3>,
+    /// Median disparity of the bounding box in pixels
+    pub disparity: f32,
+}
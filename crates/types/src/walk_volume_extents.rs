@@ -5,7 +5,15 @@ use serde::{Deserialize, Serialize};
 use path_serde::{PathDeserialize, PathIntrospect, PathSerialize};
 
 #[derive(
-    Clone, Debug, Default, Deserialize, Serialize, PathSerialize, PathDeserialize, PathIntrospect,
+    Clone,
+    Debug,
+    Default,
+    Deserialize,
+    Serialize,
+    PathSerialize,
+    PathDeserialize,
+    PathIntrospect,
+    ros_z::Message,
 )]
 pub struct WalkVolumeExtents {
     pub forward: f32,
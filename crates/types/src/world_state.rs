@@ -1,7 +1,7 @@
 use std::time::{SystemTime, UNIX_EPOCH};
 
 use booster::FallDownState;
-use hsl_network_messages::PlayerNumber;
+use hsl_network_messages::{PlayerNumber, SearchState};
 use ros_z::{Message, time::Time};
 use serde::{Deserialize, Serialize};
 
@@ -34,6 +34,7 @@ pub struct WorldState {
     pub rule_obstacles: Vec<RuleObstacle>,
     pub fall_down_state: Option<FallDownState>,
     pub suggested_search_position: Option<Point2<Field>>,
+    pub search_state: SearchState,
 }
 
 #[allow(clippy::derivable_impls)]
@@ -52,6 +53,7 @@ impl Default for WorldState {
             rule_obstacles: Default::default(),
             fall_down_state: Default::default(),
             suggested_search_position: Default::default(),
+            search_state: Default::default(),
         }
     }
 }
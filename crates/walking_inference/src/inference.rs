@@ -4,17 +4,20 @@ use booster::{ImuState, MotorCommandParameters, MotorState};
 use color_eyre::Result;
 use coordinate_systems::Ground;
 use framework::deserialize_not_implemented;
+use inference_session::ExecutionProviderChain;
 use kinematics::joints::{Joints, leg::LegJoints};
 use linear_algebra::{Vector2, vector};
 use ndarray::{Array1, Axis};
 use ort::{
-    execution_providers::{CUDAExecutionProvider, TensorRTExecutionProvider},
     inputs,
     session::{Session, builder::GraphOptimizationLevel},
     value::Tensor,
 };
 use serde::{Deserialize, Serialize};
-use types::{cycle_time::CycleTime, parameters::RLWalkingParameters};
+use types::{
+    cycle_time::CycleTime, execution_provider::ExecutionProviderKind,
+    parameters::RLWalkingParameters,
+};
 
 use crate::inputs::{WalkCommand, WalkingInferenceInputs};
 
@@ -22,6 +25,8 @@ use crate::inputs::{WalkCommand, WalkingInferenceInputs};
 pub struct WalkingInference {
     #[serde(skip, default = "deserialize_not_implemented")]
     session: Session,
+    #[serde(skip)]
+    pub execution_provider: ExecutionProviderKind,
     last_linear_velocity_command: Vector2<Ground>,
     last_angular_velocity_command: f32,
     last_gait_progress: f32,
@@ -32,19 +37,20 @@ impl WalkingInference {
     pub fn new(
         neural_network_folder: impl AsRef<Path>,
         prepare_motor_command_parameters: &MotorCommandParameters,
+        execution_providers: &[ExecutionProviderKind],
     ) -> Result<Self> {
         let neural_network_path = neural_network_folder.as_ref().join("T1.onnx");
 
-        let session = Session::builder()?
+        let mut session_builder = Session::builder()?;
+        let execution_provider = ExecutionProviderChain::default()
+            .register(execution_providers, &mut session_builder)?;
+        let session = session_builder
             .with_optimization_level(GraphOptimizationLevel::Level3)?
-            .with_execution_providers([
-                TensorRTExecutionProvider::default().build(),
-                CUDAExecutionProvider::default().build(),
-            ])?
             .commit_from_file(neural_network_path)?;
 
         Ok(Self {
             session,
+            execution_provider,
             last_linear_velocity_command: vector![0.0, 0.0],
             last_angular_velocity_command: 0.0,
             last_gait_progress: 0.0,
@@ -42,6 +42,7 @@ fn motion_type_for(motion_command: &MotionCommand) -> Option<MotionType> {
         }
         MotionCommand::Stand { .. } => Some(MotionType::Stand),
         MotionCommand::StandUp => Some(MotionType::StandUp),
+        MotionCommand::KeyframeMotion { .. } => Some(MotionType::KeyframeMotion),
         MotionCommand::Prepare => Some(MotionType::Prepare),
     }
 }
@@ -18,6 +18,7 @@ pub fn switch_motion_type(
         MotionType::Damping => condition!(is_last_motion_type, MotionType::Damping),
         MotionType::Stand => condition!(is_last_motion_type, MotionType::Stand),
         MotionType::StandUp => condition!(is_last_motion_type, MotionType::StandUp),
+        MotionType::KeyframeMotion => condition!(is_last_motion_type, MotionType::KeyframeMotion),
         MotionType::Walk => condition!(is_last_motion_type, MotionType::Walk),
     };
 
@@ -46,6 +47,7 @@ pub fn is_allowed_to_switch(blackboard: &mut Blackboard) -> bool {
         Some(MotionType::Prepare) => parameters.prepare < time_since_last_switch,
         Some(MotionType::Stand) => parameters.stand < time_since_last_switch,
         Some(MotionType::StandUp) => parameters.stand_up < time_since_last_switch,
+        Some(MotionType::KeyframeMotion) => parameters.keyframe_motion < time_since_last_switch,
         Some(MotionType::Walk) => parameters.walk < time_since_last_switch,
         None => true,
     }
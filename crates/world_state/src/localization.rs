@@ -1,7 +1,4 @@
-use std::{
-    f32::consts::FRAC_PI_2,
-    time::{Duration, SystemTime},
-};
+use std::time::{Duration, SystemTime};
 
 use booster::{FallDownState, FallDownStateType, ImuState, Odometer};
 use color_eyre::{
@@ -10,7 +7,7 @@ use color_eyre::{
 };
 use geometry::line_segment::LineSegment;
 use linear_algebra::{IntoTransform, Isometry2, Point2, Pose2, distance, point};
-use nalgebra::{Matrix2, Matrix3, Rotation2, Vector2, Vector3, matrix};
+use nalgebra::{Matrix2, Matrix3, Vector2, Vector3};
 use ordered_float::NotNan;
 use serde::{Deserialize, Serialize};
 
@@ -19,25 +16,29 @@ use coordinate_systems::{Field, Ground};
 use filtering::pose_filter::PoseFilter;
 use framework::{AdditionalOutput, MainOutput, PerceptionInput};
 use hsl_network_messages::{GamePhase, Penalty, PlayerNumber, SubState, Team};
+use localization::{
+    filter::{
+        generate_initial_pose, generate_penalized_poses, predict, primary_state_uses_localization,
+    },
+    line_fitting::{
+        FieldMarkCorrespondence, FitErrorsPerHypothesis, FitErrorsPerMeasurement,
+        get_2d_translation_measurement, get_fitted_field_mark_correspondence,
+        get_translation_and_rotation_measurement,
+        goal_support_structure_line_marks_from_field_dimensions,
+    },
+};
 use types::{
     cycle_time::CycleTime,
     field_dimensions::FieldDimensions,
-    field_marks::{CorrespondencePoints, Direction, FieldMark, field_marks_from_field_dimensions},
+    field_marks::{Direction, FieldMark, field_marks_from_field_dimensions},
     filtered_game_controller_state::FilteredGameControllerState,
     initial_pose::InitialPose,
     line_data::LineData,
     localization::{ScoredPose, Update},
-    multivariate_normal_distribution::MultivariateNormalDistribution,
     players::Players,
     primary_state::PrimaryState,
-    support_foot::Side,
 };
 
-type FitErrorsPerGradientStep = Vec<f32>;
-type FitErrorsPerOuterIteration = Vec<FitErrorsPerGradientStep>;
-type FitErrorsPerHypothesis = Vec<FitErrorsPerOuterIteration>;
-type FitErrorsPerMeasurement = Vec<FitErrorsPerHypothesis>;
-
 #[derive(Deserialize, Serialize)]
 pub struct Localization {
     field_marks: Vec<FieldMark>,
@@ -229,7 +230,7 @@ impl Localization {
             theta: imu_state.roll_pitch_yaw.z(),
         });
         let current_odometry_to_last_odometry = match (self.last_odometer, odometer_with_imu_yaw) {
-            (Some(last), Some(latest)) => odometry_delta(last, latest),
+            (Some(last), Some(latest)) => latest.to(last).inner,
             _ => Default::default(),
         };
         self.last_odometer = odometer_with_imu_yaw;
@@ -775,13 +776,6 @@ impl Localization {
     }
 }
 
-fn primary_state_uses_localization(primary_state: PrimaryState) -> bool {
-    matches!(
-        primary_state,
-        PrimaryState::Initial | PrimaryState::Ready | PrimaryState::Set | PrimaryState::Playing
-    )
-}
-
 fn penalty_exit_strategy(
     is_penalized_with_motion_in_set_or_initial: bool,
     time_when_penalized_clicked: Option<SystemTime>,
@@ -803,534 +797,14 @@ fn penalty_exit_strategy(
     }
 }
 
-pub fn goal_support_structure_line_marks_from_field_dimensions(
-    field_dimensions: &FieldDimensions,
-) -> Vec<FieldMark> {
-    let goal_width = field_dimensions.goal_inner_width + field_dimensions.goal_post_diameter;
-    let goal_depth = field_dimensions.goal_depth;
-    vec![
-        FieldMark::Line {
-            line: LineSegment(
-                point![
-                    -field_dimensions.length / 2.0 - goal_depth,
-                    -goal_width / 2.0
-                ],
-                point![
-                    -field_dimensions.length / 2.0 - goal_depth,
-                    goal_width / 2.0
-                ],
-            ),
-            direction: Direction::PositiveY,
-        },
-        FieldMark::Line {
-            line: LineSegment(
-                point![
-                    -field_dimensions.length / 2.0 - goal_depth,
-                    -goal_width / 2.0
-                ],
-                point![-field_dimensions.length / 2.0, -goal_width / 2.0],
-            ),
-            direction: Direction::PositiveX,
-        },
-        FieldMark::Line {
-            line: LineSegment(
-                point![
-                    -field_dimensions.length / 2.0 - goal_depth,
-                    goal_width / 2.0
-                ],
-                point![-field_dimensions.length / 2.0, goal_width / 2.0],
-            ),
-            direction: Direction::PositiveX,
-        },
-        FieldMark::Line {
-            line: LineSegment(
-                point![
-                    field_dimensions.length / 2.0 + goal_depth,
-                    -goal_width / 2.0
-                ],
-                point![field_dimensions.length / 2.0 + goal_depth, goal_width / 2.0],
-            ),
-            direction: Direction::PositiveY,
-        },
-        FieldMark::Line {
-            line: LineSegment(
-                point![field_dimensions.length / 2.0, -goal_width / 2.0],
-                point![
-                    field_dimensions.length / 2.0 + goal_depth,
-                    -goal_width / 2.0
-                ],
-            ),
-            direction: Direction::PositiveX,
-        },
-        FieldMark::Line {
-            line: LineSegment(
-                point![field_dimensions.length / 2.0, goal_width / 2.0],
-                point![field_dimensions.length / 2.0 + goal_depth, goal_width / 2.0],
-            ),
-            direction: Direction::PositiveX,
-        },
-    ]
-}
-
-#[derive(Clone, Copy, Debug)]
-pub struct FieldMarkCorrespondence {
-    measured_line_in_field: LineSegment<Field>,
-    field_mark: FieldMark,
-    pub correspondence_points: (CorrespondencePoints, CorrespondencePoints),
-}
-
-impl FieldMarkCorrespondence {
-    fn fit_error_sum(&self) -> f32 {
-        (self.correspondence_points.0.measured - self.correspondence_points.0.reference).norm()
-            + (self.correspondence_points.1.measured - self.correspondence_points.1.reference)
-                .norm()
-    }
-}
-
-fn predict(
-    state: &mut MultivariateNormalDistribution<3>,
-    current_odometry_to_last_odometry: nalgebra::Isometry2<f32>,
-    odometry_noise: &Vector3<f32>,
-) -> Result<()> {
-    let process_noise = odometry_process_noise(
-        current_odometry_to_last_odometry,
-        state.mean.z,
-        odometry_noise,
-    );
-
-    state.predict(
-        |state| {
-            let last_ground_to_field =
-                nalgebra::Isometry2::new(nalgebra::vector![state.x, state.y], state.z);
-            let current_ground_to_field = last_ground_to_field * current_odometry_to_last_odometry;
-
-            nalgebra::vector![
-                current_ground_to_field.translation.vector.x,
-                current_ground_to_field.translation.vector.y,
-                current_ground_to_field.rotation.angle()
-            ]
-        },
-        process_noise,
-    )?;
-    Ok(())
-}
-
-fn odometry_process_noise(
-    current_odometry_to_last_odometry: nalgebra::Isometry2<f32>,
-    current_orientation_angle: f32,
-    odometry_noise: &Vector3<f32>,
-) -> Matrix3<f32> {
-    let odometry_translation = current_odometry_to_last_odometry.translation.vector;
-    let translation_noise_in_odometry_frame = odometry_translation
-        .abs()
-        .component_mul(&odometry_noise.xy());
-    let rotation_to_field = Rotation2::new(current_orientation_angle);
-    let translation_process_noise = rotation_to_field.matrix()
-        * Matrix2::from_diagonal(&translation_noise_in_odometry_frame)
-        * rotation_to_field.matrix().transpose();
-
-    let mut process_noise = Matrix3::zeros();
-    process_noise
-        .fixed_view_mut::<2, 2>(0, 0)
-        .copy_from(&translation_process_noise);
-    process_noise[(2, 2)] =
-        current_odometry_to_last_odometry.rotation.angle().abs() * odometry_noise.z;
-    process_noise
-}
-
-fn odometry_delta(last_odometer: Odometer, current_odometer: Odometer) -> nalgebra::Isometry2<f32> {
-    let last_odometry_to_world = nalgebra::Isometry2::new(
-        nalgebra::vector![last_odometer.x, last_odometer.y],
-        last_odometer.theta,
-    );
-    let current_odometry_to_world = nalgebra::Isometry2::new(
-        nalgebra::vector![current_odometer.x, current_odometer.y],
-        current_odometer.theta,
-    );
-
-    last_odometry_to_world.inverse() * current_odometry_to_world
-}
-
-#[allow(clippy::too_many_arguments)]
-pub fn get_fitted_field_mark_correspondence(
-    measured_lines_in_field: &[LineSegment<Field>],
-    field_marks: &[FieldMark],
-    gradient_convergence_threshold: f32,
-    gradient_descent_step_size: f32,
-    line_length_acceptance_factor: f32,
-    maximum_amount_of_gradient_descent_iterations: usize,
-    maximum_amount_of_outer_iterations: usize,
-    fit_errors_is_subscribed: bool,
-) -> (
-    Vec<FieldMarkCorrespondence>,
-    f32,
-    FitErrorsPerOuterIteration,
-) {
-    if measured_lines_in_field.is_empty() || field_marks.is_empty() {
-        return (Vec::new(), f32::INFINITY, Vec::new());
-    }
-
-    let mut fit_errors = Vec::new();
-    let mut correction = nalgebra::Isometry2::identity();
-
-    for _ in 0..maximum_amount_of_outer_iterations {
-        let correspondence_points = get_correspondence_points(get_field_mark_correspondence(
-            measured_lines_in_field,
-            correction,
-            field_marks,
-            line_length_acceptance_factor,
-        ));
-        if correspondence_points.is_empty() {
-            return (Vec::new(), f32::INFINITY, fit_errors);
-        }
-
-        let weight_matrices = weight_matrices(&correspondence_points, correction);
-        let mut fit_errors_per_iteration = Vec::new();
-
-        for _ in 0..maximum_amount_of_gradient_descent_iterations {
-            let translation_gradient: Vector2<f32> = correspondence_points
-                .iter()
-                .zip(weight_matrices.iter())
-                .map(|(correspondence_points, weight_matrix)| {
-                    2.0 * weight_matrix
-                        * ((correction * correspondence_points.measured.inner)
-                            - correspondence_points.reference.inner)
-                })
-                .sum::<Vector2<f32>>()
-                / correspondence_points.len() as f32;
-            let rotation = correction.rotation.angle();
-            let rotation_derivative =
-                matrix![-rotation.sin(), -rotation.cos(); rotation.cos(), -rotation.sin()];
-            let rotation_gradient: f32 = correspondence_points
-                .iter()
-                .zip(weight_matrices.iter())
-                .map(|(correspondence_points, weight_matrix)| {
-                    (2.0 * correspondence_points.measured.inner.coords.transpose()
-                        * rotation_derivative.transpose()
-                        * weight_matrix
-                        * ((correction * correspondence_points.measured.inner)
-                            - correspondence_points.reference.inner))
-                        .x
-                })
-                .sum::<f32>()
-                / correspondence_points.len() as f32;
-
-            correction = nalgebra::Isometry2::new(
-                correction.translation.vector - (gradient_descent_step_size * translation_gradient),
-                rotation - gradient_descent_step_size * rotation_gradient,
-            );
-
-            if fit_errors_is_subscribed {
-                fit_errors_per_iteration.push(get_fit_error(
-                    &correspondence_points,
-                    &weight_matrices,
-                    correction,
-                ));
-            }
-
-            let gradient_norm = nalgebra::vector![
-                translation_gradient.x,
-                translation_gradient.y,
-                rotation_gradient
-            ]
-            .norm();
-            if gradient_norm < gradient_convergence_threshold {
-                break;
-            }
-        }
-
-        if fit_errors_is_subscribed {
-            fit_errors.push(fit_errors_per_iteration);
-        }
-    }
-
-    let field_mark_correspondences = get_field_mark_correspondence(
-        measured_lines_in_field,
-        correction,
-        field_marks,
-        line_length_acceptance_factor,
-    );
-    let correspondence_points = get_correspondence_points(field_mark_correspondences.clone());
-    if correspondence_points.is_empty() {
-        return (Vec::new(), f32::INFINITY, fit_errors);
-    }
-
-    let fit_error = get_fit_error(
-        &correspondence_points,
-        &weight_matrices(&correspondence_points, correction),
-        correction,
-    );
-
-    (field_mark_correspondences, fit_error, fit_errors)
-}
-
-fn weight_matrices(
-    correspondence_points: &[CorrespondencePoints],
-    correction: nalgebra::Isometry2<f32>,
-) -> Vec<Matrix2<f32>> {
-    correspondence_points
-        .iter()
-        .map(|correspondence_points| {
-            let normal = (correction * correspondence_points.measured.inner)
-                - correspondence_points.reference.inner;
-            if normal.norm() > 0.0 {
-                let normal_versor = normal.normalize();
-                normal_versor * normal_versor.transpose()
-            } else {
-                Matrix2::zeros()
-            }
-        })
-        .collect()
-}
-
-fn get_fit_error(
-    correspondence_points: &[CorrespondencePoints],
-    weight_matrices: &[Matrix2<f32>],
-    correction: nalgebra::Isometry2<f32>,
-) -> f32 {
-    if correspondence_points.is_empty() {
-        return f32::INFINITY;
-    }
-
-    correspondence_points
-        .iter()
-        .zip(weight_matrices.iter())
-        .map(|(correspondence_points, weight_matrix)| {
-            ((correction * correspondence_points.measured.inner
-                - correspondence_points.reference.inner)
-                .transpose()
-                * weight_matrix
-                * (correction * correspondence_points.measured.inner
-                    - correspondence_points.reference.inner))
-                .x
-        })
-        .sum::<f32>()
-        / correspondence_points.len() as f32
-}
-
-fn get_field_mark_correspondence(
-    measured_lines_in_field: &[LineSegment<Field>],
-    correction: nalgebra::Isometry2<f32>,
-    field_marks: &[FieldMark],
-    line_length_acceptance_factor: f32,
-) -> Vec<FieldMarkCorrespondence> {
-    measured_lines_in_field
-        .iter()
-        .filter_map(|&measured_line_in_field| {
-            let (correspondences, _weight, field_mark, transformed_line) = field_marks
-                .iter()
-                .filter_map(|field_mark| {
-                    let transformed_line = correction.framed_transform() * measured_line_in_field;
-                    let field_mark_length = match field_mark {
-                        FieldMark::Line { line, .. } => line.length(),
-                        FieldMark::Circle { radius, .. } => *radius,
-                    };
-                    if field_mark_length <= 0.0 {
-                        return None;
-                    }
-
-                    let measured_line_length = transformed_line.length();
-                    if measured_line_length > field_mark_length * line_length_acceptance_factor {
-                        return None;
-                    }
-
-                    let correspondences = field_mark.to_correspondence_points(transformed_line);
-                    let angle_weight = correspondences
-                        .measured_direction
-                        .dot(&correspondences.reference_direction)
-                        .abs()
-                        + measured_line_length / field_mark_length;
-                    let length_weight = measured_line_length / field_mark_length;
-                    let weight = angle_weight + length_weight;
-
-                    (weight > 0.0).then_some((
-                        correspondences,
-                        weight,
-                        field_mark,
-                        transformed_line,
-                    ))
-                })
-                .min_by_key(
-                    |(correspondence_points, weight, _field_mark, _transformed_line)| {
-                        (NotNan::new(
-                            distance(
-                                correspondence_points.correspondence_points.0.measured,
-                                correspondence_points.correspondence_points.0.reference,
-                            ) + distance(
-                                correspondence_points.correspondence_points.1.measured,
-                                correspondence_points.correspondence_points.1.reference,
-                            ),
-                        )
-                        .unwrap())
-                            / *weight
-                    },
-                )?;
-            let inverse_transformation = correction.inverse().framed_transform();
-            Some(FieldMarkCorrespondence {
-                measured_line_in_field: inverse_transformation * transformed_line,
-                field_mark: *field_mark,
-                correspondence_points: (
-                    CorrespondencePoints {
-                        measured: inverse_transformation
-                            * correspondences.correspondence_points.0.measured,
-                        reference: correspondences.correspondence_points.0.reference,
-                    },
-                    CorrespondencePoints {
-                        measured: inverse_transformation
-                            * correspondences.correspondence_points.1.measured,
-                        reference: correspondences.correspondence_points.1.reference,
-                    },
-                ),
-            })
-        })
-        .collect()
-}
-
-fn get_correspondence_points(
-    field_mark_correspondences: Vec<FieldMarkCorrespondence>,
-) -> Vec<CorrespondencePoints> {
-    field_mark_correspondences
-        .iter()
-        .flat_map(|field_mark_correspondence| {
-            [
-                field_mark_correspondence.correspondence_points.0,
-                field_mark_correspondence.correspondence_points.1,
-            ]
-        })
-        .collect()
-}
-
-fn get_translation_and_rotation_measurement(
-    ground_to_field: Isometry2<Ground, Field>,
-    field_mark_correspondence: FieldMarkCorrespondence,
-) -> Vector2<f32> {
-    let (field_mark_line, field_mark_line_direction) = match field_mark_correspondence.field_mark {
-        FieldMark::Line { line, direction } => (line, direction),
-        FieldMark::Circle { .. } => unreachable!("line measurement requested for circle mark"),
-    };
-    let measured_line_in_field = match field_mark_line_direction {
-        Direction::PositiveX
-            if field_mark_correspondence.measured_line_in_field.1.x()
-                < field_mark_correspondence.measured_line_in_field.0.x() =>
-        {
-            LineSegment(
-                field_mark_correspondence.measured_line_in_field.1,
-                field_mark_correspondence.measured_line_in_field.0,
-            )
-        }
-        Direction::PositiveY
-            if field_mark_correspondence.measured_line_in_field.1.y()
-                < field_mark_correspondence.measured_line_in_field.0.y() =>
-        {
-            LineSegment(
-                field_mark_correspondence.measured_line_in_field.1,
-                field_mark_correspondence.measured_line_in_field.0,
-            )
-        }
-        _ => field_mark_correspondence.measured_line_in_field,
-    };
-    let measured_line_in_field_vector = measured_line_in_field.1 - measured_line_in_field.0;
-    let signed_distance_to_line =
-        measured_line_in_field.signed_distance_to_point(ground_to_field.as_pose().position());
-    match field_mark_line_direction {
-        Direction::PositiveX => nalgebra::vector![
-            field_mark_line.0.y() + signed_distance_to_line,
-            (-measured_line_in_field_vector.y()).atan2(measured_line_in_field_vector.x())
-                + ground_to_field.orientation().angle()
-        ],
-        Direction::PositiveY => nalgebra::vector![
-            field_mark_line.0.x() - signed_distance_to_line,
-            measured_line_in_field_vector
-                .x()
-                .atan2(measured_line_in_field_vector.y())
-                + ground_to_field.orientation().angle()
-        ],
-    }
-}
-
-fn get_2d_translation_measurement(
-    ground_to_field: Isometry2<Ground, Field>,
-    field_mark_correspondence: FieldMarkCorrespondence,
-) -> Vector2<f32> {
-    let measured_line_vector = field_mark_correspondence.correspondence_points.1.measured
-        - field_mark_correspondence.correspondence_points.0.measured;
-    let reference_line_vector = field_mark_correspondence.correspondence_points.1.reference
-        - field_mark_correspondence.correspondence_points.0.reference;
-    let measured_line_point_0_to_robot_vector = ground_to_field.as_pose().position()
-        - field_mark_correspondence.correspondence_points.0.measured;
-    let measured_rotation = f32::atan2(
-        measured_line_point_0_to_robot_vector.y() * measured_line_vector.x()
-            - measured_line_point_0_to_robot_vector.x() * measured_line_vector.y(),
-        measured_line_point_0_to_robot_vector.x() * measured_line_vector.x()
-            + measured_line_point_0_to_robot_vector.y() * measured_line_vector.y(),
-    );
-
-    let reference_line_point_0_to_robot_vector = Rotation2::new(measured_rotation)
-        * reference_line_vector.normalize().inner
-        * measured_line_point_0_to_robot_vector.norm();
-    let reference_robot_point = field_mark_correspondence
-        .correspondence_points
-        .0
-        .reference
-        .inner
-        + reference_line_point_0_to_robot_vector;
-    reference_robot_point.coords
-}
-
-pub fn generate_initial_pose(
-    initial_pose: &InitialPose,
-    field_dimensions: &FieldDimensions,
-) -> Pose2<Field> {
-    match initial_pose.side {
-        Side::Left => Pose2::new(
-            point![
-                initial_pose.center_line_offset_x,
-                field_dimensions.width * 0.5
-            ],
-            -FRAC_PI_2,
-        ),
-        Side::Right => Pose2::new(
-            point![
-                initial_pose.center_line_offset_x,
-                -field_dimensions.width * 0.5
-            ],
-            FRAC_PI_2,
-        ),
-    }
-}
-
-fn generate_penalized_poses(
-    field_dimensions: &FieldDimensions,
-    penalized_distance: f32,
-) -> Vec<Pose2<Field>> {
-    vec![
-        Pose2::new(
-            point![
-                -field_dimensions.length * 0.5 + field_dimensions.penalty_marker_distance,
-                -field_dimensions.width * 0.5 - penalized_distance
-            ],
-            FRAC_PI_2,
-        ),
-        Pose2::new(
-            point![
-                -field_dimensions.length * 0.5 + field_dimensions.penalty_marker_distance,
-                field_dimensions.width * 0.5 + penalized_distance
-            ],
-            -FRAC_PI_2,
-        ),
-    ]
-}
-
 #[cfg(test)]
 mod tests {
     use std::{
-        f32::consts::{FRAC_PI_4, PI},
+        f32::consts::{FRAC_PI_2, PI},
         time::{Duration, SystemTime},
     };
 
     use approx::assert_relative_eq;
-    use linear_algebra::Point2;
-    use nalgebra::{matrix, vector};
 
     use super::*;
 
@@ -1386,42 +860,6 @@ mod tests {
         );
     }
 
-    #[test]
-    fn empty_measurements_produce_no_correspondence() {
-        let (correspondences, fit_error, fit_errors) = get_fitted_field_mark_correspondence(
-            &[],
-            &[FieldMark::Line {
-                line: LineSegment(point![0.0, 0.0], point![1.0, 0.0]),
-                direction: Direction::PositiveX,
-            }],
-            0.001,
-            0.1,
-            1.5,
-            8,
-            4,
-            true,
-        );
-
-        assert!(correspondences.is_empty());
-        assert!(fit_error.is_infinite());
-        assert!(fit_errors.is_empty());
-    }
-
-    #[test]
-    fn zero_length_field_marks_are_ignored() {
-        let correspondences = get_field_mark_correspondence(
-            &[LineSegment(point![0.0, 0.0], point![1.0, 0.0])],
-            nalgebra::Isometry2::identity(),
-            &[FieldMark::Circle {
-                center: Point2::origin(),
-                radius: 0.0,
-            }],
-            1.5,
-        );
-
-        assert!(correspondences.is_empty());
-    }
-
     #[test]
     fn odometry_delta_uses_relative_motion() {
         let last_odometer = Odometer {
@@ -1458,36 +896,6 @@ mod tests {
         assert_relative_eq!(delta.orientation().angle(), -PI + 0.2, epsilon = 0.0001);
     }
 
-    #[test]
-    fn standing_still_adds_no_odometry_process_noise() {
-        let process_noise = odometry_process_noise(
-            nalgebra::Isometry2::identity(),
-            0.3,
-            &vector![0.02, 0.02, 0.001],
-        );
-
-        assert_relative_eq!(process_noise, Matrix3::zeros(), epsilon = 0.0001);
-    }
-
-    #[test]
-    fn rotational_process_noise_scales_with_actual_turn() {
-        let process_noise = odometry_process_noise(
-            nalgebra::Isometry2::new(vector![0.0, 0.0], 0.2),
-            0.0,
-            &vector![0.02, 0.02, 0.001],
-        );
-
-        assert_relative_eq!(
-            process_noise,
-            matrix![
-                0.0, 0.0, 0.0;
-                0.0, 0.0, 0.0;
-                0.0, 0.0, 0.0002
-            ],
-            epsilon = 0.0001
-        );
-    }
-
     #[test]
     fn signed_angle() {
         let vector0 = nalgebra::vector![1.0_f32, 0.0_f32];
@@ -1497,171 +905,4 @@ mod tests {
         assert_relative_eq!(vector1_angle - vector0_angle, FRAC_PI_2);
         assert_relative_eq!(vector0_angle - vector1_angle, -FRAC_PI_2);
     }
-
-    #[test]
-    fn fitting_line_results_in_zero_measurement() {
-        let ground_to_field = Isometry2::identity();
-        let field_mark_correspondence = FieldMarkCorrespondence {
-            measured_line_in_field: LineSegment(point![0.0, 0.0], point![0.0, 1.0]),
-            field_mark: FieldMark::Line {
-                line: LineSegment(point![0.0, -3.0], point![0.0, 3.0]),
-                direction: Direction::PositiveY,
-            },
-            correspondence_points: (
-                CorrespondencePoints {
-                    measured: Point2::origin(),
-                    reference: Point2::origin(),
-                },
-                CorrespondencePoints {
-                    measured: Point2::origin(),
-                    reference: Point2::origin(),
-                },
-            ),
-        };
-        let update =
-            get_translation_and_rotation_measurement(ground_to_field, field_mark_correspondence);
-        assert_relative_eq!(update, Vector2::zeros());
-
-        let field_mark_correspondence = FieldMarkCorrespondence {
-            measured_line_in_field: LineSegment(point![0.0, 1.0], point![0.0, 0.0]),
-            field_mark: FieldMark::Line {
-                line: LineSegment(point![0.0, -3.0], point![0.0, 3.0]),
-                direction: Direction::PositiveY,
-            },
-            correspondence_points: (
-                CorrespondencePoints {
-                    measured: Point2::origin(),
-                    reference: Point2::origin(),
-                },
-                CorrespondencePoints {
-                    measured: Point2::origin(),
-                    reference: Point2::origin(),
-                },
-            ),
-        };
-        let update =
-            get_translation_and_rotation_measurement(ground_to_field, field_mark_correspondence);
-        assert_relative_eq!(update, Vector2::zeros());
-
-        let field_mark_correspondence = FieldMarkCorrespondence {
-            measured_line_in_field: LineSegment(point![0.0, 0.0], point![1.0, 0.0]),
-            field_mark: FieldMark::Line {
-                line: LineSegment(point![-3.0, 0.0], point![3.0, 0.0]),
-                direction: Direction::PositiveX,
-            },
-            correspondence_points: (
-                CorrespondencePoints {
-                    measured: Point2::origin(),
-                    reference: Point2::origin(),
-                },
-                CorrespondencePoints {
-                    measured: Point2::origin(),
-                    reference: Point2::origin(),
-                },
-            ),
-        };
-        let update =
-            get_translation_and_rotation_measurement(ground_to_field, field_mark_correspondence);
-        assert_relative_eq!(update, Vector2::zeros());
-    }
-
-    #[test]
-    fn translated_line_results_in_translation_measurement() {
-        let ground_to_field = Isometry2::identity();
-        let field_mark_correspondence = FieldMarkCorrespondence {
-            measured_line_in_field: LineSegment(point![1.0, 0.0], point![1.0, 1.0]),
-            field_mark: FieldMark::Line {
-                line: LineSegment(point![0.0, -3.0], point![0.0, 3.0]),
-                direction: Direction::PositiveY,
-            },
-            correspondence_points: (
-                CorrespondencePoints {
-                    measured: Point2::origin(),
-                    reference: Point2::origin(),
-                },
-                CorrespondencePoints {
-                    measured: Point2::origin(),
-                    reference: Point2::origin(),
-                },
-            ),
-        };
-        let update =
-            get_translation_and_rotation_measurement(ground_to_field, field_mark_correspondence);
-        assert_relative_eq!(update, nalgebra::vector![-1.0, 0.0]);
-    }
-
-    #[test]
-    fn rotated_line_results_in_rotation_measurement() {
-        let ground_to_field = Isometry2::identity();
-        let field_mark_correspondence = FieldMarkCorrespondence {
-            measured_line_in_field: LineSegment(point![-1.0, -1.0], point![1.0, 1.0]),
-            field_mark: FieldMark::Line {
-                line: LineSegment(point![0.0, -3.0], point![0.0, 3.0]),
-                direction: Direction::PositiveY,
-            },
-            correspondence_points: (
-                CorrespondencePoints {
-                    measured: Point2::origin(),
-                    reference: Point2::origin(),
-                },
-                CorrespondencePoints {
-                    measured: Point2::origin(),
-                    reference: Point2::origin(),
-                },
-            ),
-        };
-        let update =
-            get_translation_and_rotation_measurement(ground_to_field, field_mark_correspondence);
-        assert_relative_eq!(update, nalgebra::vector![0.0, FRAC_PI_4]);
-    }
-
-    #[test]
-    fn correct_correspondence_points() {
-        let line_length_acceptance_factor = 1.5;
-
-        let measured_lines_in_field = [LineSegment(point![0.0, 0.0], point![1.0, 0.0])];
-        let field_marks = [FieldMark::Line {
-            line: LineSegment(point![0.0, 0.0], point![1.0, 0.0]),
-            direction: Direction::PositiveX,
-        }];
-        let correspondences = get_field_mark_correspondence(
-            &measured_lines_in_field,
-            nalgebra::Isometry2::identity(),
-            &field_marks,
-            line_length_acceptance_factor,
-        );
-        assert_eq!(correspondences.len(), 1);
-        assert_relative_eq!(
-            correspondences[0].correspondence_points.0.measured,
-            point![0.0, 0.0]
-        );
-        assert_relative_eq!(
-            correspondences[0].correspondence_points.0.reference,
-            point![0.0, 0.0]
-        );
-    }
-
-    #[test]
-    fn circle_mark_correspondence_translates() {
-        let ground_to_field = Isometry2::identity();
-        let field_mark_correspondence = FieldMarkCorrespondence {
-            measured_line_in_field: LineSegment(Point2::origin(), Point2::origin()),
-            field_mark: FieldMark::Circle {
-                center: Point2::origin(),
-                radius: 0.0,
-            },
-            correspondence_points: (
-                CorrespondencePoints {
-                    measured: point![0.0, 1.0],
-                    reference: point![0.0, 0.0],
-                },
-                CorrespondencePoints {
-                    measured: point![1.0, 1.0],
-                    reference: point![1.0, 0.0],
-                },
-            ),
-        };
-        let update = get_2d_translation_measurement(ground_to_field, field_mark_correspondence);
-        assert_relative_eq!(update, nalgebra::vector![0.0, -1.0], epsilon = 0.0001);
-    }
 }
@@ -3,7 +3,7 @@ use std::time::Duration;
 use color_eyre::Result;
 use geometry::rectangle::Rectangle;
 use itertools::{chain, iproduct};
-use nalgebra::Matrix2;
+use nalgebra::{Matrix2, Matrix2x4, Matrix4, Matrix4x2, Vector4};
 use projection::{Projection, camera_matrix::CameraMatrix};
 use ros_z::time::Time;
 use serde::{Deserialize, Serialize};
@@ -203,10 +203,11 @@ impl ObstacleFilter {
                     _ => panic!("Unexpected obstacle radius"),
                 };
                 Obstacle {
-                    position: hypothesis.state.mean.framed().as_point(),
+                    position: hypothesis.position().framed().as_point(),
                     kind: hypothesis.obstacle_kind,
                     radius_at_hip_height,
                     radius_at_foot_height,
+                    velocity: hypothesis.velocity().framed(),
                 }
             })
             .collect::<Vec<_>>();
@@ -231,16 +232,22 @@ impl ObstacleFilter {
         process_noise: Matrix2<f32>,
     ) {
         for hypothesis in self.hypotheses.iter_mut() {
-            let state_prediction = last_odometry_to_current_odometry
+            let rotation = *last_odometry_to_current_odometry
                 .rotation
-                .to_rotation_matrix();
-            let control_input_model = Matrix2::identity();
+                .to_rotation_matrix()
+                .matrix();
+            // this filter does not estimate velocities, they stay zero
+            let mut state_prediction = Matrix4::identity();
+            state_prediction
+                .fixed_view_mut::<2, 2>(0, 0)
+                .copy_from(&rotation);
+            let control_input_model = Matrix4x2::identity();
             let odometry_translation = last_odometry_to_current_odometry.translation.vector;
             hypothesis.state.predict(
-                *state_prediction.matrix(),
+                state_prediction,
                 control_input_model,
                 odometry_translation,
-                process_noise,
+                control_input_model * process_noise * control_input_model.transpose(),
             )
         }
     }
@@ -258,7 +265,7 @@ impl ObstacleFilter {
             .hypotheses
             .iter_mut()
             .filter(|hypothesis| {
-                (hypothesis.state.mean - detected_position.inner.coords).norm() < matching_distance
+                (hypothesis.position() - detected_position.inner.coords).norm() < matching_distance
             })
             .peekable();
         if matching_hypotheses.peek().is_none() {
@@ -272,7 +279,7 @@ impl ObstacleFilter {
         }
         matching_hypotheses.for_each(|hypothesis| {
             hypothesis.state.update(
-                Matrix2::identity(),
+                Matrix2x4::identity(),
                 detected_position.inner.coords,
                 if kind == MeasurementKind::NetworkRobot {
                     measurement_noise
@@ -299,11 +306,13 @@ impl ObstacleFilter {
         detection_time: Time,
         initial_covariance: Matrix2<f32>,
     ) {
-        let initial_state = detected_position.inner.coords;
+        let initial_state = Vector4::new(detected_position.x(), detected_position.y(), 0.0, 0.0);
         let new_hypothesis = Hypothesis {
             state: MultivariateNormalDistribution {
                 mean: initial_state,
-                covariance: initial_covariance,
+                covariance: Matrix4x2::identity()
+                    * initial_covariance
+                    * Matrix4x2::identity().transpose(),
             },
             obstacle_kind,
             measurement_count: 1,
@@ -321,15 +330,15 @@ impl ObstacleFilter {
                 deduplicated_hypotheses
                     .iter_mut()
                     .find(|existing_hypothesis| {
-                        (existing_hypothesis.state.mean - hypothesis.state.mean).norm()
+                        (existing_hypothesis.position() - hypothesis.position()).norm()
                             < merge_distance
                     });
             match hypothesis_in_merge_distance {
                 Some(existing_hypothesis) => {
                     existing_hypothesis.state.update(
-                        Matrix2::identity(),
-                        hypothesis.state.mean,
-                        hypothesis.state.covariance,
+                        Matrix2x4::identity(),
+                        hypothesis.position(),
+                        hypothesis.position_covariance(),
                     );
                     existing_hypothesis.obstacle_kind = match existing_hypothesis.obstacle_kind {
                         ObstacleKind::Robot | ObstacleKind::GoalPost | ObstacleKind::Person => {
@@ -97,6 +97,7 @@ impl PlayerStatesReceiver {
                         }),
                     });
                 }
+                HulkMessage::VisualReferee(_) => {}
             }
         }
         self.last_player_states = player_states;
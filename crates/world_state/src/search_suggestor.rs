@@ -257,6 +257,7 @@ impl Heatmap {
                     last_seen: Time::from_wallclock(time) - ball.age,
                 })
             }
+            HulkMessage::VisualReferee(_) => None,
         };
         if let Some(ball_position) = ball {
             self[ball_position.position] = team_ball_weight;
@@ -0,0 +1,77 @@
+import argparse
+import json
+import math
+from pathlib import Path
+
+import joblib
+from segment_features import SEGMENT_FEATURES
+
+# uv run export_decision_tree.py best_models/segment_DT.joblib \
+# --output ../../etc/field_color/field_color_tree.json
+
+
+def sigmoid(value: float) -> float:
+    return 1.0 / (1.0 + math.exp(-value))
+
+
+def export_nodes(tree: dict, features: list[str]) -> list[dict]:
+    nodes = []
+
+    def visit(node: dict) -> int:
+        index = len(nodes)
+        nodes.append(None)
+        if "leaf_value" in node:
+            # a single boosting round with learning rate 1.0 predicts the
+            # raw log-odds of the field class
+            nodes[index] = {
+                "Leaf": {"field_probability": sigmoid(node["leaf_value"])}
+            }
+            return index
+        if node["decision_type"] != "<=":
+            raise ValueError(
+                f"unsupported decision type {node['decision_type']}"
+            )
+        left = visit(node["left_child"])
+        right = visit(node["right_child"])
+        nodes[index] = {
+            "Split": {
+                "feature": features[node["split_feature"]],
+                "threshold": node["threshold"],
+                "left": left,
+                "right": right,
+            }
+        }
+        return index
+
+    visit(tree)
+    return nodes
+
+
+if __name__ == "__main__":
+    parser = argparse.ArgumentParser(
+        description="Export a single-tree LightGBM field color classifier "
+        "trained with `train_decision_tree.py --features segment` for the "
+        "image_segmenter"
+    )
+    parser.add_argument("model", type=Path, help="Path to the joblib model")
+    parser.add_argument("--output", type=Path, required=True)
+
+    args = parser.parse_args()
+
+    model = joblib.load(args.model)
+    dump = model.booster_.dump_model()
+    if len(dump["tree_info"]) != 1:
+        raise ValueError("only models with a single tree can be exported")
+    features = dump["feature_names"]
+    unknown_features = [
+        feature for feature in features if feature not in SEGMENT_FEATURES
+    ]
+    if unknown_features:
+        raise ValueError(
+            f"model uses features {unknown_features} the robot does not compute, "
+            "train it with `--features segment`"
+        )
+
+    nodes = export_nodes(dump["tree_info"][0]["tree_structure"], features)
+    args.output.write_text(json.dumps(nodes, indent=2) + "\n")
+    print(f"exported {len(nodes)} nodes to {args.output}")
@@ -0,0 +1,83 @@
+from __future__ import annotations
+
+from typing import TYPE_CHECKING
+
+if TYPE_CHECKING:
+    import numpy as np
+
+# Features the robot computes from the average color of an image segment, in the
+# order and with the conversions of `types::field_color::FieldColorFeatures`.
+SEGMENT_FEATURES = [
+    "Luminance",
+    "Cb",
+    "Cr",
+    "RedChromaticity",
+    "GreenChromaticity",
+    "BlueChromaticity",
+    "Hue",
+    "Saturation",
+]
+
+HUE_DEGREE = 512
+
+
+def segment_features(rgb: np.ndarray) -> np.ndarray:
+    """Computes all `SEGMENT_FEATURES` of RGB colors with shape (n, 3)."""
+    # imported here so that `SEGMENT_FEATURES` is available without numpy
+    import numpy as np
+
+    rgb = rgb.astype(np.int32)
+    red, green, blue = rgb[:, 0], rgb[:, 1], rgb[:, 2]
+
+    luminance = np.clip(0.299 * red + 0.587 * green + 0.114 * blue, 0, 255)
+    cb = np.clip(128 - 0.168736 * red - 0.331264 * green + 0.5 * blue, 0, 255)
+    cr = np.clip(128 + 0.5 * red - 0.418688 * green - 0.081312 * blue, 0, 255)
+
+    total = (red + green + blue).astype(np.float32)
+    safe_total = np.where(total == 0, 1.0, total)
+    red_chromaticity = np.where(total == 0, 0.0, red / safe_total)
+    green_chromaticity = np.where(total == 0, 0.0, green / safe_total)
+    blue_chromaticity = 1.0 - red_chromaticity - green_chromaticity
+
+    maximum = rgb.max(axis=1)
+    minimum = rgb.min(axis=1)
+    delta = maximum - minimum
+    safe_delta = np.where(delta == 0, 1, delta)
+    hue = np.select(
+        [red == maximum, green == maximum],
+        [
+            _truncating_division((green - blue) * 60 * HUE_DEGREE, safe_delta),
+            _truncating_division((blue - red) * 60 * HUE_DEGREE, safe_delta)
+            + 120 * HUE_DEGREE,
+        ],
+        _truncating_division((red - green) * 60 * HUE_DEGREE, safe_delta)
+        + 240 * HUE_DEGREE,
+    )
+    hue = np.where(hue < 0, hue + 360 * HUE_DEGREE, hue)
+    hue = np.where(delta == 0, 0, _truncating_division(hue, HUE_DEGREE))
+    saturation = np.where(
+        delta == 0,
+        0,
+        _truncating_division(256 * delta - 8, np.where(maximum == 0, 1, maximum)),
+    )
+
+    return np.stack(
+        [
+            luminance.astype(np.uint8),
+            cb.astype(np.uint8),
+            cr.astype(np.uint8),
+            red_chromaticity,
+            green_chromaticity,
+            blue_chromaticity,
+            hue,
+            saturation.astype(np.uint8),
+        ],
+        axis=-1,
+    ).astype(np.float32)
+
+
+def _truncating_division(dividend: np.ndarray, divisor: np.ndarray) -> np.ndarray:
+    # Rust rounds integer divisions towards zero
+    import numpy as np
+
+    return np.trunc(dividend / divisor).astype(np.int32)
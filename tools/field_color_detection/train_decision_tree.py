@@ -14,6 +14,7 @@ from field_color_detection import (
 )
 from field_color_detection.neighboring_pixels import NeighboringPixels
 from lightgbm import LGBMClassifier
+from segment_features import SEGMENT_FEATURES, segment_features
 
 RADIUS = 28
 ORIENTATIONS = 4
@@ -37,6 +38,9 @@ MIN_CHILD_SAMPLES = 5
 #         /home/franziska-sophie/image-segmentation/dataset/dataset_fieldCenter.hdf5 \
 #         /home/franziska-sophie/image-segmentation/dataset/dataset_fieldCenter.hdf5 \
 # --model-file-name "new_DT"
+#
+# Only models trained with `--features segment` can be exported for the robot
+# with export_decision_tree.py, the robot has no texture features.
 
 
 def print_duration(start: float, end: float, process: str = "") -> None:
@@ -62,6 +66,13 @@ if __name__ == "__main__":
         required=True,
         help="Name of the joblib file",
     )
+    parser.add_argument(
+        "--features",
+        choices=["pixel-texture", "segment"],
+        default="pixel-texture",
+        help="Train on the green channel with texture features or on the "
+        "color features the robot computes for image segments",
+    )
 
     args = parser.parse_args()
 
@@ -70,16 +81,33 @@ if __name__ == "__main__":
     end = time.time()
     print_duration(start, end, "Reading hdf5 files")
 
-    X = X[:, FeatureIndices.g.value]
-    start = time.time()
-    extractor = NeighboringPixels(radius=RADIUS, orientations=ORIENTATIONS)
-    texture_features = extractor.get_features(
-        np.reshape(X, (-1, HEIGHT, WIDTH, 1)), 0
-    )
-    end = time.time()
-    print_duration(start, end, "Texture Feature Extraction")
+    if args.features == "segment":
+        X = segment_features(
+            X[
+                :,
+                [
+                    FeatureIndices.r.value,
+                    FeatureIndices.g.value,
+                    FeatureIndices.b.value,
+                ],
+            ]
+        )
+        feature_names = SEGMENT_FEATURES
+    else:
+        X = X[:, FeatureIndices.g.value]
+        start = time.time()
+        extractor = NeighboringPixels(radius=RADIUS, orientations=ORIENTATIONS)
+        texture_features = extractor.get_features(
+            np.reshape(X, (-1, HEIGHT, WIDTH, 1)), 0
+        )
+        end = time.time()
+        print_duration(start, end, "Texture Feature Extraction")
+
+        X = np.concatenate((X[:, np.newaxis], texture_features), axis=-1)
+        feature_names = ["Green"] + [
+            f"Texture{index}" for index in range(texture_features.shape[-1])
+        ]
 
-    X = np.concatenate((X[:, np.newaxis], texture_features), axis=-1)
     X_binary = X[y != Classes.UNKNOWN.value]
     y_binary = y[y != Classes.UNKNOWN.value]
 
@@ -95,7 +123,7 @@ if __name__ == "__main__":
     )
 
     start = time.time()
-    model = classifier.fit(X_binary, y_binary)
+    model = classifier.fit(X_binary, y_binary, feature_name=feature_names)
     end = time.time()
     print_duration(start, end, "Model Training")
 
@@ -0,0 +1,232 @@
+import argparse
+import json
+import math
+import re
+from dataclasses import dataclass
+from pathlib import Path
+
+from segment_features import HUE_DEGREE, SEGMENT_FEATURES
+
+# Trains the field color tree the robot ships with until one trained on recorded
+# segments with `train_decision_tree.py --features segment` replaces it. The
+# samples are the YCbCr color cube, labelled by the tuned `field_color_detection`
+# ranges of the image_segmenter. Runs without third-party packages:
+#
+# python3 train_from_field_color_ranges.py \
+# --parameters ../../etc/parameters/ros_z/base/image_segmenter.json5 \
+# --output ../../etc/field_color/field_color_tree.json
+
+
+def load_json5(path: Path) -> dict:
+    """Parses the subset of JSON5 used by the parameter files: unquoted keys
+    and trailing commas, no comments."""
+    text = path.read_text()
+    text = re.sub(r"([{,]\s*)([A-Za-z_][A-Za-z0-9_]*)\s*:", r'\1"\2":', text)
+    text = re.sub(r",(\s*[}\]])", r"\1", text)
+    return json.loads(text)
+
+
+def ycbcr_to_rgb(y: int, cb: int, cr: int) -> tuple[int, int, int]:
+    # `impl From<YCbCr444> for Rgb`, `f32::round` rounds halves away from zero
+    # and `as u8` saturates
+    def saturate(value: float) -> int:
+        return min(max(math.floor(value + 0.5), 0), 255)
+
+    centered_cb = cb - 128.0
+    centered_cr = cr - 128.0
+    return (
+        saturate(y + 1.40200 * centered_cr),
+        saturate(y - 0.34414 * centered_cb - 0.71414 * centered_cr),
+        saturate(y + 1.77200 * centered_cb),
+    )
+
+
+def truncating_division(dividend: int, divisor: int) -> int:
+    # Rust rounds integer divisions towards zero
+    quotient = abs(dividend) // abs(divisor)
+    return quotient if (dividend >= 0) == (divisor > 0) else -quotient
+
+
+def features(y: int, cb: int, cr: int) -> list[float]:
+    """Computes `SEGMENT_FEATURES` like `types::field_color::FieldColorFeatures`."""
+    red, green, blue = ycbcr_to_rgb(y, cb, cr)
+    total = red + green + blue
+    red_chromaticity = red / total if total else 0.0
+    green_chromaticity = green / total if total else 0.0
+
+    maximum = max(red, green, blue)
+    delta = maximum - min(red, green, blue)
+    if delta == 0:
+        hue, saturation = 0, 0
+    else:
+        if red == maximum:
+            hue = truncating_division((green - blue) * 60 * HUE_DEGREE, delta)
+        elif green == maximum:
+            hue = truncating_division(
+                (blue - red) * 60 * HUE_DEGREE, delta
+            ) + 120 * HUE_DEGREE
+        else:
+            hue = truncating_division(
+                (red - green) * 60 * HUE_DEGREE, delta
+            ) + 240 * HUE_DEGREE
+        if hue < 0:
+            hue += 360 * HUE_DEGREE
+        hue = truncating_division(hue, HUE_DEGREE)
+        saturation = truncating_division(256 * delta - 8, maximum)
+
+    return [
+        float(y),
+        float(cb),
+        float(cr),
+        red_chromaticity,
+        green_chromaticity,
+        1.0 - red_chromaticity - green_chromaticity,
+        float(hue),
+        float(saturation),
+    ]
+
+
+def is_field(y: int, feature_values: list[float], ranges: dict) -> bool:
+    """Mirrors `FieldColorDetection for FieldColorParameters`."""
+    named = dict(zip(SEGMENT_FEATURES, feature_values))
+
+    def contains(name: str, value: float) -> bool:
+        return ranges[name]["start"] <= value <= ranges[name]["end"]
+
+    return (
+        contains("luminance", y)
+        and contains("green_luminance", y)
+        and contains("red_chromaticity", named["RedChromaticity"])
+        and contains("green_chromaticity", named["GreenChromaticity"])
+        and contains("blue_chromaticity", named["BlueChromaticity"])
+        and contains("hue", named["Hue"])
+        and contains("saturation", named["Saturation"])
+    )
+
+
+@dataclass
+class Split:
+    feature: int
+    threshold: float
+    impurity: float
+
+
+def gini(field: int, total: int) -> float:
+    probability = field / total
+    return 2.0 * probability * (1.0 - probability) * total
+
+
+def best_split(
+    samples: list[tuple[list[float], bool]], minimum_samples: int
+) -> Split | None:
+    total = len(samples)
+    total_field = sum(label for _, label in samples)
+    best = None
+    for feature in range(len(SEGMENT_FEATURES)):
+        ordered = sorted(samples, key=lambda sample: sample[0][feature])
+        left_field = 0
+        for index in range(total - 1):
+            left_field += ordered[index][1]
+            value = ordered[index][0][feature]
+            next_value = ordered[index + 1][0][feature]
+            left = index + 1
+            if (
+                value == next_value
+                or left < minimum_samples
+                or total - left < minimum_samples
+            ):
+                continue
+            impurity = gini(left_field, left) + gini(
+                total_field - left_field, total - left
+            )
+            if best is None or impurity < best.impurity:
+                best = Split(feature, (value + next_value) / 2.0, impurity)
+    return best
+
+
+def train(
+    samples: list[tuple[list[float], bool]],
+    maximum_depth: int,
+    minimum_samples: int,
+) -> list[dict]:
+    nodes = []
+
+    def visit(samples: list[tuple[list[float], bool]], depth: int) -> int:
+        index = len(nodes)
+        nodes.append(None)
+        field = sum(label for _, label in samples)
+        split = None
+        if depth < maximum_depth and 0 < field < len(samples):
+            split = best_split(samples, minimum_samples)
+        if split is None or split.impurity >= gini(field, len(samples)):
+            nodes[index] = {"Leaf": {"field_probability": field / len(samples)}}
+            return index
+        left = visit(
+            [s for s in samples if s[0][split.feature] <= split.threshold],
+            depth + 1,
+        )
+        right = visit(
+            [s for s in samples if s[0][split.feature] > split.threshold],
+            depth + 1,
+        )
+        nodes[index] = {
+            "Split": {
+                "feature": SEGMENT_FEATURES[split.feature],
+                "threshold": split.threshold,
+                "left": left,
+                "right": right,
+            }
+        }
+        return index
+
+    visit(samples, 0)
+    return nodes
+
+
+def field_probability(nodes: list[dict], feature_values: list[float]) -> float:
+    node = nodes[0]
+    while "Split" in node:
+        split = node["Split"]
+        value = feature_values[SEGMENT_FEATURES.index(split["feature"])]
+        node = nodes[split["left"] if value <= split["threshold"] else split["right"]]
+    return node["Leaf"]["field_probability"]
+
+
+if __name__ == "__main__":
+    parser = argparse.ArgumentParser(
+        description="Train the field color tree of the image_segmenter on colors "
+        "labelled by its tuned field color ranges"
+    )
+    parser.add_argument("--parameters", type=Path, required=True)
+    parser.add_argument("--output", type=Path, required=True)
+    parser.add_argument("--stride", type=int, default=4)
+    parser.add_argument("--maximum-depth", type=int, default=8)
+    parser.add_argument("--minimum-samples", type=int, default=20)
+
+    args = parser.parse_args()
+
+    ranges = load_json5(args.parameters)["field_color_detection"]
+    samples = []
+    for y in range(0, 256, args.stride):
+        for cb in range(0, 256, args.stride):
+            for cr in range(0, 256, args.stride):
+                feature_values = features(y, cb, cr)
+                samples.append((feature_values, is_field(y, feature_values, ranges)))
+
+    nodes = train(samples, args.maximum_depth, args.minimum_samples)
+    agreement = sum(
+        (field_probability(nodes, feature_values) >= 0.5) == label
+        for feature_values, label in samples
+    ) / len(samples)
+    field = [feature_values for feature_values, label in samples if label]
+    recall = sum(
+        field_probability(nodes, feature_values) >= 0.5 for feature_values in field
+    ) / len(field)
+
+    args.output.parent.mkdir(parents=True, exist_ok=True)
+    args.output.write_text(json.dumps(nodes, indent=2) + "\n")
+    print(
+        f"trained {len(nodes)} nodes on {len(samples)} colors "
+        f"({len(field)} field), agreement {agreement:.4f}, field recall "
+        f"{recall:.4f}, written to {args.output}"
+    )
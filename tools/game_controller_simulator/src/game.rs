@@ -0,0 +1,163 @@
+use std::time::Duration;
+
+use hsl_network_messages::{
+    CompetitionType, GameControllerStateMessage, GamePhase, GameState, HULKS_TEAM_NUMBER, Half,
+    Player, PlayerNumber, Team, TeamColor, TeamState,
+};
+
+use crate::script::{Event, Script};
+
+/// Game as tracked by the simulated GameController.
+///
+/// The game clock only runs while playing. Penalties last until the script unpenalizes the
+/// player.
+pub struct Game {
+    message: GameControllerStateMessage,
+    half_duration: Duration,
+    players_per_team: u8,
+    packet_number: u8,
+}
+
+impl Game {
+    pub fn new(script: &Script) -> Self {
+        let team = |team_number, field_player_color| TeamState {
+            team_number,
+            field_player_color,
+            goal_keeper_color: TeamColor::Gray,
+            goal_keeper_player_number: Some(PlayerNumber::One),
+            score: 0,
+            penalty_shoot_index: 0,
+            penalty_shoots: Vec::new(),
+            remaining_amount_of_messages: script.message_budget,
+            players: vec![
+                Player {
+                    penalty: None,
+                    caution: 0,
+                };
+                script.players_per_team.into()
+            ],
+        };
+        Self {
+            message: GameControllerStateMessage {
+                competition_type: CompetitionType::Small,
+                stopped: false,
+                game_phase: GamePhase::Normal,
+                game_state: GameState::Initial,
+                sub_state: None,
+                half: Half::First,
+                remaining_time_in_half: script.half_duration,
+                secondary_time: Duration::ZERO,
+                hulks_team: team(HULKS_TEAM_NUMBER, TeamColor::Blue),
+                opponent_team: team(script.opponent_team_number, TeamColor::Red),
+                kicking_team: None,
+                hulks_team_is_home_after_coin_toss: true,
+            },
+            half_duration: script.half_duration,
+            players_per_team: script.players_per_team,
+            packet_number: 0,
+        }
+    }
+
+    pub fn message(&self) -> &GameControllerStateMessage {
+        &self.message
+    }
+
+    pub fn apply(&mut self, event: Event) {
+        match event {
+            Event::GameState(game_state) => {
+                self.message.game_state = game_state;
+            }
+            Event::KickOff { kicking_team } => {
+                self.message.kicking_team = Some(kicking_team);
+            }
+            Event::SetPlay {
+                sub_state,
+                kicking_team,
+            } => {
+                self.message.sub_state = Some(sub_state);
+                self.message.kicking_team = Some(kicking_team);
+            }
+            Event::EndSetPlay => {
+                self.message.sub_state = None;
+            }
+            Event::Penalize {
+                team,
+                player_number,
+                penalty,
+            } => {
+                if let Some(player) = self.player(team, player_number) {
+                    player.penalty = Some(penalty);
+                }
+            }
+            Event::Unpenalize {
+                team,
+                player_number,
+            } => {
+                if let Some(player) = self.player(team, player_number) {
+                    player.penalty = None;
+                }
+            }
+            Event::Goal { team } => {
+                self.team(team).score += 1;
+                self.message.game_state = GameState::Ready;
+                self.message.sub_state = None;
+                self.message.kicking_team = Some(match team {
+                    Team::Hulks => Team::Opponent,
+                    Team::Opponent => Team::Hulks,
+                });
+            }
+            Event::Stop => {
+                self.message.stopped = true;
+            }
+            Event::Resume => {
+                self.message.stopped = false;
+            }
+            Event::SecondHalf => {
+                self.message.half = Half::Second;
+                self.message.game_state = GameState::Initial;
+                self.message.sub_state = None;
+                self.message.remaining_time_in_half = self.half_duration;
+            }
+        }
+    }
+
+    pub fn advance(&mut self, elapsed: Duration) {
+        if self.message.game_state == GameState::Playing && !self.message.stopped {
+            self.message.remaining_time_in_half =
+                self.message.remaining_time_in_half.saturating_sub(elapsed);
+        }
+        self.message.secondary_time = self.message.secondary_time.saturating_sub(elapsed);
+    }
+
+    /// Spends one message of the HULKs message budget.
+    pub fn record_team_message(&mut self) {
+        let remaining_messages = &mut self.message.hulks_team.remaining_amount_of_messages;
+        *remaining_messages = remaining_messages.saturating_sub(1);
+    }
+
+    pub fn next_packet(&mut self) -> Vec<u8> {
+        let packet = self
+            .message
+            .to_packet(self.packet_number, self.players_per_team);
+        self.packet_number = self.packet_number.wrapping_add(1);
+        packet
+    }
+
+    fn team(&mut self, team: Team) -> &mut TeamState {
+        match team {
+            Team::Hulks => &mut self.message.hulks_team,
+            Team::Opponent => &mut self.message.opponent_team,
+        }
+    }
+
+    fn player(&mut self, team: Team, player_number: PlayerNumber) -> Option<&mut Player> {
+        let index = match player_number {
+            PlayerNumber::One => 0,
+            PlayerNumber::Two => 1,
+            PlayerNumber::Three => 2,
+            PlayerNumber::Four => 3,
+            PlayerNumber::Five => 4,
+        };
+        self.team(team).players.get_mut(index)
+    }
+}
@@ -0,0 +1,89 @@
+use std::{net::SocketAddr, path::PathBuf, time::Duration};
+
+use clap::Parser;
+use color_eyre::{Result, eyre::WrapErr};
+use hsl_network_messages::HulkMessage;
+use log::{debug, info};
+use tokio::{
+    select,
+    time::{Instant, interval},
+};
+
+use game_controller_simulator::{
+    game::Game,
+    script::Script,
+    socket::{GameControllerSockets, Received},
+};
+
+#[derive(Parser, Debug)]
+#[clap(author, version, about, long_about = None)]
+struct CommandlineArguments {
+    /// Script of referee decisions to play back
+    script: PathBuf,
+    /// Destination of the state messages
+    #[clap(long, default_value = "255.255.255.255:3838")]
+    state_destination: SocketAddr,
+    #[clap(long, default_value_t = 3939)]
+    return_port: u16,
+    /// Port of the team messages counted against the message budget
+    #[clap(long)]
+    team_port: Option<u16>,
+    #[clap(long, default_value_t = 500)]
+    send_interval_milliseconds: u64,
+    /// Overrides the version field of sent state messages to test the rejection by robots
+    #[clap(long)]
+    protocol_version: Option<u8>,
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    color_eyre::install()?;
+    env_logger::init();
+
+    let arguments = CommandlineArguments::parse();
+    let script = Script::load(&arguments.script)?;
+    let sockets = GameControllerSockets::bind(arguments.return_port, arguments.team_port)
+        .await
+        .wrap_err("failed to bind sockets")?;
+
+    let mut game = Game::new(&script);
+    let mut steps = script.steps.iter().peekable();
+    let start = Instant::now();
+    let mut last_update = start;
+    let mut send_interval = interval(Duration::from_millis(arguments.send_interval_milliseconds));
+
+    loop {
+        select! {
+            _ = send_interval.tick() => {
+                let now = Instant::now();
+                game.advance(now - last_update);
+                last_update = now;
+                while let Some(step) = steps.next_if(|step| step.at <= now - start) {
+                    info!("{:?}: {:?}", step.at, step.event);
+                    game.apply(step.event);
+                }
+                let mut packet = game.next_packet();
+                if let Some(protocol_version) = arguments.protocol_version {
+                    packet[4] = protocol_version;
+                }
+                sockets
+                    .send_state(arguments.state_destination, &packet)
+                    .await
+                    .wrap_err("failed to send state message")?;
+            }
+            received = sockets.receive() => {
+                match received.wrap_err("failed to receive")? {
+                    Received::Return { source, message } => {
+                        debug!("Return message from {source}: {message:?}");
+                    }
+                    Received::TeamMessage { source, message } => {
+                        game.record_team_message();
+                        if let HulkMessage::VisualReferee(message) = message {
+                            info!("Visual referee message from {source}: {message:?}");
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
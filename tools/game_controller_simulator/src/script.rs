@@ -0,0 +1,62 @@
+use std::{fs::read_to_string, path::Path, time::Duration};
+
+use color_eyre::{Result, eyre::WrapErr};
+use hsl_network_messages::{GameState, Penalty, PlayerNumber, SubState, Team};
+use serde::Deserialize;
+
+/// Timed sequence of referee decisions played back by the simulated GameController.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Script {
+    pub opponent_team_number: u8,
+    pub players_per_team: u8,
+    pub half_duration: Duration,
+    pub message_budget: u16,
+    pub steps: Vec<Step>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Step {
+    /// Time since the start of the script
+    pub at: Duration,
+    pub event: Event,
+}
+
+#[derive(Clone, Copy, Debug, Deserialize)]
+pub enum Event {
+    GameState(GameState),
+    KickOff {
+        kicking_team: Team,
+    },
+    SetPlay {
+        sub_state: SubState,
+        kicking_team: Team,
+    },
+    EndSetPlay,
+    Penalize {
+        team: Team,
+        player_number: PlayerNumber,
+        penalty: Penalty,
+    },
+    Unpenalize {
+        team: Team,
+        player_number: PlayerNumber,
+    },
+    Goal {
+        team: Team,
+    },
+    Stop,
+    Resume,
+    SecondHalf,
+}
+
+impl Script {
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let content = read_to_string(path)
+            .wrap_err_with(|| format!("failed to read script {}", path.display()))?;
+        json5::from_str(&content)
+            .wrap_err_with(|| format!("failed to parse script {}", path.display()))
+    }
+}
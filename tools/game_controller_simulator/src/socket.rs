@@ -0,0 +1,90 @@
+use std::{
+    future::pending,
+    io,
+    net::{Ipv4Addr, SocketAddr},
+};
+
+use hsl_network_messages::{GameControllerReturnMessage, HulkMessage, codec};
+use log::warn;
+use tokio::{net::UdpSocket, select};
+
+#[derive(Debug)]
+pub enum Received {
+    Return {
+        source: SocketAddr,
+        message: GameControllerReturnMessage,
+    },
+    TeamMessage {
+        source: SocketAddr,
+        message: HulkMessage,
+    },
+}
+
+/// Sockets of the simulated GameController.
+///
+/// Team messages are only received if a team port is given, since the port is usually already
+/// bound by a robot running on the same machine.
+pub struct GameControllerSockets {
+    state_socket: UdpSocket,
+    return_socket: UdpSocket,
+    team_socket: Option<UdpSocket>,
+}
+
+impl GameControllerSockets {
+    pub async fn bind(return_port: u16, team_port: Option<u16>) -> io::Result<Self> {
+        let state_socket = UdpSocket::bind((Ipv4Addr::UNSPECIFIED, 0)).await?;
+        state_socket.set_broadcast(true)?;
+        let return_socket = UdpSocket::bind((Ipv4Addr::UNSPECIFIED, return_port)).await?;
+        let team_socket = match team_port {
+            Some(team_port) => Some(UdpSocket::bind((Ipv4Addr::UNSPECIFIED, team_port)).await?),
+            None => None,
+        };
+        Ok(Self {
+            state_socket,
+            return_socket,
+            team_socket,
+        })
+    }
+
+    pub fn return_address(&self) -> io::Result<SocketAddr> {
+        self.return_socket.local_addr()
+    }
+
+    pub async fn send_state(&self, destination: SocketAddr, packet: &[u8]) -> io::Result<()> {
+        self.state_socket.send_to(packet, destination).await?;
+        Ok(())
+    }
+
+    pub async fn receive(&self) -> io::Result<Received> {
+        loop {
+            let mut return_buffer = [0; 1024];
+            let mut team_buffer = [0; 1024];
+            let team_message = async {
+                match &self.team_socket {
+                    Some(team_socket) => team_socket.recv_from(&mut team_buffer).await,
+                    None => pending().await,
+                }
+            };
+            select! {
+                result = self.return_socket.recv_from(&mut return_buffer) => {
+                    let (received_bytes, source) = result?;
+                    match return_buffer[0..received_bytes].try_into() {
+                        Ok(message) => break Ok(Received::Return { source, message }),
+                        Err(error) => {
+                            warn!("Failed to parse return message from {source} (will be discarded): {error:?}");
+                        }
+                    }
+                },
+                result = team_message => {
+                    let (received_bytes, source) = result?;
+                    match codec::decode(&team_buffer[0..received_bytes]) {
+                        Ok(message) => break Ok(Received::TeamMessage { source, message }),
+                        Err(error) => {
+                            warn!("Failed to parse team message from {source} (will be discarded): {error:?}");
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
@@ -119,6 +119,7 @@ pub fn status_color(status: &Status) -> Color32 {
     match status {
         Status::Success => Color32::CYAN,
         Status::Failure => Color32::RED,
+        Status::Running => Color32::YELLOW,
         Status::Idle => Color32::LIGHT_GRAY,
     }
 }
@@ -32,7 +32,7 @@ impl Layer<Ground> for BallFilter {
         if let Some(filter) = self.filter.get_last_value()?.flatten() {
             for hypothesis in filter.hypotheses {
                 let stroke = Stroke::new(0.01_f32, Color32::BLACK);
-                match hypothesis.mode {
+                match hypothesis.mode() {
                     BallMode::Resting(resting) => {
                         let position = Point::from(resting.mean.xy());
                         let covariance = resting.covariance.fixed_view::<2, 2>(0, 0).into_owned();
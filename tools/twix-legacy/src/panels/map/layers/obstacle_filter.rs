@@ -31,8 +31,8 @@ impl Layer<Ground> for ObstacleFilter {
     ) -> Result<()> {
         if let Some(hypotheses) = self.hypotheses.get_last_value()?.flatten() {
             for hypothesis in hypotheses.iter() {
-                let position = Point2::from(hypothesis.state.mean);
-                let covariance = hypothesis.state.covariance;
+                let position = Point2::from(hypothesis.position());
+                let covariance = hypothesis.position_covariance();
                 let stroke = Stroke::new(0.01_f32, Color32::BLACK);
                 let fill_color = Color32::from_rgba_unmultiplied(255, 255, 0, 20);
                 painter.covariance(position, covariance, stroke, fill_color);
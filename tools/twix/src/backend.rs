@@ -1,15 +1,20 @@
-use std::sync::{Arc, Mutex};
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
 
 use color_eyre::{Result, eyre::Context as _};
 use ros_z::{context::ContextBuilder, prelude::*};
-use ros_z_debug::{TopicObserver, TopicObserverOptions};
+use ros_z_debug::{TargetIdentity, TopicObserver, TopicObserverOptions, TopicReference};
 use tokio::runtime::Handle;
 use uuid::Uuid;
 
+const SUBSCRIBER_DISCOVERY_TIMEOUT: Duration = Duration::from_secs(1);
+
 pub struct RobotBackend {
     runtime_handle: Handle,
     context: Arc<Context>,
-    _node: Arc<Node>,
+    node: Arc<Node>,
     observer: TopicObserver,
     namespace: Mutex<String>,
 }
@@ -49,7 +54,7 @@ impl RobotBackend {
         Ok(Self {
             runtime_handle,
             context,
-            _node: node,
+            node,
             observer,
             namespace: Mutex::new(namespace),
         })
@@ -74,6 +79,30 @@ impl RobotBackend {
             .clone()
     }
 
+    /// Publishes a single message on a topic relative to the target namespace, e.g. a command
+    /// to a node of the robot.
+    pub async fn publish<T>(&self, topic: &str, message: &T) -> Result<()>
+    where
+        T: Message + Send + Sync + 'static,
+    {
+        let resolved_topic = TargetIdentity::new(self.namespace())
+            .and_then(|target| TopicReference::new(topic)?.resolve(&target))
+            .wrap_err_with(|| format!("failed to resolve topic {topic}"))?;
+        let publisher = self
+            .node
+            .publisher::<T>(&resolved_topic)
+            .build()
+            .await
+            .wrap_err_with(|| format!("failed to create publisher for {resolved_topic}"))?;
+        publisher
+            .wait_for_subscribers(1, SUBSCRIBER_DISCOVERY_TIMEOUT)
+            .await;
+        publisher
+            .publish(message)
+            .await
+            .wrap_err_with(|| format!("failed to publish on {resolved_topic}"))
+    }
+
     pub fn set_namespace(&self, namespace: String) -> Result<()> {
         self.observer
             .set_namespace(namespace.clone())
@@ -22,7 +22,7 @@ use egui_dock::{
 use hulk_widgets::CompletionEdit;
 use log::{error, warn};
 use panel::{Panel, PanelCreationContext, PanelUiContext};
-use panels::{ImagePanel, TextPanel};
+use panels::{CameraCalibrationPanel, ImagePanel, KickTargetsPanel, TextPanel};
 use repository::{Repository, inspect_version::check_for_update};
 use serde_json::{Value, from_str, to_string};
 use tracing_subscriber::{EnvFilter, fmt, layer::SubscriberExt, util::SubscriberInitExt};
@@ -40,7 +40,12 @@ mod selectable_panel_macro;
 mod status;
 mod visuals;
 
-impl_selectable_panel!(TextPanel, ImagePanel);
+impl_selectable_panel!(
+    TextPanel,
+    ImagePanel,
+    CameraCalibrationPanel,
+    KickTargetsPanel
+);
 
 fn panel_creation_context<'a>(
     backend: &Arc<RobotBackend>,
@@ -0,0 +1,157 @@
+use std::sync::Arc;
+
+use color_eyre::{Report, eyre::Context as _};
+use eframe::egui::Ui;
+use log::error;
+use ros_z_debug::{TopicObservation, TopicObservationStatus};
+use types::calibration::{CameraCalibrationCommand, CameraCalibrationState};
+
+use crate::{
+    backend::RobotBackend,
+    panel::{Panel, PanelCreationContext, PanelUiContext},
+    repaint::{ObservationContext, ObservationRepaint, RepaintOnUpdates},
+    status::format_topic_observation_status,
+};
+
+const STATE_TOPIC: &str = "camera_calibration/state";
+const COMMAND_TOPIC: &str = "camera_calibration/command";
+
+pub struct CameraCalibrationPanel {
+    observation: ObservationState,
+}
+
+enum ObservationState {
+    Observing(Box<ObservedState>),
+    Error(String),
+}
+
+struct ObservedState {
+    observation: TopicObservation<CameraCalibrationState>,
+    _repaint: ObservationRepaint,
+}
+
+impl Panel for CameraCalibrationPanel {
+    const STORAGE_ID: &'static str = "camera_calibration";
+    const DISPLAY_NAME: &'static str = "Camera Calibration";
+
+    fn new(context: PanelCreationContext<'_>) -> Self {
+        let observation = match create_observation(&context) {
+            Ok((observation, repaint)) => ObservationState::Observing(Box::new(ObservedState {
+                observation,
+                _repaint: repaint,
+            })),
+            Err(error) => ObservationState::Error(format!("{error:#}")),
+        };
+        Self { observation }
+    }
+
+    fn ui(&mut self, ui: &mut Ui, context: PanelUiContext<'_>) {
+        ui.vertical(|ui| {
+            ui.label(
+                "Place the robot at the calibration pose of the camera_calibration parameters \
+                 and let it stand before starting.",
+            );
+            ui.horizontal(|ui| {
+                if ui.button("Start").clicked() {
+                    send_command(context.backend, CameraCalibrationCommand::Start);
+                }
+                if ui.button("Abort").clicked() {
+                    send_command(context.backend, CameraCalibrationCommand::Abort);
+                }
+            });
+            ui.separator();
+
+            match &self.observation {
+                ObservationState::Error(error) => {
+                    ui.colored_label(ui.visuals().error_fg_color, error);
+                }
+                ObservationState::Observing(observed) => {
+                    render_status(ui, observed.observation.status());
+                    match observed.observation.latest() {
+                        Some(sample) => render_state(ui, &sample.value),
+                        None => {
+                            ui.label("Waiting for the camera calibration node.");
+                        }
+                    }
+                }
+            }
+        });
+    }
+}
+
+fn create_observation(
+    context: &impl ObservationContext,
+) -> Result<(TopicObservation<CameraCalibrationState>, ObservationRepaint), Report> {
+    let runtime_handle = context.backend().runtime_handle().clone();
+    // ros_z_debug spawns observation tasks internally and needs a current runtime.
+    let _runtime_context = runtime_handle.enter();
+    let observation = context
+        .backend()
+        .observer()
+        .observe_typed::<CameraCalibrationState>(STATE_TOPIC)
+        .wrap_err("failed to create camera calibration state observation")?
+        .spawn();
+    let repaint = observation.repaint_on_updates(context);
+    Ok((observation, repaint))
+}
+
+fn send_command(backend: &Arc<RobotBackend>, command: CameraCalibrationCommand) {
+    let backend = Arc::clone(backend);
+    backend.runtime_handle().clone().spawn(async move {
+        if let Err(error) = backend.publish(COMMAND_TOPIC, &command).await {
+            error!("failed to send camera calibration command: {error:#}");
+        }
+    });
+}
+
+fn render_status(ui: &mut Ui, status: TopicObservationStatus) {
+    ui.horizontal_wrapped(|ui| {
+        ui.label("status:");
+        ui.monospace(format_topic_observation_status(status));
+    });
+}
+
+fn render_state(ui: &mut Ui, state: &CameraCalibrationState) {
+    match state {
+        CameraCalibrationState::Idle => {
+            ui.label("Idle");
+        }
+        CameraCalibrationState::Capturing {
+            head_angle_index,
+            number_of_head_angles,
+            number_of_measurements,
+        } => {
+            ui.label(format!(
+                "Capturing head angle {} of {number_of_head_angles}",
+                head_angle_index + 1
+            ));
+            ui.label(format!("{number_of_measurements} measurements"));
+        }
+        CameraCalibrationState::Finished {
+            correction_in_robot,
+            correction_in_camera,
+        } => {
+            ui.label("Finished, corrections are stored in the robot parameters:");
+            ui.monospace(format!(
+                "correction_in_robot: [{:.5}, {:.5}, {:.5}]",
+                correction_in_robot.x(),
+                correction_in_robot.y(),
+                correction_in_robot.z()
+            ));
+            ui.monospace(format!(
+                "correction_in_camera: [{:.5}, {:.5}, {:.5}]",
+                correction_in_camera.x(),
+                correction_in_camera.y(),
+                correction_in_camera.z()
+            ));
+        }
+        CameraCalibrationState::Failed {
+            number_of_measurements,
+        } => {
+            ui.colored_label(
+                ui.visuals().error_fg_color,
+                format!("Failed with {number_of_measurements} measurements"),
+            );
+        }
+    }
+}
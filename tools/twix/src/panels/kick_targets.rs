@@ -0,0 +1,267 @@
+use std::sync::Arc;
+
+use color_eyre::{Report, eyre::Context as _};
+use coordinate_systems::Field;
+use eframe::egui::{Color32, Painter, Pos2, Rect, Sense, Stroke, StrokeKind, Ui, vec2};
+use linear_algebra::{Point2, point};
+use ros_z::dynamic::DynamicPayload;
+use ros_z_debug::{DynamicTopicObservation, SampleRecord};
+use serde::Deserialize;
+use serde_json::Value;
+use types::{field_dimensions::FieldDimensions, kick_target::KickTargetCandidate};
+
+use crate::{
+    panel::{Panel, PanelCreationContext, PanelUiContext},
+    repaint::{ObservationContext, ObservationRepaint, RepaintOnUpdates},
+    status::format_topic_observation_status,
+};
+
+const BLACKBOARD_TOPIC: &str = "behavior/blackboard";
+
+pub struct KickTargetsPanel {
+    observation: ObservationState,
+}
+
+enum ObservationState {
+    Observing(Box<ObservedBlackboard>),
+    Error(String),
+}
+
+struct ObservedBlackboard {
+    observation: DynamicTopicObservation,
+    _repaint: ObservationRepaint,
+    sample: Option<Arc<SampleRecord<DynamicPayload>>>,
+    kick_targets: Option<KickTargets>,
+}
+
+/// Part of the behavior blackboard needed to draw the kick target candidates.
+#[derive(Debug, Deserialize)]
+struct KickTargets {
+    field_dimensions: FieldDimensions,
+    kick_target_candidates: Vec<KickTargetCandidate>,
+    ball: Option<Ball>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Ball {
+    position: Point2<Field>,
+}
+
+impl Panel for KickTargetsPanel {
+    const STORAGE_ID: &'static str = "kick_targets";
+    const DISPLAY_NAME: &'static str = "Kick Targets";
+
+    fn new(context: PanelCreationContext<'_>) -> Self {
+        let observation = match create_observation(&context) {
+            Ok((observation, repaint)) => {
+                ObservationState::Observing(Box::new(ObservedBlackboard {
+                    observation,
+                    _repaint: repaint,
+                    sample: None,
+                    kick_targets: None,
+                }))
+            }
+            Err(error) => ObservationState::Error(format!("{error:#}")),
+        };
+        Self { observation }
+    }
+
+    fn ui(&mut self, ui: &mut Ui, _context: PanelUiContext<'_>) {
+        ui.vertical(|ui| match &mut self.observation {
+            ObservationState::Error(error) => {
+                ui.colored_label(ui.visuals().error_fg_color, error);
+            }
+            ObservationState::Observing(observed) => {
+                ui.horizontal_wrapped(|ui| {
+                    ui.label("status:");
+                    ui.monospace(format_topic_observation_status(
+                        observed.observation.status(),
+                    ));
+                });
+                observed.refresh();
+
+                let Some(kick_targets) = &observed.kick_targets else {
+                    ui.label("Waiting for first blackboard.");
+                    return;
+                };
+                if let Some(best) = kick_targets.kick_target_candidates.first() {
+                    ui.label(format!(
+                        "best: {:?} with {:?}, score {:.2}",
+                        best.kind, best.kick_power, best.score
+                    ));
+                }
+                let (response, painter) = ui.allocate_painter(ui.available_size(), Sense::hover());
+                FieldPainter::new(painter, response.rect, &kick_targets.field_dimensions)
+                    .paint(kick_targets);
+            }
+        });
+    }
+}
+
+impl ObservedBlackboard {
+    fn refresh(&mut self) {
+        let sample = self.observation.latest();
+        let unchanged = match (&self.sample, &sample) {
+            (Some(current), Some(next)) => Arc::ptr_eq(current, next),
+            (None, None) => true,
+            _ => false,
+        };
+        if unchanged {
+            return;
+        }
+        self.sample = sample;
+        self.kick_targets = self
+            .observation
+            .latest_json_record()
+            .and_then(|record| parse_kick_targets(record.value));
+    }
+}
+
+fn parse_kick_targets(blackboard: Value) -> Option<KickTargets> {
+    serde_json::from_value(blackboard).ok()
+}
+
+fn create_observation(
+    context: &impl ObservationContext,
+) -> Result<(DynamicTopicObservation, ObservationRepaint), Report> {
+    let runtime_handle = context.backend().runtime_handle().clone();
+    // ros_z_debug spawns observation tasks internally and needs a current runtime.
+    let _runtime_context = runtime_handle.enter();
+    let observation = context
+        .backend()
+        .observer()
+        .observe_dynamic(BLACKBOARD_TOPIC)
+        .wrap_err("failed to create blackboard observation")?
+        .spawn();
+    let repaint = observation.repaint_on_updates(context);
+    Ok((observation, repaint))
+}
+
+/// Top view of the field with the opponent goal on the right.
+struct FieldPainter {
+    painter: Painter,
+    center: Pos2,
+    scale: f32,
+}
+
+impl FieldPainter {
+    fn new(painter: Painter, rect: Rect, field_dimensions: &FieldDimensions) -> Self {
+        let total_length = field_dimensions.length + 2.0 * field_dimensions.border_strip_width;
+        let total_width = field_dimensions.width + 2.0 * field_dimensions.border_strip_width;
+        Self {
+            painter,
+            center: rect.center(),
+            scale: (rect.width() / total_length).min(rect.height() / total_width),
+        }
+    }
+
+    fn position(&self, point: Point2<Field>) -> Pos2 {
+        self.center + vec2(point.x(), -point.y()) * self.scale
+    }
+
+    fn paint(&self, kick_targets: &KickTargets) {
+        let field_dimensions = &kick_targets.field_dimensions;
+        let line = Stroke::new(field_dimensions.line_width * self.scale, Color32::WHITE);
+        let half_length = field_dimensions.length / 2.0;
+        let half_width = field_dimensions.width / 2.0;
+        let field = Rect::from_two_pos(
+            self.position(point![-half_length, half_width]),
+            self.position(point![half_length, -half_width]),
+        );
+        self.painter
+            .rect_filled(field.expand(2.0 * self.scale), 0.0, Color32::DARK_GREEN);
+        self.painter
+            .rect_stroke(field, 0.0, line, StrokeKind::Middle);
+        self.painter.line_segment(
+            [
+                self.position(point![0.0, half_width]),
+                self.position(point![0.0, -half_width]),
+            ],
+            line,
+        );
+        for goal_line in [-half_length, half_length] {
+            self.painter.line_segment(
+                [
+                    self.position(point![goal_line, field_dimensions.goal_inner_width / 2.0]),
+                    self.position(point![goal_line, -field_dimensions.goal_inner_width / 2.0]),
+                ],
+                Stroke::new(3.0 * line.width, Color32::YELLOW),
+            );
+        }
+
+        let Some(ball) = &kick_targets.ball else {
+            return;
+        };
+        // candidates are sorted by score, the chosen one is drawn last and on top
+        for (index, candidate) in kick_targets.kick_target_candidates.iter().enumerate().rev() {
+            let score = candidate.score.clamp(0.0, 1.0);
+            let color = Color32::from_rgb((255.0 * (1.0 - score)) as u8, (255.0 * score) as u8, 0);
+            let (width, outline) = if index == 0 {
+                (0.03, Color32::WHITE)
+            } else {
+                (0.01, Color32::BLACK)
+            };
+            self.painter.line_segment(
+                [
+                    self.position(ball.position),
+                    self.position(candidate.target),
+                ],
+                Stroke::new(width * self.scale, color),
+            );
+            self.painter.circle(
+                self.position(candidate.target),
+                (0.05 + 0.15 * score) * self.scale,
+                color,
+                Stroke::new(width * self.scale, outline),
+            );
+        }
+        self.painter.circle_filled(
+            self.position(ball.position),
+            field_dimensions.ball_radius * self.scale,
+            Color32::WHITE,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use coordinate_systems::Field;
+    use linear_algebra::{Point2, point};
+    use serde_json::{json, to_value};
+    use types::{
+        field_dimensions::FieldDimensions,
+        kick_target::{KickTargetCandidate, KickTargetKind},
+        motion_command::KickPower,
+    };
+
+    use super::parse_kick_targets;
+
+    #[test]
+    fn kick_targets_are_read_from_the_blackboard() {
+        let candidate = KickTargetCandidate {
+            kind: KickTargetKind::Goal,
+            target: point![4.5, 0.3],
+            kick_power: KickPower::Schlong,
+            opening_angle: 0.2,
+            accuracy: 0.8,
+            interception_risk: 0.1,
+            score: 0.72,
+        };
+        let ball: Point2<Field> = point![1.0, 0.0];
+        let blackboard = json!({
+            "field_dimensions": to_value(FieldDimensions::SPL_2025).unwrap(),
+            "kick_target_candidates": [to_value(candidate).unwrap()],
+            "ball": { "position": to_value(ball).unwrap(), "age": 0 },
+            "role": "Striker",
+        });
+
+        let kick_targets = parse_kick_targets(blackboard).unwrap();
+
+        assert_eq!(kick_targets.kick_target_candidates.len(), 1);
+        assert_eq!(
+            kick_targets.kick_target_candidates[0].kind,
+            KickTargetKind::Goal
+        );
+        assert_eq!(kick_targets.ball.unwrap().position, ball);
+    }
+}
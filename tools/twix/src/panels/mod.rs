@@ -1,5 +1,9 @@
+mod camera_calibration;
 mod image;
+mod kick_targets;
 mod text;
 
+pub use camera_calibration::CameraCalibrationPanel;
 pub use image::ImagePanel;
+pub use kick_targets::KickTargetsPanel;
 pub use text::TextPanel;